@@ -5,6 +5,7 @@ import 'package:stelliberty/clash/model/connection_model.dart';
 import 'package:stelliberty/clash/model/rule_model.dart';
 import 'package:stelliberty/clash/client/clash_core_client.dart';
 import 'package:stelliberty/clash/client/ipc_request_helper.dart';
+import 'package:stelliberty/src/bindings/signals/signals.dart';
 import 'package:stelliberty/storage/clash_preferences.dart';
 
 // 桌面端核心客户端实现
@@ -362,11 +363,27 @@ class IpcCoreClient implements ClashCoreClient {
     }
   }
 
+  // 等待 Rust 层返回的出站模式结果（校验与请求均在 Rust 层完成）
+  Future<String> _awaitModeResult(void Function() send) async {
+    final resultFuture = ClashModeResult.rustSignalStream.first.timeout(
+      const Duration(seconds: 15),
+    );
+    send();
+
+    final result = (await resultFuture).message;
+    final mode = result.mode;
+    if (mode == null) {
+      throw Exception(result.errorMessage ?? '出站模式请求失败');
+    }
+    return mode;
+  }
+
   @override
   Future<String> getMode() async {
     try {
-      final config = await getConfig();
-      return config['mode'] ?? 'rule';
+      return await _awaitModeResult(
+        () => const GetClashMode().sendSignalToRust(),
+      );
     } catch (e) {
       Logger.error('获取出站模式出错：$e');
       return 'rule';
@@ -376,13 +393,11 @@ class IpcCoreClient implements ClashCoreClient {
   @override
   Future<bool> setMode(String mode) async {
     try {
-      const validModes = ['rule', 'global', 'direct'];
-      if (!validModes.contains(mode)) {
-        throw ArgumentError('无效的出站模式：$mode');
-      }
-
-      await _patch('/configs', {'mode': mode});
-      Logger.info('出站模式已设置：$mode');
+      final confirmed = await _awaitModeResult(
+        () => SetClashMode(mode: mode).sendSignalToRust(),
+      );
+      _clearConfigCache();
+      Logger.info('出站模式已设置：$confirmed');
       return true;
     } catch (e) {
       Logger.error('设置出站模式出错：$e');
@@ -231,6 +231,12 @@ class IpcRequestHelper {
         // 等待响应（30 秒超时 - 长操作，用于配置更新）
         final response = await completer.future.timeout(_IpcTimeouts.long);
 
+        // 同一路径的后续请求会覆盖本次更新，视为完成
+        if (response.isSuperseded) {
+          Logger.debug('IPCPUT 请求已被后续请求取代：$path');
+          return {};
+        }
+
         if (!response.isSuccessful) {
           throw Exception(response.errorMessage ?? 'IPC 请求失败');
         }
@@ -445,6 +445,9 @@ class LifecycleManager {
         configPath: runtimeConfigPath,
         dataDir: clashDataDir,
         externalController: externalController,
+        mixedPort: null,
+        socksPort: null,
+        extraArgs: const [],
       ).sendSignalToRust();
 
       // 等待服务响应
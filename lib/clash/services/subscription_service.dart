@@ -68,7 +68,12 @@ class SubscriptionService {
   }
 
   // 解析订阅内容（通过 Rust）
-  Future<String> _parseSubscriptionContent(String content) async {
+  // 传入订阅 ID 时，Rust 层会记录该订阅的元数据（更新时间、节点数等）
+  Future<String> _parseSubscriptionContent(
+    String content, {
+    String? subscriptionId,
+    SubscriptionInfoData? subscriptionInfo,
+  }) async {
     final requestId = _buildParseRequestId();
     final completer = Completer<String>();
     StreamSubscription? subscription;
@@ -91,6 +96,12 @@ class SubscriptionService {
       final parseRequest = ParseSubscriptionRequest(
         requestId: requestId,
         content: content,
+        autoExcludePattern: null,
+        namePrefix: null,
+        templateName: null,
+        groupByRegion: false,
+        subscriptionId: subscriptionId,
+        subscriptionInfo: subscriptionInfo,
       );
       parseRequest.sendSignalToRust();
 
@@ -168,6 +179,8 @@ class SubscriptionService {
       // 获取配置内容并解析
       final parsedConfigContent = await _parseSubscriptionContent(
         downloadResult.content,
+        subscriptionId: subscription.id,
+        subscriptionInfo: downloadResult.subscriptionInfo,
       );
 
       // 验证配置文件
@@ -94,6 +94,12 @@ class OverrideTest {
     final request = ParseSubscriptionRequest(
       requestId: 'test-parse-${DateTime.now().millisecondsSinceEpoch}',
       content: content,
+      autoExcludePattern: null,
+      namePrefix: null,
+      templateName: null,
+      groupByRegion: false,
+      subscriptionId: null,
+      subscriptionInfo: null,
     );
     request.sendSignalToRust();
 
@@ -47,6 +47,8 @@ class SystemProxy {
     bool usePacMode = false,
     String pacScript = '',
     String? pacFilePath,
+    List<String> targetDevices = const [],
+    bool excludeSimpleHostnames = false,
   }) async {
     // 如果没有提供 PAC 文件路径，使用 PathService 的默认路径
     final finalPacFilePath = pacFilePath ?? PathService.instance.pacFilePath;
@@ -60,6 +62,8 @@ class SystemProxy {
           shouldUsePacMode: usePacMode,
           pacScript: pacScript,
           pacFilePath: finalPacFilePath,
+          targetDevices: targetDevices,
+          excludeSimpleHostnames: excludeSimpleHostnames,
         );
         signal.sendSignalToRust();
       },
@@ -69,10 +73,10 @@ class SystemProxy {
   }
 
   // 禁用系统代理（平台逻辑由 Rust 端处理）。
-  static Future<bool> disable() async {
+  static Future<bool> disable({List<String> targetDevices = const []}) async {
     return _executeRustSignal(
       sendSignal: () {
-        final signal = DisableSystemProxy();
+        final signal = DisableSystemProxy(targetDevices: targetDevices);
         signal.sendSignalToRust();
       },
       operationName: '禁用系统代理',
@@ -93,6 +97,8 @@ class SystemProxy {
           completer.complete({
             'enabled': result.message.isEnabled,
             'server': result.message.server,
+            'bypassDomains': result.message.bypassDomains,
+            'excludeSimpleHostnames': result.message.excludeSimpleHostnames,
           });
         }
       });
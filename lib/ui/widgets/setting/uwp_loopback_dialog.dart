@@ -452,7 +452,10 @@ class _UwpLoopbackDialogState extends State<UwpLoopbackDialog> {
       final enabledSids = state.getEnabledSids();
 
       // 发送保存请求到 Rust
-      SaveLoopbackConfiguration(sidStrings: enabledSids).sendSignalToRust();
+      SaveLoopbackConfiguration(
+        sidStrings: enabledSids,
+        preview: false,
+      ).sendSignalToRust();
 
       // 监听保存结果
       final result = await SaveLoopbackConfigurationResult
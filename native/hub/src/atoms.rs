@@ -1,5 +1,8 @@
 // L4 原子层模块入口
 
+pub mod config_validator;
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+pub mod elevate;
 pub mod ipc_client;
 pub mod logger;
 pub mod network_interfaces;
@@ -9,9 +12,10 @@ pub mod proxy_parser;
 pub mod shared_types;
 pub mod system_proxy;
 
+pub use config_validator::ConfigValidator;
 pub use ipc_client::{IpcClient, IpcHttpResponse};
 pub use logger::init;
 pub use override_processor::OverrideProcessor;
 pub use path_resolver as path_service;
-pub use proxy_parser::ProxyParser;
+pub use proxy_parser::{ParseOptions, ParsedSubscription, ProxyParser};
 pub use shared_types::{OverrideConfig, OverrideFormat};
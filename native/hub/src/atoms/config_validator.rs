@@ -0,0 +1,26 @@
+// 配置校验原子模块：在配置下发到核心前检查常见的结构性问题。
+// 校验只产出问题列表，由调用方决定记录日志还是拒绝配置。
+
+pub mod capabilities;
+mod dns;
+mod listeners;
+mod proxies;
+mod proxy_groups;
+mod rules;
+mod tun;
+mod validator;
+
+pub use capabilities::{
+    PROXY_GROUP_TYPES, PROXY_TYPES, RULE_TYPES, SS_CIPHERS, UDP_CAPABLE_TYPES, UDP_ORIENTED_TYPES,
+};
+pub use dns::validate_dns;
+pub use listeners::validate_listeners;
+pub use proxies::validate_proxies;
+pub use proxy_groups::validate_proxy_groups;
+pub use rules::validate_rules;
+pub use tun::validate_tun;
+pub use validator::{
+    CATEGORY_DNS, CATEGORY_LISTENERS, CATEGORY_PROXIES, CATEGORY_PROXY_GROUPS, CATEGORY_RULES,
+    CATEGORY_TUN, ConfigFileError, ConfigValidator, IssueSeverity, ValidationIssue,
+    ValidationReport,
+};
@@ -0,0 +1,106 @@
+// 核心支持的节点类型、代理组类型、规则类型与加密方式。
+// 校验器据此检查配置，界面也通过 GetSupportedCapabilities 读取同一份列表。
+
+// 节点类型
+pub const PROXY_TYPES: &[&str] = &[
+    "direct",
+    "dns",
+    "http",
+    "socks5",
+    "ss",
+    "ssr",
+    "snell",
+    "vmess",
+    "vless",
+    "trojan",
+    "hysteria",
+    "hysteria2",
+    "tuic",
+    "wireguard",
+    "ssh",
+    "mieru",
+    "anytls",
+];
+
+// 支持 UDP 转发、需要显式开启 udp 的节点类型（解析器据此补全 udp: true）
+pub const UDP_CAPABLE_TYPES: &[&str] = &[
+    "ss",
+    "ssr",
+    "vmess",
+    "vless",
+    "trojan",
+    "socks5",
+    "hysteria",
+    "hysteria2",
+    "tuic",
+    "wireguard",
+];
+
+// 通常用于承载 UDP 流量（QUIC、游戏等）的节点类型，未开启 udp 时校验器给出警告。
+// 必须是 UDP_CAPABLE_TYPES 的子集，否则解析器生成的节点会触发该警告
+pub const UDP_ORIENTED_TYPES: &[&str] = &["hysteria", "hysteria2", "tuic", "wireguard"];
+
+// 代理组类型
+pub const PROXY_GROUP_TYPES: &[&str] = &["select", "url-test", "fallback", "load-balance", "relay"];
+
+// 规则类型
+pub const RULE_TYPES: &[&str] = &[
+    "DOMAIN",
+    "DOMAIN-SUFFIX",
+    "DOMAIN-KEYWORD",
+    "DOMAIN-REGEX",
+    "DOMAIN-WILDCARD",
+    "GEOSITE",
+    "GEOIP",
+    "SRC-GEOIP",
+    "IP-ASN",
+    "SRC-IP-ASN",
+    "IP-CIDR",
+    "IP-CIDR6",
+    "SRC-IP-CIDR",
+    "IP-SUFFIX",
+    "SRC-IP-SUFFIX",
+    "DST-PORT",
+    "SRC-PORT",
+    "IN-PORT",
+    "IN-TYPE",
+    "IN-USER",
+    "IN-NAME",
+    "PROCESS-NAME",
+    "PROCESS-PATH",
+    "PROCESS-NAME-REGEX",
+    "PROCESS-PATH-REGEX",
+    "PROCESS-NAME-WILDCARD",
+    "PROCESS-PATH-WILDCARD",
+    "UID",
+    "NETWORK",
+    "DSCP",
+    "RULE-SET",
+    "AND",
+    "OR",
+    "NOT",
+    "SUB-RULE",
+    "MATCH",
+];
+
+// Shadowsocks 加密方式
+pub const SS_CIPHERS: &[&str] = &[
+    "none",
+    "aes-128-gcm",
+    "aes-192-gcm",
+    "aes-256-gcm",
+    "aes-128-cfb",
+    "aes-192-cfb",
+    "aes-256-cfb",
+    "aes-128-ctr",
+    "aes-192-ctr",
+    "aes-256-ctr",
+    "rc4-md5",
+    "chacha20-ietf",
+    "xchacha20",
+    "chacha20-ietf-poly1305",
+    "xchacha20-ietf-poly1305",
+    "2022-blake3-aes-128-gcm",
+    "2022-blake3-aes-256-gcm",
+    "2022-blake3-chacha20-poly1305",
+];
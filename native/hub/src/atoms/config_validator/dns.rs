@@ -0,0 +1,182 @@
+// DNS 校验：检查 fake-ip-filter 与 nameserver-policy 中的域名匹配模式，
+// 核心遇到无法解析的模式会拒绝整个 DNS 配置。
+
+use serde_yaml_ng::Value as YamlValue;
+
+use super::validator::{CATEGORY_DNS, IssueSeverity, ValidationIssue};
+
+// 引用外部集合的前缀（内容由核心解析，不在此检查）
+const SET_PREFIXES: &[&str] = &["geosite:", "rule-set:"];
+
+// 校验 dns 段
+pub fn validate_dns(config: &YamlValue) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let Some(dns) = config.get("dns") else {
+        return issues;
+    };
+
+    if let Some(filters) = dns.get("fake-ip-filter").and_then(|v| v.as_sequence()) {
+        for (index, filter) in filters.iter().enumerate() {
+            let location = format!("dns.fake-ip-filter[#{}]", index);
+            match filter.as_str() {
+                Some(pattern) => check_entry(pattern, &location, &mut issues),
+                None => issues.push(ValidationIssue::error(
+                    CATEGORY_DNS,
+                    location,
+                    format!("fake-ip-filter 条目应为字符串：{:?}", filter),
+                )),
+            }
+        }
+    }
+
+    if let Some(policies) = dns.get("nameserver-policy").and_then(|v| v.as_mapping()) {
+        for key in policies.keys() {
+            match key.as_str() {
+                Some(key) => {
+                    let location = format!("dns.nameserver-policy[{}]", key);
+                    // 键可用逗号列出多个域名
+                    if SET_PREFIXES.iter().any(|prefix| key.starts_with(prefix)) {
+                        check_entry(key, &location, &mut issues);
+                    } else {
+                        for pattern in key.split(',') {
+                            check_entry(pattern, &location, &mut issues);
+                        }
+                    }
+                }
+                None => issues.push(ValidationIssue::error(
+                    CATEGORY_DNS,
+                    "dns.nameserver-policy",
+                    format!("nameserver-policy 的键应为字符串：{:?}", key),
+                )),
+            }
+        }
+    }
+
+    issues
+}
+
+// 检查单个条目并记录问题
+fn check_entry(entry: &str, location: &str, issues: &mut Vec<ValidationIssue>) {
+    if let Err((severity, reason)) = check_domain_pattern(entry) {
+        let message = format!("域名模式 \"{}\" {}", entry, reason);
+        issues.push(match severity {
+            IssueSeverity::Error => ValidationIssue::error(CATEGORY_DNS, location, message),
+            IssueSeverity::Warning => ValidationIssue::warning(CATEGORY_DNS, location, message),
+        });
+    }
+}
+
+// 检查域名匹配模式：域名、*.example.com、+.example.com、.example.com
+// 或 geosite:/rule-set: 引用；核心无法解析的返回错误，可疑写法返回警告
+fn check_domain_pattern(pattern: &str) -> Result<(), (IssueSeverity, &'static str)> {
+    if pattern.trim().is_empty() {
+        return Err((IssueSeverity::Error, "为空"));
+    }
+    if pattern.chars().any(char::is_whitespace) {
+        return Err((IssueSeverity::Error, "包含空白字符"));
+    }
+
+    if let Some(prefix) = SET_PREFIXES.iter().find(|p| pattern.starts_with(**p)) {
+        return if pattern.len() > prefix.len() {
+            Ok(())
+        } else {
+            Err((IssueSeverity::Error, "缺少集合名称"))
+        };
+    }
+
+    let domain = ["+.", "*.", "."]
+        .iter()
+        .find_map(|prefix| pattern.strip_prefix(prefix))
+        .unwrap_or(pattern);
+    if domain.is_empty() {
+        return Err((IssueSeverity::Error, "缺少域名"));
+    }
+
+    let mut result = Ok(());
+    for label in domain.split('.') {
+        if label == "*" {
+            continue;
+        }
+        if label.chars().any(|c| {
+            c.is_ascii() && !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '*' | '+'))
+        }) {
+            return Err((IssueSeverity::Error, "包含非法字符"));
+        }
+        if label.is_empty() {
+            result = Err((IssueSeverity::Warning, "包含连续的点或以点结尾"));
+        } else if label.contains(['*', '+']) {
+            result = Err((IssueSeverity::Warning, "中的通配符只能作为完整的一段"));
+        } else if label.len() > 63 {
+            result = Err((IssueSeverity::Warning, "中有超过 63 个字符的段"));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> YamlValue {
+        serde_yaml_ng::from_str(yaml).unwrap_or(YamlValue::Null)
+    }
+
+    #[test]
+    fn test_valid_filter_list() {
+        let config = parse(
+            r#"
+dns:
+  enhanced-mode: fake-ip
+  fake-ip-filter:
+    - "*.lan"
+    - "+.local"
+    - localhost.ptlogin2.qq.com
+    - .example.org
+    - "time.*.com"
+    - geosite:private
+    - rule-set:fakeip-bypass
+    - 测试.中国
+  nameserver-policy:
+    "geosite:cn,private": 223.5.5.5
+    "www.baidu.com,+.google.cn": 114.114.114.114
+    "+.arpa": 10.0.0.1
+"#,
+        );
+        let issues = validate_dns(&config);
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_filter_entry_with_spaces() {
+        let config = parse(
+            r#"
+dns:
+  fake-ip-filter:
+    - "+.example.com"
+    - "bad entry.com"
+    - "ex*ample.com"
+  nameserver-policy:
+    "a..example.com,+.ok.com": 1.1.1.1
+    "geosite:": 8.8.8.8
+"#,
+        );
+        let issues = validate_dns(&config);
+        let summary: Vec<(IssueSeverity, &str)> = issues
+            .iter()
+            .map(|i| (i.severity, i.location.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (IssueSeverity::Error, "dns.fake-ip-filter[#1]"),
+                (IssueSeverity::Warning, "dns.fake-ip-filter[#2]"),
+                (
+                    IssueSeverity::Warning,
+                    "dns.nameserver-policy[a..example.com,+.ok.com]"
+                ),
+                (IssueSeverity::Error, "dns.nameserver-policy[geosite:]"),
+            ]
+        );
+        assert!(issues[0].message.contains("空白字符"));
+    }
+}
@@ -0,0 +1,311 @@
+// 入站校验：检查 listeners 列表，并与顶层端口一起检测端口冲突。
+
+use serde_yaml_ng::Value as YamlValue;
+use std::net::IpAddr;
+
+use super::validator::{CATEGORY_LISTENERS, ValidationIssue};
+
+// 支持的 listener 类型（与 mihomo 入站类型一致）
+const LISTENER_TYPES: &[&str] = &[
+    "mixed",
+    "http",
+    "socks",
+    "redir",
+    "tproxy",
+    "tun",
+    "tunnel",
+    "shadowsocks",
+    "vmess",
+    "vless",
+    "trojan",
+    "tuic",
+    "hysteria2",
+    "anytls",
+];
+
+// 不监听端口的 listener 类型
+const PORTLESS_LISTENER_TYPES: &[&str] = &["tun"];
+
+// 顶层入站端口字段
+const TOP_LEVEL_PORT_KEYS: &[&str] = &[
+    "port",
+    "socks-port",
+    "mixed-port",
+    "redir-port",
+    "tproxy-port",
+];
+
+// 已占用的入站端口
+struct InboundPort {
+    // 来源描述，例如 mixed-port、listeners[in-http]
+    source: String,
+    port: u16,
+    // 监听地址，None 表示所有地址
+    listen: Option<IpAddr>,
+}
+
+// 校验 listeners 列表与入站端口冲突
+pub fn validate_listeners(config: &YamlValue) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut inbound_ports = top_level_ports(config);
+
+    if let Some(listeners) = config.get("listeners").and_then(|v| v.as_sequence()) {
+        let mut names: Vec<&str> = Vec::new();
+        for (index, listener) in listeners.iter().enumerate() {
+            let location = listener_location(listener, index);
+            check_name(listener, &location, &mut names, &mut issues);
+            if let Some(port) = check_listener(listener, &location, &mut issues) {
+                inbound_ports.push(port);
+            }
+        }
+    }
+
+    check_port_conflicts(&inbound_ports, &mut issues);
+    issues
+}
+
+// 生成 listener 位置描述（优先使用名称）
+fn listener_location(listener: &YamlValue, index: usize) -> String {
+    match listener.get("name").and_then(|v| v.as_str()) {
+        Some(name) if !name.is_empty() => format!("listeners[{}]", name),
+        _ => format!("listeners[#{}]", index),
+    }
+}
+
+// 收集顶层端口（0 表示未启用），监听地址取 bind-address
+fn top_level_ports(config: &YamlValue) -> Vec<InboundPort> {
+    let listen = config
+        .get("bind-address")
+        .and_then(|v| v.as_str())
+        .and_then(parse_listen);
+
+    TOP_LEVEL_PORT_KEYS
+        .iter()
+        .filter_map(|key| {
+            let port = config.get(*key)?.as_u64()?;
+            let port = u16::try_from(port).ok().filter(|port| *port != 0)?;
+            Some(InboundPort {
+                source: key.to_string(),
+                port,
+                listen,
+            })
+        })
+        .collect()
+}
+
+// 解析监听地址，通配地址视为所有地址
+fn parse_listen(listen: &str) -> Option<IpAddr> {
+    listen
+        .trim_matches(['[', ']'])
+        .parse::<IpAddr>()
+        .ok()
+        .filter(|addr| !addr.is_unspecified())
+}
+
+// 检查名称：必须为非空字符串且不能重复
+fn check_name<'a>(
+    listener: &'a YamlValue,
+    location: &str,
+    names: &mut Vec<&'a str>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    match listener.get("name").and_then(|v| v.as_str()) {
+        Some(name) if !name.is_empty() => {
+            if names.contains(&name) {
+                issues.push(ValidationIssue::error(
+                    CATEGORY_LISTENERS,
+                    location,
+                    format!("listener 名称重复：{}", name),
+                ));
+            } else {
+                names.push(name);
+            }
+        }
+        _ => issues.push(ValidationIssue::error(
+            CATEGORY_LISTENERS,
+            location,
+            "listener 缺少 name",
+        )),
+    }
+}
+
+// 检查类型、端口与监听地址，返回该 listener 占用的端口
+fn check_listener(
+    listener: &YamlValue,
+    location: &str,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<InboundPort> {
+    let listener_type = listener.get("type").and_then(|v| v.as_str()).unwrap_or("");
+    if !LISTENER_TYPES.contains(&listener_type) {
+        issues.push(ValidationIssue::error(
+            CATEGORY_LISTENERS,
+            location,
+            format!(
+                "listener 类型无效：{}（可选 {}）",
+                if listener_type.is_empty() {
+                    "未设置"
+                } else {
+                    listener_type
+                },
+                LISTENER_TYPES.join("/")
+            ),
+        ));
+        return None;
+    }
+
+    let listen = match listener.get("listen") {
+        None => None,
+        Some(value) => match value.as_str() {
+            Some(raw) if raw.trim_matches(['[', ']']).parse::<IpAddr>().is_ok() => {
+                parse_listen(raw)
+            }
+            _ => {
+                issues.push(ValidationIssue::error(
+                    CATEGORY_LISTENERS,
+                    location,
+                    format!("listen 不是有效的 IP 地址：{:?}", value),
+                ));
+                return None;
+            }
+        },
+    };
+
+    if PORTLESS_LISTENER_TYPES.contains(&listener_type) {
+        return None;
+    }
+
+    let port = listener
+        .get("port")
+        .and_then(|v| v.as_u64())
+        .and_then(|port| u16::try_from(port).ok())
+        .filter(|port| *port != 0);
+    let Some(port) = port else {
+        issues.push(ValidationIssue::error(
+            CATEGORY_LISTENERS,
+            location,
+            format!(
+                "{} listener 端口无效：{:?}（应为 1-65535）",
+                listener_type,
+                listener.get("port").unwrap_or(&YamlValue::Null)
+            ),
+        ));
+        return None;
+    };
+
+    Some(InboundPort {
+        source: location.to_string(),
+        port,
+        listen,
+    })
+}
+
+// 检查端口冲突：同一端口的监听地址重叠时核心无法启动对应入站
+fn check_port_conflicts(ports: &[InboundPort], issues: &mut Vec<ValidationIssue>) {
+    for (index, current) in ports.iter().enumerate() {
+        let conflict = ports[..index].iter().find(|earlier| {
+            earlier.port == current.port
+                && match (earlier.listen, current.listen) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => true,
+                }
+        });
+        if let Some(earlier) = conflict {
+            issues.push(ValidationIssue::error(
+                CATEGORY_LISTENERS,
+                current.source.as_str(),
+                format!("端口 {} 与 {} 重复", current.port, earlier.source),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> YamlValue {
+        serde_yaml_ng::from_str(yaml).unwrap_or(YamlValue::Null)
+    }
+
+    #[test]
+    fn test_valid_listeners() {
+        let config = parse(
+            r#"
+mixed-port: 7890
+listeners:
+  - {name: in-socks, type: socks, port: 7891, listen: 0.0.0.0}
+  - {name: in-http, type: http, port: 7892, listen: "::1"}
+  - {name: lan-mixed, type: mixed, port: 7890, listen: 192.168.1.2}
+  - {name: in-tun, type: tun}
+"#,
+        );
+        let issues = validate_listeners(&config);
+        // lan-mixed 与顶层 mixed-port 同端口，但顶层监听所有地址
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].location, "listeners[lan-mixed]");
+
+        let config = parse(
+            r#"
+bind-address: 127.0.0.1
+mixed-port: 7890
+listeners:
+  - {name: lan-mixed, type: mixed, port: 7890, listen: 192.168.1.2}
+  - {name: in-tun, type: tun}
+"#,
+        );
+        assert!(validate_listeners(&config).is_empty());
+    }
+
+    #[test]
+    fn test_all_mihomo_listener_types_accepted() {
+        for listener_type in LISTENER_TYPES {
+            let config = parse(&format!(
+                "listeners:\n  - {{name: in, type: {}, port: 7893}}\n",
+                listener_type
+            ));
+            assert!(
+                validate_listeners(&config).is_empty(),
+                "{} 应为有效类型",
+                listener_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicate_listener_names() {
+        let config = parse(
+            r#"
+listeners:
+  - {name: in, type: socks, port: 7891}
+  - {name: in, type: http, port: 7892}
+  - {type: redirect, port: 7893, listen: localhost}
+"#,
+        );
+        let issues = validate_listeners(&config);
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].location, "listeners[in]");
+        assert!(messages[0].contains("名称重复"));
+        assert!(messages[1].contains("缺少 name"));
+        assert!(messages[2].contains("类型无效"));
+    }
+
+    #[test]
+    fn test_listener_ports_conflict_with_top_level() {
+        let config = parse(
+            r#"
+socks-port: 7891
+listeners:
+  - {name: in-socks, type: socks, port: 7891}
+  - {name: in-http, type: http, port: 70000}
+  - {name: in-mixed, type: mixed, port: 7893, listen: not-an-ip}
+"#,
+        );
+        let issues = validate_listeners(&config);
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(issues.len(), 3);
+        assert!(messages[0].contains("端口无效"));
+        assert!(messages[1].contains("listen"));
+        assert_eq!(messages[2], "端口 7891 与 socks-port 重复");
+    }
+}
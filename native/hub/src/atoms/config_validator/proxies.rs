@@ -0,0 +1,316 @@
+// 代理节点校验：检查节点类型与加密方式、附加配置块（如 smux）、传输层配置与 UDP 开关。
+
+use serde_yaml_ng::Value as YamlValue;
+
+use super::capabilities::{PROXY_TYPES, SS_CIPHERS, UDP_ORIENTED_TYPES};
+use super::validator::{CATEGORY_PROXIES, ValidationIssue};
+
+// smux 支持的多路复用协议
+const SMUX_PROTOCOLS: &[&str] = &["smux", "yamux", "h2mux"];
+
+// smux 中要求为非负整数的字段
+const SMUX_NUMERIC_FIELDS: &[&str] = &["max-connections", "min-streams", "max-streams"];
+
+// 传输层（network）与其配置块：核心只读取与 network 对应的配置块
+const TRANSPORT_OPTS: &[(&str, &str)] = &[
+    ("ws", "ws-opts"),
+    ("grpc", "grpc-opts"),
+    ("h2", "h2-opts"),
+    ("http", "http-opts"),
+];
+
+// 校验 proxies 列表
+pub fn validate_proxies(config: &YamlValue) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    let Some(proxies) = config.get("proxies").and_then(|v| v.as_sequence()) else {
+        return issues;
+    };
+
+    for (index, proxy) in proxies.iter().enumerate() {
+        let location = proxy_location(proxy, index);
+
+        check_type(proxy, &location, &mut issues);
+        if let Some(smux) = proxy.get("smux") {
+            check_smux(smux, &location, &mut issues);
+        }
+
+        check_transport(proxy, &location, &mut issues);
+        check_udp(proxy, &location, &mut issues);
+    }
+
+    issues
+}
+
+// 生成代理位置描述（序号 + 节点名）
+fn proxy_location(proxy: &YamlValue, index: usize) -> String {
+    match proxy.get("name").and_then(|v| v.as_str()) {
+        Some(name) => format!("proxies[#{}]（{}）", index, name),
+        None => format!("proxies[#{}]", index),
+    }
+}
+
+// 检查节点类型与 Shadowsocks 加密方式是否受核心支持（仅警告，核心版本可能更新）
+fn check_type(proxy: &YamlValue, location: &str, issues: &mut Vec<ValidationIssue>) {
+    let proxy_type = proxy.get("type").and_then(|v| v.as_str()).unwrap_or("");
+    if !PROXY_TYPES.contains(&proxy_type) {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_PROXIES,
+            location,
+            format!("未知的节点类型：{}", proxy_type),
+        ));
+        return;
+    }
+
+    if proxy_type == "ss"
+        && let Some(cipher) = proxy.get("cipher").and_then(|v| v.as_str())
+        && !SS_CIPHERS.contains(&cipher.to_ascii_lowercase().as_str())
+    {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_PROXIES,
+            location,
+            format!("未知的 Shadowsocks 加密方式：{}", cipher),
+        ));
+    }
+}
+
+// 检查 smux 配置块：协议必须受支持，数值字段必须为非负整数
+fn check_smux(smux: &YamlValue, location: &str, issues: &mut Vec<ValidationIssue>) {
+    if !smux.is_mapping() {
+        issues.push(ValidationIssue::error(
+            CATEGORY_PROXIES,
+            location,
+            "smux 配置必须为映射",
+        ));
+        return;
+    }
+
+    if let Some(protocol) = smux.get("protocol") {
+        match protocol.as_str() {
+            Some(protocol) if SMUX_PROTOCOLS.contains(&protocol) => {}
+            Some(protocol) => issues.push(ValidationIssue::error(
+                CATEGORY_PROXIES,
+                location,
+                format!(
+                    "smux 协议不受支持：{}（可选 {}）",
+                    protocol,
+                    SMUX_PROTOCOLS.join("/")
+                ),
+            )),
+            None => issues.push(ValidationIssue::error(
+                CATEGORY_PROXIES,
+                location,
+                format!("smux 协议不是字符串：{:?}", protocol),
+            )),
+        }
+    }
+
+    for field in SMUX_NUMERIC_FIELDS {
+        let Some(value) = smux.get(*field) else {
+            continue;
+        };
+        if value.as_u64().is_none() {
+            issues.push(ValidationIssue::error(
+                CATEGORY_PROXIES,
+                location,
+                format!("smux.{} 必须为非负整数：{:?}", field, value),
+            ));
+        }
+    }
+}
+
+// 检查 network 与传输层配置块是否对应：多余的配置块会被核心忽略，
+// 缺少配置块时使用默认路径/服务名，二者都可能导致连接失败（仅警告）
+fn check_transport(proxy: &YamlValue, location: &str, issues: &mut Vec<ValidationIssue>) {
+    let network = proxy
+        .get("network")
+        .and_then(|v| v.as_str())
+        .unwrap_or("tcp");
+
+    for (transport, opts_key) in TRANSPORT_OPTS {
+        let has_opts = proxy.get(*opts_key).is_some();
+        if network == *transport && !has_opts {
+            issues.push(ValidationIssue::warning(
+                CATEGORY_PROXIES,
+                location,
+                format!(
+                    "network 为 {}，但缺少 {}，将使用默认传输参数",
+                    network, opts_key
+                ),
+            ));
+        } else if network != *transport && has_opts {
+            issues.push(ValidationIssue::warning(
+                CATEGORY_PROXIES,
+                location,
+                format!(
+                    "{} 与 network（{}）不匹配，该配置块不会生效",
+                    opts_key, network
+                ),
+            ));
+        }
+    }
+}
+
+// 检查 UDP 开关：面向 UDP 的节点类型未开启 udp 时，UDP 规则会静默失败（仅警告）
+fn check_udp(proxy: &YamlValue, location: &str, issues: &mut Vec<ValidationIssue>) {
+    let proxy_type = proxy.get("type").and_then(|v| v.as_str()).unwrap_or("");
+    if !UDP_ORIENTED_TYPES.contains(&proxy_type) {
+        return;
+    }
+
+    let is_udp_enabled = proxy.get("udp").and_then(|v| v.as_bool()).unwrap_or(false);
+    if !is_udp_enabled {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_PROXIES,
+            location,
+            format!(
+                "{} 节点未开启 udp，QUIC 等 UDP 流量经该节点时将无法转发",
+                proxy_type
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atoms::config_validator::IssueSeverity;
+
+    fn parse(yaml: &str) -> YamlValue {
+        serde_yaml_ng::from_str(yaml).unwrap_or(YamlValue::Null)
+    }
+
+    #[test]
+    fn test_valid_smux() {
+        let config = parse(
+            r#"
+proxies:
+  - name: ss
+    type: ss
+    smux:
+      enabled: true
+      protocol: h2mux
+      max-connections: 4
+      min-streams: 4
+"#,
+        );
+        assert!(validate_proxies(&config).is_empty());
+    }
+
+    #[test]
+    fn test_smux_bad_protocol_and_negative_field() {
+        let config = parse(
+            r#"
+proxies:
+  - name: ok
+    type: ss
+  - name: bad
+    type: vmess
+    smux:
+      enabled: true
+      protocol: mplex
+      min-streams: -1
+"#,
+        );
+        let issues = validate_proxies(&config);
+        assert_eq!(issues.len(), 2);
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.category == CATEGORY_PROXIES)
+        );
+        assert_eq!(issues[0].location, "proxies[#1]（bad）");
+        assert!(issues[0].message.contains("mplex"));
+    }
+
+    #[test]
+    fn test_transport_opts_mismatch() {
+        let config = parse(
+            r#"
+proxies:
+  - name: grpc-ok
+    type: vless
+    server: a.example.com
+    port: 443
+    network: grpc
+    grpc-opts: {grpc-service-name: svc}
+  - name: grpc-ws
+    type: vless
+    server: b.example.com
+    port: 443
+    network: grpc
+    ws-opts: {path: /ws}
+  - name: tcp-h2
+    type: vmess
+    server: c.example.com
+    port: 443
+    h2-opts: {path: /}
+"#,
+        );
+        let issues = validate_proxies(&config);
+        let summary: Vec<(&str, bool)> = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.location.as_str(),
+                    issue.severity == IssueSeverity::Warning,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("proxies[#1]（grpc-ws）", true),
+                ("proxies[#1]（grpc-ws）", true),
+                ("proxies[#2]（tcp-h2）", true),
+            ]
+        );
+        assert!(issues[0].message.contains("ws-opts"));
+        assert!(issues[1].message.contains("grpc-opts"));
+        assert!(issues[2].message.contains("h2-opts"));
+    }
+
+    #[test]
+    fn test_unknown_type_and_cipher() {
+        let config = parse(
+            r#"
+proxies:
+  - {name: ok, type: ss, server: a.example.com, port: 8388, cipher: AES-128-GCM}
+  - {name: bad-cipher, type: ss, server: b.example.com, port: 8388, cipher: aes-512-gcm}
+  - {name: bad-type, type: shadowsocks, server: c.example.com, port: 8388}
+"#,
+        );
+        let issues = validate_proxies(&config);
+        let locations: Vec<&str> = issues.iter().map(|i| i.location.as_str()).collect();
+        assert_eq!(
+            locations,
+            vec!["proxies[#1]（bad-cipher）", "proxies[#2]（bad-type）"]
+        );
+        assert!(issues[0].message.contains("aes-512-gcm"));
+        assert!(issues[1].message.contains("shadowsocks"));
+    }
+
+    #[test]
+    fn test_udp_oriented_nodes_without_udp() {
+        let config = parse(
+            r#"
+proxies:
+  - {name: hy2-on, type: hysteria2, server: a.example.com, port: 443, udp: true}
+  - {name: hy2-off, type: hysteria2, server: b.example.com, port: 443}
+  - {name: tuic-off, type: tuic, server: c.example.com, port: 443, udp: false}
+  - {name: ss-off, type: ss, server: d.example.com, port: 8388}
+"#,
+        );
+        let issues = validate_proxies(&config);
+        let locations: Vec<&str> = issues.iter().map(|i| i.location.as_str()).collect();
+        assert_eq!(
+            locations,
+            vec!["proxies[#1]（hy2-off）", "proxies[#2]（tuic-off）"]
+        );
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.severity == IssueSeverity::Warning)
+        );
+    }
+}
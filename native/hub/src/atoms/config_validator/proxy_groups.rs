@@ -0,0 +1,448 @@
+// 代理组校验：检查代理组类型、健康检查类代理组的 url 与 interval、负载均衡策略，以及节点筛选正则。
+
+use serde_yaml_ng::Value as YamlValue;
+
+use super::capabilities::PROXY_GROUP_TYPES;
+use super::validator::{CATEGORY_PROXY_GROUPS, ValidationIssue};
+
+// 需要健康检查参数的代理组类型
+const HEALTH_CHECK_GROUP_TYPES: &[&str] = &["url-test", "fallback", "load-balance"];
+
+// load-balance 支持的负载均衡策略（未设置时核心使用 consistent-hashing）
+const LOAD_BALANCE_STRATEGIES: &[&str] = &["round-robin", "consistent-hashing", "sticky-sessions"];
+
+// interval 过小时会频繁发起测速请求
+const MIN_REASONABLE_INTERVAL_SECS: u64 = 10;
+
+// interval 过大时节点故障长时间无法被发现
+const MAX_REASONABLE_INTERVAL_SECS: u64 = 86400;
+
+// filter 作用的节点来源：include-all 系列与 use（proxies 列表不参与筛选）
+const FILTER_SOURCE_KEYS: &[&str] = &[
+    "include-all",
+    "include-all-proxies",
+    "include-all-providers",
+    "use",
+];
+
+// 核心使用 regexp2，支持 regex crate 不支持的环视与反向引用
+const UNSUPPORTED_REGEX_SYNTAX: &[&str] = &["(?=", "(?!", "(?<=", "(?<!"];
+
+// 校验 proxy-groups 列表
+pub fn validate_proxy_groups(config: &YamlValue) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    let Some(groups) = config.get("proxy-groups").and_then(|v| v.as_sequence()) else {
+        return issues;
+    };
+
+    for (index, group) in groups.iter().enumerate() {
+        let location = group_location(group, index);
+        let group_type = group.get("type").and_then(|v| v.as_str()).unwrap_or("");
+
+        if !PROXY_GROUP_TYPES.contains(&group_type) {
+            issues.push(ValidationIssue::warning(
+                CATEGORY_PROXY_GROUPS,
+                &location,
+                format!("未知的代理组类型：{}", group_type),
+            ));
+        }
+        if HEALTH_CHECK_GROUP_TYPES.contains(&group_type) {
+            check_health_check_url(group, &location, &mut issues);
+            check_health_check_interval(group, &location, &mut issues);
+        }
+        if group_type == "load-balance" {
+            check_load_balance_strategy(group, &location, &mut issues);
+        }
+
+        check_filters(group, &location, &mut issues);
+    }
+
+    issues
+}
+
+// 生成代理组位置描述（优先使用组名）
+fn group_location(group: &YamlValue, index: usize) -> String {
+    match group.get("name").and_then(|v| v.as_str()) {
+        Some(name) => format!("proxy-groups[{}]", name),
+        None => format!("proxy-groups[#{}]", index),
+    }
+}
+
+// 检查 filter/exclude-filter：正则必须可编译，filter 需要有可筛选的节点来源
+fn check_filters(group: &YamlValue, location: &str, issues: &mut Vec<ValidationIssue>) {
+    for key in ["filter", "exclude-filter"] {
+        let Some(pattern) = group.get(key).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        // 多个正则以反引号分隔
+        for part in pattern.split('`').filter(|part| !part.is_empty()) {
+            check_filter_regex(key, part, location, issues);
+        }
+    }
+
+    let has_filter = group
+        .get("filter")
+        .and_then(|v| v.as_str())
+        .is_some_and(|filter| !filter.is_empty());
+    let has_source = FILTER_SOURCE_KEYS.iter().any(|key| match group.get(*key) {
+        Some(YamlValue::Bool(enabled)) => *enabled,
+        Some(YamlValue::Sequence(providers)) => !providers.is_empty(),
+        _ => false,
+    });
+    if has_filter && !has_source {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            "设置了 filter 但未启用 include-all 或 use，filter 不会生效",
+        ));
+    }
+}
+
+fn check_filter_regex(key: &str, pattern: &str, location: &str, issues: &mut Vec<ValidationIssue>) {
+    let Err(e) = regex::Regex::new(pattern) else {
+        return;
+    };
+
+    let is_unsupported_syntax = UNSUPPORTED_REGEX_SYNTAX
+        .iter()
+        .any(|syntax| pattern.contains(syntax))
+        || matches!(e, regex::Error::Syntax(ref message) if message.contains("backreferences"));
+    if is_unsupported_syntax {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            format!("{} 使用了环视或反向引用，无法在本地校验：{}", key, pattern),
+        ));
+    } else {
+        // regex 的错误信息为多行（含定位标记），仅保留最后一行描述
+        let reason = e.to_string();
+        let reason = reason.lines().last().unwrap_or_default().trim();
+        issues.push(ValidationIssue::error(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            format!("{} 正则无效：{}（{}）", key, pattern, reason),
+        ));
+    }
+}
+
+// 检查健康检查地址：必须是非空的 HTTP(S) URL
+fn check_health_check_url(group: &YamlValue, location: &str, issues: &mut Vec<ValidationIssue>) {
+    let url = group
+        .get("url")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim();
+
+    if url.is_empty() {
+        issues.push(ValidationIssue::error(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            "缺少健康检查地址 url，代理组将无法测速",
+        ));
+        return;
+    }
+
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => {}
+        Ok(parsed) => issues.push(ValidationIssue::error(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            format!(
+                "健康检查地址必须为 HTTP(S) URL：{}（协议 {}）",
+                url,
+                parsed.scheme()
+            ),
+        )),
+        Err(e) => issues.push(ValidationIssue::error(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            format!("健康检查地址无法解析：{}（{}）", url, e),
+        )),
+    }
+}
+
+// 检查负载均衡策略：未设置时提示使用默认策略，无法识别的策略会导致核心加载失败
+fn check_load_balance_strategy(
+    group: &YamlValue,
+    location: &str,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let Some(value) = group.get("strategy") else {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            "未设置负载均衡策略 strategy，将使用默认的 consistent-hashing",
+        ));
+        return;
+    };
+
+    match value.as_str() {
+        Some(strategy) if LOAD_BALANCE_STRATEGIES.contains(&strategy) => {}
+        Some(strategy) => issues.push(ValidationIssue::error(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            format!(
+                "无法识别的负载均衡策略：{}（可选 {}）",
+                strategy,
+                LOAD_BALANCE_STRATEGIES.join("、")
+            ),
+        )),
+        None => issues.push(ValidationIssue::error(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            format!("负载均衡策略不是字符串：{:?}", value),
+        )),
+    }
+}
+
+// 检查健康检查间隔：必须为正整数，过小或过大时给出警告
+fn check_health_check_interval(
+    group: &YamlValue,
+    location: &str,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let Some(value) = group.get("interval") else {
+        issues.push(ValidationIssue::error(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            "缺少健康检查间隔 interval",
+        ));
+        return;
+    };
+
+    let interval = match value.as_i64() {
+        Some(interval) if interval > 0 => interval as u64,
+        Some(interval) => {
+            issues.push(ValidationIssue::error(
+                CATEGORY_PROXY_GROUPS,
+                location,
+                format!("健康检查间隔必须为正整数：{}", interval),
+            ));
+            return;
+        }
+        None => {
+            issues.push(ValidationIssue::error(
+                CATEGORY_PROXY_GROUPS,
+                location,
+                format!("健康检查间隔不是整数：{:?}", value),
+            ));
+            return;
+        }
+    };
+
+    if interval < MIN_REASONABLE_INTERVAL_SECS {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            format!("健康检查间隔过小（{}s），将频繁发起测速请求", interval),
+        ));
+    } else if interval > MAX_REASONABLE_INTERVAL_SECS {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_PROXY_GROUPS,
+            location,
+            format!(
+                "健康检查间隔过大（{}s），节点故障可能长时间无法被发现",
+                interval
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atoms::config_validator::IssueSeverity;
+
+    fn parse(yaml: &str) -> YamlValue {
+        serde_yaml_ng::from_str(yaml).unwrap_or(YamlValue::Null)
+    }
+
+    #[test]
+    fn test_url_test_group_missing_url() {
+        let config = parse(
+            r#"
+proxy-groups:
+  - name: AUTO
+    type: url-test
+    proxies: [a, b]
+    interval: 300
+"#,
+        );
+        let issues = validate_proxy_groups(&config);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+        assert_eq!(issues[0].location, "proxy-groups[AUTO]");
+    }
+
+    #[test]
+    fn test_zero_interval() {
+        let config = parse(
+            r#"
+proxy-groups:
+  - name: FB
+    type: fallback
+    proxies: [a, b]
+    url: https://www.gstatic.com/generate_204
+    interval: 0
+"#,
+        );
+        let issues = validate_proxy_groups(&config);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+    }
+
+    #[test]
+    fn test_unknown_group_type() {
+        let config = parse(
+            r#"
+proxy-groups:
+  - {name: CHAIN, type: relay, proxies: [a, b]}
+  - {name: SMART, type: smart, proxies: [a, b]}
+"#,
+        );
+        let issues = validate_proxy_groups(&config);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].location, "proxy-groups[SMART]");
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+    }
+
+    #[test]
+    fn test_select_group_does_not_require_health_check() {
+        let config = parse(
+            r#"
+proxy-groups:
+  - name: PROXY
+    type: select
+    proxies: [a, b]
+  - name: AUTO
+    type: url-test
+    proxies: [a, b]
+    url: http://www.gstatic.com/generate_204
+    interval: 5
+"#,
+        );
+        let issues = validate_proxy_groups(&config);
+        // 仅 AUTO 的过小间隔产生警告
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+        assert_eq!(issues[0].location, "proxy-groups[AUTO]");
+    }
+
+    #[test]
+    fn test_load_balance_strategy() {
+        let config = parse(
+            r#"
+proxy-groups:
+  - name: LB
+    type: load-balance
+    proxies: [a, b]
+    url: https://www.gstatic.com/generate_204
+    interval: 300
+    strategy: round-robin
+  - name: BAD
+    type: load-balance
+    proxies: [a, b]
+    strategy: least-connections
+  - name: DEFAULT
+    type: load-balance
+    proxies: [a, b]
+    url: https://www.gstatic.com/generate_204
+    interval: 300
+"#,
+        );
+        let issues = validate_proxy_groups(&config);
+        let summary: Vec<(&str, IssueSeverity)> = issues
+            .iter()
+            .map(|issue| (issue.location.as_str(), issue.severity))
+            .collect();
+        // BAD 缺少 url 与 interval，且策略无法识别；DEFAULT 仅提示使用默认策略
+        assert_eq!(
+            summary,
+            vec![
+                ("proxy-groups[BAD]", IssueSeverity::Error),
+                ("proxy-groups[BAD]", IssueSeverity::Error),
+                ("proxy-groups[BAD]", IssueSeverity::Error),
+                ("proxy-groups[DEFAULT]", IssueSeverity::Warning),
+            ]
+        );
+        assert!(issues[2].message.contains("least-connections"));
+    }
+
+    #[test]
+    fn test_valid_filter() {
+        let config = parse(
+            r#"
+proxy-groups:
+  - name: HK
+    type: select
+    include-all: true
+    proxies: null
+    filter: "(?i)港|hk|hong kong`🇭🇰"
+    exclude-filter: "0\\.[1-5]x"
+  - name: JP
+    type: select
+    use: [provider-a]
+    filter: "日本|JP"
+"#,
+        );
+        assert!(validate_proxy_groups(&config).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_filter_regex() {
+        let config = parse(
+            r#"
+proxy-groups:
+  - name: HK
+    type: select
+    include-all: true
+    filter: "(港|HK"
+    exclude-filter: "[过期"
+  - name: US
+    type: select
+    include-all: true
+    filter: "^(?!.*(过期|剩余)).*美国"
+"#,
+        );
+        let issues = validate_proxy_groups(&config);
+        assert_eq!(issues.len(), 3);
+
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+        assert_eq!(issues[0].location, "proxy-groups[HK]");
+        assert!(issues[0].message.starts_with("filter 正则无效：(港|HK"));
+        assert!(issues[0].message.contains("unclosed group"));
+        assert_eq!(issues[1].severity, IssueSeverity::Error);
+        assert!(issues[1].message.starts_with("exclude-filter 正则无效"));
+
+        // 环视为核心支持的语法，仅提示无法校验
+        assert_eq!(issues[2].severity, IssueSeverity::Warning);
+        assert_eq!(issues[2].location, "proxy-groups[US]");
+    }
+
+    #[test]
+    fn test_filter_without_source() {
+        let config = parse(
+            r#"
+proxy-groups:
+  - name: HK
+    type: select
+    proxies: [a, b]
+    filter: "HK"
+  - name: JP
+    type: select
+    include-all: false
+    use: []
+    filter: "JP"
+"#,
+        );
+        let issues = validate_proxy_groups(&config);
+        assert_eq!(issues.len(), 2);
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.severity == IssueSeverity::Warning)
+        );
+        assert_eq!(issues[1].location, "proxy-groups[JP]");
+    }
+}
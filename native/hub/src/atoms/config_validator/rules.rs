@@ -0,0 +1,274 @@
+// 规则校验：检查规则类型与 IP 类规则的 CIDR 参数，避免核心加载时报错；
+// 并提示重复规则与位置不当的 MATCH。
+
+use serde_yaml_ng::Value as YamlValue;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use super::capabilities::RULE_TYPES;
+use super::validator::{CATEGORY_RULES, ValidationIssue};
+
+// CIDR 参数的地址族要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CidrFamily {
+    V4,
+    V6,
+    Any,
+}
+
+// 校验 rules 列表
+pub fn validate_rules(config: &YamlValue) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    let Some(rules) = config.get("rules").and_then(|v| v.as_sequence()) else {
+        return issues;
+    };
+
+    // 规则（去除字段两侧空白后）首次出现的位置
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+    for (index, rule) in rules.iter().enumerate() {
+        let Some(rule) = rule.as_str() else {
+            continue;
+        };
+        let location = format!("rules[#{}]", index);
+        check_rule_type(rule, &location, &mut issues);
+        check_cidr_rule(rule, &location, &mut issues);
+
+        let normalized = rule.split(',').map(str::trim).collect::<Vec<_>>().join(",");
+        if let Some(first) = first_seen.get(&normalized) {
+            issues.push(ValidationIssue::warning(
+                CATEGORY_RULES,
+                &location,
+                format!("与 rules[#{}] 重复的规则：{}", first, rule),
+            ));
+        } else {
+            first_seen.insert(normalized, index);
+        }
+    }
+
+    check_match_placement(rules, &mut issues);
+    issues
+}
+
+// MATCH 匹配所有流量，其后的规则永远不会命中（仅警告）
+fn check_match_placement(rules: &[YamlValue], issues: &mut Vec<ValidationIssue>) {
+    let is_match = |rule: &YamlValue| {
+        rule.as_str().is_some_and(|rule| {
+            rule.split(',')
+                .next()
+                .is_some_and(|rule_type| rule_type.trim().eq_ignore_ascii_case("MATCH"))
+        })
+    };
+    let Some(index) = rules.iter().position(is_match) else {
+        return;
+    };
+
+    let unreachable = rules.len() - index - 1;
+    if unreachable > 0 {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_RULES,
+            format!("rules[#{}]", index),
+            format!(
+                "MATCH 不是最后一条规则，其后的 {} 条规则不会生效",
+                unreachable
+            ),
+        ));
+    }
+}
+
+// 检查规则类型是否受核心支持（仅警告）
+fn check_rule_type(rule: &str, location: &str, issues: &mut Vec<ValidationIssue>) {
+    let rule_type = rule
+        .split(',')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_uppercase();
+    if !RULE_TYPES.contains(&rule_type.as_str()) {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_RULES,
+            location,
+            format!("未知的规则类型：{}", rule),
+        ));
+    }
+}
+
+// 获取规则类型对应的 CIDR 地址族要求，非 IP 类规则返回 None
+fn cidr_family(rule_type: &str) -> Option<CidrFamily> {
+    match rule_type {
+        "IP-CIDR" => Some(CidrFamily::V4),
+        "IP-CIDR6" => Some(CidrFamily::V6),
+        "SRC-IP-CIDR" => Some(CidrFamily::Any),
+        _ => None,
+    }
+}
+
+// 检查 IP-CIDR/IP-CIDR6/SRC-IP-CIDR 规则，格式：类型,CIDR,目标[,no-resolve]
+fn check_cidr_rule(rule: &str, location: &str, issues: &mut Vec<ValidationIssue>) {
+    let fields: Vec<&str> = rule.split(',').map(str::trim).collect();
+    let rule_type = fields[0].to_ascii_uppercase();
+    let Some(family) = cidr_family(&rule_type) else {
+        return;
+    };
+
+    let (Some(cidr), Some(_target)) = (fields.get(1), fields.get(2)) else {
+        issues.push(ValidationIssue::error(
+            CATEGORY_RULES,
+            location,
+            format!("{} 规则缺少 CIDR 或目标：{}", rule_type, rule),
+        ));
+        return;
+    };
+
+    if let Err(reason) = parse_cidr(cidr, family) {
+        issues.push(ValidationIssue::error(
+            CATEGORY_RULES,
+            location,
+            format!("{} 规则的 CIDR 无效（{}）：{}", rule_type, reason, cidr),
+        ));
+    }
+
+    // 目标之后只允许 no-resolve 选项
+    for option in fields.iter().skip(3) {
+        if !option.eq_ignore_ascii_case("no-resolve") {
+            issues.push(ValidationIssue::warning(
+                CATEGORY_RULES,
+                location,
+                format!("{} 规则包含未知选项：{}", rule_type, option),
+            ));
+        }
+    }
+}
+
+// 解析 CIDR（地址/前缀长度），并检查地址族与前缀范围
+fn parse_cidr(cidr: &str, family: CidrFamily) -> Result<(), String> {
+    let Some((address, prefix)) = cidr.split_once('/') else {
+        return Err("缺少前缀长度".to_string());
+    };
+
+    let address: IpAddr = address
+        .parse()
+        .map_err(|_| format!("无法解析 IP 地址 {}", address))?;
+
+    match (family, address) {
+        (CidrFamily::V4, IpAddr::V6(_)) => return Err("IP-CIDR 需要 IPv4 地址".to_string()),
+        (CidrFamily::V6, IpAddr::V4(_)) => return Err("IP-CIDR6 需要 IPv6 地址".to_string()),
+        _ => {}
+    }
+
+    let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+    match prefix.parse::<u8>() {
+        Ok(prefix) if prefix <= max_prefix => Ok(()),
+        _ => Err(format!("前缀长度应为 0-{}，实际为 {}", max_prefix, prefix)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atoms::config_validator::IssueSeverity;
+
+    fn parse(yaml: &str) -> YamlValue {
+        serde_yaml_ng::from_str(yaml).unwrap_or(YamlValue::Null)
+    }
+
+    #[test]
+    fn test_valid_v4_cidr() {
+        let config = parse(
+            r#"
+rules:
+  - IP-CIDR,192.168.0.0/16,DIRECT,no-resolve
+  - IP-CIDR6,2001:db8::/32,DIRECT
+  - SRC-IP-CIDR,10.0.0.1/32,REJECT
+  - DOMAIN-SUFFIX,example.com,PROXY
+"#,
+        );
+        assert!(validate_rules(&config).is_empty());
+    }
+
+    #[test]
+    fn test_v4_value_in_cidr6_rule() {
+        let config = parse(
+            r#"
+rules:
+  - IP-CIDR6,10.0.0.0/8,DIRECT
+"#,
+        );
+        let issues = validate_rules(&config);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].location, "rules[#0]");
+        assert!(issues[0].message.contains("需要 IPv6"));
+    }
+
+    #[test]
+    fn test_garbage_cidr() {
+        let config = parse(
+            r#"
+rules:
+  - IP-CIDR,not-an-ip,DIRECT
+  - IP-CIDR,10.0.0.0/33,DIRECT
+"#,
+        );
+        let issues = validate_rules(&config);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("缺少前缀长度"));
+        assert!(issues[1].message.contains("0-32"));
+    }
+
+    #[test]
+    fn test_match_before_other_rules() {
+        let config = parse(
+            r#"
+rules:
+  - DOMAIN-SUFFIX,example.com,PROXY
+  - MATCH,PROXY
+  - DOMAIN,ads.example.com,REJECT
+  - GEOIP,CN,DIRECT
+"#,
+        );
+        let issues = validate_rules(&config);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].location, "rules[#1]");
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+        assert!(issues[0].message.contains("2 条规则"));
+
+        // 位于末尾的 MATCH 不提示
+        let config = parse("rules:\n  - DOMAIN-SUFFIX,example.com,PROXY\n  - MATCH,DIRECT\n");
+        assert!(validate_rules(&config).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_ip_cidr_rule() {
+        let config = parse(
+            r#"
+rules:
+  - IP-CIDR,10.0.0.0/8,DIRECT,no-resolve
+  - DOMAIN-SUFFIX,example.com,PROXY
+  - IP-CIDR, 10.0.0.0/8, DIRECT, no-resolve
+  - IP-CIDR,10.0.0.0/8,PROXY
+  - MATCH,PROXY
+"#,
+        );
+        let issues = validate_rules(&config);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].location, "rules[#2]");
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+        assert!(issues[0].message.contains("rules[#0]"));
+    }
+
+    #[test]
+    fn test_unknown_rule_type() {
+        let config = parse(
+            r#"
+rules:
+  - domain-keyword,google,PROXY
+  - AND,((NETWORK,UDP),(DST-PORT,443)),REJECT
+  - FINAL,PROXY
+"#,
+        );
+        let issues = validate_rules(&config);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].location, "rules[#2]");
+        assert!(issues[0].message.contains("FINAL"));
+    }
+}
@@ -0,0 +1,239 @@
+// TUN 校验：检查 tun 段的协议栈、DNS 劫持地址与开关类型，
+// 核心对这些字段解析失败时会直接拒绝启动 TUN。
+
+use serde_yaml_ng::Value as YamlValue;
+use std::net::IpAddr;
+
+use super::validator::{CATEGORY_TUN, ValidationIssue};
+
+// 核心支持的 TUN 协议栈（不区分大小写）
+const TUN_STACKS: &[&str] = &["system", "gvisor", "mixed"];
+
+// dns-hijack 条目允许的协议前缀
+const DNS_HIJACK_SCHEMES: &[&str] = &["tcp://", "udp://"];
+
+// 取值必须为布尔值的开关
+const BOOL_KEYS: &[&str] = &["enable", "auto-route", "auto-detect-interface"];
+
+// 校验 tun 段。is_service_mode 为核心是否经服务启动，None 表示未知（如单独校验导入的配置），
+// 此时不检查运行权限
+pub fn validate_tun(config: &YamlValue, is_service_mode: Option<bool>) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let Some(tun) = config.get("tun") else {
+        return issues;
+    };
+    if !tun.is_mapping() {
+        issues.push(ValidationIssue::error(
+            CATEGORY_TUN,
+            "tun",
+            "tun 应为映射结构",
+        ));
+        return issues;
+    }
+
+    for key in BOOL_KEYS {
+        if let Some(value) = tun.get(*key)
+            && !value.is_bool()
+        {
+            issues.push(ValidationIssue::error(
+                CATEGORY_TUN,
+                format!("tun.{}", key),
+                format!("{} 应为布尔值：{:?}", key, value),
+            ));
+        }
+    }
+
+    let is_enabled = tun.get("enable").and_then(|v| v.as_bool()) == Some(true);
+    check_stack(tun, is_enabled, &mut issues);
+
+    if let Some(hijacks) = tun.get("dns-hijack") {
+        match hijacks.as_sequence() {
+            Some(entries) => {
+                for (index, entry) in entries.iter().enumerate() {
+                    let location = format!("tun.dns-hijack[#{}]", index);
+                    match entry.as_str() {
+                        Some(address) if is_valid_dns_hijack(address) => {}
+                        Some(address) => issues.push(ValidationIssue::error(
+                            CATEGORY_TUN,
+                            location,
+                            format!(
+                                "DNS 劫持地址格式无效（应为 any:53 或 tcp://地址:端口）：{}",
+                                address
+                            ),
+                        )),
+                        None => issues.push(ValidationIssue::error(
+                            CATEGORY_TUN,
+                            location,
+                            format!("dns-hijack 条目应为字符串：{:?}", entry),
+                        )),
+                    }
+                }
+            }
+            None => issues.push(ValidationIssue::error(
+                CATEGORY_TUN,
+                "tun.dns-hijack",
+                "dns-hijack 应为列表",
+            )),
+        }
+    }
+
+    // 不经服务启动时核心以普通用户权限运行，缺少网络管理权限时 TUN 无法创建网卡
+    if is_enabled && is_service_mode == Some(false) {
+        issues.push(ValidationIssue::warning(
+            CATEGORY_TUN,
+            "tun.enable",
+            "配置启用了 TUN，但未使用服务模式，核心缺少管理员权限时 TUN 将无法启动",
+        ));
+    }
+
+    issues
+}
+
+// 检查协议栈：缺失时核心使用默认值，无法识别时拒绝
+fn check_stack(tun: &YamlValue, is_enabled: bool, issues: &mut Vec<ValidationIssue>) {
+    match tun.get("stack") {
+        None if is_enabled => issues.push(ValidationIssue::warning(
+            CATEGORY_TUN,
+            "tun.stack",
+            "未指定 TUN 协议栈，将使用核心默认值",
+        )),
+        None => {}
+        Some(stack) => match stack.as_str() {
+            Some(name) if TUN_STACKS.contains(&name.to_ascii_lowercase().as_str()) => {}
+            _ => issues.push(ValidationIssue::error(
+                CATEGORY_TUN,
+                "tun.stack",
+                format!(
+                    "无法识别的 TUN 协议栈（可选：{}）：{:?}",
+                    TUN_STACKS.join("/"),
+                    stack
+                ),
+            )),
+        },
+    }
+}
+
+// dns-hijack 条目：[tcp://|udp://]any|IP:端口，IPv6 需带方括号
+fn is_valid_dns_hijack(entry: &str) -> bool {
+    let address = DNS_HIJACK_SCHEMES
+        .iter()
+        .find_map(|scheme| entry.strip_prefix(scheme))
+        .unwrap_or(entry);
+    let Some((host, port)) = address.rsplit_once(':') else {
+        return false;
+    };
+    if !matches!(port.parse::<u16>(), Ok(port) if port > 0) {
+        return false;
+    }
+
+    if host == "any" {
+        return true;
+    }
+    match host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+        Some(v6) => v6.parse::<std::net::Ipv6Addr>().is_ok(),
+        None => host.parse::<IpAddr>().is_ok_and(|ip| ip.is_ipv4()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atoms::config_validator::IssueSeverity;
+
+    fn parse(yaml: &str) -> YamlValue {
+        serde_yaml_ng::from_str(yaml).unwrap_or(YamlValue::Null)
+    }
+
+    fn locations(issues: &[ValidationIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.location.as_str()).collect()
+    }
+
+    #[test]
+    fn test_valid_tun() {
+        let config = parse(
+            r#"
+external-controller-unix: /tmp/stelliberty.sock
+tun:
+  enable: true
+  stack: Mixed
+  auto-route: true
+  auto-detect-interface: false
+  dns-hijack:
+    - any:53
+    - tcp://any:53
+    - udp://198.18.0.2:53
+    - "[fd00::2]:53"
+"#,
+        );
+        let issues = validate_tun(&config, None);
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_unknown_stack() {
+        let config = parse(
+            r#"
+external-controller-unix: /tmp/stelliberty.sock
+tun:
+  enable: true
+  stack: lwip
+"#,
+        );
+        let issues = validate_tun(&config, None);
+        assert_eq!(locations(&issues), vec!["tun.stack"]);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+        assert!(issues[0].message.contains("lwip"));
+    }
+
+    #[test]
+    fn test_malformed_dns_hijack() {
+        let config = parse(
+            r#"
+external-controller-pipe: \\.\pipe\stelliberty
+tun:
+  enable: true
+  stack: gvisor
+  auto-route: "yes"
+  dns-hijack:
+    - any:53
+    - any
+    - http://any:53
+    - 8.8.8.8:0
+"#,
+        );
+        let issues = validate_tun(&config, None);
+        assert_eq!(
+            locations(&issues),
+            vec![
+                "tun.auto-route",
+                "tun.dns-hijack[#1]",
+                "tun.dns-hijack[#2]",
+                "tun.dns-hijack[#3]",
+            ]
+        );
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.severity == IssueSeverity::Error)
+        );
+    }
+
+    #[test]
+    fn test_enabled_without_service_mode() {
+        // 运行时配置总会注入 IPC 控制器字段，是否服务模式由调用方给出
+        let config = parse(
+            r#"
+external-controller-unix: /tmp/stelliberty.sock
+tun:
+  enable: true
+  stack: system
+"#,
+        );
+        let issues = validate_tun(&config, Some(false));
+        assert_eq!(locations(&issues), vec!["tun.enable"]);
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+
+        assert!(validate_tun(&config, Some(true)).is_empty());
+        assert!(validate_tun(&config, None).is_empty());
+    }
+}
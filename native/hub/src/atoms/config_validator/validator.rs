@@ -0,0 +1,273 @@
+// 配置校验入口：解析 YAML 并汇总各项检查结果。
+
+use serde_yaml_ng::Value as YamlValue;
+use std::fmt;
+use std::io::{BufReader, ErrorKind};
+use std::path::Path;
+
+use super::dns::validate_dns;
+use super::listeners::validate_listeners;
+use super::proxies::validate_proxies;
+use super::proxy_groups::validate_proxy_groups;
+use super::rules::validate_rules;
+use super::tun::validate_tun;
+
+// 问题分类
+pub const CATEGORY_PROXIES: &str = "代理配置";
+pub const CATEGORY_PROXY_GROUPS: &str = "代理组配置";
+pub const CATEGORY_RULES: &str = "规则配置";
+pub const CATEGORY_LISTENERS: &str = "入站配置";
+pub const CATEGORY_DNS: &str = "DNS 配置";
+pub const CATEGORY_TUN: &str = "TUN 配置";
+
+// 问题严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    // 可以加载，但行为可能不符合预期
+    Warning,
+    // 核心无法正确使用该配置项
+    Error,
+}
+
+// 单条校验问题
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    // 问题分类，例如 代理配置
+    pub category: &'static str,
+    // 问题所在位置，例如 proxy-groups[AUTO]
+    pub location: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub fn warning(
+        category: &'static str,
+        location: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            category,
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn error(
+        category: &'static str,
+        location: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            category,
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+}
+
+// 校验报告
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == IssueSeverity::Error)
+    }
+
+    // 将所有问题输出到日志
+    pub fn log_issues(&self) {
+        for issue in &self.issues {
+            match issue.severity {
+                IssueSeverity::Warning => {
+                    log::warn!(
+                        "配置校验警告 [{}] {}：{}",
+                        issue.category,
+                        issue.location,
+                        issue.message
+                    )
+                }
+                IssueSeverity::Error => {
+                    log::error!(
+                        "配置校验错误 [{}] {}：{}",
+                        issue.category,
+                        issue.location,
+                        issue.message
+                    )
+                }
+            }
+        }
+    }
+}
+
+// 读取配置文件失败的原因（与校验问题分开上报）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigFileError {
+    NotFound(String),
+    PermissionDenied(String),
+    // 其他 IO 错误
+    Read(String),
+    // 不是合法的 YAML
+    Parse(String),
+}
+
+impl fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(path) => write!(f, "配置文件不存在：{}", path),
+            Self::PermissionDenied(path) => write!(f, "没有权限读取配置文件：{}", path),
+            Self::Read(e) => write!(f, "读取配置文件失败：{}", e),
+            Self::Parse(e) => write!(f, "解析配置失败：{}", e),
+        }
+    }
+}
+
+pub struct ConfigValidator;
+
+impl ConfigValidator {
+    // 解析 YAML 配置文本，展开合并键后返回
+    pub fn parse_content(content: &str) -> Result<YamlValue, String> {
+        let mut config: YamlValue =
+            serde_yaml_ng::from_str(content).map_err(|e| format!("解析配置失败：{}", e))?;
+        config
+            .apply_merge()
+            .map_err(|e| format!("展开合并键失败：{}", e))?;
+        Ok(config)
+    }
+
+    // 校验 YAML 配置文本
+    pub fn validate_content(content: &str) -> Result<ValidationReport, String> {
+        let config = Self::parse_content(content)?;
+        Ok(Self::validate(&config))
+    }
+
+    // 校验配置文件（直接从文件流式解析，无需先读入完整文本）
+    pub fn validate_file(path: &Path) -> Result<ValidationReport, ConfigFileError> {
+        let file = std::fs::File::open(path).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => ConfigFileError::NotFound(path.display().to_string()),
+            ErrorKind::PermissionDenied => {
+                ConfigFileError::PermissionDenied(path.display().to_string())
+            }
+            _ => ConfigFileError::Read(format!("{}（{}）", path.display(), e)),
+        })?;
+
+        let mut config: YamlValue = serde_yaml_ng::from_reader(BufReader::new(file))
+            .map_err(|e| ConfigFileError::Parse(e.to_string()))?;
+        // 锚点在解析时已展开，合并键（<<）需要单独展开
+        config
+            .apply_merge()
+            .map_err(|e| ConfigFileError::Parse(e.to_string()))?;
+        Ok(Self::validate(&config))
+    }
+
+    // 校验即将下发给核心的运行时配置（已知核心是否经服务启动）
+    pub fn validate_runtime_content(
+        content: &str,
+        is_service_mode: bool,
+    ) -> Result<ValidationReport, String> {
+        let config = Self::parse_content(content)?;
+        Ok(Self::validate_with_mode(&config, Some(is_service_mode)))
+    }
+
+    // 校验已解析的配置
+    pub fn validate(config: &YamlValue) -> ValidationReport {
+        Self::validate_with_mode(config, None)
+    }
+
+    fn validate_with_mode(config: &YamlValue, is_service_mode: Option<bool>) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        report.issues.extend(validate_proxies(config));
+        report.issues.extend(validate_proxy_groups(config));
+        report.issues.extend(validate_rules(config));
+        report.issues.extend(validate_listeners(config));
+        report.issues.extend(validate_dns(config));
+        report.issues.extend(validate_tun(config, is_service_mode));
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_file_by_path() {
+        let dir =
+            std::env::temp_dir().join(format!("stelliberty-validate-file-{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+
+        let config_path = dir.join("config.yaml");
+        let written = std::fs::write(
+            &config_path,
+            "proxies:\n  - {name: hy2, type: hysteria2, server: a.example.com, port: 443}\nrules:\n  - IP-CIDR,10.0.0.0/33,DIRECT\n",
+        );
+        assert!(written.is_ok());
+
+        let report = ConfigValidator::validate_file(&config_path);
+        assert!(report.as_ref().is_ok_and(|r| r.has_errors()));
+        let categories: Vec<&str> = report
+            .map(|r| r.issues.iter().map(|issue| issue.category).collect())
+            .unwrap_or_default();
+        assert_eq!(categories, vec![CATEGORY_PROXIES, CATEGORY_RULES]);
+
+        // 文件不存在与解析失败需与校验问题区分
+        let missing = dir.join("missing.yaml");
+        assert_eq!(
+            ConfigValidator::validate_file(&missing).err(),
+            Some(ConfigFileError::NotFound(missing.display().to_string()))
+        );
+
+        let broken_path = dir.join("broken.yaml");
+        assert!(std::fs::write(&broken_path, "proxies: [unclosed\n").is_ok());
+        assert!(matches!(
+            ConfigValidator::validate_file(&broken_path),
+            Err(ConfigFileError::Parse(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_validate_merged_anchors() {
+        let content = "\
+x-hy2: &hy2
+  type: hysteria2
+  password: secret
+  udp: true
+proxies:
+  - <<: *hy2
+    name: HK
+    server: hk.example.com
+    port: 443
+  - <<: *hy2
+    name: JP
+    server: jp.example.com
+    port: 443
+proxy-groups:
+  - {name: PROXY, type: select, proxies: [HK, JP]}
+rules:
+  - MATCH,PROXY
+";
+        let report = ConfigValidator::validate_content(content)
+            .unwrap_or_else(|e| panic!("解析失败：{}", e));
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+
+        // 锚点中的错误配置在每个合并了它的节点上都会报告
+        let broken = content.replace("udp: true", "udp: true\n  smux: {protocol: unknown}");
+        let report = ConfigValidator::validate_content(&broken)
+            .unwrap_or_else(|e| panic!("解析失败：{}", e));
+        let locations: Vec<&str> = report
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+            .map(|issue| issue.location.as_str())
+            .collect();
+        assert_eq!(locations, vec!["proxies[#0]（HK）", "proxies[#1]（JP）"]);
+    }
+}
@@ -0,0 +1,287 @@
+// 提权执行：统一封装各平台以管理员权限运行外部程序的方式。
+// Windows 使用 UAC（ShellExecuteW runas），macOS 使用 osascript，Linux 使用 pkexec。
+
+#[cfg(not(windows))]
+use std::process::Command;
+
+// Windows：用户在 UAC 对话框中点击「否」（ERROR_CANCELLED）
+#[cfg(any(windows, test))]
+const ERROR_CANCELLED: u32 = 1223;
+
+// Linux：pkexec 授权对话框被用户关闭
+#[cfg(any(not(windows), test))]
+const PKEXEC_DISMISSED: i32 = 126;
+
+// macOS：osascript 授权对话框被用户取消（userCanceledErr）
+#[cfg(any(not(windows), test))]
+const OSASCRIPT_CANCELLED: &str = "(-128)";
+
+// 提权执行结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElevationOutcome {
+    // 执行成功（Windows 上表示已成功以管理员身份启动，不等待其结束）
+    Succeeded,
+    // 用户拒绝了提权请求
+    UserCancelled,
+    // 提权后执行失败，或提权本身失败
+    Failed { code: i32, message: String },
+}
+
+// 以管理员权限运行程序
+// 返回 Err 表示提权工具本身无法启动（如缺少 pkexec）
+pub fn run(program: &str, args: &[&str]) -> Result<ElevationOutcome, String> {
+    log::info!("以管理员权限执行：{} {}", program, args.join(" "));
+
+    #[cfg(windows)]
+    {
+        Ok(run_with_uac(program, args))
+    }
+
+    #[cfg(not(windows))]
+    {
+        let output = if nix::unistd::geteuid().is_root() {
+            // 已有 root 权限，直接执行
+            Command::new(program).args(args).output()
+        } else {
+            elevation_command(program, args).output()
+        }
+        .map_err(|e| format!("无法执行提权命令：{}", e))?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(outcome_from_exit(
+            output.status.code(),
+            stderr.trim(),
+            stdout.trim(),
+        ))
+    }
+}
+
+#[cfg(windows)]
+fn run_with_uac(program: &str, args: &[&str]) -> ElevationOutcome {
+    use windows::Win32::Foundation::GetLastError;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_HIDE;
+    use windows::core::{HSTRING, PCWSTR};
+
+    let verb = HSTRING::from("runas");
+    let file = HSTRING::from(program);
+    let parameters = HSTRING::from(join_windows_args(args));
+
+    unsafe {
+        let result = ShellExecuteW(
+            None, // 使用 None 表示无父窗口
+            PCWSTR(verb.as_ptr()),
+            PCWSTR(file.as_ptr()),
+            PCWSTR(parameters.as_ptr()),
+            PCWSTR::null(),
+            SW_HIDE,
+        );
+        // 用户取消 UAC 时返回值为 SE_ERR_ACCESSDENIED，需结合 GetLastError 区分
+        let last_error = GetLastError().0;
+        outcome_from_shell_execute(result.0 as isize, last_error)
+    }
+}
+
+// 将 ShellExecuteW 返回的 HINSTANCE 值映射为执行结果（> 32 表示成功）
+#[cfg(any(windows, test))]
+fn outcome_from_shell_execute(result_value: isize, last_error: u32) -> ElevationOutcome {
+    // 取消码需先于成功判断（1223 同样大于 32）
+    if result_value == ERROR_CANCELLED as isize {
+        return ElevationOutcome::UserCancelled;
+    }
+    if result_value > 32 {
+        return ElevationOutcome::Succeeded;
+    }
+    // 失败时 GetLastError 才有意义
+    if last_error == ERROR_CANCELLED {
+        return ElevationOutcome::UserCancelled;
+    }
+
+    let message = match result_value {
+        0 => "系统内存或资源不足",
+        2 => "找不到指定的文件",
+        3 => "找不到指定的路径",
+        5 => "拒绝访问（权限不足）",
+        8 => "内存不足",
+        11 => "程序文件损坏或无效",
+        26 => "无法共享",
+        27 => "文件名关联不完整或无效",
+        28 => "操作超时",
+        29 => "DDE 事务失败",
+        30 => "DDE 事务正在处理中",
+        31 => "没有关联的应用程序",
+        32 => "未找到或未注册 DLL",
+        _ => "未知错误",
+    };
+    ElevationOutcome::Failed {
+        code: result_value as i32,
+        message: message.to_string(),
+    }
+}
+
+// 拼接 Windows 命令行参数（含空白或引号的参数加引号）
+#[cfg(any(windows, test))]
+fn join_windows_args(args: &[&str]) -> String {
+    args.iter()
+        .map(|arg| {
+            if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+                return arg.to_string();
+            }
+            // 引号前与结尾处的反斜杠需要加倍
+            let mut quoted = String::from("\"");
+            let mut backslashes = 0;
+            for c in arg.chars() {
+                match c {
+                    '\\' => backslashes += 1,
+                    '"' => {
+                        quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                        backslashes = 0;
+                    }
+                    _ => {
+                        quoted.push_str(&"\\".repeat(backslashes));
+                        backslashes = 0;
+                    }
+                }
+                if c != '\\' {
+                    quoted.push(c);
+                }
+            }
+            quoted.push_str(&"\\".repeat(backslashes * 2));
+            quoted.push('"');
+            quoted
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 构造图形化提权命令
+#[cfg(target_os = "linux")]
+fn elevation_command(program: &str, args: &[&str]) -> Command {
+    let mut command = Command::new("pkexec");
+    command.arg(program).args(args);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn elevation_command(program: &str, args: &[&str]) -> Command {
+    let shell_command = std::iter::once(program)
+        .chain(args.iter().copied())
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let script = format!(
+        r#"do shell script "{}" with administrator privileges"#,
+        shell_command.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+
+    let mut command = Command::new("osascript");
+    command.args(["-e", &script]);
+    command
+}
+
+// 单引号转义 shell 参数
+#[cfg(any(target_os = "macos", test))]
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+// 根据退出状态判断结果（pkexec 与 osascript 的取消方式不同，分别识别）
+#[cfg(any(not(windows), test))]
+fn outcome_from_exit(code: Option<i32>, stderr: &str, stdout: &str) -> ElevationOutcome {
+    let code = code.unwrap_or(-1);
+    if code == 0 {
+        return ElevationOutcome::Succeeded;
+    }
+    if code == PKEXEC_DISMISSED || stderr.contains(OSASCRIPT_CANCELLED) {
+        return ElevationOutcome::UserCancelled;
+    }
+
+    let message = if stderr.is_empty() { stdout } else { stderr };
+    ElevationOutcome::Failed {
+        code,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_execute_outcome() {
+        assert_eq!(
+            outcome_from_shell_execute(42, 0),
+            ElevationOutcome::Succeeded
+        );
+        // 成功时残留的错误码不影响结果
+        assert_eq!(
+            outcome_from_shell_execute(42, ERROR_CANCELLED),
+            ElevationOutcome::Succeeded
+        );
+        assert_eq!(
+            outcome_from_shell_execute(1223, 0),
+            ElevationOutcome::UserCancelled
+        );
+        // 用户取消时 ShellExecuteW 实际返回 SE_ERR_ACCESSDENIED
+        assert_eq!(
+            outcome_from_shell_execute(5, ERROR_CANCELLED),
+            ElevationOutcome::UserCancelled
+        );
+        assert_eq!(
+            outcome_from_shell_execute(5, 0),
+            ElevationOutcome::Failed {
+                code: 5,
+                message: "拒绝访问（权限不足）".to_string()
+            }
+        );
+        assert_eq!(
+            outcome_from_shell_execute(2, 0),
+            ElevationOutcome::Failed {
+                code: 2,
+                message: "找不到指定的文件".to_string()
+            }
+        );
+        assert!(matches!(
+            outcome_from_shell_execute(32, 0),
+            ElevationOutcome::Failed { code: 32, .. }
+        ));
+    }
+
+    #[test]
+    fn test_exit_outcome() {
+        assert_eq!(
+            outcome_from_exit(Some(0), "", ""),
+            ElevationOutcome::Succeeded
+        );
+        assert_eq!(
+            outcome_from_exit(Some(126), "", ""),
+            ElevationOutcome::UserCancelled
+        );
+        assert_eq!(
+            outcome_from_exit(Some(1), "execution error: User canceled. (-128)", ""),
+            ElevationOutcome::UserCancelled
+        );
+        assert_eq!(
+            outcome_from_exit(Some(1), "", "服务未安装"),
+            ElevationOutcome::Failed {
+                code: 1,
+                message: "服务未安装".to_string()
+            }
+        );
+        assert!(matches!(
+            outcome_from_exit(None, "", ""),
+            ElevationOutcome::Failed { code: -1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_argument_quoting() {
+        assert_eq!(
+            join_windows_args(&["/create", "/xml", r"C:\Program Files\task.xml", "/f"]),
+            r#"/create /xml "C:\Program Files\task.xml" /f"#
+        );
+        assert_eq!(join_windows_args(&[r"C:\a b\", ""]), r#""C:\a b\\" """#);
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+}
@@ -4,7 +4,8 @@ pub mod detector;
 
 // 导出公共接口
 pub use detector::{
-    GetNetworkInterfaces, NetworkInterfacesInfo, get_hostname, get_network_addresses,
+    GetNetworkInterfaces, NetworkInterfaceEntry, NetworkInterfaceKind, NetworkInterfacesInfo,
+    get_hostname, get_network_addresses, list_network_interfaces,
 };
 
 pub use detector::init;
@@ -1,7 +1,7 @@
 // 网络接口信息查询：提供跨平台的网络信息获取能力。
-// 输出可用地址列表与主机名。
+// 输出可用地址列表、主机名与网络接口列表（供系统代理目标设备选择）。
 
-use rinf::{DartSignal, RustSignal};
+use rinf::{DartSignal, RustSignal, SignalPiece};
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 use tokio::spawn;
@@ -10,16 +10,36 @@ use tokio::spawn;
 #[derive(Deserialize, DartSignal)]
 pub struct GetNetworkInterfaces;
 
+// 网络接口类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, SignalPiece)]
+pub enum NetworkInterfaceKind {
+    Ethernet,
+    Wireless,
+    // 拨号/VPN 连接（Windows RAS）
+    Dialup,
+    Other,
+}
+
+// 单个网络接口（macOS 为网络服务名，与系统代理目标设备一致）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SignalPiece)]
+pub struct NetworkInterfaceEntry {
+    pub name: String,
+    pub kind: NetworkInterfaceKind,
+    pub is_up: bool,
+}
+
 // Rust → Dart：网络接口信息
 #[derive(Serialize, RustSignal)]
 pub struct NetworkInterfacesInfo {
     pub addresses: Vec<String>,
     pub hostname: Option<String>,
+    // 已过滤回环与虚拟接口
+    pub interfaces: Vec<NetworkInterfaceEntry>,
 }
 
 impl GetNetworkInterfaces {
     // 收集系统网络接口信息并输出可用地址列表。
-    pub fn handle(&self) {
+    pub async fn handle(&self) {
         log::info!("收到获取网络接口请求");
 
         let mut addresses = vec!["127.0.0.1".to_string(), "localhost".to_string()];
@@ -58,9 +78,18 @@ impl GetNetworkInterfaces {
 
         log::debug!("最终地址列表：{:?}", clean_addresses);
 
+        let interfaces = match list_network_interfaces().await {
+            Ok(interfaces) => interfaces,
+            Err(e) => {
+                log::warn!("获取网络接口列表失败：{}", e);
+                Vec::new()
+            }
+        };
+
         let response = NetworkInterfacesInfo {
             addresses: clean_addresses,
             hostname,
+            interfaces,
         };
 
         response.send_signal_to_dart();
@@ -159,12 +188,394 @@ pub fn get_network_addresses() -> Result<Vec<String>, String> {
     }
 }
 
+// 根据接口或硬件端口名称推断类型（macOS 硬件端口名、Windows 适配器友好名）
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+fn kind_from_label(label: &str) -> NetworkInterfaceKind {
+    let label = label.to_lowercase();
+    if ["wi-fi", "wifi", "wlan", "airport", "wireless", "无线"]
+        .iter()
+        .any(|hint| label.contains(hint))
+    {
+        NetworkInterfaceKind::Wireless
+    } else if ["ethernet", "lan", "以太网"]
+        .iter()
+        .any(|hint| label.contains(hint))
+    {
+        NetworkInterfaceKind::Ethernet
+    } else {
+        NetworkInterfaceKind::Other
+    }
+}
+
+// 判断 Windows 适配器是否为虚拟机、容器或隧道创建的虚拟网卡
+#[cfg(any(target_os = "windows", test))]
+fn is_virtual_adapter(name: &str) -> bool {
+    let name = name.to_lowercase();
+    [
+        "vethernet",
+        "vmware",
+        "virtualbox",
+        "hyper-v",
+        "loopback",
+        "isatap",
+        "teredo",
+        "wsl",
+    ]
+    .iter()
+    .any(|hint| name.contains(hint))
+}
+
+// 解析 networksetup -listnetworkserviceorder 输出，返回 服务名 → (硬件端口, 设备名)
+#[cfg(any(target_os = "macos", test))]
+fn parse_service_order(stdout: &str) -> std::collections::HashMap<String, (String, String)> {
+    let mut services = std::collections::HashMap::new();
+    let mut current_service: Option<String> = None;
+
+    for line in stdout.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("(Hardware Port: ") {
+            let Some(service) = current_service.take() else {
+                continue;
+            };
+            let rest = rest.trim_end_matches(')');
+            let (port, device) = match rest.split_once(", Device: ") {
+                Some((port, device)) => (port, device),
+                None => (rest, ""),
+            };
+            services.insert(service, (port.to_string(), device.to_string()));
+        } else if line.starts_with('(')
+            && let Some((_, name)) = line.split_once(") ")
+        {
+            // 已禁用的服务以 * 开头
+            current_service = Some(name.trim_start_matches('*').to_string());
+        }
+    }
+
+    services
+}
+
+// Linux：按 sysfs 信息判断接口类型，回环与虚拟接口返回 None
+#[cfg(any(target_os = "linux", test))]
+fn classify_sysfs_interface(
+    type_code: u32,
+    is_wireless: bool,
+    is_virtual: bool,
+) -> Option<NetworkInterfaceKind> {
+    // ARPHRD_LOOPBACK
+    if type_code == 772 || is_virtual {
+        return None;
+    }
+    Some(match type_code {
+        // ARPHRD_ETHER
+        1 if is_wireless => NetworkInterfaceKind::Wireless,
+        1 => NetworkInterfaceKind::Ethernet,
+        // ARPHRD_PPP
+        512 => NetworkInterfaceKind::Dialup,
+        _ => NetworkInterfaceKind::Other,
+    })
+}
+
+// 列出网络接口（回环与虚拟接口已过滤）
+pub async fn list_network_interfaces() -> Result<Vec<NetworkInterfaceEntry>, String> {
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+
+        let services = crate::atoms::system_proxy::get_network_devices().await?;
+
+        let order = Command::new("/usr/sbin/networksetup")
+            .arg("-listnetworkserviceorder")
+            .output()
+            .map_err(|e| format!("执行 networksetup 失败：{}", e))?;
+        let order = parse_service_order(&String::from_utf8_lossy(&order.stdout));
+
+        // 有地址的设备视为已连接
+        let active_devices: Vec<String> = {
+            use network_interface::NetworkInterface;
+            use network_interface::NetworkInterfaceConfig;
+
+            NetworkInterface::show()
+                .map(|interfaces| {
+                    interfaces
+                        .into_iter()
+                        .filter(|iface| !iface.internal && !iface.addr.is_empty())
+                        .map(|iface| iface.name)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Ok(services
+            .into_iter()
+            .map(|service| {
+                let (kind, is_up) = match order.get(&service) {
+                    Some((port, device)) => (
+                        kind_from_label(port),
+                        active_devices.iter().any(|active| active == device),
+                    ),
+                    None => (kind_from_label(&service), false),
+                };
+                NetworkInterfaceEntry {
+                    name: service,
+                    kind,
+                    is_up,
+                }
+            })
+            .collect())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::path::Path;
+
+        let read = |path: &Path| {
+            std::fs::read_to_string(path)
+                .map(|content| content.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        let dir = std::fs::read_dir("/sys/class/net")
+            .map_err(|e| format!("读取 /sys/class/net 失败：{}", e))?;
+
+        let mut interfaces = Vec::new();
+        for entry in dir.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let type_code = read(&path.join("type")).parse().unwrap_or(0);
+            let is_wireless = path.join("wireless").exists();
+            // 物理网卡存在 device 链接，虚拟接口（docker、veth、tun 等）没有
+            let is_virtual = !path.join("device").exists();
+
+            if let Some(kind) = classify_sysfs_interface(type_code, is_wireless, is_virtual) {
+                interfaces.push(NetworkInterfaceEntry {
+                    name,
+                    kind,
+                    is_up: read(&path.join("operstate")) == "up",
+                });
+            }
+        }
+
+        interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(interfaces)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use network_interface::NetworkInterface;
+        use network_interface::NetworkInterfaceConfig;
+
+        let mut interfaces: Vec<NetworkInterfaceEntry> = Vec::new();
+
+        let adapters = NetworkInterface::show().map_err(|e| format!("无法获取网络接口：{}", e))?;
+        for adapter in adapters {
+            if adapter.internal || is_virtual_adapter(&adapter.name) {
+                continue;
+            }
+            // 有非 APIPA IPv4 地址的适配器视为已连接
+            let is_up = adapter
+                .addr
+                .iter()
+                .any(|addr| matches!(addr.ip(), IpAddr::V4(ipv4) if !is_apipa_address(&ipv4)));
+            match interfaces
+                .iter_mut()
+                .find(|entry| entry.name == adapter.name)
+            {
+                Some(entry) => entry.is_up |= is_up,
+                None => interfaces.push(NetworkInterfaceEntry {
+                    kind: kind_from_label(&adapter.name),
+                    name: adapter.name,
+                    is_up,
+                }),
+            }
+        }
+
+        interfaces.extend(ras::list_entries());
+        Ok(interfaces)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        Ok(Vec::new())
+    }
+}
+
+// Windows RAS 拨号/VPN 连接（系统代理会同步到这些连接）
+#[cfg(target_os = "windows")]
+mod ras {
+    use super::{NetworkInterfaceEntry, NetworkInterfaceKind};
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::NetworkManagement::Rras::{
+        RASCONNW, RASENTRYNAMEW, RasEnumConnectionsW, RasEnumEntriesW,
+    };
+
+    fn wide_to_string(wide: &[u16]) -> String {
+        let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+        String::from_utf16_lossy(&wide[..len])
+    }
+
+    // 当前已连接的 RAS 连接名
+    fn connected_names() -> Vec<String> {
+        unsafe {
+            let mut size = std::mem::size_of::<RASCONNW>() as u32;
+            let mut count = 0u32;
+            let mut probe = RASCONNW {
+                dwSize: size,
+                ..Default::default()
+            };
+            let result = RasEnumConnectionsW(Some(&mut probe), &mut size, &mut count);
+            if result == ERROR_SUCCESS.0 {
+                return if count > 0 {
+                    vec![wide_to_string(&probe.szEntryName)]
+                } else {
+                    Vec::new()
+                };
+            }
+
+            let mut connections = vec![
+                RASCONNW {
+                    dwSize: std::mem::size_of::<RASCONNW>() as u32,
+                    ..Default::default()
+                };
+                count as usize
+            ];
+            if RasEnumConnectionsW(Some(connections.as_mut_ptr()), &mut size, &mut count)
+                != ERROR_SUCCESS.0
+            {
+                return Vec::new();
+            }
+            connections
+                .iter()
+                .take(count as usize)
+                .map(|connection| wide_to_string(&connection.szEntryName))
+                .collect()
+        }
+    }
+
+    // 电话簿中的全部 RAS 连接
+    pub fn list_entries() -> Vec<NetworkInterfaceEntry> {
+        let names = unsafe {
+            let mut size = std::mem::size_of::<RASENTRYNAMEW>() as u32;
+            let mut count = 0u32;
+            let mut probe = RASENTRYNAMEW {
+                dwSize: size,
+                ..Default::default()
+            };
+            let result = RasEnumEntriesW(None, None, Some(&mut probe), &mut size, &mut count);
+            if result == ERROR_SUCCESS.0 {
+                if count > 0 {
+                    vec![wide_to_string(&probe.szEntryName)]
+                } else {
+                    Vec::new()
+                }
+            } else if count > 0 {
+                let mut entries = vec![
+                    RASENTRYNAMEW {
+                        dwSize: std::mem::size_of::<RASENTRYNAMEW>() as u32,
+                        ..Default::default()
+                    };
+                    count as usize
+                ];
+                if RasEnumEntriesW(
+                    None,
+                    None,
+                    Some(entries.as_mut_ptr()),
+                    &mut size,
+                    &mut count,
+                ) == ERROR_SUCCESS.0
+                {
+                    entries
+                        .iter()
+                        .take(count as usize)
+                        .map(|entry| wide_to_string(&entry.szEntryName))
+                        .collect()
+                } else {
+                    Vec::new()
+                }
+            } else {
+                Vec::new()
+            }
+        };
+
+        let connected = connected_names();
+        names
+            .into_iter()
+            .map(|name| NetworkInterfaceEntry {
+                is_up: connected.contains(&name),
+                name,
+                kind: NetworkInterfaceKind::Dialup,
+            })
+            .collect()
+    }
+}
+
 pub fn init() {
     spawn(async {
         let receiver = GetNetworkInterfaces::get_dart_signal_receiver();
         while let Some(dart_signal) = receiver.recv().await {
-            dart_signal.message.handle();
+            dart_signal.message.handle().await;
         }
         log::info!("获取网络接口消息通道已关闭，退出监听器");
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_service_order() {
+        let stdout = "An asterisk (*) denotes that a network service is disabled.\n\
+                      (1) Wi-Fi\n\
+                      (Hardware Port: Wi-Fi, Device: en0)\n\
+                      \n\
+                      (*) Bluetooth PAN\n\
+                      (Hardware Port: Bluetooth PAN, Device: en6)\n\
+                      \n\
+                      (2) USB 10/100/1000 LAN\n\
+                      (Hardware Port: USB 10/100/1000 LAN, Device: en7)\n";
+        let services = parse_service_order(stdout);
+
+        assert_eq!(
+            services.get("Wi-Fi"),
+            Some(&("Wi-Fi".to_string(), "en0".to_string()))
+        );
+        assert_eq!(
+            services.get("USB 10/100/1000 LAN"),
+            Some(&("USB 10/100/1000 LAN".to_string(), "en7".to_string()))
+        );
+        assert_eq!(services.len(), 3);
+    }
+
+    #[test]
+    fn test_interface_kind_detection() {
+        assert_eq!(kind_from_label("Wi-Fi"), NetworkInterfaceKind::Wireless);
+        assert_eq!(kind_from_label("WLAN 2"), NetworkInterfaceKind::Wireless);
+        assert_eq!(
+            kind_from_label("USB 10/100/1000 LAN"),
+            NetworkInterfaceKind::Ethernet
+        );
+        assert_eq!(kind_from_label("以太网"), NetworkInterfaceKind::Ethernet);
+        assert_eq!(
+            kind_from_label("Thunderbolt Bridge"),
+            NetworkInterfaceKind::Other
+        );
+
+        assert!(is_virtual_adapter("vEthernet (WSL)"));
+        assert!(is_virtual_adapter("VMware Network Adapter VMnet8"));
+        assert!(!is_virtual_adapter("Ethernet"));
+
+        assert_eq!(classify_sysfs_interface(772, false, true), None);
+        assert_eq!(classify_sysfs_interface(1, false, true), None);
+        assert_eq!(
+            classify_sysfs_interface(1, true, false),
+            Some(NetworkInterfaceKind::Wireless)
+        );
+        assert_eq!(
+            classify_sysfs_interface(1, false, false),
+            Some(NetworkInterfaceKind::Ethernet)
+        );
+        assert_eq!(
+            classify_sysfs_interface(512, false, false),
+            Some(NetworkInterfaceKind::Dialup)
+        );
+    }
+}
@@ -34,6 +34,12 @@ pub struct PathService {
     // 日志文件路径
     log_file: PathBuf,
 
+    // 订阅配置模板目录
+    templates_dir: PathBuf,
+
+    // 订阅元数据索引文件
+    subscription_meta_file: PathBuf,
+
     // Windows 特有：自启动任务目录
     #[cfg(target_os = "windows")]
     tasks_dir: PathBuf,
@@ -74,6 +80,12 @@ impl PathService {
         // 日志文件路径
         let log_file = app_data_dir.join("running.logs");
 
+        // 配置模板目录
+        let templates_dir = app_data_dir.join("templates");
+
+        // 订阅元数据索引
+        let subscription_meta_file = app_data_dir.join("subscription_meta.json");
+
         // Windows 自启动任务目录
         #[cfg(target_os = "windows")]
         let tasks_dir = {
@@ -90,6 +102,8 @@ impl PathService {
             assets_service_dir,
             assets_service_binary,
             log_file,
+            templates_dir,
+            subscription_meta_file,
             #[cfg(target_os = "windows")]
             tasks_dir,
         })
@@ -153,6 +167,8 @@ impl PathService {
                 .join("service")
                 .join("stelliberty-service"),
             log_file: current_dir.join("data").join("running.logs"),
+            templates_dir: current_dir.join("data").join("templates"),
+            subscription_meta_file: current_dir.join("data").join("subscription_meta.json"),
             #[cfg(target_os = "windows")]
             tasks_dir: current_dir.join("tasks"),
         }
@@ -188,6 +204,16 @@ impl PathService {
         &self.log_file
     }
 
+    // 获取配置模板目录
+    pub fn templates_dir(&self) -> &PathBuf {
+        &self.templates_dir
+    }
+
+    // 获取订阅元数据索引文件路径
+    pub fn subscription_meta_file(&self) -> &PathBuf {
+        &self.subscription_meta_file
+    }
+
     // 获取自启动任务目录（仅 Windows）
     #[cfg(target_os = "windows")]
     pub fn tasks_dir(&self) -> &PathBuf {
@@ -199,6 +225,7 @@ impl PathService {
         let dirs = vec![
             &self.app_data_dir,
             &self.service_private_dir,
+            &self.templates_dir,
             #[cfg(target_os = "windows")]
             &self.tasks_dir,
         ];
@@ -268,6 +295,22 @@ pub fn log_file() -> PathBuf {
         .unwrap_or_else(|_| PathBuf::from("running.logs"))
 }
 
+// 获取配置模板目录
+pub fn templates_dir() -> PathBuf {
+    PATH_SERVICE
+        .read()
+        .map(|s| s.templates_dir().clone())
+        .unwrap_or_else(|_| PathBuf::from("templates"))
+}
+
+// 获取订阅元数据索引文件路径
+pub fn subscription_meta_file() -> PathBuf {
+    PATH_SERVICE
+        .read()
+        .map(|s| s.subscription_meta_file().clone())
+        .unwrap_or_else(|_| PathBuf::from("subscription_meta.json"))
+}
+
 // 获取自启动任务目录（仅 Windows）
 #[cfg(target_os = "windows")]
 pub fn tasks_dir() -> PathBuf {
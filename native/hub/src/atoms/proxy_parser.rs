@@ -1,5 +1,12 @@
 // 代理链接解析器原子模块
 
 mod parser;
+pub mod region;
+mod singbox;
+mod surge;
+pub mod template;
+mod yaml_output;
 
-pub use parser::ProxyParser;
+pub use parser::{
+    LinkLine, LinkLineOutcome, ParseOptions, ParsedBlob, ParsedSubscription, ProxyParser,
+};
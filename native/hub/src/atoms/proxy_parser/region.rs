@@ -0,0 +1,205 @@
+// 节点地区识别：根据节点名称中的旗帜 emoji 与常见中英文地区关键词推断所属地区，
+// 用于导入扁平节点列表时按地区生成自动测速组。
+
+// 未识别地区的节点所在组
+pub const OTHER_GROUP_NAME: &str = "其他";
+
+// 地区定义：旗帜 + 显示名称 + 名称关键词
+pub struct Region {
+    pub flag: &'static str,
+    pub name: &'static str,
+    keywords: &'static [&'static str],
+}
+
+impl Region {
+    // 代理组名称，如「🇭🇰 香港」
+    pub fn group_name(&self) -> String {
+        format!("{} {}", self.flag, self.name)
+    }
+}
+
+// 按生成代理组的顺序排列；英文关键词不区分大小写，且前后不能紧邻字母
+pub const REGIONS: &[Region] = &[
+    Region {
+        flag: "🇭🇰",
+        name: "香港",
+        keywords: &["香港", "HK", "Hong Kong", "HongKong"],
+    },
+    Region {
+        flag: "🇹🇼",
+        name: "台湾",
+        keywords: &["台湾", "台灣", "TW", "Taiwan", "台北"],
+    },
+    Region {
+        flag: "🇯🇵",
+        name: "日本",
+        keywords: &[
+            "日本", "JP", "Japan", "东京", "東京", "Tokyo", "大阪", "Osaka",
+        ],
+    },
+    Region {
+        flag: "🇸🇬",
+        name: "新加坡",
+        keywords: &["新加坡", "狮城", "SG", "Singapore"],
+    },
+    Region {
+        flag: "🇰🇷",
+        name: "韩国",
+        keywords: &["韩国", "韓國", "KR", "Korea", "首尔", "Seoul"],
+    },
+    Region {
+        flag: "🇺🇸",
+        name: "美国",
+        keywords: &[
+            "美国",
+            "美國",
+            "US",
+            "USA",
+            "United States",
+            "洛杉矶",
+            "硅谷",
+            "纽约",
+            "Los Angeles",
+            "San Jose",
+            "Seattle",
+        ],
+    },
+    Region {
+        flag: "🇬🇧",
+        name: "英国",
+        // 不含 GB，避免匹配流量信息（如 100GB）
+        keywords: &["英国", "UK", "United Kingdom", "伦敦", "London"],
+    },
+    Region {
+        flag: "🇩🇪",
+        name: "德国",
+        keywords: &["德国", "DE", "Germany", "法兰克福", "Frankfurt"],
+    },
+];
+
+// 推断节点所属地区：取名称中最先出现的旗帜或关键词，无法识别时返回 None
+pub fn infer_region(name: &str) -> Option<&'static Region> {
+    region_index(name).map(|index| &REGIONS[index])
+}
+
+// 所属地区在 REGIONS 中的下标
+fn region_index(name: &str) -> Option<usize> {
+    REGIONS
+        .iter()
+        .enumerate()
+        .filter_map(|(index, region)| {
+            std::iter::once(region.flag)
+                .chain(region.keywords.iter().copied())
+                .filter_map(|keyword| find_keyword(name, keyword))
+                .min()
+                .map(|position| (position, index))
+        })
+        .min_by_key(|(position, _)| *position)
+        .map(|(_, index)| index)
+}
+
+// 查找关键词首次出现的位置；英文关键词需位于单词边界，避免 Plus 命中 US
+fn find_keyword(name: &str, keyword: &str) -> Option<usize> {
+    if !keyword.is_ascii() {
+        return name.find(keyword);
+    }
+
+    let haystack = name.to_ascii_lowercase();
+    let needle = keyword.to_ascii_lowercase();
+    let is_letter_at = |index: Option<&u8>| index.is_some_and(u8::is_ascii_alphabetic);
+    haystack
+        .match_indices(&needle)
+        .map(|(start, _)| start)
+        .find(|&start| {
+            let end = start + needle.len();
+            !is_letter_at(
+                start
+                    .checked_sub(1)
+                    .and_then(|i| haystack.as_bytes().get(i)),
+            ) && !is_letter_at(haystack.as_bytes().get(end))
+        })
+}
+
+// 按地区分组后的代理组
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionGroup {
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+// 将节点按地区分组：按 REGIONS 顺序输出非空地区组，未识别的节点放入「其他」组（位于最后）
+pub fn group_by_region(names: &[String]) -> Vec<RegionGroup> {
+    let mut buckets: Vec<Vec<String>> = vec![Vec::new(); REGIONS.len() + 1];
+    for name in names {
+        let index = region_index(name).unwrap_or(REGIONS.len());
+        buckets[index].push(name.clone());
+    }
+
+    buckets
+        .into_iter()
+        .enumerate()
+        .filter(|(_, members)| !members.is_empty())
+        .map(|(index, members)| RegionGroup {
+            name: REGIONS
+                .get(index)
+                .map(Region::group_name)
+                .unwrap_or_else(|| OTHER_GROUP_NAME.to_string()),
+            members,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region_name(name: &str) -> Option<&'static str> {
+        infer_region(name).map(|region| region.name)
+    }
+
+    #[test]
+    fn test_infer_region() {
+        assert_eq!(region_name("🇭🇰 香港 01"), Some("香港"));
+        assert_eq!(region_name("HK-02 IPLC"), Some("香港"));
+        assert_eq!(region_name("hong kong 03"), Some("香港"));
+        assert_eq!(region_name("🇯🇵 Tokyo 01"), Some("日本"));
+        assert_eq!(region_name("日本大阪02"), Some("日本"));
+        assert_eq!(region_name("JP03"), Some("日本"));
+        // 多个地区时取最先出现的（中转节点通常以落地地区开头）
+        assert_eq!(region_name("日本 via HK"), Some("日本"));
+        // 英文关键词需位于单词边界
+        assert_eq!(region_name("Plus 01"), None);
+        assert_eq!(region_name("剩余流量：100G"), None);
+        assert_eq!(region_name("Backup"), None);
+    }
+
+    #[test]
+    fn test_group_by_region() {
+        let names: Vec<String> = [
+            "JP-01",
+            "剩余流量",
+            "🇭🇰 香港 01",
+            "HK-02",
+            "Backup",
+            "东京 02",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+        let groups = group_by_region(&names);
+
+        let group = |name: &str, members: &[&str]| RegionGroup {
+            name: name.to_string(),
+            members: members.iter().map(|member| member.to_string()).collect(),
+        };
+        assert_eq!(
+            groups,
+            vec![
+                group("🇭🇰 香港", &["🇭🇰 香港 01", "HK-02"]),
+                group("🇯🇵 日本", &["JP-01", "东京 02"]),
+                group("其他", &["剩余流量", "Backup"]),
+            ]
+        );
+        assert!(group_by_region(&[]).is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+// sing-box 配置转换：将 outbounds 中的常见节点转换为 Clash 节点。
+// 仅转换代理类出站，selector/urltest/direct/block 等出站由 Clash 代理组替代。
+
+use serde_json::{Value as JsonValue, json};
+
+// 非代理类出站类型（转换时跳过）
+const NON_PROXY_OUTBOUNDS: &[&str] = &["selector", "urltest", "direct", "block", "dns"];
+
+// 判断内容是否为 sing-box 配置（包含 outbounds 数组的 JSON 对象）
+pub fn is_singbox_config(content: &str) -> bool {
+    let trimmed = content.trim_start();
+    if !trimmed.starts_with('{') {
+        return false;
+    }
+
+    serde_json::from_str::<JsonValue>(trimmed)
+        .map(|value| value.get("outbounds").is_some_and(|v| v.is_array()))
+        .unwrap_or(false)
+}
+
+// 转换 sing-box 配置中的出站为 Clash 节点列表
+pub fn convert_outbounds(content: &str) -> Result<Vec<JsonValue>, String> {
+    let config: JsonValue =
+        serde_json::from_str(content.trim()).map_err(|e| format!("JSON 解析失败：{}", e))?;
+    let outbounds = config
+        .get("outbounds")
+        .and_then(|v| v.as_array())
+        .ok_or("未找到 outbounds 数组")?;
+
+    let mut proxies = Vec::new();
+    for outbound in outbounds {
+        let outbound_type = outbound["type"].as_str().unwrap_or("");
+        let tag = outbound["tag"].as_str().unwrap_or("");
+
+        if NON_PROXY_OUTBOUNDS.contains(&outbound_type) {
+            log::debug!("跳过非代理出站：{}（{}）", tag, outbound_type);
+            continue;
+        }
+
+        match convert_outbound(outbound) {
+            Ok(proxy) => proxies.push(proxy),
+            Err(e) => log::warn!("跳过 sing-box 出站：{}（{}）- {}", tag, outbound_type, e),
+        }
+    }
+
+    Ok(proxies)
+}
+
+// 转换单个出站
+fn convert_outbound(outbound: &JsonValue) -> Result<JsonValue, String> {
+    let outbound_type = outbound["type"].as_str().ok_or("缺少 type 字段")?;
+    let server = outbound["server"].as_str().ok_or("缺少 server 字段")?;
+    let port = outbound["server_port"]
+        .as_i64()
+        .ok_or("缺少 server_port 字段")?;
+    let name = super::parser::node_name(outbound["tag"].as_str(), outbound_type, server, port);
+
+    let mut proxy = match outbound_type {
+        "shadowsocks" => json!({
+            "name": name,
+            "type": "ss",
+            "server": server,
+            "port": port,
+            "cipher": outbound["method"].as_str().ok_or("缺少 method 字段")?,
+            "password": outbound["password"].as_str().unwrap_or(""),
+        }),
+        "vmess" => json!({
+            "name": name,
+            "type": "vmess",
+            "server": server,
+            "port": port,
+            "uuid": outbound["uuid"].as_str().ok_or("缺少 uuid 字段")?,
+            "alterId": outbound["alter_id"].as_i64().unwrap_or(0),
+            // 未设置时为 null，生成配置时补为 auto
+            "cipher": outbound["security"].as_str(),
+            "network": "tcp",
+        }),
+        "vless" => {
+            let mut proxy = json!({
+                "name": name,
+                "type": "vless",
+                "server": server,
+                "port": port,
+                "uuid": outbound["uuid"].as_str().ok_or("缺少 uuid 字段")?,
+                "network": "tcp",
+            });
+            if let Some(flow) = outbound["flow"].as_str().filter(|f| !f.is_empty()) {
+                proxy["flow"] = json!(flow);
+            }
+            proxy
+        }
+        "trojan" => json!({
+            "name": name,
+            "type": "trojan",
+            "server": server,
+            "port": port,
+            "password": outbound["password"].as_str().ok_or("缺少 password 字段")?,
+        }),
+        "hysteria2" => {
+            let mut proxy = json!({
+                "name": name,
+                "type": "hysteria2",
+                "server": server,
+                "port": port,
+                "password": outbound["password"].as_str().unwrap_or(""),
+            });
+            if let Some(obfs) = outbound["obfs"]["type"].as_str() {
+                proxy["obfs"] = json!(obfs);
+                if let Some(obfs_password) = outbound["obfs"]["password"].as_str() {
+                    proxy["obfs-password"] = json!(obfs_password);
+                }
+            }
+            if let Some(up) = outbound["up_mbps"].as_i64() {
+                proxy["up"] = json!(up);
+            }
+            if let Some(down) = outbound["down_mbps"].as_i64() {
+                proxy["down"] = json!(down);
+            }
+            proxy
+        }
+        "tuic" => {
+            let mut proxy = json!({
+                "name": name,
+                "type": "tuic",
+                "server": server,
+                "port": port,
+                "uuid": outbound["uuid"].as_str().ok_or("缺少 uuid 字段")?,
+                "password": outbound["password"].as_str().unwrap_or(""),
+            });
+            if let Some(congestion) = outbound["congestion_control"].as_str() {
+                proxy["congestion-control"] = json!(congestion);
+            }
+            proxy
+        }
+        other => return Err(format!("不支持的出站类型：{}", other)),
+    };
+
+    apply_tls(&mut proxy, outbound, outbound_type);
+    apply_transport(&mut proxy, outbound);
+
+    Ok(proxy)
+}
+
+// 转换 TLS 配置
+fn apply_tls(proxy: &mut JsonValue, outbound: &JsonValue, outbound_type: &str) {
+    let tls = &outbound["tls"];
+    if !tls["enabled"].as_bool().unwrap_or(false) {
+        return;
+    }
+
+    // vmess/vless 使用 tls + servername，其余协议默认启用 TLS 并使用 sni
+    let sni_key = match outbound_type {
+        "vmess" | "vless" => {
+            proxy["tls"] = json!(true);
+            "servername"
+        }
+        _ => "sni",
+    };
+
+    if let Some(server_name) = tls["server_name"].as_str() {
+        proxy[sni_key] = json!(server_name);
+    }
+    if tls["insecure"].as_bool().unwrap_or(false) {
+        proxy["skip-cert-verify"] = json!(true);
+    }
+    if let Some(alpn) = tls["alpn"].as_array() {
+        proxy["alpn"] = json!(alpn);
+    }
+    if let Some(fingerprint) = tls["utls"]["fingerprint"].as_str() {
+        proxy["client-fingerprint"] = json!(fingerprint);
+    }
+
+    let reality = &tls["reality"];
+    if reality["enabled"].as_bool().unwrap_or(false) {
+        proxy["reality-opts"] = json!({
+            "public-key": reality["public_key"].as_str().unwrap_or(""),
+            "short-id": reality["short_id"].as_str().unwrap_or(""),
+        });
+    }
+}
+
+// 转换传输层配置（ws / grpc）
+fn apply_transport(proxy: &mut JsonValue, outbound: &JsonValue) {
+    let transport = &outbound["transport"];
+    match transport["type"].as_str() {
+        Some("ws") => {
+            let mut ws_opts = json!({
+                "path": transport["path"].as_str().unwrap_or("/"),
+            });
+            if let Some(host) = transport["headers"]["Host"].as_str() {
+                ws_opts["headers"] = json!({"Host": host});
+            }
+            proxy["network"] = json!("ws");
+            proxy["ws-opts"] = ws_opts;
+        }
+        Some("grpc") => {
+            proxy["network"] = json!("grpc");
+            proxy["grpc-opts"] = json!({
+                "grpc-service-name": transport["service_name"].as_str().unwrap_or(""),
+            });
+        }
+        Some(other) => {
+            log::warn!("暂不支持的 sing-box 传输类型：{}，按 TCP 处理", other);
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SINGBOX_DOC: &str = r#"{
+        "outbounds": [
+            {"type": "selector", "tag": "select", "outbounds": ["ss-hk", "vless-jp"]},
+            {
+                "type": "shadowsocks",
+                "tag": "ss-hk",
+                "server": "hk.example.com",
+                "server_port": 8388,
+                "method": "aes-128-gcm",
+                "password": "secret"
+            },
+            {
+                "type": "vless",
+                "tag": "vless-jp",
+                "server": "jp.example.com",
+                "server_port": 443,
+                "uuid": "b831381d-6324-4d53-ad4f-8cda48b30811",
+                "flow": "xtls-rprx-vision",
+                "tls": {
+                    "enabled": true,
+                    "server_name": "www.microsoft.com",
+                    "reality": {"enabled": true, "public_key": "pbk", "short_id": "0123"}
+                }
+            },
+            {"type": "direct", "tag": "direct"},
+            {"type": "wireguard", "tag": "wg", "server": "wg.example.com", "server_port": 51820}
+        ]
+    }"#;
+
+    #[test]
+    fn test_detect_singbox_config() {
+        assert!(is_singbox_config(SINGBOX_DOC));
+        assert!(!is_singbox_config("proxies: []"));
+        assert!(!is_singbox_config(r#"{"proxies": []}"#));
+    }
+
+    #[test]
+    fn test_convert_two_outbounds() {
+        let proxies = convert_outbounds(SINGBOX_DOC).unwrap_or_default();
+        assert_eq!(proxies.len(), 2);
+
+        let ss = &proxies[0];
+        assert_eq!(ss["name"], "ss-hk");
+        assert_eq!(ss["type"], "ss");
+        assert_eq!(ss["port"], 8388);
+        assert_eq!(ss["cipher"], "aes-128-gcm");
+
+        let vless = &proxies[1];
+        assert_eq!(vless["type"], "vless");
+        assert_eq!(vless["tls"], true);
+        assert_eq!(vless["servername"], "www.microsoft.com");
+        assert_eq!(vless["flow"], "xtls-rprx-vision");
+        assert_eq!(vless["reality-opts"]["public-key"], "pbk");
+    }
+}
@@ -0,0 +1,417 @@
+// Surge / Quantumult X 配置转换：将 Surge [Proxy] 段或 Quantumult X [server_local] 段中的
+// 节点行转换为 Clash 节点（支持 ss/vmess/trojan/http/socks5），其余指令记录后跳过。
+
+use serde_json::{Value as JsonValue, json};
+use std::collections::HashMap;
+
+// Surge 节点段
+const SURGE_PROXY_SECTION: &str = "[proxy]";
+
+// Quantumult X 节点段
+const QUANX_SERVER_SECTION: &str = "[server_local]";
+
+// Surge 中不对应代理节点的内置策略
+const SURGE_BUILTIN_POLICIES: &[&str] = &["direct", "reject", "reject-tinygif", "reject-drop"];
+
+// 可转换的 Surge 节点类型
+const SURGE_PROXY_TYPES: &[&str] = &[
+    "ss",
+    "vmess",
+    "trojan",
+    "http",
+    "https",
+    "socks5",
+    "socks5-tls",
+];
+
+// 配置方言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dialect {
+    Surge,
+    QuantumultX,
+}
+
+// 判断内容是否包含 Surge [Proxy] 段或 Quantumult X [server_local] 段
+pub fn is_surge_like_config(content: &str) -> bool {
+    content.lines().any(|line| section_dialect(line).is_some())
+}
+
+// 转换节点段中的所有节点行
+pub fn convert_proxies(content: &str) -> Vec<JsonValue> {
+    let mut proxies = Vec::new();
+    let mut dialect = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            dialect = section_dialect(line);
+            continue;
+        }
+        let Some(dialect) = dialect else {
+            continue;
+        };
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with(';')
+            || line.starts_with("//")
+        {
+            continue;
+        }
+
+        let result = match dialect {
+            Dialect::Surge => convert_surge_line(line),
+            Dialect::QuantumultX => convert_quanx_line(line),
+        };
+        match result {
+            Ok(Some(proxy)) => proxies.push(proxy),
+            Ok(None) => log::debug!("跳过内置策略：{}", line),
+            Err(e) => log::warn!("跳过不支持的节点行：{} - {}", line, e),
+        }
+    }
+
+    proxies
+}
+
+// 识别段标题
+fn section_dialect(line: &str) -> Option<Dialect> {
+    match line.trim().to_ascii_lowercase().as_str() {
+        SURGE_PROXY_SECTION => Some(Dialect::Surge),
+        QUANX_SERVER_SECTION => Some(Dialect::QuantumultX),
+        _ => None,
+    }
+}
+
+// 拆分逗号分隔的字段，key=value 形式放入参数表，其余按顺序作为位置参数
+fn split_fields(fields: &str) -> (Vec<&str>, HashMap<String, String>) {
+    let mut positional = Vec::new();
+    let mut params = HashMap::new();
+    for field in fields.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        match field.split_once('=') {
+            Some((key, value)) => {
+                params.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+            None => positional.push(field),
+        }
+    }
+    (positional, params)
+}
+
+fn is_true(params: &HashMap<String, String>, key: &str) -> bool {
+    params
+        .get(key)
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+fn parse_port(port: &str) -> Result<u16, String> {
+    port.trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port > 0)
+        .ok_or_else(|| format!("端口无效：{}", port))
+}
+
+// 转换 Surge 节点行：名称 = 类型, 服务器, 端口, key=value, ...
+fn convert_surge_line(line: &str) -> Result<Option<JsonValue>, String> {
+    let (name, definition) = line.split_once('=').ok_or("缺少 = 分隔符")?;
+    let name = name.trim();
+    let (positional, params) = split_fields(definition);
+
+    let proxy_type = positional
+        .first()
+        .ok_or("缺少节点类型")?
+        .to_ascii_lowercase();
+    if SURGE_BUILTIN_POLICIES.contains(&proxy_type.as_str()) {
+        return Ok(None);
+    }
+    if !SURGE_PROXY_TYPES.contains(&proxy_type.as_str()) {
+        return Err(format!("不支持的节点类型：{}", proxy_type));
+    }
+    let server = *positional.get(1).ok_or("缺少服务器地址")?;
+    let port = parse_port(positional.get(2).ok_or("缺少端口")?)?;
+    let name = super::parser::node_name(Some(name), &proxy_type, server, port);
+
+    let mut proxy = match proxy_type.as_str() {
+        "ss" => {
+            let mut proxy = json!({
+                "name": name,
+                "type": "ss",
+                "server": server,
+                "port": port,
+                "cipher": params.get("encrypt-method").ok_or("缺少 encrypt-method")?,
+                "password": params.get("password").map(String::as_str).unwrap_or(""),
+            });
+            if let Some(mode) = params.get("obfs") {
+                proxy["plugin"] = json!("obfs");
+                proxy["plugin-opts"] = json!({
+                    "mode": mode,
+                    "host": params.get("obfs-host").map(String::as_str).unwrap_or(server),
+                });
+            }
+            proxy
+        }
+        "vmess" => {
+            let mut proxy = json!({
+                "name": name,
+                "type": "vmess",
+                "server": server,
+                "port": port,
+                "uuid": params.get("username").ok_or("缺少 username")?,
+                "alterId": 0,
+                "network": "tcp",
+            });
+            if is_true(&params, "tls") {
+                proxy["tls"] = json!(true);
+                if let Some(sni) = params.get("sni") {
+                    proxy["servername"] = json!(sni);
+                }
+            }
+            proxy
+        }
+        "trojan" => {
+            let mut proxy = json!({
+                "name": name,
+                "type": "trojan",
+                "server": server,
+                "port": port,
+                "password": params.get("password").ok_or("缺少 password")?,
+            });
+            if let Some(sni) = params.get("sni") {
+                proxy["sni"] = json!(sni);
+            }
+            proxy
+        }
+        "http" | "https" | "socks5" | "socks5-tls" => {
+            let is_tls = matches!(proxy_type.as_str(), "https" | "socks5-tls");
+            let mut proxy = json!({
+                "name": name,
+                "type": if proxy_type.starts_with("http") { "http" } else { "socks5" },
+                "server": server,
+                "port": port,
+            });
+            // 用户名与密码可写作位置参数或 username=/password=
+            let username = params
+                .get("username")
+                .map(String::as_str)
+                .or(positional.get(3).copied());
+            let password = params
+                .get("password")
+                .map(String::as_str)
+                .or(positional.get(4).copied());
+            if let Some(username) = username {
+                proxy["username"] = json!(username);
+            }
+            if let Some(password) = password {
+                proxy["password"] = json!(password);
+            }
+            if is_tls {
+                proxy["tls"] = json!(true);
+                if let Some(sni) = params.get("sni") {
+                    proxy["sni"] = json!(sni);
+                }
+            }
+            proxy
+        }
+        other => return Err(format!("不支持的节点类型：{}", other)),
+    };
+
+    if is_true(&params, "skip-cert-verify") {
+        proxy["skip-cert-verify"] = json!(true);
+    }
+    if let Some(udp) = params.get("udp-relay") {
+        proxy["udp"] = json!(udp.eq_ignore_ascii_case("true"));
+    }
+    if is_true(&params, "ws") {
+        let mut ws_opts = json!({
+            "path": params.get("ws-path").map(String::as_str).unwrap_or("/"),
+        });
+        // ws-headers=Host:example.com|User-Agent:xxx
+        let host = params.get("ws-headers").and_then(|headers| {
+            headers.split('|').find_map(|header| {
+                header
+                    .split_once(':')
+                    .filter(|(key, _)| key.trim().eq_ignore_ascii_case("host"))
+                    .map(|(_, value)| value.trim().to_string())
+            })
+        });
+        if let Some(host) = host {
+            ws_opts["headers"] = json!({"Host": host});
+        }
+        proxy["network"] = json!("ws");
+        proxy["ws-opts"] = ws_opts;
+    }
+
+    Ok(Some(proxy))
+}
+
+// 转换 Quantumult X 节点行：类型=服务器:端口, key=value, ..., tag=名称
+fn convert_quanx_line(line: &str) -> Result<Option<JsonValue>, String> {
+    let (proxy_type, rest) = line.split_once('=').ok_or("缺少 = 分隔符")?;
+    let proxy_type = proxy_type.trim().to_ascii_lowercase();
+    let (address, options) = rest.split_once(',').unwrap_or((rest, ""));
+    let (_, params) = split_fields(options);
+
+    let (host, port) = address.trim().rsplit_once(':').ok_or("缺少端口")?;
+    let server = host.trim_start_matches('[').trim_end_matches(']');
+    let port = parse_port(port)?;
+    let name = super::parser::node_name(
+        params.get("tag").map(String::as_str),
+        &proxy_type,
+        server,
+        port,
+    );
+
+    let obfs = params.get("obfs").map(String::as_str);
+    let mut proxy = match proxy_type.as_str() {
+        "shadowsocks" => {
+            let mut proxy = json!({
+                "name": name,
+                "type": "ss",
+                "server": server,
+                "port": port,
+                "cipher": params.get("method").ok_or("缺少 method")?,
+                "password": params.get("password").map(String::as_str).unwrap_or(""),
+            });
+            if let Some(mode @ ("http" | "tls")) = obfs {
+                proxy["plugin"] = json!("obfs");
+                proxy["plugin-opts"] = json!({
+                    "mode": mode,
+                    "host": params.get("obfs-host").map(String::as_str).unwrap_or(server),
+                });
+            }
+            proxy
+        }
+        "vmess" => json!({
+            "name": name,
+            "type": "vmess",
+            "server": server,
+            "port": port,
+            "uuid": params.get("password").ok_or("缺少 password")?,
+            "alterId": 0,
+            "cipher": params.get("method").map(String::as_str).unwrap_or("auto"),
+            "network": "tcp",
+        }),
+        "trojan" => json!({
+            "name": name,
+            "type": "trojan",
+            "server": server,
+            "port": port,
+            "password": params.get("password").ok_or("缺少 password")?,
+        }),
+        "http" | "socks5" => {
+            let mut proxy = json!({
+                "name": name,
+                "type": proxy_type,
+                "server": server,
+                "port": port,
+            });
+            for key in ["username", "password"] {
+                if let Some(value) = params.get(key).filter(|v| !v.eq_ignore_ascii_case("none")) {
+                    proxy[key] = json!(value);
+                }
+            }
+            proxy
+        }
+        other => return Err(format!("不支持的节点类型：{}", other)),
+    };
+
+    // over-tls=true 或 obfs=over-tls/wss 表示使用 TLS
+    let is_tls = is_true(&params, "over-tls") || matches!(obfs, Some("over-tls" | "wss"));
+    if is_tls && proxy_type != "shadowsocks" {
+        let sni_key = if proxy_type == "vmess" {
+            proxy["tls"] = json!(true);
+            "servername"
+        } else {
+            if proxy_type != "trojan" {
+                proxy["tls"] = json!(true);
+            }
+            "sni"
+        };
+        if let Some(sni) = params.get("tls-host").or(params.get("obfs-host")) {
+            proxy[sni_key] = json!(sni);
+        }
+    }
+    if params
+        .get("tls-verification")
+        .is_some_and(|v| v.eq_ignore_ascii_case("false"))
+    {
+        proxy["skip-cert-verify"] = json!(true);
+    }
+    if let Some(udp) = params.get("udp-relay") {
+        proxy["udp"] = json!(udp.eq_ignore_ascii_case("true"));
+    }
+    if matches!(obfs, Some("ws" | "wss")) {
+        let mut ws_opts = json!({
+            "path": params.get("obfs-uri").map(String::as_str).unwrap_or("/"),
+        });
+        if let Some(host) = params.get("obfs-host") {
+            ws_opts["headers"] = json!({"Host": host});
+        }
+        proxy["network"] = json!("ws");
+        proxy["ws-opts"] = ws_opts;
+    }
+
+    Ok(Some(proxy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SURGE_DOC: &str = r#"
+[General]
+loglevel = notify
+
+[Proxy]
+DIRECT = direct
+HK = ss, hk.example.com, 8388, encrypt-method=aes-128-gcm, password=secret, udp-relay=true
+JP = vmess, jp.example.com, 443, username=b831381d-6324-4d53-ad4f-8cda48b30811, ws=true, ws-path=/ray, ws-headers=Host:cdn.example.com, tls=true, sni=jp.example.com
+WG = wireguard, section-name=wg
+
+[Rule]
+FINAL,DIRECT
+"#;
+
+    #[test]
+    fn test_detect_surge_like_config() {
+        assert!(is_surge_like_config(SURGE_DOC));
+        assert!(is_surge_like_config("[server_local]\n"));
+        assert!(!is_surge_like_config("proxies: []"));
+    }
+
+    #[test]
+    fn test_convert_surge_proxies() {
+        let proxies = convert_proxies(SURGE_DOC);
+        assert_eq!(proxies.len(), 2);
+
+        let ss = &proxies[0];
+        assert_eq!(ss["name"], "HK");
+        assert_eq!(ss["type"], "ss");
+        assert_eq!(ss["server"], "hk.example.com");
+        assert_eq!(ss["port"], 8388);
+        assert_eq!(ss["cipher"], "aes-128-gcm");
+        assert_eq!(ss["password"], "secret");
+        assert_eq!(ss["udp"], true);
+
+        let vmess = &proxies[1];
+        assert_eq!(vmess["type"], "vmess");
+        assert_eq!(vmess["uuid"], "b831381d-6324-4d53-ad4f-8cda48b30811");
+        assert_eq!(vmess["tls"], true);
+        assert_eq!(vmess["servername"], "jp.example.com");
+        assert_eq!(vmess["network"], "ws");
+        assert_eq!(vmess["ws-opts"]["path"], "/ray");
+        assert_eq!(vmess["ws-opts"]["headers"]["Host"], "cdn.example.com");
+    }
+
+    #[test]
+    fn test_convert_quanx_proxies() {
+        let content = "[server_local]\n\
+            shadowsocks=1.2.3.4:8388, method=chacha20-ietf-poly1305, password=pwd, obfs=http, obfs-host=bing.com, tag=SS\n\
+            trojan=tr.example.com:443, password=pwd, over-tls=true, tls-host=tr.example.com, tag=Trojan\n";
+        let proxies = convert_proxies(content);
+        assert_eq!(proxies.len(), 2);
+        assert_eq!(proxies[0]["name"], "SS");
+        assert_eq!(proxies[0]["plugin-opts"]["host"], "bing.com");
+        assert_eq!(proxies[1]["type"], "trojan");
+        assert_eq!(proxies[1]["sni"], "tr.example.com");
+    }
+}
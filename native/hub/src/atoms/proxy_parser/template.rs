@@ -0,0 +1,266 @@
+// 订阅配置模板：用户在模板目录中保存命名的 Clash 配置（如精简、完整规则、游戏），
+// 解析订阅时将节点注入模板，代理组中的 <proxies> 占位符展开为节点名称。
+
+use serde_json::Value as JsonValue;
+use serde_yaml_ng::Value as YamlValue;
+use std::path::Path;
+
+// 内置模板名称（模板目录中的同名文件优先）
+pub const BUILTIN_TEMPLATE_NAME: &str = "default";
+
+// 代理组中的节点占位符
+pub const PROXIES_PLACEHOLDER: &str = "<proxies>";
+
+// 模板文件扩展名
+const TEMPLATE_EXTENSIONS: &[&str] = &["yaml", "yml"];
+
+// 自动测速类代理组：占位符展开为 AUTO 成员（遵循节点排除规则）
+const AUTO_GROUP_TYPES: &[&str] = &["url-test", "fallback", "load-balance"];
+
+const BUILTIN_TEMPLATE: &str = r#"proxy-groups:
+  - name: PROXY
+    type: select
+    proxies:
+      - AUTO
+      - <proxies>
+  - name: AUTO
+    type: url-test
+    proxies:
+      - <proxies>
+    url: https://www.gstatic.com/generate_204
+    interval: 300
+rules:
+  - GEOIP,LAN,DIRECT,no-resolve
+  - MATCH,PROXY
+"#;
+
+// 列出可用模板（内置模板在前，其余按名称排序）
+pub fn list_templates(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path
+                            .extension()
+                            .and_then(|ext| ext.to_str())
+                            .is_some_and(|ext| TEMPLATE_EXTENSIONS.contains(&ext))
+                })
+                .filter_map(|path| path.file_stem()?.to_str().map(str::to_string))
+                .filter(|name| name != BUILTIN_TEMPLATE_NAME)
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.dedup();
+    names.insert(0, BUILTIN_TEMPLATE_NAME.to_string());
+    names
+}
+
+// 按名称读取模板内容
+pub fn load_template(dir: &Path, name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("无效的模板名称：{}", name));
+    }
+
+    for ext in TEMPLATE_EXTENSIONS {
+        let path = dir.join(format!("{}.{}", name, ext));
+        if path.is_file() {
+            return std::fs::read_to_string(&path)
+                .map_err(|e| format!("读取模板失败：{}，{}", path.display(), e));
+        }
+    }
+
+    if name == BUILTIN_TEMPLATE_NAME {
+        return Ok(BUILTIN_TEMPLATE.to_string());
+    }
+    Err(format!("模板不存在：{}", name))
+}
+
+// 将节点注入模板：节点追加到模板自带的 proxies 之后，
+// 代理组中的占位符展开为全部节点（自动测速类组展开为 AUTO 成员）
+pub fn merge_into_template(
+    template: &str,
+    proxies: Vec<JsonValue>,
+    proxy_names: &[String],
+    auto_names: &[String],
+) -> Result<YamlValue, String> {
+    let mut config: YamlValue =
+        serde_yaml_ng::from_str(template).map_err(|e| format!("解析模板失败：{}", e))?;
+    config
+        .apply_merge()
+        .map_err(|e| format!("展开模板合并键失败：{}", e))?;
+    let root = config
+        .as_mapping_mut()
+        .ok_or_else(|| "模板顶层不是映射".to_string())?;
+
+    let mut has_placeholder = false;
+    if let Some(groups) = root
+        .get_mut("proxy-groups")
+        .and_then(YamlValue::as_sequence_mut)
+    {
+        for group in groups {
+            let is_auto = group["type"]
+                .as_str()
+                .is_some_and(|group_type| AUTO_GROUP_TYPES.contains(&group_type));
+            let Some(members) = group
+                .get_mut("proxies")
+                .and_then(YamlValue::as_sequence_mut)
+            else {
+                continue;
+            };
+            let Some(index) = members
+                .iter()
+                .position(|member| member.as_str() == Some(PROXIES_PLACEHOLDER))
+            else {
+                continue;
+            };
+
+            has_placeholder = true;
+            let names = if is_auto { auto_names } else { proxy_names };
+            members.splice(
+                index..=index,
+                names.iter().map(|name| YamlValue::from(name.as_str())),
+            );
+        }
+    }
+    if !has_placeholder {
+        return Err(format!(
+            "模板的代理组中没有节点占位符 {}",
+            PROXIES_PLACEHOLDER
+        ));
+    }
+
+    let proxies: Vec<YamlValue> = proxies
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("JSON 转 YAML 失败：{}", e))?;
+    match root.get_mut("proxies").and_then(YamlValue::as_sequence_mut) {
+        Some(existing) => existing.extend(proxies),
+        None => {
+            root.insert("proxies".into(), YamlValue::Sequence(proxies));
+        }
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn names(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    fn members(config: &YamlValue, group: usize) -> Vec<String> {
+        config["proxy-groups"][group]["proxies"]
+            .as_sequence()
+            .map(|members| {
+                members
+                    .iter()
+                    .filter_map(|m| m.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_load_named_template() {
+        let dir =
+            std::env::temp_dir().join(format!("stelliberty-templates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap_or_else(|e| panic!("创建目录失败：{}", e));
+        std::fs::write(dir.join("gaming.yaml"), "rules: []\n")
+            .unwrap_or_else(|e| panic!("写入失败：{}", e));
+        std::fs::write(dir.join("minimal.yml"), "rules: []\n")
+            .unwrap_or_else(|e| panic!("写入失败：{}", e));
+        std::fs::write(dir.join("notes.txt"), "").unwrap_or_else(|e| panic!("写入失败：{}", e));
+
+        assert_eq!(
+            list_templates(&dir),
+            names(&["default", "gaming", "minimal"])
+        );
+        assert_eq!(load_template(&dir, "gaming"), Ok("rules: []\n".to_string()));
+        assert_eq!(
+            load_template(&dir, "minimal"),
+            Ok("rules: []\n".to_string())
+        );
+        assert_eq!(
+            load_template(&dir, "default"),
+            Ok(BUILTIN_TEMPLATE.to_string())
+        );
+        assert!(load_template(&dir, "missing").is_err());
+        assert!(load_template(&dir, "../gaming").is_err());
+
+        // 目录不存在时仍可使用内置模板
+        let missing = dir.join("missing");
+        assert_eq!(list_templates(&missing), names(&["default"]));
+        assert!(load_template(&missing, "default").is_ok());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merge_nodes_into_template() {
+        let template = r#"
+proxies:
+  - {name: Home, type: socks5, server: 192.168.1.1, port: 1080}
+proxy-groups:
+  - name: Game
+    type: select
+    proxies: [DIRECT, <proxies>, Home]
+  - name: Fast
+    type: fallback
+    proxies: [<proxies>]
+  - name: Static
+    type: select
+    proxies: [DIRECT]
+rules:
+  - MATCH,Game
+"#;
+        let proxies = vec![
+            json!({"name": "HK 01", "type": "ss"}),
+            json!({"name": "Info", "type": "ss"}),
+        ];
+        let config = merge_into_template(
+            template,
+            proxies,
+            &names(&["HK 01", "Info"]),
+            &names(&["HK 01"]),
+        )
+        .unwrap_or_else(|e| panic!("合并失败：{}", e));
+
+        assert_eq!(
+            members(&config, 0),
+            names(&["DIRECT", "HK 01", "Info", "Home"])
+        );
+        assert_eq!(members(&config, 1), names(&["HK 01"]));
+        assert_eq!(members(&config, 2), names(&["DIRECT"]));
+
+        let merged: Vec<&str> = config["proxies"]
+            .as_sequence()
+            .map(|proxies| proxies.iter().filter_map(|p| p["name"].as_str()).collect())
+            .unwrap_or_default();
+        assert_eq!(merged, vec!["Home", "HK 01", "Info"]);
+        assert_eq!(config["rules"][0].as_str(), Some("MATCH,Game"));
+
+        // 内置模板
+        let config = merge_into_template(
+            BUILTIN_TEMPLATE,
+            vec![json!({"name": "HK 01", "type": "ss"})],
+            &names(&["HK 01"]),
+            &names(&["HK 01"]),
+        )
+        .unwrap_or_else(|e| panic!("合并失败：{}", e));
+        assert_eq!(members(&config, 0), names(&["AUTO", "HK 01"]));
+        assert_eq!(members(&config, 1), names(&["HK 01"]));
+
+        // 没有占位符的模板无法注入节点
+        assert!(merge_into_template("rules: []", Vec::new(), &[], &[]).is_err());
+    }
+}
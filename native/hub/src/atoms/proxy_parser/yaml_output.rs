@@ -0,0 +1,247 @@
+// 生成配置的 YAML 输出整理：固定键顺序，并为核心可能误读的字符串加引号。
+// 节点经 JSON 中转后键按字母排序，这里恢复为便于阅读的顺序。
+
+use serde_yaml_ng::{Mapping, Value as YamlValue};
+
+// 放在配置末尾的顶层键（按此顺序），其余顶层键保持原顺序在前
+const TRAILING_KEYS: &[&str] = &[
+    "proxy-providers",
+    "proxies",
+    "proxy-groups",
+    "rule-providers",
+    "rules",
+];
+
+// 节点与代理组中排在最前的键
+const LEADING_ITEM_KEYS: &[&str] = &["name", "type", "server", "port"];
+
+// YAML 1.1 中被解析为布尔值的单词（true/false 已由序列化器加引号）
+const YAML11_BOOL_WORDS: &[&str] = &[
+    "y", "Y", "yes", "Yes", "YES", "n", "N", "no", "No", "NO", "on", "On", "ON", "off", "Off",
+    "OFF",
+];
+
+// 整理配置的键顺序：常规设置在前，节点、代理组与规则在后，规则位于最后
+pub fn order_config(config: YamlValue) -> YamlValue {
+    let YamlValue::Mapping(mut root) = config else {
+        return config;
+    };
+
+    let mut trailing = Vec::new();
+    for key in TRAILING_KEYS {
+        if let Some(value) = root.shift_remove(*key) {
+            trailing.push((YamlValue::from(*key), value));
+        }
+    }
+    for (key, mut value) in trailing {
+        if matches!(key.as_str(), Some("proxies" | "proxy-groups"))
+            && let YamlValue::Sequence(items) = &mut value
+        {
+            for item in items.iter_mut() {
+                if let YamlValue::Mapping(map) = item {
+                    *map = order_item(std::mem::take(map));
+                }
+            }
+        }
+        root.insert(key, value);
+    }
+
+    YamlValue::Mapping(root)
+}
+
+// 节点/代理组：名称、类型、地址、端口在前，其余键保持原顺序
+fn order_item(mut item: Mapping) -> Mapping {
+    let mut ordered = Mapping::with_capacity(item.len());
+    for key in LEADING_ITEM_KEYS {
+        if let Some(value) = item.shift_remove(*key) {
+            ordered.insert(YamlValue::from(*key), value);
+        }
+    }
+    ordered.extend(item);
+    ordered
+}
+
+// 为序列化器未加引号、但核心（YAML 1.1 规则）会解析为非字符串的值加单引号，
+// 如 yes/on 与带下划线的数字、60 进制数字
+pub fn quote_ambiguous_scalars(yaml: &str) -> String {
+    let mut output = String::with_capacity(yaml.len());
+    // 块标量（| 或 >）所属键的列，缩进更深的行均为其内容，原样保留
+    let mut block_column: Option<usize> = None;
+    for line in yaml.lines() {
+        let indent = line.len() - line.trim_start().len();
+        if block_column.is_some_and(|column| line.trim().is_empty() || indent > column) {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        }
+        block_column = None;
+
+        match scalar_start(line) {
+            Some((start, column)) if line[start..].starts_with(['|', '>']) => {
+                block_column = Some(column);
+                output.push_str(line);
+            }
+            Some((start, _))
+                if !line[start..].starts_with(['\'', '"', '[', '{', '&', '*', '!', '#'])
+                    && is_yaml11_ambiguous(&line[start..]) =>
+            {
+                output.push_str(&line[..start]);
+                output.push('\'');
+                output.push_str(&line[start..].replace('\'', "''"));
+                output.push('\'');
+            }
+            _ => output.push_str(line),
+        }
+        output.push('\n');
+    }
+    output
+}
+
+// 定位行内的标量值（映射值或列表项），返回其起始位置与所属键（或列表项）的列
+fn scalar_start(line: &str) -> Option<(usize, usize)> {
+    let mut start = line.len() - line.trim_start().len();
+    let mut is_sequence_item = false;
+    while line[start..].starts_with("- ") {
+        start += 2;
+        is_sequence_item = true;
+    }
+
+    let rest = &line[start..];
+    let key_end = match rest.chars().next()? {
+        // 带引号的键（序列化器对含特殊字符的键加引号）
+        quote @ ('\'' | '"') => {
+            let close = rest[1..].find(quote)? + 1;
+            rest[close + 1..].starts_with(": ").then_some(close + 1)
+        }
+        _ => rest.find(": "),
+    };
+
+    match key_end {
+        Some(end) if start + end + 2 < line.len() => Some((start + end + 2, start)),
+        // 列表项本身即为标量
+        None if is_sequence_item && !rest.ends_with(':') => Some((start, start - 2)),
+        _ => None,
+    }
+}
+
+// 是否为 YAML 1.1 下会被解析为布尔值或数字的字符串
+fn is_yaml11_ambiguous(value: &str) -> bool {
+    if YAML11_BOOL_WORDS.contains(&value) {
+        return true;
+    }
+
+    let body = value.strip_prefix(['+', '-']).unwrap_or(value);
+    // 序列化器输出的数字不含下划线与冒号，含这些字符的只能是字符串
+    if body.contains('_') {
+        let digits = body.replace('_', "");
+        return digits.parse::<f64>().is_ok()
+            || [("0x", 16), ("0o", 8), ("0b", 2)]
+                .iter()
+                .any(|(prefix, radix)| {
+                    digits
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| i64::from_str_radix(rest, *radix).is_ok())
+                });
+    }
+    if body.contains(':') {
+        let mut parts = body.split(':');
+        let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        let head_is_number = parts.next().is_some_and(is_digits);
+        return head_is_number
+            && parts.all(|part| {
+                let whole = part.split_once('.').map_or(part, |(whole, _)| whole);
+                whole.len() <= 2 && is_digits(whole)
+            });
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_ambiguous_scalars() {
+        let yaml = "\
+proxies:
+- name: A
+  password: yes
+  port: 443
+  short-id: 6ba85179
+  uuid: 1_000
+  obfs-password: '123456'
+- 12:30
+- name: 'a: b'
+  sni: on
+  script: |-
+    enabled: yes
+    - on
+  udp: true
+rules:
+- MATCH,PROXY
+";
+        assert_eq!(
+            quote_ambiguous_scalars(yaml),
+            "\
+proxies:
+- name: A
+  password: 'yes'
+  port: 443
+  short-id: 6ba85179
+  uuid: '1_000'
+  obfs-password: '123456'
+- '12:30'
+- name: 'a: b'
+  sni: 'on'
+  script: |-
+    enabled: yes
+    - on
+  udp: true
+rules:
+- MATCH,PROXY
+"
+        );
+
+        assert!(is_yaml11_ambiguous("0x_ff"));
+        assert!(is_yaml11_ambiguous("-1:20:30.5"));
+        assert!(!is_yaml11_ambiguous("1:2:3:abc"));
+        assert!(!is_yaml11_ambiguous("example.com"));
+        assert!(!is_yaml11_ambiguous("my_password"));
+    }
+
+    #[test]
+    fn test_order_config() {
+        let config: YamlValue = serde_yaml_ng::from_str(
+            "rules: [MATCH,PROXY]\n\
+             proxies: [{cipher: aes-128-gcm, name: A, port: 8388, server: a.example.com, type: ss}]\n\
+             mode: rule\n\
+             proxy-groups: [{name: PROXY, proxies: [A], type: select}]\n\
+             dns: {enable: true}\n",
+        )
+        .unwrap_or_else(|e| panic!("解析失败：{}", e));
+        let ordered = order_config(config);
+
+        let keys = |value: &YamlValue| -> Vec<String> {
+            value
+                .as_mapping()
+                .map(|map| {
+                    map.keys()
+                        .filter_map(|key| key.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        assert_eq!(
+            keys(&ordered),
+            vec!["mode", "dns", "proxies", "proxy-groups", "rules"]
+        );
+        assert_eq!(
+            keys(&ordered["proxies"][0]),
+            vec!["name", "type", "server", "port", "cipher"]
+        );
+        assert_eq!(
+            keys(&ordered["proxy-groups"][0]),
+            vec!["name", "type", "proxies"]
+        );
+    }
+}
@@ -1,8 +1,21 @@
 // 系统代理原子模块
 
+#[cfg(any(target_os = "windows", target_os = "linux", test))]
+mod bypass;
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+mod command_runner;
+#[cfg(any(target_os = "linux", test))]
+mod env_file;
+#[cfg(target_os = "macos")]
+mod macos_prefs;
 pub mod manager;
 
 // 导出公共接口
-pub use manager::{disable_proxy, enable_proxy, get_proxy_info};
+pub use manager::{
+    ProxyBypass, ProxyResult, detect_proxy_conflict, disable_proxy, enable_proxy, get_proxy_info,
+};
+
+#[cfg(target_os = "macos")]
+pub use manager::get_network_devices;
 
 pub use manager::init;
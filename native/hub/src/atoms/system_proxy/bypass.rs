@@ -0,0 +1,192 @@
+// 代理绕过列表：校验用户填写的绕过项，并按各平台的语法输出。
+// IPv6 地址在不同平台写法不同：WinINet 需要方括号包裹字面量且不支持网段，
+// gsettings ignore-hosts 与 no_proxy 则接受裸地址与 CIDR 网段。
+
+use std::net::Ipv6Addr;
+
+// 单个绕过项
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BypassEntry {
+    // 域名、IPv4 地址/网段或通配符，原样传给系统
+    Host(String),
+    // IPv6 地址字面量
+    Ipv6(Ipv6Addr),
+    // IPv6 网段（地址 + 前缀长度）
+    Ipv6Cidr(Ipv6Addr, u8),
+}
+
+// 解析单个绕过项，空白项返回 None
+fn parse_entry(raw: &str) -> Result<Option<BypassEntry>, String> {
+    let entry = raw.trim();
+    if entry.is_empty() {
+        return Ok(None);
+    }
+
+    // [::1] 形式的带方括号字面量
+    if let Some(inner) = entry.strip_prefix('[') {
+        let address = inner
+            .strip_suffix(']')
+            .and_then(|inner| inner.parse::<Ipv6Addr>().ok())
+            .ok_or_else(|| format!("无效的 IPv6 地址：{}", entry))?;
+        return Ok(Some(BypassEntry::Ipv6(address)));
+    }
+
+    // 含两个以上冒号视为 IPv6（host:port 只有一个冒号），通配符交由系统处理
+    if entry.matches(':').count() < 2 || entry.contains('*') {
+        return Ok(Some(BypassEntry::Host(entry.to_string())));
+    }
+
+    match entry.split_once('/') {
+        Some((address, prefix)) => {
+            let address = address
+                .parse::<Ipv6Addr>()
+                .map_err(|_| format!("无效的 IPv6 网段：{}", entry))?;
+            let prefix = prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= 128)
+                .ok_or_else(|| format!("无效的 IPv6 前缀长度：{}", entry))?;
+            Ok(Some(BypassEntry::Ipv6Cidr(address, prefix)))
+        }
+        None => entry
+            .parse::<Ipv6Addr>()
+            .map(|address| Some(BypassEntry::Ipv6(address)))
+            .map_err(|_| format!("无效的 IPv6 地址：{}", entry)),
+    }
+}
+
+// 解析全部绕过项，无效项记录日志后跳过
+fn parse_entries(domains: &[String]) -> Vec<BypassEntry> {
+    domains
+        .iter()
+        .filter_map(|raw| match parse_entry(raw) {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("已忽略绕过项：{}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// WinINet 绕过列表（分号分隔）：IPv6 字面量写作 [addr]，
+// 网段仅 /128 可转为字面量，其余无法表达，记录日志后跳过
+#[cfg(any(target_os = "windows", test))]
+pub fn wininet_bypass_list(domains: &[String], exclude_simple_hostnames: bool) -> String {
+    let mut entries: Vec<String> = parse_entries(domains)
+        .into_iter()
+        .filter_map(|entry| match entry {
+            BypassEntry::Host(host) => Some(host),
+            BypassEntry::Ipv6(address) | BypassEntry::Ipv6Cidr(address, 128) => {
+                Some(format!("[{}]", address))
+            }
+            BypassEntry::Ipv6Cidr(address, prefix) => {
+                log::warn!(
+                    "WinINet 不支持 IPv6 网段，已忽略绕过项：{}/{}",
+                    address,
+                    prefix
+                );
+                None
+            }
+        })
+        .collect();
+
+    // <local> 即「对本地地址不使用代理」，跳过不含点的主机名
+    if exclude_simple_hostnames && !entries.iter().any(|entry| entry == "<local>") {
+        entries.push("<local>".to_string());
+    }
+    entries.join(";")
+}
+
+// gsettings、KDE 与 no_proxy 使用的绕过项：IPv6 去掉方括号，网段保留 CIDR 写法
+#[cfg(any(target_os = "linux", test))]
+pub fn unix_bypass_entries(domains: &[String]) -> Vec<String> {
+    parse_entries(domains)
+        .into_iter()
+        .map(|entry| match entry {
+            BypassEntry::Host(host) => host,
+            BypassEntry::Ipv6(address) => address.to_string(),
+            BypassEntry::Ipv6Cidr(address, prefix) => format!("{}/{}", address, prefix),
+        })
+        .collect()
+}
+
+// GNOME ignore-hosts 的 GVariant 字符串数组
+#[cfg(any(target_os = "linux", test))]
+pub fn gsettings_ignore_hosts(domains: &[String]) -> String {
+    let quoted: Vec<String> = unix_bypass_entries(domains)
+        .iter()
+        .map(|entry| format!("'{}'", entry))
+        .collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_wininet_ipv6_entries() {
+        let domains = entries(&[
+            "localhost",
+            "192.168.*",
+            "::1",
+            "[fe80::1]",
+            "0:0:0:0:0:0:0:1/128",
+            "fe80::/10",
+            "fe80::zz",
+            "  ",
+        ]);
+        assert_eq!(
+            wininet_bypass_list(&domains, true),
+            "localhost;192.168.*;[::1];[fe80::1];[::1];<local>"
+        );
+
+        // 已包含 <local> 时不重复添加
+        assert_eq!(
+            wininet_bypass_list(&entries(&["<local>", "::1"]), true),
+            "<local>;[::1]"
+        );
+        assert_eq!(wininet_bypass_list(&[], false), "");
+    }
+
+    #[test]
+    fn test_gsettings_ipv6_entries() {
+        let domains = entries(&[
+            "localhost",
+            "127.0.0.0/8",
+            "[::1]",
+            "fe80::/10",
+            "FD00:0:0::/8",
+            "fe80::/129",
+            "[::1",
+        ]);
+        assert_eq!(
+            unix_bypass_entries(&domains),
+            entries(&["localhost", "127.0.0.0/8", "::1", "fe80::/10", "fd00::/8"])
+        );
+        assert_eq!(
+            gsettings_ignore_hosts(&domains),
+            "['localhost', '127.0.0.0/8', '::1', 'fe80::/10', 'fd00::/8']"
+        );
+        assert_eq!(gsettings_ignore_hosts(&[]), "[]");
+    }
+
+    #[test]
+    fn test_parse_entry_keeps_host_port() {
+        assert_eq!(
+            parse_entry("example.com:8080"),
+            Ok(Some(BypassEntry::Host("example.com:8080".to_string())))
+        );
+        assert_eq!(
+            parse_entry("fe80::*"),
+            Ok(Some(BypassEntry::Host("fe80::*".to_string())))
+        );
+        assert_eq!(parse_entry(""), Ok(None));
+        assert!(parse_entry("1::2::3").is_err());
+    }
+}
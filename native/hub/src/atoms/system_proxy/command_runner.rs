@@ -0,0 +1,222 @@
+// 系统代理命令执行器：逐条执行 networksetup/gsettings/kwriteconfig5 命令并汇总结果。
+// 对已知的瞬时失败（接口切换、配置库繁忙等）自动重试。
+
+use std::process::Command;
+use std::time::Duration;
+
+use super::manager::ProxyResult;
+
+// 瞬时失败的最大重试次数
+const MAX_RETRIES: usize = 2;
+
+// 重试前的等待时间
+const RETRY_DELAY: Duration = Duration::from_millis(300);
+
+// 已知的瞬时失败特征（小写匹配）
+const TRANSIENT_PATTERNS: &[&str] = &[
+    // macOS：VPN 切换接口后网络服务短暂处于禁用状态
+    "network service is disabled",
+    // macOS：SystemConfiguration 偏好设置被其他进程锁定
+    "unable to commit changes",
+    "resource busy",
+    "resource temporarily unavailable",
+    // GNOME：dconf/D-Bus 短暂不可用
+    "timeout was reached",
+    "failed to connect",
+    "could not connect",
+];
+
+// 判断错误信息是否属于可重试的瞬时失败
+pub fn is_transient_failure(message: &str) -> bool {
+    let message = message.to_lowercase();
+    TRANSIENT_PATTERNS
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+// 执行一次命令，失败时返回包含输出的错误描述
+fn execute_once(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("执行 {} 失败：{}", program, e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // networksetup 出错时可能返回 0 但在 stdout 输出 "** Error"
+    if output.status.success() && !stdout.trim_start().starts_with("** Error") {
+        return Ok(());
+    }
+
+    let detail = [stderr.trim(), stdout.trim()]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" | ");
+    Err(format!(
+        "{} 执行失败（{}）：{}",
+        program,
+        output.status.code().map_or_else(
+            || "被信号终止".to_string(),
+            |code| format!("退出码 {}", code)
+        ),
+        detail
+    ))
+}
+
+// 执行操作，遇到瞬时失败时按固定间隔重试
+async fn retry_transient<F>(description: &str, mut attempt: F) -> Result<(), String>
+where
+    F: FnMut() -> Result<(), String>,
+{
+    let mut retries = 0;
+    loop {
+        match attempt() {
+            Ok(()) => {
+                if retries > 0 {
+                    log::info!("{} 在第 {} 次重试后成功", description, retries);
+                }
+                return Ok(());
+            }
+            Err(e) if retries < MAX_RETRIES && is_transient_failure(&e) => {
+                retries += 1;
+                log::warn!(
+                    "{} 遇到瞬时失败，{}ms 后重试（{}/{}）：{}",
+                    description,
+                    RETRY_DELAY.as_millis(),
+                    retries,
+                    MAX_RETRIES,
+                    e
+                );
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// 执行单条命令（带瞬时失败重试）
+pub async fn run_command(program: &str, args: &[&str]) -> Result<(), String> {
+    let description = format!("{} {}", program, args.join(" "));
+    retry_transient(&description, || execute_once(program, args)).await
+}
+
+// 一组命令的执行结果
+#[derive(Debug, Default)]
+pub struct CommandBatch {
+    succeeded: usize,
+    failures: Vec<String>,
+}
+
+impl CommandBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 执行命令并记录结果，失败不会中断后续命令
+    pub async fn run(&mut self, program: &str, args: &[&str]) {
+        match run_command(program, args).await {
+            Ok(()) => self.succeeded += 1,
+            Err(e) => {
+                log::warn!("{}", e);
+                self.failures.push(e);
+            }
+        }
+    }
+
+    // 记录不经命令行完成的操作结果
+    #[cfg(target_os = "macos")]
+    pub fn record(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.succeeded += 1,
+            Err(e) => {
+                log::warn!("{}", e);
+                self.failures.push(e);
+            }
+        }
+    }
+
+    // 汇总结果：全部失败时返回错误，部分失败时仅记录警告
+    pub fn into_result(self, operation: &str) -> ProxyResult {
+        if self.failures.is_empty() {
+            return ProxyResult::Success;
+        }
+
+        if self.succeeded == 0 {
+            return ProxyResult::Error(format!("{}失败：{}", operation, self.failures.join("；")));
+        }
+
+        log::warn!(
+            "{}部分完成：{} 条命令成功，{} 条失败",
+            operation,
+            self.succeeded,
+            self.failures.len()
+        );
+        ProxyResult::Success
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_predicate() {
+        assert!(is_transient_failure(
+            "** Error: The Network Service is disabled."
+        ));
+        assert!(is_transient_failure(
+            "Error: Timeout was reached while contacting dconf"
+        ));
+        assert!(!is_transient_failure(
+            "** Error: The parameters were not valid."
+        ));
+        assert!(!is_transient_failure(
+            "No such schema “org.gnome.system.proxy”"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_succeeds_on_second_attempt() {
+        let mut attempts = 0;
+        let result = retry_transient("test", || {
+            attempts += 1;
+            if attempts == 1 {
+                Err("** Error: The network service is disabled.".to_string())
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_transient_failure_is_not_retried() {
+        let mut attempts = 0;
+        let result = retry_transient("test", || {
+            attempts += 1;
+            Err("** Error: The parameters were not valid.".to_string())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let mut attempts = 0;
+        let result = retry_transient("test", || {
+            attempts += 1;
+            Err("resource busy".to_string())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, MAX_RETRIES + 1);
+    }
+}
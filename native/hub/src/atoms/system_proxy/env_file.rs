@@ -0,0 +1,160 @@
+// 代理环境变量文件：无桌面环境（服务器/无头 Linux）时，
+// 通过 http_proxy 等环境变量为之后登录的会话设置代理。
+
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+// 文件首行标记，避免误删或误读非本程序生成的文件
+const FILE_MARKER: &str = "# Generated by Stelliberty, do not edit";
+
+// root 时写入全局 profile，普通用户写入 systemd 用户环境
+#[cfg(target_os = "linux")]
+const SYSTEM_PROFILE_PATH: &str = "/etc/profile.d/stelliberty-proxy.sh";
+#[cfg(target_os = "linux")]
+const USER_ENVIRONMENT_FILE: &str = ".config/environment.d/stelliberty-proxy.conf";
+
+// 文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvFileFormat {
+    // shell 脚本（export KEY="value"）
+    Shell,
+    // environment.d（KEY=value）
+    EnvironmentD,
+}
+
+// 从文件中读回的代理设置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvProxy {
+    // host:port
+    pub server: String,
+    pub bypass_domains: Vec<String>,
+}
+
+// 当前用户对应的文件路径与格式
+#[cfg(target_os = "linux")]
+pub fn target_path() -> Option<(PathBuf, EnvFileFormat)> {
+    if nix::unistd::geteuid().is_root() {
+        return Some((PathBuf::from(SYSTEM_PROFILE_PATH), EnvFileFormat::Shell));
+    }
+    let home = std::env::var_os("HOME")?;
+    Some((
+        PathBuf::from(home).join(USER_ENVIRONMENT_FILE),
+        EnvFileFormat::EnvironmentD,
+    ))
+}
+
+// 生成环境变量文件内容
+pub fn render(host: &str, port: u16, bypass_domains: &[String], format: EnvFileFormat) -> String {
+    let http = format!("http://{}:{}", host, port);
+    let socks = format!("socks5://{}:{}", host, port);
+    let no_proxy = bypass_domains.join(",");
+
+    let mut content = format!("{}\n", FILE_MARKER);
+    for (key, value) in [
+        ("http_proxy", http.as_str()),
+        ("https_proxy", http.as_str()),
+        ("all_proxy", socks.as_str()),
+        ("no_proxy", no_proxy.as_str()),
+    ] {
+        if value.is_empty() {
+            continue;
+        }
+        match format {
+            EnvFileFormat::Shell => content.push_str(&format!("export {}=\"{}\"\n", key, value)),
+            EnvFileFormat::EnvironmentD => content.push_str(&format!("{}={}\n", key, value)),
+        }
+    }
+    content
+}
+
+// 解析环境变量文件（两种格式均可），非本程序生成或缺少 http_proxy 时返回 None
+pub fn parse(content: &str) -> Option<EnvProxy> {
+    let mut lines = content.lines();
+    if lines.next()?.trim() != FILE_MARKER {
+        return None;
+    }
+
+    let mut server = None;
+    let mut bypass_domains = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        let assignment = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = assignment.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"');
+        match key.trim() {
+            "http_proxy" => {
+                server = Some(
+                    value
+                        .strip_prefix("http://")
+                        .unwrap_or(value)
+                        .trim_end_matches('/')
+                        .to_string(),
+                );
+            }
+            "no_proxy" => {
+                bypass_domains = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|domain| !domain.is_empty())
+                    .map(String::from)
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    Some(EnvProxy {
+        server: server.filter(|server| !server.is_empty())?,
+        bypass_domains,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bypass() -> Vec<String> {
+        vec!["localhost".to_string(), "127.0.0.1".to_string()]
+    }
+
+    #[test]
+    fn test_render_formats() {
+        assert_eq!(
+            render("127.0.0.1", 7890, &bypass(), EnvFileFormat::Shell),
+            format!(
+                "{}\nexport http_proxy=\"http://127.0.0.1:7890\"\nexport https_proxy=\"http://127.0.0.1:7890\"\nexport all_proxy=\"socks5://127.0.0.1:7890\"\nexport no_proxy=\"localhost,127.0.0.1\"\n",
+                FILE_MARKER
+            )
+        );
+
+        let content = render("127.0.0.1", 7890, &[], EnvFileFormat::EnvironmentD);
+        assert!(content.contains("\nhttp_proxy=http://127.0.0.1:7890\n"));
+        assert!(!content.contains("no_proxy"));
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        for format in [EnvFileFormat::Shell, EnvFileFormat::EnvironmentD] {
+            let content = render("10.0.0.2", 7890, &bypass(), format);
+            assert_eq!(
+                parse(&content),
+                Some(EnvProxy {
+                    server: "10.0.0.2:7890".to_string(),
+                    bypass_domains: bypass(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_foreign_files() {
+        assert_eq!(parse("export http_proxy=\"http://127.0.0.1:8080\"\n"), None);
+        assert_eq!(
+            parse(&format!("{}\nno_proxy=localhost\n", FILE_MARKER)),
+            None
+        );
+        assert_eq!(parse(""), None);
+    }
+}
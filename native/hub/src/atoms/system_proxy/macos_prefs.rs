@@ -0,0 +1,298 @@
+// macOS 网络偏好设置：networksetup 没有「排除简单主机名」选项，
+// 通过 SystemConfiguration 直接修改网络服务代理配置中的 ExcludeSimpleHostnames。
+// 修改网络配置需要授权，普通用户进程须通过 Authorization Services 获取授权后打开偏好设置。
+
+use std::ffi::{CStr, CString, c_char, c_void};
+
+type CFTypeRef = *const c_void;
+type CFStringRef = *const c_void;
+type CFArrayRef = *const c_void;
+type CFDictionaryRef = *const c_void;
+type CFMutableDictionaryRef = *mut c_void;
+type CFNumberRef = *const c_void;
+type CFIndex = isize;
+type Boolean = u8;
+type AuthorizationRef = *const c_void;
+type OSStatus = i32;
+
+const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const CF_NUMBER_INT_TYPE: CFIndex = 9;
+
+// kAuthorizationFlagInteractionAllowed | kAuthorizationFlagExtendRights | kAuthorizationFlagPreAuthorize
+const AUTHORIZATION_FLAGS: u32 = (1 << 0) | (1 << 1) | (1 << 4);
+const AUTHORIZATION_FLAG_DEFAULTS: u32 = 0;
+const ERR_AUTHORIZATION_SUCCESS: OSStatus = 0;
+
+#[link(name = "Security", kind = "framework")]
+unsafe extern "C" {
+    fn AuthorizationCreate(
+        rights: *const c_void,
+        environment: *const c_void,
+        flags: u32,
+        authorization: *mut AuthorizationRef,
+    ) -> OSStatus;
+    fn AuthorizationFree(authorization: AuthorizationRef, flags: u32) -> OSStatus;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+unsafe extern "C" {
+    fn CFRelease(cf: CFTypeRef);
+    fn CFStringCreateWithCString(
+        alloc: CFTypeRef,
+        c_str: *const c_char,
+        encoding: u32,
+    ) -> CFStringRef;
+    fn CFStringGetCString(
+        string: CFStringRef,
+        buffer: *mut c_char,
+        buffer_size: CFIndex,
+        encoding: u32,
+    ) -> Boolean;
+    fn CFArrayGetCount(array: CFArrayRef) -> CFIndex;
+    fn CFArrayGetValueAtIndex(array: CFArrayRef, index: CFIndex) -> CFTypeRef;
+    fn CFDictionaryCreateMutable(
+        alloc: CFTypeRef,
+        capacity: CFIndex,
+        key_callbacks: *const c_void,
+        value_callbacks: *const c_void,
+    ) -> CFMutableDictionaryRef;
+    fn CFDictionaryCreateMutableCopy(
+        alloc: CFTypeRef,
+        capacity: CFIndex,
+        dict: CFDictionaryRef,
+    ) -> CFMutableDictionaryRef;
+    fn CFDictionarySetValue(dict: CFMutableDictionaryRef, key: CFTypeRef, value: CFTypeRef);
+    fn CFNumberCreate(alloc: CFTypeRef, number_type: CFIndex, value: *const c_void) -> CFNumberRef;
+
+    static kCFTypeDictionaryKeyCallBacks: c_void;
+    static kCFTypeDictionaryValueCallBacks: c_void;
+}
+
+#[link(name = "SystemConfiguration", kind = "framework")]
+unsafe extern "C" {
+    fn SCPreferencesCreateWithAuthorization(
+        alloc: CFTypeRef,
+        name: CFStringRef,
+        prefs_id: CFStringRef,
+        authorization: AuthorizationRef,
+    ) -> CFTypeRef;
+    fn SCPreferencesLock(prefs: CFTypeRef, wait: Boolean) -> Boolean;
+    fn SCPreferencesUnlock(prefs: CFTypeRef) -> Boolean;
+    fn SCPreferencesCommitChanges(prefs: CFTypeRef) -> Boolean;
+    fn SCPreferencesApplyChanges(prefs: CFTypeRef) -> Boolean;
+    fn SCNetworkSetCopyCurrent(prefs: CFTypeRef) -> CFTypeRef;
+    fn SCNetworkSetCopyServices(set: CFTypeRef) -> CFArrayRef;
+    fn SCNetworkServiceGetName(service: CFTypeRef) -> CFStringRef;
+    fn SCNetworkServiceCopyProtocol(service: CFTypeRef, protocol_type: CFStringRef) -> CFTypeRef;
+    fn SCNetworkProtocolGetConfiguration(protocol: CFTypeRef) -> CFDictionaryRef;
+    fn SCNetworkProtocolSetConfiguration(protocol: CFTypeRef, config: CFDictionaryRef) -> Boolean;
+    fn SCError() -> i32;
+    fn SCErrorString(status: i32) -> *const c_char;
+
+    static kSCNetworkProtocolTypeProxies: CFStringRef;
+    static kSCPropNetProxiesExcludeSimpleHostnames: CFStringRef;
+}
+
+// 持有 CoreFoundation 对象，离开作用域时释放
+struct Owned(CFTypeRef);
+
+impl Owned {
+    fn new(object: CFTypeRef, what: &str) -> Result<Self, String> {
+        if object.is_null() {
+            Err(format!("{}失败：{}", what, last_error()))
+        } else {
+            Ok(Self(object))
+        }
+    }
+}
+
+impl Drop for Owned {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.0) };
+    }
+}
+
+// 持有授权引用，离开作用域时释放
+struct Authorization(AuthorizationRef);
+
+impl Authorization {
+    fn create() -> Result<Self, String> {
+        let mut authorization: AuthorizationRef = std::ptr::null();
+        let status = unsafe {
+            AuthorizationCreate(
+                std::ptr::null(),
+                std::ptr::null(),
+                AUTHORIZATION_FLAGS,
+                &mut authorization,
+            )
+        };
+        if status != ERR_AUTHORIZATION_SUCCESS || authorization.is_null() {
+            return Err(format!("获取网络设置授权失败（OSStatus {}）", status));
+        }
+        Ok(Self(authorization))
+    }
+}
+
+impl Drop for Authorization {
+    fn drop(&mut self) {
+        unsafe { AuthorizationFree(self.0, AUTHORIZATION_FLAG_DEFAULTS) };
+    }
+}
+
+fn last_error() -> String {
+    unsafe {
+        let message = SCErrorString(SCError());
+        if message.is_null() {
+            "未知错误".to_string()
+        } else {
+            CStr::from_ptr(message).to_string_lossy().into_owned()
+        }
+    }
+}
+
+fn cf_string(value: &str) -> Result<Owned, String> {
+    let c_value = CString::new(value).map_err(|e| format!("字符串无效：{}", e))?;
+    let string = unsafe {
+        CFStringCreateWithCString(std::ptr::null(), c_value.as_ptr(), CF_STRING_ENCODING_UTF8)
+    };
+    Owned::new(string, "创建字符串")
+}
+
+fn rust_string(string: CFStringRef) -> Option<String> {
+    if string.is_null() {
+        return None;
+    }
+    let mut buffer = [0 as c_char; 256];
+    let ok = unsafe {
+        CFStringGetCString(
+            string,
+            buffer.as_mut_ptr(),
+            buffer.len() as CFIndex,
+            CF_STRING_ENCODING_UTF8,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr(buffer.as_ptr()) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+// 设置指定网络服务的「排除简单主机名」选项并立即生效
+pub fn set_exclude_simple_hostnames(devices: &[String], enabled: bool) -> Result<(), String> {
+    let name = cf_string("Stelliberty")?;
+    // 未授权打开的偏好设置只读，提交修改时会因权限不足失败
+    let authorization = Authorization::create()?;
+    let prefs = Owned::new(
+        unsafe {
+            SCPreferencesCreateWithAuthorization(
+                std::ptr::null(),
+                name.0,
+                std::ptr::null(),
+                authorization.0,
+            )
+        },
+        "打开网络偏好设置",
+    )?;
+
+    if unsafe { SCPreferencesLock(prefs.0, 1) } == 0 {
+        return Err(format!("锁定网络偏好设置失败：{}", last_error()));
+    }
+    let result = update_services(&prefs, devices, enabled);
+    unsafe { SCPreferencesUnlock(prefs.0) };
+    result
+}
+
+fn update_services(prefs: &Owned, devices: &[String], enabled: bool) -> Result<(), String> {
+    let set = Owned::new(
+        unsafe { SCNetworkSetCopyCurrent(prefs.0) },
+        "读取当前网络位置",
+    )?;
+    let services = Owned::new(
+        unsafe { SCNetworkSetCopyServices(set.0) },
+        "读取网络服务列表",
+    )?;
+
+    let flag: i32 = i32::from(enabled);
+    let value = Owned::new(
+        unsafe {
+            CFNumberCreate(
+                std::ptr::null(),
+                CF_NUMBER_INT_TYPE,
+                &flag as *const i32 as *const c_void,
+            )
+        },
+        "创建数值",
+    )?;
+
+    let mut updated = 0;
+    for index in 0..unsafe { CFArrayGetCount(services.0) } {
+        let service = unsafe { CFArrayGetValueAtIndex(services.0, index) };
+        let Some(service_name) = rust_string(unsafe { SCNetworkServiceGetName(service) }) else {
+            continue;
+        };
+        if !devices.contains(&service_name) {
+            continue;
+        }
+
+        let Ok(protocol) = Owned::new(
+            unsafe { SCNetworkServiceCopyProtocol(service, kSCNetworkProtocolTypeProxies) },
+            "读取代理配置",
+        ) else {
+            log::warn!("网络服务没有代理配置，已跳过：{}", service_name);
+            continue;
+        };
+
+        let current = unsafe { SCNetworkProtocolGetConfiguration(protocol.0) };
+        let config = Owned::new(
+            unsafe {
+                if current.is_null() {
+                    CFDictionaryCreateMutable(
+                        std::ptr::null(),
+                        0,
+                        &kCFTypeDictionaryKeyCallBacks,
+                        &kCFTypeDictionaryValueCallBacks,
+                    )
+                } else {
+                    CFDictionaryCreateMutableCopy(std::ptr::null(), 0, current)
+                }
+            },
+            "复制代理配置",
+        )?;
+        unsafe {
+            CFDictionarySetValue(
+                config.0 as CFMutableDictionaryRef,
+                kSCPropNetProxiesExcludeSimpleHostnames,
+                value.0,
+            );
+        }
+        if unsafe { SCNetworkProtocolSetConfiguration(protocol.0, config.0) } == 0 {
+            return Err(format!(
+                "写入 {} 代理配置失败：{}",
+                service_name,
+                last_error()
+            ));
+        }
+        updated += 1;
+    }
+
+    if updated == 0 {
+        return Ok(());
+    }
+    if unsafe { SCPreferencesCommitChanges(prefs.0) } == 0 {
+        return Err(format!("保存网络偏好设置失败：{}", last_error()));
+    }
+    if unsafe { SCPreferencesApplyChanges(prefs.0) } == 0 {
+        return Err(format!("应用网络偏好设置失败：{}", last_error()));
+    }
+
+    log::info!(
+        "已{}排除简单主机名（{} 个网络服务）",
+        if enabled { "启用" } else { "关闭" },
+        updated
+    );
+    Ok(())
+}
@@ -14,16 +14,34 @@ pub struct EnableSystemProxy {
     pub should_use_pac_mode: bool,
     pub pac_script: String,
     pub pac_file_path: String,
+    // 仅对指定的网络设备生效（目前仅 macOS 支持），为空时应用到所有设备
+    #[serde(default)]
+    pub target_devices: Vec<String>,
+    // 不经代理访问不含点的主机名（macOS「排除简单主机名」，Windows 对应 <local>）
+    #[serde(default)]
+    pub exclude_simple_hostnames: bool,
 }
 
 // Dart → Rust：禁用系统代理
 #[derive(Deserialize, DartSignal)]
-pub struct DisableSystemProxy;
+pub struct DisableSystemProxy {
+    // 仅对指定的网络设备生效（目前仅 macOS 支持），为空时应用到所有设备
+    #[serde(default)]
+    pub target_devices: Vec<String>,
+}
 
 // Dart → Rust：获取系统代理状态
 #[derive(Deserialize, DartSignal)]
 pub struct GetSystemProxy;
 
+// Dart → Rust：检测系统代理是否被其他代理软件占用
+#[derive(Deserialize, DartSignal)]
+pub struct DetectSystemProxyConflict {
+    // 本应用的代理地址
+    pub host: String,
+    pub port: u16,
+}
+
 // Rust → Dart：代理操作结果
 #[derive(Serialize, RustSignal)]
 pub struct SystemProxyResult {
@@ -36,6 +54,24 @@ pub struct SystemProxyResult {
 pub struct SystemProxyInfo {
     pub is_enabled: bool,
     pub server: Option<String>,
+    // 绕过列表与「排除简单主机名」（目前仅 macOS 回报）
+    pub bypass_domains: Vec<String>,
+    pub exclude_simple_hostnames: bool,
+}
+
+// Rust → Dart：系统代理占用检测结果
+#[derive(Serialize, RustSignal)]
+pub struct SystemProxyConflict {
+    pub has_conflict: bool,
+    // 当前系统代理地址（仅在存在冲突时返回）
+    pub current_server: Option<String>,
+}
+
+// 代理绕过设置
+#[derive(Debug, Clone, Default)]
+pub struct ProxyBypass {
+    pub domains: Vec<String>,
+    pub exclude_simple_hostnames: bool,
 }
 
 // 代理操作结果
@@ -46,10 +82,12 @@ pub enum ProxyResult {
 }
 
 // 系统代理配置信息
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ProxyInfo {
     pub is_enabled: bool,
     pub server: Option<String>,
+    pub bypass_domains: Vec<String>,
+    pub exclude_simple_hostnames: bool,
 }
 
 impl EnableSystemProxy {
@@ -64,10 +102,14 @@ impl EnableSystemProxy {
         let result = enable_proxy(
             &self.host,
             self.port,
-            self.bypass_domains,
+            ProxyBypass {
+                domains: self.bypass_domains,
+                exclude_simple_hostnames: self.exclude_simple_hostnames,
+            },
             self.should_use_pac_mode,
             &self.pac_script,
             &self.pac_file_path,
+            &self.target_devices,
         )
         .await;
 
@@ -94,7 +136,7 @@ impl DisableSystemProxy {
     pub async fn handle(&self) {
         log::info!("收到禁用代理请求");
 
-        let result = disable_proxy().await;
+        let result = disable_proxy(&self.target_devices).await;
 
         let response = match result {
             ProxyResult::Success => SystemProxyResult {
@@ -124,19 +166,92 @@ impl GetSystemProxy {
         let response = SystemProxyInfo {
             is_enabled: proxy_info.is_enabled,
             server: proxy_info.server,
+            bypass_domains: proxy_info.bypass_domains,
+            exclude_simple_hostnames: proxy_info.exclude_simple_hostnames,
         };
 
         response.send_signal_to_dart();
     }
 }
 
+impl DetectSystemProxyConflict {
+    pub async fn handle(&self) {
+        let proxy_info = get_proxy_info().await;
+        let current_server = detect_proxy_conflict(&proxy_info, &self.host, self.port);
+        if let Some(server) = &current_server {
+            log::warn!(
+                "检测到其他代理软件：当前系统代理为 {}，本应用为 {}:{}",
+                server,
+                self.host,
+                self.port
+            );
+        }
+
+        SystemProxyConflict {
+            has_conflict: current_server.is_some(),
+            current_server,
+        }
+        .send_signal_to_dart();
+    }
+}
+
+// 判断系统代理是否指向其他程序，是则返回当前代理地址
+pub fn detect_proxy_conflict(info: &ProxyInfo, host: &str, port: u16) -> Option<String> {
+    if !info.is_enabled {
+        return None;
+    }
+    let server = info.server.as_deref()?.trim();
+    if server.is_empty() {
+        return None;
+    }
+
+    let is_ours = match parse_proxy_server(server) {
+        Some((current_host, current_port)) => {
+            current_port == port && same_host(&current_host, host)
+        }
+        None => false,
+    };
+    (!is_ours).then(|| server.to_string())
+}
+
+// 解析代理地址为 (主机, 端口)，支持 host:port、scheme://host:port、[IPv6]:port
+// 以及 Windows 的 http=host:port;https=host:port 形式（取第一项）
+fn parse_proxy_server(server: &str) -> Option<(String, u16)> {
+    let first = server.split(';').next()?.trim();
+    let address = first
+        .split_once('=')
+        .map(|(_, address)| address)
+        .unwrap_or(first);
+    let address = address
+        .split_once("://")
+        .map(|(_, address)| address)
+        .unwrap_or(address)
+        .trim_end_matches('/');
+
+    let (host, port) = address.rsplit_once(':')?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_ascii_lowercase(), port.parse().ok()?))
+}
+
+// 比较主机名，各种本机回环地址视为相同
+fn same_host(a: &str, b: &str) -> bool {
+    let is_loopback = |host: &str| matches!(host, "localhost" | "::1") || host.starts_with("127.");
+    a.eq_ignore_ascii_case(b) || (is_loopback(a) && is_loopback(&b.to_ascii_lowercase()))
+}
+
 #[cfg(target_os = "windows")]
 mod windows_impl {
-    use super::{ProxyInfo, ProxyResult};
+    use super::super::bypass;
+    use super::{
+        MAX_PROXY_SERVER_LEN, ProxyBypass, ProxyInfo, ProxyResult, bounded_utf16_to_string,
+    };
     use std::ffi::OsStr;
     use std::fs;
     use std::os::windows::ffi::OsStrExt;
-    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::Foundation::{ERROR_SUCCESS, HGLOBAL};
     use windows::Win32::NetworkManagement::Rras::{RASENTRYNAMEW, RasEnumEntriesW};
     use windows::Win32::Networking::WinInet::{
         INTERNET_OPTION_PER_CONNECTION_OPTION, INTERNET_OPTION_REFRESH,
@@ -145,16 +260,18 @@ mod windows_impl {
         INTERNET_PER_CONN_PROXY_BYPASS, INTERNET_PER_CONN_PROXY_SERVER, InternetQueryOptionW,
         InternetSetOptionW, PROXY_TYPE_AUTO_PROXY_URL, PROXY_TYPE_DIRECT, PROXY_TYPE_PROXY,
     };
+    use windows::Win32::System::Memory::GlobalSize;
     use windows::core::PWSTR;
 
     // 配置并启用系统代理，可选使用 PAC 脚本。
     pub async fn enable_proxy(
         host: &str,
         port: u16,
-        bypass_domains: Vec<String>,
+        bypass: ProxyBypass,
         should_use_pac_mode: bool,
         pac_script: &str,
         pac_file_path: &str,
+        _target_devices: &[String],
     ) -> ProxyResult {
         if should_use_pac_mode {
             log::info!("正在设置系统代理 (PAC 模式)");
@@ -171,7 +288,8 @@ mod windows_impl {
                 .chain(std::iter::once(0))
                 .collect();
 
-            let bypasses = bypass_domains.join(";");
+            let bypasses =
+                bypass::wininet_bypass_list(&bypass.domains, bypass.exclude_simple_hostnames);
             let mut bypasses_wide: Vec<u16> = OsStr::new(&bypasses)
                 .encode_wide()
                 .chain(std::iter::once(0))
@@ -320,7 +438,7 @@ mod windows_impl {
     }
 
     // 移除系统代理配置并恢复直连。
-    pub async fn disable_proxy() -> ProxyResult {
+    pub async fn disable_proxy(_target_devices: &[String]) -> ProxyResult {
         log::info!("正在禁用系统代理");
 
         unsafe {
@@ -452,6 +570,7 @@ mod windows_impl {
                     return ProxyInfo {
                         is_enabled: false,
                         server: None,
+                        ..Default::default()
                     };
                 }
             }
@@ -464,6 +583,7 @@ mod windows_impl {
                 return ProxyInfo {
                     is_enabled: false,
                     server: None,
+                    ..Default::default()
                 };
             }
 
@@ -473,27 +593,31 @@ mod windows_impl {
                 return ProxyInfo {
                     is_enabled: true,
                     server: None,
+                    ..Default::default()
                 };
             }
 
-            // 转换为 Rust String
-            let server_wide = {
-                let mut len = 0;
-                let mut ptr = server_ptr.0;
-                while *ptr != 0 {
-                    len += 1;
-                    ptr = ptr.add(1);
-                }
-                std::slice::from_raw_parts(server_ptr.0, len)
-            };
-
-            let server_string = String::from_utf16_lossy(server_wide);
+            // 字符串由 WinInet 以 GlobalAlloc 分配，按分配大小限定读取范围，
+            // 缓冲区未以 NUL 结尾时截断而不越界
+            let capacity = GlobalSize(HGLOBAL(server_ptr.0.cast())) / std::mem::size_of::<u16>();
+            if capacity == 0 {
+                log::warn!("无法获取系统代理地址的缓冲区大小，忽略该地址");
+                return ProxyInfo {
+                    is_enabled: true,
+                    server: None,
+                    ..Default::default()
+                };
+            }
+            let server_wide =
+                std::slice::from_raw_parts(server_ptr.0, capacity.min(MAX_PROXY_SERVER_LEN));
+            let server_string = bounded_utf16_to_string(server_wide, MAX_PROXY_SERVER_LEN);
 
             log::info!("当前系统代理：{}", server_string);
 
             ProxyInfo {
                 is_enabled: true,
                 server: Some(server_string),
+                ..Default::default()
             }
         }
     }
@@ -504,11 +628,18 @@ mod windows_impl {
 
 #[cfg(target_os = "macos")]
 mod macos_impl {
-    use super::{ProxyInfo, ProxyResult};
+    use super::super::command_runner::CommandBatch;
+    use super::super::macos_prefs;
+    use super::{
+        ProxyBypass, ProxyInfo, ProxyResult, bypass_domains_args, disable_proxy_args,
+        enable_proxy_args, parse_network_services, parse_scutil_proxy, select_target_devices,
+    };
     use std::process::Command;
 
+    const NETWORKSETUP: &str = "/usr/sbin/networksetup";
+
     // 获取所有网络设备列表
-    async fn get_network_devices() -> Result<Vec<String>, String> {
+    pub async fn get_network_devices() -> Result<Vec<String>, String> {
         let output = Command::new("/usr/sbin/networksetup")
             .arg("-listallnetworkservices")
             .output()
@@ -518,113 +649,108 @@ mod macos_impl {
             return Err("获取网络设备列表失败".to_string());
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let devices: Vec<String> = stdout
-            .lines()
-            .filter(|line| !line.is_empty() && !line.contains('*'))
-            .map(|s| s.to_string())
-            .collect();
-
+        let devices = parse_network_services(&String::from_utf8_lossy(&output.stdout));
         log::info!("找到 {} 个网络设备", devices.len());
         Ok(devices)
     }
 
+    // 获取本次操作的目标设备（未指定时为全部设备）
+    async fn resolve_devices(target_devices: &[String]) -> Result<Vec<String>, String> {
+        let devices = get_network_devices().await?;
+        if devices.is_empty() {
+            return Err("未找到网络设备".to_string());
+        }
+        select_target_devices(devices, target_devices)
+    }
+
     // 启用 macOS 系统代理
     pub async fn enable_proxy(
         host: &str,
         port: u16,
-        bypass_domains: Vec<String>,
+        bypass: ProxyBypass,
         _should_use_pac_mode: bool,
         _pac_script: &str,
         _pac_file_path: &str,
+        target_devices: &[String],
     ) -> ProxyResult {
         log::info!("正在设置 macOS 系统代理：{}:{}", host, port);
 
-        let devices = match get_network_devices().await {
-            Ok(d) if !d.is_empty() => d,
-            Ok(_) => return ProxyResult::Error("未找到网络设备".to_string()),
+        let devices = match resolve_devices(target_devices).await {
+            Ok(d) => d,
             Err(e) => return ProxyResult::Error(e),
         };
 
         let port_str = port.to_string();
+        let mut batch = CommandBatch::new();
 
         for device in &devices {
-            // 设置 HTTP 代理
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setwebproxystate", device, "on"])
-                .status();
-
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setwebproxy", device, host, &port_str])
-                .status();
-
-            // 设置 HTTPS 代理
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setsecurewebproxystate", device, "on"])
-                .status();
-
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setsecurewebproxy", device, host, &port_str])
-                .status();
-
-            // 设置 SOCKS 代理
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setsocksfirewallproxystate", device, "on"])
-                .status();
-
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setsocksfirewallproxy", device, host, &port_str])
-                .status();
-
-            // 设置绕过域名
-            if !bypass_domains.is_empty() {
-                let mut args = vec!["-setproxybypassdomains", device];
-                let bypass_refs: Vec<&str> = bypass_domains.iter().map(|s| s.as_str()).collect();
-                args.extend(bypass_refs);
-
-                let _ = Command::new("/usr/sbin/networksetup").args(&args).status();
+            let device = device.as_str();
+
+            // 设置 HTTP、HTTPS 与 SOCKS 代理
+            for args in enable_proxy_args(device, host, &port_str) {
+                batch.run(NETWORKSETUP, &args).await;
             }
+
+            // 设置绕过域名（列表为空时清除旧设置）
+            let args = bypass_domains_args(device, &bypass.domains);
+            batch.run(NETWORKSETUP, &args).await;
         }
 
-        log::info!("macOS 系统代理设置成功");
-        ProxyResult::Success
+        // networksetup 不支持「排除简单主机名」，直接写入网络偏好设置
+        batch.record(macos_prefs::set_exclude_simple_hostnames(
+            &devices,
+            bypass.exclude_simple_hostnames,
+        ));
+
+        let result = batch.into_result("设置 macOS 系统代理");
+        if matches!(result, ProxyResult::Success) {
+            log::info!("macOS 系统代理设置成功");
+        }
+        result
     }
 
     // 禁用 macOS 系统代理
-    pub async fn disable_proxy() -> ProxyResult {
+    pub async fn disable_proxy(target_devices: &[String]) -> ProxyResult {
         log::info!("正在禁用 macOS 系统代理");
 
-        let devices = match get_network_devices().await {
-            Ok(d) if !d.is_empty() => d,
-            Ok(_) => return ProxyResult::Error("未找到网络设备".to_string()),
+        let devices = match resolve_devices(target_devices).await {
+            Ok(d) => d,
             Err(e) => return ProxyResult::Error(e),
         };
 
-        for device in &devices {
-            // 禁用所有类型的代理
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setautoproxystate", device, "off"])
-                .status();
+        let mut batch = CommandBatch::new();
 
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setwebproxystate", device, "off"])
-                .status();
+        for device in &devices {
+            let device = device.as_str();
 
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setsecurewebproxystate", device, "off"])
-                .status();
+            // 禁用所有类型的代理
+            for args in disable_proxy_args(device) {
+                batch.run(NETWORKSETUP, &args).await;
+            }
+            batch
+                .run(NETWORKSETUP, &bypass_domains_args(device, &[]))
+                .await;
+        }
 
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setsocksfirewallproxystate", device, "off"])
-                .status();
+        // 与清空绕过列表一致，恢复「排除简单主机名」的默认值
+        batch.record(macos_prefs::set_exclude_simple_hostnames(&devices, false));
 
-            let _ = Command::new("/usr/sbin/networksetup")
-                .args(["-setproxybypassdomains", device, ""])
-                .status();
+        let result = batch.into_result("禁用 macOS 系统代理");
+        if matches!(result, ProxyResult::Success) {
+            log::info!("macOS 系统代理已禁用");
         }
+        result
+    }
 
-        log::info!("macOS 系统代理已禁用");
-        ProxyResult::Success
+    // 读取当前生效的绕过列表与「排除简单主机名」状态
+    fn get_proxy_options() -> (Vec<String>, bool) {
+        match Command::new("/usr/sbin/scutil").arg("--proxy").output() {
+            Ok(output) => parse_scutil_proxy(&String::from_utf8_lossy(&output.stdout)),
+            Err(e) => {
+                log::warn!("执行 scutil 失败：{}", e);
+                (Vec::new(), false)
+            }
+        }
     }
 
     // 获取 macOS 系统代理状态
@@ -637,6 +763,7 @@ mod macos_impl {
                 return ProxyInfo {
                     is_enabled: false,
                     server: None,
+                    ..Default::default()
                 };
             }
         };
@@ -674,28 +801,44 @@ mod macos_impl {
                 };
 
                 log::info!("当前 macOS 系统代理：{}", server_str);
+                let (bypass_domains, exclude_simple_hostnames) = get_proxy_options();
                 return ProxyInfo {
                     is_enabled: true,
                     server: Some(server_str),
+                    bypass_domains,
+                    exclude_simple_hostnames,
                 };
             }
         }
 
+        let (bypass_domains, exclude_simple_hostnames) = get_proxy_options();
         ProxyInfo {
             is_enabled: false,
             server: None,
+            bypass_domains,
+            exclude_simple_hostnames,
         }
     }
 }
 
 // ==================== Linux 实现 ====================
-// 支持 GNOME (gsettings) 和 KDE (kwriteconfig5)
+// 支持 GNOME (gsettings) 和 KDE (kwriteconfig5)，无桌面环境时写入代理环境变量文件
 
 #[cfg(target_os = "linux")]
 mod linux_impl {
-    use super::{ProxyInfo, ProxyResult};
+    use super::super::bypass;
+    use super::super::command_runner::{CommandBatch, run_command};
+    use super::super::env_file;
+    use super::{ProxyBypass, ProxyInfo, ProxyResult};
     use std::process::Command;
 
+    // 判断是否为无头环境（无图形会话且未识别到桌面）
+    fn is_headless() -> bool {
+        ["DISPLAY", "WAYLAND_DISPLAY", "XDG_CURRENT_DESKTOP"]
+            .iter()
+            .all(|key| std::env::var_os(key).is_none_or(|value| value.is_empty()))
+    }
+
     // 检测桌面环境类型
     fn detect_desktop_environment() -> String {
         std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default()
@@ -710,42 +853,70 @@ mod linux_impl {
     pub async fn enable_proxy(
         host: &str,
         port: u16,
-        bypass_domains: Vec<String>,
+        bypass: ProxyBypass,
         _should_use_pac_mode: bool,
         _pac_script: &str,
         _pac_file_path: &str,
+        _target_devices: &[String],
     ) -> ProxyResult {
         log::info!("正在设置 Linux 系统代理：{}:{}", host, port);
 
-        if is_kde() {
-            enable_proxy_kde(host, port, bypass_domains).await
+        if is_headless() {
+            enable_proxy_env(host, port, &bypass::unix_bypass_entries(&bypass.domains))
+        } else if is_kde() {
+            enable_proxy_kde(host, port, bypass::unix_bypass_entries(&bypass.domains)).await
         } else {
-            enable_proxy_gnome(host, port, bypass_domains).await
+            enable_proxy_gnome(host, port, &bypass.domains).await
+        }
+    }
+
+    // 无头环境：写入代理环境变量文件（对之后启动的登录会话生效）
+    fn enable_proxy_env(host: &str, port: u16, bypass_domains: &[String]) -> ProxyResult {
+        let Some((path, format)) = env_file::target_path() else {
+            return ProxyResult::Error("无法获取 HOME 环境变量".to_string());
+        };
+
+        if let Some(parent) = path.parent()
+            && let Err(e) = std::fs::create_dir_all(parent)
+        {
+            return ProxyResult::Error(format!("创建目录失败：{}，{}", parent.display(), e));
         }
+        let content = env_file::render(host, port, bypass_domains, format);
+        if let Err(e) = std::fs::write(&path, content) {
+            return ProxyResult::Error(format!("写入代理环境变量失败：{}，{}", path.display(), e));
+        }
+
+        log::info!("未检测到桌面环境，已写入代理环境变量：{}", path.display());
+        ProxyResult::Success
     }
 
     // 启用 GNOME 系统代理 (gsettings)
-    async fn enable_proxy_gnome(host: &str, port: u16, bypass_domains: Vec<String>) -> ProxyResult {
+    async fn enable_proxy_gnome(host: &str, port: u16, bypass_domains: &[String]) -> ProxyResult {
         // 设置代理模式为手动
-        let result = Command::new("gsettings")
-            .args(["set", "org.gnome.system.proxy", "mode", "manual"])
-            .status();
-
-        match result {
-            Ok(_) => {}
-            Err(_) => return ProxyResult::Error("设置 GNOME 代理模式失败".to_string()),
+        if let Err(e) = run_command(
+            "gsettings",
+            &["set", "org.gnome.system.proxy", "mode", "manual"],
+        )
+        .await
+        {
+            return ProxyResult::Error(format!("设置 GNOME 代理模式失败：{}", e));
         }
 
+        let mut batch = CommandBatch::new();
+
         // 设置忽略的主机列表
-        let ignore_hosts = format!("['{}']", bypass_domains.join("', '"));
-        let _ = Command::new("gsettings")
-            .args([
-                "set",
-                "org.gnome.system.proxy",
-                "ignore-hosts",
-                &ignore_hosts,
-            ])
-            .status();
+        let ignore_hosts = bypass::gsettings_ignore_hosts(bypass_domains);
+        batch
+            .run(
+                "gsettings",
+                &[
+                    "set",
+                    "org.gnome.system.proxy",
+                    "ignore-hosts",
+                    &ignore_hosts,
+                ],
+            )
+            .await;
 
         let port_str = port.to_string();
 
@@ -753,17 +924,19 @@ mod linux_impl {
         for proxy_type in &["http", "https", "socks"] {
             let schema = format!("org.gnome.system.proxy.{}", proxy_type);
 
-            let _ = Command::new("gsettings")
-                .args(["set", &schema, "host", host])
-                .status();
-
-            let _ = Command::new("gsettings")
-                .args(["set", &schema, "port", &port_str])
-                .status();
+            batch
+                .run("gsettings", &["set", &schema, "host", host])
+                .await;
+            batch
+                .run("gsettings", &["set", &schema, "port", &port_str])
+                .await;
         }
 
-        log::info!("Linux GNOME 系统代理设置成功");
-        ProxyResult::Success
+        let result = batch.into_result("设置 GNOME 系统代理");
+        if matches!(result, ProxyResult::Success) {
+            log::info!("Linux GNOME 系统代理设置成功");
+        }
+        result
     }
 
     // 启用 KDE 系统代理 (kwriteconfig5)
@@ -774,76 +947,120 @@ mod linux_impl {
         };
 
         let config_file = format!("{}/.config/kioslaverc", home_dir);
+        let mut batch = CommandBatch::new();
 
         // 设置代理类型为手动 (1)
-        let _ = Command::new("kwriteconfig5")
-            .args([
-                "--file",
-                &config_file,
-                "--group",
-                "Proxy Settings",
-                "--key",
-                "ProxyType",
-                "1",
-            ])
-            .status();
+        batch
+            .run(
+                "kwriteconfig5",
+                &[
+                    "--file",
+                    &config_file,
+                    "--group",
+                    "Proxy Settings",
+                    "--key",
+                    "ProxyType",
+                    "1",
+                ],
+            )
+            .await;
 
         // 设置绕过域名
         let bypasses = bypass_domains.join(",");
-        let _ = Command::new("kwriteconfig5")
-            .args([
-                "--file",
-                &config_file,
-                "--group",
-                "Proxy Settings",
-                "--key",
-                "NoProxyFor",
-                &bypasses,
-            ])
-            .status();
+        batch
+            .run(
+                "kwriteconfig5",
+                &[
+                    "--file",
+                    &config_file,
+                    "--group",
+                    "Proxy Settings",
+                    "--key",
+                    "NoProxyFor",
+                    &bypasses,
+                ],
+            )
+            .await;
 
         // 为 HTTP、HTTPS、SOCKS 设置代理
         for proxy_type in &["http", "https", "socks"] {
             let key = format!("{}Proxy", proxy_type);
             let value = format!("{}://{}:{}", proxy_type, host, port);
 
-            let _ = Command::new("kwriteconfig5")
-                .args([
-                    "--file",
-                    &config_file,
-                    "--group",
-                    "Proxy Settings",
-                    "--key",
-                    &key,
-                    &value,
-                ])
-                .status();
+            batch
+                .run(
+                    "kwriteconfig5",
+                    &[
+                        "--file",
+                        &config_file,
+                        "--group",
+                        "Proxy Settings",
+                        "--key",
+                        &key,
+                        &value,
+                    ],
+                )
+                .await;
         }
 
-        log::info!("Linux KDE 系统代理设置成功");
-        ProxyResult::Success
+        let result = batch.into_result("设置 KDE 系统代理");
+        if matches!(result, ProxyResult::Success) {
+            log::info!("Linux KDE 系统代理设置成功");
+        }
+        result
     }
 
     // 禁用 Linux 系统代理
-    pub async fn disable_proxy() -> ProxyResult {
+    pub async fn disable_proxy(_target_devices: &[String]) -> ProxyResult {
         log::info!("正在禁用 Linux 系统代理");
 
-        if is_kde() {
+        if is_headless() {
+            disable_proxy_env()
+        } else if is_kde() {
             disable_proxy_kde().await
         } else {
             disable_proxy_gnome().await
         }
     }
 
+    // 无头环境：删除本程序生成的代理环境变量文件
+    fn disable_proxy_env() -> ProxyResult {
+        let Some((path, _)) = env_file::target_path() else {
+            return ProxyResult::Error("无法获取 HOME 环境变量".to_string());
+        };
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return ProxyResult::Success,
+            Err(e) => {
+                return ProxyResult::Error(format!(
+                    "读取代理环境变量失败：{}，{}",
+                    path.display(),
+                    e
+                ));
+            }
+        };
+        if env_file::parse(&content).is_none() {
+            log::warn!("代理环境变量文件不是本程序生成，已保留：{}", path.display());
+            return ProxyResult::Success;
+        }
+        if let Err(e) = std::fs::remove_file(&path) {
+            return ProxyResult::Error(format!("删除代理环境变量失败：{}，{}", path.display(), e));
+        }
+
+        log::info!("已删除代理环境变量：{}", path.display());
+        ProxyResult::Success
+    }
+
     // 禁用 GNOME 系统代理
     async fn disable_proxy_gnome() -> ProxyResult {
-        let result = Command::new("gsettings")
-            .args(["set", "org.gnome.system.proxy", "mode", "none"])
-            .status();
-
-        match result {
-            Ok(_) => {}
-            Err(_) => return ProxyResult::Error("禁用 GNOME 代理失败".to_string()),
+        if let Err(e) = run_command(
+            "gsettings",
+            &["set", "org.gnome.system.proxy", "mode", "none"],
+        )
+        .await
+        {
+            return ProxyResult::Error(format!("禁用 GNOME 代理失败：{}", e));
         }
 
         log::info!("Linux GNOME 系统代理已禁用");
@@ -860,8 +1077,9 @@ mod linux_impl {
         let config_file = format!("{}/.config/kioslaverc", home_dir);
 
         // 设置代理类型为无代理 (0)
-        let result = Command::new("kwriteconfig5")
-            .args([
+        if let Err(e) = run_command(
+            "kwriteconfig5",
+            &[
                 "--file",
                 &config_file,
                 "--group",
@@ -869,12 +1087,11 @@ mod linux_impl {
                 "--key",
                 "ProxyType",
                 "0",
-            ])
-            .status();
-
-        match result {
-            Ok(_) => {}
-            Err(_) => return ProxyResult::Error("禁用 KDE 代理失败".to_string()),
+            ],
+        )
+        .await
+        {
+            return ProxyResult::Error(format!("禁用 KDE 代理失败：{}", e));
         }
 
         log::info!("Linux KDE 系统代理已禁用");
@@ -885,13 +1102,32 @@ mod linux_impl {
     pub async fn get_proxy_info() -> ProxyInfo {
         log::info!("正在查询 Linux 系统代理状态");
 
-        if is_kde() {
+        if is_headless() {
+            get_proxy_info_env()
+        } else if is_kde() {
             get_proxy_info_kde().await
         } else {
             get_proxy_info_gnome().await
         }
     }
 
+    // 无头环境：读回代理环境变量文件
+    fn get_proxy_info_env() -> ProxyInfo {
+        let proxy = env_file::target_path()
+            .and_then(|(path, _)| std::fs::read_to_string(path).ok())
+            .and_then(|content| env_file::parse(&content));
+
+        match proxy {
+            Some(proxy) => ProxyInfo {
+                is_enabled: true,
+                server: Some(proxy.server),
+                bypass_domains: proxy.bypass_domains,
+                ..Default::default()
+            },
+            None => ProxyInfo::default(),
+        }
+    }
+
     // 获取 GNOME 系统代理状态
     async fn get_proxy_info_gnome() -> ProxyInfo {
         // 查询代理模式
@@ -905,6 +1141,7 @@ mod linux_impl {
                 return ProxyInfo {
                     is_enabled: false,
                     server: None,
+                    ..Default::default()
                 };
             }
         };
@@ -913,6 +1150,7 @@ mod linux_impl {
             return ProxyInfo {
                 is_enabled: false,
                 server: None,
+                ..Default::default()
             };
         }
 
@@ -939,17 +1177,20 @@ mod linux_impl {
                     return ProxyInfo {
                         is_enabled: true,
                         server: Some(server_str),
+                        ..Default::default()
                     };
                 }
 
                 ProxyInfo {
                     is_enabled: false,
                     server: None,
+                    ..Default::default()
                 }
             }
             _ => ProxyInfo {
                 is_enabled: false,
                 server: None,
+                ..Default::default()
             },
         }
     }
@@ -962,6 +1203,7 @@ mod linux_impl {
                 return ProxyInfo {
                     is_enabled: false,
                     server: None,
+                    ..Default::default()
                 };
             }
         };
@@ -986,6 +1228,7 @@ mod linux_impl {
                 return ProxyInfo {
                     is_enabled: false,
                     server: None,
+                    ..Default::default()
                 };
             }
         };
@@ -995,6 +1238,7 @@ mod linux_impl {
             return ProxyInfo {
                 is_enabled: false,
                 server: None,
+                ..Default::default()
             };
         }
 
@@ -1021,22 +1265,151 @@ mod linux_impl {
                     return ProxyInfo {
                         is_enabled: true,
                         server: Some(server_str),
+                        ..Default::default()
                     };
                 }
 
                 ProxyInfo {
                     is_enabled: false,
                     server: None,
+                    ..Default::default()
                 }
             }
             Err(_) => ProxyInfo {
                 is_enabled: false,
                 server: None,
+                ..Default::default()
             },
         }
     }
 }
 
+// 组装 networksetup 启用 HTTP、HTTPS 与 SOCKS 代理的参数（先开启状态，再设置地址）
+#[cfg(any(target_os = "macos", test))]
+fn enable_proxy_args<'a>(device: &'a str, host: &'a str, port: &'a str) -> Vec<Vec<&'a str>> {
+    [
+        ("-setwebproxystate", "-setwebproxy"),
+        ("-setsecurewebproxystate", "-setsecurewebproxy"),
+        ("-setsocksfirewallproxystate", "-setsocksfirewallproxy"),
+    ]
+    .into_iter()
+    .flat_map(|(state_flag, server_flag)| {
+        [
+            vec![state_flag, device, "on"],
+            vec![server_flag, device, host, port],
+        ]
+    })
+    .collect()
+}
+
+// 组装 networksetup 关闭全部代理（含 PAC）的参数
+#[cfg(any(target_os = "macos", test))]
+fn disable_proxy_args(device: &str) -> Vec<Vec<&str>> {
+    [
+        "-setautoproxystate",
+        "-setwebproxystate",
+        "-setsecurewebproxystate",
+        "-setsocksfirewallproxystate",
+    ]
+    .into_iter()
+    .map(|flag| vec![flag, device, "off"])
+    .collect()
+}
+
+// 组装 networksetup 设置绕过列表的参数（空列表需传入 Empty 才会清除）
+#[cfg(any(target_os = "macos", test))]
+fn bypass_domains_args<'a>(device: &'a str, bypass_domains: &'a [String]) -> Vec<&'a str> {
+    let mut args = vec!["-setproxybypassdomains", device];
+    if bypass_domains.is_empty() {
+        args.push("Empty");
+    } else {
+        args.extend(bypass_domains.iter().map(|s| s.as_str()));
+    }
+    args
+}
+
+// 解析 scutil --proxy 输出，返回绕过列表与「排除简单主机名」状态
+#[cfg(any(target_os = "macos", test))]
+fn parse_scutil_proxy(output: &str) -> (Vec<String>, bool) {
+    let mut bypass_domains = Vec::new();
+    let mut exclude_simple_hostnames = false;
+    let mut in_exceptions = false;
+
+    for line in output.lines().map(str::trim) {
+        if in_exceptions {
+            if line == "}" {
+                in_exceptions = false;
+            } else if let Some((_, domain)) = line.split_once(" : ") {
+                bypass_domains.push(domain.trim().to_string());
+            }
+            continue;
+        }
+
+        match line.split_once(" : ") {
+            Some(("ExceptionsList", _)) => in_exceptions = true,
+            Some(("ExcludeSimpleHostnames", value)) => exclude_simple_hostnames = value == "1",
+            _ => {}
+        }
+    }
+
+    (bypass_domains, exclude_simple_hostnames)
+}
+
+// 解析 networksetup -listallnetworkservices 输出：跳过说明行与带 * 的已禁用服务
+#[cfg(any(target_os = "macos", test))]
+fn parse_network_services(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter(|line| !line.is_empty() && !line.contains('*'))
+        .map(|s| s.to_string())
+        .collect()
+}
+
+// 读取系统代理地址时最多读取的 UTF-16 单元数
+#[cfg(target_os = "windows")]
+const MAX_PROXY_SERVER_LEN: usize = 4096;
+
+// 将 UTF-16 缓冲区转换为字符串：在首个 NUL 处截断，未终止时最多转换 max_len 个单元，
+// 无效的代理对替换为 U+FFFD
+#[cfg(any(target_os = "windows", test))]
+fn bounded_utf16_to_string(wide: &[u16], max_len: usize) -> String {
+    let wide = &wide[..wide.len().min(max_len)];
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+    String::from_utf16_lossy(&wide[..len])
+}
+
+// 从可用设备中筛选目标设备：未指定时返回全部，指定时忽略不存在的设备名
+#[cfg(any(target_os = "macos", test))]
+fn select_target_devices(
+    available: Vec<String>,
+    target_devices: &[String],
+) -> Result<Vec<String>, String> {
+    if target_devices.is_empty() {
+        return Ok(available);
+    }
+
+    for target in target_devices {
+        if !available.contains(target) {
+            log::warn!("网络设备不存在，已忽略：{}", target);
+        }
+    }
+
+    let selected: Vec<String> = available
+        .into_iter()
+        .filter(|device| target_devices.contains(device))
+        .collect();
+
+    if selected.is_empty() {
+        return Err(format!(
+            "指定的网络设备均不存在：{}",
+            target_devices.join(", ")
+        ));
+    }
+
+    log::info!("系统代理仅作用于设备：{}", selected.join(", "));
+    Ok(selected)
+}
+
 // ==================== 平台导出 ====================
 
 // Windows 导出
@@ -1045,7 +1418,7 @@ pub use windows_impl::{disable_proxy, enable_proxy, get_proxy_info};
 
 // macOS 导出
 #[cfg(target_os = "macos")]
-pub use macos_impl::{disable_proxy, enable_proxy, get_proxy_info};
+pub use macos_impl::{disable_proxy, enable_proxy, get_network_devices, get_proxy_info};
 
 // Linux 导出
 #[cfg(target_os = "linux")]
@@ -1056,16 +1429,17 @@ pub use linux_impl::{disable_proxy, enable_proxy, get_proxy_info};
 pub async fn enable_proxy(
     _host: &str,
     _port: u16,
-    _bypass_domains: Vec<String>,
+    _bypass: ProxyBypass,
     _should_use_pac_mode: bool,
     _pac_script: &str,
     _pac_file_path: &str,
+    _target_devices: &[String],
 ) -> ProxyResult {
     ProxyResult::Error("当前平台不支持系统代理设置".to_string())
 }
 
 #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-pub async fn disable_proxy() -> ProxyResult {
+pub async fn disable_proxy(_target_devices: &[String]) -> ProxyResult {
     ProxyResult::Error("当前平台不支持系统代理设置".to_string())
 }
 
@@ -1074,6 +1448,7 @@ pub async fn get_proxy_info() -> ProxyInfo {
     ProxyInfo {
         is_enabled: false,
         server: None,
+        ..Default::default()
     }
 }
 
@@ -1101,4 +1476,186 @@ pub fn init() {
         }
         log::info!("获取系统代理状态消息通道已关闭，退出监听器");
     });
+
+    spawn(async {
+        let receiver = DetectSystemProxyConflict::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy_info(server: Option<&str>) -> ProxyInfo {
+        ProxyInfo {
+            is_enabled: true,
+            server: server.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bounded_utf16_to_string() {
+        let wide = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+
+        let mut terminated = wide("127.0.0.1:7890");
+        terminated.extend([0, b'x' as u16]);
+        assert_eq!(bounded_utf16_to_string(&terminated, 64), "127.0.0.1:7890");
+
+        // 未以 NUL 结尾：读到缓冲区末尾或上限为止
+        let unterminated = wide("127.0.0.1:7890");
+        assert_eq!(bounded_utf16_to_string(&unterminated, 64), "127.0.0.1:7890");
+        assert_eq!(bounded_utf16_to_string(&unterminated, 9), "127.0.0.1");
+
+        // 孤立的代理项不会导致失败
+        let malformed = [b'a' as u16, 0xD800, b'b' as u16];
+        assert_eq!(bounded_utf16_to_string(&malformed, 64), "a\u{FFFD}b");
+        assert_eq!(bounded_utf16_to_string(&[], 64), "");
+    }
+
+    #[test]
+    fn test_detect_proxy_conflict() {
+        let conflict =
+            |server: Option<&str>| detect_proxy_conflict(&proxy_info(server), "127.0.0.1", 7890);
+
+        // 本应用设置的代理
+        assert_eq!(conflict(Some("127.0.0.1:7890")), None);
+        assert_eq!(conflict(Some("localhost:7890")), None);
+        assert_eq!(conflict(Some("http://127.0.0.1:7890/")), None);
+        assert_eq!(
+            conflict(Some("http=127.0.0.1:7890;https=127.0.0.1:7890")),
+            None
+        );
+        assert_eq!(conflict(Some("[::1]:7890")), None);
+        assert_eq!(conflict(None), None);
+
+        // 其他代理软件
+        assert_eq!(
+            conflict(Some("127.0.0.1:1080")),
+            Some("127.0.0.1:1080".to_string())
+        );
+        assert_eq!(
+            conflict(Some("192.168.1.2:7890")),
+            Some("192.168.1.2:7890".to_string())
+        );
+        assert_eq!(
+            conflict(Some("proxy.corp.example")),
+            Some("proxy.corp.example".to_string())
+        );
+
+        // 系统代理未启用时不视为冲突
+        let disabled = ProxyInfo {
+            is_enabled: false,
+            ..proxy_info(Some("10.0.0.1:8080"))
+        };
+        assert_eq!(detect_proxy_conflict(&disabled, "127.0.0.1", 7890), None);
+    }
+
+    fn devices(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_select_all_devices_when_unspecified() {
+        let available = devices(&["Wi-Fi", "Ethernet", "Tailscale"]);
+        let selected = select_target_devices(available.clone(), &[]);
+        assert_eq!(selected, Ok(available));
+    }
+
+    #[test]
+    fn test_parse_network_services() {
+        let stdout = "An asterisk (*) denotes that a network service is disabled.\n\
+                      Wi-Fi\n\
+                      *Bluetooth PAN\n\
+                      USB 10/100/1000 LAN\n\
+                      \n\
+                      Thunderbolt Bridge\n";
+        assert_eq!(
+            parse_network_services(stdout),
+            vec!["Wi-Fi", "USB 10/100/1000 LAN", "Thunderbolt Bridge"]
+        );
+        assert!(parse_network_services("").is_empty());
+    }
+
+    #[test]
+    fn test_select_only_target_devices() {
+        let available = devices(&["Wi-Fi", "Ethernet", "Tailscale"]);
+        let selected = select_target_devices(available, &devices(&["Wi-Fi", "USB LAN"]));
+        assert_eq!(selected, Ok(devices(&["Wi-Fi"])));
+    }
+
+    #[test]
+    fn test_bypass_domains_args() {
+        let bypass = devices(&["localhost", "*.local"]);
+        assert_eq!(
+            bypass_domains_args("Wi-Fi", &bypass),
+            vec!["-setproxybypassdomains", "Wi-Fi", "localhost", "*.local"]
+        );
+        assert_eq!(
+            bypass_domains_args("Wi-Fi", &[]),
+            vec!["-setproxybypassdomains", "Wi-Fi", "Empty"]
+        );
+    }
+
+    #[test]
+    fn test_enable_proxy_args() {
+        assert_eq!(
+            enable_proxy_args("Wi-Fi", "127.0.0.1", "7890"),
+            vec![
+                vec!["-setwebproxystate", "Wi-Fi", "on"],
+                vec!["-setwebproxy", "Wi-Fi", "127.0.0.1", "7890"],
+                vec!["-setsecurewebproxystate", "Wi-Fi", "on"],
+                vec!["-setsecurewebproxy", "Wi-Fi", "127.0.0.1", "7890"],
+                vec!["-setsocksfirewallproxystate", "Wi-Fi", "on"],
+                vec!["-setsocksfirewallproxy", "Wi-Fi", "127.0.0.1", "7890"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_disable_proxy_args() {
+        assert_eq!(
+            disable_proxy_args("USB LAN"),
+            vec![
+                vec!["-setautoproxystate", "USB LAN", "off"],
+                vec!["-setwebproxystate", "USB LAN", "off"],
+                vec!["-setsecurewebproxystate", "USB LAN", "off"],
+                vec!["-setsocksfirewallproxystate", "USB LAN", "off"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_scutil_proxy() {
+        let output = "<dictionary> {
+  ExceptionsList : <array> {
+    0 : *.local
+    1 : 169.254/16
+  }
+  ExcludeSimpleHostnames : 1
+  HTTPEnable : 1
+  HTTPPort : 7890
+  HTTPProxy : 127.0.0.1
+}";
+        assert_eq!(
+            parse_scutil_proxy(output),
+            (devices(&["*.local", "169.254/16"]), true)
+        );
+
+        let output = "<dictionary> {
+  ExcludeSimpleHostnames : 0
+  HTTPEnable : 0
+}";
+        assert_eq!(parse_scutil_proxy(output), (Vec::new(), false));
+    }
+
+    #[test]
+    fn test_select_unknown_devices_fails() {
+        let available = devices(&["Wi-Fi", "Ethernet"]);
+        let selected = select_target_devices(available, &devices(&["USB LAN"]));
+        assert!(selected.is_err());
+    }
 }
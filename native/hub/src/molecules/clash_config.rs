@@ -1,13 +1,21 @@
 // Clash 配置管理分子模块
 
+pub mod capabilities;
 pub mod generator;
 pub mod injector;
 pub mod runtime_params;
+pub mod validation;
 
+pub use capabilities::{GetSupportedCapabilities, SupportedCapabilities};
 pub use generator::{GenerateRuntimeConfigRequest, GenerateRuntimeConfigResponse};
 pub use injector::inject_runtime_params;
 pub use runtime_params::RuntimeConfigParams;
+pub use validation::{
+    SaveConfigValidated, SaveConfigValidatedResult, ValidateConfigFile, ValidateConfigFileResult,
+};
 
 pub fn init_listeners() {
+    capabilities::init();
     generator::init();
+    validation::init();
 }
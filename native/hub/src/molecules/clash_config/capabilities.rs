@@ -0,0 +1,101 @@
+// 核心能力列表：向界面提供校验器使用的节点类型、代理组类型、规则类型与加密方式，
+// 添加节点等界面据此校验输入，不再在 Dart 侧维护副本。
+
+use crate::atoms::config_validator::{PROXY_GROUP_TYPES, PROXY_TYPES, RULE_TYPES, SS_CIPHERS};
+use rinf::{DartSignal, RustSignal};
+use serde::{Deserialize, Serialize};
+
+// Dart → Rust：查询核心支持的类型列表
+#[derive(Deserialize, DartSignal)]
+pub struct GetSupportedCapabilities;
+
+// Rust → Dart：核心支持的类型列表
+#[derive(Debug, Serialize, RustSignal)]
+pub struct SupportedCapabilities {
+    pub proxy_types: Vec<String>,
+    pub proxy_group_types: Vec<String>,
+    pub rule_types: Vec<String>,
+    // Shadowsocks 加密方式
+    pub ciphers: Vec<String>,
+}
+
+impl GetSupportedCapabilities {
+    pub fn handle(self) -> SupportedCapabilities {
+        supported_capabilities()
+    }
+}
+
+pub fn supported_capabilities() -> SupportedCapabilities {
+    let to_vec = |items: &[&str]| items.iter().map(|item| item.to_string()).collect();
+    SupportedCapabilities {
+        proxy_types: to_vec(PROXY_TYPES),
+        proxy_group_types: to_vec(PROXY_GROUP_TYPES),
+        rule_types: to_vec(RULE_TYPES),
+        ciphers: to_vec(SS_CIPHERS),
+    }
+}
+
+pub fn init() {
+    tokio::spawn(async {
+        let receiver = GetSupportedCapabilities::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle().send_signal_to_dart();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atoms::config_validator::{validate_proxies, validate_proxy_groups, validate_rules};
+
+    #[test]
+    fn test_capabilities_match_validator() {
+        let capabilities = supported_capabilities();
+        assert_eq!(capabilities.proxy_types, PROXY_TYPES);
+        assert_eq!(capabilities.proxy_group_types, PROXY_GROUP_TYPES);
+        assert_eq!(capabilities.rule_types, RULE_TYPES);
+        assert_eq!(capabilities.ciphers, SS_CIPHERS);
+
+        // 列表中的每一项都能通过校验器的类型检查
+        let proxies = capabilities
+            .proxy_types
+            .iter()
+            .map(|proxy_type| format!("  - {{name: {0}, type: {0}}}\n", proxy_type))
+            .chain(
+                capabilities
+                    .ciphers
+                    .iter()
+                    .map(|cipher| format!("  - {{name: ss-{0}, type: ss, cipher: {0}}}\n", cipher)),
+            )
+            .collect::<String>();
+        let groups = capabilities
+            .proxy_group_types
+            .iter()
+            .map(|group_type| format!("  - {{name: {0}, type: {0}, proxies: [a]}}\n", group_type))
+            .collect::<String>();
+        let config: serde_yaml_ng::Value =
+            serde_yaml_ng::from_str(&format!("proxies:\n{}proxy-groups:\n{}", proxies, groups))
+                .unwrap_or_else(|e| panic!("解析失败：{}", e));
+
+        let type_issues = validate_proxies(&config)
+            .into_iter()
+            .chain(validate_proxy_groups(&config))
+            .filter(|issue| issue.message.starts_with("未知"))
+            .count();
+        assert_eq!(type_issues, 0);
+
+        for rule_type in &capabilities.rule_types {
+            let config: serde_yaml_ng::Value =
+                serde_yaml_ng::from_str(&format!("rules: ['{},value,DIRECT']", rule_type))
+                    .unwrap_or_else(|e| panic!("解析失败：{}", e));
+            assert!(
+                !validate_rules(&config)
+                    .iter()
+                    .any(|issue| issue.message.starts_with("未知")),
+                "{}",
+                rule_type
+            );
+        }
+    }
+}
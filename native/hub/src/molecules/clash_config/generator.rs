@@ -5,8 +5,9 @@ use rinf::{DartSignal, RustSignal};
 use serde::{Deserialize, Serialize};
 
 use super::runtime_params::RuntimeConfigParams;
-use crate::atoms::OverrideProcessor;
+use crate::atoms::{ConfigValidator, OverrideProcessor};
 use crate::molecules::OverrideConfig;
+use crate::molecules::clash_process::ServiceManager;
 
 // Dart → Rust：生成运行时配置请求
 #[derive(Debug, Clone, Serialize, Deserialize, DartSignal)]
@@ -79,7 +80,14 @@ fn generate_runtime_config_internal(
     // 2. 注入运行时参数
     let final_config = super::injector::inject_runtime_params(&config_after_override, params)?;
 
-    // 3. 输出配置摘要（调试用）
+    // 3. 校验配置（仅记录问题，不阻止下发）。服务已安装时主程序经服务启动核心
+    let is_service_mode = ServiceManager::is_service_registered();
+    match ConfigValidator::validate_runtime_content(&final_config, is_service_mode) {
+        Ok(report) => report.log_issues(),
+        Err(e) => log::warn!("配置校验跳过：{}", e),
+    }
+
+    // 4. 输出配置摘要（调试用）
     log_config_summary(&final_config);
 
     Ok(final_config)
@@ -0,0 +1,303 @@
+// 配置文件校验：按路径在 Rust 侧读取并校验配置，避免大配置经 Dart-Rust 桥传输。
+// 保存手动编辑的配置时先校验，校验通过才写入磁盘。
+
+use rinf::{DartSignal, RustSignal, SignalPiece};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::atoms::ConfigValidator;
+use crate::atoms::config_validator::{ConfigFileError, IssueSeverity, ValidationIssue};
+
+// Dart → Rust：按路径校验配置文件
+#[derive(Deserialize, DartSignal)]
+pub struct ValidateConfigFile {
+    pub path: String,
+}
+
+// Dart → Rust：校验通过后保存配置文件
+#[derive(Deserialize, DartSignal)]
+pub struct SaveConfigValidated {
+    pub path: String,
+    pub content: String,
+}
+
+// 配置文件校验状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, SignalPiece)]
+pub enum ConfigFileStatus {
+    // 已完成校验（可能包含问题）
+    Validated,
+    NotFound,
+    PermissionDenied,
+    ReadFailed,
+    ParseFailed,
+}
+
+// 单条校验问题
+#[derive(Serialize, SignalPiece)]
+pub struct ConfigIssue {
+    pub is_error: bool,
+    pub category: String,
+    pub location: String,
+    pub message: String,
+}
+
+// Rust → Dart：配置文件校验结果
+#[derive(Serialize, RustSignal)]
+pub struct ValidateConfigFileResult {
+    pub path: String,
+    pub status: ConfigFileStatus,
+    // 是否存在错误级别的问题
+    pub has_errors: bool,
+    pub issues: Vec<ConfigIssue>,
+    // 文件读取或解析失败的原因
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：校验并保存配置的结果
+#[derive(Serialize, RustSignal)]
+pub struct SaveConfigValidatedResult {
+    pub path: String,
+    pub is_saved: bool,
+    pub has_errors: bool,
+    pub issues: Vec<ConfigIssue>,
+    // 原文件的备份路径（原文件不存在时为 None）
+    pub backup_path: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// 保存结果
+#[derive(Debug)]
+pub enum SaveOutcome {
+    // 已写入，附带校验警告与备份路径
+    Saved {
+        issues: Vec<ValidationIssue>,
+        backup_path: Option<PathBuf>,
+    },
+    // 存在错误级别问题，未写入
+    Rejected {
+        issues: Vec<ValidationIssue>,
+    },
+}
+
+impl SaveConfigValidated {
+    pub fn handle(self) -> SaveConfigValidatedResult {
+        log::info!("校验并保存配置文件：{}", self.path);
+
+        let mut result = SaveConfigValidatedResult {
+            path: self.path.clone(),
+            is_saved: false,
+            has_errors: true,
+            issues: Vec::new(),
+            backup_path: None,
+            error_message: None,
+        };
+        match save_config_validated(Path::new(&self.path), &self.content) {
+            Ok(SaveOutcome::Saved {
+                issues,
+                backup_path,
+            }) => {
+                log::info!("配置文件已保存：{}", self.path);
+                result.is_saved = true;
+                result.has_errors = false;
+                result.issues = issues.into_iter().map(ConfigIssue::from).collect();
+                result.backup_path = backup_path.map(|path| path.display().to_string());
+            }
+            Ok(SaveOutcome::Rejected { issues }) => {
+                log::warn!("配置校验未通过，未保存：{}", self.path);
+                result.issues = issues.into_iter().map(ConfigIssue::from).collect();
+            }
+            Err(e) => {
+                log::error!("保存配置文件失败：{}", e);
+                result.error_message = Some(e);
+            }
+        }
+        result
+    }
+}
+
+// 校验配置文本，通过后经临时文件原子替换目标文件，原文件保留为 .bak
+pub fn save_config_validated(path: &Path, content: &str) -> Result<SaveOutcome, String> {
+    let report = ConfigValidator::validate_content(content)?;
+    report.log_issues();
+    if report.has_errors() {
+        return Ok(SaveOutcome::Rejected {
+            issues: report.issues,
+        });
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("无效的配置文件路径：{}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+    std::fs::write(&temp_path, content).map_err(|e| format!("写入临时文件失败：{}", e))?;
+
+    let backup_path = if path.exists() {
+        let backup_path = path.with_file_name(format!("{}.bak", file_name));
+        if let Err(e) = std::fs::copy(path, &backup_path) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(format!("备份原配置失败：{}", e));
+        }
+        Some(backup_path)
+    } else {
+        None
+    };
+
+    if let Err(e) = std::fs::rename(&temp_path, path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(format!("替换配置文件失败：{}", e));
+    }
+
+    Ok(SaveOutcome::Saved {
+        issues: report.issues,
+        backup_path,
+    })
+}
+
+impl ValidateConfigFile {
+    pub fn handle(self) -> ValidateConfigFileResult {
+        log::debug!("校验配置文件：{}", self.path);
+
+        match ConfigValidator::validate_file(Path::new(&self.path)) {
+            Ok(report) => {
+                report.log_issues();
+                ValidateConfigFileResult {
+                    path: self.path,
+                    status: ConfigFileStatus::Validated,
+                    has_errors: report.has_errors(),
+                    issues: report.issues.into_iter().map(ConfigIssue::from).collect(),
+                    error_message: None,
+                }
+            }
+            Err(e) => {
+                log::warn!("{}", e);
+                let status = match e {
+                    ConfigFileError::NotFound(_) => ConfigFileStatus::NotFound,
+                    ConfigFileError::PermissionDenied(_) => ConfigFileStatus::PermissionDenied,
+                    ConfigFileError::Read(_) => ConfigFileStatus::ReadFailed,
+                    ConfigFileError::Parse(_) => ConfigFileStatus::ParseFailed,
+                };
+                ValidateConfigFileResult {
+                    path: self.path,
+                    status,
+                    has_errors: true,
+                    issues: Vec::new(),
+                    error_message: Some(e.to_string()),
+                }
+            }
+        }
+    }
+}
+
+impl From<ValidationIssue> for ConfigIssue {
+    fn from(issue: ValidationIssue) -> Self {
+        Self {
+            is_error: issue.severity == IssueSeverity::Error,
+            category: issue.category.to_string(),
+            location: issue.location,
+            message: issue.message,
+        }
+    }
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = ValidateConfigFile::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            // 文件读取与解析为阻塞操作
+            tokio::task::spawn_blocking(move || {
+                message.handle().send_signal_to_dart();
+            });
+        }
+    });
+
+    spawn(async {
+        let receiver = SaveConfigValidated::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::task::spawn_blocking(move || {
+                message.handle().send_signal_to_dart();
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_CONFIG: &str = r#"
+proxies:
+  - {name: HK-01, type: ss, server: hk.example.com, port: 8388, cipher: aes-128-gcm, password: p}
+proxy-groups:
+  - {name: PROXY, type: select, proxies: [HK-01]}
+rules:
+  - MATCH,PROXY
+"#;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "stelliberty-save-config-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap_or_else(|e| panic!("创建目录失败：{}", e));
+        dir
+    }
+
+    #[test]
+    fn test_reject_invalid_config() {
+        let dir = temp_dir("reject");
+        let path = dir.join("config.yaml");
+        std::fs::write(&path, VALID_CONFIG).unwrap_or_else(|e| panic!("写入失败：{}", e));
+
+        // url-test 组缺少测速地址为错误级别问题
+        let invalid = VALID_CONFIG.replace("type: select", "type: url-test");
+        let outcome = save_config_validated(&path, &invalid);
+        assert!(matches!(outcome, Ok(SaveOutcome::Rejected { ref issues }) if !issues.is_empty()));
+        assert!(save_config_validated(&path, "proxies: [").is_err());
+
+        // 原文件不变，也不产生备份或临时文件
+        assert_eq!(
+            std::fs::read_to_string(&path).ok().as_deref(),
+            Some(VALID_CONFIG)
+        );
+        assert!(!dir.join("config.yaml.bak").exists());
+        assert!(!dir.join(".config.yaml.tmp").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_accept_valid_config() {
+        let dir = temp_dir("accept");
+        let path = dir.join("config.yaml");
+
+        // 首次保存没有原文件可备份
+        let outcome = save_config_validated(&path, VALID_CONFIG);
+        assert!(matches!(
+            outcome,
+            Ok(SaveOutcome::Saved {
+                backup_path: None,
+                ..
+            })
+        ));
+
+        let updated = VALID_CONFIG.replace("MATCH,PROXY", "MATCH,DIRECT");
+        let outcome = save_config_validated(&path, &updated);
+        let backup_path = dir.join("config.yaml.bak");
+        assert!(
+            matches!(outcome, Ok(SaveOutcome::Saved { backup_path: Some(ref backup), .. }) if *backup == backup_path)
+        );
+        assert_eq!(std::fs::read_to_string(&path).ok(), Some(updated));
+        assert_eq!(
+            std::fs::read_to_string(&backup_path).ok().as_deref(),
+            Some(VALID_CONFIG)
+        );
+        assert!(!dir.join(".config.yaml.tmp").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
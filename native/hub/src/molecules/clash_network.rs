@@ -1,23 +1,34 @@
 // Clash 网络管理分子模块
 
+pub mod allow_lan;
 pub mod connection;
 pub mod handlers;
 pub mod ipc_client;
+pub mod mode;
+pub mod selections;
+pub mod update_queue;
 pub mod ws_client;
 
+pub use allow_lan::{AllowLanResult, GetAllowLan, SetAllowLan};
 #[cfg(windows)]
 pub use connection::connect_named_pipe;
 #[cfg(unix)]
 pub use connection::connect_unix_socket;
 pub use handlers::{
     IpcDeleteRequest, IpcGetRequest, IpcLogData, IpcPatchRequest, IpcPostRequest, IpcPutRequest,
-    IpcResponse, IpcTrafficData, StartLogStream, StartTrafficStream, StopLogStream,
-    StopTrafficStream, StreamResult, cleanup_all_network_resources, init_rest_api_listeners,
-    internal_ipc_get, start_connection_pool_health_check,
+    IpcResponse, IpcTrafficData, NotifyNetworkChanged, SetIpcKeepAlive, StartLogStream,
+    StartTrafficStream, StopLogStream, StopTrafficStream, StreamReconnectStatus, StreamResult,
+    cleanup_all_network_resources, init_rest_api_listeners, internal_ipc_get, internal_ipc_request,
+    start_connection_pool_health_check,
 };
 pub use ipc_client::{HttpResponse, IpcClient};
-pub use ws_client::WebSocketClient;
+pub use mode::{ClashModeResult, GetClashMode, SetClashMode};
+pub use selections::{GetGroupSelections, GroupSelection, GroupSelectionsResult};
+pub use ws_client::{ReconnectBackoff, ReconnectEvent, WebSocketClient};
 
 pub fn init_listeners() {
     init_rest_api_listeners();
+    allow_lan::init();
+    mode::init();
+    selections::init();
 }
@@ -0,0 +1,227 @@
+// 局域网共享：通过控制器 /configs 接口设置 allow-lan 与 bind-address，
+// 返回核心确认后的状态，无需手动编辑配置文件。
+
+use super::handlers::{internal_ipc_get, internal_ipc_request};
+use rinf::{DartSignal, RustSignal};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+// 监听所有网卡
+const BIND_ANY: &str = "*";
+
+const FIREWALL_WARNING: &str = "已允许局域网连接：同一网络中的设备均可使用此代理，请确认系统防火墙已放行代理端口并仅在可信网络中开启";
+
+// Dart → Rust：设置局域网共享
+#[derive(Deserialize, DartSignal)]
+pub struct SetAllowLan {
+    pub is_enabled: bool,
+    // 监听地址（IP 或 *），为空时保持核心当前值
+    pub bind_address: String,
+}
+
+// Dart → Rust：查询局域网共享状态
+#[derive(Deserialize, DartSignal)]
+pub struct GetAllowLan;
+
+// Rust → Dart：局域网共享状态（以核心返回为准）
+#[derive(Serialize, RustSignal)]
+pub struct AllowLanResult {
+    pub is_enabled: Option<bool>,
+    pub bind_address: Option<String>,
+    // 开启共享时的防火墙提醒
+    pub warning: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// 核心中的局域网共享配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowLanState {
+    pub is_enabled: bool,
+    pub bind_address: String,
+}
+
+impl SetAllowLan {
+    pub async fn handle(self) {
+        log::info!(
+            "设置局域网共享：{}，监听地址：{}",
+            self.is_enabled,
+            self.bind_address
+        );
+
+        let result = set_allow_lan(self.is_enabled, &self.bind_address).await;
+        if let Err(ref e) = result {
+            log::error!("设置局域网共享失败：{}", e);
+        }
+        AllowLanResult::from(result).send_signal_to_dart();
+    }
+}
+
+impl GetAllowLan {
+    pub async fn handle(self) {
+        let result = get_allow_lan().await;
+        if let Err(ref e) = result {
+            log::warn!("查询局域网共享状态失败：{}", e);
+        }
+        AllowLanResult::from(result).send_signal_to_dart();
+    }
+}
+
+impl From<Result<AllowLanState, String>> for AllowLanResult {
+    fn from(result: Result<AllowLanState, String>) -> Self {
+        match result {
+            Ok(state) => Self {
+                is_enabled: Some(state.is_enabled),
+                bind_address: Some(state.bind_address),
+                warning: state.is_enabled.then(|| FIREWALL_WARNING.to_string()),
+                error_message: None,
+            },
+            Err(e) => Self {
+                is_enabled: None,
+                bind_address: None,
+                warning: None,
+                error_message: Some(e),
+            },
+        }
+    }
+}
+
+async fn set_allow_lan(is_enabled: bool, bind_address: &str) -> Result<AllowLanState, String> {
+    let bind_address = normalize_bind_address(bind_address)?;
+    if is_enabled {
+        log::warn!("{}", FIREWALL_WARNING);
+    }
+
+    internal_ipc_request(
+        "PATCH",
+        "/configs",
+        Some(&build_allow_lan_body(is_enabled, bind_address.as_deref())),
+    )
+    .await?;
+
+    let confirmed = get_allow_lan().await?;
+    let is_bind_applied = bind_address
+        .as_deref()
+        .is_none_or(|address| address == confirmed.bind_address);
+    if confirmed.is_enabled != is_enabled || !is_bind_applied {
+        return Err(format!(
+            "核心未应用局域网共享设置：当前 allow-lan={}，bind-address={}",
+            confirmed.is_enabled, confirmed.bind_address
+        ));
+    }
+    log::info!(
+        "局域网共享已设置：{}，监听地址：{}",
+        confirmed.is_enabled,
+        confirmed.bind_address
+    );
+    Ok(confirmed)
+}
+
+async fn get_allow_lan() -> Result<AllowLanState, String> {
+    parse_allow_lan(&internal_ipc_get("/configs").await?)
+}
+
+// 校验监听地址：必须是 IP 地址或 *，为空表示不修改
+pub fn normalize_bind_address(bind_address: &str) -> Result<Option<String>, String> {
+    let address = bind_address.trim();
+    if address.is_empty() {
+        return Ok(None);
+    }
+    if address == BIND_ANY {
+        return Ok(Some(BIND_ANY.to_string()));
+    }
+    address
+        .parse::<IpAddr>()
+        .map(|ip| Some(ip.to_string()))
+        .map_err(|_| format!("无效的监听地址：{}（应为 IP 地址或 *）", address))
+}
+
+// 构造 PATCH /configs 请求体（未指定监听地址时只修改 allow-lan）
+pub fn build_allow_lan_body(is_enabled: bool, bind_address: Option<&str>) -> String {
+    let mut body = serde_json::json!({ "allow-lan": is_enabled });
+    if let Some(address) = bind_address {
+        body["bind-address"] = serde_json::json!(address);
+    }
+    body.to_string()
+}
+
+// 从 GET /configs 响应中读取 allow-lan 与 bind-address
+fn parse_allow_lan(body: &str) -> Result<AllowLanState, String> {
+    let config: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("解析核心配置失败：{}", e))?;
+    let is_enabled = config["allow-lan"]
+        .as_bool()
+        .ok_or_else(|| "核心配置中缺少 allow-lan 字段".to_string())?;
+    let bind_address = config["bind-address"]
+        .as_str()
+        .unwrap_or(BIND_ANY)
+        .to_string();
+    Ok(AllowLanState {
+        is_enabled,
+        bind_address,
+    })
+}
+
+pub fn init() {
+    tokio::spawn(async {
+        let receiver = SetAllowLan::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle().await;
+        }
+    });
+
+    tokio::spawn(async {
+        let receiver = GetAllowLan::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_allow_lan_body() {
+        assert_eq!(
+            build_allow_lan_body(true, Some("*")),
+            r#"{"allow-lan":true,"bind-address":"*"}"#
+        );
+        assert_eq!(build_allow_lan_body(false, None), r#"{"allow-lan":false}"#);
+        let body = build_allow_lan_body(true, Some("192.168.1.10"));
+        let value: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+        assert_eq!(
+            value,
+            serde_json::json!({ "allow-lan": true, "bind-address": "192.168.1.10" })
+        );
+
+        assert_eq!(
+            parse_allow_lan(r#"{"allow-lan":true,"bind-address":"0.0.0.0"}"#),
+            Ok(AllowLanState {
+                is_enabled: true,
+                bind_address: "0.0.0.0".to_string()
+            })
+        );
+        assert!(parse_allow_lan(r#"{"mode":"rule"}"#).is_err());
+    }
+
+    #[test]
+    fn test_normalize_bind_address() {
+        assert_eq!(normalize_bind_address("*"), Ok(Some("*".to_string())));
+        assert_eq!(
+            normalize_bind_address(" 192.168.1.10 "),
+            Ok(Some("192.168.1.10".to_string()))
+        );
+        assert_eq!(
+            normalize_bind_address("0:0:0:0:0:0:0:1"),
+            Ok(Some("::1".to_string()))
+        );
+        assert_eq!(normalize_bind_address(""), Ok(None));
+
+        assert!(normalize_bind_address("localhost").is_err());
+        assert!(normalize_bind_address("192.168.1.256").is_err());
+        assert!(normalize_bind_address("0.0.0.0:7890").is_err());
+        // 拒绝可能注入额外字段的输入
+        assert!(normalize_bind_address(r#"*", "allow-lan": true"#).is_err());
+    }
+}
@@ -2,12 +2,14 @@
 // 内置重试、连接池与必要的降噪日志策略。
 
 use super::ipc_client::IpcClient;
-use super::ws_client::WebSocketClient;
+use super::update_queue::{UpdateOutcome, UpdateQueue};
+use super::ws_client::{ReconnectBackoff, ReconnectEvent, WebSocketClient};
 use once_cell::sync::Lazy;
 use rinf::{DartSignal, RustSignal};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::{RwLock, Semaphore};
 
@@ -55,6 +57,16 @@ pub struct IpcDeleteRequest {
     pub path: String,
 }
 
+// Dart → Rust：系统网络发生变化（切换 Wi-Fi/有线、开关 VPN 等）
+#[derive(Deserialize, DartSignal)]
+pub struct NotifyNetworkChanged;
+
+// Dart → Rust：开关连接池保活（健康检查时向空闲连接发送保活请求）
+#[derive(Deserialize, DartSignal)]
+pub struct SetIpcKeepAlive {
+    pub is_enabled: bool,
+}
+
 // Rust → Dart：IPC 请求响应
 #[derive(Serialize, RustSignal)]
 pub struct IpcResponse {
@@ -68,6 +80,8 @@ pub struct IpcResponse {
     pub is_successful: bool,
     // 错误消息（如果有）
     pub error_message: Option<String>,
+    // 是否被同一路径的后续请求取代而未执行
+    pub is_superseded: bool,
 }
 
 // WebSocket 流式数据
@@ -124,6 +138,38 @@ pub struct StreamResult {
     pub error_message: Option<String>,
 }
 
+// Rust → Dart：流式连接断线重连状态
+#[derive(Serialize, RustSignal)]
+pub struct StreamReconnectStatus {
+    // 流名称：traffic 或 logs
+    pub stream: String,
+    // 第几次重连尝试
+    pub attempt: u32,
+    // 本次重连是否已成功
+    pub is_reconnected: bool,
+    // 断开或上次重连失败的原因
+    pub error_message: Option<String>,
+}
+
+// 将重连事件转发到 Dart 层
+fn report_reconnect(stream: &str, event: ReconnectEvent) {
+    let status = match event {
+        ReconnectEvent::Reconnecting { attempt, reason } => StreamReconnectStatus {
+            stream: stream.to_string(),
+            attempt,
+            is_reconnected: false,
+            error_message: Some(reason),
+        },
+        ReconnectEvent::Reconnected { attempt } => StreamReconnectStatus {
+            stream: stream.to_string(),
+            attempt,
+            is_reconnected: true,
+            error_message: None,
+        },
+    };
+    status.send_signal_to_dart();
+}
+
 // 检查错误是否为 IPC 尚未就绪（启动时的正常情况）
 fn is_ipc_not_ready_error(error_msg: &str) -> bool {
     // Windows：os error 2（文件不存在）。
@@ -175,6 +221,7 @@ async fn handle_ipc_request_with_retry(
                     body: String::new(),
                     is_successful: false,
                     error_message: Some(format!("获取连接失败：{}", e)),
+                    is_superseded: false,
                 }
                 .send_signal_to_dart();
                 return;
@@ -207,6 +254,7 @@ async fn handle_ipc_request_with_retry(
                     body: response.body,
                     is_successful: true,
                     error_message: None,
+                    is_superseded: false,
                 }
                 .send_signal_to_dart();
                 return;
@@ -246,6 +294,7 @@ async fn handle_ipc_request_with_retry(
                     body: String::new(),
                     is_successful: false,
                     error_message: Some(format!("IPC 请求失败：{}", e)),
+                    is_superseded: false,
                 }
                 .send_signal_to_dart();
                 return;
@@ -258,6 +307,12 @@ async fn handle_ipc_request_with_retry(
 const MAX_POOL_SIZE: usize = 30; // 连接池上限
 const IDLE_TIMEOUT_MS: u64 = 35000; // 35 秒空闲超时（大于健康检查周期 30 秒，避免批量延迟测试期间连接被误删）
 const MAX_CONCURRENT_CONNECTIONS: usize = 20; // IPC 最大并发连接创建数（限制新连接创建速度，避免冲击 IPC 服务器）
+const NETWORK_CHANGE_WARM_CONNECTIONS: usize = 3; // 网络变化后预热的连接数
+const KEEP_ALIVE_PATH: &str = "/version"; // 保活请求路径（核心最轻量的接口）
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(2); // 保活请求超时
+
+// 连接池保活开关：部分系统的管道超时短于空闲超时，开启后健康检查会验证连接确实可用
+static POOL_KEEP_ALIVE_ENABLED: AtomicBool = AtomicBool::new(false);
 
 // 连接包装器
 struct PooledConnection {
@@ -275,9 +330,20 @@ impl PooledConnection {
 
         let mut buf = [0u8; 1];
         match self.conn.try_read(&mut buf) {
-            Ok(0) => false,                                      // 连接已关闭
-            Ok(_) => true, // 有数据可读（不应发生，但连接有效）
+            Ok(0) => false, // 连接已关闭
+            // 空闲连接上出现残留数据，说明上一次响应未读完或服务端已重启，不能复用
+            Ok(_) => false,
             Err(e) if e.kind() == ErrorKind::WouldBlock => true, // 无数据但连接正常
+            // 服务端重启后旧管道断开（半开连接），必须丢弃
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::BrokenPipe | ErrorKind::NotConnected | ErrorKind::ConnectionReset
+                ) =>
+            {
+                log::trace!("检测到已断开的 IPC 连接（服务端可能已重启）：{}", e);
+                false
+            }
             Err(_) => false, // 其他错误表示连接失效
         }
     }
@@ -291,8 +357,8 @@ static IPC_CONNECTION_POOL: Lazy<Arc<RwLock<VecDeque<PooledConnection>>>> =
 static CONNECTION_SEMAPHORE: Lazy<Arc<Semaphore>> =
     Lazy::new(|| Arc::new(Semaphore::new(MAX_CONCURRENT_CONNECTIONS)));
 
-// 配置更新信号量（限制并发为 1，防止竞态条件）
-static CONFIG_UPDATE_SEMAPHORE: Lazy<Arc<Semaphore>> = Lazy::new(|| Arc::new(Semaphore::new(1)));
+// 配置更新队列（串行执行，同一路径只保留最新的待执行请求）
+static CONFIG_UPDATE_QUEUE: Lazy<UpdateQueue> = Lazy::new(UpdateQueue::new);
 
 // 启动连接池健康检查（30 秒间隔）
 pub fn start_connection_pool_health_check() {
@@ -303,37 +369,15 @@ pub fn start_connection_pool_health_check() {
         loop {
             interval.tick().await;
 
-            // 健康检查（使用 try_write 避免阻塞）
-            let mut pool = match IPC_CONNECTION_POOL.try_write() {
-                Ok(pool) => pool,
-                Err(_) => {
-                    log::trace!("健康检查：连接池繁忙，跳过本轮");
-                    continue;
-                }
-            };
-
-            let initial_count = pool.len();
-            if initial_count == 0 {
-                continue; // 连接池为空，跳过
-            }
-
-            log::trace!("开始连接池健康检查（当前 {} 个连接）", initial_count);
-
-            // 检查并移除失效连接（时间过期 + 连接状态检查）
-            pool.retain(|pooled_conn| {
-                pooled_conn.last_used.elapsed() < Duration::from_millis(IDLE_TIMEOUT_MS)
-                    && pooled_conn.is_valid()
-            });
-
-            let removed = initial_count - pool.len();
-            if removed > 0 {
-                log::info!(
-                    "健康检查：移除{}个过期连接（剩余{}个）",
+            let keep_alive = POOL_KEEP_ALIVE_ENABLED.load(Ordering::Relaxed);
+            match health_check_pass(&IPC_CONNECTION_POOL, keep_alive).await {
+                None => log::trace!("健康检查：连接池繁忙，跳过本轮"),
+                Some(0) => log::trace!("健康检查完成：所有连接正常"),
+                Some(removed) => log::info!(
+                    "健康检查：移除{}个失效连接（剩余{}个）",
                     removed,
-                    pool.len()
-                );
-            } else {
-                log::trace!("健康检查完成：所有连接正常（{}个）", pool.len());
+                    IPC_CONNECTION_POOL.read().await.len()
+                ),
             }
         }
     });
@@ -341,6 +385,70 @@ pub fn start_connection_pool_health_check() {
     log::info!("连接池健康检查已启动（30 秒间隔）");
 }
 
+// 单轮健康检查：移除过期或失效的连接；启用保活时再向剩余连接发送保活请求，只保留有响应的连接。
+// 返回移除的连接数，连接池繁忙时返回 None
+async fn health_check_pass(
+    pool: &RwLock<VecDeque<PooledConnection>>,
+    keep_alive: bool,
+) -> Option<usize> {
+    // 使用 try_write 避免阻塞正常请求
+    let (initial_count, idle) = {
+        let mut pool = pool.try_write().ok()?;
+        let initial_count = pool.len();
+
+        // 检查并移除失效连接（时间过期 + 连接状态检查）
+        pool.retain(|pooled_conn| {
+            pooled_conn.last_used.elapsed() < Duration::from_millis(IDLE_TIMEOUT_MS)
+                && pooled_conn.is_valid()
+        });
+        if !keep_alive || pool.is_empty() {
+            return Some(initial_count - pool.len());
+        }
+        (initial_count, pool.drain(..).collect::<Vec<_>>())
+    };
+
+    // 保活请求期间不持有锁，新请求会直接创建连接
+    let mut live = Vec::with_capacity(idle.len());
+    for pooled in idle {
+        if let Some(pooled) = keep_alive_connection(pooled).await {
+            live.push(pooled);
+        }
+    }
+    let live_count = live.len();
+
+    let mut pool = pool.write().await;
+    for pooled in live {
+        if pool.len() < MAX_POOL_SIZE {
+            pool.push_back(pooled);
+        }
+    }
+    Some(initial_count - live_count)
+}
+
+// 发送保活请求，连接有正常响应时返回该连接。保活请求不计入使用时间，
+// 否则连接永远不会因空闲超时被移除
+async fn keep_alive_connection(pooled: PooledConnection) -> Option<PooledConnection> {
+    let last_used = pooled.last_used;
+    let request = IpcClient::request_with_connection("GET", KEEP_ALIVE_PATH, None, pooled.conn);
+    match tokio::time::timeout(KEEP_ALIVE_TIMEOUT, request).await {
+        Ok(Ok((response, conn))) if (200..300).contains(&response.status_code) => {
+            Some(PooledConnection { conn, last_used })
+        }
+        Ok(Ok((response, _))) => {
+            log::trace!("保活请求返回 HTTP {}，丢弃连接", response.status_code);
+            None
+        }
+        Ok(Err(e)) => {
+            log::trace!("保活请求失败，丢弃连接：{}", e);
+            None
+        }
+        Err(_) => {
+            log::trace!("保活请求超时，丢弃连接");
+            None
+        }
+    }
+}
+
 // 连接获取通用逻辑宏（消除 Windows 和 Unix 平台的重复代码）
 macro_rules! acquire_connection_with_retry {
     ($connect_fn:expr, $conn_type:literal) => {{
@@ -489,6 +597,38 @@ pub async fn cleanup_ipc_connection_pool() -> usize {
     count
 }
 
+// 预热连接池：新建连接放入池中（不超过上限），返回新增数量
+pub async fn warm_up_ipc_connection_pool(count: usize) -> usize {
+    warm_up_connection_pool(&IpcClient::default_ipc_path(), count).await
+}
+
+async fn warm_up_connection_pool(ipc_path: &str, count: usize) -> usize {
+    let mut warmed = 0;
+    while warmed < count {
+        if IPC_CONNECTION_POOL.read().await.len() >= MAX_POOL_SIZE {
+            break;
+        }
+
+        #[cfg(windows)]
+        let result = super::connection::connect_named_pipe(ipc_path).await;
+        #[cfg(unix)]
+        let result = super::connection::connect_unix_socket(ipc_path).await;
+
+        match result {
+            Ok(conn) => {
+                release_connection(conn).await;
+                warmed += 1;
+            }
+            Err(e) => {
+                // 核心未运行时无需预热
+                log::debug!("预热 IPC 连接失败：{}", e);
+                break;
+            }
+        }
+    }
+    warmed
+}
+
 // 清理 WebSocket 客户端（在 Clash 停止时调用）
 pub async fn cleanup_ws_client() -> bool {
     let mut client_guard = WS_CLIENT.write().await;
@@ -515,6 +655,30 @@ pub async fn cleanup_all_network_resources() {
     );
 }
 
+// 网络变化处理器：旧连接可能已静默失效，主动重建连接池
+impl NotifyNetworkChanged {
+    pub async fn handle(self) {
+        let removed = cleanup_ipc_connection_pool().await;
+        let warmed = warm_up_ipc_connection_pool(NETWORK_CHANGE_WARM_CONNECTIONS).await;
+        log::info!(
+            "网络已变化，重建 IPC 连接池（丢弃 {} 个，预热 {} 个）",
+            removed,
+            warmed
+        );
+    }
+}
+
+// 连接池保活开关处理器
+impl SetIpcKeepAlive {
+    pub fn handle(self) {
+        POOL_KEEP_ALIVE_ENABLED.store(self.is_enabled, Ordering::Relaxed);
+        log::info!(
+            "IPC 连接池保活已{}",
+            if self.is_enabled { "开启" } else { "关闭" }
+        );
+    }
+}
+
 // GET 请求处理器
 impl IpcGetRequest {
     pub fn handle(self) {
@@ -540,35 +704,35 @@ impl IpcPostRequest {
     }
 }
 
-// PUT 请求处理器（需要获取配置更新信号量）
+// PUT 请求处理器（经配置更新队列串行执行）
 impl IpcPutRequest {
     pub fn handle(self) {
         tokio::spawn(async move {
-            // 获取配置更新信号量，防止并发配置修改
-            let _permit = match CONFIG_UPDATE_SEMAPHORE.acquire().await {
-                Ok(permit) => permit,
-                Err(e) => {
-                    log::error!("获取配置更新信号量失败：{}", e);
-                    IpcResponse {
-                        request_id: self.request_id,
-                        status_code: 0,
-                        body: String::new(),
-                        is_successful: false,
-                        error_message: Some(format!("获取配置更新信号量失败：{}", e)),
-                    }
-                    .send_signal_to_dart();
-                    return;
-                }
-            };
+            let outcome = CONFIG_UPDATE_QUEUE
+                .run(&self.path, || {
+                    handle_ipc_request_with_retry(
+                        "PUT",
+                        &self.path,
+                        self.body.as_deref(),
+                        self.request_id,
+                        false,
+                    )
+                })
+                .await;
 
-            handle_ipc_request_with_retry(
-                "PUT",
-                &self.path,
-                self.body.as_deref(),
-                self.request_id,
-                false,
-            )
-            .await;
+            // 被取代的请求也要响应，避免 Dart 侧一直等待
+            if outcome == UpdateOutcome::Superseded {
+                log::debug!("IPC PUT 请求已被后续请求取代：{}", self.path);
+                IpcResponse {
+                    request_id: self.request_id,
+                    status_code: 0,
+                    body: String::new(),
+                    is_successful: false,
+                    error_message: Some("已被同一路径的后续请求取代".to_string()),
+                    is_superseded: true,
+                }
+                .send_signal_to_dart();
+            }
         });
     }
 }
@@ -640,6 +804,20 @@ pub fn init_rest_api_listeners() {
         }
     });
 
+    tokio::spawn(async {
+        let receiver = SetIpcKeepAlive::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle();
+        }
+    });
+
+    tokio::spawn(async {
+        let receiver = NotifyNetworkChanged::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle().await;
+        }
+    });
+
     // WebSocket 流式数据监听器
     tokio::spawn(async {
         let receiver = StartTrafficStream::get_dart_signal_receiver();
@@ -697,16 +875,21 @@ impl StartTrafficStream {
         let client = WS_CLIENT.read().await;
         if let Some(ws_client) = client.as_ref() {
             match ws_client
-                .connect("/traffic", |json_value| {
-                    // 解析流量数据
-                    if let Some(obj) = json_value.as_object() {
-                        let upload = obj.get("up").and_then(|v| v.as_u64()).unwrap_or(0);
-                        let download = obj.get("down").and_then(|v| v.as_u64()).unwrap_or(0);
-
-                        // 发送到 Dart 层
-                        IpcTrafficData { upload, download }.send_signal_to_dart();
-                    }
-                })
+                .connect_with_reconnect(
+                    "/traffic",
+                    ReconnectBackoff::default(),
+                    |json_value| {
+                        // 解析流量数据
+                        if let Some(obj) = json_value.as_object() {
+                            let upload = obj.get("up").and_then(|v| v.as_u64()).unwrap_or(0);
+                            let download = obj.get("down").and_then(|v| v.as_u64()).unwrap_or(0);
+
+                            // 发送到 Dart 层
+                            IpcTrafficData { upload, download }.send_signal_to_dart();
+                        }
+                    },
+                    |event| report_reconnect("traffic", event),
+                )
                 .await
             {
                 Ok(connection_id) => {
@@ -771,24 +954,29 @@ impl StartLogStream {
         let client = WS_CLIENT.read().await;
         if let Some(ws_client) = client.as_ref() {
             match ws_client
-                .connect("/logs?level=info", |json_value| {
-                    // 解析日志数据
-                    if let Some(obj) = json_value.as_object() {
-                        let log_type = obj
-                            .get("type")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("info")
-                            .to_string();
-                        let payload = obj
-                            .get("payload")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        // 发送到 Dart 层
-                        IpcLogData { log_type, payload }.send_signal_to_dart();
-                    }
-                })
+                .connect_with_reconnect(
+                    "/logs?level=info",
+                    ReconnectBackoff::default(),
+                    |json_value| {
+                        // 解析日志数据
+                        if let Some(obj) = json_value.as_object() {
+                            let log_type = obj
+                                .get("type")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("info")
+                                .to_string();
+                            let payload = obj
+                                .get("payload")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("")
+                                .to_string();
+
+                            // 发送到 Dart 层
+                            IpcLogData { log_type, payload }.send_signal_to_dart();
+                        }
+                    },
+                    |event| report_reconnect("logs", event),
+                )
                 .await
             {
                 Ok(connection_id) => {
@@ -919,11 +1107,20 @@ impl StopMemoryStream {
 // 内部 IPC GET 接口：直接使用连接池发送请求。
 // 用于批量延迟测试等内部调用场景。
 pub async fn internal_ipc_get(path: &str) -> Result<String, String> {
+    internal_ipc_request("GET", path, None).await
+}
+
+// 内部 IPC 请求接口：返回 2xx 响应体，其余状态码视为错误
+pub async fn internal_ipc_request(
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+) -> Result<String, String> {
     // 从连接池获取连接
     let ipc_conn = acquire_connection().await?;
 
     // 使用连接发送请求
-    match IpcClient::request_with_connection("GET", path, None, ipc_conn).await {
+    match IpcClient::request_with_connection(method, path, body, ipc_conn).await {
         Ok((response, ipc_conn)) => {
             // 归还连接
             release_connection(ipc_conn).await;
@@ -937,3 +1134,110 @@ pub async fn internal_ipc_get(path: &str) -> Result<String, String> {
         Err(e) => Err(e),
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::net::UnixListener;
+
+    #[tokio::test]
+    async fn test_network_change_rebuilds_pool() {
+        let path = std::env::temp_dir()
+            .join(format!("stelliberty_pool_test_{}.sock", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let _ = std::fs::remove_file(&path);
+        let listener =
+            UnixListener::bind(&path).unwrap_or_else(|e| panic!("无法监听测试 socket：{}", e));
+
+        // 保持服务端连接打开
+        let server = tokio::spawn(async move {
+            let mut streams = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                streams.push(stream);
+            }
+        });
+
+        assert_eq!(warm_up_connection_pool(&path, 2).await, 2);
+        assert_eq!(IPC_CONNECTION_POOL.read().await.len(), 2);
+
+        assert_eq!(cleanup_ipc_connection_pool().await, 2);
+        assert!(IPC_CONNECTION_POOL.read().await.is_empty());
+
+        assert_eq!(warm_up_connection_pool(&path, 3).await, 3);
+        assert_eq!(IPC_CONNECTION_POOL.read().await.len(), 3);
+
+        cleanup_ipc_connection_pool().await;
+        server.abort();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // 创建测试连接，服务端收到请求后按 respond 决定回复或直接断开
+    fn stub_connection(respond: bool, idle: Duration) -> PooledConnection {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (client, mut server) =
+            UnixStream::pair().unwrap_or_else(|e| panic!("无法创建测试连接：{}", e));
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            while let Ok(n) = server.read(&mut buf).await {
+                if n == 0 || !respond {
+                    break;
+                }
+                let body = r#"{"version":"test"}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                if server.write_all(response.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+        PooledConnection {
+            conn: client,
+            last_used: Instant::now()
+                .checked_sub(idle)
+                .unwrap_or_else(Instant::now),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_keep_alive() {
+        let pool = || {
+            RwLock::new(VecDeque::from([
+                stub_connection(true, Duration::from_secs(20)),
+                // 连接仍打开但不再响应请求（服务端收到请求后断开）
+                stub_connection(false, Duration::from_secs(20)),
+                // 空闲超时的连接不发送保活请求
+                stub_connection(true, Duration::from_millis(IDLE_TIMEOUT_MS + 1000)),
+            ]))
+        };
+
+        // 未开启保活时只按空闲时间与连接状态判断
+        let without_keep_alive = pool();
+        assert_eq!(health_check_pass(&without_keep_alive, false).await, Some(1));
+        assert_eq!(without_keep_alive.read().await.len(), 2);
+
+        // 开启保活后丢弃无响应的连接，保留的连接仍按原使用时间计算空闲
+        let with_keep_alive = pool();
+        assert_eq!(health_check_pass(&with_keep_alive, true).await, Some(2));
+        let remaining = with_keep_alive.read().await;
+        assert_eq!(remaining.len(), 1);
+        assert!(remaining[0].last_used.elapsed() >= Duration::from_secs(20));
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_does_not_prevent_idle_eviction() {
+        // 保活成功的连接在空闲超时后仍会被移除
+        let pool = RwLock::new(VecDeque::from([stub_connection(
+            true,
+            Duration::from_millis(IDLE_TIMEOUT_MS - 500),
+        )]));
+        assert_eq!(health_check_pass(&pool, true).await, Some(0));
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert_eq!(health_check_pass(&pool, true).await, Some(1));
+        assert!(pool.read().await.is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+// 出站模式：校验模式名后通过控制器 /configs 接口切换与查询，返回核心确认后的模式。
+
+use super::handlers::{internal_ipc_get, internal_ipc_request};
+use rinf::{DartSignal, RustSignal};
+use serde::{Deserialize, Serialize};
+
+// 所有核心均支持的出站模式
+const BASIC_MODES: [&str; 3] = ["rule", "global", "direct"];
+
+// 仅 Clash Premium 支持的脚本模式
+const SCRIPT_MODE: &str = "script";
+
+// Dart → Rust：切换出站模式
+#[derive(Deserialize, DartSignal)]
+pub struct SetClashMode {
+    pub mode: String,
+}
+
+// Dart → Rust：查询当前出站模式
+#[derive(Deserialize, DartSignal)]
+pub struct GetClashMode;
+
+// Rust → Dart：当前出站模式（以核心返回为准）
+#[derive(Serialize, RustSignal)]
+pub struct ClashModeResult {
+    pub mode: Option<String>,
+    pub error_message: Option<String>,
+}
+
+impl SetClashMode {
+    pub async fn handle(self) {
+        log::info!("切换出站模式：{}", self.mode);
+
+        let result = set_mode(&self.mode).await;
+        if let Err(ref e) = result {
+            log::error!("切换出站模式失败：{}", e);
+        }
+        ClashModeResult::from(result).send_signal_to_dart();
+    }
+}
+
+impl GetClashMode {
+    pub async fn handle(self) {
+        let result = get_mode().await;
+        if let Err(ref e) = result {
+            log::warn!("查询出站模式失败：{}", e);
+        }
+        ClashModeResult::from(result).send_signal_to_dart();
+    }
+}
+
+impl From<Result<String, String>> for ClashModeResult {
+    fn from(result: Result<String, String>) -> Self {
+        match result {
+            Ok(mode) => Self {
+                mode: Some(mode),
+                error_message: None,
+            },
+            Err(e) => Self {
+                mode: None,
+                error_message: Some(e),
+            },
+        }
+    }
+}
+
+async fn set_mode(mode: &str) -> Result<String, String> {
+    let is_script_supported = if mode.trim().eq_ignore_ascii_case(SCRIPT_MODE) {
+        is_script_mode_supported().await?
+    } else {
+        false
+    };
+    let mode = normalize_mode(mode, is_script_supported)?;
+
+    internal_ipc_request("PATCH", "/configs", Some(&build_mode_body(mode))).await?;
+
+    let confirmed = get_mode().await?;
+    if confirmed != mode {
+        return Err(format!(
+            "核心未应用出站模式：请求 {}，当前 {}",
+            mode, confirmed
+        ));
+    }
+    log::info!("出站模式已切换：{}", confirmed);
+    Ok(confirmed)
+}
+
+async fn get_mode() -> Result<String, String> {
+    parse_config_mode(&internal_ipc_get("/configs").await?)
+}
+
+// 脚本模式仅 Clash Premium 支持（/version 返回 premium: true）
+async fn is_script_mode_supported() -> Result<bool, String> {
+    let body = internal_ipc_get("/version").await?;
+    let version: serde_json::Value =
+        serde_json::from_str(&body).map_err(|e| format!("解析核心版本失败：{}", e))?;
+    Ok(version["premium"].as_bool().unwrap_or(false))
+}
+
+// 校验并规范化模式名（忽略大小写与首尾空白）
+pub fn normalize_mode(mode: &str, is_script_supported: bool) -> Result<&'static str, String> {
+    let normalized = mode.trim().to_ascii_lowercase();
+    if let Some(mode) = BASIC_MODES.iter().find(|m| **m == normalized) {
+        return Ok(mode);
+    }
+    if normalized == SCRIPT_MODE {
+        return if is_script_supported {
+            Ok(SCRIPT_MODE)
+        } else {
+            Err("当前核心不支持 script 模式".to_string())
+        };
+    }
+    Err(format!(
+        "无效的出站模式：{}（可选：{}）",
+        mode,
+        BASIC_MODES.join("/")
+    ))
+}
+
+// 构造 PATCH /configs 请求体
+pub fn build_mode_body(mode: &str) -> String {
+    serde_json::json!({ "mode": mode }).to_string()
+}
+
+// 从 GET /configs 响应中读取模式（核心可能返回大写形式）
+fn parse_config_mode(body: &str) -> Result<String, String> {
+    let config: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("解析核心配置失败：{}", e))?;
+    config["mode"]
+        .as_str()
+        .map(|mode| mode.to_ascii_lowercase())
+        .ok_or_else(|| "核心配置中缺少 mode 字段".to_string())
+}
+
+pub fn init() {
+    tokio::spawn(async {
+        let receiver = SetClashMode::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle().await;
+        }
+    });
+
+    tokio::spawn(async {
+        let receiver = GetClashMode::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_mode_body() {
+        for mode in BASIC_MODES {
+            let body = build_mode_body(mode);
+            let value: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+            assert_eq!(value, serde_json::json!({ "mode": mode }));
+        }
+        assert_eq!(build_mode_body("global"), r#"{"mode":"global"}"#);
+
+        assert_eq!(
+            parse_config_mode(r#"{"mode":"Rule","port":0}"#),
+            Ok("rule".to_string())
+        );
+        assert!(parse_config_mode(r#"{"port":0}"#).is_err());
+    }
+
+    #[test]
+    fn test_normalize_mode() {
+        assert_eq!(normalize_mode("rule", false), Ok("rule"));
+        assert_eq!(normalize_mode(" Global ", false), Ok("global"));
+        assert_eq!(normalize_mode("DIRECT", false), Ok("direct"));
+        assert_eq!(normalize_mode("script", true), Ok("script"));
+
+        assert!(normalize_mode("script", false).is_err());
+        assert!(normalize_mode("", false).is_err());
+        assert!(normalize_mode("proxy", true).is_err());
+        // 拒绝可能注入额外字段的输入
+        assert!(normalize_mode(r#"rule", "allow-lan": true"#, false).is_err());
+    }
+}
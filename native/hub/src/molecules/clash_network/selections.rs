@@ -0,0 +1,137 @@
+// 代理组当前选择：从控制器 /proxies 接口提取各 Selector 组的当前节点与可选节点，
+// 核心重启后 UI 据此高亮已选节点，无需在 Dart 端解析完整的 /proxies 响应。
+
+use super::handlers::internal_ipc_get;
+use rinf::{DartSignal, RustSignal, SignalPiece};
+use serde::{Deserialize, Serialize};
+
+// 手动选择类型的代理组
+const SELECTOR_TYPE: &str = "Selector";
+
+// Dart → Rust：查询各代理组的当前选择
+#[derive(Deserialize, DartSignal)]
+pub struct GetGroupSelections;
+
+// 单个代理组的选择状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SignalPiece)]
+pub struct GroupSelection {
+    pub name: String,
+    // 当前选中的节点，核心尚未选择时为空
+    pub now: Option<String>,
+    pub all: Vec<String>,
+}
+
+// Rust → Dart：各代理组的当前选择（按组名排序）
+#[derive(Serialize, RustSignal)]
+pub struct GroupSelectionsResult {
+    pub selections: Vec<GroupSelection>,
+    pub error_message: Option<String>,
+}
+
+impl GetGroupSelections {
+    pub async fn handle(self) {
+        let result = match get_group_selections().await {
+            Ok(selections) => GroupSelectionsResult {
+                selections,
+                error_message: None,
+            },
+            Err(e) => {
+                log::warn!("查询代理组选择失败：{}", e);
+                GroupSelectionsResult {
+                    selections: Vec::new(),
+                    error_message: Some(e),
+                }
+            }
+        };
+        result.send_signal_to_dart();
+    }
+}
+
+async fn get_group_selections() -> Result<Vec<GroupSelection>, String> {
+    parse_group_selections(&internal_ipc_get("/proxies").await?)
+}
+
+// 从 GET /proxies 响应中提取 Selector 组
+pub fn parse_group_selections(body: &str) -> Result<Vec<GroupSelection>, String> {
+    let response: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("解析代理列表失败：{}", e))?;
+    let proxies = response["proxies"]
+        .as_object()
+        .ok_or_else(|| "代理列表中缺少 proxies 字段".to_string())?;
+
+    let mut selections: Vec<GroupSelection> = proxies
+        .iter()
+        .filter(|(_, proxy)| proxy["type"].as_str() == Some(SELECTOR_TYPE))
+        .map(|(name, proxy)| GroupSelection {
+            name: name.clone(),
+            now: proxy["now"]
+                .as_str()
+                .filter(|now| !now.is_empty())
+                .map(str::to_string),
+            all: proxy["all"]
+                .as_array()
+                .map(|all| {
+                    all.iter()
+                        .filter_map(|node| node.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect();
+    selections.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(selections)
+}
+
+pub fn init() {
+    tokio::spawn(async {
+        let receiver = GetGroupSelections::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXIES: &str = r#"{
+        "proxies": {
+            "DIRECT": {"name": "DIRECT", "type": "Direct", "history": []},
+            "HK 01": {"name": "HK 01", "type": "Shadowsocks", "history": []},
+            "JP 01": {"name": "JP 01", "type": "Trojan", "history": []},
+            "Proxy": {"name": "Proxy", "type": "Selector", "now": "JP 01", "all": ["HK 01", "JP 01", "DIRECT"]},
+            "Auto": {"name": "Auto", "type": "URLTest", "now": "HK 01", "all": ["HK 01", "JP 01"]},
+            "Empty": {"name": "Empty", "type": "Selector", "now": "", "all": []},
+            "Fresh": {"name": "Fresh", "type": "Selector", "all": ["HK 01"]},
+            "GLOBAL": {"name": "GLOBAL", "type": "Selector", "now": "Proxy", "all": ["Proxy", "Auto", "DIRECT"]}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_group_selections() {
+        let selections =
+            parse_group_selections(PROXIES).unwrap_or_else(|e| panic!("解析失败：{}", e));
+
+        // 只保留 Selector 组，按组名排序
+        let names: Vec<&str> = selections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Empty", "Fresh", "GLOBAL", "Proxy"]);
+
+        let proxy = &selections[3];
+        assert_eq!(proxy.now.as_deref(), Some("JP 01"));
+        assert_eq!(proxy.all, vec!["HK 01", "JP 01", "DIRECT"]);
+
+        // 尚未选择（空字符串或缺少 now 字段）时为 None
+        assert_eq!(selections[0].now, None);
+        assert!(selections[0].all.is_empty());
+        assert_eq!(selections[1].now, None);
+        assert_eq!(selections[1].all, vec!["HK 01"]);
+    }
+
+    #[test]
+    fn test_parse_group_selections_invalid() {
+        assert!(parse_group_selections("not json").is_err());
+        assert!(parse_group_selections(r#"{"message":"Unauthorized"}"#).is_err());
+        assert_eq!(parse_group_selections(r#"{"proxies":{}}"#), Ok(Vec::new()));
+    }
+}
@@ -0,0 +1,145 @@
+// 配置更新队列：串行执行配置更新，同一路径排队中的请求只保留最新的一个。
+// 快速连续切换（如反复切换代理组节点）时跳过中间状态，避免核心应用过期配置。
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// 排队结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum UpdateOutcome<T> {
+    // 已执行
+    Applied(T),
+    // 排队期间被同一路径的后续请求取代，未执行
+    Superseded,
+}
+
+pub struct UpdateQueue {
+    // 执行锁：同一时间只执行一个更新
+    running: tokio::sync::Mutex<()>,
+    // 各路径最新请求的序号
+    latest: Mutex<HashMap<String, u64>>,
+    next_ticket: AtomicU64,
+}
+
+impl UpdateQueue {
+    pub fn new() -> Self {
+        Self {
+            running: tokio::sync::Mutex::new(()),
+            latest: Mutex::new(HashMap::new()),
+            next_ticket: AtomicU64::new(0),
+        }
+    }
+
+    // 登记请求并等待执行；轮到时若已有同一路径的更新请求，则放弃执行
+    pub async fn run<T, F, Fut>(&self, key: &str, update: F) -> UpdateOutcome<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        self.latest_map().insert(key.to_string(), ticket);
+
+        let _guard = self.running.lock().await;
+        {
+            let mut latest = self.latest_map();
+            if latest.get(key) != Some(&ticket) {
+                return UpdateOutcome::Superseded;
+            }
+            // 开始执行后不再被取代，之后到达的请求会排在本次之后执行
+            latest.remove(key);
+        }
+
+        UpdateOutcome::Applied(update().await)
+    }
+
+    fn latest_map(&self) -> std::sync::MutexGuard<'_, HashMap<String, u64>> {
+        // 锁内不会 panic，中毒时沿用数据即可
+        self.latest
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for UpdateQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::{Mutex as AsyncMutex, oneshot};
+
+    #[tokio::test]
+    async fn test_only_latest_pending_update_executes() {
+        let queue = Arc::new(UpdateQueue::new());
+        let executed = Arc::new(AsyncMutex::new(Vec::new()));
+
+        // 第一个更新执行中，阻塞队列
+        let (release, blocked) = oneshot::channel::<()>();
+        let first = {
+            let queue = queue.clone();
+            let executed = executed.clone();
+            tokio::spawn(async move {
+                queue
+                    .run("/proxies/GLOBAL", || async move {
+                        let _ = blocked.await;
+                        executed.lock().await.push(0);
+                    })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        // 排队三个同一路径的更新
+        let mut pending = Vec::new();
+        for value in 1..=3 {
+            let queue = queue.clone();
+            let executed = executed.clone();
+            pending.push(tokio::spawn(async move {
+                queue
+                    .run("/proxies/GLOBAL", || async move {
+                        executed.lock().await.push(value);
+                    })
+                    .await
+            }));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let _ = release.send(());
+        assert_eq!(
+            first.await.unwrap_or(UpdateOutcome::Superseded),
+            UpdateOutcome::Applied(())
+        );
+
+        let mut outcomes = Vec::new();
+        for handle in pending {
+            outcomes.push(handle.await.unwrap_or(UpdateOutcome::Superseded));
+        }
+        assert_eq!(
+            outcomes,
+            vec![
+                UpdateOutcome::Superseded,
+                UpdateOutcome::Superseded,
+                UpdateOutcome::Applied(()),
+            ]
+        );
+        assert_eq!(*executed.lock().await, vec![0, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_different_paths_are_not_coalesced() {
+        let queue = UpdateQueue::new();
+        let (a, b) = tokio::join!(
+            queue.run("/proxies/A", || async { "a" }),
+            queue.run("/proxies/B", || async { "b" }),
+        );
+        assert_eq!(a, UpdateOutcome::Applied("a"));
+        assert_eq!(b, UpdateOutcome::Applied("b"));
+    }
+}
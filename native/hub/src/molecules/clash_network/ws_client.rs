@@ -1,12 +1,13 @@
 // WebSocket over IPC 客户端
-// 通过 Named Pipe/Unix Socket 建立 WebSocket 连接
+// 通过 Named Pipe/Unix Socket 建立 WebSocket 连接，流式连接可在断开后按退避自动重连
 
 use super::connection;
 use base64::Engine;
-use futures_util::stream::StreamExt;
+use futures_util::stream::{SplitStream, StreamExt};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio_tungstenite::{client_async, tungstenite::protocol::Message};
+use std::time::Duration;
+use tokio_tungstenite::{WebSocketStream, client_async, tungstenite::protocol::Message};
 
 #[cfg(unix)]
 use tokio::net::UnixStream;
@@ -14,6 +15,14 @@ use tokio::net::UnixStream;
 #[cfg(windows)]
 use tokio::net::windows::named_pipe::NamedPipeClient;
 
+#[cfg(unix)]
+type IpcStream = UnixStream;
+
+#[cfg(windows)]
+type IpcStream = NamedPipeClient;
+
+type WsReader = SplitStream<WebSocketStream<IpcStream>>;
+
 // HTTP Request 构建器 (来自 http crate)
 use http::Request;
 use http::header::{CONNECTION, HOST, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_VERSION, UPGRADE};
@@ -21,6 +30,58 @@ use http::header::{CONNECTION, HOST, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_VERSION, U
 // WebSocket 连接 ID
 pub type ConnectionId = u32;
 
+// 重连退避：每次失败后等待时间翻倍，直到上限；连接成功后重置
+#[derive(Debug, Clone)]
+pub struct ReconnectBackoff {
+    initial: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_secs(30))
+    }
+}
+
+impl ReconnectBackoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            attempt: 0,
+        }
+    }
+
+    // 开始一次新的重连尝试，返回尝试前需要等待的时间
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self
+            .initial
+            .saturating_mul(1 << self.attempt.min(16))
+            .min(self.max);
+        self.attempt += 1;
+        delay
+    }
+
+    // 当前是第几次重连尝试（从 1 开始，未重连时为 0）
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+// 重连过程中的状态变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectEvent {
+    // 连接已断开，即将进行第 attempt 次重连
+    Reconnecting { attempt: u32, reason: String },
+    // 第 attempt 次重连成功
+    Reconnected { attempt: u32 },
+}
+
 // WebSocket 客户端
 pub struct WebSocketClient {
     ipc_path: String,
@@ -50,28 +111,119 @@ impl WebSocketClient {
 
     // 连接到 WebSocket 端点并开始接收消息。
     // 返回连接 ID，用于后续管理与断开连接。
-    pub async fn connect<F>(&self, endpoint: &str, on_message: F) -> Result<ConnectionId, String>
+    pub async fn connect<F>(
+        &self,
+        endpoint: &str,
+        mut on_message: F,
+    ) -> Result<ConnectionId, String>
     where
         F: Fn(serde_json::Value) + Send + 'static,
     {
         log::debug!("开始建立 WebSocket 连接：{}", endpoint);
 
-        // 1. 分配连接 ID
-        let connection_id = {
-            let mut id_guard = self.next_connection_id.lock().await;
-            let id = *id_guard;
-            *id_guard += 1;
-            id
-        };
+        let connection_id = self.allocate_connection_id().await;
+        let reader = Self::open(&self.ipc_path, endpoint).await?;
+        log::info!("WebSocket 连接建立成功[{}]：{}", connection_id, endpoint);
+
+        // 启动消息接收循环
+        let connections = self.connections.clone();
+        let handle = tokio::spawn(async move {
+            Self::receive_messages(connection_id, reader, &mut on_message).await;
+
+            // 连接结束后，从连接表中移除
+            let mut conns = connections.lock().await;
+            conns.remove(&connection_id);
+        });
+
+        // 存储连接句柄
+        {
+            let mut conns = self.connections.lock().await;
+            conns.insert(connection_id, handle);
+        }
+
+        Ok(connection_id)
+    }
+
+    // 连接到 WebSocket 端点，断开后按退避自动重连并重新订阅同一端点。
+    // 首次连接失败直接返回错误；之后持续重连，直到调用 disconnect。
+    pub async fn connect_with_reconnect<F, S>(
+        &self,
+        endpoint: &str,
+        mut backoff: ReconnectBackoff,
+        mut on_message: F,
+        on_reconnect: S,
+    ) -> Result<ConnectionId, String>
+    where
+        F: Fn(serde_json::Value) + Send + 'static,
+        S: Fn(ReconnectEvent) + Send + 'static,
+    {
+        let connection_id = self.allocate_connection_id().await;
+
+        let mut reader = Self::open(&self.ipc_path, endpoint).await?;
+        log::info!(
+            "WebSocket 连接建立成功[{}]：{}（断线自动重连）",
+            connection_id,
+            endpoint
+        );
 
-        // 2. 连接到 IPC 端点
+        let ipc_path = self.ipc_path.clone();
+        let endpoint = endpoint.to_string();
+        let handle = tokio::spawn(async move {
+            loop {
+                let mut reason =
+                    Self::receive_messages(connection_id, reader, &mut on_message).await;
+
+                reader = loop {
+                    let delay = backoff.next_delay();
+                    let attempt = backoff.attempt();
+                    log::info!(
+                        "WebSocket 连接已断开[{}]，{}ms 后进行第 {} 次重连：{}",
+                        connection_id,
+                        delay.as_millis(),
+                        attempt,
+                        reason
+                    );
+                    on_reconnect(ReconnectEvent::Reconnecting { attempt, reason });
+                    tokio::time::sleep(delay).await;
+
+                    match Self::open(&ipc_path, &endpoint).await {
+                        Ok(reader) => {
+                            log::info!("WebSocket 重连成功[{}]：{}", connection_id, endpoint);
+                            on_reconnect(ReconnectEvent::Reconnected { attempt });
+                            backoff.reset();
+                            break reader;
+                        }
+                        Err(e) => reason = e,
+                    }
+                };
+            }
+        });
+
+        {
+            let mut conns = self.connections.lock().await;
+            conns.insert(connection_id, handle);
+        }
+
+        Ok(connection_id)
+    }
+
+    async fn allocate_connection_id(&self) -> ConnectionId {
+        let mut id_guard = self.next_connection_id.lock().await;
+        let id = *id_guard;
+        *id_guard += 1;
+        id
+    }
+
+    // 连接 IPC 端点并完成 WebSocket 握手，返回读取流
+    async fn open(ipc_path: &str, endpoint: &str) -> Result<WsReader, String> {
+        // 1. 连接到 IPC 端点
         #[cfg(windows)]
-        let stream = self.connect_windows().await?;
+        let stream = connection::connect_named_pipe(ipc_path).await?;
 
         #[cfg(unix)]
-        let stream = self.connect_unix().await?;
+        let stream = connection::connect_unix_socket(ipc_path).await?;
 
-        // 3. 构造 WebSocket 握手请求（使用 http::Request）
+        // 2. 构造 WebSocket 握手请求（使用 http::Request）
         // 关键：使用 ws:// scheme 以通过 tungstenite 的 URI 验证
         let uri = format!("ws://localhost{}", endpoint);
         log::trace!("构造 URI：{}", uri);
@@ -90,81 +242,74 @@ impl WebSocketClient {
 
         log::trace!("发送 WebSocket 握手请求：{}", endpoint);
 
-        // 4. 使用 client_async 建立 WebSocket 连接
+        // 3. 使用 client_async 建立 WebSocket 连接
         let (ws_stream, _) = client_async(request, stream)
             .await
             .map_err(|e| format!("WebSocket 握手失败：{}", e))?;
 
-        log::info!("WebSocket 连接建立成功[{}]：{}", connection_id, endpoint);
-
-        // 5. 分离读写流
-        let (_writer, mut reader) = ws_stream.split();
+        // 4. 分离读写流
+        let (_writer, reader) = ws_stream.split();
+        Ok(reader)
+    }
 
-        // 6. 启动消息接收循环
-        let connections = self.connections.clone();
-        let handle = tokio::spawn(async move {
-            log::trace!("WebSocket 消息接收循环已启动 [{}]", connection_id);
-
-            while let Some(message) = reader.next().await {
-                match message {
-                    Ok(Message::Text(text)) => {
-                        // 解析 JSON 消息
-                        match serde_json::from_str::<serde_json::Value>(&text) {
-                            Ok(json_value) => {
-                                log::trace!(
-                                    "WebSocket 收到消息[{}]：{}bytes",
-                                    connection_id,
-                                    text.len()
-                                );
-                                on_message(json_value);
-                            }
-                            Err(e) => {
-                                log::error!(
-                                    "WebSocket 消息 JSON 解析失败[{}]：{}",
-                                    connection_id,
-                                    e
-                                );
-                            }
+    // 消息接收循环，返回连接结束的原因
+    async fn receive_messages<F>(
+        connection_id: ConnectionId,
+        mut reader: WsReader,
+        on_message: &mut F,
+    ) -> String
+    where
+        F: Fn(serde_json::Value),
+    {
+        log::trace!("WebSocket 消息接收循环已启动 [{}]", connection_id);
+
+        let reason = loop {
+            let Some(message) = reader.next().await else {
+                break "连接已断开".to_string();
+            };
+            match message {
+                Ok(Message::Text(text)) => {
+                    // 解析 JSON 消息
+                    match serde_json::from_str::<serde_json::Value>(&text) {
+                        Ok(json_value) => {
+                            log::trace!(
+                                "WebSocket 收到消息[{}]：{}bytes",
+                                connection_id,
+                                text.len()
+                            );
+                            on_message(json_value);
+                        }
+                        Err(e) => {
+                            log::error!("WebSocket 消息 JSON 解析失败[{}]：{}", connection_id, e);
                         }
                     }
-                    Ok(Message::Close(close_frame)) => {
-                        log::info!("WebSocket 连接关闭[{}]：{:?}", connection_id, close_frame);
-                        break;
-                    }
-                    Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => {
-                        // Ping/Pong 由 tokio-tungstenite 自动处理
-                    }
-                    Ok(Message::Binary(data)) => {
-                        log::debug!(
-                            "WebSocket 收到二进制消息[{}]：{}bytes",
-                            connection_id,
-                            data.len()
-                        );
-                    }
-                    Ok(Message::Frame(_)) => {
-                        // 忽略原始帧
-                    }
-                    Err(e) => {
-                        log::error!("WebSocket 消息读取错误[{}]：{}", connection_id, e);
-                        break;
-                    }
+                }
+                Ok(Message::Close(close_frame)) => {
+                    log::info!("WebSocket 连接关闭[{}]：{:?}", connection_id, close_frame);
+                    break "连接被对端关闭".to_string();
+                }
+                Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => {
+                    // Ping/Pong 由 tokio-tungstenite 自动处理
+                }
+                Ok(Message::Binary(data)) => {
+                    log::debug!(
+                        "WebSocket 收到二进制消息[{}]：{}bytes",
+                        connection_id,
+                        data.len()
+                    );
+                }
+                Ok(Message::Frame(_)) => {
+                    // 忽略原始帧
+                }
+                Err(e) => {
+                    log::error!("WebSocket 消息读取错误[{}]：{}", connection_id, e);
+                    break format!("消息读取错误：{}", e);
                 }
             }
+        };
 
-            log::debug!("WebSocket 消息接收循环已结束[{}]", connection_id);
-
-            // 连接结束后，从连接表中移除
-            let mut conns = connections.lock().await;
-            conns.remove(&connection_id);
-        });
-
-        // 存储连接句柄
-        {
-            let mut conns = self.connections.lock().await;
-            conns.insert(connection_id, handle);
-        }
-
-        Ok(connection_id)
+        log::debug!("WebSocket 消息接收循环已结束[{}]", connection_id);
+        reason
     }
 
     // 断开指定的 WebSocket 连接
@@ -197,18 +342,6 @@ impl WebSocketClient {
             log::info!("所有 WebSocket 连接已断开");
         }
     }
-
-    // Windows: 连接到 Named Pipe
-    #[cfg(windows)]
-    async fn connect_windows(&self) -> Result<NamedPipeClient, String> {
-        connection::connect_named_pipe(&self.ipc_path).await
-    }
-
-    // Unix: 连接到 Unix Socket
-    #[cfg(unix)]
-    async fn connect_unix(&self) -> Result<UnixStream, String> {
-        connection::connect_unix_socket(&self.ipc_path).await
-    }
 }
 
 #[cfg(test)]
@@ -238,4 +371,133 @@ mod tests {
         // 验证初始 ID 从 1 开始
         assert_eq!(*client.next_connection_id.blocking_lock(), 1);
     }
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let mut backoff = ReconnectBackoff::new(Duration::from_millis(500), Duration::from_secs(4));
+        assert_eq!(backoff.attempt(), 0);
+
+        let delays: Vec<u128> = (0..6).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, vec![500, 1000, 2000, 4000, 4000, 4000]);
+        assert_eq!(backoff.attempt(), 6);
+
+        // 重连成功后从初始间隔重新开始
+        backoff.reset();
+        assert_eq!(backoff.attempt(), 0);
+        assert_eq!(backoff.next_delay(), Duration::from_millis(500));
+        assert_eq!(backoff.attempt(), 1);
+
+        // 大量失败后不溢出
+        for _ in 0..100 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.next_delay(), Duration::from_secs(4));
+    }
+
+    // 桩服务：第一个连接发送一条消息后断开并停止监听，收到通知后重新监听并接受连接
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_reconnect_after_drop() {
+        use futures_util::SinkExt;
+        use tokio::net::UnixListener;
+        use tokio::sync::mpsc::unbounded_channel;
+
+        let socket_path = std::env::temp_dir().join(format!(
+            "stelliberty-ws-reconnect-{}.sock",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&socket_path);
+
+        async fn serve_once(listener: &UnixListener, payload: &str) -> WebSocketStream<UnixStream> {
+            let (stream, _) = listener
+                .accept()
+                .await
+                .unwrap_or_else(|e| panic!("接受连接失败：{}", e));
+            let mut ws = tokio_tungstenite::accept_async(stream)
+                .await
+                .unwrap_or_else(|e| panic!("握手失败：{}", e));
+            ws.send(Message::text(payload))
+                .await
+                .unwrap_or_else(|e| panic!("发送失败：{}", e));
+            ws
+        }
+
+        let (rebind_tx, mut rebind_rx) = unbounded_channel::<()>();
+        let server_path = socket_path.clone();
+        let listener =
+            UnixListener::bind(&socket_path).unwrap_or_else(|e| panic!("监听失败：{}", e));
+        let server = tokio::spawn(async move {
+            let first = serve_once(&listener, r#"{"up":1}"#).await;
+            drop(listener);
+            let _ = std::fs::remove_file(&server_path);
+            drop(first);
+
+            rebind_rx.recv().await;
+            let listener =
+                UnixListener::bind(&server_path).unwrap_or_else(|e| panic!("监听失败：{}", e));
+            let second = serve_once(&listener, r#"{"up":2}"#).await;
+            // 保持连接直到测试结束
+            std::future::pending::<()>().await;
+            drop(second);
+        });
+
+        let (message_tx, mut message_rx) = unbounded_channel();
+        let (event_tx, mut event_rx) = unbounded_channel();
+        let client = WebSocketClient::new(socket_path.display().to_string());
+        let connection_id = client
+            .connect_with_reconnect(
+                "/traffic",
+                ReconnectBackoff::new(Duration::from_millis(20), Duration::from_millis(200)),
+                move |value| {
+                    let _ = message_tx.send(value["up"].as_u64());
+                },
+                move |event| {
+                    let _ = event_tx.send(event);
+                },
+            )
+            .await
+            .unwrap_or_else(|e| panic!("首次连接失败：{}", e));
+
+        let timeout = Duration::from_secs(5);
+        let next_message = async |rx: &mut tokio::sync::mpsc::UnboundedReceiver<Option<u64>>| {
+            tokio::time::timeout(timeout, rx.recv())
+                .await
+                .ok()
+                .flatten()
+                .flatten()
+        };
+        assert_eq!(next_message(&mut message_rx).await, Some(1));
+
+        // 第 1 次重连时服务不可用，第 2 次前恢复监听
+        let mut attempts = Vec::new();
+        loop {
+            let event = tokio::time::timeout(timeout, event_rx.recv())
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| panic!("未收到重连事件"));
+            match event {
+                ReconnectEvent::Reconnecting { attempt, .. } => {
+                    attempts.push(attempt);
+                    if attempt == 2 {
+                        let _ = rebind_tx.send(());
+                    }
+                }
+                ReconnectEvent::Reconnected { attempt } => {
+                    assert_eq!(attempt, 2);
+                    break;
+                }
+            }
+        }
+        assert_eq!(attempts, vec![1, 2]);
+        assert_eq!(next_message(&mut message_rx).await, Some(2));
+
+        // 停止后不再重连
+        client.disconnect(connection_id).await;
+        server.abort();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(event_rx.try_recv().is_err());
+        assert!(client.connections.lock().await.is_empty());
+        let _ = std::fs::remove_file(&socket_path);
+    }
 }
@@ -1,9 +1,17 @@
 // Clash 进程管理分子模块
 
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+pub mod diagnostics;
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+pub mod geodata;
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+pub mod preflight;
 pub mod process_manager;
 
 #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
 pub mod service_manager;
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+pub mod tun_requirements;
 
 pub use process_manager::{ClashProcessResult, StartClashProcess, StopClashProcess};
 
@@ -15,6 +23,18 @@ pub fn init_listeners() {
 
     #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
     service_manager::init();
+
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    diagnostics::init();
+
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    preflight::init();
+
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    geodata::init();
+
+    #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+    tun_requirements::init();
 }
 
 pub fn cleanup() {
@@ -0,0 +1,331 @@
+// 诊断包：收集服务日志、核心输出、运行配置与版本信息并打包为 zip，便于问题反馈。
+// 单项收集失败不影响打包，失败原因记录在 manifest.json 中。
+
+use super::service_manager::ServiceManager;
+use rinf::{DartSignal, RustSignal};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+// 收集的服务日志行数
+const SERVICE_LOG_LINES: usize = 2000;
+
+// Dart → Rust：创建诊断包
+#[derive(Deserialize, DartSignal)]
+pub struct CreateDiagnosticBundle {
+    pub output_path: String,
+    // 是否脱敏运行配置中的密码、UUID 等字段
+    pub redact_secrets: bool,
+}
+
+// Rust → Dart：诊断包创建结果
+#[derive(Serialize, RustSignal)]
+pub struct DiagnosticBundleResult {
+    pub output_path: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// 打包前收集到的原始数据
+pub struct DiagnosticInputs {
+    pub service_logs: Result<Vec<String>, String>,
+    pub core_output: Result<Vec<String>, String>,
+    // 运行配置文本与脱敏字段数
+    pub running_config: Result<(String, u32), String>,
+    pub versions: BTreeMap<String, String>,
+}
+
+// 收集失败的项目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingItem {
+    pub file: String,
+    pub reason: String,
+}
+
+// 诊断包清单（manifest.json）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleManifest {
+    pub created_at: String,
+    pub redact_secrets: bool,
+    pub redacted_fields: u32,
+    pub versions: BTreeMap<String, String>,
+    pub files: Vec<String>,
+    pub missing: Vec<MissingItem>,
+}
+
+// 诊断包内的单个文件
+pub struct BundleEntry {
+    pub name: String,
+    pub content: Vec<u8>,
+}
+
+impl CreateDiagnosticBundle {
+    pub async fn handle(self) {
+        log::info!("收到创建诊断包请求：{}", self.output_path);
+
+        let inputs = collect_inputs(self.redact_secrets).await;
+        let created_at = chrono::Local::now().to_rfc3339();
+        let (manifest, entries) = assemble_bundle(inputs, self.redact_secrets, created_at);
+        if !manifest.missing.is_empty() {
+            log::warn!(
+                "诊断包缺少 {} 项：{:?}",
+                manifest.missing.len(),
+                manifest.missing
+            );
+        }
+
+        let output_path = self.output_path.clone();
+        let result =
+            tokio::task::spawn_blocking(move || write_zip(Path::new(&output_path), &entries))
+                .await
+                .map_err(|e| format!("写入任务异常：{}", e))
+                .and_then(|result| result);
+
+        let response = match result {
+            Ok(()) => {
+                log::info!("诊断包已创建：{}", self.output_path);
+                DiagnosticBundleResult {
+                    output_path: Some(self.output_path),
+                    error_message: None,
+                }
+            }
+            Err(e) => {
+                log::error!("创建诊断包失败：{}", e);
+                DiagnosticBundleResult {
+                    output_path: None,
+                    error_message: Some(e),
+                }
+            }
+        };
+        response.send_signal_to_dart();
+    }
+}
+
+// 通过服务收集各项数据
+async fn collect_inputs(redact_secrets: bool) -> DiagnosticInputs {
+    let service_manager = ServiceManager::default();
+
+    let mut versions = BTreeMap::new();
+    versions.insert(
+        "platform".to_string(),
+        format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+    );
+    if let Some(version) = ServiceManager::get_bundled_service_version() {
+        versions.insert("bundled_service".to_string(), version);
+    }
+    if let Some(version) = ServiceManager::get_installed_service_version() {
+        versions.insert("installed_service".to_string(), version);
+    }
+    if let Ok(version) = service_manager.running_service_version().await {
+        versions.insert("running_service".to_string(), version);
+    }
+
+    DiagnosticInputs {
+        service_logs: service_manager
+            .recent_service_logs(SERVICE_LOG_LINES)
+            .await
+            .map_err(|e| e.to_string()),
+        core_output: service_manager
+            .recent_core_output()
+            .await
+            .map_err(|e| e.to_string()),
+        running_config: service_manager
+            .export_running_config(redact_secrets)
+            .await
+            .map_err(|e| e.to_string()),
+        versions,
+    }
+}
+
+// 组装诊断包内容：manifest.json 位于首位，其余文件按固定顺序排列
+pub fn assemble_bundle(
+    inputs: DiagnosticInputs,
+    redact_secrets: bool,
+    created_at: String,
+) -> (BundleManifest, Vec<BundleEntry>) {
+    let mut entries = Vec::new();
+    let mut missing = Vec::new();
+    let mut redacted_fields = 0;
+
+    let mut add = |name: &str, content: Result<Vec<u8>, String>| match content {
+        Ok(content) => entries.push(BundleEntry {
+            name: name.to_string(),
+            content,
+        }),
+        Err(reason) => missing.push(MissingItem {
+            file: name.to_string(),
+            reason,
+        }),
+    };
+
+    add(
+        "service.log",
+        inputs.service_logs.map(|lines| join_lines(&lines)),
+    );
+    add(
+        "core_output.log",
+        inputs.core_output.map(|lines| join_lines(&lines)),
+    );
+    add(
+        "running_config.json",
+        inputs.running_config.map(|(config, count)| {
+            redacted_fields = count;
+            config.into_bytes()
+        }),
+    );
+
+    let manifest = BundleManifest {
+        created_at,
+        redact_secrets,
+        redacted_fields,
+        versions: inputs.versions,
+        files: entries.iter().map(|entry| entry.name.clone()).collect(),
+        missing,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest).unwrap_or_default();
+    entries.insert(
+        0,
+        BundleEntry {
+            name: "manifest.json".to_string(),
+            content: manifest_json,
+        },
+    );
+
+    (manifest, entries)
+}
+
+fn join_lines(lines: &[String]) -> Vec<u8> {
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.into_bytes()
+}
+
+// 写入 zip 文件（自动创建父目录）
+fn write_zip(path: &Path, entries: &[BundleEntry]) -> Result<(), String> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败：{}", e))?;
+    }
+    let file = std::fs::File::create(path).map_err(|e| format!("创建文件失败：{}", e))?;
+
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for entry in entries {
+        writer
+            .start_file(entry.name.as_str(), options)
+            .map_err(|e| format!("写入 {} 失败：{}", entry.name, e))?;
+        writer
+            .write_all(&entry.content)
+            .map_err(|e| format!("写入 {} 失败：{}", entry.name, e))?;
+    }
+    writer
+        .finish()
+        .map_err(|e| format!("完成 zip 写入失败：{}", e))?;
+    Ok(())
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = CreateDiagnosticBundle::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("platform".to_string(), "linux-x86_64".to_string()),
+            ("running_service".to_string(), "1.5.2".to_string()),
+        ])
+    }
+
+    fn entry_names(entries: &[BundleEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_assemble_complete_bundle() {
+        let inputs = DiagnosticInputs {
+            service_logs: Ok(vec![
+                "[INFO] 服务启动".to_string(),
+                "[INFO] 收到心跳".to_string(),
+            ]),
+            core_output: Ok(vec![
+                "level=info msg=\"Start initial configuration\"".to_string(),
+            ]),
+            running_config: Ok(("{\"port\": 7890}".to_string(), 3)),
+            versions: versions(),
+        };
+        let (manifest, entries) =
+            assemble_bundle(inputs, true, "2026-01-01T00:00:00+08:00".to_string());
+
+        assert_eq!(
+            entry_names(&entries),
+            vec![
+                "manifest.json",
+                "service.log",
+                "core_output.log",
+                "running_config.json"
+            ]
+        );
+        assert_eq!(
+            manifest.files,
+            vec!["service.log", "core_output.log", "running_config.json"]
+        );
+        assert!(manifest.missing.is_empty());
+        assert_eq!(manifest.redacted_fields, 3);
+        assert_eq!(
+            entries[1].content,
+            "[INFO] 服务启动\n[INFO] 收到心跳\n".as_bytes()
+        );
+
+        let written: serde_json::Value =
+            serde_json::from_slice(&entries[0].content).unwrap_or_default();
+        assert_eq!(written["redact_secrets"], true);
+        assert_eq!(written["versions"]["running_service"], "1.5.2");
+        assert_eq!(written["created_at"], "2026-01-01T00:00:00+08:00");
+    }
+
+    #[test]
+    fn test_assemble_records_missing_items() {
+        let inputs = DiagnosticInputs {
+            service_logs: Ok(Vec::new()),
+            core_output: Err("服务未运行".to_string()),
+            running_config: Err("Clash 未运行".to_string()),
+            versions: versions(),
+        };
+        let (manifest, entries) = assemble_bundle(inputs, false, String::new());
+
+        assert_eq!(entry_names(&entries), vec!["manifest.json", "service.log"]);
+        assert!(entries[1].content.is_empty());
+        assert_eq!(manifest.redacted_fields, 0);
+        assert_eq!(
+            manifest.missing,
+            vec![
+                MissingItem {
+                    file: "core_output.log".to_string(),
+                    reason: "服务未运行".to_string(),
+                },
+                MissingItem {
+                    file: "running_config.json".to_string(),
+                    reason: "Clash 未运行".to_string(),
+                },
+            ]
+        );
+    }
+}
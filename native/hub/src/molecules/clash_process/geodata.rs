@@ -0,0 +1,99 @@
+// 地理数据文件状态：查询 GeoIP/GeoSite 等文件的大小、修改时间与构建时间，
+// UI 据此提示数据已过旧多少天。服务可用时由服务读取其管理的数据目录，否则在本进程内读取。
+
+use super::service_manager::ServiceManager;
+use rinf::{DartSignal, RustSignal, SignalPiece};
+use serde::{Deserialize, Serialize};
+use stelliberty_service::clash::geodata;
+use stelliberty_service::ipc::GeoDataFile;
+
+// Dart → Rust：查询地理数据文件状态
+#[derive(Deserialize, DartSignal)]
+pub struct GetGeoDataInfo {
+    // 数据目录（为空时使用服务最近一次启动核心时的数据目录）
+    pub data_dir: String,
+}
+
+// 单个地理数据文件的状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SignalPiece)]
+pub struct GeoDataFileInfo {
+    pub name: String,
+    pub is_present: bool,
+    pub size: u64,
+    // 修改时间（Unix 秒）
+    pub modified_secs: Option<u64>,
+    // 数据库构建时间（Unix 秒，仅 MMDB 格式可解析）
+    pub build_epoch: Option<u64>,
+    pub database_type: Option<String>,
+    // 距今天数
+    pub age_days: Option<u64>,
+}
+
+// Rust → Dart：地理数据文件状态
+#[derive(Serialize, RustSignal)]
+pub struct GeoDataInfoResult {
+    pub files: Vec<GeoDataFileInfo>,
+    pub error_message: Option<String>,
+}
+
+impl GetGeoDataInfo {
+    pub async fn handle(self) {
+        let files = match ServiceManager::default()
+            .geo_data_info(self.data_dir.clone())
+            .await
+        {
+            Ok(files) => Ok(files),
+            Err(e) if !self.data_dir.is_empty() => {
+                log::debug!("服务查询地理数据失败，改为本地读取：{}", e);
+                Ok(geodata::collect_geo_info(
+                    std::path::Path::new(&self.data_dir),
+                    std::time::SystemTime::now(),
+                ))
+            }
+            Err(e) => Err(e),
+        };
+
+        let result = match files {
+            Ok(files) => GeoDataInfoResult {
+                files: files.into_iter().map(GeoDataFileInfo::from).collect(),
+                error_message: None,
+            },
+            Err(e) => {
+                log::warn!("查询地理数据失败：{}", e);
+                GeoDataInfoResult {
+                    files: Vec::new(),
+                    error_message: Some(e.to_string()),
+                }
+            }
+        };
+        result.send_signal_to_dart();
+    }
+}
+
+impl From<GeoDataFile> for GeoDataFileInfo {
+    fn from(file: GeoDataFile) -> Self {
+        Self {
+            name: file.name,
+            is_present: file.is_present,
+            size: file.size,
+            modified_secs: file.modified_secs,
+            build_epoch: file.build_epoch,
+            database_type: file.database_type,
+            age_days: file.age_days,
+        }
+    }
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = GetGeoDataInfo::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+}
@@ -0,0 +1,186 @@
+// 启动前自检：汇总服务安装与响应状态，以及核心程序、数据目录、运行权限检查结果。
+// 服务可用时由服务进程执行检查（反映核心实际运行的权限），否则在本进程内检查。
+
+use super::service_manager::ServiceManager;
+use rinf::{DartSignal, RustSignal, SignalPiece};
+use serde::{Deserialize, Serialize};
+use stelliberty_service::clash::preflight::{self, SystemProbe};
+use stelliberty_service::ipc::PreflightItem;
+
+// 服务自检项名称
+const ITEM_SERVICE: &str = "service";
+
+// Dart → Rust：执行启动前自检
+#[derive(Deserialize, DartSignal)]
+pub struct RunPreflightCheck {
+    pub core_path: String,
+    pub data_dir: String,
+}
+
+// 单项自检结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SignalPiece)]
+pub struct PreflightCheckItem {
+    pub name: String,
+    pub is_passed: bool,
+    pub detail: String,
+}
+
+// Rust → Dart：启动前自检结果
+#[derive(Serialize, RustSignal)]
+pub struct PreflightCheckResult {
+    pub is_all_passed: bool,
+    pub items: Vec<PreflightCheckItem>,
+}
+
+// 服务探测结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceProbe {
+    pub is_installed: bool,
+    // 服务响应 IPC 时为其版本号
+    pub running_version: Option<String>,
+}
+
+impl RunPreflightCheck {
+    pub async fn handle(self) {
+        log::info!("执行启动前自检");
+
+        let service_manager = ServiceManager::default();
+        let service = ServiceProbe {
+            is_installed: ServiceManager::is_service_registered(),
+            running_version: service_manager.running_service_version().await.ok(),
+        };
+
+        let checks = if service.running_version.is_some() {
+            match service_manager
+                .run_preflight_check(self.core_path.clone(), self.data_dir.clone())
+                .await
+            {
+                Ok(items) => Some(items),
+                Err(e) => {
+                    // 旧版服务不支持自检命令
+                    log::warn!("服务自检失败，改为本地检查：{}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let checks = checks.unwrap_or_else(|| {
+            preflight::run_checks(
+                &SystemProbe { is_service: false },
+                &self.core_path,
+                &self.data_dir,
+            )
+        });
+
+        let items = assemble_items(&service, checks);
+        for item in items.iter().filter(|item| !item.is_passed) {
+            log::warn!("自检未通过[{}]：{}", item.name, item.detail);
+        }
+
+        PreflightCheckResult {
+            is_all_passed: items.iter().all(|item| item.is_passed),
+            items,
+        }
+        .send_signal_to_dart();
+    }
+}
+
+// 组装自检结果：服务状态位于首位，其后为环境检查项
+pub fn assemble_items(
+    service: &ServiceProbe,
+    checks: Vec<PreflightItem>,
+) -> Vec<PreflightCheckItem> {
+    let (is_passed, detail) = match (&service.running_version, service.is_installed) {
+        (Some(version), _) => (true, format!("服务运行中（v{}）", version)),
+        (None, true) => (
+            false,
+            "服务已安装但未响应，请尝试重启或修复服务".to_string(),
+        ),
+        (None, false) => (false, "服务未安装".to_string()),
+    };
+
+    std::iter::once(PreflightCheckItem {
+        name: ITEM_SERVICE.to_string(),
+        is_passed,
+        detail,
+    })
+    .chain(checks.into_iter().map(|item| PreflightCheckItem {
+        name: item.name,
+        is_passed: item.is_passed,
+        detail: item.detail,
+    }))
+    .collect()
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = RunPreflightCheck::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: &str, is_passed: bool) -> PreflightItem {
+        PreflightItem {
+            name: name.to_string(),
+            is_passed,
+            detail: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_assemble_items() {
+        let checks = vec![
+            check(preflight::ITEM_CORE_BINARY, true),
+            check(preflight::ITEM_DATA_DIR, true),
+            check(preflight::ITEM_PRIVILEGES, false),
+        ];
+
+        let running = ServiceProbe {
+            is_installed: true,
+            running_version: Some("1.5.2".to_string()),
+        };
+        let items = assemble_items(&running, checks.clone());
+        let summary: Vec<(&str, bool)> = items
+            .iter()
+            .map(|item| (item.name.as_str(), item.is_passed))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("service", true),
+                ("core_binary", true),
+                ("data_dir", true),
+                ("privileges", false),
+            ]
+        );
+        assert!(items[0].detail.contains("1.5.2"));
+
+        let stopped = ServiceProbe {
+            is_installed: true,
+            running_version: None,
+        };
+        let items = assemble_items(&stopped, checks.clone());
+        assert!(!items[0].is_passed);
+        assert!(items[0].detail.contains("未响应"));
+
+        let missing = ServiceProbe {
+            is_installed: false,
+            running_version: None,
+        };
+        let items = assemble_items(&missing, Vec::new());
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].detail, "服务未安装");
+    }
+}
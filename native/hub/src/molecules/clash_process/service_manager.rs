@@ -1,13 +1,22 @@
 // Clash 服务模式管理：通过 Windows Service/systemd 运行核心进程。
 // 需要提升权限以完成安装、启停与状态查询。
 
+use crate::atoms::elevate::{self, ElevationOutcome};
+use crate::atoms::system_proxy::{self, ProxyResult};
 use crate::molecules::clash_process::process_manager::ClashProcessResult;
 use anyhow::{Context, Result};
-use rinf::{DartSignal, RustSignal};
+use once_cell::sync::Lazy;
+use rinf::{DartSignal, RustSignal, SignalPiece};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use stelliberty_service::ipc::{IpcClient, IpcCommand, IpcResponse};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use stelliberty_service::ipc::{
+    CacheKind, DnsServerReachability, GeoDataFile, IpcClient, IpcCommand, IpcResponse, OrphanCore,
+    PreflightItem, ServiceEvent, ServicePaths,
+};
+use stelliberty_service::service::installer::{RepairAction, plan_repair};
 
 // 服务管理器
 
@@ -28,6 +37,175 @@ pub enum ServiceStatus {
     Unknown,
 }
 
+// 版本信息（服务程序 version --json 的输出）
+#[derive(Deserialize)]
+struct ServiceVersionInfo {
+    version: String,
+}
+
+// 执行服务程序的 version 子命令并解析版本号
+// 旧版服务程序会忽略 --json 参数并输出文本格式，由 parse_version_output 兼容处理
+fn query_service_version(binary_path: &Path, label: &str) -> Option<String> {
+    // Windows 平台使用 CREATE_NO_WINDOW 避免终端窗口闪屏
+    let mut cmd = Command::new(binary_path);
+    cmd.args(["version", "--json"]);
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(e) => {
+            log::error!("执行{} version 命令失败：{}", label, e);
+            return None;
+        }
+    };
+
+    if !output.status.success() {
+        log::error!("{} version 命令返回错误", label);
+        return None;
+    }
+
+    parse_version_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+// 解析 version 命令输出：优先 JSON（{"version":"1.5.0"}），否则回退到 Stelliberty Service v1.5.0
+fn parse_version_output(stdout: &str) -> Option<String> {
+    let stdout = stdout.trim();
+    if let Ok(info) = serde_json::from_str::<ServiceVersionInfo>(stdout) {
+        return Some(info.version);
+    }
+    stdout
+        .strip_prefix("Stelliberty Service v")
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+// 存活检测：服务刚启动时 IPC 可能尚未就绪，已安装的服务有限重试几次
+#[cfg(any(windows, target_os = "linux"))]
+const LIVENESS_ATTEMPTS: usize = 3;
+#[cfg(any(windows, target_os = "linux"))]
+const LIVENESS_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(150);
+#[cfg(any(windows, target_os = "linux"))]
+const LIVENESS_ATTEMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(300);
+
+// 依次尝试存活检测，首次成功立即返回，全部失败返回 None
+#[cfg(any(windows, target_os = "linux"))]
+async fn retry_liveness<T, F, Fut>(mut attempt: F) -> Option<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Option<T>>,
+{
+    for index in 0..LIVENESS_ATTEMPTS {
+        if index > 0 {
+            tokio::time::sleep(LIVENESS_RETRY_INTERVAL).await;
+        }
+        if let Some(value) = attempt().await {
+            return Some(value);
+        }
+        log::debug!(
+            "服务 IPC 未响应（第 {}/{} 次）",
+            index + 1,
+            LIVENESS_ATTEMPTS
+        );
+    }
+    None
+}
+
+// 切换配置最多经历两次“停止 → 启动 → 等待就绪（10 秒）”，客户端超时需覆盖整个过程
+const SWITCH_CONFIG_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 重启后等待新服务实例响应的轮询次数与间隔（最多 10 秒）
+const RESTART_VERIFY_ATTEMPTS: usize = 20;
+const RESTART_VERIFY_INTERVAL: Duration = Duration::from_millis(500);
+
+// 判断响应来自重启后的新实例：服务运行时长不超过重启以来的耗时（留 1 秒取整余量），
+// 否则说明旧进程仍在运行，stop 实际未生效
+pub fn is_restart_complete(service_uptime_secs: u64, elapsed: Duration) -> bool {
+    service_uptime_secs <= elapsed.as_secs() + 1
+}
+
+// 心跳最小发送间隔：服务端 70 秒未收到心跳才判定超时，更密集的心跳只会增加连接开销
+const HEARTBEAT_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+static HEARTBEAT_DEBOUNCER: Lazy<HeartbeatDebouncer> =
+    Lazy::new(|| HeartbeatDebouncer::new(HEARTBEAT_MIN_INTERVAL));
+
+// 心跳去抖：记录上次发送时间，最小间隔内的重复请求直接跳过，
+// 避免 Dart 端异常高频触发时反复新建 IPC 连接
+struct HeartbeatDebouncer {
+    min_interval: Duration,
+    last_sent: Mutex<Option<Instant>>,
+}
+
+impl HeartbeatDebouncer {
+    fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            last_sent: Mutex::new(None),
+        }
+    }
+
+    // 间隔已满足时记录本次发送时间并返回 true（先占位，并发请求只有一个能通过）
+    fn try_acquire(&self) -> bool {
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|e| {
+            log::error!("获取心跳时间锁失败：{}", e);
+            e.into_inner()
+        });
+        let now = Instant::now();
+        if last_sent.is_some_and(|last| now.duration_since(last) < self.min_interval) {
+            return false;
+        }
+        *last_sent = Some(now);
+        true
+    }
+
+    // 按去抖规则执行发送，返回是否实际发送
+    async fn run<F, Fut>(&self, send: F) -> bool
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        if !self.try_acquire() {
+            log::trace!("距上次心跳不足 {:?}，跳过发送", self.min_interval);
+            return false;
+        }
+        send().await;
+        true
+    }
+}
+
+// 服务最近一次上报的路径。服务程序的实际位置以服务为准，
+// 本地推算的路径（path_service）与服务不一致时会找不到已安装的服务程序
+static REPORTED_SERVICE_PATHS: Mutex<Option<ServicePaths>> = Mutex::new(None);
+
+// 私有目录中的服务程序：优先使用服务上报的路径，服务从未连接过时回退到本地推算
+fn private_service_binary() -> PathBuf {
+    REPORTED_SERVICE_PATHS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|paths| PathBuf::from(&paths.binary_path))
+        .unwrap_or_else(crate::atoms::path_service::service_private_binary)
+}
+
+// 提权执行服务程序子命令的参数。私有目录须在提权前按当前用户解析，
+// 提权后以 root 运行时服务程序推算出的是 root 的数据目录
+fn elevated_args(operation: &str, private_binary: &Path) -> Vec<String> {
+    let mut args = vec![operation.to_string()];
+    if matches!(operation, "install" | "repair")
+        && let Some(private_dir) = private_binary.parent()
+    {
+        args.push("--private-dir".to_string());
+        args.push(private_dir.to_string_lossy().into_owned());
+    }
+    args
+}
+
 // 服务管理器
 pub struct ServiceManager {
     ipc_client: IpcClient,
@@ -48,7 +226,7 @@ impl ServiceManager {
 
     // 获取已安装服务的版本号（从私有目录中的服务程序）
     pub fn get_installed_service_version() -> Option<String> {
-        let service_binary_path = crate::atoms::path_service::service_private_binary();
+        let service_binary_path = private_service_binary();
 
         // 检查私有目录中的服务程序是否存在
         if !service_binary_path.exists() {
@@ -59,38 +237,7 @@ impl ServiceManager {
             return None;
         }
 
-        // 执行服务程序的 version 子命令
-        // Windows 平台使用 CREATE_NO_WINDOW 避免终端窗口闪屏
-        let mut cmd = Command::new(&service_binary_path);
-        cmd.arg("version");
-
-        #[cfg(windows)]
-        {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            cmd.creation_flags(CREATE_NO_WINDOW);
-        }
-
-        let output = match cmd.output() {
-            Ok(output) => output,
-            Err(e) => {
-                log::error!("执行私有目录服务程序 version 命令失败：{}", e);
-                return None;
-            }
-        };
-
-        if !output.status.success() {
-            log::error!("私有目录服务程序 version 命令返回错误");
-            return None;
-        }
-
-        // 解析输出：Service v1.5.0
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let version = stdout
-            .trim()
-            .strip_prefix("Stelliberty Service v")
-            .map(|v| v.to_string());
-
+        let version = query_service_version(&service_binary_path, "私有目录服务程序");
         log::debug!("已安装服务版本：{:?}", version);
         version
     }
@@ -108,38 +255,7 @@ impl ServiceManager {
             return None;
         }
 
-        // 执行服务程序的 version 子命令
-        // Windows 平台使用 CREATE_NO_WINDOW 避免终端窗口闪屏
-        let mut cmd = Command::new(&source_service_binary);
-        cmd.arg("version");
-
-        #[cfg(windows)]
-        {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            cmd.creation_flags(CREATE_NO_WINDOW);
-        }
-
-        let output = match cmd.output() {
-            Ok(output) => output,
-            Err(e) => {
-                log::error!("执行服务程序 version 命令失败：{}", e);
-                return None;
-            }
-        };
-
-        if !output.status.success() {
-            log::error!("服务程序 version 命令返回错误");
-            return None;
-        }
-
-        // 解析输出：Service v1.5.0
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let version = stdout
-            .trim()
-            .strip_prefix("Stelliberty Service v")
-            .map(|v| v.to_string());
-
+        let version = query_service_version(&source_service_binary, "服务程序");
         log::debug!("内置服务版本：{:?}", version);
         version
     }
@@ -154,16 +270,19 @@ impl ServiceManager {
                 return ServiceStatus::NotInstalled;
             }
 
-            // 服务已安装，快速检测是否运行（不带重试的 Heartbeat）
-            let is_running = tokio::time::timeout(
-                std::time::Duration::from_millis(300),
-                self.ipc_client.send_command(IpcCommand::Heartbeat),
-            )
+            // 服务已安装，检测是否运行（IPC 可能尚未就绪，有限重试）
+            let is_running = retry_liveness(|| async {
+                tokio::time::timeout(
+                    LIVENESS_ATTEMPT_TIMEOUT,
+                    self.ipc_client.send_command(IpcCommand::Heartbeat),
+                )
+                .await
+                .ok()
+                .and_then(|r| r.ok())
+                .filter(|resp| matches!(resp, IpcResponse::HeartbeatAck))
+            })
             .await
-            .ok()
-            .and_then(|r| r.ok())
-            .map(|resp| matches!(resp, IpcResponse::HeartbeatAck))
-            .unwrap_or(false);
+            .is_some();
 
             if !is_running {
                 log::debug!("服务已安装但未运行");
@@ -206,12 +325,23 @@ impl ServiceManager {
 
                 // 服务已安装，检查是否运行中
                 if Self::is_systemd_service_active() {
-                    // 服务正在运行，尝试 IPC 获取详细状态
-                    if let Ok(IpcResponse::Status {
+                    // 服务正在运行，尝试 IPC 获取详细状态（刚启动时 IPC 可能尚未就绪，有限重试）
+                    let status = retry_liveness(|| async {
+                        tokio::time::timeout(
+                            LIVENESS_ATTEMPT_TIMEOUT,
+                            self.ipc_client.send_command(IpcCommand::GetStatus),
+                        )
+                        .await
+                        .ok()
+                        .and_then(|r| r.ok())
+                    })
+                    .await;
+
+                    if let Some(IpcResponse::Status {
                         is_clash_running: _,
                         clash_pid,
                         service_uptime,
-                    }) = self.ipc_client.send_command(IpcCommand::GetStatus).await
+                    }) = status
                     {
                         if let Some(pid) = clash_pid {
                             ServiceStatus::Running {
@@ -223,7 +353,7 @@ impl ServiceManager {
                             ServiceStatus::Stopped
                         }
                     } else {
-                        // IPC 失败但 systemd 显示 active，可能刚启动
+                        // 重试后 IPC 仍未响应
                         log::debug!("systemd 服务 active，但 IPC 连接失败");
                         ServiceStatus::Stopped
                     }
@@ -294,65 +424,9 @@ impl ServiceManager {
             }
         }
 
-        #[cfg(target_os = "linux")]
-        {
-            // 检查是否已有 root 权限
-            let has_root = nix::unistd::geteuid().is_root();
-
-            if has_root {
-                // 已有 root 权限，直接执行
-                let output = Command::new(&self.service_binary_path)
-                    .arg("install")
-                    .output()
-                    .context("执行安装命令失败")?;
-
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    anyhow::bail!("安装服务失败：{}{}", stderr, stdout);
-                }
-            } else {
-                // 尝试 pkexec 提权
-                let output = Command::new("pkexec")
-                    .arg(&self.service_binary_path)
-                    .arg("install")
-                    .output();
-
-                match output {
-                    Ok(output) if output.status.success() => {
-                        // pkexec 成功
-                    }
-                    Ok(output) => {
-                        // pkexec 执行了但失败
-                        let code = output.status.code().unwrap_or(-1);
-                        if code == 126 || code == 127 {
-                            // 126: 用户取消授权，127: pkexec 未找到
-                            anyhow::bail!("安装失败，请以 sudo 运行应用后重试");
-                        }
-                        let stderr = String::from_utf8_lossy(&output.stderr);
-                        anyhow::bail!("安装失败：{}", stderr.trim());
-                    }
-                    Err(_) => {
-                        // pkexec 命令不存在
-                        anyhow::bail!("安装失败，请以 sudo 运行应用后重试");
-                    }
-                }
-            }
-        }
-
-        #[cfg(target_os = "macos")]
-        {
-            // macOS 使用 osascript 进行图形化提权（由服务程序内部实现）
-            let output = Command::new(&self.service_binary_path)
-                .arg("install")
-                .output()
-                .context("执行安装命令失败")?;
-
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                anyhow::bail!("安装服务失败：{}", stderr);
-            }
-        }
+        // Linux 使用 pkexec、macOS 使用 osascript 提权，命令执行完毕后返回
+        #[cfg(not(windows))]
+        self.run_elevated("install")?;
 
         Ok(())
     }
@@ -361,6 +435,9 @@ impl ServiceManager {
     pub async fn uninstall_service(&self) -> Result<()> {
         log::info!("卸载 Stelliberty Service…");
 
+        // 卸载后服务不再响应，先记录服务实际使用的程序路径，供删除私有目录中的程序
+        self.refresh_service_paths().await;
+
         // 卸载前先清理网络资源（避免 WebSocket 异常断开）
         // 注意：网络资源会通过连接池健康检查自动清理
 
@@ -372,61 +449,8 @@ impl ServiceManager {
             log::info!("服务已卸载（服务进程已自动停止）");
         }
 
-        #[cfg(target_os = "linux")]
-        {
-            // 检查是否已有 root 权限
-            let has_root = nix::unistd::geteuid().is_root();
-
-            if has_root {
-                // 已有 root 权限，直接执行
-                let output = Command::new(&self.service_binary_path)
-                    .arg("uninstall")
-                    .output()
-                    .context("执行卸载命令失败")?;
-
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    anyhow::bail!("卸载服务失败：{}{}", stderr, stdout);
-                }
-            } else {
-                // 尝试 pkexec 提权
-                let output = Command::new("pkexec")
-                    .arg(&self.service_binary_path)
-                    .arg("uninstall")
-                    .output();
-
-                match output {
-                    Ok(output) if output.status.success() => {
-                        // pkexec 成功
-                    }
-                    Ok(output) => {
-                        let code = output.status.code().unwrap_or(-1);
-                        if code == 126 || code == 127 {
-                            anyhow::bail!("卸载失败，请以 sudo 运行应用后重试");
-                        }
-                        let stderr = String::from_utf8_lossy(&output.stderr);
-                        anyhow::bail!("卸载失败：{}", stderr.trim());
-                    }
-                    Err(_) => {
-                        anyhow::bail!("卸载失败，请以 sudo 运行应用后重试");
-                    }
-                }
-            }
-        }
-
-        #[cfg(target_os = "macos")]
-        {
-            let output = Command::new(&self.service_binary_path)
-                .arg("uninstall")
-                .output()
-                .context("执行卸载命令失败")?;
-
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                anyhow::bail!("卸载服务失败：{}", stderr);
-            }
-        }
+        #[cfg(not(windows))]
+        self.run_elevated("uninstall")?;
 
         // 通知 Dart 端核心已停止
         ClashProcessResult {
@@ -444,7 +468,7 @@ impl ServiceManager {
 
     // 删除私有目录中的服务二进制（卸载时调用）
     async fn remove_service_binary_from_private(&self) -> Result<()> {
-        let private_service_binary = crate::atoms::path_service::service_private_binary();
+        let private_service_binary = private_service_binary();
 
         if private_service_binary.exists() {
             log::info!(
@@ -487,68 +511,139 @@ impl ServiceManager {
         Ok(())
     }
 
-    // 以管理员权限运行命令（Windows）
-    #[cfg(windows)]
-    async fn run_elevated_command(&self, operation: &str) -> Result<()> {
-        use windows::Win32::UI::Shell::ShellExecuteW;
-        use windows::Win32::UI::WindowsAndMessaging::SW_HIDE;
-        use windows::core::{HSTRING, PCWSTR};
+    // 修复服务：重新复制私有目录中的服务程序并重启服务，保留服务注册项
+    // 服务未注册时返回 RepairAction::Install，由调用方提示用户重新安装
+    pub async fn repair_service(&self) -> Result<RepairAction> {
+        self.refresh_service_paths().await;
+        let private_service_binary = private_service_binary();
+        let action = plan_repair(
+            Self::is_service_registered(),
+            private_service_binary.exists(),
+        );
+        log::info!("修复 Stelliberty Service，判定结果：{:?}", action);
+
+        if action == RepairAction::Install {
+            return Ok(action);
+        }
+
+        // 私有目录程序存在时也交给服务程序复核（可能已被部分隔离或篡改）
+        #[cfg(windows)]
+        self.run_elevated_command("repair").await?;
+
+        #[cfg(not(windows))]
+        self.run_elevated("repair")?;
+
+        // 等待服务恢复响应（每 500ms 检查一次，最多 10 秒）
+        for _ in 0..20 {
+            if self.ipc_client.is_service_running().await {
+                log::info!("服务修复完成，服务已恢复运行");
+                return Ok(action);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+
+        anyhow::bail!("修复命令已执行，但服务未在 10 秒内恢复运行")
+    }
 
+    // 重启服务：以管理员权限执行服务程序的 restart 子命令（先停止再启动），
+    // 随后确认响应来自新启动的服务实例
+    pub async fn restart_service(&self) -> Result<()> {
+        if !Self::is_service_registered() {
+            anyhow::bail!("服务未安装，请先安装服务");
+        }
+        log::info!("重启 Stelliberty Service");
+        let started = Instant::now();
+
+        #[cfg(windows)]
+        self.run_elevated_command("restart").await?;
+
+        #[cfg(not(windows))]
+        self.run_elevated("restart")?;
+
+        for _ in 0..RESTART_VERIFY_ATTEMPTS {
+            if let Ok(IpcResponse::Health { health }) =
+                self.ipc_client.send_command(IpcCommand::Health).await
+            {
+                if is_restart_complete(health.service_uptime_secs, started.elapsed()) {
+                    log::info!("服务重启完成，耗时 {} ms", started.elapsed().as_millis());
+                    return Ok(());
+                }
+                log::debug!(
+                    "服务仍是旧实例（已运行 {} 秒），继续等待",
+                    health.service_uptime_secs
+                );
+            }
+            tokio::time::sleep(RESTART_VERIFY_INTERVAL).await;
+        }
+
+        anyhow::bail!("重启命令已执行，但服务未在 10 秒内恢复运行")
+    }
+
+    // 以管理员权限执行服务程序子命令（Windows 上 UAC 确认后即返回，不等待命令结束）
+    fn run_elevated(&self, operation: &str) -> Result<()> {
+        let operation_name = match operation {
+            "install" => "安装",
+            "uninstall" => "卸载",
+            "repair" => "修复",
+            "restart" => "重启",
+            _ => operation,
+        };
         let binary_path = self
             .service_binary_path
             .to_str()
             .context("服务程序路径包含无效字符")?;
 
-        log::info!("以管理员权限执行：{} {}", binary_path, operation);
+        // 提权只发生一次：服务程序子命令以管理员身份运行，其内部不再二次提权
+        let args = elevated_args(operation, &private_service_binary());
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let outcome = elevate::run(binary_path, &args).map_err(|e| {
+            anyhow::anyhow!(
+                "{}服务失败：{}，请以 sudo 运行应用后重试",
+                operation_name,
+                e
+            )
+        })?;
+
+        match outcome {
+            ElevationOutcome::Succeeded => Ok(()),
+            ElevationOutcome::UserCancelled => {
+                anyhow::bail!("已取消管理员授权，服务{}未执行", operation_name)
+            }
+            #[cfg(windows)]
+            ElevationOutcome::Failed { code, message } => anyhow::bail!(
+                "服务{}失败（错误代码：{}）：{}。\n\n请确保：\n1. 已在 UAC 对话框中点击\"是\"\n2. 服务程序文件完整且未被杀毒软件隔离\n3. 当前用户具有管理员权限",
+                operation_name,
+                code,
+                message
+            ),
+            #[cfg(not(windows))]
+            ElevationOutcome::Failed { code, message } => {
+                anyhow::bail!(
+                    "{}服务失败（退出码：{}）：{}",
+                    operation_name,
+                    code,
+                    message
+                )
+            }
+        }
+    }
 
+    // 以管理员权限运行命令并等待服务状态变化（Windows）
+    #[cfg(windows)]
+    async fn run_elevated_command(&self, operation: &str) -> Result<()> {
         // 再次验证服务程序是否存在（防止文件被删除）
         if !self.service_binary_path.exists() {
-            anyhow::bail!("服务程序文件不存在：{}。可能已被删除或移动", binary_path);
-        }
-
-        let verb = HSTRING::from("runas");
-        let file = HSTRING::from(binary_path);
-        let parameters = HSTRING::from(operation);
-
-        unsafe {
-            let result = ShellExecuteW(
-                None, // 使用 None 表示无父窗口
-                PCWSTR(verb.as_ptr()),
-                PCWSTR(file.as_ptr()),
-                PCWSTR(parameters.as_ptr()),
-                PCWSTR::null(),
-                SW_HIDE,
+            anyhow::bail!(
+                "服务程序文件不存在：{}。可能已被删除或移动",
+                self.service_binary_path.display()
             );
+        }
 
-            // ShellExecuteW 返回 HINSTANCE，值 > 32 表示成功
-            let result_value = result.0 as isize;
-            if result_value <= 32 {
-                // 根据返回值提供详细错误信息
-                let error_detail = match result_value {
-                    0 => "系统内存或资源不足",
-                    2 => "找不到指定的服务程序文件",
-                    3 => "找不到指定的路径",
-                    5 => "拒绝访问（权限不足）",
-                    8 => "内存不足",
-                    11 => "服务程序文件损坏或无效",
-                    26 => "无法共享",
-                    27 => "文件名关联不完整或无效",
-                    28 => "操作超时",
-                    29 => "DDE 事务失败",
-                    30 => "DDE 事务正在处理中",
-                    31 => "没有关联的应用程序",
-                    32 => "未找到或未注册 DLL",
-                    _ if result_value == 1223 => "用户取消了 UAC 权限提升对话框",
-                    _ => "未知错误",
-                };
+        self.run_elevated(operation)?;
 
-                anyhow::bail!(
-                    "服务{}失败（错误代码：{}）：{}。\n\n请确保：\n1. 已在 UAC 对话框中点击\"是\"\n2. 服务程序文件完整且未被杀毒软件隔离\n3. 当前用户具有管理员权限",
-                    operation,
-                    result_value,
-                    error_detail
-                );
-            }
+        // 修复与重启不改变服务注册状态，由调用方检测服务是否恢复
+        if operation == "repair" || operation == "restart" {
+            return Ok(());
         }
 
         // 问题 12：使用轮询代替固定等待，更精确地检测操作完成
@@ -585,21 +680,19 @@ impl ServiceManager {
     }
 
     // 启动 Clash 核心（通过服务）
-    pub async fn start_clash(
-        &self,
-        core_path: String,
-        config_path: String,
-        data_dir: String,
-        external_controller: String,
-    ) -> Result<Option<u32>> {
+    pub async fn start_clash(&self, request: &StartClash) -> Result<Option<u32>> {
         log::debug!("通过服务启动 Clash 核心…");
         let response = self
             .ipc_client
             .send_command(IpcCommand::StartClash {
-                core_path,
-                config_path,
-                data_dir,
-                external_controller,
+                core_path: request.core_path.clone(),
+                config_path: request.config_path.clone(),
+                data_dir: request.data_dir.clone(),
+                external_controller: request.external_controller.clone(),
+                mixed_port: request.mixed_port,
+                socks_port: request.socks_port,
+                extra_args: request.extra_args.clone(),
+                priority: request.priority.clone(),
             })
             .await
             .context("发送启动命令失败")?;
@@ -609,16 +702,21 @@ impl ServiceManager {
                 log::debug!("Clash 启动成功：{:?}", message);
 
                 // 启动后立即获取 PID
-                match self.ipc_client.send_command(IpcCommand::GetStatus).await {
+                let clash_pid = match self.ipc_client.send_command(IpcCommand::GetStatus).await {
                     Ok(IpcResponse::Status { clash_pid, .. }) => {
                         log::debug!("获取到 Clash PID：{:?}", clash_pid);
-                        Ok(clash_pid)
+                        clash_pid
                     }
                     _ => {
                         log::warn!("无法获取 Clash PID");
-                        Ok(None)
+                        None
                     }
-                }
+                };
+
+                // 等待核心控制器就绪后再返回，避免后续 API 请求过早发出
+                self.wait_core_ready().await;
+
+                Ok(clash_pid)
             }
             IpcResponse::Error { code, message } => {
                 anyhow::bail!("Clash 启动失败（code={}）：{}", code, message)
@@ -627,6 +725,45 @@ impl ServiceManager {
         }
     }
 
+    // 等待核心控制器就绪（由服务轮询核心 API），返回是否就绪
+    async fn wait_core_ready(&self) -> bool {
+        const CORE_READY_TIMEOUT_MS: u64 = 10_000;
+
+        // 服务端轮询期间连接保持打开，客户端超时需大于轮询超时
+        let client = IpcClient::new()
+            .with_timeout(std::time::Duration::from_millis(
+                CORE_READY_TIMEOUT_MS + 2_000,
+            ))
+            .with_max_retries(0);
+
+        match client
+            .send_command(IpcCommand::WaitCoreReady {
+                timeout_ms: CORE_READY_TIMEOUT_MS,
+            })
+            .await
+        {
+            Ok(IpcResponse::CoreReadiness {
+                is_ready,
+                elapsed_ms,
+            }) => {
+                if is_ready {
+                    log::debug!("核心控制器已就绪，耗时 {}ms", elapsed_ms);
+                } else {
+                    log::warn!("等待核心控制器就绪超时（{}ms）", elapsed_ms);
+                }
+                is_ready
+            }
+            Ok(response) => {
+                log::warn!("等待核心就绪时收到意外响应：{:?}", response);
+                false
+            }
+            Err(e) => {
+                log::warn!("等待核心就绪失败：{}", e);
+                false
+            }
+        }
+    }
+
     // 停止 Clash 核心（通过服务）
     pub async fn stop_clash(&self) -> Result<()> {
         log::debug!("通过服务停止 Clash 核心…");
@@ -648,56 +785,435 @@ impl ServiceManager {
         }
     }
 
-    #[cfg(windows)]
-    fn is_service_installed() -> bool {
-        use windows_service::{
-            service::ServiceAccess,
-            service_manager::{ServiceManager, ServiceManagerAccess},
-        };
+    // 清除核心缓存（通过服务转发到核心控制器）
+    pub async fn flush_cache(&self, kind: CacheKind) -> Result<Option<String>> {
+        log::debug!("通过服务清除核心缓存：{:?}", kind);
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::FlushCache { kind })
+            .await
+            .context("发送清除缓存命令失败")?;
 
-        const SERVICE_NAME: &str = "StellibertyService";
+        match response {
+            IpcResponse::Success { message } => Ok(message),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("清除缓存失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
 
-        let Ok(manager) =
-            ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
-        else {
-            return false;
-        };
+    // 通过服务列出孤立的核心进程
+    pub async fn list_orphan_cores(&self) -> Result<Vec<OrphanCore>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::ListOrphanCores)
+            .await
+            .context("发送列出孤立进程命令失败")?;
 
-        manager
-            .open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS)
-            .is_ok()
+        match response {
+            IpcResponse::OrphanCores { cores } => Ok(cores),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("列出孤立进程失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
     }
 
-    // 检查 systemd 服务是否已安装（仅 Linux）
-    #[cfg(target_os = "linux")]
-    fn is_systemd_service_installed() -> bool {
-        const SERVICE_FILE: &str = "/etc/systemd/system/StellibertyService.service";
-        std::path::Path::new(SERVICE_FILE).exists()
-    }
+    // 通过服务强制终止选中的孤立核心进程
+    pub async fn kill_orphan_cores(&self, pids: Vec<u32>) -> Result<Option<String>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::KillOrphanCores { pids })
+            .await
+            .context("发送清理孤立进程命令失败")?;
 
-    // 检查 systemd 服务是否正在运行（仅 Linux）
-    #[cfg(target_os = "linux")]
-    fn is_systemd_service_active() -> bool {
-        const SERVICE_NAME: &str = "StellibertyService";
-        Command::new("systemctl")
-            .args(["is-active", "--quiet", SERVICE_NAME])
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false)
+        match response {
+            IpcResponse::Success { message } => Ok(message),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("清理孤立进程失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
     }
-}
 
-impl Default for ServiceManager {
-    fn default() -> Self {
-        Self::new().unwrap_or_else(|e| {
-            log::error!("创建 ServiceManager 失败：{}", e);
+    // 通过服务检测 DoH/DoT 服务器是否可达
+    pub async fn check_dns_reachability(
+        &self,
+        nameservers: Vec<String>,
+    ) -> Result<Vec<DnsServerReachability>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::CheckDnsReachability { nameservers })
+            .await
+            .context("发送 DNS 可达性检测命令失败")?;
 
-            // 使用备用路径（尝试从私有目录或便携式目录）
-            let service_binary_path = {
-                let private_binary = crate::atoms::path_service::service_private_binary();
-                if private_binary.exists() {
-                    private_binary
-                } else {
+        match response {
+            IpcResponse::DnsReachability { results } => Ok(results),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("DNS 可达性检测失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务经核心代理检测出口 IP，返回 (IP, 国家/地区, ASN)
+    pub async fn check_egress_ip(
+        &self,
+        url: String,
+    ) -> Result<(String, Option<String>, Option<String>)> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::CheckEgressIp { url })
+            .await
+            .context("发送出口 IP 检测命令失败")?;
+
+        match response {
+            IpcResponse::EgressIp { ip, country, asn } => Ok((ip, country, asn)),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("出口 IP 检测失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务获取 count 个当前可绑定的本地端口
+    pub async fn suggest_free_ports(&self, count: usize) -> Result<Vec<u16>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::SuggestFreePorts { count })
+            .await
+            .context("发送空闲端口建议命令失败")?;
+
+        match response {
+            IpcResponse::FreePorts { ports } => Ok(ports),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("建议空闲端口失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务启用或关闭防泄漏开关，返回（是否启用，拦截规则是否生效）
+    pub async fn set_kill_switch(&self, is_enabled: bool) -> Result<(bool, bool)> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::SetKillSwitch {
+                enabled: is_enabled,
+            })
+            .await
+            .context("发送防泄漏开关命令失败")?;
+
+        match response {
+            IpcResponse::KillSwitch {
+                is_enabled,
+                is_engaged,
+            } => Ok((is_enabled, is_engaged)),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("设置防泄漏开关失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 耗时操作前暂停服务的心跳超时自动关闭，返回实际暂停秒数（服务端上限 5 分钟）
+    pub async fn suspend_auto_shutdown(&self, duration_secs: Option<u64>) -> Result<u64> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::SuspendAutoShutdown { duration_secs })
+            .await
+            .context("发送暂停自动关闭命令失败")?;
+
+        match response {
+            IpcResponse::AutoShutdown { remaining_secs, .. } => Ok(remaining_secs),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("暂停自动关闭失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 耗时操作结束后恢复心跳超时自动关闭
+    pub async fn resume_auto_shutdown(&self) -> Result<()> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::ResumeAutoShutdown)
+            .await
+            .context("发送恢复自动关闭命令失败")?;
+
+        match response {
+            IpcResponse::AutoShutdown { .. } => Ok(()),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("恢复自动关闭失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务导出核心运行中的配置，返回配置文本与脱敏字段数
+    pub async fn export_running_config(&self, redact: bool) -> Result<(String, u32)> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::ExportRunningConfig { redact })
+            .await
+            .context("发送导出运行配置命令失败")?;
+
+        match response {
+            IpcResponse::RunningConfig {
+                config,
+                redacted_fields,
+            } => Ok((config, redacted_fields)),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("导出运行配置失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务获取最近的服务日志（按时间顺序）
+    pub async fn recent_service_logs(&self, lines: usize) -> Result<Vec<String>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::GetLogs {
+                lines,
+                offset: 0,
+                before_timestamp: None,
+                since_timestamp: None,
+            })
+            .await
+            .context("发送获取日志命令失败")?;
+
+        match response {
+            IpcResponse::Logs { lines, .. } => Ok(lines),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("获取服务日志失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务获取日志文件路径（服务日志未写入文件时为 None）
+    pub async fn service_logs_path(&self) -> Result<Option<String>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::GetServiceLogsPath)
+            .await
+            .context("发送获取日志路径命令失败")?;
+
+        match response {
+            IpcResponse::LogsPath { path } => Ok(path),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("获取服务日志路径失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务获取其实际使用的路径，并记录下来供后续定位服务程序
+    pub async fn service_paths(&self) -> Result<ServicePaths> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::GetServicePaths)
+            .await
+            .context("发送获取服务路径命令失败")?;
+
+        match response {
+            IpcResponse::ServicePaths { paths } => {
+                let local_binary = crate::atoms::path_service::service_private_binary();
+                if Path::new(&paths.binary_path) != local_binary {
+                    log::warn!(
+                        "服务上报的程序路径与本地推算不一致：{}（本地：{}），以服务为准",
+                        paths.binary_path,
+                        local_binary.display()
+                    );
+                }
+                *REPORTED_SERVICE_PATHS
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = Some(paths.clone());
+                Ok(paths)
+            }
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("获取服务路径失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 尽量从服务获取路径（服务未运行时沿用上次记录或本地推算）
+    async fn refresh_service_paths(&self) {
+        if let Err(e) = self.service_paths().await {
+            log::debug!("无法从服务获取路径，沿用已有记录：{}", e);
+        }
+    }
+
+    // 通过服务获取核心最近的输出
+    pub async fn recent_core_output(&self) -> Result<Vec<String>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::GetCoreOutput)
+            .await
+            .context("发送获取核心输出命令失败")?;
+
+        match response {
+            IpcResponse::CoreOutput { lines } => Ok(lines),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("获取核心输出失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务执行启动前自检
+    pub async fn run_preflight_check(
+        &self,
+        core_path: String,
+        data_dir: String,
+    ) -> Result<Vec<PreflightItem>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::RunPreflightCheck {
+                core_path,
+                data_dir,
+            })
+            .await
+            .context("发送启动前自检命令失败")?;
+
+        match response {
+            IpcResponse::Preflight { items } => Ok(items),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("启动前自检失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务切换核心配置，返回最终生效的配置、是否已恢复原配置及新配置失败原因
+    pub async fn switch_config(
+        &self,
+        new_config_path: String,
+    ) -> Result<(String, bool, Option<String>)> {
+        // 不重试：重复发送会再次重启核心
+        let client = IpcClient::new()
+            .with_timeout(SWITCH_CONFIG_TIMEOUT)
+            .with_max_retries(0);
+        let response = client
+            .send_command(IpcCommand::SwitchConfig { new_config_path })
+            .await
+            .context("发送切换配置命令失败")?;
+
+        match response {
+            IpcResponse::ConfigSwitched {
+                active_config_path,
+                is_reverted,
+                reason,
+            } => Ok((active_config_path, is_reverted, reason)),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("切换配置失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 通过服务查询数据目录中的地理数据文件状态
+    pub async fn geo_data_info(&self, data_dir: String) -> Result<Vec<GeoDataFile>> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::GetGeoDataInfo { data_dir })
+            .await
+            .context("发送查询地理数据命令失败")?;
+
+        match response {
+            IpcResponse::GeoDataInfo { files } => Ok(files),
+            IpcResponse::Error { code, message } => {
+                anyhow::bail!("查询地理数据失败（code={}）：{}", code, message)
+            }
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    // 获取正在运行的服务版本号
+    pub async fn running_service_version(&self) -> Result<String> {
+        let response = self
+            .ipc_client
+            .send_command(IpcCommand::GetVersion)
+            .await
+            .context("发送获取版本命令失败")?;
+
+        match response {
+            IpcResponse::Version { version } => Ok(version),
+            _ => anyhow::bail!("收到意外响应：{:?}", response),
+        }
+    }
+
+    #[cfg(windows)]
+    fn is_service_installed() -> bool {
+        use windows_service::{
+            service::ServiceAccess,
+            service_manager::{ServiceManager, ServiceManagerAccess},
+        };
+
+        const SERVICE_NAME: &str = "StellibertyService";
+
+        let Ok(manager) =
+            ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        else {
+            return false;
+        };
+
+        manager
+            .open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS)
+            .is_ok()
+    }
+
+    // 检查服务是否已注册到系统服务管理器
+    pub fn is_service_registered() -> bool {
+        #[cfg(windows)]
+        {
+            Self::is_service_installed()
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            Self::is_systemd_service_installed()
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            const SERVICE_PLIST_PATH: &str = "/Library/LaunchDaemons/com.stelliberty.service.plist";
+            std::path::Path::new(SERVICE_PLIST_PATH).exists()
+        }
+    }
+
+    // 检查 systemd 服务是否已安装（仅 Linux）
+    #[cfg(target_os = "linux")]
+    fn is_systemd_service_installed() -> bool {
+        const SERVICE_FILE: &str = "/etc/systemd/system/StellibertyService.service";
+        std::path::Path::new(SERVICE_FILE).exists()
+    }
+
+    // 检查 systemd 服务是否正在运行（仅 Linux）
+    #[cfg(target_os = "linux")]
+    fn is_systemd_service_active() -> bool {
+        const SERVICE_NAME: &str = "StellibertyService";
+        Command::new("systemctl")
+            .args(["is-active", "--quiet", SERVICE_NAME])
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+}
+
+impl Default for ServiceManager {
+    fn default() -> Self {
+        Self::new().unwrap_or_else(|e| {
+            log::error!("创建 ServiceManager 失败：{}", e);
+
+            // 使用备用路径（尝试从私有目录或便携式目录）
+            let service_binary_path = {
+                let private_binary = private_service_binary();
+                if private_binary.exists() {
+                    private_binary
+                } else {
                     // 备用：尝试从 assets 目录
                     crate::atoms::path_service::assets_service_binary()
                 }
@@ -725,6 +1241,14 @@ pub struct InstallService;
 #[derive(Deserialize, DartSignal)]
 pub struct UninstallService;
 
+// Dart → Rust：修复服务请求
+#[derive(Deserialize, DartSignal)]
+pub struct RepairService;
+
+// Dart → Rust：重启服务请求
+#[derive(Deserialize, DartSignal)]
+pub struct RestartService;
+
 // Dart → Rust：通过服务启动 Clash
 #[derive(Deserialize, DartSignal)]
 pub struct StartClash {
@@ -732,6 +1256,13 @@ pub struct StartClash {
     pub config_path: String,
     pub data_dir: String,
     pub external_controller: String,
+    // 启动时覆盖的入站端口（为空时沿用配置文件）
+    pub mixed_port: Option<u16>,
+    pub socks_port: Option<u16>,
+    // 追加在内置参数之后的核心参数（不能覆盖 -d、-f、-ext-ctl）
+    pub extra_args: Vec<String>,
+    // 核心进程优先级：normal（默认）、below_normal、low，由服务校验
+    pub priority: Option<String>,
 }
 
 // Dart → Rust：通过服务停止 Clash
@@ -746,6 +1277,66 @@ pub struct SendServiceHeartbeat;
 #[derive(Deserialize, DartSignal)]
 pub struct GetServiceVersion;
 
+// Dart → Rust：获取服务日志文件路径（用于在文件管理器中打开）
+#[derive(Deserialize, DartSignal)]
+pub struct GetServiceLogsPath;
+
+// Dart → Rust：获取服务实际使用的私有目录、程序路径与日志路径
+#[derive(Deserialize, DartSignal)]
+pub struct GetServicePaths;
+
+// Dart → Rust：清除核心缓存（kind 取值 fakeip / dns / all）
+#[derive(Deserialize, DartSignal)]
+pub struct FlushCoreCache {
+    pub kind: String,
+}
+
+// Dart → Rust：列出孤立的核心进程
+#[derive(Deserialize, DartSignal)]
+pub struct ListOrphanCoreProcesses;
+
+// Dart → Rust：清理选中的孤立核心进程
+#[derive(Deserialize, DartSignal)]
+pub struct KillOrphanCoreProcesses {
+    pub pids: Vec<u32>,
+}
+
+// Dart → Rust：检测 DoH/DoT 服务器在当前网络下是否可达
+#[derive(Deserialize, DartSignal)]
+pub struct CheckDnsReachability {
+    pub nameservers: Vec<String>,
+}
+
+// Dart → Rust：经核心代理检测当前出口 IP（url 为空时使用默认回显服务）
+#[derive(Deserialize, DartSignal)]
+pub struct CheckEgressIp {
+    pub url: String,
+}
+
+// Dart → Rust：建议当前可用的本地端口（配置端口被占用时自动选择）
+#[derive(Deserialize, DartSignal)]
+pub struct SuggestFreePorts {
+    pub count: u32,
+}
+
+// Dart → Rust：启用或关闭防泄漏开关（核心停止或崩溃后阻止直连流量）
+#[derive(Deserialize, DartSignal)]
+pub struct SetKillSwitch {
+    pub is_enabled: bool,
+}
+
+// Dart → Rust：切换核心配置（新配置未就绪时自动恢复原配置）
+#[derive(Deserialize, DartSignal)]
+pub struct SwitchConfig {
+    pub new_config_path: String,
+}
+
+// Dart → Rust：导出核心运行中的配置（用于问题反馈）
+#[derive(Deserialize, DartSignal)]
+pub struct ExportRunningConfig {
+    pub redact: bool,
+}
+
 // Rust → Dart：服务状态响应
 #[derive(Serialize, RustSignal)]
 pub struct ServiceStatusResponse {
@@ -761,6 +1352,145 @@ pub struct ServiceOperationResult {
     pub error_message: Option<String>,
 }
 
+// Rust → Dart：修复服务结果
+#[derive(Serialize, RustSignal)]
+pub struct RepairServiceResult {
+    pub is_successful: bool,
+    // 服务未注册，需要重新安装（此时 is_successful 为 false）
+    pub requires_install: bool,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：重启服务结果
+#[derive(Serialize, RustSignal)]
+pub struct RestartServiceResult {
+    pub is_successful: bool,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：清除核心缓存结果
+#[derive(Serialize, RustSignal)]
+pub struct FlushCoreCacheResult {
+    pub is_successful: bool,
+    pub message: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// 孤立核心进程信息
+#[derive(Serialize, SignalPiece)]
+pub struct OrphanCoreProcess {
+    pub pid: u32,
+    pub command_line: String,
+}
+
+// Rust → Dart：孤立核心进程列表
+#[derive(Serialize, RustSignal)]
+pub struct OrphanCoreProcessList {
+    pub processes: Vec<OrphanCoreProcess>,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：清理孤立核心进程结果
+#[derive(Serialize, RustSignal)]
+pub struct KillOrphanCoreProcessesResult {
+    pub is_successful: bool,
+    pub message: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// 单个 DNS 服务器的检测结果
+#[derive(Serialize, SignalPiece)]
+pub struct DnsServerStatus {
+    pub nameserver: String,
+    pub is_reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：DNS 可达性检测结果
+#[derive(Serialize, RustSignal)]
+pub struct DnsReachabilityResult {
+    pub servers: Vec<DnsServerStatus>,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：服务日志文件路径
+#[derive(Serialize, RustSignal)]
+pub struct ServiceLogsPathResult {
+    // 日志文件的绝对路径（服务日志未写入文件时为 None）
+    pub path: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：服务路径（服务不可用时路径均为 None）
+#[derive(Serialize, RustSignal)]
+pub struct ServicePathsResult {
+    pub private_dir: Option<String>,
+    pub binary_path: Option<String>,
+    pub log_path: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：出口 IP 检测结果
+#[derive(Serialize, RustSignal)]
+pub struct EgressIpResult {
+    pub ip: Option<String>,
+    pub country: Option<String>,
+    pub asn: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：空闲端口建议结果
+#[derive(Serialize, RustSignal)]
+pub struct SuggestFreePortsResult {
+    pub ports: Vec<u16>,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：防泄漏开关设置结果
+#[derive(Serialize, RustSignal)]
+pub struct KillSwitchResult {
+    pub is_enabled: bool,
+    // 拦截规则当前是否已生效
+    pub is_engaged: bool,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：配置切换结果
+#[derive(Serialize, RustSignal)]
+pub struct SwitchConfigResult {
+    // 最终生效的配置文件（切换与恢复都失败时为空）
+    pub active_config_path: Option<String>,
+    // 新配置启动失败，已恢复原配置
+    pub is_reverted: bool,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：核心运行配置导出结果
+#[derive(Serialize, RustSignal)]
+pub struct ExportRunningConfigResult {
+    pub config: Option<String>,
+    pub redacted_fields: u32,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：服务模式下核心意外退出（由服务主动推送）
+#[derive(Serialize, RustSignal)]
+pub struct ServiceCoreExited {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub last_output_lines: Vec<String>,
+}
+
+// Rust → Dart：核心反复崩溃后服务已关闭 TUN 以安全模式重启（系统代理已同时关闭）
+#[derive(Serialize, RustSignal)]
+pub struct ServiceSafeModeActivated {
+    pub config_path: String,
+    pub crash_count: u32,
+    // 关闭系统代理失败的原因
+    pub proxy_error_message: Option<String>,
+}
+
 // Rust → Dart：服务版本号响应
 #[derive(Serialize, RustSignal)]
 pub struct ServiceVersionResponse {
@@ -889,146 +1619,582 @@ impl UninstallService {
                     is_successful: false,
                     error_message: Some(e.to_string()),
                 }
-                .send_signal_to_dart();
+                .send_signal_to_dart();
+            }
+        }
+    }
+}
+
+impl RepairService {
+    pub async fn handle(&self) {
+        let service_manager = match ServiceManager::new() {
+            Ok(sm) => sm,
+            Err(e) => {
+                log::error!("创建 ServiceManager 失败：{}", e);
+                RepairServiceResult {
+                    is_successful: false,
+                    requires_install: false,
+                    error_message: Some(format!("创建服务管理器失败：{}", e)),
+                }
+                .send_signal_to_dart();
+                return;
+            }
+        };
+
+        let response = match service_manager.repair_service().await {
+            Ok(RepairAction::Install) => {
+                log::warn!("服务未注册，无法修复");
+                RepairServiceResult {
+                    is_successful: false,
+                    requires_install: true,
+                    error_message: Some("服务未安装，请重新安装服务".to_string()),
+                }
+            }
+            Ok(_) => {
+                log::info!("服务修复成功");
+                RepairServiceResult {
+                    is_successful: true,
+                    requires_install: false,
+                    error_message: None,
+                }
+            }
+            Err(e) => {
+                log::error!("服务修复失败：{}", e);
+                RepairServiceResult {
+                    is_successful: false,
+                    requires_install: false,
+                    error_message: Some(e.to_string()),
+                }
+            }
+        };
+
+        response.send_signal_to_dart();
+    }
+}
+
+impl RestartService {
+    pub async fn handle(&self) {
+        let service_manager = match ServiceManager::new() {
+            Ok(sm) => sm,
+            Err(e) => {
+                log::error!("创建 ServiceManager 失败：{}", e);
+                RestartServiceResult {
+                    is_successful: false,
+                    error_message: Some(format!("创建服务管理器失败：{}", e)),
+                }
+                .send_signal_to_dart();
+                return;
+            }
+        };
+
+        let response = match service_manager.restart_service().await {
+            Ok(()) => {
+                log::info!("服务重启成功");
+                RestartServiceResult {
+                    is_successful: true,
+                    error_message: None,
+                }
+            }
+            Err(e) => {
+                log::error!("服务重启失败：{}", e);
+                RestartServiceResult {
+                    is_successful: false,
+                    error_message: Some(e.to_string()),
+                }
+            }
+        };
+
+        response.send_signal_to_dart();
+    }
+}
+
+impl StartClash {
+    pub async fn handle(&self) {
+        let service_manager = match ServiceManager::new() {
+            Ok(sm) => sm,
+            Err(e) => {
+                log::error!("创建 ServiceManager 失败：{}", e);
+                ClashProcessResult {
+                    is_successful: false,
+                    error_message: Some(format!("创建服务管理器失败：{}", e)),
+                    pid: None,
+                }
+                .send_signal_to_dart();
+                return;
+            }
+        };
+
+        match service_manager.start_clash(self).await {
+            Ok(pid) => {
+                log::info!("通过服务启动 Clash 成功，PID：{:?}", pid);
+                ClashProcessResult {
+                    is_successful: true,
+                    error_message: None,
+                    pid,
+                }
+                .send_signal_to_dart();
+            }
+            Err(e) => {
+                log::error!("通过服务启动 Clash 失败：{}", e);
+                ClashProcessResult {
+                    is_successful: false,
+                    error_message: Some(e.to_string()),
+                    pid: None,
+                }
+                .send_signal_to_dart();
+            }
+        }
+    }
+}
+
+impl StopClash {
+    pub async fn handle(&self) {
+        let service_manager = match ServiceManager::new() {
+            Ok(sm) => sm,
+            Err(e) => {
+                log::error!("创建 ServiceManager 失败：{}", e);
+                ClashProcessResult {
+                    is_successful: false,
+                    error_message: Some(format!("创建服务管理器失败：{}", e)),
+                    pid: None,
+                }
+                .send_signal_to_dart();
+                return;
+            }
+        };
+
+        match service_manager.stop_clash().await {
+            Ok(()) => {
+                log::info!("通过服务停止 Clash 成功");
+
+                ClashProcessResult {
+                    is_successful: true,
+                    error_message: None,
+                    pid: None,
+                }
+                .send_signal_to_dart();
+            }
+            Err(e) => {
+                log::error!("通过服务停止 Clash 失败：{}", e);
+                ClashProcessResult {
+                    is_successful: false,
+                    error_message: Some(e.to_string()),
+                    pid: None,
+                }
+                .send_signal_to_dart();
+            }
+        }
+    }
+}
+
+impl SendServiceHeartbeat {
+    pub async fn handle(&self) {
+        HEARTBEAT_DEBOUNCER.run(send_heartbeat).await;
+    }
+}
+
+// 向服务发送一次心跳
+async fn send_heartbeat() {
+    let client = IpcClient::new()
+        .with_timeout(Duration::from_secs(2))
+        .with_max_retries(0);
+
+    match client.send_command(IpcCommand::Heartbeat).await {
+        Ok(IpcResponse::HeartbeatAck) => {
+            log::trace!("服务心跳发送成功");
+            // 成功时不需要向 Dart 发送信号
+        }
+        Ok(resp) => {
+            log::warn!("发送心跳时收到意外响应: {:?}", resp);
+        }
+        Err(e) => {
+            log::warn!("发送服务心跳失败: {}", e);
+        }
+    }
+}
+
+impl GetServiceVersion {
+    pub async fn handle(&self) {
+        // 获取已安装服务的版本号（从私有目录）
+        let installed_version = ServiceManager::get_installed_service_version();
+
+        // 获取内置服务的版本号（从 assets）
+        let bundled_version =
+            ServiceManager::get_bundled_service_version().unwrap_or_else(|| "unknown".to_string());
+
+        log::debug!(
+            "服务版本信息 - 已安装: {:?}, 内置: {}",
+            installed_version,
+            bundled_version
+        );
+
+        ServiceVersionResponse {
+            installed_version,
+            bundled_version,
+        }
+        .send_signal_to_dart();
+    }
+}
+
+impl FlushCoreCache {
+    pub async fn handle(&self) {
+        let kind = match self.kind.as_str() {
+            "fakeip" => CacheKind::FakeIp,
+            "dns" => CacheKind::Dns,
+            "all" => CacheKind::All,
+            other => {
+                log::warn!("未知的缓存类型：{}", other);
+                FlushCoreCacheResult {
+                    is_successful: false,
+                    message: None,
+                    error_message: Some(format!("未知的缓存类型：{}", other)),
+                }
+                .send_signal_to_dart();
+                return;
+            }
+        };
+
+        let service_manager = ServiceManager::default();
+        match service_manager.flush_cache(kind).await {
+            Ok(message) => {
+                log::info!("清除核心缓存成功：{:?}", message);
+                FlushCoreCacheResult {
+                    is_successful: true,
+                    message,
+                    error_message: None,
+                }
+                .send_signal_to_dart();
+            }
+            Err(e) => {
+                log::error!("清除核心缓存失败：{}", e);
+                FlushCoreCacheResult {
+                    is_successful: false,
+                    message: None,
+                    error_message: Some(e.to_string()),
+                }
+                .send_signal_to_dart();
+            }
+        }
+    }
+}
+
+impl ListOrphanCoreProcesses {
+    pub async fn handle(&self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager.list_orphan_cores().await {
+            Ok(cores) => OrphanCoreProcessList {
+                processes: cores
+                    .into_iter()
+                    .map(|core| OrphanCoreProcess {
+                        pid: core.pid,
+                        command_line: core.command_line,
+                    })
+                    .collect(),
+                error_message: None,
+            },
+            Err(e) => {
+                log::error!("列出孤立核心进程失败：{}", e);
+                OrphanCoreProcessList {
+                    processes: Vec::new(),
+                    error_message: Some(e.to_string()),
+                }
+            }
+        };
+        response.send_signal_to_dart();
+    }
+}
+
+impl KillOrphanCoreProcesses {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        match service_manager.kill_orphan_cores(self.pids).await {
+            Ok(message) => {
+                log::info!("清理孤立核心进程成功：{:?}", message);
+                KillOrphanCoreProcessesResult {
+                    is_successful: true,
+                    message,
+                    error_message: None,
+                }
+                .send_signal_to_dart();
+            }
+            Err(e) => {
+                log::error!("清理孤立核心进程失败：{}", e);
+                KillOrphanCoreProcessesResult {
+                    is_successful: false,
+                    message: None,
+                    error_message: Some(e.to_string()),
+                }
+                .send_signal_to_dart();
+            }
+        }
+    }
+}
+
+impl CheckDnsReachability {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager
+            .check_dns_reachability(self.nameservers)
+            .await
+        {
+            Ok(results) => DnsReachabilityResult {
+                servers: results
+                    .into_iter()
+                    .map(|result| DnsServerStatus {
+                        nameserver: result.nameserver,
+                        is_reachable: result.is_reachable,
+                        latency_ms: result.latency_ms,
+                        error_message: result.error,
+                    })
+                    .collect(),
+                error_message: None,
+            },
+            Err(e) => {
+                log::error!("DNS 可达性检测失败：{}", e);
+                DnsReachabilityResult {
+                    servers: Vec::new(),
+                    error_message: Some(e.to_string()),
+                }
+            }
+        };
+        response.send_signal_to_dart();
+    }
+}
+
+impl GetServiceLogsPath {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager.service_logs_path().await {
+            Ok(path) => ServiceLogsPathResult {
+                path,
+                error_message: None,
+            },
+            Err(e) => {
+                log::warn!("获取服务日志路径失败：{}", e);
+                ServiceLogsPathResult {
+                    path: None,
+                    error_message: Some(e.to_string()),
+                }
+            }
+        };
+        response.send_signal_to_dart();
+    }
+}
+
+impl GetServicePaths {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager.service_paths().await {
+            Ok(paths) => ServicePathsResult {
+                private_dir: Some(paths.private_dir),
+                binary_path: Some(paths.binary_path),
+                log_path: paths.log_path,
+                error_message: None,
+            },
+            Err(e) => {
+                log::warn!("获取服务路径失败：{}", e);
+                ServicePathsResult {
+                    private_dir: None,
+                    binary_path: None,
+                    log_path: None,
+                    error_message: Some(e.to_string()),
+                }
             }
-        }
+        };
+        response.send_signal_to_dart();
     }
 }
 
-impl StartClash {
-    pub async fn handle(&self) {
-        let service_manager = match ServiceManager::new() {
-            Ok(sm) => sm,
+impl CheckEgressIp {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager.check_egress_ip(self.url).await {
+            Ok((ip, country, asn)) => EgressIpResult {
+                ip: Some(ip),
+                country,
+                asn,
+                error_message: None,
+            },
             Err(e) => {
-                log::error!("创建 ServiceManager 失败：{}", e);
-                ClashProcessResult {
-                    is_successful: false,
-                    error_message: Some(format!("创建服务管理器失败：{}", e)),
-                    pid: None,
+                log::warn!("出口 IP 检测失败：{}", e);
+                EgressIpResult {
+                    ip: None,
+                    country: None,
+                    asn: None,
+                    error_message: Some(e.to_string()),
                 }
-                .send_signal_to_dart();
-                return;
             }
         };
+        response.send_signal_to_dart();
+    }
+}
 
-        match service_manager
-            .start_clash(
-                self.core_path.clone(),
-                self.config_path.clone(),
-                self.data_dir.clone(),
-                self.external_controller.clone(),
-            )
+impl SuggestFreePorts {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager
+            .suggest_free_ports(self.count as usize)
             .await
         {
-            Ok(pid) => {
-                log::info!("通过服务启动 Clash 成功，PID：{:?}", pid);
-                ClashProcessResult {
-                    is_successful: true,
-                    error_message: None,
-                    pid,
-                }
-                .send_signal_to_dart();
-            }
+            Ok(ports) => SuggestFreePortsResult {
+                ports,
+                error_message: None,
+            },
             Err(e) => {
-                log::error!("通过服务启动 Clash 失败：{}", e);
-                ClashProcessResult {
-                    is_successful: false,
+                log::warn!("获取空闲端口建议失败：{}", e);
+                SuggestFreePortsResult {
+                    ports: Vec::new(),
                     error_message: Some(e.to_string()),
-                    pid: None,
                 }
-                .send_signal_to_dart();
             }
-        }
+        };
+        response.send_signal_to_dart();
     }
 }
 
-impl StopClash {
-    pub async fn handle(&self) {
-        let service_manager = match ServiceManager::new() {
-            Ok(sm) => sm,
+impl SetKillSwitch {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager.set_kill_switch(self.is_enabled).await {
+            Ok((is_enabled, is_engaged)) => KillSwitchResult {
+                is_enabled,
+                is_engaged,
+                error_message: None,
+            },
             Err(e) => {
-                log::error!("创建 ServiceManager 失败：{}", e);
-                ClashProcessResult {
-                    is_successful: false,
-                    error_message: Some(format!("创建服务管理器失败：{}", e)),
-                    pid: None,
+                log::warn!("设置防泄漏开关失败：{}", e);
+                KillSwitchResult {
+                    is_enabled: false,
+                    is_engaged: false,
+                    error_message: Some(e.to_string()),
                 }
-                .send_signal_to_dart();
-                return;
             }
         };
+        response.send_signal_to_dart();
+    }
+}
 
-        match service_manager.stop_clash().await {
-            Ok(()) => {
-                log::info!("通过服务停止 Clash 成功");
-
-                ClashProcessResult {
-                    is_successful: true,
-                    error_message: None,
-                    pid: None,
+impl SwitchConfig {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager.switch_config(self.new_config_path).await {
+            Ok((active_config_path, is_reverted, reason)) => {
+                if is_reverted {
+                    log::warn!("新配置启动失败，已恢复原配置：{:?}", reason);
+                }
+                SwitchConfigResult {
+                    active_config_path: Some(active_config_path),
+                    is_reverted,
+                    error_message: reason,
                 }
-                .send_signal_to_dart();
             }
             Err(e) => {
-                log::error!("通过服务停止 Clash 失败：{}", e);
-                ClashProcessResult {
-                    is_successful: false,
+                log::error!("切换配置失败：{}", e);
+                SwitchConfigResult {
+                    active_config_path: None,
+                    is_reverted: false,
                     error_message: Some(e.to_string()),
-                    pid: None,
                 }
-                .send_signal_to_dart();
             }
-        }
+        };
+        response.send_signal_to_dart();
     }
 }
 
-impl SendServiceHeartbeat {
-    pub async fn handle(&self) {
-        let client = IpcClient::new()
-            .with_timeout(std::time::Duration::from_secs(2))
-            .with_max_retries(0);
-
-        match client.send_command(IpcCommand::Heartbeat).await {
-            Ok(IpcResponse::HeartbeatAck) => {
-                log::trace!("服务心跳发送成功");
-                // 成功时不需要向 Dart 发送信号
-            }
-            Ok(resp) => {
-                log::warn!("发送心跳时收到意外响应: {:?}", resp);
-            }
+impl ExportRunningConfig {
+    pub async fn handle(self) {
+        let service_manager = ServiceManager::default();
+        let response = match service_manager.export_running_config(self.redact).await {
+            Ok((config, redacted_fields)) => ExportRunningConfigResult {
+                config: Some(config),
+                redacted_fields,
+                error_message: None,
+            },
             Err(e) => {
-                log::warn!("发送服务心跳失败: {}", e);
+                log::error!("导出运行配置失败：{}", e);
+                ExportRunningConfigResult {
+                    config: None,
+                    redacted_fields: 0,
+                    error_message: Some(e.to_string()),
+                }
             }
-        }
+        };
+        response.send_signal_to_dart();
     }
 }
 
-impl GetServiceVersion {
-    pub async fn handle(&self) {
-        // 获取已安装服务的版本号（从私有目录）
-        let installed_version = ServiceManager::get_installed_service_version();
-
-        // 获取内置服务的版本号（从 assets）
-        let bundled_version =
-            ServiceManager::get_bundled_service_version().unwrap_or_else(|| "unknown".to_string());
+// 安全模式下关闭系统代理（TUN 已关闭，避免系统流量指向可能不可用的代理），再通知 Dart
+async fn on_safe_mode_activated(config_path: String, crash_count: u32) {
+    let proxy_error_message = match system_proxy::disable_proxy(&[]).await {
+        ProxyResult::Success => None,
+        ProxyResult::Error(e) => {
+            log::error!("安全模式关闭系统代理失败：{}", e);
+            Some(e)
+        }
+    };
+    ServiceSafeModeActivated {
+        config_path,
+        crash_count,
+        proxy_error_message,
+    }
+    .send_signal_to_dart();
+}
 
-        log::debug!(
-            "服务版本信息 - 已安装: {:?}, 内置: {}",
-            installed_version,
-            bundled_version
-        );
+// 订阅服务事件并转发给 Dart，连接断开（服务未运行或重启）后定期重连
+async fn forward_service_events() {
+    const RECONNECT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+    let ipc_client = IpcClient::default();
+    loop {
+        let result = ipc_client
+            .subscribe_events(|event| {
+                match event {
+                    ServiceEvent::CoreExited {
+                        code,
+                        signal,
+                        last_output_lines,
+                    } => {
+                        log::warn!(
+                            "服务报告核心意外退出（退出码：{:?}，信号：{:?}）",
+                            code,
+                            signal
+                        );
+                        ServiceCoreExited {
+                            code,
+                            signal,
+                            last_output_lines,
+                        }
+                        .send_signal_to_dart();
+                    }
+                    ServiceEvent::SafeModeActivated {
+                        config_path,
+                        crash_count,
+                    } => {
+                        log::warn!(
+                            "核心连续崩溃 {} 次，服务已以安全模式重启：{}",
+                            crash_count,
+                            config_path
+                        );
+                        tokio::spawn(on_safe_mode_activated(config_path, crash_count));
+                    }
+                }
+                true
+            })
+            .await;
 
-        ServiceVersionResponse {
-            installed_version,
-            bundled_version,
+        if let Err(e) = result {
+            log::trace!("服务事件订阅断开：{}", e);
         }
-        .send_signal_to_dart();
+        tokio::time::sleep(RECONNECT_INTERVAL).await;
     }
 }
 
 pub fn init() {
     use tokio::spawn;
 
+    // 订阅服务事件
+    spawn(forward_service_events());
+
     // 获取服务状态
     spawn(async {
         let receiver = GetServiceStatus::get_dart_signal_receiver();
@@ -1062,6 +2228,28 @@ pub fn init() {
         }
     });
 
+    // 修复服务
+    spawn(async {
+        let receiver = RepairService::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 重启服务
+    spawn(async {
+        let receiver = RestartService::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
     // 通过服务启动 Clash
     spawn(async {
         let receiver = StartClash::get_dart_signal_receiver();
@@ -1105,4 +2293,237 @@ pub fn init() {
             });
         }
     });
+
+    // 清除核心缓存
+    spawn(async {
+        let receiver = FlushCoreCache::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 列出孤立核心进程
+    spawn(async {
+        let receiver = ListOrphanCoreProcesses::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 清理孤立核心进程
+    spawn(async {
+        let receiver = KillOrphanCoreProcesses::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // DNS 可达性检测
+    spawn(async {
+        let receiver = CheckDnsReachability::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 服务日志路径
+    spawn(async {
+        let receiver = GetServiceLogsPath::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 服务路径
+    spawn(async {
+        let receiver = GetServicePaths::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 出口 IP 检测
+    spawn(async {
+        let receiver = CheckEgressIp::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 空闲端口建议
+    spawn(async {
+        let receiver = SuggestFreePorts::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 防泄漏开关
+    spawn(async {
+        let receiver = SetKillSwitch::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 切换配置
+    spawn(async {
+        let receiver = SwitchConfig::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+
+    // 导出运行配置
+    spawn(async {
+        let receiver = ExportRunningConfig::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // 模拟在第 answer_on 次请求时才响应的 IPC
+    #[cfg(any(windows, target_os = "linux"))]
+    async fn stub_ipc(attempts: &AtomicUsize, answer_on: usize) -> Option<u32> {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        (attempt >= answer_on).then_some(42)
+    }
+
+    #[cfg(any(windows, target_os = "linux"))]
+    #[tokio::test]
+    async fn test_liveness_answers_on_third_attempt() {
+        let attempts = AtomicUsize::new(0);
+        let start = std::time::Instant::now();
+        let result = retry_liveness(|| stub_ipc(&attempts, 3)).await;
+        assert_eq!(result, Some(42));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert!(start.elapsed() >= LIVENESS_RETRY_INTERVAL * 2);
+    }
+
+    #[cfg(any(windows, target_os = "linux"))]
+    #[tokio::test]
+    async fn test_liveness_fast_path_and_give_up() {
+        // 首次即响应：不等待
+        let attempts = AtomicUsize::new(0);
+        let start = std::time::Instant::now();
+        assert_eq!(retry_liveness(|| stub_ipc(&attempts, 1)).await, Some(42));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(start.elapsed() < LIVENESS_RETRY_INTERVAL);
+
+        // 始终不响应：尝试次数有上限
+        let attempts = AtomicUsize::new(0);
+        assert_eq!(retry_liveness(|| stub_ipc(&attempts, 10)).await, None);
+        assert_eq!(attempts.load(Ordering::SeqCst), LIVENESS_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_is_restart_complete() {
+        // 新实例：运行时长不超过重启耗时
+        assert!(is_restart_complete(0, Duration::from_millis(800)));
+        assert!(is_restart_complete(3, Duration::from_millis(2500)));
+        // 旧实例仍在运行：运行时长远超重启耗时
+        assert!(!is_restart_complete(3600, Duration::from_secs(2)));
+        assert!(!is_restart_complete(5, Duration::from_millis(500)));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_debounce() {
+        let debouncer = HeartbeatDebouncer::new(Duration::from_millis(200));
+        let sends = AtomicUsize::new(0);
+        let send = || async {
+            sends.fetch_add(1, Ordering::SeqCst);
+        };
+
+        // 间隔内的连续请求只实际发送一次
+        let mut sent = Vec::new();
+        for _ in 0..10 {
+            sent.push(debouncer.run(send).await);
+        }
+        assert_eq!(sends.load(Ordering::SeqCst), 1);
+        assert_eq!(sent.iter().filter(|is_sent| **is_sent).count(), 1);
+        assert!(sent[0]);
+
+        // 超过最小间隔后恢复发送
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(debouncer.run(send).await);
+        assert_eq!(sends.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_parse_version_output_json() {
+        assert_eq!(
+            parse_version_output("{\"version\":\"1.5.2\"}\n"),
+            Some("1.5.2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_version_output_legacy_fallback() {
+        assert_eq!(
+            parse_version_output("Stelliberty Service v1.5.0\n"),
+            Some("1.5.0".to_string())
+        );
+        assert_eq!(parse_version_output("Stelliberty Service v"), None);
+        assert_eq!(parse_version_output("unexpected output"), None);
+    }
+
+    #[test]
+    fn test_elevated_args_pass_private_dir() {
+        let binary = Path::new("/home/user/.local/share/stelliberty/service/stelliberty-service");
+        assert_eq!(
+            elevated_args("install", binary),
+            vec![
+                "install",
+                "--private-dir",
+                "/home/user/.local/share/stelliberty/service"
+            ]
+        );
+        assert_eq!(
+            elevated_args("repair", binary)[1..3],
+            [
+                "--private-dir",
+                "/home/user/.local/share/stelliberty/service"
+            ]
+        );
+        assert_eq!(elevated_args("uninstall", binary), vec!["uninstall"]);
+    }
 }
@@ -0,0 +1,290 @@
+// TUN 模式就绪检查：TUN 需要由特权服务启动核心，并依赖平台的虚拟网卡驱动。
+// 逐项报告服务安装、服务运行与驱动状态，便于界面引导用户先安装服务。
+
+use super::preflight::ServiceProbe;
+use super::service_manager::ServiceManager;
+use rinf::{DartSignal, RustSignal, SignalPiece};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// 检查项名称
+const ITEM_SERVICE_INSTALLED: &str = "service_installed";
+const ITEM_SERVICE_RUNNING: &str = "service_running";
+const ITEM_TUN_DRIVER: &str = "tun_driver";
+
+// Dart → Rust：检查 TUN 模式的前置条件
+#[derive(Deserialize, DartSignal)]
+pub struct CheckTunRequirements {
+    // 核心程序路径（Windows 下在其所在目录查找 wintun.dll）
+    pub core_path: String,
+}
+
+// 单项检查结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SignalPiece)]
+pub struct TunRequirementItem {
+    pub name: String,
+    pub is_passed: bool,
+    pub detail: String,
+}
+
+// Rust → Dart：TUN 模式就绪状态
+#[derive(Serialize, RustSignal)]
+pub struct TunRequirementsResult {
+    pub is_ready: bool,
+    pub is_service_installed: bool,
+    pub is_service_running: bool,
+    // 顺序固定：服务安装、服务运行、驱动
+    pub items: Vec<TunRequirementItem>,
+}
+
+// 各平台 TUN 依赖的虚拟网卡驱动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TunDriver {
+    // Windows：wintun.dll
+    Wintun,
+    // Linux：/dev/net/tun 设备
+    TunDevice,
+    // macOS：系统内置的 utun 接口
+    Utun,
+}
+
+impl TunDriver {
+    // 当前平台的驱动
+    pub fn current() -> Self {
+        #[cfg(windows)]
+        {
+            Self::Wintun
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            Self::TunDevice
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            Self::Utun
+        }
+    }
+
+    // 驱动的候选位置，任一存在即可
+    fn candidate_paths(self, core_path: &Path) -> Vec<PathBuf> {
+        match self {
+            Self::Wintun => {
+                let mut paths: Vec<PathBuf> = core_path
+                    .parent()
+                    .map(|dir| dir.join("wintun.dll"))
+                    .into_iter()
+                    .collect();
+                if let Some(system_root) = std::env::var_os("SystemRoot") {
+                    paths.push(
+                        PathBuf::from(system_root)
+                            .join("System32")
+                            .join("wintun.dll"),
+                    );
+                }
+                paths
+            }
+            Self::TunDevice => vec![PathBuf::from("/dev/net/tun")],
+            Self::Utun => Vec::new(),
+        }
+    }
+}
+
+impl CheckTunRequirements {
+    pub async fn handle(self) {
+        log::info!("检查 TUN 模式前置条件");
+
+        let service = ServiceProbe {
+            is_installed: ServiceManager::is_service_registered(),
+            running_version: ServiceManager::default()
+                .running_service_version()
+                .await
+                .ok(),
+        };
+        let driver = probe_driver(TunDriver::current(), Path::new(&self.core_path), |path| {
+            path.exists()
+        });
+
+        let items = assemble_requirements(&service, driver);
+        for item in items.iter().filter(|item| !item.is_passed) {
+            log::info!("TUN 前置条件未满足[{}]：{}", item.name, item.detail);
+        }
+
+        TunRequirementsResult {
+            is_ready: items.iter().all(|item| item.is_passed),
+            is_service_installed: items[0].is_passed,
+            is_service_running: items[1].is_passed,
+            items,
+        }
+        .send_signal_to_dart();
+    }
+}
+
+// 检查驱动是否存在（exists 由调用方提供，便于测试）
+pub fn probe_driver(
+    driver: TunDriver,
+    core_path: &Path,
+    exists: impl Fn(&Path) -> bool,
+) -> TunRequirementItem {
+    let (is_passed, detail) = match driver {
+        TunDriver::Utun => (true, "使用系统内置的 utun 接口".to_string()),
+        _ => {
+            let candidates = driver.candidate_paths(core_path);
+            match candidates.iter().find(|path| exists(path)) {
+                Some(path) => (true, format!("已找到 {}", path.display())),
+                None => {
+                    let locations = candidates
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join("、");
+                    let detail = match driver {
+                        TunDriver::Wintun => {
+                            format!("未找到 wintun.dll（已查找：{}）", locations)
+                        }
+                        _ => format!("未找到 TUN 设备 {}，请确认内核已加载 tun 模块", locations),
+                    };
+                    (false, detail)
+                }
+            }
+        }
+    };
+
+    TunRequirementItem {
+        name: ITEM_TUN_DRIVER.to_string(),
+        is_passed,
+        detail,
+    }
+}
+
+// 组装检查结果：服务安装、服务运行、驱动
+pub fn assemble_requirements(
+    service: &ServiceProbe,
+    driver: TunRequirementItem,
+) -> Vec<TunRequirementItem> {
+    // 开发环境下服务可能未注册但正在运行，以实际响应为准
+    let is_installed = service.is_installed || service.running_version.is_some();
+    let installed = TunRequirementItem {
+        name: ITEM_SERVICE_INSTALLED.to_string(),
+        is_passed: is_installed,
+        detail: if is_installed {
+            "服务已安装".to_string()
+        } else {
+            "TUN 模式需要通过服务以管理员权限运行核心，请先安装服务".to_string()
+        },
+    };
+
+    let running = TunRequirementItem {
+        name: ITEM_SERVICE_RUNNING.to_string(),
+        is_passed: service.running_version.is_some(),
+        detail: match (&service.running_version, is_installed) {
+            (Some(version), _) => format!("服务运行中（v{}）", version),
+            (None, true) => "服务已安装但未运行，请启动或修复服务".to_string(),
+            (None, false) => "服务未运行".to_string(),
+        },
+    };
+
+    vec![installed, running, driver]
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = CheckTunRequirements::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(items: &[TunRequirementItem]) -> Vec<(&str, bool)> {
+        items
+            .iter()
+            .map(|item| (item.name.as_str(), item.is_passed))
+            .collect()
+    }
+
+    #[test]
+    fn test_probe_driver() {
+        let core_path = Path::new("/opt/stelliberty/core/mihomo");
+
+        let present = probe_driver(TunDriver::TunDevice, core_path, |path| {
+            path == Path::new("/dev/net/tun")
+        });
+        assert!(present.is_passed);
+        let missing = probe_driver(TunDriver::TunDevice, core_path, |_| false);
+        assert!(!missing.is_passed);
+        assert!(missing.detail.contains("/dev/net/tun"));
+
+        // wintun.dll 放在核心程序旁即可
+        let beside_core = probe_driver(TunDriver::Wintun, core_path, |path| {
+            path == Path::new("/opt/stelliberty/core/wintun.dll")
+        });
+        assert!(beside_core.is_passed);
+        let missing = probe_driver(TunDriver::Wintun, core_path, |_| false);
+        assert!(!missing.is_passed);
+        assert!(missing.detail.contains("wintun.dll"));
+
+        assert!(probe_driver(TunDriver::Utun, core_path, |_| false).is_passed);
+    }
+
+    #[test]
+    fn test_assemble_requirements() {
+        let driver = |is_passed| TunRequirementItem {
+            name: ITEM_TUN_DRIVER.to_string(),
+            is_passed,
+            detail: String::new(),
+        };
+
+        let running = ServiceProbe {
+            is_installed: true,
+            running_version: Some("1.5.2".to_string()),
+        };
+        let items = assemble_requirements(&running, driver(true));
+        assert_eq!(
+            summary(&items),
+            vec![
+                ("service_installed", true),
+                ("service_running", true),
+                ("tun_driver", true),
+            ]
+        );
+        assert!(items[1].detail.contains("1.5.2"));
+
+        let stopped = ServiceProbe {
+            is_installed: true,
+            running_version: None,
+        };
+        let items = assemble_requirements(&stopped, driver(true));
+        assert_eq!(
+            summary(&items)[..2],
+            [("service_installed", true), ("service_running", false)]
+        );
+        assert!(items[1].detail.contains("未运行"));
+
+        // 未安装时引导用户安装服务
+        let missing = ServiceProbe {
+            is_installed: false,
+            running_version: None,
+        };
+        let items = assemble_requirements(&missing, driver(false));
+        assert_eq!(
+            summary(&items),
+            vec![
+                ("service_installed", false),
+                ("service_running", false),
+                ("tun_driver", false),
+            ]
+        );
+        assert!(items[0].detail.contains("安装服务"));
+    }
+}
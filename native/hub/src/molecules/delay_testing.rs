@@ -3,8 +3,8 @@
 pub mod tester;
 
 pub use tester::{
-    BatchDelayTestComplete, BatchDelayTestRequest, DelayTestProgress, SingleDelayTestRequest,
-    SingleDelayTestResult,
+    BatchDelayTestComplete, BatchDelayTestRequest, DelayFailure, DelayTestProgress,
+    ProxyDelayResult, SingleDelayTestRequest, SingleDelayTestResult, TestProxyDelay,
 };
 
 pub fn init_listeners() {
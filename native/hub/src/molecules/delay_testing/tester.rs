@@ -1,8 +1,9 @@
 // Clash 延迟测试模块
 
 use futures_util::stream::{self, StreamExt};
-use rinf::{DartSignal, RustSignal};
+use rinf::{DartSignal, RustSignal, SignalPiece};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::spawn;
@@ -24,6 +25,52 @@ pub struct SingleDelayTestResult {
     pub delay_ms: i32, // -1 表示失败
 }
 
+// Dart → Rust：按需测试单个代理延迟（返回失败原因）
+#[derive(Deserialize, DartSignal)]
+pub struct TestProxyDelay {
+    pub name: String,
+    pub url: String,
+    pub timeout_ms: u32,
+}
+
+// 延迟测试失败类型
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, SignalPiece)]
+pub enum DelayFailure {
+    // 超过超时时间未完成
+    Timeout,
+    // 节点无法连接到测试地址
+    Unreachable,
+    // 核心中不存在该节点
+    NodeNotFound,
+    // IPC 请求失败或响应无法解析
+    Error,
+}
+
+// Rust → Dart：单个代理延迟测试结果
+#[derive(Serialize, RustSignal)]
+pub struct ProxyDelayResult {
+    pub name: String,
+    pub delay_ms: Option<u32>,
+    pub failure: Option<DelayFailure>,
+    pub error_message: Option<String>,
+}
+
+// 延迟测试失败详情
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelayTestError {
+    pub failure: DelayFailure,
+    pub message: String,
+}
+
+impl DelayTestError {
+    fn new(failure: DelayFailure, message: impl Into<String>) -> Self {
+        Self {
+            failure,
+            message: message.into(),
+        }
+    }
+}
+
 // Dart → Rust：批量延迟测试请求
 #[derive(Deserialize, DartSignal)]
 pub struct BatchDelayTestRequest {
@@ -69,6 +116,16 @@ pub fn init() {
         log::info!("单节点延迟测试消息通道已关闭，退出监听器");
     });
 
+    // 按需延迟测试请求监听器
+    spawn(async {
+        let receiver = TestProxyDelay::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            spawn(async move {
+                dart_signal.message.handle().await;
+            });
+        }
+    });
+
     // 批量延迟测试请求监听器
     spawn(async {
         let receiver = BatchDelayTestRequest::get_dart_signal_receiver();
@@ -100,6 +157,40 @@ async fn handle_single_delay_test_request(request: SingleDelayTestRequest) {
     .send_signal_to_dart();
 }
 
+impl TestProxyDelay {
+    pub async fn handle(self) {
+        log::info!(
+            "按需测试节点延迟：{}（timeout {}ms，url={}）",
+            self.name,
+            self.timeout_ms,
+            self.url
+        );
+
+        let result = measure_delay(&self.name, &self.url, self.timeout_ms, |path| async move {
+            IpcClient::get_with_pool(&path).await
+        })
+        .await;
+        let response = match result {
+            Ok(delay_ms) => ProxyDelayResult {
+                name: self.name,
+                delay_ms: Some(delay_ms),
+                failure: None,
+                error_message: None,
+            },
+            Err(e) => {
+                log::warn!("节点延迟测试失败：{} - {}", self.name, e.message);
+                ProxyDelayResult {
+                    name: self.name,
+                    delay_ms: None,
+                    failure: Some(e.failure),
+                    error_message: Some(e.message),
+                }
+            }
+        };
+        response.send_signal_to_dart();
+    }
+}
+
 // 处理批量延迟测试请求
 async fn handle_batch_delay_test_request(request: BatchDelayTestRequest) {
     let node_names = request.node_names;
@@ -200,76 +291,154 @@ async fn batch_test_delays(
     results
 }
 
-fn timeout_result(node_name: &str, timeout_ms: u32, elapsed_ms: u128, retry_count: u32) -> i32 {
-    log::warn!(
-        "节点延迟测试超时：{} - 超过 {}ms（耗时 {}ms，重试 {} 次）",
-        node_name,
-        timeout_ms,
-        elapsed_ms,
-        retry_count
-    );
-    -1
+// 测试单个节点延迟：通过 IPC 调用 Clash API，失败时返回 -1（批量与旧版单节点测试使用）
+async fn test_single_node(node_name: &str, test_url: &str, timeout_ms: u32) -> i32 {
+    let start_time = Instant::now();
+    let result = measure_delay(node_name, test_url, timeout_ms, |path| async move {
+        IpcClient::get_with_pool(&path).await
+    })
+    .await;
+    let elapsed_ms = start_time.elapsed().as_millis();
+
+    match result {
+        Ok(delay_ms) => {
+            log::info!(
+                "节点延迟测试成功：{} - {}ms（耗时 {}ms）",
+                node_name,
+                delay_ms,
+                elapsed_ms
+            );
+            delay_ms as i32
+        }
+        Err(e) => {
+            log::warn!(
+                "节点延迟测试失败：{} - {}（耗时 {}ms）",
+                node_name,
+                e.message,
+                elapsed_ms
+            );
+            -1
+        }
+    }
 }
 
-// 测试单个节点延迟：通过 IPC 调用 Clash API。
-// GET /proxies/{proxyName}/delay?timeout={timeout}&url={testUrl}
-async fn test_single_node(node_name: &str, test_url: &str, timeout_ms: u32) -> i32 {
-    // 构建 Clash API 路径
-    let encoded_name = urlencoding::encode(node_name);
-    let path = format!(
+// 延迟测试接口路径：GET /proxies/{proxyName}/delay?timeout={timeout}&url={testUrl}
+pub fn delay_path(node_name: &str, test_url: &str, timeout_ms: u32) -> String {
+    format!(
         "/proxies/{}/delay?timeout={}&url={}",
-        encoded_name, timeout_ms, test_url
-    );
+        urlencoding::encode(node_name),
+        timeout_ms,
+        urlencoding::encode(test_url)
+    )
+}
 
-    let start_time = Instant::now();
+// 发起延迟测试：fetch 负责按路径请求控制器，超时后不再等待响应
+pub async fn measure_delay<F, Fut>(
+    node_name: &str,
+    test_url: &str,
+    timeout_ms: u32,
+    fetch: F,
+) -> Result<u32, DelayTestError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let path = delay_path(node_name, test_url, timeout_ms);
     let timeout = Duration::from_millis(timeout_ms as u64);
-    let response = tokio::time::timeout(timeout, IpcClient::get_with_pool(&path)).await;
-
-    match response {
-        Ok(result) => match result {
-            Ok(body) => match serde_json::from_str::<serde_json::Value>(&body) {
-                Ok(json) => {
-                    if let Some(delay) = json.get("delay").and_then(|v| v.as_i64()) {
-                        let delay_i32 = delay as i32;
-                        let elapsed_ms = start_time.elapsed().as_millis();
-                        if delay_i32 > 0 {
-                            log::info!(
-                                "节点延迟测试成功：{} - {}ms（耗时 {}ms，重试 0 次）",
-                                node_name,
-                                delay_i32,
-                                elapsed_ms
-                            );
-                        } else {
-                            log::warn!(
-                                "节点延迟测试失败：{} - 超时（耗时 {}ms，重试 0 次）",
-                                node_name,
-                                elapsed_ms
-                            );
-                        }
-                        return delay_i32;
-                    }
-                    log::error!("节点延迟测试响应格式错误：{}", node_name);
-                    -1
-                }
-                Err(e) => {
-                    log::error!("节点延迟测试 JSON 解析失败：{} - {}", node_name, e);
-                    -1
-                }
-            },
-            Err(e) => {
-                if e.contains("HTTP 503") || e.contains("HTTP 504") {
-                    return timeout_result(
-                        node_name,
-                        timeout_ms,
-                        start_time.elapsed().as_millis(),
-                        0,
-                    );
-                }
+    match tokio::time::timeout(timeout, fetch(path)).await {
+        Ok(response) => parse_delay_response(response),
+        Err(_) => Err(DelayTestError::new(
+            DelayFailure::Timeout,
+            format!("超过 {}ms", timeout_ms),
+        )),
+    }
+}
 
-                log::warn!("节点延迟测试 IPC 请求失败：{} - {}", node_name, e);
-                -1
-            }
-        },
-        Err(_) => timeout_result(node_name, timeout_ms, start_time.elapsed().as_millis(), 0),
+// 解析控制器响应：404 为节点不存在，504 为超时，503 为节点无法连通
+pub fn parse_delay_response(response: Result<String, String>) -> Result<u32, DelayTestError> {
+    let body = response.map_err(|e| {
+        let failure = match e.as_str() {
+            "HTTP 404" => DelayFailure::NodeNotFound,
+            "HTTP 504" => DelayFailure::Timeout,
+            "HTTP 503" => DelayFailure::Unreachable,
+            _ => DelayFailure::Error,
+        };
+        DelayTestError::new(failure, e)
+    })?;
+
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| DelayTestError::new(DelayFailure::Error, format!("JSON 解析失败：{}", e)))?;
+    match json.get("delay").and_then(|v| v.as_i64()) {
+        Some(delay) if delay > 0 => Ok(delay as u32),
+        // 部分核心以 delay: 0 表示超时
+        Some(_) => Err(DelayTestError::new(DelayFailure::Timeout, "延迟为 0")),
+        None => Err(DelayTestError::new(
+            DelayFailure::Error,
+            format!("响应格式错误：{}", body),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_measure_delay_with_stub() {
+        let result = measure_delay("香港 01", "https://www.gstatic.com/generate_204", 3000, |path| {
+            assert_eq!(
+                path,
+                "/proxies/%E9%A6%99%E6%B8%AF%2001/delay?timeout=3000&url=https%3A%2F%2Fwww.gstatic.com%2Fgenerate_204"
+            );
+            async { Ok(r#"{"delay":123}"#.to_string()) }
+        })
+        .await;
+        assert_eq!(result, Ok(123));
+
+        // 未知节点
+        let result = measure_delay("missing", "http://cp.cloudflare.com", 3000, |_| async {
+            Err("HTTP 404".to_string())
+        })
+        .await;
+        assert_eq!(
+            result.map_err(|e| e.failure),
+            Err(DelayFailure::NodeNotFound)
+        );
+
+        // 请求未在超时时间内完成
+        let result = measure_delay("slow", "http://cp.cloudflare.com", 10, |_| async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(r#"{"delay":1}"#.to_string())
+        })
+        .await;
+        assert_eq!(result.map_err(|e| e.failure), Err(DelayFailure::Timeout));
+    }
+
+    #[test]
+    fn test_parse_delay_response() {
+        let failure = |response: Result<String, String>| {
+            parse_delay_response(response).map_err(|e| e.failure)
+        };
+        assert_eq!(failure(Ok(r#"{"delay":88}"#.to_string())), Ok(88));
+        assert_eq!(
+            failure(Ok(r#"{"delay":0}"#.to_string())),
+            Err(DelayFailure::Timeout)
+        );
+        assert_eq!(
+            failure(Err("HTTP 504".to_string())),
+            Err(DelayFailure::Timeout)
+        );
+        assert_eq!(
+            failure(Err("HTTP 503".to_string())),
+            Err(DelayFailure::Unreachable)
+        );
+        assert_eq!(
+            failure(Ok(r#"{"message":"error"}"#.to_string())),
+            Err(DelayFailure::Error)
+        );
+        assert_eq!(
+            failure(Err("IPC 连接失败".to_string())),
+            Err(DelayFailure::Error)
+        );
     }
 }
@@ -1,14 +1,17 @@
 // 覆写处理分子模块
 
 pub mod downloader;
+pub mod merged_export;
 
 // 内部使用
 mod processor;
 
 pub use downloader::{DownloadOverrideRequest, DownloadOverrideResponse};
+pub use merged_export::{ExportMergedConfig, ExportMergedConfigResult};
 pub use processor::{
-    ApplyOverridesRequest, ApplyOverridesResponse, ParseSubscriptionRequest,
-    ParseSubscriptionResponse,
+    ApplyOverridesRequest, ApplyOverridesResponse, ConfigTemplateList, ListConfigTemplates,
+    ParseProxyBlob, ParseProxyBlobResult, ParseProxyLink, ParseProxyLinkResult,
+    ParseSubscriptionRequest, ParseSubscriptionResponse,
 };
 
 // 从分子层共享类型导入
@@ -20,4 +23,5 @@ pub use crate::atoms::OverrideProcessor;
 pub fn init_listeners() {
     processor::init();
     downloader::init();
+    merged_export::init();
 }
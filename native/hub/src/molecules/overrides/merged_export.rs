@@ -0,0 +1,205 @@
+// 导出合并后的配置：按应用相同的流程（订阅解析 + 依次应用覆写）生成最终配置并校验，
+// 便于用户查看实际交给核心的内容。
+
+use crate::atoms::config_validator::ValidationReport;
+use crate::atoms::{ConfigValidator, OverrideProcessor, ProxyParser};
+use crate::molecules::clash_config::validation::ConfigIssue;
+use crate::molecules::{OverrideConfig, OverrideFormat};
+use rinf::{DartSignal, RustSignal};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// Dart → Rust：导出订阅与覆写合并后的配置
+#[derive(Deserialize, DartSignal)]
+pub struct ExportMergedConfig {
+    pub subscription_path: String,
+    // 按应用顺序排列的覆写文件（.js 为 JavaScript 覆写，其余按 YAML 合并）
+    pub override_paths: Vec<String>,
+}
+
+// Rust → Dart：合并结果
+#[derive(Serialize, RustSignal)]
+pub struct ExportMergedConfigResult {
+    pub merged_config: Option<String>,
+    // 合并结果是否存在错误级别的校验问题
+    pub has_errors: bool,
+    pub issues: Vec<ConfigIssue>,
+    pub error_message: Option<String>,
+}
+
+impl ExportMergedConfig {
+    pub fn handle(self) -> ExportMergedConfigResult {
+        log::info!(
+            "导出合并配置：{}（覆写 {} 个）",
+            self.subscription_path,
+            self.override_paths.len()
+        );
+
+        match export_merged_config(Path::new(&self.subscription_path), &self.override_paths) {
+            Ok((merged_config, report)) => {
+                report.log_issues();
+                ExportMergedConfigResult {
+                    merged_config: Some(merged_config),
+                    has_errors: report.has_errors(),
+                    issues: report.issues.into_iter().map(ConfigIssue::from).collect(),
+                    error_message: None,
+                }
+            }
+            Err(e) => {
+                log::error!("导出合并配置失败：{}", e);
+                ExportMergedConfigResult {
+                    merged_config: None,
+                    has_errors: true,
+                    issues: Vec::new(),
+                    error_message: Some(e),
+                }
+            }
+        }
+    }
+}
+
+// 读取订阅与覆写文件，合并后返回最终配置及其校验结果
+pub fn export_merged_config(
+    subscription_path: &Path,
+    override_paths: &[String],
+) -> Result<(String, ValidationReport), String> {
+    let subscription = std::fs::read_to_string(subscription_path)
+        .map_err(|e| format!("读取订阅配置失败：{}，{}", subscription_path.display(), e))?;
+    let overrides = override_paths
+        .iter()
+        .map(|path| load_override(Path::new(path)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let merged = merge_config(&subscription, overrides)?;
+    let report = ConfigValidator::validate_content(&merged)?;
+    Ok((merged, report))
+}
+
+// 与应用覆写时相同：先将订阅解析为 Clash 配置，再按顺序应用覆写
+pub fn merge_config(subscription: &str, overrides: Vec<OverrideConfig>) -> Result<String, String> {
+    let base_config = ProxyParser::parse_subscription(subscription)
+        .map_err(|e| format!("订阅解析失败：{}", e))?;
+    if overrides.is_empty() {
+        return Ok(base_config);
+    }
+    OverrideProcessor::new()?.apply_overrides(&base_config, overrides)
+}
+
+// 读取覆写文件，按扩展名判断格式
+fn load_override(path: &Path) -> Result<OverrideConfig, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("读取覆写文件失败：{}，{}", path.display(), e))?;
+    let is_javascript = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("js"));
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    Ok(OverrideConfig {
+        id: name.clone(),
+        name,
+        format: if is_javascript {
+            OverrideFormat::Javascript
+        } else {
+            OverrideFormat::Yaml
+        },
+        content,
+    })
+}
+
+pub fn init() {
+    tokio::spawn(async {
+        let receiver = ExportMergedConfig::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            // 文件读取与覆写执行为阻塞操作
+            tokio::task::spawn_blocking(move || {
+                message.handle().send_signal_to_dart();
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_CONFIG: &str = r#"
+mode: rule
+proxies:
+  - {name: HK-01, type: ss, server: hk.example.com, port: 8388, cipher: aes-128-gcm, password: p}
+proxy-groups:
+  - {name: PROXY, type: select, proxies: [HK-01]}
+rules:
+  - DOMAIN-SUFFIX,example.com,PROXY
+  - MATCH,PROXY
+"#;
+
+    fn parse(content: &str) -> serde_yaml_ng::Value {
+        serde_yaml_ng::from_str(content).unwrap_or_else(|e| panic!("解析失败：{}", e))
+    }
+
+    #[test]
+    fn test_merge_mode_and_appended_rule() {
+        let overrides = vec![OverrideConfig {
+            id: "custom".to_string(),
+            name: "custom".to_string(),
+            format: OverrideFormat::Yaml,
+            content: "mode: global\nrules+:\n  - DOMAIN,ads.example.com,REJECT\n".to_string(),
+        }];
+        let merged = merge_config(BASE_CONFIG, overrides).unwrap_or_else(|e| panic!("{}", e));
+        let config = parse(&merged);
+
+        assert_eq!(config["mode"].as_str(), Some("global"));
+        let rules: Vec<&str> = config["rules"]
+            .as_sequence()
+            .map(|rules| rules.iter().filter_map(|rule| rule.as_str()).collect())
+            .unwrap_or_default();
+        assert_eq!(
+            rules,
+            vec![
+                "DOMAIN-SUFFIX,example.com,PROXY",
+                "MATCH,PROXY",
+                "DOMAIN,ads.example.com,REJECT"
+            ]
+        );
+        // 未覆写的字段保持不变
+        assert_eq!(config["proxies"], parse(BASE_CONFIG)["proxies"]);
+    }
+
+    #[test]
+    fn test_export_from_files() {
+        let dir = std::env::temp_dir().join(format!("stelliberty-merged-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap_or_else(|e| panic!("创建目录失败：{}", e));
+        let write = |name: &str, content: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, content).unwrap_or_else(|e| panic!("写入失败：{}", e));
+            path.to_string_lossy().into_owned()
+        };
+
+        let subscription = write("subscription.yaml", BASE_CONFIG);
+        let overrides = vec![
+            write("mode.yaml", "mode: direct\n"),
+            // 替换规则（数组默认整体替换）后再前置一条
+            write("rules.yaml", "rules:\n  - MATCH,DIRECT\n"),
+            write("prepend.yml", "+rules:\n  - DOMAIN,a.example.com,PROXY\n"),
+        ];
+
+        let (merged, report) = export_merged_config(Path::new(&subscription), &overrides)
+            .unwrap_or_else(|e| panic!("{}", e));
+        let config = parse(&merged);
+        assert_eq!(config["mode"].as_str(), Some("direct"));
+        assert_eq!(
+            config["rules"],
+            parse("['DOMAIN,a.example.com,PROXY', 'MATCH,DIRECT']")
+        );
+        assert!(!report.has_errors());
+
+        let missing = dir.join("missing.yaml").to_string_lossy().into_owned();
+        assert!(export_merged_config(Path::new(&subscription), &[missing]).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
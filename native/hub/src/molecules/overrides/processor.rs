@@ -1,10 +1,15 @@
 // 覆写处理器
 // 处理配置覆写（YAML 合并 + JavaScript 执行）
 
-use crate::atoms::ProxyParser;
 use crate::atoms::override_processor::OverrideProcessor;
+use crate::atoms::path_service;
+use crate::atoms::proxy_parser::template;
+use crate::atoms::proxy_parser::{LinkLine, LinkLineOutcome};
+use crate::atoms::{ParseOptions, ProxyParser};
 use crate::molecules::OverrideConfig;
-use rinf::{DartSignal, RustSignal};
+use crate::molecules::subscription::SubscriptionInfoData;
+use crate::molecules::subscription::meta::{self, SubscriptionMeta};
+use rinf::{DartSignal, RustSignal, SignalPiece};
 use serde::{Deserialize, Serialize};
 
 // Dart → Rust：应用覆写请求
@@ -28,6 +33,70 @@ pub struct ApplyOverridesResponse {
 pub struct ParseSubscriptionRequest {
     pub request_id: String, // 请求标识符，用于响应匹配
     pub content: String,
+    // 名称匹配该正则的节点不加入 AUTO 组（仍保留在 PROXY 组）
+    pub auto_exclude_pattern: Option<String>,
+    // 添加到每个节点名称前的前缀，多个订阅合并时避免重名
+    pub name_prefix: Option<String>,
+    // 配置模板名称（模板目录中的文件名或内置的 default），为空时生成默认配置
+    pub template_name: Option<String>,
+    // 按节点名称识别地区，生成各地区的自动测速组
+    pub group_by_region: bool,
+    // 订阅 ID：设置时解析成功后记录订阅元数据（更新时间、节点数等）
+    pub subscription_id: Option<String>,
+    // 下载时读取的 subscription-userinfo，随元数据一同记录
+    pub subscription_info: Option<SubscriptionInfoData>,
+}
+
+// Dart → Rust：列出可用的配置模板
+#[derive(Deserialize, DartSignal)]
+pub struct ListConfigTemplates;
+
+// Rust → Dart：可用的配置模板名称（内置模板在前）
+#[derive(Serialize, RustSignal)]
+pub struct ConfigTemplateList {
+    pub names: Vec<String>,
+}
+
+// Dart → Rust：解析单个代理链接（手动添加节点前预览）
+#[derive(Deserialize, DartSignal)]
+pub struct ParseProxyLink {
+    pub link: String,
+}
+
+// Rust → Dart：代理链接解析结果
+#[derive(Serialize, RustSignal)]
+pub struct ParseProxyLinkResult {
+    // 补全缺省字段后的 Clash 节点（JSON）
+    pub node_json: Option<String>,
+    pub error_message: Option<String>,
+}
+
+// Dart → Rust：逐行解析粘贴的混合文本（链接、注释与其他内容）
+#[derive(Deserialize, DartSignal)]
+pub struct ParseProxyBlob {
+    pub text: String,
+}
+
+// 粘贴文本中单行的解析结果
+#[derive(Serialize, SignalPiece)]
+pub struct ProxyBlobLine {
+    // 在原文中的行号（从 1 开始）
+    pub line_number: u32,
+    pub source: String,
+    // 解析成功时为 Clash 节点（JSON）
+    pub node_json: Option<String>,
+    // 未导入的原因（注释行或解析失败）
+    pub skip_reason: Option<String>,
+}
+
+// Rust → Dart：粘贴文本解析结果
+#[derive(Serialize, RustSignal)]
+pub struct ParseProxyBlobResult {
+    // 每个非空行的结果，按原文顺序
+    pub lines: Vec<ProxyBlobLine>,
+    // 由成功解析的节点生成的配置
+    pub parsed_config: Option<String>,
+    pub error_message: Option<String>,
 }
 
 // Rust → Dart：解析订阅响应
@@ -110,8 +179,35 @@ impl ParseSubscriptionRequest {
             self.content.len()
         );
 
-        match ProxyParser::parse_subscription(&self.content) {
-            Ok(parsed_config) => {
+        let template = self
+            .template_name
+            .as_deref()
+            .filter(|name| !name.trim().is_empty())
+            .map(|name| template::load_template(&path_service::templates_dir(), name))
+            .transpose();
+        let options = template.map(|template| ParseOptions {
+            auto_exclude_pattern: self.auto_exclude_pattern,
+            name_prefix: self.name_prefix,
+            template,
+            group_by_region: self.group_by_region,
+        });
+        match options.and_then(|options| {
+            ProxyParser::parse_subscription_detailed_with_options(&self.content, &options)
+        }) {
+            Ok(parsed) => {
+                if let Some(id) = self.subscription_id.as_deref() {
+                    let meta = SubscriptionMeta::from_parsed(
+                        &parsed,
+                        self.subscription_info,
+                        std::time::SystemTime::now(),
+                    );
+                    if let Err(e) =
+                        meta::record_meta(&path_service::subscription_meta_file(), id, meta)
+                    {
+                        log::warn!("记录订阅元数据失败 [{}]：{}", id, e);
+                    }
+                }
+                let parsed_config = parsed.yaml;
                 log::info!(
                     "订阅解析成功 [{}]，配置长度：{}字节",
                     self.request_id,
@@ -139,6 +235,81 @@ impl ParseSubscriptionRequest {
     }
 }
 
+impl ListConfigTemplates {
+    pub fn handle(self) {
+        ConfigTemplateList {
+            names: template::list_templates(&path_service::templates_dir()),
+        }
+        .send_signal_to_dart();
+    }
+}
+
+impl ParseProxyLink {
+    pub fn handle(self) {
+        let result = match ProxyParser::parse_proxy_link(&self.link) {
+            Ok(node) => ParseProxyLinkResult {
+                node_json: Some(node.to_string()),
+                error_message: None,
+            },
+            Err(e) => {
+                log::warn!("代理链接解析失败：{}", e);
+                ParseProxyLinkResult {
+                    node_json: None,
+                    error_message: Some(e),
+                }
+            }
+        };
+        result.send_signal_to_dart();
+    }
+}
+
+impl ParseProxyBlob {
+    pub fn handle(self) {
+        let result = match ProxyParser::parse_proxy_blob(&self.text, &ParseOptions::default()) {
+            Ok(parsed) => {
+                let lines: Vec<ProxyBlobLine> =
+                    parsed.lines.into_iter().map(ProxyBlobLine::from).collect();
+                let imported = lines.iter().filter(|line| line.node_json.is_some()).count();
+                log::info!(
+                    "粘贴文本解析完成：{} 行，成功 {} 个节点",
+                    lines.len(),
+                    imported
+                );
+                ParseProxyBlobResult {
+                    lines,
+                    parsed_config: parsed.yaml,
+                    error_message: None,
+                }
+            }
+            Err(e) => {
+                log::error!("粘贴文本生成配置失败：{}", e);
+                ParseProxyBlobResult {
+                    lines: Vec::new(),
+                    parsed_config: None,
+                    error_message: Some(e),
+                }
+            }
+        };
+        result.send_signal_to_dart();
+    }
+}
+
+impl From<LinkLine> for ProxyBlobLine {
+    fn from(line: LinkLine) -> Self {
+        let (node_json, skip_reason) = match line.outcome {
+            LinkLineOutcome::Parsed(node) => (Some(node.to_string()), None),
+            LinkLineOutcome::Comment => (None, Some("注释行".to_string())),
+            LinkLineOutcome::Skipped(reason) => (None, Some(reason)),
+        };
+        Self {
+            line_number: line.line_number as u32,
+            source: line.source,
+            node_json,
+            skip_reason,
+        }
+    }
+}
+
 pub fn init() {
     use tokio::spawn;
 
@@ -157,4 +328,28 @@ pub fn init() {
             dart_signal.message.handle();
         }
     });
+
+    // 配置模板列表监听器
+    spawn(async {
+        let receiver = ListConfigTemplates::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle();
+        }
+    });
+
+    // 代理链接预览监听器
+    spawn(async {
+        let receiver = ParseProxyLink::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle();
+        }
+    });
+
+    // 粘贴文本解析监听器
+    spawn(async {
+        let receiver = ParseProxyBlob::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle();
+        }
+    });
 }
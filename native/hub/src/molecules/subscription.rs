@@ -1,13 +1,26 @@
 // 订阅管理分子模块
 
+pub mod batch_import;
+pub mod diff;
 pub mod downloader;
+pub mod local_file;
+pub mod meta;
 pub mod parser;
 
+pub use batch_import::{
+    BatchImportItemResult, BatchImportProgress, BatchImportSubscriptions, CancelBatchImport,
+};
+pub use diff::{ConfigDiff, DiffSubscriptionConfigsRequest, diff_clash_configs};
 pub use downloader::{
     DownloadSubscriptionRequest, DownloadSubscriptionResponse, SubscriptionInfoData,
 };
+pub use local_file::{import_local_subscription, is_local_source};
+pub use meta::{GetSubscriptionMeta, SubscriptionMeta, SubscriptionMetaResult};
 pub use parser::ProxyParser;
 
 pub fn init_listeners() {
     downloader::init();
+    batch_import::init();
+    diff::init();
+    meta::init();
 }
@@ -0,0 +1,409 @@
+// 批量导入订阅：并发下载多个订阅链接并逐个解析，
+// 单个链接失败不影响其他链接，支持中途取消。
+
+use super::downloader::{SubscriptionInfoData, download_subscription};
+use crate::atoms::ProxyParser;
+use crate::molecules::ProxyMode;
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use rinf::{DartSignal, RustSignal};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+// 同时下载的订阅数量上限
+const BATCH_IMPORT_CONCURRENCY: usize = 4;
+
+// 进行中的批量导入（batch_id → 取消信号）
+static BATCH_CANCELLERS: Lazy<Mutex<HashMap<String, watch::Sender<bool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Dart → Rust：批量导入订阅
+#[derive(Deserialize, DartSignal)]
+pub struct BatchImportSubscriptions {
+    pub batch_id: String,
+    pub urls: Vec<String>,
+    pub proxy_mode: ProxyMode,
+    pub user_agent: String,
+    pub timeout_seconds: u64,
+    pub mixed_port: u16,
+}
+
+// Dart → Rust：取消批量导入（已完成的结果保留）
+#[derive(Deserialize, DartSignal)]
+pub struct CancelBatchImport {
+    pub batch_id: String,
+}
+
+// Rust → Dart：单个订阅的导入结果
+#[derive(Serialize, RustSignal)]
+pub struct BatchImportItemResult {
+    pub batch_id: String,
+    // 在请求 urls 中的下标
+    pub index: u32,
+    pub url: String,
+    pub is_successful: bool,
+    pub is_cancelled: bool,
+    // 原始订阅内容与解析后的 Clash 配置
+    pub content: Option<String>,
+    pub parsed_config: Option<String>,
+    pub subscription_info: Option<SubscriptionInfoData>,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：批量导入整体进度
+#[derive(Serialize, RustSignal)]
+pub struct BatchImportProgress {
+    pub batch_id: String,
+    pub completed: u32,
+    pub succeeded: u32,
+    pub total: u32,
+    pub is_finished: bool,
+    pub is_cancelled: bool,
+}
+
+// 下载参数
+#[derive(Clone, Copy)]
+pub struct ImportSettings<'a> {
+    pub proxy_mode: ProxyMode,
+    pub user_agent: &'a str,
+    pub timeout_seconds: u64,
+    pub mixed_port: u16,
+}
+
+// 单个订阅的处理结果
+#[derive(Debug)]
+pub struct ImportedSubscription {
+    pub content: String,
+    pub parsed_config: String,
+    pub subscription_info: Option<SubscriptionInfoData>,
+}
+
+#[derive(Debug)]
+pub enum ImportOutcome {
+    Imported(ImportedSubscription),
+    Failed(String),
+    Cancelled,
+}
+
+impl BatchImportSubscriptions {
+    pub async fn handle(self) {
+        log::info!(
+            "收到批量导入订阅请求 [{}]，共 {} 个链接",
+            self.batch_id,
+            self.urls.len()
+        );
+
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        BATCH_CANCELLERS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(self.batch_id.clone(), cancel_tx);
+
+        let settings = ImportSettings {
+            proxy_mode: self.proxy_mode,
+            user_agent: &self.user_agent,
+            timeout_seconds: self.timeout_seconds,
+            mixed_port: self.mixed_port,
+        };
+        let total = self.urls.len() as u32;
+        let batch_id = self.batch_id.clone();
+        let progress = Mutex::new((0u32, 0u32));
+
+        let outcomes = import_subscriptions(
+            &self.urls,
+            settings,
+            BATCH_IMPORT_CONCURRENCY,
+            cancel_rx.clone(),
+            |index, url, outcome| {
+                let (completed, succeeded) = {
+                    let mut progress = progress.lock().unwrap_or_else(|e| e.into_inner());
+                    progress.0 += 1;
+                    if matches!(outcome, ImportOutcome::Imported(_)) {
+                        progress.1 += 1;
+                    }
+                    *progress
+                };
+                item_result(&batch_id, index, url, outcome).send_signal_to_dart();
+                BatchImportProgress {
+                    batch_id: batch_id.clone(),
+                    completed,
+                    succeeded,
+                    total,
+                    is_finished: false,
+                    is_cancelled: *cancel_rx.borrow(),
+                }
+                .send_signal_to_dart();
+            },
+        )
+        .await;
+
+        BATCH_CANCELLERS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.batch_id);
+
+        let succeeded = outcomes
+            .iter()
+            .filter(|outcome| matches!(outcome, ImportOutcome::Imported(_)))
+            .count() as u32;
+        let is_cancelled = *cancel_rx.borrow();
+        log::info!(
+            "批量导入订阅完成 [{}]：成功 {}/{}{}",
+            self.batch_id,
+            succeeded,
+            total,
+            if is_cancelled { "（已取消）" } else { "" }
+        );
+        BatchImportProgress {
+            batch_id: self.batch_id,
+            completed: outcomes.len() as u32,
+            succeeded,
+            total,
+            is_finished: true,
+            is_cancelled,
+        }
+        .send_signal_to_dart();
+    }
+}
+
+impl CancelBatchImport {
+    pub fn handle(self) {
+        let cancellers = BATCH_CANCELLERS.lock().unwrap_or_else(|e| e.into_inner());
+        match cancellers.get(&self.batch_id) {
+            Some(cancel_tx) => {
+                log::info!("取消批量导入订阅 [{}]", self.batch_id);
+                let _ = cancel_tx.send(true);
+            }
+            None => log::debug!("批量导入不存在或已结束 [{}]", self.batch_id),
+        }
+    }
+}
+
+fn item_result(
+    batch_id: &str,
+    index: usize,
+    url: &str,
+    outcome: &ImportOutcome,
+) -> BatchImportItemResult {
+    let mut result = BatchImportItemResult {
+        batch_id: batch_id.to_string(),
+        index: index as u32,
+        url: url.to_string(),
+        is_successful: false,
+        is_cancelled: false,
+        content: None,
+        parsed_config: None,
+        subscription_info: None,
+        error_message: None,
+    };
+    match outcome {
+        ImportOutcome::Imported(imported) => {
+            result.is_successful = true;
+            result.content = Some(imported.content.clone());
+            result.parsed_config = Some(imported.parsed_config.clone());
+            result.subscription_info = imported.subscription_info.clone();
+        }
+        ImportOutcome::Failed(e) => result.error_message = Some(e.clone()),
+        ImportOutcome::Cancelled => result.is_cancelled = true,
+    }
+    result
+}
+
+// 并发导入订阅，每完成一个调用 on_item，返回按请求顺序排列的结果
+pub async fn import_subscriptions(
+    urls: &[String],
+    settings: ImportSettings<'_>,
+    concurrency: usize,
+    cancel_rx: watch::Receiver<bool>,
+    on_item: impl Fn(usize, &str, &ImportOutcome),
+) -> Vec<ImportOutcome> {
+    let mut outcomes: Vec<Option<ImportOutcome>> = urls.iter().map(|_| None).collect();
+
+    let tasks: Vec<_> = urls
+        .iter()
+        .enumerate()
+        .map(|(index, url)| {
+            let mut cancel_rx = cancel_rx.clone();
+            async move {
+                if *cancel_rx.borrow() {
+                    return (index, ImportOutcome::Cancelled);
+                }
+                let outcome = tokio::select! {
+                    outcome = import_one(url, settings) => outcome,
+                    _ = cancel_rx.wait_for(|cancelled| *cancelled) => ImportOutcome::Cancelled,
+                };
+                (index, outcome)
+            }
+        })
+        .collect();
+    let mut tasks = futures_util::stream::iter(tasks).buffer_unordered(concurrency.max(1));
+
+    while let Some((index, outcome)) = tasks.next().await {
+        if let ImportOutcome::Failed(e) = &outcome {
+            log::warn!("导入订阅失败：{}，{}", urls[index], e);
+        }
+        on_item(index, &urls[index], &outcome);
+        outcomes[index] = Some(outcome);
+    }
+
+    outcomes
+        .into_iter()
+        .map(|outcome| outcome.unwrap_or(ImportOutcome::Cancelled))
+        .collect()
+}
+
+// 下载并解析单个订阅
+async fn import_one(url: &str, settings: ImportSettings<'_>) -> ImportOutcome {
+    let downloaded = download_subscription(
+        url,
+        settings.proxy_mode,
+        settings.user_agent,
+        settings.timeout_seconds,
+        settings.mixed_port,
+    )
+    .await;
+    let (content, subscription_info) = match downloaded {
+        Ok(downloaded) => downloaded,
+        Err(e) => return ImportOutcome::Failed(format!("下载失败：{}", e)),
+    };
+
+    match ProxyParser::parse_subscription(&content) {
+        Ok(parsed_config) => ImportOutcome::Imported(ImportedSubscription {
+            content,
+            parsed_config,
+            subscription_info,
+        }),
+        Err(e) => ImportOutcome::Failed(format!("解析失败：{}", e)),
+    }
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = BatchImportSubscriptions::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            tokio::spawn(async move {
+                dart_signal.message.handle().await;
+            });
+        }
+    });
+
+    spawn(async {
+        let receiver = CancelBatchImport::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const GOOD_SUBSCRIPTION: &str = "trojan://secret@hk.example.com:443#HK-01\n";
+
+    // 模拟订阅服务器：/bad 返回 404，/slow 不响应，其余返回订阅内容
+    async fn start_mock_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap_or_else(|e| panic!("绑定端口失败：{}", e));
+        let address = listener
+            .local_addr()
+            .unwrap_or_else(|e| panic!("读取地址失败：{}", e));
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = [0u8; 1024];
+                    let read = stream.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                    let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+                    let response = match path.as_str() {
+                        "/bad" => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                        "/slow" => {
+                            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                            return;
+                        }
+                        _ => format!(
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            GOOD_SUBSCRIPTION.len(),
+                            GOOD_SUBSCRIPTION
+                        ),
+                    };
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    fn settings() -> ImportSettings<'static> {
+        ImportSettings {
+            proxy_mode: ProxyMode::Direct,
+            user_agent: "stelliberty-test",
+            timeout_seconds: 10,
+            mixed_port: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_import_with_failing_url() {
+        let base = start_mock_server().await;
+        let urls = vec![
+            format!("{}/good1", base),
+            format!("{}/bad", base),
+            format!("{}/good2", base),
+        ];
+        let (_cancel_tx, cancel_rx) = watch::channel(false);
+        let reported = Mutex::new(Vec::new());
+
+        let outcomes = import_subscriptions(&urls, settings(), 2, cancel_rx, |index, _, _| {
+            reported
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(index);
+        })
+        .await;
+
+        assert_eq!(outcomes.len(), 3);
+        assert!(
+            matches!(&outcomes[0], ImportOutcome::Imported(imported) if imported.parsed_config.contains("HK-01"))
+        );
+        assert!(matches!(&outcomes[1], ImportOutcome::Failed(e) if e.contains("404")));
+        assert!(matches!(&outcomes[2], ImportOutcome::Imported(_)));
+
+        let mut reported = reported.into_inner().unwrap_or_default();
+        reported.sort_unstable();
+        assert_eq!(reported, vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_import_cancel() {
+        let base = start_mock_server().await;
+        let urls = vec![
+            format!("{}/good1", base),
+            format!("{}/slow", base),
+            format!("{}/slow", base),
+        ];
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+
+        // 第一个链接完成后取消，慢速请求应被中止
+        let outcomes = import_subscriptions(&urls, settings(), 1, cancel_rx, |index, _, _| {
+            if index == 0 {
+                let _ = cancel_tx.send(true);
+            }
+        })
+        .await;
+
+        assert!(matches!(&outcomes[0], ImportOutcome::Imported(_)));
+        assert!(matches!(&outcomes[1], ImportOutcome::Cancelled));
+        assert!(matches!(&outcomes[2], ImportOutcome::Cancelled));
+    }
+}
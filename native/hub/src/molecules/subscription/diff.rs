@@ -0,0 +1,275 @@
+// 订阅配置差异：订阅更新后对比新旧配置，列出新增、移除与变更的节点及规则。
+
+use rinf::{DartSignal, RustSignal, SignalPiece};
+use serde::{Deserialize, Serialize};
+use serde_yaml_ng::{Mapping, Value as YamlValue};
+use std::collections::{HashMap, HashSet};
+
+use crate::atoms::ConfigValidator;
+
+// Dart → Rust：对比新旧订阅配置
+#[derive(Deserialize, DartSignal)]
+pub struct DiffSubscriptionConfigsRequest {
+    pub request_id: String, // 请求标识符，用于响应匹配
+    pub old_content: String,
+    pub new_content: String,
+}
+
+// Rust → Dart：订阅配置差异
+#[derive(Serialize, RustSignal)]
+pub struct DiffSubscriptionConfigsResponse {
+    pub request_id: String,
+    pub is_successful: bool,
+    pub diff: Option<ConfigDiff>,
+    pub error_message: Option<String>,
+}
+
+// 节点变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SignalPiece)]
+pub struct ProxyChange {
+    pub name: String,
+    // 发生变化的字段（按字段名排序）
+    pub fields: Vec<String>,
+}
+
+// 配置差异
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, SignalPiece)]
+pub struct ConfigDiff {
+    pub added_proxies: Vec<String>,
+    pub removed_proxies: Vec<String>,
+    pub changed_proxies: Vec<ProxyChange>,
+    pub added_rules: Vec<String>,
+    pub removed_rules: Vec<String>,
+}
+
+impl DiffSubscriptionConfigsRequest {
+    pub fn handle(self) {
+        let response = match diff_clash_configs(&self.old_content, &self.new_content) {
+            Ok(diff) => {
+                log::info!(
+                    "订阅配置差异 [{}]：新增节点 {}，移除节点 {}，变更节点 {}，新增规则 {}，移除规则 {}",
+                    self.request_id,
+                    diff.added_proxies.len(),
+                    diff.removed_proxies.len(),
+                    diff.changed_proxies.len(),
+                    diff.added_rules.len(),
+                    diff.removed_rules.len()
+                );
+                DiffSubscriptionConfigsResponse {
+                    request_id: self.request_id,
+                    is_successful: true,
+                    diff: Some(diff),
+                    error_message: None,
+                }
+            }
+            Err(e) => {
+                log::error!("对比订阅配置失败 [{}]：{}", self.request_id, e);
+                DiffSubscriptionConfigsResponse {
+                    request_id: self.request_id,
+                    is_successful: false,
+                    diff: None,
+                    error_message: Some(e),
+                }
+            }
+        };
+
+        response.send_signal_to_dart();
+    }
+}
+
+// 对比新旧配置：节点按 name 匹配，除 name 外任一连接字段不同即视为变更；规则按文本比较
+pub fn diff_clash_configs(old: &str, new: &str) -> Result<ConfigDiff, String> {
+    let old_config = ConfigValidator::parse_content(old).map_err(|e| format!("旧配置：{}", e))?;
+    let new_config = ConfigValidator::parse_content(new).map_err(|e| format!("新配置：{}", e))?;
+
+    let old_proxies = named_proxies(&old_config);
+    let new_proxies = named_proxies(&new_config);
+    // 按名称建立索引，避免大订阅逐一线性查找
+    let old_index: HashMap<&str, &Mapping> = old_proxies
+        .iter()
+        .map(|(name, fields)| (name.as_str(), fields))
+        .collect();
+    let new_names: HashSet<&str> = new_proxies.iter().map(|(name, _)| name.as_str()).collect();
+
+    let mut diff = ConfigDiff::default();
+    for (name, new_fields) in &new_proxies {
+        match old_index.get(name.as_str()) {
+            None => diff.added_proxies.push(name.clone()),
+            Some(old_fields) => {
+                let fields = changed_fields(old_fields, new_fields);
+                if !fields.is_empty() {
+                    diff.changed_proxies.push(ProxyChange {
+                        name: name.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    for (name, _) in &old_proxies {
+        if !new_names.contains(name.as_str()) {
+            diff.removed_proxies.push(name.clone());
+        }
+    }
+
+    let old_rules = rule_lines(&old_config);
+    let new_rules = rule_lines(&new_config);
+    let old_rule_set: HashSet<&String> = old_rules.iter().collect();
+    let new_rule_set: HashSet<&String> = new_rules.iter().collect();
+    diff.added_rules = new_rules
+        .iter()
+        .filter(|rule| !old_rule_set.contains(rule))
+        .cloned()
+        .collect();
+    diff.removed_rules = old_rules
+        .iter()
+        .filter(|rule| !new_rule_set.contains(rule))
+        .cloned()
+        .collect();
+
+    Ok(diff)
+}
+
+// 提取带名称的节点（名称重复时只保留第一个，与核心行为一致）
+fn named_proxies(config: &YamlValue) -> Vec<(String, Mapping)> {
+    let mut proxies: Vec<(String, Mapping)> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let Some(sequence) = config.get("proxies").and_then(|v| v.as_sequence()) else {
+        return proxies;
+    };
+
+    for proxy in sequence {
+        let Some(mapping) = proxy.as_mapping() else {
+            continue;
+        };
+        let Some(name) = mapping.get("name").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        if !seen.insert(name.to_string()) {
+            continue;
+        }
+        let mut fields = mapping.clone();
+        fields.remove("name");
+        proxies.push((name.to_string(), fields));
+    }
+    proxies
+}
+
+// 列出两个节点间取值不同的字段
+fn changed_fields(old: &Mapping, new: &Mapping) -> Vec<String> {
+    let mut fields: Vec<String> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| match key.as_str() {
+            Some(key) => key.to_string(),
+            None => format!("{:?}", key),
+        })
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+// 提取规则文本（去除首尾空白）
+fn rule_lines(config: &YamlValue) -> Vec<String> {
+    config
+        .get("rules")
+        .and_then(|v| v.as_sequence())
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(|rule| rule.as_str())
+                .map(|rule| rule.trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = DiffSubscriptionConfigsRequest::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::task::spawn_blocking(move || {
+                message.handle();
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_CONFIG: &str = r#"
+proxies:
+  - {name: HK-01, type: ss, server: hk1.example.com, port: 8388, cipher: aes-128-gcm, password: p1}
+  - {name: JP-01, type: vmess, server: jp1.example.com, port: 443, uuid: 11111111-1111-1111-1111-111111111111}
+  - {name: US-01, type: trojan, server: us1.example.com, port: 443, password: p3}
+rules:
+  - DOMAIN-SUFFIX,example.com,PROXY
+  - MATCH,DIRECT
+"#;
+
+    #[test]
+    fn test_added_node() {
+        let new_config = OLD_CONFIG.replace(
+            "rules:",
+            "  - {name: SG-01, type: ss, server: sg1.example.com, port: 8388, cipher: aes-128-gcm, password: p4}\nrules:",
+        );
+        let diff = diff_clash_configs(OLD_CONFIG, &new_config);
+        assert_eq!(
+            diff,
+            Ok(ConfigDiff {
+                added_proxies: vec!["SG-01".to_string()],
+                ..ConfigDiff::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_removed_node_and_rules() {
+        let new_config = r#"
+proxies:
+  - {name: HK-01, type: ss, server: hk1.example.com, port: 8388, cipher: aes-128-gcm, password: p1}
+  - {name: US-01, type: trojan, server: us1.example.com, port: 443, password: p3}
+rules:
+  - DOMAIN-KEYWORD,google,PROXY
+  - MATCH,DIRECT
+"#;
+        let diff = diff_clash_configs(OLD_CONFIG, new_config);
+        assert_eq!(
+            diff,
+            Ok(ConfigDiff {
+                removed_proxies: vec!["JP-01".to_string()],
+                added_rules: vec!["DOMAIN-KEYWORD,google,PROXY".to_string()],
+                removed_rules: vec!["DOMAIN-SUFFIX,example.com,PROXY".to_string()],
+                ..ConfigDiff::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_node_with_changed_server() {
+        let new_config = OLD_CONFIG
+            .replace("server: us1.example.com", "server: us2.example.com")
+            .replace("- {name: HK-01", "- {udp: true, name: HK-01");
+        let diff = diff_clash_configs(OLD_CONFIG, &new_config);
+        assert_eq!(
+            diff.map(|d| d.changed_proxies),
+            Ok(vec![
+                ProxyChange {
+                    name: "HK-01".to_string(),
+                    fields: vec!["udp".to_string()],
+                },
+                ProxyChange {
+                    name: "US-01".to_string(),
+                    fields: vec!["server".to_string()],
+                },
+            ])
+        );
+    }
+}
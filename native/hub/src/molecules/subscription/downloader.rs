@@ -1,6 +1,7 @@
 // 订阅下载器
 // 处理订阅配置的 HTTP 下载，支持多种代理模式
 
+use super::local_file::{is_local_source, read_local_subscription};
 use crate::molecules::ProxyMode;
 use reqwest::{Client, Proxy};
 use rinf::{DartSignal, RustSignal};
@@ -29,7 +30,7 @@ pub struct DownloadSubscriptionResponse {
 }
 
 // 订阅信息
-#[derive(Serialize, Deserialize, Clone, Debug, rinf::SignalPiece)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, rinf::SignalPiece)]
 pub struct SubscriptionInfoData {
     pub upload: Option<u64>,
     pub download: Option<u64>,
@@ -82,7 +83,7 @@ impl DownloadSubscriptionRequest {
 }
 
 // 下载订阅配置并返回内容与订阅信息。
-// 支持代理模式、超时与自定义 User-Agent。
+// 支持代理模式、超时与自定义 User-Agent；本地文件地址直接读取文件。
 pub async fn download_subscription(
     url: &str,
     proxy_mode: ProxyMode,
@@ -90,6 +91,11 @@ pub async fn download_subscription(
     timeout_seconds: u64,
     mixed_port: u16,
 ) -> Result<(String, Option<SubscriptionInfoData>), Box<dyn std::error::Error + Send + Sync>> {
+    if is_local_source(url) {
+        let content = read_local_subscription(url)?;
+        return Ok((content, None));
+    }
+
     log::info!("开始下载订阅：{}", url);
     log::info!("代理模式：{:?}", proxy_mode);
 
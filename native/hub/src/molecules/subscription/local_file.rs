@@ -0,0 +1,108 @@
+// 本地文件订阅：支持 file:// URL 或绝对路径，更新时重新读取文件，
+// 便于在本地（或挂载的共享目录）维护配置并复用订阅更新流程。
+
+use crate::atoms::ProxyParser;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+// 判断订阅地址是否指向本地文件
+pub fn is_local_source(source: &str) -> bool {
+    let source = source.trim();
+    source.starts_with("file://") || Path::new(source).is_absolute()
+}
+
+// 将 file:// URL 或本地路径转换为文件路径
+pub fn local_path(source: &str) -> Result<PathBuf, String> {
+    let source = source.trim();
+    if source.starts_with("file://") {
+        let url = Url::parse(source).map_err(|e| format!("无效的文件 URL：{}（{}）", source, e))?;
+        return url
+            .to_file_path()
+            .map_err(|()| format!("无法从 URL 解析本地路径：{}", source));
+    }
+    Ok(PathBuf::from(source))
+}
+
+// 读取本地订阅文件内容
+pub fn read_local_subscription(source: &str) -> Result<String, String> {
+    let path = local_path(source)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => format!("订阅文件不存在：{}", path.display()),
+        ErrorKind::PermissionDenied => format!("没有权限读取订阅文件：{}", path.display()),
+        _ => format!("读取订阅文件失败：{}（{}）", path.display(), e),
+    })?;
+
+    if content.trim().is_empty() {
+        return Err(format!("订阅文件内容为空：{}", path.display()));
+    }
+    log::info!(
+        "读取本地订阅成功：{}，内容长度：{} 字节",
+        path.display(),
+        content.len()
+    );
+    Ok(content)
+}
+
+// 读取并解析本地订阅，返回原始内容与标准 Clash 配置
+pub fn import_local_subscription(source: &str) -> Result<(String, String), String> {
+    let content = read_local_subscription(source)?;
+    let parsed_config =
+        ProxyParser::parse_subscription(&content).map_err(|e| format!("解析失败：{}", e))?;
+    Ok((content, parsed_config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUBSCRIPTION: &str = "trojan://password@example.com:443?sni=example.com#Local%20Node";
+
+    fn temp_subscription(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "stelliberty-local-sub-{}-{}.txt",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, SUBSCRIPTION).unwrap_or_else(|e| panic!("写入临时文件失败：{}", e));
+        path
+    }
+
+    #[test]
+    fn test_import_plain_path() {
+        let path = temp_subscription("plain");
+        let source = path.display().to_string();
+        assert!(is_local_source(&source));
+
+        let (content, parsed_config) =
+            import_local_subscription(&source).unwrap_or_else(|e| panic!("导入失败：{}", e));
+        assert_eq!(content, SUBSCRIPTION);
+        assert!(parsed_config.contains("Local Node"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_import_file_url() {
+        let path = temp_subscription("url");
+        let source = Url::from_file_path(&path)
+            .unwrap_or_else(|()| panic!("无法构造文件 URL：{}", path.display()))
+            .to_string();
+        assert!(source.starts_with("file://"));
+        assert!(is_local_source(&source));
+
+        let (_, parsed_config) =
+            import_local_subscription(&source).unwrap_or_else(|e| panic!("导入失败：{}", e));
+        assert!(parsed_config.contains("Local Node"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let path = std::env::temp_dir().join("stelliberty-local-sub-missing.txt");
+        let error = import_local_subscription(&path.display().to_string())
+            .err()
+            .unwrap_or_default();
+        assert!(error.contains("订阅文件不存在"), "{}", error);
+        assert!(!is_local_source("https://example.com/sub"));
+    }
+}
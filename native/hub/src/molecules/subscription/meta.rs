@@ -0,0 +1,255 @@
+// 订阅元数据：导入或更新订阅时记录更新时间、节点数、协议分布与流量信息，
+// 持久化到 JSON 索引，界面展示「上次更新」「42 个节点」时无需重新解析订阅。
+
+use super::downloader::SubscriptionInfoData;
+use crate::atoms::ParsedSubscription;
+use crate::atoms::path_service;
+use once_cell::sync::Lazy;
+use rinf::{DartSignal, RustSignal, SignalPiece};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 串行化索引文件的读改写，避免并发更新互相覆盖
+static INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// 单个协议的节点数
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, SignalPiece)]
+pub struct ProtocolCount {
+    pub protocol: String,
+    pub count: u32,
+}
+
+// 单个订阅的元数据
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, SignalPiece)]
+pub struct SubscriptionMeta {
+    // 最近一次成功解析的时间（Unix 秒）
+    pub updated_at: i64,
+    pub node_count: u32,
+    // 各协议节点数（按首次出现的顺序）
+    pub protocol_counts: Vec<ProtocolCount>,
+    // subscription-userinfo 流量与到期信息
+    pub subscription_info: Option<SubscriptionInfoData>,
+}
+
+// 订阅 ID → 元数据
+pub type SubscriptionMetaIndex = BTreeMap<String, SubscriptionMeta>;
+
+// Dart → Rust：读取订阅元数据
+#[derive(Deserialize, DartSignal)]
+pub struct GetSubscriptionMeta {
+    pub id: String,
+}
+
+// Rust → Dart：订阅元数据（未记录过时 meta 为空）
+#[derive(Serialize, RustSignal)]
+pub struct SubscriptionMetaResult {
+    pub id: String,
+    pub meta: Option<SubscriptionMeta>,
+    pub error_message: Option<String>,
+}
+
+impl GetSubscriptionMeta {
+    pub fn handle(self) {
+        let result = match load_index(&path_service::subscription_meta_file()) {
+            Ok(mut index) => SubscriptionMetaResult {
+                meta: index.remove(&self.id),
+                id: self.id,
+                error_message: None,
+            },
+            Err(e) => {
+                log::warn!("读取订阅元数据失败：{}", e);
+                SubscriptionMetaResult {
+                    id: self.id,
+                    meta: None,
+                    error_message: Some(e),
+                }
+            }
+        };
+        result.send_signal_to_dart();
+    }
+}
+
+impl SubscriptionMeta {
+    // 由解析结果生成元数据
+    pub fn from_parsed(
+        parsed: &ParsedSubscription,
+        subscription_info: Option<SubscriptionInfoData>,
+        updated_at: SystemTime,
+    ) -> Self {
+        Self {
+            updated_at: updated_at
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or_default(),
+            node_count: parsed.total_parsed as u32,
+            protocol_counts: parsed
+                .protocol_counts
+                .iter()
+                .map(|(protocol, count)| ProtocolCount {
+                    protocol: protocol.clone(),
+                    count: *count as u32,
+                })
+                .collect(),
+            subscription_info,
+        }
+    }
+}
+
+// 读取索引，文件不存在时返回空索引
+pub fn load_index(path: &Path) -> Result<SubscriptionMetaIndex, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("解析订阅元数据索引失败：{}，{}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SubscriptionMetaIndex::new()),
+        Err(e) => Err(format!("读取订阅元数据索引失败：{}，{}", path.display(), e)),
+    }
+}
+
+// 写入索引（先写临时文件再重命名，避免写入中断留下半个文件）
+pub fn save_index(path: &Path, index: &SubscriptionMetaIndex) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("创建目录失败：{}，{}", parent.display(), e))?;
+    }
+    let content =
+        serde_json::to_string_pretty(index).map_err(|e| format!("序列化订阅元数据失败：{}", e))?;
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, content)
+        .map_err(|e| format!("写入订阅元数据失败：{}，{}", temp_path.display(), e))?;
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| format!("替换订阅元数据索引失败：{}，{}", path.display(), e))
+}
+
+// 记录（或覆盖）订阅的元数据；索引损坏时重建
+pub fn record_meta(path: &Path, id: &str, meta: SubscriptionMeta) -> Result<(), String> {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index = load_index(path).unwrap_or_else(|e| {
+        log::warn!("{}，将重建索引", e);
+        SubscriptionMetaIndex::new()
+    });
+    index.insert(id.to_string(), meta);
+    save_index(path, &index)
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = GetSubscriptionMeta::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            dart_signal.message.handle();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atoms::ProxyParser;
+    use std::time::Duration;
+
+    fn temp_index(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("stelliberty-meta-{}-{}", name, std::process::id()))
+            .join("subscription_meta.json")
+    }
+
+    fn parse(content: &str) -> ParsedSubscription {
+        ProxyParser::parse_subscription_detailed(content)
+            .unwrap_or_else(|e| panic!("解析失败：{}", e))
+    }
+
+    #[test]
+    fn test_index_read_write() {
+        let path = temp_index("rw");
+        assert_eq!(load_index(&path), Ok(SubscriptionMetaIndex::new()));
+
+        let info = SubscriptionInfoData {
+            upload: Some(1),
+            download: Some(2),
+            total: Some(1024),
+            expire: Some(1_900_000_000),
+        };
+        let parsed = parse(
+            "trojan://secret@a.example.com:443#A\n\
+             trojan://secret@b.example.com:443#B\n\
+             ss://aes-128-gcm:pass@c.example.com:8388#C\n",
+        );
+        let meta = SubscriptionMeta::from_parsed(
+            &parsed,
+            Some(info),
+            UNIX_EPOCH + Duration::from_secs(1_800_000_000),
+        );
+        assert_eq!(meta.node_count, 3);
+        assert_eq!(
+            meta.protocol_counts,
+            vec![
+                ProtocolCount {
+                    protocol: "trojan".to_string(),
+                    count: 2
+                },
+                ProtocolCount {
+                    protocol: "ss".to_string(),
+                    count: 1
+                },
+            ]
+        );
+
+        record_meta(&path, "sub-1", meta.clone()).unwrap_or_else(|e| panic!("写入失败：{}", e));
+        let index = load_index(&path).unwrap_or_else(|e| panic!("读取失败：{}", e));
+        assert_eq!(index.get("sub-1"), Some(&meta));
+        assert_eq!(index.get("sub-2"), None);
+
+        // 损坏的索引读取报错，记录时重建
+        std::fs::write(&path, "{").unwrap_or_else(|e| panic!("写入失败：{}", e));
+        assert!(load_index(&path).is_err());
+        record_meta(&path, "sub-2", meta.clone()).unwrap_or_else(|e| panic!("写入失败：{}", e));
+        let index = load_index(&path).unwrap_or_else(|e| panic!("读取失败：{}", e));
+        assert_eq!(index.keys().collect::<Vec<_>>(), vec!["sub-2"]);
+
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    #[test]
+    fn test_update_on_reimport() {
+        let path = temp_index("reimport");
+        let first = SubscriptionMeta::from_parsed(
+            &parse("trojan://secret@a.example.com:443#A\n"),
+            None,
+            UNIX_EPOCH + Duration::from_secs(1_800_000_000),
+        );
+        let other = SubscriptionMeta::from_parsed(
+            &parse("trojan://secret@z.example.com:443#Z\n"),
+            None,
+            UNIX_EPOCH + Duration::from_secs(1_800_000_000),
+        );
+        record_meta(&path, "sub-1", first).unwrap_or_else(|e| panic!("写入失败：{}", e));
+        record_meta(&path, "sub-2", other.clone()).unwrap_or_else(|e| panic!("写入失败：{}", e));
+
+        // 重新导入后覆盖原记录，其他订阅不受影响
+        let second = SubscriptionMeta::from_parsed(
+            &parse(
+                "trojan://secret@a.example.com:443#A\n\
+                 trojan://secret@b.example.com:443#B\n",
+            ),
+            None,
+            UNIX_EPOCH + Duration::from_secs(1_800_003_600),
+        );
+        record_meta(&path, "sub-1", second).unwrap_or_else(|e| panic!("写入失败：{}", e));
+
+        let index = load_index(&path).unwrap_or_else(|e| panic!("读取失败：{}", e));
+        assert_eq!(index.len(), 2);
+        assert_eq!(index["sub-1"].node_count, 2);
+        assert_eq!(index["sub-1"].updated_at, 1_800_003_600);
+        assert_eq!(index.get("sub-2"), Some(&other));
+
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::atoms::{network_interfaces, system_proxy};
 pub mod app_update;
 pub mod auto_start;
 pub mod backup;
+pub mod cleanup;
 #[cfg(windows)]
 pub mod loopback;
 pub mod power_event;
@@ -12,12 +13,15 @@ pub mod url_launcher;
 
 pub use app_update::{AppUpdateResult, CheckAppUpdateRequest};
 pub use auto_start::{AutoStartStatusResult, GetAutoStartStatus, SetAutoStartStatus};
-pub use backup::{BackupOperationResult, CreateBackupRequest, RestoreBackupRequest};
+pub use backup::{
+    BackupOperationResult, BackupProgress, CreateBackupRequest, RestoreBackupRequest,
+};
+pub use cleanup::{FullCleanup, FullCleanupResult};
 
 #[cfg(windows)]
 pub use loopback::{
-    AppContainerInfo, AppContainersComplete, GetAppContainers, SaveLoopbackConfiguration,
-    SaveLoopbackConfigurationResult, SetLoopback, SetLoopbackResult,
+    AppContainerInfo, AppContainersComplete, ContainerEnumError, GetAppContainers,
+    SaveLoopbackConfiguration, SaveLoopbackConfigurationResult, SetLoopback, SetLoopbackResult,
 };
 pub use power_event::{
     PowerEventType, SystemPowerEvent, start_power_event_listener, stop_power_event_listener,
@@ -31,6 +35,7 @@ pub fn init_listeners() {
     app_update::init();
     auto_start::init();
     backup::init();
+    cleanup::init();
     #[cfg(windows)]
     loopback::init();
     url_launcher::init();
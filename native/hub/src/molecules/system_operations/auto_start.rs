@@ -188,94 +188,26 @@ fn enable_auto_start_windows() -> Result<(), String> {
     log::debug!("已写入 XML 配置文件");
 
     // 使用 UAC 提权执行 schtasks.exe
-    log::debug!(
-        "以管理员权限执行: schtasks.exe /create /tn {} /xml \"{}\" /f",
-        APP_NAME,
-        xml_path.display()
-    );
-
-    run_elevated_schtasks("create", &xml_path)?;
+    let xml_path = xml_path.to_string_lossy();
+    run_elevated_schtasks(&["/create", "/tn", APP_NAME, "/xml", &xml_path, "/f"])?;
 
     log::info!("✅ 已成功启用开机自启动（任务计划程序）");
     Ok(())
 }
 
 #[cfg(target_os = "windows")]
-fn run_elevated_schtasks(operation: &str, xml_path: &std::path::Path) -> Result<(), String> {
-    use windows::Win32::UI::Shell::ShellExecuteW;
-    use windows::Win32::UI::WindowsAndMessaging::SW_HIDE;
-    use windows::core::{HSTRING, PCWSTR};
-
-    let args = match operation {
-        "create" => format!(
-            "/create /tn {} /xml \"{}\" /f",
-            APP_NAME,
-            xml_path.display()
-        ),
-        "delete" => format!("/delete /tn {} /f", APP_NAME),
-        _ => return Err(format!("未知操作：{}", operation)),
-    };
-
-    log::debug!("UAC 提权执行：schtasks.exe {}", args);
-
-    let verb = HSTRING::from("runas");
-    let file = HSTRING::from("schtasks.exe");
-    let parameters = HSTRING::from(args);
+fn run_elevated_schtasks(args: &[&str]) -> Result<(), String> {
+    use crate::atoms::elevate::{self, ElevationOutcome};
 
-    unsafe {
-        let result = ShellExecuteW(
-            None,
-            PCWSTR(verb.as_ptr()),
-            PCWSTR(file.as_ptr()),
-            PCWSTR(parameters.as_ptr()),
-            PCWSTR::null(),
-            SW_HIDE,
-        );
-
-        let result_value = result.0 as isize;
-        if result_value <= 32 {
-            return Err(format!(
-                "ShellExecuteW 失败 (代码: {})，用户可能取消了 UAC 提示",
-                result_value
-            ));
+    match elevate::run("schtasks.exe", args)? {
+        ElevationOutcome::Succeeded => {}
+        ElevationOutcome::UserCancelled => {
+            return Err("已取消 UAC 授权，任务计划未修改".to_string());
         }
-    }
-
-    // 等待操作完成
-    std::thread::sleep(std::time::Duration::from_secs(2));
-
-    Ok(())
-}
-
-#[cfg(target_os = "windows")]
-fn run_elevated_schtasks_delete() -> Result<(), String> {
-    use windows::Win32::UI::Shell::ShellExecuteW;
-    use windows::Win32::UI::WindowsAndMessaging::SW_HIDE;
-    use windows::core::{HSTRING, PCWSTR};
-
-    let args = format!("/delete /tn {} /f", APP_NAME);
-
-    log::debug!("UAC 提权执行：schtasks.exe {}", args);
-
-    let verb = HSTRING::from("runas");
-    let file = HSTRING::from("schtasks.exe");
-    let parameters = HSTRING::from(args);
-
-    unsafe {
-        let result = ShellExecuteW(
-            None,
-            PCWSTR(verb.as_ptr()),
-            PCWSTR(file.as_ptr()),
-            PCWSTR(parameters.as_ptr()),
-            PCWSTR::null(),
-            SW_HIDE,
-        );
-
-        let result_value = result.0 as isize;
-        if result_value <= 32 {
+        ElevationOutcome::Failed { code, message } => {
             return Err(format!(
-                "ShellExecuteW 失败 (代码: {})，用户可能取消了 UAC 提示",
-                result_value
+                "以管理员权限执行 schtasks.exe 失败（代码：{}）：{}",
+                code, message
             ));
         }
     }
@@ -297,7 +229,7 @@ fn disable_auto_start_windows() -> Result<(), String> {
         return Ok(());
     }
 
-    run_elevated_schtasks_delete()?;
+    run_elevated_schtasks(&["/delete", "/tn", APP_NAME, "/f"])?;
 
     log::info!("✅ 已成功禁用开机自启动（任务计划程序）");
     Ok(())
@@ -6,7 +6,7 @@ use rinf::{DartSignal, RustSignal};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs as async_fs;
 
 // Dart → Rust：创建备份请求
@@ -46,6 +46,33 @@ pub struct BackupOperationResult {
     pub error_message: Option<String>,
 }
 
+// Rust → Dart：备份/还原进度（逐个文件上报）
+#[derive(Serialize, RustSignal)]
+pub struct BackupProgress {
+    pub stage: String,
+    pub current: u64,
+    pub total: u64,
+}
+
+// 进度回调：(阶段, 已完成数量, 总数量)
+pub type ProgressCallback<'a> = &'a (dyn Fn(&str, usize, usize) + Send + Sync);
+
+// 进度阶段标识（由 Dart 侧负责本地化展示）
+pub const STAGE_COLLECT_SUBSCRIPTIONS: &str = "collect_subscriptions";
+pub const STAGE_COLLECT_OVERRIDES: &str = "collect_overrides";
+pub const STAGE_RESTORE_SUBSCRIPTIONS: &str = "restore_subscriptions";
+pub const STAGE_RESTORE_OVERRIDES: &str = "restore_overrides";
+
+// 将进度转发给 Dart
+fn send_progress(stage: &str, current: usize, total: usize) {
+    BackupProgress {
+        stage: stage.to_string(),
+        current: current as u64,
+        total: total as u64,
+    }
+    .send_signal_to_dart();
+}
+
 impl CreateBackupRequest {
     // 处理创建备份请求
     pub async fn handle(self) {
@@ -61,7 +88,8 @@ impl CreateBackupRequest {
             pac_file_path: &self.pac_file_path,
         };
 
-        let result = create_backup(&self.target_path, &self.app_version, paths).await;
+        let result =
+            create_backup(&self.target_path, &self.app_version, paths, &send_progress).await;
 
         let response = match result {
             Ok(path) => {
@@ -101,7 +129,7 @@ impl RestoreBackupRequest {
             pac_file_path: &self.pac_file_path,
         };
 
-        let result = restore_backup(&self.backup_path, paths).await;
+        let result = restore_backup(&self.backup_path, paths, &send_progress).await;
 
         let response = match result {
             Ok(()) => {
@@ -194,6 +222,7 @@ pub async fn create_backup(
     target_path: &str,
     app_version: &str,
     paths: BackupPaths<'_>,
+    on_progress: ProgressCallback<'_>,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     log::info!("开始创建备份到：{}", target_path);
 
@@ -204,11 +233,16 @@ pub async fn create_backup(
     let clash_prefs = HashMap::new();
 
     // 收集订阅数据
-    let subscriptions =
-        collect_subscriptions(paths.subscriptions_dir, paths.subscriptions_list_path).await?;
+    let subscriptions = collect_subscriptions(
+        paths.subscriptions_dir,
+        paths.subscriptions_list_path,
+        on_progress,
+    )
+    .await?;
 
     // 收集覆写数据
-    let overrides = collect_overrides(paths.overrides_dir, paths.overrides_list_path).await?;
+    let overrides =
+        collect_overrides(paths.overrides_dir, paths.overrides_list_path, on_progress).await?;
 
     // 收集 DNS 配置
     let dns_config = collect_file_base64(paths.dns_config_path).await;
@@ -249,6 +283,7 @@ pub async fn create_backup(
 pub async fn restore_backup(
     backup_path: &str,
     paths: BackupPaths<'_>,
+    on_progress: ProgressCallback<'_>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     log::info!("开始还原备份：{}", backup_path);
 
@@ -282,6 +317,7 @@ pub async fn restore_backup(
         &backup_data.data.subscriptions,
         paths.subscriptions_dir,
         paths.subscriptions_list_path,
+        on_progress,
     )
     .await?;
 
@@ -290,6 +326,7 @@ pub async fn restore_backup(
         &backup_data.data.overrides,
         paths.overrides_dir,
         paths.overrides_list_path,
+        on_progress,
     )
     .await?;
 
@@ -327,6 +364,7 @@ async fn collect_preferences(
 async fn collect_subscriptions(
     subscriptions_dir: &str,
     subscriptions_list_path: &str,
+    on_progress: ProgressCallback<'_>,
 ) -> Result<SubscriptionBackup, Box<dyn std::error::Error + Send + Sync>> {
     let mut backup = SubscriptionBackup {
         list: None,
@@ -339,20 +377,17 @@ async fn collect_subscriptions(
     }
 
     // 读取所有订阅配置文件
-    if Path::new(subscriptions_dir).exists() {
-        let mut entries = async_fs::read_dir(subscriptions_dir).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("yaml")
-                && let Some(file_name) = path.file_stem().and_then(|s| s.to_str())
-            {
-                let content = async_fs::read(&path).await?;
-                backup.configs.insert(
-                    file_name.to_string(),
-                    general_purpose::STANDARD.encode(&content),
-                );
-            }
+    let files = list_files(subscriptions_dir, is_yaml_file).await?;
+    let total = files.len();
+    for (index, path) in files.iter().enumerate() {
+        if let Some(file_name) = path.file_stem().and_then(|s| s.to_str()) {
+            let content = async_fs::read(path).await?;
+            backup.configs.insert(
+                file_name.to_string(),
+                general_purpose::STANDARD.encode(&content),
+            );
         }
+        on_progress(STAGE_COLLECT_SUBSCRIPTIONS, index + 1, total);
     }
 
     Ok(backup)
@@ -362,6 +397,7 @@ async fn collect_subscriptions(
 async fn collect_overrides(
     overrides_dir: &str,
     overrides_list_path: &str,
+    on_progress: ProgressCallback<'_>,
 ) -> Result<OverrideBackup, Box<dyn std::error::Error + Send + Sync>> {
     let mut backup = OverrideBackup {
         list: None,
@@ -374,25 +410,48 @@ async fn collect_overrides(
     }
 
     // 读取所有覆写文件
-    if Path::new(overrides_dir).exists() {
-        let mut entries = async_fs::read_dir(overrides_dir).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.is_file()
-                && let Some(file_name) = path.file_name().and_then(|s| s.to_str())
-            {
-                let content = async_fs::read(&path).await?;
-                backup.files.insert(
-                    file_name.to_string(),
-                    general_purpose::STANDARD.encode(&content),
-                );
-            }
+    let files = list_files(overrides_dir, |path| path.is_file()).await?;
+    let total = files.len();
+    for (index, path) in files.iter().enumerate() {
+        if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
+            let content = async_fs::read(path).await?;
+            backup.files.insert(
+                file_name.to_string(),
+                general_purpose::STANDARD.encode(&content),
+            );
         }
+        on_progress(STAGE_COLLECT_OVERRIDES, index + 1, total);
     }
 
     Ok(backup)
 }
 
+// 列出目录下符合条件的文件（按路径排序，保证进度顺序稳定）
+async fn list_files(
+    dir: &str,
+    filter: fn(&Path) -> bool,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut files = Vec::new();
+    if !Path::new(dir).exists() {
+        return Ok(files);
+    }
+
+    let mut entries = async_fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if filter(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// 判断是否为订阅配置文件（.yaml）
+fn is_yaml_file(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("yaml")
+}
+
 // 收集文件并 Base64 编码
 async fn collect_file_base64(path: &str) -> Option<String> {
     if !Path::new(path).exists() {
@@ -452,6 +511,7 @@ async fn restore_subscriptions(
     backup: &SubscriptionBackup,
     subscriptions_dir: &str,
     subscriptions_list_path: &str,
+    on_progress: ProgressCallback<'_>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // 清空现有订阅配置文件
     if Path::new(subscriptions_dir).exists() {
@@ -471,10 +531,13 @@ async fn restore_subscriptions(
     }
 
     // 还原订阅配置文件
-    for (file_name, base64_content) in &backup.configs {
-        let content = general_purpose::STANDARD.decode(base64_content)?;
+    let file_names = sorted_keys(&backup.configs);
+    let total = file_names.len();
+    for (index, file_name) in file_names.into_iter().enumerate() {
+        let content = general_purpose::STANDARD.decode(&backup.configs[file_name])?;
         let file_path = format!("{}/{}.yaml", subscriptions_dir, file_name);
         async_fs::write(&file_path, content).await?;
+        on_progress(STAGE_RESTORE_SUBSCRIPTIONS, index + 1, total);
     }
 
     log::info!("订阅数据已还原");
@@ -486,6 +549,7 @@ async fn restore_overrides(
     backup: &OverrideBackup,
     overrides_dir: &str,
     overrides_list_path: &str,
+    on_progress: ProgressCallback<'_>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // 清空现有覆写文件
     if Path::new(overrides_dir).exists() {
@@ -505,16 +569,26 @@ async fn restore_overrides(
     }
 
     // 还原覆写文件
-    for (file_name, base64_content) in &backup.files {
-        let content = general_purpose::STANDARD.decode(base64_content)?;
+    let file_names = sorted_keys(&backup.files);
+    let total = file_names.len();
+    for (index, file_name) in file_names.into_iter().enumerate() {
+        let content = general_purpose::STANDARD.decode(&backup.files[file_name])?;
         let file_path = format!("{}/{}", overrides_dir, file_name);
         async_fs::write(&file_path, content).await?;
+        on_progress(STAGE_RESTORE_OVERRIDES, index + 1, total);
     }
 
     log::info!("覆写数据已还原");
     Ok(())
 }
 
+// 获取排序后的文件名列表
+fn sorted_keys(files: &HashMap<String, String>) -> Vec<&String> {
+    let mut keys: Vec<&String> = files.keys().collect();
+    keys.sort();
+    keys
+}
+
 // 还原文件（Base64 解码）
 async fn restore_file_base64(
     base64_content: &str,
@@ -554,3 +628,105 @@ pub fn init() {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    type ProgressLog = Mutex<Vec<(String, usize, usize)>>;
+
+    fn record(log: &ProgressLog, stage: &str, current: usize, total: usize) {
+        if let Ok(mut entries) = log.lock() {
+            entries.push((stage.to_string(), current, total));
+        }
+    }
+
+    fn take(log: &ProgressLog) -> Vec<(String, usize, usize)> {
+        log.lock()
+            .map(|mut entries| std::mem::take(&mut *entries))
+            .unwrap_or_default()
+    }
+
+    fn entry(stage: &str, current: usize, total: usize) -> (String, usize, usize) {
+        (stage.to_string(), current, total)
+    }
+
+    // 按顺序生成 BackupPaths 所需的 7 个路径
+    fn test_paths(base: &Path) -> [String; 7] {
+        [
+            "preferences.json",
+            "subscriptions",
+            "subscriptions_list.json",
+            "overrides",
+            "overrides_list.json",
+            "dns.yaml",
+            "proxy.pac",
+        ]
+        .map(|name| base.join(name).to_string_lossy().to_string())
+    }
+
+    fn backup_paths(paths: &[String; 7]) -> BackupPaths<'_> {
+        BackupPaths {
+            preferences_path: &paths[0],
+            subscriptions_dir: &paths[1],
+            subscriptions_list_path: &paths[2],
+            overrides_dir: &paths[3],
+            overrides_list_path: &paths[4],
+            dns_config_path: &paths[5],
+            pac_file_path: &paths[6],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_progress_callbacks_fire_in_order() {
+        let root = std::env::temp_dir().join(format!("stelliberty-backup-{}", std::process::id()));
+        let source = test_paths(&root.join("source"));
+        let target = test_paths(&root.join("target"));
+
+        for dir in [&source[1], &source[3]] {
+            std::fs::create_dir_all(dir).unwrap_or_else(|e| panic!("{}", e));
+        }
+        for file in [
+            format!("{}/b.yaml", source[1]),
+            format!("{}/a.yaml", source[1]),
+            format!("{}/notes.txt", source[1]),
+            format!("{}/rule.js", source[3]),
+            source[2].clone(),
+            source[4].clone(),
+        ] {
+            std::fs::write(&file, "[]").unwrap_or_else(|e| panic!("{}", e));
+        }
+
+        let log = ProgressLog::default();
+        let on_progress =
+            |stage: &str, current: usize, total: usize| record(&log, stage, current, total);
+        let backup_file = root.join("backup.json").to_string_lossy().to_string();
+
+        let created =
+            create_backup(&backup_file, "test", backup_paths(&source), &on_progress).await;
+        assert!(created.is_ok());
+        assert_eq!(
+            take(&log),
+            vec![
+                entry(STAGE_COLLECT_SUBSCRIPTIONS, 1, 2),
+                entry(STAGE_COLLECT_SUBSCRIPTIONS, 2, 2),
+                entry(STAGE_COLLECT_OVERRIDES, 1, 1),
+            ]
+        );
+
+        let restored = restore_backup(&backup_file, backup_paths(&target), &on_progress).await;
+        assert!(restored.is_ok());
+        assert_eq!(
+            take(&log),
+            vec![
+                entry(STAGE_RESTORE_SUBSCRIPTIONS, 1, 2),
+                entry(STAGE_RESTORE_SUBSCRIPTIONS, 2, 2),
+                entry(STAGE_RESTORE_OVERRIDES, 1, 1),
+            ]
+        );
+        assert!(Path::new(&format!("{}/a.yaml", target[1])).exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,206 @@
+// 完整清理：卸载或重置应用前撤销系统代理、开机自启与（可选）后台服务。
+// 各步骤互不依赖，某一步失败不影响其余步骤，结果逐项汇报给 Dart 端。
+
+use crate::atoms::system_proxy::{self, ProxyResult};
+use rinf::{DartSignal, RustSignal, SignalPiece};
+use serde::{Deserialize, Serialize};
+
+use super::auto_start::set_auto_start_status;
+
+// Dart → Rust：执行完整清理
+#[derive(Deserialize, DartSignal)]
+pub struct FullCleanup {
+    // 是否同时卸载后台服务（会请求管理员权限）
+    pub should_uninstall_service: bool,
+}
+
+// 清理步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, SignalPiece)]
+pub enum CleanupStep {
+    SystemProxy,
+    AutoStart,
+    Service,
+}
+
+// 单个步骤的执行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, SignalPiece)]
+pub enum CleanupStatus {
+    Cleaned,
+    // 无需清理（如服务未安装）或未请求执行
+    Skipped,
+    Failed,
+}
+
+// 单个步骤的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, SignalPiece)]
+pub struct CleanupStepResult {
+    pub step: CleanupStep,
+    pub status: CleanupStatus,
+    pub error_message: Option<String>,
+}
+
+// Rust → Dart：完整清理结果
+#[derive(Debug, Serialize, RustSignal)]
+pub struct FullCleanupResult {
+    pub steps: Vec<CleanupStepResult>,
+    // 没有失败的步骤
+    pub is_all_successful: bool,
+}
+
+impl FullCleanup {
+    pub async fn handle(self) {
+        log::info!(
+            "开始完整清理（卸载服务：{}）",
+            self.should_uninstall_service
+        );
+
+        let proxy = match system_proxy::disable_proxy(&[]).await {
+            ProxyResult::Success => Ok(true),
+            ProxyResult::Error(e) => Err(e),
+        };
+
+        // Windows 下会等待任务计划程序生效，放到阻塞线程执行
+        let auto_start = tokio::task::spawn_blocking(|| set_auto_start_status(false))
+            .await
+            .map_err(|e| format!("执行任务失败：{}", e))
+            .and_then(|result| result)
+            .and_then(|is_enabled| {
+                if is_enabled {
+                    Err("开机自启仍处于启用状态".to_string())
+                } else {
+                    Ok(true)
+                }
+            });
+
+        let service = if self.should_uninstall_service {
+            uninstall_service().await
+        } else {
+            Ok(false)
+        };
+
+        let result = summarize(vec![
+            (CleanupStep::SystemProxy, proxy),
+            (CleanupStep::AutoStart, auto_start),
+            (CleanupStep::Service, service),
+        ]);
+        for step in result.steps.iter() {
+            match step.status {
+                CleanupStatus::Failed => log::warn!(
+                    "清理失败[{:?}]：{}",
+                    step.step,
+                    step.error_message.as_deref().unwrap_or_default()
+                ),
+                status => log::info!("清理[{:?}]：{:?}", step.step, status),
+            }
+        }
+        result.send_signal_to_dart();
+    }
+}
+
+// 卸载后台服务，服务未安装时跳过
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+async fn uninstall_service() -> Result<bool, String> {
+    use crate::molecules::clash_process::ServiceManager;
+
+    if !ServiceManager::is_service_registered() {
+        return Ok(false);
+    }
+    ServiceManager::default()
+        .uninstall_service()
+        .await
+        .map(|()| true)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+async fn uninstall_service() -> Result<bool, String> {
+    Ok(false)
+}
+
+// 汇总各步骤结果：Ok(true) 为已清理，Ok(false) 为跳过
+pub fn summarize(outcomes: Vec<(CleanupStep, Result<bool, String>)>) -> FullCleanupResult {
+    let steps: Vec<CleanupStepResult> = outcomes
+        .into_iter()
+        .map(|(step, outcome)| {
+            let (status, error_message) = match outcome {
+                Ok(true) => (CleanupStatus::Cleaned, None),
+                Ok(false) => (CleanupStatus::Skipped, None),
+                Err(e) => (CleanupStatus::Failed, Some(e)),
+            };
+            CleanupStepResult {
+                step,
+                status,
+                error_message,
+            }
+        })
+        .collect();
+
+    FullCleanupResult {
+        is_all_successful: steps
+            .iter()
+            .all(|step| step.status != CleanupStatus::Failed),
+        steps,
+    }
+}
+
+pub fn init() {
+    use tokio::spawn;
+
+    spawn(async {
+        let receiver = FullCleanup::get_dart_signal_receiver();
+        while let Some(dart_signal) = receiver.recv().await {
+            let message = dart_signal.message;
+            tokio::spawn(async move {
+                message.handle().await;
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_mixed_outcomes() {
+        let result = summarize(vec![
+            (
+                CleanupStep::SystemProxy,
+                Err("networksetup 执行失败".to_string()),
+            ),
+            (CleanupStep::AutoStart, Ok(true)),
+            (CleanupStep::Service, Ok(false)),
+        ]);
+
+        // 失败的步骤不影响后续步骤的结果
+        let statuses: Vec<(CleanupStep, CleanupStatus)> = result
+            .steps
+            .iter()
+            .map(|step| (step.step, step.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (CleanupStep::SystemProxy, CleanupStatus::Failed),
+                (CleanupStep::AutoStart, CleanupStatus::Cleaned),
+                (CleanupStep::Service, CleanupStatus::Skipped),
+            ]
+        );
+        assert_eq!(
+            result.steps[0].error_message.as_deref(),
+            Some("networksetup 执行失败")
+        );
+        assert!(!result.is_all_successful);
+    }
+
+    #[test]
+    fn test_summarize_all_successful() {
+        let result = summarize(vec![
+            (CleanupStep::SystemProxy, Ok(true)),
+            (CleanupStep::AutoStart, Ok(true)),
+            (CleanupStep::Service, Ok(false)),
+        ]);
+        assert!(result.is_all_successful);
+        assert!(result.steps.iter().all(|step| step.error_message.is_none()));
+    }
+}
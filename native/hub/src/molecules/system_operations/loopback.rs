@@ -1,8 +1,10 @@
 // Windows UWP 回环豁免管理：提供回环豁免的查询与配置能力。
 // 仅在 Windows 平台启用。
 
+use once_cell::sync::Lazy;
 use rinf::{DartSignal, RustSignal};
 use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
 use tokio::spawn;
 
 #[cfg(windows)]
@@ -37,12 +39,17 @@ pub struct SetLoopback {
 #[derive(Deserialize, DartSignal)]
 pub struct SaveLoopbackConfiguration {
     pub sid_strings: Vec<String>,
+    // 为 true 时只计算变更计划并返回，不实际修改
+    pub preview: bool,
 }
 
 // Rust → Dart：应用容器列表（用于初始化）
 #[derive(Serialize, RustSignal)]
 pub struct AppContainersList {
     pub containers: Vec<String>,
+    pub error_message: Option<String>,
+    // 失败原因为权限不足，Dart 端应提示以管理员身份运行
+    pub requires_elevation: bool,
 }
 
 // Rust → Dart：单个应用容器信息
@@ -72,6 +79,153 @@ pub struct AppContainersComplete;
 pub struct SaveLoopbackConfigurationResult {
     pub is_successful: bool,
     pub error_message: Option<String>,
+    pub requires_elevation: bool,
+    // 是否为预览结果（未实际修改）
+    pub is_preview: bool,
+    // 将启用回环豁免的应用名称
+    pub to_enable: Vec<String>,
+    // 将禁用回环豁免的应用名称
+    pub to_disable: Vec<String>,
+    // 预计因系统保护被跳过的应用名称
+    pub protected: Vec<String>,
+}
+
+// 此前修改时因系统保护被拒绝的容器 SID，用于预览时提示将被跳过的应用
+static KNOWN_PROTECTED_SIDS: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
+
+// 单个容器的回环豁免变更
+#[derive(Debug, Clone)]
+pub struct LoopbackChange {
+    pub container: AppContainer,
+    pub should_enable: bool,
+    // 此前修改时被系统保护拒绝，预计仍会被跳过
+    pub is_known_protected: bool,
+}
+
+// 保存配置的变更计划：只包含回环状态需要改变的容器
+#[derive(Debug, Clone, Default)]
+pub struct LoopbackPlan {
+    pub changes: Vec<LoopbackChange>,
+}
+
+impl LoopbackPlan {
+    fn names(&self, filter: impl Fn(&LoopbackChange) -> bool) -> Vec<String> {
+        self.changes
+            .iter()
+            .filter(|change| filter(change))
+            .map(|change| change.container.display_name.clone())
+            .collect()
+    }
+
+    pub fn to_enable(&self) -> Vec<String> {
+        self.names(|change| change.should_enable && !change.is_known_protected)
+    }
+
+    pub fn to_disable(&self) -> Vec<String> {
+        self.names(|change| !change.should_enable && !change.is_known_protected)
+    }
+
+    pub fn protected(&self) -> Vec<String> {
+        self.names(|change| change.is_known_protected)
+    }
+}
+
+// 根据期望启用的 SID 集合计算变更计划（不调用系统 API）
+pub fn plan_loopback_changes(
+    containers: &[AppContainer],
+    sid_strings: &[String],
+    known_protected: &HashSet<String>,
+) -> LoopbackPlan {
+    // 性能优化：使用 HashSet 进行 O(1) 查找，避免 O(n²) 复杂度
+    let enabled_sids: HashSet<&str> = sid_strings.iter().map(String::as_str).collect();
+
+    let changes = containers
+        .iter()
+        .filter_map(|container| {
+            let should_enable = enabled_sids.contains(container.sid_string.as_str());
+            (container.is_loopback_enabled != should_enable).then(|| LoopbackChange {
+                container: container.clone(),
+                should_enable,
+                is_known_protected: known_protected.contains(&container.sid_string),
+            })
+        })
+        .collect();
+    LoopbackPlan { changes }
+}
+
+// 系统保护的应用拒绝修改时返回 ERROR_ACCESS_DENIED
+fn is_protected_error(error: &str) -> bool {
+    error.contains("0x80070005")
+        || error.contains("0x00000005")
+        || error.contains("ERROR_ACCESS_DENIED")
+}
+
+// 名称较少时附上具体名称
+fn describe_names(label: &str, names: &[String]) -> String {
+    if names.len() <= 3 {
+        format!("{}：{}个（{}）", label, names.len(), names.join("、"))
+    } else {
+        format!("{}：{}个", label, names.len())
+    }
+}
+
+// 预览结果的说明文字
+fn preview_message(plan: &LoopbackPlan) -> String {
+    let mut message_parts = Vec::new();
+    for (label, names) in [
+        ("将启用", plan.to_enable()),
+        ("将禁用", plan.to_disable()),
+        ("预计跳过系统保护应用", plan.protected()),
+    ] {
+        if !names.is_empty() {
+            message_parts.push(describe_names(label, &names));
+        }
+    }
+
+    if message_parts.is_empty() {
+        "无需修改".to_string()
+    } else {
+        message_parts.join("，")
+    }
+}
+
+// 枚举应用容器失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerEnumError {
+    // 当前进程完整性级别不足
+    AccessDenied,
+    // RPC 服务不可用（防火墙相关服务未运行）
+    RpcUnavailable,
+    // 其他错误码
+    Other(u32),
+}
+
+impl ContainerEnumError {
+    // 将 API 返回码映射为错误类型（兼容 Win32 错误码与 HRESULT）
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            5 | 0x80070005 => Self::AccessDenied,
+            1722 | 0x800706BA => Self::RpcUnavailable,
+            code => Self::Other(code),
+        }
+    }
+
+    pub fn requires_elevation(&self) -> bool {
+        matches!(self, Self::AccessDenied)
+    }
+}
+
+impl std::fmt::Display for ContainerEnumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AccessDenied => write!(f, "枚举应用容器失败：权限不足，请以管理员身份运行后重试"),
+            Self::RpcUnavailable => write!(
+                f,
+                "枚举应用容器失败：RPC 服务不可用，请确认 Windows 防火墙服务已启动"
+            ),
+            Self::Other(code) => write!(f, "枚举应用容器失败（错误码：0x{:08X}）", code),
+        }
+    }
 }
 
 impl GetAppContainers {
@@ -82,7 +236,12 @@ impl GetAppContainers {
         match enumerate_app_containers() {
             Ok(containers) => {
                 log::info!("发送{}个容器信息到 Dart", containers.len());
-                AppContainersList { containers: vec![] }.send_signal_to_dart();
+                AppContainersList {
+                    containers: vec![],
+                    error_message: None,
+                    requires_elevation: false,
+                }
+                .send_signal_to_dart();
 
                 for c in containers {
                     AppContainerInfo {
@@ -102,7 +261,12 @@ impl GetAppContainers {
             }
             Err(e) => {
                 log::error!("获取应用容器失败：{}", e);
-                AppContainersList { containers: vec![] }.send_signal_to_dart();
+                AppContainersList {
+                    containers: vec![],
+                    error_message: Some(e.to_string()),
+                    requires_elevation: e.requires_elevation(),
+                }
+                .send_signal_to_dart();
                 // 即使失败也发送完成信号，避免 Dart 端无限等待
                 AppContainersComplete.send_signal_to_dart();
             }
@@ -141,9 +305,13 @@ impl SetLoopback {
 }
 
 impl SaveLoopbackConfiguration {
-    // 批量保存回环豁免配置。
+    // 批量保存回环豁免配置，预览模式下只返回变更计划。
     pub fn handle(self) {
-        log::info!("处理保存配置请求，期望启用{}个容器", self.sid_strings.len());
+        log::info!(
+            "处理保存配置请求，期望启用{}个容器（预览：{}）",
+            self.sid_strings.len(),
+            self.preview
+        );
 
         // 获取所有容器
         let containers = match enumerate_app_containers() {
@@ -153,49 +321,70 @@ impl SaveLoopbackConfiguration {
                 SaveLoopbackConfigurationResult {
                     is_successful: false,
                     error_message: Some(format!("无法枚举容器：{}", e)),
+                    requires_elevation: e.requires_elevation(),
+                    is_preview: self.preview,
+                    to_enable: vec![],
+                    to_disable: vec![],
+                    protected: vec![],
                 }
                 .send_signal_to_dart();
                 return;
             }
         };
 
-        // 性能优化：使用 HashSet 进行 O(1) 查找，避免 O(n²) 复杂度
-        use std::collections::HashSet as StdHashSet;
-        let enabled_sids: StdHashSet<&str> = self.sid_strings.iter().map(|s| s.as_str()).collect();
+        let known_protected = KNOWN_PROTECTED_SIDS
+            .lock()
+            .map(|sids| sids.clone())
+            .unwrap_or_default();
+        let plan = plan_loopback_changes(&containers, &self.sid_strings, &known_protected);
+
+        if self.preview {
+            log::info!("回环豁免预览完成，待修改：{}个", plan.changes.len());
+            SaveLoopbackConfigurationResult {
+                is_successful: true,
+                error_message: Some(preview_message(&plan)),
+                requires_elevation: false,
+                is_preview: true,
+                to_enable: plan.to_enable(),
+                to_disable: plan.to_disable(),
+                protected: plan.protected(),
+            }
+            .send_signal_to_dart();
+            return;
+        }
 
         let mut errors = Vec::new();
         let mut skipped = Vec::new();
         let mut success_count = 0;
-        let mut skipped_count = 0;
 
-        // 对每个容器，检查是否应该启用（现在是 O(1) 查找）
-        for container in containers {
-            let should_enable = enabled_sids.contains(container.sid_string.as_str());
+        for change in &plan.changes {
+            let container = &change.container;
+            log::info!(
+                "修改容器：{}(SID：{}) | {} -> {}",
+                container.display_name,
+                container.sid_string,
+                container.is_loopback_enabled,
+                change.should_enable
+            );
 
-            if container.is_loopback_enabled != should_enable {
-                log::info!(
-                    "修改容器：{}(SID：{}) | {} -> {}",
-                    container.display_name,
-                    container.sid_string,
-                    container.is_loopback_enabled,
-                    should_enable
-                );
-
-                if let Err(e) = set_loopback_exemption_by_sid(&container.sid, should_enable) {
-                    // 检查是否是系统保护的应用（ERROR_ACCESS_DENIED）
-                    if e.contains("0x80070005")
-                        || e.contains("0x00000005")
-                        || e.contains("ERROR_ACCESS_DENIED")
-                    {
-                        log::info!("跳过系统保护的应用：{}", container.display_name);
-                        skipped.push(container.display_name.clone());
-                        skipped_count += 1;
-                    } else {
-                        log::error!("设置容器失败：{} - {}", container.display_name, e);
-                        errors.push(format!("{}：{}", container.display_name, e));
-                    }
-                } else {
+            match set_loopback_exemption_by_sid(&container.sid, change.should_enable) {
+                Ok(()) => {
                     success_count += 1;
+                    if let Ok(mut sids) = KNOWN_PROTECTED_SIDS.lock() {
+                        sids.remove(&container.sid_string);
+                    }
+                }
+                // 检查是否是系统保护的应用（ERROR_ACCESS_DENIED）
+                Err(e) if is_protected_error(&e) => {
+                    log::info!("跳过系统保护的应用：{}", container.display_name);
+                    skipped.push(container.display_name.clone());
+                    if let Ok(mut sids) = KNOWN_PROTECTED_SIDS.lock() {
+                        sids.insert(container.sid_string.clone());
+                    }
+                }
+                Err(e) => {
+                    log::error!("设置容器失败：{} - {}", container.display_name, e);
+                    errors.push(format!("{}：{}", container.display_name, e));
                 }
             }
         }
@@ -203,7 +392,7 @@ impl SaveLoopbackConfiguration {
         log::info!(
             "配置保存完成，成功：{}，跳过：{}，错误：{}",
             success_count,
-            skipped_count,
+            skipped.len(),
             errors.len()
         );
 
@@ -214,36 +403,37 @@ impl SaveLoopbackConfiguration {
             message_parts.push(format!("成功修改：{}个", success_count));
         }
 
-        if skipped_count > 0 {
-            message_parts.push(format!("跳过系统保护应用：{}个", skipped_count));
-            if skipped.len() <= 3 {
-                // 如果跳过的应用少于等于 3 个，显示具体名称
-                message_parts.push(format!("（{}）", skipped.join("、")));
-            }
+        if !skipped.is_empty() {
+            // 如果跳过的应用少于等于 3 个，显示具体名称
+            message_parts.push(describe_names("跳过系统保护应用", &skipped));
         }
 
-        if errors.is_empty() {
-            SaveLoopbackConfigurationResult {
-                is_successful: true,
-                error_message: if message_parts.is_empty() {
-                    Some("配置保存成功（无需修改）".to_string())
-                } else {
-                    Some(message_parts.join("，"))
-                },
+        let is_successful = errors.is_empty();
+        let error_message = if is_successful {
+            if message_parts.is_empty() {
+                "配置保存成功（无需修改）".to_string()
+            } else {
+                message_parts.join("，")
             }
-            .send_signal_to_dart();
         } else {
             message_parts.push(format!("失败：{}个", errors.len()));
-            SaveLoopbackConfigurationResult {
-                is_successful: false,
-                error_message: Some(format!(
-                    "{}。\n错误详情：\n{}",
-                    message_parts.join("，"),
-                    errors.join("\n")
-                )),
-            }
-            .send_signal_to_dart();
+            format!(
+                "{}。\n错误详情：\n{}",
+                message_parts.join("，"),
+                errors.join("\n")
+            )
+        };
+
+        SaveLoopbackConfigurationResult {
+            is_successful,
+            error_message: Some(error_message),
+            requires_elevation: false,
+            is_preview: false,
+            to_enable: plan.to_enable(),
+            to_disable: plan.to_disable(),
+            protected: skipped,
         }
+        .send_signal_to_dart();
     }
 }
 
@@ -342,7 +532,7 @@ unsafe fn sid_to_string(sid: *mut SID) -> String {
 
 // 枚举 UWP 应用容器并返回回环状态。
 #[cfg(windows)]
-pub fn enumerate_app_containers() -> Result<Vec<AppContainer>, String> {
+pub fn enumerate_app_containers() -> Result<Vec<AppContainer>, ContainerEnumError> {
     unsafe {
         log::info!("开始枚举应用容器");
         let mut count: u32 = 0;
@@ -352,7 +542,7 @@ pub fn enumerate_app_containers() -> Result<Vec<AppContainer>, String> {
 
         if result != 0 {
             log::error!("枚举应用容器失败：{}", result);
-            return Err(format!("枚举应用容器失败：{}", result));
+            return Err(ContainerEnumError::from_code(result));
         }
 
         if count == 0 || containers.is_null() {
@@ -508,7 +698,7 @@ pub fn set_loopback_exemption(package_family_name: &str, enabled: bool) -> Resul
 
         if result != 0 {
             log::error!("枚举应用容器失败：{}", result);
-            return Err(format!("枚举应用容器失败：{}", result));
+            return Err(ContainerEnumError::from_code(result).to_string());
         }
 
         if count == 0 || containers.is_null() {
@@ -628,3 +818,101 @@ pub fn init() {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_enum_error_mapping() {
+        let access_denied = ContainerEnumError::from_code(5);
+        assert_eq!(access_denied, ContainerEnumError::AccessDenied);
+        assert_eq!(
+            ContainerEnumError::from_code(0x80070005),
+            ContainerEnumError::AccessDenied
+        );
+        assert!(access_denied.requires_elevation());
+        assert!(access_denied.to_string().contains("管理员"));
+
+        let rpc = ContainerEnumError::from_code(1722);
+        assert_eq!(rpc, ContainerEnumError::RpcUnavailable);
+        assert!(!rpc.requires_elevation());
+        assert!(rpc.to_string().contains("RPC"));
+
+        let other = ContainerEnumError::from_code(0x80004005);
+        assert_eq!(other, ContainerEnumError::Other(0x80004005));
+        assert!(other.to_string().contains("0x80004005"));
+    }
+
+    fn container(name: &str, sid_string: &str, is_loopback_enabled: bool) -> AppContainer {
+        AppContainer {
+            app_container_name: name.to_lowercase(),
+            display_name: name.to_string(),
+            package_family_name: format!("{}_8wekyb3d8bbwe", name),
+            sid: vec![1, 2, 0, 0, 0, 0, 0, 15],
+            sid_string: sid_string.to_string(),
+            is_loopback_enabled,
+        }
+    }
+
+    #[test]
+    fn test_plan_loopback_changes() {
+        let containers = vec![
+            container("Mail", "S-1-15-2-1", false),
+            container("Store", "S-1-15-2-2", true),
+            container("Photos", "S-1-15-2-3", true),
+            container("Edge", "S-1-15-2-4", false),
+            container("Weather", "S-1-15-2-5", false),
+        ];
+        // 期望启用 Mail、Photos、Edge，Edge 此前被系统保护拒绝
+        let sid_strings = vec![
+            "S-1-15-2-1".to_string(),
+            "S-1-15-2-3".to_string(),
+            "S-1-15-2-4".to_string(),
+            "S-1-15-2-9".to_string(),
+        ];
+        let known_protected: HashSet<String> = ["S-1-15-2-4".to_string()].into();
+
+        let plan = plan_loopback_changes(&containers, &sid_strings, &known_protected);
+        // Photos 已启用、Weather 已禁用，均无需修改；不存在的 SID 被忽略
+        assert_eq!(plan.changes.len(), 3);
+        assert_eq!(plan.to_enable(), vec!["Mail".to_string()]);
+        assert_eq!(plan.to_disable(), vec!["Store".to_string()]);
+        assert_eq!(plan.protected(), vec!["Edge".to_string()]);
+        assert!(
+            plan.changes
+                .iter()
+                .any(|c| c.should_enable && c.is_known_protected)
+        );
+
+        let message = preview_message(&plan);
+        assert!(message.contains("将启用：1个（Mail）"), "{}", message);
+        assert!(message.contains("将禁用：1个（Store）"), "{}", message);
+        assert!(
+            message.contains("预计跳过系统保护应用：1个（Edge）"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_plan_without_changes() {
+        let containers = vec![
+            container("Mail", "S-1-15-2-1", true),
+            container("Store", "S-1-15-2-2", false),
+        ];
+        let plan = plan_loopback_changes(&containers, &["S-1-15-2-1".to_string()], &HashSet::new());
+        assert!(plan.changes.is_empty());
+        assert_eq!(preview_message(&plan), "无需修改");
+
+        // 清空期望列表时禁用所有已启用的容器
+        let plan = plan_loopback_changes(&containers, &[], &HashSet::new());
+        assert_eq!(plan.to_disable(), vec!["Mail".to_string()]);
+        assert!(is_protected_error(
+            "设置回环豁免失败 (错误码: 0x80070005, 十进制: 2147942405)"
+        ));
+        assert!(!is_protected_error(
+            "设置回环豁免失败 (错误码: 0x80070057, 十进制: 2147942487)"
+        ));
+    }
+}
@@ -1,6 +1,21 @@
 // Clash 核心管理模块
 
+pub mod config_export;
+pub mod config_switch;
+pub mod controller;
+pub mod core_policy;
+pub mod dns_check;
+pub mod egress;
+pub mod exit_monitor;
+pub mod geodata;
+pub mod kill_switch;
+pub mod launch;
 pub mod manager;
+pub mod orphan;
+pub mod ports;
+pub mod preflight;
+pub mod safe_mode;
+pub mod stream_fanout;
 
 // Re-export
 pub use manager::*;
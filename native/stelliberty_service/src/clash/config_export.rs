@@ -0,0 +1,129 @@
+// 运行配置导出
+//
+// 从核心控制器读取当前生效的配置，用于问题反馈；可选脱敏敏感字段
+
+use super::controller;
+use serde_json::Value;
+
+// 脱敏后的占位值
+const REDACTED: &str = "<redacted>";
+
+// 需要脱敏的字段（节点密码、UUID、预共享密钥、认证信息等）
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "uuid",
+    "private-key",
+    "pre-shared-key",
+    "psk",
+    "auth",
+    "auth-str",
+    "token",
+    "secret",
+    "obfs-password",
+    "authentication",
+];
+
+// 导出核心运行中的配置，返回格式化后的 JSON 与脱敏字段数
+pub async fn export_running_config(
+    controller_path: &str,
+    redact: bool,
+) -> Result<(String, u32), String> {
+    let response = controller::request(controller_path, "GET", "/configs").await?;
+    if !response.is_success() {
+        return Err(format!("导出运行配置失败: HTTP {}", response.status_code));
+    }
+
+    let mut config: Value = serde_json::from_str(&response.body)
+        .map_err(|e| format!("导出运行配置失败: 无法解析响应: {}", e))?;
+    let redacted_fields = if redact {
+        redact_secrets(&mut config)
+    } else {
+        0
+    };
+
+    let text =
+        serde_json::to_string_pretty(&config).map_err(|e| format!("导出运行配置失败: {}", e))?;
+    Ok((text, redacted_fields))
+}
+
+// 递归替换敏感字段的值，返回替换数量（空值保持原样，便于区分未设置）
+pub fn redact_secrets(value: &mut Value) -> u32 {
+    match value {
+        Value::Object(map) => map
+            .iter_mut()
+            .map(|(key, field)| {
+                let is_secret = SECRET_KEYS.iter().any(|k| key.eq_ignore_ascii_case(k));
+                if is_secret && !is_empty(field) {
+                    *field = redact_value(field);
+                    1
+                } else {
+                    redact_secrets(field)
+                }
+            })
+            .sum(),
+        Value::Array(items) => items.iter_mut().map(redact_secrets).sum(),
+        _ => 0,
+    }
+}
+
+// 保留结构（数组长度），只替换内容
+fn redact_value(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|_| Value::String(REDACTED.to_string()))
+                .collect(),
+        ),
+        _ => Value::String(REDACTED.to_string()),
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_sample_config() {
+        let mut config = json!({
+            "mixed-port": 7890,
+            "authentication": ["user:pass"],
+            "skip-auth-prefixes": ["127.0.0.1/8"],
+            "tun": { "enable": true, "stack": "mixed" },
+            "proxies": [
+                { "name": "hk", "type": "ss", "server": "hk.example.com", "password": "p1" },
+                { "name": "jp", "type": "vless", "uuid": "11111111-1111-1111-1111-111111111111" },
+                { "name": "wg", "type": "wireguard", "private-key": "key", "pre-shared-key": "" }
+            ]
+        });
+
+        assert_eq!(redact_secrets(&mut config), 4);
+        assert_eq!(config["authentication"], json!([REDACTED]));
+        assert_eq!(config["proxies"][0]["password"], REDACTED);
+        assert_eq!(config["proxies"][0]["server"], "hk.example.com");
+        assert_eq!(config["proxies"][1]["uuid"], REDACTED);
+        assert_eq!(config["proxies"][2]["private-key"], REDACTED);
+        // 空值不替换
+        assert_eq!(config["proxies"][2]["pre-shared-key"], "");
+        assert_eq!(config["skip-auth-prefixes"], json!(["127.0.0.1/8"]));
+        assert_eq!(config["tun"]["stack"], "mixed");
+    }
+
+    #[test]
+    fn test_redact_without_secrets() {
+        let mut config = json!({ "mode": "rule", "log-level": "info", "ipv6": false });
+        let original = config.clone();
+        assert_eq!(redact_secrets(&mut config), 0);
+        assert_eq!(config, original);
+    }
+}
@@ -0,0 +1,103 @@
+// 切换核心配置：用新配置重启核心并等待控制器就绪，失败时自动恢复原配置，
+// 避免切换失败后用户处于断网状态。
+
+use std::future::Future;
+
+// 切换结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwitchOutcome {
+    // 新配置已生效
+    Switched,
+    // 新配置启动失败，已恢复原配置
+    Reverted { reason: String },
+}
+
+// 依次尝试新配置与原配置，apply 负责以指定配置重启核心并确认其就绪。
+// 两者都失败时返回错误（此时核心可能处于停止状态）。
+pub async fn switch_config<F, Fut>(
+    previous_config: &str,
+    new_config: &str,
+    mut apply: F,
+) -> Result<SwitchOutcome, String>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let reason = match apply(new_config.to_string()).await {
+        Ok(()) => {
+            log::info!("配置已切换: {}", new_config);
+            return Ok(SwitchOutcome::Switched);
+        }
+        Err(e) => e,
+    };
+
+    log::warn!(
+        "新配置启动失败，恢复原配置: {} ({})",
+        previous_config,
+        reason
+    );
+    match apply(previous_config.to_string()).await {
+        Ok(()) => Ok(SwitchOutcome::Reverted { reason }),
+        Err(revert_error) => Err(format!(
+            "新配置启动失败: {}; 恢复原配置也失败: {}",
+            reason, revert_error
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    // 模拟重启：记录尝试过的配置，failing 中的配置启动失败
+    async fn stub_reload(
+        attempts: &RefCell<Vec<String>>,
+        failing: &[&str],
+        config: String,
+    ) -> Result<(), String> {
+        attempts.borrow_mut().push(config.clone());
+        if failing.contains(&config.as_str()) {
+            Err(format!("{} 未就绪", config))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_switch_success() {
+        let attempts = RefCell::new(Vec::new());
+        let outcome = switch_config("old.yaml", "new.yaml", |config| {
+            stub_reload(&attempts, &[], config)
+        })
+        .await;
+        assert_eq!(outcome, Ok(SwitchOutcome::Switched));
+        assert_eq!(attempts.into_inner(), vec!["new.yaml"]);
+    }
+
+    #[tokio::test]
+    async fn test_switch_reverts_on_failure() {
+        let attempts = RefCell::new(Vec::new());
+        let outcome = switch_config("old.yaml", "new.yaml", |config| {
+            stub_reload(&attempts, &["new.yaml"], config)
+        })
+        .await;
+        assert_eq!(
+            outcome,
+            Ok(SwitchOutcome::Reverted {
+                reason: "new.yaml 未就绪".to_string()
+            })
+        );
+        assert_eq!(attempts.into_inner(), vec!["new.yaml", "old.yaml"]);
+
+        // 原配置也无法恢复
+        let attempts = RefCell::new(Vec::new());
+        let outcome = switch_config("old.yaml", "new.yaml", |config| {
+            stub_reload(&attempts, &["new.yaml", "old.yaml"], config)
+        })
+        .await;
+        let error = outcome.unwrap_err();
+        assert!(error.contains("new.yaml 未就绪"));
+        assert!(error.contains("old.yaml 未就绪"));
+    }
+}
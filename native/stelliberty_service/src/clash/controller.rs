@@ -0,0 +1,562 @@
+// Clash 核心控制器客户端
+//
+// 通过核心的 external-controller-pipe / external-controller-unix 发送 HTTP 请求
+
+use crate::ipc::protocol::CacheKind;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+// 控制器请求超时
+const CONTROLLER_TIMEOUT: Duration = Duration::from_secs(3);
+
+// 就绪探测的轮询间隔
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// 控制器响应体上限，防止异常响应占用过多内存
+const MAX_RESPONSE_SIZE: usize = 1024 * 1024;
+
+// 流式接口单帧（单行）上限
+const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+#[cfg(windows)]
+type ControllerConnection = tokio::net::windows::named_pipe::NamedPipeClient;
+#[cfg(not(windows))]
+type ControllerConnection = tokio::net::UnixStream;
+
+// 控制器 HTTP 响应
+#[derive(Debug, Clone)]
+pub struct ControllerResponse {
+    pub status_code: u16,
+    pub body: String,
+}
+
+impl ControllerResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+
+    // 核心未实现该接口
+    pub fn is_unsupported(&self) -> bool {
+        matches!(self.status_code, 404 | 405 | 501)
+    }
+}
+
+// 各缓存类型对应的控制器接口（名称, 路径）
+pub fn flush_endpoints(kind: CacheKind) -> &'static [(&'static str, &'static str)] {
+    const FAKEIP: (&str, &str) = ("Fake-IP", "/cache/fakeip/flush");
+    const DNS: (&str, &str) = ("DNS", "/cache/dns/flush");
+
+    match kind {
+        CacheKind::FakeIp => &[FAKEIP],
+        CacheKind::Dns => &[DNS],
+        CacheKind::All => &[FAKEIP, DNS],
+    }
+}
+
+// 从运行时配置中读取控制器 IPC 路径
+// 该字段由主程序在生成配置时写入顶层，无需完整解析 YAML
+pub fn read_controller_path(config_path: &str) -> Option<String> {
+    #[cfg(windows)]
+    const CONTROLLER_KEY: &str = "external-controller-pipe:";
+    #[cfg(not(windows))]
+    const CONTROLLER_KEY: &str = "external-controller-unix:";
+
+    let content = std::fs::read_to_string(config_path).ok()?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(CONTROLLER_KEY))
+        .map(unquote_yaml_scalar)
+        .filter(|path| !path.is_empty())
+}
+
+// 去除 YAML 标量的引号
+fn unquote_yaml_scalar(raw: &str) -> String {
+    let value = raw.trim();
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        inner.replace("''", "'")
+    } else if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        inner.replace("\\\\", "\\")
+    } else {
+        value.to_string()
+    }
+}
+
+// 向控制器发送 HTTP 请求
+pub async fn request(
+    controller_path: &str,
+    method: &str,
+    path: &str,
+) -> Result<ControllerResponse, String> {
+    tokio::time::timeout(
+        CONTROLLER_TIMEOUT,
+        request_inner(controller_path, method, path),
+    )
+    .await
+    .map_err(|_| format!("请求核心控制器超时: {} {}", method, path))?
+}
+
+// 连接核心控制器
+async fn connect(controller_path: &str) -> Result<ControllerConnection, String> {
+    #[cfg(windows)]
+    let stream = tokio::net::windows::named_pipe::ClientOptions::new()
+        .open(controller_path)
+        .map_err(|e| format!("连接核心控制器失败: {}", e))?;
+
+    #[cfg(not(windows))]
+    let stream = tokio::net::UnixStream::connect(controller_path)
+        .await
+        .map_err(|e| format!("连接核心控制器失败: {}", e))?;
+
+    Ok(stream)
+}
+
+async fn request_inner(
+    controller_path: &str,
+    method: &str,
+    path: &str,
+) -> Result<ControllerResponse, String> {
+    let mut stream = connect(controller_path).await?;
+
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        method, path
+    );
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("发送控制器请求失败: {}", e))?;
+
+    let mut raw = Vec::new();
+    (&mut stream)
+        .take(MAX_RESPONSE_SIZE as u64)
+        .read_to_end(&mut raw)
+        .await
+        .map_err(|e| format!("读取控制器响应失败: {}", e))?;
+
+    parse_response(&raw)
+}
+
+// 解析 HTTP 响应（仅关心状态码和响应体）
+// 响应体按字节解码 chunked 后再转为字符串：分块边界可能落在多字节字符中间（如中文节点名）
+fn parse_response(raw: &[u8]) -> Result<ControllerResponse, String> {
+    let (header, body) = match raw.windows(4).position(|window| window == b"\r\n\r\n") {
+        Some(header_end) => (&raw[..header_end], Some(&raw[header_end + 4..])),
+        None => (raw, None),
+    };
+    let header = String::from_utf8_lossy(header);
+    let status_code = parse_status_line(header.lines().next().ok_or("控制器响应为空")?)?;
+    let Some(body) = body else {
+        return Ok(ControllerResponse {
+            status_code,
+            body: String::new(),
+        });
+    };
+
+    // 较大的响应（如 /configs）可能以 chunked 编码返回
+    let body = if header.lines().any(is_chunked_header) {
+        decode_chunked(body)?
+    } else {
+        body.to_vec()
+    };
+    let body = String::from_utf8(body).map_err(|e| format!("控制器响应不是有效的 UTF-8: {}", e))?;
+
+    Ok(ControllerResponse { status_code, body })
+}
+
+// 解析状态行中的状态码
+fn parse_status_line(status_line: &str) -> Result<u16, String> {
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("无法解析控制器响应: {}", status_line))
+}
+
+// 是否为 Transfer-Encoding: chunked 响应头
+fn is_chunked_header(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(name, value)| {
+        name.trim().eq_ignore_ascii_case("transfer-encoding")
+            && value.trim().eq_ignore_ascii_case("chunked")
+    })
+}
+
+// 解码 chunked 响应体
+pub fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or("chunked 响应不完整")?;
+        let size_line = String::from_utf8_lossy(&body[..line_end]);
+        let size_text = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_text, 16)
+            .map_err(|_| format!("chunked 长度无效: {}", size_text))?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let chunk = body.get(..size).ok_or("chunked 响应不完整")?;
+        decoded.extend_from_slice(chunk);
+        body = body.get(size + 2..).unwrap_or_default();
+    }
+}
+
+// 订阅控制器的流式接口（如 /traffic、/logs），核心每推送一行 JSON 回调一次
+// 参数 on_line: 返回 false 表示停止接收；核心关闭连接时正常返回
+pub async fn stream_lines<F>(controller_path: &str, path: &str, on_line: F) -> Result<(), String>
+where
+    F: FnMut(String) -> bool,
+{
+    let mut stream = connect(controller_path).await?;
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("发送控制器请求失败: {}", e))?;
+
+    let mut reader = BufReader::new(stream);
+    let is_chunked = tokio::time::timeout(CONTROLLER_TIMEOUT, read_stream_header(&mut reader))
+        .await
+        .map_err(|_| format!("请求核心控制器超时: GET {}", path))??;
+    read_stream_body(&mut reader, is_chunked, on_line).await
+}
+
+// 读取流式响应头，返回响应体是否为 chunked 编码
+async fn read_stream_header<R>(reader: &mut R) -> Result<bool, String>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut status_code = None;
+    let mut is_chunked = false;
+    loop {
+        let mut line = String::new();
+        let read = (&mut *reader)
+            .take(MAX_FRAME_SIZE as u64)
+            .read_line(&mut line)
+            .await
+            .map_err(|e| format!("读取控制器响应失败: {}", e))?;
+        if read == 0 {
+            return Err("控制器响应不完整".to_string());
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        match status_code {
+            None => status_code = Some(parse_status_line(line)?),
+            Some(_) => is_chunked |= is_chunked_header(line),
+        }
+    }
+
+    match status_code {
+        Some(code) if (200..300).contains(&code) => Ok(is_chunked),
+        Some(code) => Err(format!("控制器返回 HTTP {}", code)),
+        None => Err("控制器响应为空".to_string()),
+    }
+}
+
+// 逐行读取流式响应体，跳过空行
+async fn read_stream_body<R, F>(
+    reader: &mut R,
+    is_chunked: bool,
+    mut on_line: F,
+) -> Result<(), String>
+where
+    R: AsyncBufRead + Unpin,
+    F: FnMut(String) -> bool,
+{
+    let read_error = |e: std::io::Error| format!("读取控制器响应失败: {}", e);
+    let mut pending = Vec::new();
+    loop {
+        if is_chunked {
+            let mut size_line = String::new();
+            if (&mut *reader)
+                .take(MAX_FRAME_SIZE as u64)
+                .read_line(&mut size_line)
+                .await
+                .map_err(read_error)?
+                == 0
+            {
+                return Ok(());
+            }
+            let size_text = size_line.split(';').next().unwrap_or_default().trim();
+            let size = usize::from_str_radix(size_text, 16)
+                .map_err(|_| format!("chunked 长度无效: {}", size_text))?;
+            if size == 0 {
+                return Ok(());
+            }
+            if pending.len() + size > MAX_FRAME_SIZE {
+                return Err("控制器推送的数据帧过大".to_string());
+            }
+
+            let start = pending.len();
+            pending.resize(start + size, 0);
+            reader
+                .read_exact(&mut pending[start..])
+                .await
+                .map_err(read_error)?;
+            let mut crlf = [0u8; 2];
+            reader.read_exact(&mut crlf).await.map_err(read_error)?;
+        } else if (&mut *reader)
+            .take(MAX_FRAME_SIZE as u64)
+            .read_until(b'\n', &mut pending)
+            .await
+            .map_err(read_error)?
+            == 0
+        {
+            return Ok(());
+        }
+
+        // 一个 chunk 可能包含多行，也可能只有半行
+        while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line).trim().to_string();
+            if !line.is_empty() && !on_line(line) {
+                return Ok(());
+            }
+        }
+        if pending.len() >= MAX_FRAME_SIZE {
+            return Err("控制器推送的数据帧过大".to_string());
+        }
+    }
+}
+
+// 清除核心缓存，返回结果描述
+// 全部接口均不受支持时返回 "核心不支持"
+pub async fn flush_cache(controller_path: &str, kind: CacheKind) -> Result<String, String> {
+    let mut flushed = Vec::new();
+    let mut unsupported = Vec::new();
+    let mut failures = Vec::new();
+
+    for (name, endpoint) in flush_endpoints(kind) {
+        match request(controller_path, "POST", endpoint).await {
+            Ok(response) if response.is_success() => flushed.push(*name),
+            Ok(response) if response.is_unsupported() => {
+                log::warn!(
+                    "核心不支持清除 {} 缓存 (HTTP {})",
+                    name,
+                    response.status_code
+                );
+                unsupported.push(*name);
+            }
+            Ok(response) => failures.push(format!("{}: HTTP {}", name, response.status_code)),
+            Err(e) => failures.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(format!("清除缓存失败: {}", failures.join("; ")));
+    }
+    if flushed.is_empty() {
+        return Err("核心不支持".to_string());
+    }
+
+    let mut message = format!("已清除 {} 缓存", flushed.join("/"));
+    if !unsupported.is_empty() {
+        message.push_str(&format!("（{} 缓存：核心不支持）", unsupported.join("/")));
+    }
+    Ok(message)
+}
+
+// 轮询控制器 GET /version，直到响应成功或超时，返回是否就绪
+pub async fn wait_until_ready(controller_path: &str, timeout: Duration) -> bool {
+    let start = Instant::now();
+    let mut attempts = 0u32;
+
+    loop {
+        attempts += 1;
+        match request(controller_path, "GET", "/version").await {
+            Ok(response) if response.is_success() => {
+                log::info!(
+                    "核心控制器已就绪（耗时 {}ms，探测 {} 次）",
+                    start.elapsed().as_millis(),
+                    attempts
+                );
+                return true;
+            }
+            Ok(response) => {
+                log::trace!("核心控制器尚未就绪: HTTP {}", response.status_code);
+            }
+            Err(e) => {
+                log::trace!("核心控制器尚未就绪: {}", e);
+            }
+        }
+
+        if start.elapsed() + READY_POLL_INTERVAL > timeout {
+            log::warn!(
+                "等待核心控制器就绪超时（{}ms，探测 {} 次）",
+                timeout.as_millis(),
+                attempts
+            );
+            return false;
+        }
+        tokio::time::sleep(READY_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flush_endpoints_per_kind() {
+        let paths = |kind| {
+            flush_endpoints(kind)
+                .iter()
+                .map(|(_, path)| *path)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(paths(CacheKind::FakeIp), vec!["/cache/fakeip/flush"]);
+        assert_eq!(paths(CacheKind::Dns), vec!["/cache/dns/flush"]);
+        assert_eq!(
+            paths(CacheKind::All),
+            vec!["/cache/fakeip/flush", "/cache/dns/flush"]
+        );
+    }
+
+    #[test]
+    fn test_cache_kind_wire_format() {
+        let kind: CacheKind = serde_json::from_str("\"fakeip\"").unwrap_or(CacheKind::All);
+        assert_eq!(kind, CacheKind::FakeIp);
+        let kind: CacheKind = serde_json::from_str("\"dns\"").unwrap_or(CacheKind::All);
+        assert_eq!(kind, CacheKind::Dns);
+    }
+
+    #[test]
+    fn test_parse_response_status() {
+        let response = parse_response(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        assert!(response.is_ok_and(|r| r.is_unsupported()));
+
+        let response = parse_response(b"HTTP/1.1 204 No Content\r\n\r\n");
+        assert!(response.is_ok_and(|r| r.is_success()));
+
+        let response = parse_response(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n{\"a\r\n4\r\n\":1}\r\n0\r\n\r\n",
+        );
+        assert!(response.is_ok_and(|r| r.body == r#"{"a":1}"#));
+
+        // 分块边界切开多字节字符
+        let body = "{\"name\":\"香港节点\"}".as_bytes();
+        let (first, second) = body.split_at(11);
+        let mut raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        for chunk in [first, second] {
+            raw.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+            raw.extend_from_slice(chunk);
+            raw.extend_from_slice(b"\r\n");
+        }
+        raw.extend_from_slice(b"0\r\n\r\n");
+        let response = parse_response(&raw);
+        assert!(response.is_ok_and(|r| r.body == "{\"name\":\"香港节点\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_read_stream_frames() {
+        async fn frames(raw: &[u8]) -> Result<Vec<String>, String> {
+            let mut reader = raw;
+            let is_chunked = read_stream_header(&mut reader).await?;
+            let mut frames = Vec::new();
+            read_stream_body(&mut reader, is_chunked, |frame| {
+                frames.push(frame);
+                true
+            })
+            .await?;
+            Ok(frames)
+        }
+
+        // chunk 边界与行边界不一致时按行拼接
+        let chunked = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+            17\r\n{\"up\":1,\"down\":2}\n{\"up\"\r\n\
+            d\r\n:3,\"down\":4}\n\r\n0\r\n\r\n";
+        assert_eq!(
+            frames(chunked).await,
+            Ok(vec![
+                r#"{"up":1,"down":2}"#.to_string(),
+                r#"{"up":3,"down":4}"#.to_string()
+            ])
+        );
+
+        let plain = b"HTTP/1.1 200 OK\r\n\r\n{\"type\":\"info\"}\n\n{\"type\":\"warning\"}\n";
+        assert_eq!(
+            frames(plain).await,
+            Ok(vec![
+                r#"{"type":"info"}"#.to_string(),
+                r#"{"type":"warning"}"#.to_string()
+            ])
+        );
+
+        assert!(frames(b"HTTP/1.1 401 Unauthorized\r\n\r\n").await.is_err());
+    }
+
+    #[test]
+    fn test_unquote_yaml_scalar() {
+        assert_eq!(
+            unquote_yaml_scalar(" /tmp/stelliberty.sock"),
+            "/tmp/stelliberty.sock"
+        );
+        assert_eq!(
+            unquote_yaml_scalar(r"'\\.\pipe\stelliberty'"),
+            r"\\.\pipe\stelliberty"
+        );
+        assert_eq!(
+            unquote_yaml_scalar(r#""\\\\.\\pipe\\stelliberty""#),
+            r"\\.\pipe\stelliberty"
+        );
+    }
+
+    // 测试用控制器 socket 路径
+    #[cfg(unix)]
+    fn stub_socket_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "stelliberty_test_{}_{}.sock",
+                name,
+                std::process::id()
+            ))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_wait_until_ready_after_delay() {
+        use tokio::net::UnixListener;
+
+        let path = stub_socket_path("ready");
+        let _ = std::fs::remove_file(&path);
+
+        // 模拟 300ms 后才开始监听控制器的核心
+        let stub_path = path.clone();
+        let stub = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let Ok(listener) = UnixListener::bind(&stub_path) else {
+                return;
+            };
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+                    .await;
+            }
+        });
+
+        assert!(wait_until_ready(&path, Duration::from_secs(3)).await);
+
+        stub.abort();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_wait_until_ready_never_ready() {
+        let path = stub_socket_path("never");
+        let _ = std::fs::remove_file(&path);
+
+        let start = Instant::now();
+        assert!(!wait_until_ready(&path, Duration::from_millis(500)).await);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
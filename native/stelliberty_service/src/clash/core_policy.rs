@@ -0,0 +1,172 @@
+// 核心路径策略：服务以管理员权限运行，只允许启动位于应用核心目录下的 clash-core
+//
+// 允许的根目录在 install/repair 时由随应用打包的服务程序记录到私有目录，
+// 运行中的服务从自身所在目录读取，IPC 客户端无法修改。
+
+use super::orphan::is_core_executable;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// 策略文件名（与服务程序位于同一私有目录）
+pub const CORE_POLICY_FILE_NAME: &str = "core_policy.json";
+
+// 核心目录名（相对于 flutter_assets/assets）
+const CORE_DIR_NAME: &str = "clash-core";
+
+// 核心路径策略
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CorePolicy {
+    // 允许存放核心程序的根目录
+    pub allowed_roots: Vec<PathBuf>,
+}
+
+impl CorePolicy {
+    // 根据随应用打包的服务程序路径推导策略
+    // 服务程序：<assets>/service/stelliberty-service，核心：<assets>/clash-core/clash-core
+    pub fn for_bundled_service(service_exe: &Path) -> Self {
+        let allowed_roots = service_exe
+            .parent()
+            .and_then(Path::parent)
+            .map(|assets_dir| vec![assets_dir.join(CORE_DIR_NAME)])
+            .unwrap_or_default();
+        Self { allowed_roots }
+    }
+
+    // 从运行中服务程序所在目录读取策略
+    pub fn load() -> Result<Self, String> {
+        let service_dir = std::env::current_exe()
+            .map_err(|e| format!("无法获取服务程序路径: {}", e))?
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| "无法获取服务程序所在目录".to_string())?;
+        Self::load_from(&service_dir.join(CORE_POLICY_FILE_NAME))
+    }
+
+    // 从指定策略文件读取
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            format!(
+                "读取核心路径策略失败: {} ({})\n提示: 请重新安装或修复服务",
+                path.display(),
+                e
+            )
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("解析核心路径策略失败: {} ({})", path.display(), e))
+    }
+
+    // 写入策略文件
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("序列化核心路径策略失败: {}", e))?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("写入核心路径策略失败: {} ({})", path.display(), e))
+    }
+
+    // 校验核心路径，通过时返回解析符号链接后的真实路径（用于启动，避免校验后被替换）
+    pub fn check(&self, core_path: &Path) -> Result<PathBuf, String> {
+        let resolved = core_path
+            .canonicalize()
+            .map_err(|e| format!("无法解析核心路径: {} ({})", core_path.display(), e))?;
+
+        if !is_core_executable(&resolved.to_string_lossy()) {
+            return Err(format!(
+                "核心路径不符合策略: {}\n原因: 文件名必须为 clash-core",
+                resolved.display()
+            ));
+        }
+
+        let is_allowed = self
+            .allowed_roots
+            .iter()
+            .filter_map(|root| root.canonicalize().ok())
+            .any(|root| resolved.starts_with(root));
+
+        if !is_allowed {
+            return Err(format!(
+                "核心路径不符合策略: {}\n原因: 不在允许的目录中（{}）",
+                resolved.display(),
+                self.allowed_roots
+                    .iter()
+                    .map(|root| root.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 创建测试目录：<tmp>/<name>/assets/{service,clash-core} 与外部目录 outside
+    fn setup(name: &str) -> (PathBuf, CorePolicy) {
+        let base = std::env::temp_dir().join(format!(
+            "stelliberty-core-policy-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base);
+        for dir in ["assets/service", "assets/clash-core", "outside"] {
+            std::fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        std::fs::write(base.join("assets/clash-core/clash-core"), b"core").unwrap();
+        std::fs::write(base.join("outside/clash-core"), b"evil").unwrap();
+
+        let policy =
+            CorePolicy::for_bundled_service(&base.join("assets/service/stelliberty-service"));
+        (base, policy)
+    }
+
+    #[test]
+    fn test_allowed_core_path() {
+        let (base, policy) = setup("allowed");
+
+        let resolved = policy
+            .check(&base.join("assets/clash-core/clash-core"))
+            .unwrap();
+        assert!(resolved.ends_with("clash-core/clash-core"));
+
+        // 策略文件读写往返
+        let policy_file = base.join(CORE_POLICY_FILE_NAME);
+        policy.save_to(&policy_file).unwrap();
+        let loaded = CorePolicy::load_from(&policy_file).unwrap();
+        assert_eq!(loaded.allowed_roots, policy.allowed_roots);
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_core_path_outside_allowed_roots() {
+        let (base, policy) = setup("outside");
+
+        let err = policy.check(&base.join("outside/clash-core")).unwrap_err();
+        assert!(err.contains("不在允许的目录中"));
+
+        // 通过 .. 跳出根目录同样会被拒绝
+        let err = policy
+            .check(&base.join("assets/clash-core/../../outside/clash-core"))
+            .unwrap_err();
+        assert!(err.contains("不在允许的目录中"));
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_escape_is_rejected() {
+        let (base, policy) = setup("symlink");
+
+        let link = base.join("assets/clash-core/linked/clash-core");
+        std::fs::create_dir_all(link.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(base.join("outside/clash-core"), &link).unwrap();
+
+        let err = policy.check(&link).unwrap_err();
+        assert!(err.contains("不在允许的目录中"));
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,499 @@
+// DNS 可达性检测：向配置的 DoH/DoT 服务器发起一次真实查询，判断其在当前网络下是否可用
+//
+// 受限网络中 DoH/DoT 服务器常被屏蔽，此时核心能正常启动但所有域名解析都会卡住，
+// 主程序可在启动前通过该检测提示用户更换 nameserver。
+
+use crate::ipc::protocol::DnsServerReachability;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore, pki_types::ServerName};
+
+// 单个服务器的检测超时（连接、握手、查询合计）
+pub const DNS_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+// 探测查询的域名
+const PROBE_DOMAIN: &str = "www.example.com";
+
+// DNS 响应上限（DoT 长度前缀为 u16，DoH 额外包含 HTTP 头）
+const MAX_RESPONSE_SIZE: usize = 64 * 1024;
+
+const DOT_DEFAULT_PORT: u16 = 853;
+const DOH_DEFAULT_PORT: u16 = 443;
+
+// TLS 客户端配置（使用内置根证书，不依赖系统证书库）
+pub(super) static TLS_CONFIG: LazyLock<Result<Arc<ClientConfig>, String>> = LazyLock::new(|| {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map(|builder| Arc::new(builder.with_root_certificates(roots).with_no_client_auth()))
+        .map_err(|e| format!("初始化 TLS 配置失败: {}", e))
+});
+
+// 可检测的 nameserver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Nameserver {
+    // DNS over TLS：tls://host[:port]
+    Tls {
+        host: String,
+        port: u16,
+    },
+    // DNS over HTTPS：https://host[:port]/path
+    Https {
+        host: String,
+        port: u16,
+        path: String,
+    },
+}
+
+impl Nameserver {
+    // 解析 mihomo 格式的 nameserver，忽略 # 之后的附加参数（如 #PROXY&h3=true）
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let value = raw.trim();
+        let value = value.split_once('#').map_or(value, |(url, _)| url);
+
+        if let Some(rest) = value.strip_prefix("tls://") {
+            let authority = rest.split('/').next().unwrap_or_default();
+            let (host, port) = split_host_port(authority, DOT_DEFAULT_PORT)?;
+            return Ok(Self::Tls { host, port });
+        }
+
+        if let Some(rest) = value.strip_prefix("https://") {
+            let (authority, path) = match rest.find('/') {
+                Some(index) => (&rest[..index], &rest[index..]),
+                None => (rest, "/dns-query"),
+            };
+            let (host, port) = split_host_port(authority, DOH_DEFAULT_PORT)?;
+            return Ok(Self::Https {
+                host,
+                port,
+                path: path.to_string(),
+            });
+        }
+
+        Err(format!("仅支持检测 DoH/DoT 服务器: {}", raw))
+    }
+
+    fn host(&self) -> &str {
+        match self {
+            Self::Tls { host, .. } | Self::Https { host, .. } => host,
+        }
+    }
+
+    fn port(&self) -> u16 {
+        match self {
+            Self::Tls { port, .. } | Self::Https { port, .. } => *port,
+        }
+    }
+}
+
+// 拆分 host[:port]，支持 [IPv6]:port
+pub(super) fn split_host_port(authority: &str, default_port: u16) -> Result<(String, u16), String> {
+    let parse_port = |port: &str| {
+        port.parse::<u16>()
+            .ok()
+            .filter(|port| *port != 0)
+            .ok_or_else(|| format!("端口无效: {}", authority))
+    };
+
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or_else(|| format!("IPv6 地址缺少 ]: {}", authority))?;
+        match rest.strip_prefix(':') {
+            Some(port) => (host, parse_port(port)?),
+            None if rest.is_empty() => (host, default_port),
+            None => return Err(format!("地址格式无效: {}", authority)),
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, parse_port(port)?),
+            None => (authority, default_port),
+        }
+    };
+
+    if host.is_empty() {
+        return Err(format!("缺少服务器地址: {}", authority));
+    }
+    Ok((host.to_string(), port))
+}
+
+// 并发检测所有 nameserver，结果顺序与输入一致
+pub async fn check_nameservers(
+    nameservers: Vec<String>,
+    timeout: Duration,
+) -> Vec<DnsServerReachability> {
+    let handles: Vec<_> = nameservers
+        .into_iter()
+        .map(|nameserver| tokio::spawn(check_nameserver(nameserver, timeout)))
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        if let Ok(result) = handle.await {
+            results.push(result);
+        }
+    }
+    results
+}
+
+// 检测单个 nameserver，超时只影响该服务器
+pub async fn check_nameserver(nameserver: String, timeout: Duration) -> DnsServerReachability {
+    let start = Instant::now();
+    let outcome = match Nameserver::parse(&nameserver) {
+        Ok(server) => tokio::time::timeout(timeout, probe(&server))
+            .await
+            .unwrap_or_else(|_| Err(format!("查询超时（{}ms）", timeout.as_millis()))),
+        Err(e) => Err(e),
+    };
+
+    match outcome {
+        Ok(()) => DnsServerReachability {
+            nameserver,
+            is_reachable: true,
+            latency_ms: Some(start.elapsed().as_millis() as u64),
+            error: None,
+        },
+        Err(e) => {
+            log::debug!("DNS 服务器不可达: {} ({})", nameserver, e);
+            DnsServerReachability {
+                nameserver,
+                is_reachable: false,
+                latency_ms: None,
+                error: Some(e),
+            }
+        }
+    }
+}
+
+// 建立 TLS 连接并发送一次查询
+async fn probe(server: &Nameserver) -> Result<(), String> {
+    let tcp = TcpStream::connect((server.host(), server.port()))
+        .await
+        .map_err(|e| format!("连接失败: {}", e))?;
+
+    let config = TLS_CONFIG.as_ref().map_err(Clone::clone)?;
+    let server_name = ServerName::try_from(server.host().to_string())
+        .map_err(|e| format!("服务器名称无效: {} ({})", server.host(), e))?;
+    let mut stream = TlsConnector::from(config.clone())
+        .connect(server_name, tcp)
+        .await
+        .map_err(|e| format!("TLS 握手失败: {}", e))?;
+
+    match server {
+        Nameserver::Tls { .. } => {
+            let id = query_id();
+            let response = exchange_dot(&mut stream, &build_query(id, PROBE_DOMAIN)).await?;
+            check_response(&response, id)
+        }
+        Nameserver::Https { host, path, .. } => {
+            // RFC 8484 建议 DoH 查询 ID 为 0，便于 HTTP 缓存
+            let response =
+                exchange_doh(&mut stream, host, path, &build_query(0, PROBE_DOMAIN)).await?;
+            check_response(&response, 0)
+        }
+    }
+}
+
+// 生成查询 ID（无需密码学随机，只用于匹配响应）
+fn query_id() -> u16 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    (nanos ^ std::process::id()) as u16
+}
+
+// 构造 A 记录查询报文（RD=1）
+fn build_query(id: u16, domain: &str) -> Vec<u8> {
+    let mut query = Vec::with_capacity(32 + domain.len());
+    query.extend_from_slice(&id.to_be_bytes());
+    // flags: RD；QDCOUNT=1，其余计数为 0
+    query.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    for label in domain.split('.').filter(|label| !label.is_empty()) {
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    // 根标签、QTYPE=A、QCLASS=IN
+    query.extend_from_slice(&[0, 0x00, 0x01, 0x00, 0x01]);
+    query
+}
+
+// 校验响应报文：ID 匹配、为响应包，且 RCODE 为 NOERROR/NXDOMAIN
+fn check_response(response: &[u8], id: u16) -> Result<(), String> {
+    if response.len() < 12 {
+        return Err(format!("响应报文过短（{} 字节）", response.len()));
+    }
+    if u16::from_be_bytes([response[0], response[1]]) != id {
+        return Err("响应 ID 与查询不匹配".to_string());
+    }
+    if response[2] & 0x80 == 0 {
+        return Err("收到的不是 DNS 响应".to_string());
+    }
+    match response[3] & 0x0F {
+        0 | 3 => Ok(()),
+        rcode => Err(format!("服务器返回错误（rcode={}）", rcode)),
+    }
+}
+
+// DoT 查询：报文前加 2 字节长度（RFC 7858）
+async fn exchange_dot<S>(stream: &mut S, query: &[u8]) -> Result<Vec<u8>, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut request = Vec::with_capacity(query.len() + 2);
+    request.extend_from_slice(&(query.len() as u16).to_be_bytes());
+    request.extend_from_slice(query);
+    stream
+        .write_all(&request)
+        .await
+        .map_err(|e| format!("发送查询失败: {}", e))?;
+
+    let length = stream
+        .read_u16()
+        .await
+        .map_err(|e| format!("读取响应失败: {}", e))?;
+    let mut response = vec![0u8; length as usize];
+    stream
+        .read_exact(&mut response)
+        .await
+        .map_err(|e| format!("读取响应失败: {}", e))?;
+    Ok(response)
+}
+
+// DoH 查询：HTTP/1.1 POST application/dns-message（RFC 8484）
+async fn exchange_doh<S>(
+    stream: &mut S,
+    host: &str,
+    path: &str,
+    query: &[u8],
+) -> Result<Vec<u8>, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let header = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/dns-message\r\nAccept: application/dns-message\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        path,
+        host,
+        query.len()
+    );
+    let mut request = header.into_bytes();
+    request.extend_from_slice(query);
+    stream
+        .write_all(&request)
+        .await
+        .map_err(|e| format!("发送查询失败: {}", e))?;
+
+    // 部分服务器在 close_notify 前直接断开连接，只要已读到数据就继续解析
+    let mut raw = Vec::new();
+    let read_result = (&mut *stream)
+        .take(MAX_RESPONSE_SIZE as u64)
+        .read_to_end(&mut raw)
+        .await;
+    if let Err(e) = read_result
+        && raw.is_empty()
+    {
+        return Err(format!("读取响应失败: {}", e));
+    }
+
+    parse_http_body(&raw)
+}
+
+// 解析 HTTP 响应，状态码为 200 时返回响应体（DoH 为 DNS 报文）
+pub(super) fn parse_http_body(raw: &[u8]) -> Result<Vec<u8>, String> {
+    let header_end = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or("HTTP 响应不完整")?;
+    let header = String::from_utf8_lossy(&raw[..header_end]);
+    let body = &raw[header_end + 4..];
+
+    let status_line = header.lines().next().unwrap_or_default();
+    let status_code = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("无法解析 HTTP 响应: {}", status_line))?;
+    if status_code != 200 {
+        return Err(format!("HTTP 状态码 {}", status_code));
+    }
+
+    let is_chunked = header.lines().any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    if is_chunked {
+        super::controller::decode_chunked(body)
+    } else {
+        Ok(body.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    // 根据查询构造应答（回显 ID，置 QR 位）
+    fn answer(query: &[u8]) -> Vec<u8> {
+        let mut response = query.to_vec();
+        response[2] |= 0x80;
+        response
+    }
+
+    #[test]
+    fn test_parse_nameserver() {
+        assert_eq!(
+            Nameserver::parse("tls://dns.google"),
+            Ok(Nameserver::Tls {
+                host: "dns.google".to_string(),
+                port: 853
+            })
+        );
+        assert_eq!(
+            Nameserver::parse("https://[2606:4700::1111]:8443/dns-query#PROXY&h3=true"),
+            Ok(Nameserver::Https {
+                host: "2606:4700::1111".to_string(),
+                port: 8443,
+                path: "/dns-query".to_string()
+            })
+        );
+        assert_eq!(
+            Nameserver::parse("https://doh.pub"),
+            Ok(Nameserver::Https {
+                host: "doh.pub".to_string(),
+                port: 443,
+                path: "/dns-query".to_string()
+            })
+        );
+        assert!(Nameserver::parse("223.5.5.5").is_err());
+        assert!(Nameserver::parse("tls://dns.google:0").is_err());
+    }
+
+    #[test]
+    fn test_check_response() {
+        let query = build_query(0x1234, PROBE_DOMAIN);
+        assert!(check_response(&answer(&query), 0x1234).is_ok());
+        // 未置 QR 位 / ID 不匹配 / SERVFAIL
+        assert!(check_response(&query, 0x1234).is_err());
+        assert!(check_response(&answer(&query), 0x4321).is_err());
+        let mut servfail = answer(&query);
+        servfail[3] |= 0x02;
+        assert!(check_response(&servfail, 0x1234).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dot_exchange_with_mock_resolver() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let resolver = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let length = stream.read_u16().await.unwrap();
+            let mut query = vec![0u8; length as usize];
+            stream.read_exact(&mut query).await.unwrap();
+            let response = answer(&query);
+            stream
+                .write_all(&(response.len() as u16).to_be_bytes())
+                .await
+                .unwrap();
+            stream.write_all(&response).await.unwrap();
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let response = exchange_dot(&mut stream, &build_query(7, PROBE_DOMAIN))
+            .await
+            .unwrap();
+        assert!(check_response(&response, 7).is_ok());
+        resolver.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_doh_exchange_with_mock_resolver() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let resolver = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut raw = vec![0u8; 4096];
+            let n = stream.read(&mut raw).await.unwrap();
+            let request = &raw[..n];
+            assert!(request.starts_with(b"POST /dns-query HTTP/1.1\r\n"));
+            let header_end = request.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            let response = answer(&request[header_end + 4..]);
+
+            // 以 chunked 编码分两段返回
+            let (first, second) = response.split_at(5);
+            let mut reply = b"HTTP/1.1 200 OK\r\nContent-Type: application/dns-message\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+            for chunk in [first, second] {
+                reply.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+                reply.extend_from_slice(chunk);
+                reply.extend_from_slice(b"\r\n");
+            }
+            reply.extend_from_slice(b"0\r\n\r\n");
+            stream.write_all(&reply).await.unwrap();
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let response = exchange_doh(
+            &mut stream,
+            "127.0.0.1",
+            "/dns-query",
+            &build_query(0, PROBE_DOMAIN),
+        )
+        .await
+        .unwrap();
+        assert!(check_response(&response, 0).is_ok());
+        resolver.await.unwrap();
+
+        assert_eq!(
+            parse_http_body(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n"),
+            Err("HTTP 状态码 403".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_servers_time_out_independently() {
+        // 接受连接但从不响应的服务器（模拟被屏蔽后丢包的情况）
+        let silent = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let silent_port = silent.local_addr().unwrap().port();
+        let silent_task = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = silent.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        // 已关闭的端口（连接被拒绝）
+        let closed_port = {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap().port()
+        };
+
+        let start = Instant::now();
+        let results = check_nameservers(
+            vec![
+                format!("tls://127.0.0.1:{}", silent_port),
+                format!("https://127.0.0.1:{}/dns-query", silent_port),
+                format!("tls://127.0.0.1:{}", closed_port),
+                "udp://8.8.8.8".to_string(),
+            ],
+            Duration::from_millis(300),
+        )
+        .await;
+        silent_task.abort();
+
+        // 各服务器并发检测，总耗时不应累加
+        assert!(start.elapsed() < Duration::from_millis(900));
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|result| !result.is_reachable));
+        assert!(results[0].error.as_deref().unwrap().contains("超时"));
+        assert!(results[1].error.as_deref().unwrap().contains("超时"));
+        assert!(results[2].error.as_deref().unwrap().contains("连接失败"));
+        assert!(results[3].error.as_deref().unwrap().contains("DoH/DoT"));
+    }
+}
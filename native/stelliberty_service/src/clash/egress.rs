@@ -0,0 +1,359 @@
+// 出口 IP 检测：经核心的本地代理端口请求 IP 回显服务，得到当前代理链路实际的出口地址
+//
+// http:// 地址直接以代理请求形式发送，https:// 地址先 CONNECT 建立隧道再进行 TLS 握手。
+
+use super::dns_check::{TLS_CONFIG, parse_http_body, split_host_port};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::pki_types::ServerName;
+
+// 整个检测的超时（经代理连接、握手、读取响应合计）
+pub const EGRESS_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 未指定地址时使用的回显服务（返回 ip、country、asn）
+pub const DEFAULT_ECHO_URL: &str = "https://api.ip.sb/geoip";
+
+// 回显响应上限
+const MAX_RESPONSE_SIZE: usize = 64 * 1024;
+
+// CONNECT 响应头上限
+const MAX_CONNECT_HEADER_SIZE: usize = 8 * 1024;
+
+// 各回显服务的字段名（ip.sb、ipinfo.io、ip-api.com、httpbin 等）
+const IP_KEYS: &[&str] = &["ip", "query", "origin", "address"];
+const COUNTRY_KEYS: &[&str] = &["country", "country_code", "countryCode", "country_name"];
+const ASN_KEYS: &[&str] = &["asn", "as", "org"];
+
+// 出口 IP 信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgressIp {
+    pub ip: String,
+    pub country: Option<String>,
+    pub asn: Option<String>,
+}
+
+// 回显服务地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EchoUrl {
+    is_https: bool,
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl EchoUrl {
+    fn parse(raw: &str) -> Result<Self, String> {
+        let value = raw.trim();
+        let (is_https, rest) = if let Some(rest) = value.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = value.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(format!("仅支持 http:// 或 https:// 地址: {}", raw));
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = split_host_port(authority, if is_https { 443 } else { 80 })?;
+        Ok(Self {
+            is_https,
+            host,
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    // 请求行与 Host 头使用的 host[:port]
+    fn authority(&self) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        format!("{}:{}", host, self.port)
+    }
+}
+
+// 经核心代理端口检测出口 IP，url 为空时使用默认回显服务
+pub async fn check_egress_ip(
+    proxy_port: u16,
+    url: &str,
+    timeout: Duration,
+) -> Result<EgressIp, String> {
+    let url = if url.trim().is_empty() {
+        DEFAULT_ECHO_URL
+    } else {
+        url
+    };
+    let echo_url = EchoUrl::parse(url)?;
+
+    let body = tokio::time::timeout(timeout, fetch(proxy_port, &echo_url))
+        .await
+        .map_err(|_| format!("检测出口 IP 超时（{}ms）: {}", timeout.as_millis(), url))??;
+    parse_echo_body(&body)
+}
+
+// 经代理请求回显服务，返回响应体
+async fn fetch(proxy_port: u16, url: &EchoUrl) -> Result<Vec<u8>, String> {
+    let mut tcp = TcpStream::connect((Ipv4Addr::LOCALHOST, proxy_port))
+        .await
+        .map_err(|e| format!("连接核心代理端口 {} 失败: {}", proxy_port, e))?;
+    let authority = url.authority();
+
+    if !url.is_https {
+        let target = format!("http://{}{}", authority, url.path);
+        return http_get(&mut tcp, &target, &authority).await;
+    }
+
+    open_tunnel(&mut tcp, &authority).await?;
+    let config = TLS_CONFIG.as_ref().map_err(Clone::clone)?;
+    let server_name = ServerName::try_from(url.host.clone())
+        .map_err(|e| format!("服务器名称无效: {} ({})", url.host, e))?;
+    let mut stream = TlsConnector::from(config.clone())
+        .connect(server_name, tcp)
+        .await
+        .map_err(|e| format!("TLS 握手失败: {}", e))?;
+    http_get(&mut stream, &url.path, &authority).await
+}
+
+// 通过 HTTP CONNECT 建立到目标的隧道
+async fn open_tunnel(stream: &mut TcpStream, authority: &str) -> Result<(), String> {
+    let request = format!(
+        "CONNECT {} HTTP/1.1\r\nHost: {}\r\n\r\n",
+        authority, authority
+    );
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("发送 CONNECT 请求失败: {}", e))?;
+
+    // 逐块读取到响应头结束，代理在隧道建立前不会发送其他数据
+    let mut header = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !header.windows(4).any(|window| window == b"\r\n\r\n") {
+        if header.len() > MAX_CONNECT_HEADER_SIZE {
+            return Err("CONNECT 响应头过大".to_string());
+        }
+        let n = stream
+            .read(&mut buf)
+            .await
+            .map_err(|e| format!("读取 CONNECT 响应失败: {}", e))?;
+        if n == 0 {
+            return Err("代理在建立隧道前关闭了连接".to_string());
+        }
+        header.extend_from_slice(&buf[..n]);
+    }
+
+    let status_line = String::from_utf8_lossy(&header);
+    let status_code = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok());
+    match status_code {
+        Some(200) => Ok(()),
+        Some(code) => Err(format!("代理拒绝建立隧道: HTTP {}", code)),
+        None => Err("无法解析 CONNECT 响应".to_string()),
+    }
+}
+
+// 发送 GET 请求并读取响应体
+async fn http_get<S>(stream: &mut S, target: &str, host: &str) -> Result<Vec<u8>, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: application/json\r\nUser-Agent: stelliberty-service\r\nConnection: close\r\n\r\n",
+        target, host
+    );
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("发送请求失败: {}", e))?;
+
+    let mut raw = Vec::new();
+    let read_result = (&mut *stream)
+        .take(MAX_RESPONSE_SIZE as u64)
+        .read_to_end(&mut raw)
+        .await;
+    if let Err(e) = read_result
+        && raw.is_empty()
+    {
+        return Err(format!("读取响应失败: {}", e));
+    }
+
+    parse_http_body(&raw)
+}
+
+// 解析回显服务的响应：JSON 对象或纯文本 IP
+fn parse_echo_body(body: &[u8]) -> Result<EgressIp, String> {
+    let text = String::from_utf8_lossy(body);
+    let text = text.trim();
+
+    let Ok(serde_json::Value::Object(fields)) = serde_json::from_str(text) else {
+        // 部分服务（如 icanhazip）直接返回纯文本 IP
+        return text
+            .parse::<IpAddr>()
+            .map(|ip| EgressIp {
+                ip: ip.to_string(),
+                country: None,
+                asn: None,
+            })
+            .map_err(|_| {
+                let preview: String = text.chars().take(64).collect();
+                format!("响应不是有效的 JSON: {}", preview)
+            });
+    };
+
+    let field = |keys: &[&str]| {
+        keys.iter().find_map(|key| match fields.get(*key)? {
+            serde_json::Value::String(value) if !value.trim().is_empty() => {
+                Some(value.trim().to_string())
+            }
+            serde_json::Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        })
+    };
+
+    // httpbin 的 origin 可能包含经过的多个地址，第一个为客户端地址
+    let ip = field(IP_KEYS).ok_or("响应中没有 IP 字段")?;
+    let ip = ip.split(',').next().unwrap_or_default().trim();
+    let ip = ip
+        .parse::<IpAddr>()
+        .map_err(|_| format!("响应中的 IP 无效: {}", ip))?;
+
+    let asn = field(ASN_KEYS).map(|asn| {
+        if asn.bytes().all(|b| b.is_ascii_digit()) {
+            format!("AS{}", asn)
+        } else {
+            asn
+        }
+    });
+
+    Ok(EgressIp {
+        ip: ip.to_string(),
+        country: field(COUNTRY_KEYS),
+        asn,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    // 启动模拟代理，校验收到的请求行并返回固定响应
+    async fn mock_proxy(expected_request_line: &'static str, reply: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut raw = vec![0u8; 4096];
+            let n = stream.read(&mut raw).await.unwrap();
+            let request = String::from_utf8_lossy(&raw[..n]).into_owned();
+            assert_eq!(request.lines().next(), Some(expected_request_line));
+            stream.write_all(reply.as_bytes()).await.unwrap();
+        });
+        port
+    }
+
+    #[test]
+    fn test_parse_echo_body() {
+        let info = parse_echo_body(
+            br#"{"ip":"203.0.113.7","country_code":"JP","country":"Japan","asn":2516}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            info,
+            EgressIp {
+                ip: "203.0.113.7".to_string(),
+                country: Some("Japan".to_string()),
+                asn: Some("AS2516".to_string()),
+            }
+        );
+
+        let info = parse_echo_body(
+            br#"{"status":"success","query":"2001:db8::1","countryCode":"DE","as":"AS3320 Deutsche Telekom AG"}"#,
+        )
+        .unwrap();
+        assert_eq!(info.ip, "2001:db8::1");
+        assert_eq!(info.country.as_deref(), Some("DE"));
+        assert_eq!(info.asn.as_deref(), Some("AS3320 Deutsche Telekom AG"));
+
+        let info = parse_echo_body(b"198.51.100.4\n").unwrap();
+        assert_eq!((info.ip.as_str(), info.country), ("198.51.100.4", None));
+
+        assert!(parse_echo_body(b"<html>blocked</html>").is_err());
+        assert!(parse_echo_body(br#"{"country":"JP"}"#).is_err());
+        assert!(parse_echo_body(br#"{"ip":"not-an-ip"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_echo_url() {
+        let url = EchoUrl::parse("https://api.ip.sb/geoip").unwrap();
+        assert!(url.is_https);
+        assert_eq!(
+            (url.authority(), url.path),
+            ("api.ip.sb:443".to_string(), "/geoip".to_string())
+        );
+
+        let url = EchoUrl::parse("http://[2001:db8::1]:8080").unwrap();
+        assert_eq!(
+            (url.authority(), url.path),
+            ("[2001:db8::1]:8080".to_string(), "/".to_string())
+        );
+
+        assert!(EchoUrl::parse("ftp://example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_egress_ip_through_mock_proxy() {
+        let port = mock_proxy(
+            "GET http://echo.test:80/json HTTP/1.1",
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 43\r\n\r\n{\"ip\":\"203.0.113.9\",\"country\":\"SG\",\"asn\":1}",
+        )
+        .await;
+        let info = check_egress_ip(port, "http://echo.test/json", Duration::from_secs(3))
+            .await
+            .unwrap();
+        assert_eq!(info.ip, "203.0.113.9");
+        assert_eq!(info.country.as_deref(), Some("SG"));
+
+        // 非 JSON 响应
+        let port = mock_proxy(
+            "GET http://echo.test:80/ HTTP/1.1",
+            "HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\nforbidden",
+        )
+        .await;
+        let result = check_egress_ip(port, "http://echo.test", Duration::from_secs(3)).await;
+        assert!(result.is_err_and(|e| e.contains("JSON")));
+
+        // 代理拒绝 CONNECT
+        let port = mock_proxy(
+            "CONNECT echo.test:443 HTTP/1.1",
+            "HTTP/1.1 502 Bad Gateway\r\n\r\n",
+        )
+        .await;
+        let result = check_egress_ip(port, "https://echo.test/ip", Duration::from_secs(3)).await;
+        assert!(result.is_err_and(|e| e.contains("502")));
+    }
+
+    #[tokio::test]
+    async fn test_check_egress_ip_times_out() {
+        // 接受连接但从不响应的代理
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let silent = tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let result = check_egress_ip(port, "http://echo.test", Duration::from_millis(300)).await;
+        assert!(result.is_err_and(|e| e.contains("超时")));
+        silent.abort();
+    }
+}
@@ -0,0 +1,199 @@
+// 核心退出监控：保留核心最近的输出（同时实时广播），并在核心意外退出时向订阅者广播事件
+
+use super::ClashManager;
+use super::safe_mode;
+use crate::ipc::protocol::ServiceEvent;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::process::ExitStatus;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::{RwLock, broadcast};
+
+// 核心输出缓冲区容量（行）
+const OUTPUT_BUFFER_CAPACITY: usize = 50;
+
+// 事件广播通道容量
+const EVENT_BROADCAST_CAPACITY: usize = 16;
+
+// 核心输出广播通道容量
+const OUTPUT_BROADCAST_CAPACITY: usize = 256;
+
+// 核心存活检查间隔
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// 全局事件广播通道
+static EVENT_BROADCASTER: LazyLock<broadcast::Sender<ServiceEvent>> = LazyLock::new(|| {
+    let (tx, _) = broadcast::channel(EVENT_BROADCAST_CAPACITY);
+    tx
+});
+
+// 订阅服务事件
+pub fn subscribe_events() -> broadcast::Receiver<ServiceEvent> {
+    EVENT_BROADCASTER.subscribe()
+}
+
+// 广播服务事件（没有订阅者时直接丢弃）
+pub fn publish_event(event: ServiceEvent) {
+    let _ = EVENT_BROADCASTER.send(event);
+}
+
+// 核心输出实时广播通道
+static OUTPUT_BROADCASTER: LazyLock<broadcast::Sender<String>> = LazyLock::new(|| {
+    let (tx, _) = broadcast::channel(OUTPUT_BROADCAST_CAPACITY);
+    tx
+});
+
+// 订阅核心输出（仅包含订阅之后的新行）
+pub fn subscribe_core_output() -> broadcast::Receiver<String> {
+    OUTPUT_BROADCASTER.subscribe()
+}
+
+// 核心输出环形缓冲区（stdout/stderr 共用，只保留最近的若干行）
+#[derive(Debug, Clone, Default)]
+pub struct OutputBuffer {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl OutputBuffer {
+    // 追加一行，超出容量时丢弃最旧的行，并广播给实时订阅者
+    pub fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() >= OUTPUT_BUFFER_CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(line.clone());
+        let _ = OUTPUT_BROADCASTER.send(line);
+    }
+
+    // 获取当前缓冲的全部行
+    pub fn snapshot(&self) -> Vec<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines.iter().cloned().collect()
+    }
+
+    // 清空缓冲区（每次启动核心前调用）
+    pub fn clear(&self) {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    // 在后台线程中持续读取输出管道，必须读空管道以免核心写满缓冲区后阻塞
+    pub fn capture<R: Read + Send + 'static>(&self, reader: R) {
+        let buffer = self.clone();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        let text = String::from_utf8_lossy(&line);
+                        let text = text.trim_end();
+                        if !text.is_empty() {
+                            buffer.push(text.to_string());
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+// 根据退出状态构造 CoreExited 事件
+pub fn core_exited_event(status: ExitStatus, last_output_lines: Vec<String>) -> ServiceEvent {
+    #[cfg(unix)]
+    let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+    #[cfg(not(unix))]
+    let signal = None;
+
+    ServiceEvent::CoreExited {
+        code: status.code(),
+        signal,
+        last_output_lines,
+    }
+}
+
+// 启动退出监控任务：定期调用 is_running，由其在检测到意外退出时广播事件；
+// 连续崩溃达到阈值时以安全模式重启核心
+pub fn spawn_exit_watcher(clash_manager: Arc<RwLock<ClashManager>>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(EXIT_CHECK_INTERVAL).await;
+            let is_safe_mode_requested = {
+                let manager = clash_manager.read().await;
+                manager.is_running();
+                manager.take_safe_mode_request()
+            };
+            if is_safe_mode_requested {
+                enter_safe_mode(&clash_manager).await;
+            }
+        }
+    })
+}
+
+// 以安全模式重启核心并通知主程序
+async fn enter_safe_mode(clash_manager: &RwLock<ClashManager>) {
+    let result = clash_manager.write().await.restart_in_safe_mode();
+    match result {
+        Ok(config_path) => {
+            log::warn!("已关闭 TUN 并以安全模式重启核心: {}", config_path);
+            publish_event(ServiceEvent::SafeModeActivated {
+                config_path,
+                crash_count: safe_mode::CRASH_THRESHOLD as u32,
+            });
+        }
+        Err(e) => log::error!("以安全模式重启核心失败: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_buffer_keeps_last_lines() {
+        let buffer = OutputBuffer::default();
+        for i in 0..OUTPUT_BUFFER_CAPACITY + 5 {
+            buffer.push(format!("line {}", i));
+        }
+
+        let lines = buffer.snapshot();
+        assert_eq!(lines.len(), OUTPUT_BUFFER_CAPACITY);
+        assert_eq!(lines.first().map(String::as_str), Some("line 5"));
+
+        buffer.clear();
+        assert!(buffer.snapshot().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_core_exited_event_from_exit_status() {
+        use std::os::unix::process::ExitStatusExt;
+
+        // 正常退出：退出码 1（wait 状态高 8 位）
+        let event = core_exited_event(
+            ExitStatus::from_raw(1 << 8),
+            vec!["fatal: parse config error".to_string()],
+        );
+        assert_eq!(
+            event,
+            ServiceEvent::CoreExited {
+                code: Some(1),
+                signal: None,
+                last_output_lines: vec!["fatal: parse config error".to_string()],
+            }
+        );
+
+        // 被信号终止：SIGKILL
+        let event = core_exited_event(ExitStatus::from_raw(9), Vec::new());
+        assert_eq!(
+            event,
+            ServiceEvent::CoreExited {
+                code: None,
+                signal: Some(9),
+                last_output_lines: Vec::new(),
+            }
+        );
+    }
+}
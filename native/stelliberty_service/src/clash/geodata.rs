@@ -0,0 +1,272 @@
+// GeoIP/GeoSite 数据文件状态：列出数据目录中各地理数据文件的大小、修改时间，
+// 并尽量读取 MMDB 元数据中的构建时间，供主程序提示数据是否过旧。
+//
+// 核心按文件名不区分大小写查找数据文件，这里保持一致。
+
+use crate::ipc::protocol::GeoDataFile;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 核心使用的地理数据文件（按展示顺序）
+pub const GEO_DATA_FILES: &[&str] = &[
+    "geoip.metadb",
+    "GeoIP.dat",
+    "GeoSite.dat",
+    "Country.mmdb",
+    "ASN.mmdb",
+];
+
+// MMDB 元数据位于文件末尾，以该标记开头，且不超过 128 KiB
+const MMDB_METADATA_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+const MMDB_METADATA_MAX_LEN: u64 = 128 * 1024;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+// 汇总数据目录中各地理数据文件的状态，缺失的文件 is_present 为 false
+pub fn collect_geo_info(data_dir: &Path, now: SystemTime) -> Vec<GeoDataFile> {
+    let entries: Vec<String> = std::fs::read_dir(data_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    GEO_DATA_FILES
+        .iter()
+        .map(|name| {
+            match entries
+                .iter()
+                .find(|entry| entry.eq_ignore_ascii_case(name))
+            {
+                Some(actual) => file_info(&data_dir.join(actual), actual, now),
+                None => missing(name),
+            }
+        })
+        .collect()
+}
+
+fn missing(name: &str) -> GeoDataFile {
+    GeoDataFile {
+        name: name.to_string(),
+        is_present: false,
+        size: 0,
+        modified_secs: None,
+        build_epoch: None,
+        database_type: None,
+        age_days: None,
+    }
+}
+
+fn file_info(path: &Path, name: &str, now: SystemTime) -> GeoDataFile {
+    let Some(metadata) = std::fs::metadata(path)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+    else {
+        return missing(name);
+    };
+    let modified_secs = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs());
+
+    let is_mmdb = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mmdb") || ext.eq_ignore_ascii_case("metadb"));
+    let (build_epoch, database_type) = if is_mmdb {
+        read_tail(path, MMDB_METADATA_MAX_LEN)
+            .map(|tail| parse_mmdb_metadata(&tail))
+            .unwrap_or_default()
+    } else {
+        (None, None)
+    };
+
+    // 优先按数据库构建时间计算，.dat 文件没有版本信息，按修改时间计算
+    let now_secs = now
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let age_days = build_epoch
+        .or(modified_secs)
+        .map(|since| now_secs.saturating_sub(since) / SECS_PER_DAY);
+
+    GeoDataFile {
+        name: name.to_string(),
+        is_present: true,
+        size: metadata.len(),
+        modified_secs,
+        build_epoch,
+        database_type,
+        age_days,
+    }
+}
+
+// 读取文件末尾最多 max_len 字节
+fn read_tail(path: &Path, max_len: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(max_len)))?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+// 从 MMDB 文件末尾解析 build_epoch 与 database_type，无法解析的字段为 None
+pub fn parse_mmdb_metadata(tail: &[u8]) -> (Option<u64>, Option<String>) {
+    let Some(start) = tail
+        .windows(MMDB_METADATA_MARKER.len())
+        .rposition(|window| window == MMDB_METADATA_MARKER)
+    else {
+        return (None, None);
+    };
+    let metadata = &tail[start + MMDB_METADATA_MARKER.len()..];
+
+    (
+        value_after_key(metadata, "build_epoch").and_then(decode_uint),
+        value_after_key(metadata, "database_type").and_then(decode_string),
+    )
+}
+
+// 定位元数据映射中键对应的值（键为短字符串：类型 2，长度直接编码在控制字节中）
+fn value_after_key<'a>(metadata: &'a [u8], key: &str) -> Option<&'a [u8]> {
+    let mut encoded = vec![0x40 | key.len() as u8];
+    encoded.extend_from_slice(key.as_bytes());
+    let position = metadata
+        .windows(encoded.len())
+        .position(|window| window == encoded)?;
+    Some(&metadata[position + encoded.len()..])
+}
+
+// 控制字节：高 3 位为类型（0 表示扩展类型，下一字节 + 7），低 5 位为长度
+fn decode_field(data: &[u8]) -> Option<(u8, &[u8])> {
+    let control = *data.first()?;
+    let size = usize::from(control & 0x1f);
+    let (field_type, payload) = match control >> 5 {
+        0 => (data.get(1)?.checked_add(7)?, data.get(2..)?),
+        field_type => (field_type, &data[1..]),
+    };
+    // 长度 29 以上使用额外字节编码，元数据中的这两个字段不会出现
+    if size >= 29 {
+        return None;
+    }
+    Some((field_type, payload.get(..size)?))
+}
+
+// uint16 / uint32 / uint64（类型 5、6、9），大端序
+fn decode_uint(data: &[u8]) -> Option<u64> {
+    match decode_field(data)? {
+        (5 | 6 | 9, bytes) if bytes.len() <= 8 => Some(
+            bytes
+                .iter()
+                .fold(0u64, |value, byte| (value << 8) | u64::from(*byte)),
+        ),
+        _ => None,
+    }
+}
+
+fn decode_string(data: &[u8]) -> Option<String> {
+    match decode_field(data)? {
+        (2, bytes) => std::str::from_utf8(bytes).ok().map(str::to_string),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // 构造只含元数据的 MMDB 文件尾部
+    fn mmdb_tail(database_type: &str, build_epoch: u32) -> Vec<u8> {
+        let mut data = b"\x00\x00\x00 search tree".to_vec();
+        data.extend_from_slice(MMDB_METADATA_MARKER);
+        data.push(0xe0 | 2);
+        data.push(0x40 | 13);
+        data.extend_from_slice(b"database_type");
+        data.push(0x40 | database_type.len() as u8);
+        data.extend_from_slice(database_type.as_bytes());
+        data.push(0x40 | 11);
+        data.extend_from_slice(b"build_epoch");
+        // uint64（扩展类型 9 = 7 + 2），4 字节
+        data.extend_from_slice(&[0x04, 0x02]);
+        data.extend_from_slice(&build_epoch.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn test_parse_mmdb_metadata() {
+        assert_eq!(
+            parse_mmdb_metadata(&mmdb_tail("GeoLite2-Country", 1_700_000_000)),
+            (Some(1_700_000_000), Some("GeoLite2-Country".to_string()))
+        );
+        assert_eq!(parse_mmdb_metadata(b"not a database"), (None, None));
+        // 标记后内容被截断
+        assert_eq!(parse_mmdb_metadata(MMDB_METADATA_MARKER), (None, None));
+    }
+
+    #[test]
+    fn test_collect_geo_info() {
+        let dir = std::env::temp_dir().join(format!("stelliberty-geodata-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let now = UNIX_EPOCH + Duration::from_secs(1_800_000_000);
+        let set_mtime = |path: &Path, days_ago: u64| {
+            let file = std::fs::File::options().write(true).open(path).unwrap();
+            file.set_modified(now - Duration::from_secs(days_ago * SECS_PER_DAY))
+                .unwrap();
+        };
+
+        // 文件名大小写与核心默认名称不同
+        let geoip = dir.join("geoip.dat");
+        std::fs::write(&geoip, vec![0u8; 1024]).unwrap();
+        set_mtime(&geoip, 30);
+
+        // MMDB 按构建时间计算天数（构建于 10 天前，修改时间为 2 天前）
+        let country = dir.join("Country.mmdb");
+        let build_epoch = 1_800_000_000 - 10 * SECS_PER_DAY as u32;
+        std::fs::write(&country, mmdb_tail("GeoLite2-Country", build_epoch)).unwrap();
+        set_mtime(&country, 2);
+
+        let files = collect_geo_info(&dir, now);
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "geoip.metadb",
+                "geoip.dat",
+                "GeoSite.dat",
+                "Country.mmdb",
+                "ASN.mmdb"
+            ]
+        );
+
+        assert!(!files[0].is_present);
+        assert_eq!(files[0].age_days, None);
+
+        assert!(files[1].is_present);
+        assert_eq!(files[1].size, 1024);
+        assert_eq!(
+            files[1].modified_secs,
+            Some(1_800_000_000 - 30 * SECS_PER_DAY)
+        );
+        assert_eq!(files[1].build_epoch, None);
+        assert_eq!(files[1].age_days, Some(30));
+
+        assert!(!files[2].is_present);
+
+        assert_eq!(files[3].build_epoch, Some(u64::from(build_epoch)));
+        assert_eq!(files[3].database_type.as_deref(), Some("GeoLite2-Country"));
+        assert_eq!(files[3].age_days, Some(10));
+
+        // 数据目录不存在时全部标记为缺失
+        let missing = collect_geo_info(&dir.join("missing"), now);
+        assert_eq!(missing.len(), GEO_DATA_FILES.len());
+        assert!(missing.iter().all(|file| !file.is_present));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
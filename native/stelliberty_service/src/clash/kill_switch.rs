@@ -0,0 +1,822 @@
+// 防泄漏开关（Kill Switch）：核心停止或意外退出后安装系统防火墙规则，只允许连接代理服务器，
+// 核心重新启动时移除规则，避免代理中断期间流量绕过代理直连。
+//
+// Linux 使用 nftables，macOS 使用 pf 锚点，Windows 使用 netsh advfirewall（底层为 WFP）。
+// 规则始终放行回环地址与服务进程本身，主程序随时可以通过 IPC 关闭开关恢复网络；
+// 服务退出前也会移除规则。安装规则前会把恢复所需的信息（Windows 原有防火墙策略、
+// pf 引用令牌）写入磁盘，服务崩溃或被强制结束后，下次启动时据此还原，
+// 不会在服务不在时把用户锁在网络之外。
+
+use serde::{Deserialize, Serialize};
+use serde_yaml_ng::Value as YamlValue;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+// nftables 表名
+#[cfg(any(target_os = "linux", test))]
+const NFT_TABLE: &str = "stelliberty_killswitch";
+
+// pf 锚点（系统默认 pf.conf 会加载 com.apple/* 下的锚点）
+#[cfg(target_os = "macos")]
+const PF_ANCHOR: &str = "com.apple/stelliberty.killswitch";
+
+// Windows 防火墙规则名（同名规则可一次删除）
+#[cfg(any(windows, test))]
+const NETSH_RULE_NAME: &str = "Stelliberty Kill Switch";
+
+// Windows 防火墙配置文件（策略按配置文件分别保存与恢复）
+#[cfg(windows)]
+const FIREWALL_PROFILES: &[&str] = &["domainprofile", "privateprofile", "publicprofile"];
+
+// 恢复记录文件名（与服务程序位于同一目录）
+const RECORD_FILE_NAME: &str = "kill_switch.json";
+
+// 规则内容：允许直连的代理服务器与需要放行的服务进程
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KillSwitchSpec {
+    pub servers: Vec<SocketAddr>,
+    // 服务程序路径（Windows 按程序放行）
+    pub service_exe: Option<PathBuf>,
+    // 服务进程所在的 cgroup v2 路径（Linux 按 cgroup 放行）
+    pub service_cgroup: Option<String>,
+}
+
+// 开关状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KillSwitchStatus {
+    pub is_enabled: bool,
+    // 拦截规则当前是否已安装
+    pub is_engaged: bool,
+}
+
+// 移除规则时需要还原的系统状态，规则生效期间同时保存在磁盘上
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct EngagedRecord {
+    // 安装规则前各配置文件的防火墙策略（Windows），如 ("publicprofile", "blockinbound,allowoutbound")
+    #[serde(default)]
+    firewall_policies: Vec<(String, String)>,
+    // pfctl -E 返回的引用令牌（macOS），释放时归还
+    #[serde(default)]
+    pf_token: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct KillSwitchState {
+    is_enabled: bool,
+    is_engaged: bool,
+    // 最近一次启动核心时解析出的代理服务器地址
+    servers: Vec<SocketAddr>,
+    record: EngagedRecord,
+}
+
+static KILL_SWITCH: LazyLock<Mutex<KillSwitchState>> = LazyLock::new(Default::default);
+
+fn lock_state() -> MutexGuard<'static, KillSwitchState> {
+    KILL_SWITCH.lock().unwrap_or_else(|e| {
+        log::warn!("KillSwitch 锁中毒，正在恢复");
+        e.into_inner()
+    })
+}
+
+fn status_of(state: &KillSwitchState) -> KillSwitchStatus {
+    KillSwitchStatus {
+        is_enabled: state.is_enabled,
+        is_engaged: state.is_engaged,
+    }
+}
+
+// 启用或关闭开关，关闭时立即移除已安装的规则
+pub fn set_enabled(is_enabled: bool) -> Result<KillSwitchStatus, String> {
+    let mut state = lock_state();
+    state.is_enabled = is_enabled;
+    if !is_enabled && state.is_engaged {
+        release(&mut state)?;
+        log::info!("防泄漏开关已关闭，拦截规则已移除");
+    }
+    Ok(status_of(&state))
+}
+
+// 当前开关状态
+pub fn status() -> KillSwitchStatus {
+    status_of(&lock_state())
+}
+
+// 核心即将启动：移除拦截规则，并记录新配置中的代理服务器
+pub fn on_core_starting(config_path: &str) {
+    let is_enabled = {
+        let mut state = lock_state();
+        if state.is_engaged {
+            match release(&mut state) {
+                Ok(()) => log::info!("核心启动，防泄漏规则已移除"),
+                Err(e) => log::warn!("移除防泄漏规则失败: {}", e),
+            }
+        }
+        state.is_enabled
+    };
+    if !is_enabled {
+        return;
+    }
+
+    // 规则移除后再解析域名（否则 DNS 查询会被拦截）；解析可能较慢，期间不持有锁
+    let servers = proxy_servers(config_path);
+    log::info!("防泄漏开关已记录 {} 个代理服务器地址", servers.len());
+    lock_state().servers = servers;
+}
+
+// 核心已停止或意外退出：开关启用时安装拦截规则
+pub fn on_core_stopped() {
+    let mut state = lock_state();
+    if !state.is_enabled || state.is_engaged {
+        return;
+    }
+
+    let spec = KillSwitchSpec {
+        servers: state.servers.clone(),
+        service_exe: std::env::current_exe().ok(),
+        service_cgroup: current_cgroup(),
+    };
+    match engage(&mut state, &spec) {
+        Ok(()) => log::warn!(
+            "核心已停止，防泄漏规则已生效（放行 {} 个代理服务器）",
+            spec.servers.len()
+        ),
+        Err(e) => log::error!("安装防泄漏规则失败: {}", e),
+    }
+}
+
+// 服务退出前移除规则
+pub fn release_on_exit() {
+    let mut state = lock_state();
+    if state.is_engaged {
+        match release(&mut state) {
+            Ok(()) => log::info!("服务退出，防泄漏规则已移除"),
+            Err(e) => log::error!("服务退出时移除防泄漏规则失败: {}", e),
+        }
+    }
+}
+
+// 服务启动时清理上次运行残留的规则（服务崩溃或被强制结束时未能移除），
+// 按恢复记录还原防火墙策略；没有残留时移除操作不产生影响
+pub fn release_stale() {
+    let record = record_path().and_then(|path| load_record_from(&path));
+    let has_record = record.is_some();
+
+    let mut state = lock_state();
+    state.record = record.unwrap_or_default();
+    state.is_engaged = has_record;
+    match release(&mut state) {
+        Ok(()) if has_record => log::warn!("已移除上次运行残留的防泄漏规则"),
+        Ok(()) => {}
+        Err(e) if has_record => log::error!("移除上次运行残留的防泄漏规则失败: {}", e),
+        Err(e) => log::debug!("启动时清理防泄漏规则: {}", e),
+    }
+}
+
+fn record_path() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()?
+        .parent()
+        .map(|dir| dir.join(RECORD_FILE_NAME))
+}
+
+fn load_record_from(path: &Path) -> Option<EngagedRecord> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|e| log::warn!("解析防泄漏恢复记录失败: {} ({})", path.display(), e))
+        .ok()
+}
+
+fn save_record_to(path: &Path, record: &EngagedRecord) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(record)
+        .map_err(|e| format!("序列化防泄漏恢复记录失败: {}", e))?;
+    std::fs::write(path, content)
+        .map_err(|e| format!("写入防泄漏恢复记录失败: {} ({})", path.display(), e))
+}
+
+// 保存恢复记录；无法保存时不安装规则，避免崩溃后无从还原
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn persist_record(state: &mut KillSwitchState, record: EngagedRecord) -> Result<(), String> {
+    let path = record_path().ok_or("无法获取服务程序所在目录")?;
+    save_record_to(&path, &record)?;
+    state.record = record;
+    Ok(())
+}
+
+// 规则已移除：清空恢复记录
+fn clear_record(state: &mut KillSwitchState) {
+    state.record = EngagedRecord::default();
+    if let Some(path) = record_path()
+        && let Err(e) = std::fs::remove_file(&path)
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        log::warn!("删除防泄漏恢复记录失败: {} ({})", path.display(), e);
+    }
+}
+
+// 从核心配置中收集代理服务器地址（域名在此解析，proxy-providers 中的节点不包含在内）
+pub fn proxy_servers(config_path: &str) -> Vec<SocketAddr> {
+    let config: YamlValue = match std::fs::read_to_string(config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_yaml_ng::from_str(&content).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("读取代理服务器列表失败: {} ({})", config_path, e);
+            return Vec::new();
+        }
+    };
+
+    let mut servers: Vec<SocketAddr> = config
+        .get("proxies")
+        .and_then(YamlValue::as_sequence)
+        .map(|proxies| proxies.iter().flat_map(resolve_proxy).collect())
+        .unwrap_or_default();
+    servers.sort_unstable();
+    servers.dedup();
+    servers
+}
+
+// 解析单个节点的服务器地址
+fn resolve_proxy(proxy: &YamlValue) -> Vec<SocketAddr> {
+    let Some(server) = proxy.get("server").and_then(YamlValue::as_str) else {
+        return Vec::new();
+    };
+    let Some(port) = proxy
+        .get("port")
+        .and_then(YamlValue::as_u64)
+        .and_then(|port| u16::try_from(port).ok())
+    else {
+        return Vec::new();
+    };
+
+    if let Ok(ip) = server.trim_matches(['[', ']']).parse::<IpAddr>() {
+        return vec![SocketAddr::new(ip, port)];
+    }
+    match (server, port).to_socket_addrs() {
+        Ok(addrs) => addrs.collect(),
+        Err(e) => {
+            log::warn!("解析代理服务器地址失败: {} ({})", server, e);
+            Vec::new()
+        }
+    }
+}
+
+// 当前进程的 cgroup v2 路径（根 cgroup 无法区分进程，返回 None）
+fn current_cgroup() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/self/cgroup")
+            .ok()
+            .and_then(|content| parse_cgroup(&content))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_cgroup(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.trim().trim_start_matches('/').to_string())
+        .filter(|path| !path.is_empty())
+}
+
+// 生成 nftables 规则集（先声明再删除同名表，重复安装时整体替换）
+#[cfg(any(target_os = "linux", test))]
+pub fn nft_ruleset(spec: &KillSwitchSpec) -> String {
+    let mut rules = vec![
+        "oifname \"lo\" accept".to_string(),
+        "ip daddr 127.0.0.0/8 accept".to_string(),
+        "ip6 daddr ::1 accept".to_string(),
+    ];
+    if let Some(cgroup) = &spec.service_cgroup {
+        let level = cgroup.split('/').count();
+        rules.push(format!(
+            "socket cgroupv2 level {} \"{}\" accept",
+            level, cgroup
+        ));
+    }
+    for server in &spec.servers {
+        let family = if server.is_ipv4() { "ip" } else { "ip6" };
+        rules.push(format!(
+            "{} daddr {} meta l4proto {{ tcp, udp }} th dport {} accept",
+            family,
+            server.ip(),
+            server.port()
+        ));
+    }
+
+    let mut ruleset = format!(
+        "table inet {table}\ndelete table inet {table}\ntable inet {table} {{\n    chain output {{\n        type filter hook output priority 0; policy drop;\n",
+        table = NFT_TABLE
+    );
+    for rule in rules {
+        ruleset.push_str("        ");
+        ruleset.push_str(&rule);
+        ruleset.push('\n');
+    }
+    ruleset.push_str("    }\n}\n");
+    ruleset
+}
+
+// 生成 pf 锚点规则（quick 规则按顺序匹配，放行规则在前）
+#[cfg(any(target_os = "macos", test))]
+pub fn pf_rules(spec: &KillSwitchSpec) -> String {
+    let mut rules = vec!["pass out quick on lo0 all".to_string()];
+    for server in &spec.servers {
+        rules.push(format!(
+            "pass out quick proto {{ tcp udp }} to {} port {}",
+            server.ip(),
+            server.port()
+        ));
+    }
+    // pf 只能按用户匹配，服务以 root 运行：只放行 root 的 DNS 查询（DNS 可用性检测），
+    // 其他以 root 运行的进程不能借此绕过拦截
+    rules.push("pass out quick proto { tcp udp } to any port { 53 853 } user root".to_string());
+    rules.push("block drop out quick all".to_string());
+    rules.join("\n") + "\n"
+}
+
+// 从 netsh advfirewall show <配置文件> firewallpolicy 的输出中提取策略（小写）。
+// 输出中的标签随系统语言变化，只匹配不做本地化的策略值，如 BlockInbound,AllowOutbound
+#[cfg(any(windows, test))]
+fn parse_firewall_policy(output: &str) -> Option<String> {
+    const INBOUND: &[&str] = &[
+        "blockinbound",
+        "blockinboundalways",
+        "allowinbound",
+        "notconfigured",
+    ];
+    const OUTBOUND: &[&str] = &["allowoutbound", "blockoutbound", "notconfigured"];
+
+    output
+        .split_whitespace()
+        .map(str::to_ascii_lowercase)
+        .find(|token| {
+            token.split_once(',').is_some_and(|(inbound, outbound)| {
+                INBOUND.contains(&inbound) && OUTBOUND.contains(&outbound)
+            })
+        })
+}
+
+#[cfg(any(windows, test))]
+fn netsh_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+// 生成安装规则的 netsh 命令：先添加放行规则，再把各配置文件的出站策略改为阻止（入站策略保持原样）
+#[cfg(any(windows, test))]
+pub fn netsh_engage_commands(
+    spec: &KillSwitchSpec,
+    firewall_policies: &[(String, String)],
+) -> Vec<Vec<String>> {
+    let allow_rule = |extra: Vec<String>| {
+        let mut args: Vec<String> = [
+            "advfirewall",
+            "firewall",
+            "add",
+            "rule",
+            &format!("name={}", NETSH_RULE_NAME),
+            "dir=out",
+            "action=allow",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        args.extend(extra);
+        args
+    };
+
+    let mut commands = vec![allow_rule(vec!["remoteip=127.0.0.0/8,::1".to_string()])];
+    if let Some(exe) = &spec.service_exe {
+        commands.push(allow_rule(vec![format!("program={}", exe.display())]));
+    }
+
+    // 相同端口的服务器合并为一条规则
+    let mut ports: Vec<u16> = spec.servers.iter().map(SocketAddr::port).collect();
+    ports.sort_unstable();
+    ports.dedup();
+    for port in ports {
+        let ips: Vec<String> = spec
+            .servers
+            .iter()
+            .filter(|server| server.port() == port)
+            .map(|server| server.ip().to_string())
+            .collect();
+        for protocol in ["tcp", "udp"] {
+            commands.push(allow_rule(vec![
+                format!("protocol={}", protocol),
+                format!("remoteip={}", ips.join(",")),
+                format!("remoteport={}", port),
+            ]));
+        }
+    }
+
+    for (profile, policy) in firewall_policies {
+        let inbound = policy.split(',').next().unwrap_or("blockinbound");
+        commands.push(netsh_args(&[
+            "advfirewall",
+            "set",
+            profile,
+            "firewallpolicy",
+            &format!("{},blockoutbound", inbound),
+        ]));
+    }
+    commands
+}
+
+// 生成恢复防火墙策略的 netsh 命令：还原安装规则前保存的各配置文件策略
+#[cfg(any(windows, test))]
+pub fn netsh_restore_commands(firewall_policies: &[(String, String)]) -> Vec<Vec<String>> {
+    firewall_policies
+        .iter()
+        .map(|(profile, policy)| {
+            netsh_args(&["advfirewall", "set", profile, "firewallpolicy", policy])
+        })
+        .collect()
+}
+
+// 生成删除放行规则的 netsh 命令
+#[cfg(any(windows, test))]
+pub fn netsh_delete_rule_command() -> Vec<String> {
+    netsh_args(&[
+        "advfirewall",
+        "firewall",
+        "delete",
+        "rule",
+        &format!("name={}", NETSH_RULE_NAME),
+    ])
+}
+
+// 执行命令，stdin 不为空时写入标准输入，返回合并后的输出
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn run_command(program: &str, args: &[&str], stdin: Option<&str>) -> Result<String, String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("执行 {} 失败: {}", program, e))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())
+            .map_err(|e| format!("写入 {} 输入失败: {}", program, e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("等待 {} 失败: {}", program, e))?;
+
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    if output.status.success() {
+        Ok(text)
+    } else {
+        Err(format!(
+            "{} {} 失败: {}",
+            program,
+            args.join(" "),
+            text.trim()
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn engage(state: &mut KillSwitchState, spec: &KillSwitchSpec) -> Result<(), String> {
+    // 记录本身即为残留标记，启动时据此删除表
+    persist_record(state, EngagedRecord::default())?;
+    if let Err(e) = run_command("nft", &["-f", "-"], Some(&nft_ruleset(spec))) {
+        clear_record(state);
+        return Err(e);
+    }
+    state.is_engaged = true;
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn release(state: &mut KillSwitchState) -> Result<(), String> {
+    match run_command("nft", &["delete", "table", "inet", NFT_TABLE], None) {
+        // 表已不存在时视为已移除
+        Ok(_) => {}
+        Err(e) if e.contains("No such file or directory") => {}
+        Err(e) => return Err(e),
+    }
+    clear_record(state);
+    state.is_engaged = false;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn engage(state: &mut KillSwitchState, spec: &KillSwitchSpec) -> Result<(), String> {
+    run_command(
+        "pfctl",
+        &["-a", PF_ANCHOR, "-f", "-"],
+        Some(&pf_rules(spec)),
+    )?;
+    // 增加 pf 启用引用计数，输出形如 Token : 1234567890
+    let output = run_command("pfctl", &["-E"], None)?;
+    let pf_token = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Token :"))
+        .map(|token| token.trim().to_string());
+    let record = EngagedRecord {
+        pf_token,
+        ..Default::default()
+    };
+    if let Err(e) = persist_record(state, record.clone()) {
+        state.record = record;
+        let _ = release(state);
+        return Err(e);
+    }
+    state.is_engaged = true;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn release(state: &mut KillSwitchState) -> Result<(), String> {
+    run_command("pfctl", &["-a", PF_ANCHOR, "-F", "rules"], None)?;
+    if let Some(token) = state.record.pf_token.take() {
+        run_command("pfctl", &["-X", &token], None)?;
+    }
+    clear_record(state);
+    state.is_engaged = false;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn run_netsh(commands: Vec<Vec<String>>) -> Result<(), String> {
+    for args in commands {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        run_command("netsh", &args, None)?;
+    }
+    Ok(())
+}
+
+// 读取各配置文件当前的防火墙策略
+#[cfg(windows)]
+fn query_firewall_policies() -> Result<Vec<(String, String)>, String> {
+    FIREWALL_PROFILES
+        .iter()
+        .map(|&profile| {
+            let output = run_command(
+                "netsh",
+                &["advfirewall", "show", profile, "firewallpolicy"],
+                None,
+            )?;
+            parse_firewall_policy(&output)
+                .map(|policy| (profile.to_string(), policy))
+                .ok_or_else(|| format!("无法解析 {} 的防火墙策略: {}", profile, output.trim()))
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn engage(state: &mut KillSwitchState, spec: &KillSwitchSpec) -> Result<(), String> {
+    // 先保存原有策略再修改，移除规则时原样恢复，而不是覆盖为系统默认值
+    let firewall_policies = query_firewall_policies()?;
+    let commands = netsh_engage_commands(spec, &firewall_policies);
+    persist_record(
+        state,
+        EngagedRecord {
+            firewall_policies,
+            ..Default::default()
+        },
+    )?;
+
+    if let Err(e) = run_netsh(commands) {
+        // 部分规则已添加时回滚，避免残留阻止策略
+        state.is_engaged = true;
+        let _ = release(state);
+        return Err(e);
+    }
+    state.is_engaged = true;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn release(state: &mut KillSwitchState) -> Result<(), String> {
+    // 先恢复防火墙策略，保证网络可用；失败时保留记录以便重试
+    run_netsh(netsh_restore_commands(&state.record.firewall_policies))?;
+    // 删除放行规则失败不影响联网（启动时清理可能本就没有规则）
+    if let Err(e) = run_netsh(vec![netsh_delete_rule_command()]) {
+        if state.is_engaged {
+            log::warn!("删除防泄漏放行规则失败: {}", e);
+        } else {
+            log::debug!("删除防泄漏放行规则失败: {}", e);
+        }
+    }
+    clear_record(state);
+    state.is_engaged = false;
+    Ok(())
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn engage(_state: &mut KillSwitchState, _spec: &KillSwitchSpec) -> Result<(), String> {
+    Err("当前平台不支持防泄漏开关".to_string())
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn release(state: &mut KillSwitchState) -> Result<(), String> {
+    clear_record(state);
+    state.is_engaged = false;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> KillSwitchSpec {
+        KillSwitchSpec {
+            servers: vec![
+                "203.0.113.10:443".parse().unwrap(),
+                "203.0.113.11:443".parse().unwrap(),
+                "[2001:db8::1]:8388".parse().unwrap(),
+            ],
+            service_exe: Some(PathBuf::from(
+                r"C:\Program Files\Stelliberty\stelliberty-service.exe",
+            )),
+            service_cgroup: Some("system.slice/StellibertyService.service".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_nft_ruleset() {
+        let ruleset = nft_ruleset(&spec());
+        assert!(ruleset.starts_with(
+            "table inet stelliberty_killswitch\ndelete table inet stelliberty_killswitch\n"
+        ));
+        assert!(ruleset.contains("policy drop;"));
+        assert!(ruleset.contains("oifname \"lo\" accept"));
+        assert!(ruleset.contains(
+            "socket cgroupv2 level 2 \"system.slice/StellibertyService.service\" accept"
+        ));
+        assert!(
+            ruleset.contains("ip daddr 203.0.113.10 meta l4proto { tcp, udp } th dport 443 accept")
+        );
+        assert!(
+            ruleset
+                .contains("ip6 daddr 2001:db8::1 meta l4proto { tcp, udp } th dport 8388 accept")
+        );
+
+        // 没有 cgroup 与服务器时仍放行回环
+        let ruleset = nft_ruleset(&KillSwitchSpec::default());
+        assert!(ruleset.contains("ip daddr 127.0.0.0/8 accept"));
+        assert!(!ruleset.contains("cgroupv2"));
+    }
+
+    #[test]
+    fn test_pf_rules_order() {
+        let rules = pf_rules(&spec());
+        let lines: Vec<&str> = rules.lines().collect();
+        assert_eq!(lines.first(), Some(&"pass out quick on lo0 all"));
+        assert_eq!(lines.last(), Some(&"block drop out quick all"));
+        assert!(lines.contains(&"pass out quick proto { tcp udp } to 2001:db8::1 port 8388"));
+        // root 只放行 DNS，不能放行任意流量
+        assert!(
+            lines.contains(&"pass out quick proto { tcp udp } to any port { 53 853 } user root")
+        );
+        assert!(!lines.contains(&"pass out quick proto { tcp udp } user root"));
+    }
+
+    fn saved_policies() -> Vec<(String, String)> {
+        vec![
+            (
+                "domainprofile".to_string(),
+                "blockinboundalways,allowoutbound".to_string(),
+            ),
+            (
+                "privateprofile".to_string(),
+                "allowinbound,allowoutbound".to_string(),
+            ),
+            (
+                "publicprofile".to_string(),
+                "blockinbound,blockoutbound".to_string(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_netsh_commands() {
+        let commands = netsh_engage_commands(&spec(), &saved_policies());
+        // 回环 + 服务程序 + 两个端口各 tcp/udp + 三个配置文件的策略
+        assert_eq!(commands.len(), 9);
+        assert!(commands[0].contains(&"remoteip=127.0.0.0/8,::1".to_string()));
+        assert!(commands[1].contains(
+            &r"program=C:\Program Files\Stelliberty\stelliberty-service.exe".to_string()
+        ));
+        assert!(commands[2].contains(&"remoteip=203.0.113.10,203.0.113.11".to_string()));
+        assert!(commands[2].contains(&"remoteport=443".to_string()));
+        // 阻止策略最后设置，放行规则已就位；入站策略保持原样
+        let policies: Vec<(&str, &str)> = commands[6..]
+            .iter()
+            .map(|args| (args[2].as_str(), args[4].as_str()))
+            .collect();
+        assert_eq!(
+            policies,
+            vec![
+                ("domainprofile", "blockinboundalways,blockoutbound"),
+                ("privateprofile", "allowinbound,blockoutbound"),
+                ("publicprofile", "blockinbound,blockoutbound"),
+            ]
+        );
+
+        // 移除时原样恢复保存的策略，而不是覆盖为默认值
+        let restore = netsh_restore_commands(&saved_policies());
+        assert_eq!(restore.len(), 3);
+        assert_eq!(
+            restore[1],
+            netsh_args(&[
+                "advfirewall",
+                "set",
+                "privateprofile",
+                "firewallpolicy",
+                "allowinbound,allowoutbound"
+            ])
+        );
+        assert!(netsh_restore_commands(&[]).is_empty());
+        assert!(netsh_delete_rule_command().contains(&"name=Stelliberty Kill Switch".to_string()));
+    }
+
+    #[test]
+    fn test_parse_firewall_policy() {
+        let english = "\r\nPublic Profile Settings: \r\n\
+                       ----------------------------------------------------------------------\r\n\
+                       Firewall Policy                       BlockInbound,AllowOutbound\r\n\r\nOk.\r\n";
+        assert_eq!(
+            parse_firewall_policy(english).as_deref(),
+            Some("blockinbound,allowoutbound")
+        );
+
+        // 标签本地化时仍能识别策略值
+        let chinese = "公用配置文件 设置:\r\n------\r\n防火墙策略                            BlockInboundAlways,BlockOutbound\r\n确定。\r\n";
+        assert_eq!(
+            parse_firewall_policy(chinese).as_deref(),
+            Some("blockinboundalways,blockoutbound")
+        );
+        assert_eq!(parse_firewall_policy("Ok.\r\n"), None);
+    }
+
+    #[test]
+    fn test_engaged_record_roundtrip() {
+        let path = std::env::temp_dir().join(format!(
+            "stelliberty-killswitch-record-{}.json",
+            std::process::id()
+        ));
+        let record = EngagedRecord {
+            firewall_policies: saved_policies(),
+            pf_token: Some("1234567890".to_string()),
+        };
+        save_record_to(&path, &record).unwrap();
+        assert_eq!(load_record_from(&path), Some(record));
+
+        // 记录缺少字段时按默认值读取
+        std::fs::write(&path, "{}").unwrap();
+        assert_eq!(load_record_from(&path), Some(EngagedRecord::default()));
+
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(load_record_from(&path), None);
+    }
+
+    #[test]
+    fn test_proxy_servers_from_config() {
+        let path = std::env::temp_dir().join(format!(
+            "stelliberty-killswitch-{}.yaml",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "proxies:\n  - {name: a, server: 203.0.113.10, port: 443}\n  - {name: b, server: 203.0.113.10, port: 443}\n  - {name: c, server: '[2001:db8::1]', port: 8388}\n  - {name: d, port: 80}\n",
+        )
+        .unwrap();
+
+        let servers = proxy_servers(&path.to_string_lossy());
+        assert_eq!(
+            servers,
+            vec![
+                "203.0.113.10:443".parse::<SocketAddr>().unwrap(),
+                "[2001:db8::1]:8388".parse::<SocketAddr>().unwrap(),
+            ]
+        );
+        assert!(proxy_servers("/nonexistent/config.yaml").is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_parse_cgroup() {
+        assert_eq!(
+            parse_cgroup("0::/system.slice/StellibertyService.service\n").as_deref(),
+            Some("system.slice/StellibertyService.service")
+        );
+        assert_eq!(parse_cgroup("0::/\n"), None);
+    }
+}
@@ -0,0 +1,412 @@
+// 核心启动参数：构建命令行参数，并在需要覆盖入站端口时生成派生配置文件
+
+use serde_yaml_ng::Value as YamlValue;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// 派生配置目录（位于服务私有目录下，IPC 客户端无法预置其中的文件）
+const DERIVED_CONFIG_DIR: &str = "derived";
+
+// 端口覆盖派生配置的文件名前缀
+const PORT_OVERRIDE_KIND: &str = "ports";
+
+// 由服务管理的核心参数，附加参数不得覆盖，否则核心实际状态与主程序记录不一致
+// （-config 以 base64 传入配置，-ext-ctl-unix/-ext-ctl-pipe/-secret 会另开或改写控制器入口）
+const MANAGED_FLAGS: &[&str] = &[
+    "d",
+    "f",
+    "config",
+    "ext-ctl",
+    "ext-ctl-unix",
+    "ext-ctl-pipe",
+    "secret",
+];
+
+// 核心进程优先级（大批量延迟测试时避免核心占满 CPU）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CorePriority {
+    #[default]
+    Normal,
+    BelowNormal,
+    Low,
+}
+
+impl CorePriority {
+    // 解析主程序传入的优先级名称，未指定时为 normal
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(str::trim) {
+            None | Some("") | Some("normal") => Ok(Self::Normal),
+            Some("below_normal") => Ok(Self::BelowNormal),
+            Some("low") => Ok(Self::Low),
+            Some(other) => Err(format!(
+                "无效的核心优先级: {}（可选: normal、below_normal、low）",
+                other
+            )),
+        }
+    }
+
+    // Unix nice 值
+    #[cfg(any(unix, test))]
+    pub fn nice_value(self) -> i32 {
+        match self {
+            Self::Normal => 0,
+            Self::BelowNormal => 10,
+            Self::Low => 19,
+        }
+    }
+
+    // Windows 优先级类（NORMAL / BELOW_NORMAL / IDLE_PRIORITY_CLASS）
+    #[cfg(any(windows, test))]
+    pub fn windows_priority_class(self) -> u32 {
+        match self {
+            Self::Normal => 0x0000_0020,
+            Self::BelowNormal => 0x0000_4000,
+            Self::Low => 0x0000_0040,
+        }
+    }
+}
+
+// 入站端口覆盖（未设置的端口沿用配置文件）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PortOverrides {
+    pub mixed_port: Option<u16>,
+    pub socks_port: Option<u16>,
+}
+
+impl PortOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.mixed_port.is_none() && self.socks_port.is_none()
+    }
+
+    // 校验端口范围，且不能与其他端口（包括外部控制器端口）重复
+    pub fn validate(&self, external_controller: &str) -> Result<(), String> {
+        let controller_port = external_controller
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<u16>().ok());
+
+        let mut used = Vec::new();
+        for (name, port) in [
+            ("mixed-port", self.mixed_port),
+            ("socks-port", self.socks_port),
+        ] {
+            let Some(port) = port else {
+                continue;
+            };
+            if port == 0 {
+                return Err(format!("{} 超出范围: {}（应为 1-65535）", name, port));
+            }
+            if used.contains(&port) {
+                return Err(format!("{} 与其他入站端口重复: {}", name, port));
+            }
+            if controller_port == Some(port) {
+                return Err(format!("{} 与外部控制器端口重复: {}", name, port));
+            }
+            used.push(port);
+        }
+
+        Ok(())
+    }
+
+    // 将端口覆盖写入配置
+    fn apply(&self, config: &mut YamlValue) -> Result<(), String> {
+        let Some(mapping) = config.as_mapping_mut() else {
+            return Err("配置文件根节点不是映射".to_string());
+        };
+        for (key, port) in [
+            ("mixed-port", self.mixed_port),
+            ("socks-port", self.socks_port),
+        ] {
+            if let Some(port) = port {
+                mapping.insert(YamlValue::from(key), YamlValue::from(port));
+            }
+        }
+        Ok(())
+    }
+}
+
+// 校验附加参数，拒绝覆盖受管理的参数（支持 -flag、--flag 与 -flag=value 写法）
+pub fn validate_extra_args(extra_args: &[String]) -> Result<(), String> {
+    for arg in extra_args {
+        let Some(flag) = arg.strip_prefix('-') else {
+            continue;
+        };
+        let flag = flag.strip_prefix('-').unwrap_or(flag);
+        let name = flag.split_once('=').map_or(flag, |(name, _)| name);
+        if MANAGED_FLAGS.contains(&name) {
+            return Err(format!("附加参数不能覆盖由服务管理的参数: {}", arg));
+        }
+    }
+    Ok(())
+}
+
+// 构建核心命令行参数（config_path 为实际加载的配置，可能是派生配置），附加参数追加在内置参数之后
+pub fn core_args(
+    data_dir: &str,
+    config_path: &str,
+    external_controller: &str,
+    extra_args: &[String],
+) -> Vec<String> {
+    // external-controller 无论是否启用都必须传递，空字符串表示禁用 HTTP API
+    let mut args = vec![
+        "-d".to_string(),
+        data_dir.to_string(),
+        "-f".to_string(),
+        config_path.to_string(),
+        "-ext-ctl".to_string(),
+        external_controller.to_string(),
+    ];
+    args.extend_from_slice(extra_args);
+    args
+}
+
+// 读取配置中的本地代理端口（优先 mixed-port，其次 HTTP 代理 port）
+pub fn read_proxy_port(config_path: &str) -> Option<u16> {
+    let content = std::fs::read_to_string(config_path).ok()?;
+    let config: YamlValue = serde_yaml_ng::from_str(&content).ok()?;
+    ["mixed-port", "port"]
+        .iter()
+        .filter_map(|key| config.get(*key).and_then(YamlValue::as_u64))
+        .find(|port| *port != 0)
+        .and_then(|port| u16::try_from(port).ok())
+}
+
+// 生成带端口覆盖的派生配置文件，返回其路径
+pub fn write_port_override_config(
+    config_path: &str,
+    ports: &PortOverrides,
+) -> Result<PathBuf, String> {
+    let content = std::fs::read_to_string(config_path)
+        .map_err(|e| format!("读取配置文件失败: {} ({})", config_path, e))?;
+    let mut config: YamlValue =
+        serde_yaml_ng::from_str(&content).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    ports.apply(&mut config)?;
+
+    let output =
+        serde_yaml_ng::to_string(&config).map_err(|e| format!("序列化配置文件失败: {}", e))?;
+    write_derived_config(PORT_OVERRIDE_KIND, &output)
+        .map_err(|e| format!("写入端口覆盖配置失败: {}", e))
+}
+
+// 写入派生配置文件并返回其路径
+// 服务以管理员权限运行，而原配置所在目录由 IPC 客户端指定，不能在其中按固定文件名写入
+// （可预置符号链接指向任意文件）。派生配置统一写入服务私有目录，使用随机文件名独占创建
+pub fn write_derived_config(kind: &str, content: &str) -> Result<PathBuf, String> {
+    let dir = std::env::current_exe()
+        .map_err(|e| format!("获取服务程序路径失败: {}", e))?
+        .parent()
+        .map(|dir| dir.join(DERIVED_CONFIG_DIR))
+        .ok_or_else(|| "无法确定服务私有目录".to_string())?;
+    write_derived_config_in(&dir, kind, content)
+}
+
+fn write_derived_config_in(dir: &Path, kind: &str, content: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("{} ({})", dir.display(), e))?;
+    remove_derived_configs(dir, kind);
+
+    let path = dir.join(format!("{}-{}.yaml", kind, random_token()));
+    create_new_file(&path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|e| format!("{} ({})", path.display(), e))?;
+    Ok(path)
+}
+
+// 清理同类旧派生配置（上次启动时生成，核心已停止）
+fn remove_derived_configs(dir: &Path, kind: &str) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let prefix = format!("{}-", kind);
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(&prefix) && name.ends_with(".yaml") {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+// 独占创建新文件：目标已存在（包括符号链接）时失败，Unix 下不跟随符号链接且仅所有者可读写
+pub fn create_new_file(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_NOFOLLOW).mode(0o600);
+    }
+    options.open(path)
+}
+
+// 随机文件名片段（RandomState 每次构造使用不同的随机种子）
+pub fn random_token() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    if let Ok(elapsed) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_args_without_overrides() {
+        let args = core_args("/data", "/data/runtime_config.yaml", "127.0.0.1:9090", &[]);
+        assert_eq!(
+            args,
+            vec![
+                "-d",
+                "/data",
+                "-f",
+                "/data/runtime_config.yaml",
+                "-ext-ctl",
+                "127.0.0.1:9090"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_port_overrides_apply() {
+        let ports = PortOverrides {
+            mixed_port: Some(7891),
+            socks_port: None,
+        };
+        let mut config: YamlValue =
+            serde_yaml_ng::from_str("mixed-port: 7890\nmode: rule\n").unwrap();
+        ports.apply(&mut config).unwrap();
+        assert_eq!(config["mixed-port"].as_u64(), Some(7891));
+        assert_eq!(config["mode"].as_str(), Some("rule"));
+        assert!(config.get("socks-port").is_none());
+    }
+
+    #[test]
+    fn test_write_derived_config_uses_fresh_file() {
+        let dir = std::env::temp_dir().join(format!("stelliberty_derived_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let first = write_derived_config_in(&dir, "ports", "mixed-port: 7890\n").unwrap();
+        let second = write_derived_config_in(&dir, "ports", "mixed-port: 7891\n").unwrap();
+        assert_ne!(first, second);
+        assert!(second.starts_with(&dir));
+        // 同类旧文件已清理
+        assert!(!first.exists());
+        assert_eq!(
+            std::fs::read_to_string(&second).unwrap(),
+            "mixed-port: 7891\n"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_new_file_refuses_symlink() {
+        let dir =
+            std::env::temp_dir().join(format!("stelliberty_no_follow_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let target = dir.join("target");
+        std::fs::write(&target, "original").unwrap();
+        let link = dir.join("link.yaml");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(create_new_file(&link).is_err());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "original");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_core_args_appends_extra_args() {
+        let extra_args = vec!["-ext-ui".to_string(), "ui".to_string(), "--m".to_string()];
+        assert!(validate_extra_args(&extra_args).is_ok());
+
+        let args = core_args(
+            "/data",
+            "/data/runtime_config.yaml",
+            "127.0.0.1:9090",
+            &extra_args,
+        );
+        assert_eq!(args.len(), 9);
+        assert_eq!(args[..2], ["-d", "/data"]);
+        assert_eq!(args[6..], extra_args[..]);
+    }
+
+    #[test]
+    fn test_validate_extra_args_rejects_managed_flags() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(validate_extra_args(&args(&["-f", "/tmp/other.yaml"])).is_err());
+        assert!(validate_extra_args(&args(&["--f=/tmp/other.yaml"])).is_err());
+        assert!(validate_extra_args(&args(&["-ext-ctl=0.0.0.0:9090"])).is_err());
+        assert!(validate_extra_args(&args(&["-m", "-d"])).is_err());
+        // 每个受管理参数的 --flag 与 -flag=value 写法均被拒绝
+        for flag in MANAGED_FLAGS {
+            assert!(validate_extra_args(&args(&[&format!("--{}", flag), "value"])).is_err());
+            assert!(validate_extra_args(&args(&[&format!("-{}=value", flag)])).is_err());
+        }
+        // 参数值中出现的同名文本不影响
+        assert!(validate_extra_args(&args(&["-ext-ui", "secret"])).is_ok());
+    }
+
+    #[test]
+    fn test_read_proxy_port() {
+        let path = std::env::temp_dir().join(format!(
+            "stelliberty_proxy_port_{}.yaml",
+            std::process::id()
+        ));
+        let path_str = path.to_string_lossy().to_string();
+
+        std::fs::write(&path, "port: 7890\nmixed-port: 7891\n").unwrap();
+        assert_eq!(read_proxy_port(&path_str), Some(7891));
+        std::fs::write(&path, "port: 7890\nmixed-port: 0\n").unwrap();
+        assert_eq!(read_proxy_port(&path_str), Some(7890));
+        std::fs::write(&path, "socks-port: 7892\n").unwrap();
+        assert_eq!(read_proxy_port(&path_str), None);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_validate_port_overrides() {
+        let ports = |mixed_port, socks_port| PortOverrides {
+            mixed_port,
+            socks_port,
+        };
+        assert!(
+            ports(Some(7890), Some(7891))
+                .validate("127.0.0.1:9090")
+                .is_ok()
+        );
+        assert!(ports(Some(0), None).validate("").is_err());
+        assert!(ports(Some(7890), Some(7890)).validate("").is_err());
+        assert!(ports(Some(9090), None).validate("127.0.0.1:9090").is_err());
+    }
+
+    #[test]
+    fn test_core_priority_mapping() {
+        assert_eq!(CorePriority::parse(None).unwrap(), CorePriority::Normal);
+        assert_eq!(CorePriority::parse(Some("")).unwrap(), CorePriority::Normal);
+        assert_eq!(
+            CorePriority::parse(Some("below_normal")).unwrap(),
+            CorePriority::BelowNormal
+        );
+        assert_eq!(CorePriority::parse(Some("low")).unwrap(), CorePriority::Low);
+        assert!(CorePriority::parse(Some("high")).is_err());
+        assert!(CorePriority::parse(Some("realtime")).is_err());
+
+        let mapped: Vec<(i32, u32)> = [
+            CorePriority::Normal,
+            CorePriority::BelowNormal,
+            CorePriority::Low,
+        ]
+        .into_iter()
+        .map(|priority| (priority.nice_value(), priority.windows_priority_class()))
+        .collect();
+        // NORMAL_PRIORITY_CLASS、BELOW_NORMAL_PRIORITY_CLASS、IDLE_PRIORITY_CLASS
+        assert_eq!(mapped, vec![(0, 0x20), (10, 0x4000), (19, 0x40)]);
+    }
+}
@@ -1,7 +1,36 @@
 // Clash 核心进程管理器
 
+use super::core_policy::CorePolicy;
+use super::exit_monitor::{OutputBuffer, core_exited_event, publish_event};
+use super::kill_switch;
+use super::launch::{
+    CorePriority, PortOverrides, core_args, read_proxy_port, validate_extra_args,
+    write_port_override_config,
+};
+use super::ports::is_port_bindable;
+use super::safe_mode::{self, CrashTracker};
+use crate::ipc::protocol::OrphanCore;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+// 清理孤立进程时的默认退出宽限期
+const DEFAULT_KILL_GRACE: Duration = Duration::from_secs(1);
+
+// 停止核心时的退出宽限期（核心需要时间撤销 TUN 路由）
+const STOP_KILL_GRACE: Duration = Duration::from_secs(3);
+
+// 终止进程的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KillOutcome {
+    // 宽限期内自行退出
+    Exited,
+    // 宽限期后被强制终止
+    Killed,
+}
 
 // Clash 进程状态
 #[derive(Debug, Clone)]
@@ -14,6 +43,47 @@ pub struct ClashStatus {
     pub uptime: u64,
 }
 
+// 启动核心失败的原因（详细排查提示写入日志，错误码供 UI 给出对应处理建议）
+#[derive(Error, Debug)]
+pub enum StartError {
+    // 核心文件不存在
+    #[error("Clash 核心文件不存在: {0}")]
+    CoreNotFound(String),
+    // 配置文件不存在
+    #[error("配置文件不存在: {0}")]
+    ConfigNotFound(String),
+    // 入站端口已被占用
+    #[error("端口 {0} 已被占用")]
+    PortInUse(u16),
+    // 核心路径未通过策略校验，或没有执行权限
+    #[error("没有权限启动核心: {0}")]
+    PermissionDenied(String),
+    // 创建核心进程失败
+    #[error("启动 Clash 进程失败: {0}")]
+    SpawnFailed(std::io::Error),
+    // 附加参数或端口覆盖不合法
+    #[error("{0}")]
+    InvalidArguments(String),
+    // 其他失败（停止旧实例、写入派生配置等）
+    #[error("{0}")]
+    Other(String),
+}
+
+impl StartError {
+    // IPC 错误码（保持稳定，1001 为未细分的启动失败）
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::Other(_) => 1001,
+            Self::CoreNotFound(_) => 1101,
+            Self::ConfigNotFound(_) => 1102,
+            Self::PortInUse(_) => 1103,
+            Self::PermissionDenied(_) => 1104,
+            Self::SpawnFailed(_) => 1105,
+            Self::InvalidArguments(_) => 1106,
+        }
+    }
+}
+
 // Clash 管理器
 pub struct ClashManager {
     // Clash 核心路径
@@ -22,6 +92,13 @@ pub struct ClashManager {
     config_path: Option<String>,
     // 数据目录
     data_dir: Option<String>,
+    // 启动时的入站端口覆盖
+    ports: PortOverrides,
+    // 启动时的外部控制器地址与附加参数（切换配置时沿用）
+    external_controller: String,
+    extra_args: Vec<String>,
+    // 核心进程优先级（切换配置时沿用）
+    priority: CorePriority,
     // API 主机
     api_host: Option<String>,
     // API 端口
@@ -30,6 +107,12 @@ pub struct ClashManager {
     child: Mutex<Option<Child>>,
     // 启动时间
     start_time: Mutex<Option<std::time::Instant>>,
+    // 核心最近的输出（意外退出时随事件上报）
+    output: OutputBuffer,
+    // 最近的意外退出记录
+    crashes: Mutex<CrashTracker>,
+    // 连续崩溃达到阈值，等待退出监控以安全模式重启
+    is_safe_mode_pending: AtomicBool,
 }
 
 impl Default for ClashManager {
@@ -38,10 +121,17 @@ impl Default for ClashManager {
             core_path: None,
             config_path: None,
             data_dir: None,
+            ports: PortOverrides::default(),
+            external_controller: String::new(),
+            extra_args: Vec::new(),
+            priority: CorePriority::Normal,
             api_host: None,
             api_port: None,
             child: Mutex::new(None),
             start_time: Mutex::new(None),
+            output: OutputBuffer::default(),
+            crashes: Mutex::new(CrashTracker::default()),
+            is_safe_mode_pending: AtomicBool::new(false),
         }
     }
 }
@@ -52,6 +142,11 @@ impl ClashManager {
         Self::default()
     }
 
+    // 设置之后启动的核心进程优先级
+    pub fn set_priority(&mut self, priority: CorePriority) {
+        self.priority = priority;
+    }
+
     // 启动 Clash 核心
     pub fn start(
         &mut self,
@@ -59,11 +154,18 @@ impl ClashManager {
         config_path: String,
         data_dir: String,
         external_controller: String,
-    ) -> Result<(), String> {
+        ports: PortOverrides,
+        extra_args: Vec<String>,
+    ) -> Result<(), StartError> {
+        // 附加参数不合法时不影响正在运行的实例
+        validate_extra_args(&extra_args)
+            .inspect_err(|e| log::error!("{}", e))
+            .map_err(StartError::InvalidArguments)?;
+
         // 如果已经在运行，先停止
         if self.is_running() {
             log::info!("Clash 已在运行，先停止旧实例");
-            self.stop()?;
+            self.stop().map_err(StartError::Other)?;
         }
 
         if let Err(e) = Self::cleanup_orphan_processes() {
@@ -83,67 +185,79 @@ impl ClashManager {
             }
         );
 
-        // 检查核心文件是否存在
-        if !std::path::Path::new(&core_path).exists() {
-            let error_msg = format!(
-                "Clash 核心文件不存在\n路径: {}\n提示: 请检查核心文件是否正确安装",
-                core_path
+        // 校验核心路径策略，防止 IPC 客户端让服务以管理员权限运行任意程序
+        let resolved_core_path = Self::check_launch(&core_path, &config_path, &ports, |path| {
+            CorePolicy::load().and_then(|policy| policy.check(path))
+        })?;
+
+        // 应用入站端口覆盖（在服务私有目录生成派生配置文件）
+        let launch_config_path = if !ports.is_empty() {
+            ports
+                .validate(&external_controller)
+                .inspect_err(|e| log::error!("{}", e))
+                .map_err(StartError::InvalidArguments)?;
+            let derived_path = write_port_override_config(&config_path, &ports)
+                .inspect_err(|e| log::error!("{}", e))
+                .map_err(StartError::Other)?;
+            log::info!(
+                "入站端口覆盖: mixed-port={:?}, socks-port={:?}（{}）",
+                ports.mixed_port,
+                ports.socks_port,
+                derived_path.display()
             );
-            log::error!("{}", error_msg);
-            return Err(error_msg);
-        }
+            derived_path.to_string_lossy().to_string()
+        } else {
+            config_path.clone()
+        };
 
-        // 检查配置文件是否存在
-        if !std::path::Path::new(&config_path).exists() {
-            let error_msg = format!(
-                "配置文件不存在\n路径: {}\n提示: 请检查配置文件是否正确生成",
-                config_path
-            );
-            log::error!("{}", error_msg);
-            return Err(error_msg);
-        }
+        kill_switch::on_core_starting(&config_path);
 
         // 构建启动参数
-        let mut args = vec![
-            "-d".to_string(),
-            data_dir.clone(),
-            "-f".to_string(),
-            config_path.clone(),
-        ];
-
-        // 添加 external-controller 参数
-        // 无论是否启用，都必须传递此参数
-        // 空字符串表示禁用 HTTP API
-        args.push("-ext-ctl".to_string());
-        args.push(external_controller.clone());
+        let args = core_args(
+            &data_dir,
+            &launch_config_path,
+            &external_controller,
+            &extra_args,
+        );
 
         log::debug!("Clash 启动参数: {:?}", args);
 
-        // 启动进程，重定向输出防止缓冲区阻塞
-        let child = Command::new(&core_path)
-            .args(&args)
-            .stdout(Stdio::null()) // 防止输出缓冲区填满导致进程阻塞
-            .stderr(Stdio::null())
-            .spawn()
-            .map_err(|e| {
-                let error_msg = format!(
-                    "启动 Clash 失败: {}\n核心路径: {}\n配置文件: {}\n数据目录: {}\n外部控制器: {}\n{}",
+        // 启动进程，输出由后台线程持续读取到环形缓冲区，防止管道写满导致进程阻塞
+        let mut child =
+            Self::spawn_core(&resolved_core_path, &args, self.priority).inspect_err(|e| {
+                log::error!(
+                    "{}\n核心路径: {}\n配置文件: {}\n数据目录: {}\n外部控制器: {}",
                     e,
                     core_path,
                     config_path,
                     data_dir,
-                    if external_controller.is_empty() { "禁用" } else { &external_controller },
-                    Self::format_io_error_hint(&e)
-                );
-                log::error!("{}", error_msg);
-                error_msg
+                    if external_controller.is_empty() {
+                        "禁用"
+                    } else {
+                        &external_controller
+                    },
+                )
             })?;
 
         let pid = child.id();
+        if self.priority != CorePriority::Normal {
+            Self::apply_priority(&child, self.priority);
+        }
+
+        self.output.clear();
+        if let Some(stdout) = child.stdout.take() {
+            self.output.capture(stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.output.capture(stderr);
+        }
 
         self.core_path = Some(core_path);
         self.config_path = Some(config_path);
         self.data_dir = Some(data_dir);
+        self.ports = ports;
+        self.external_controller = external_controller;
+        self.extra_args = extra_args;
         self.api_host = None;
         self.api_port = None;
 
@@ -160,12 +274,73 @@ impl ClashManager {
         Ok(())
     }
 
-    // 强制停止 Clash（Windows 使用 taskkill）
+    // 降低核心进程优先级，失败时只记录警告，核心以默认优先级继续运行。
+    // Windows 使用 SetPriorityClass；Unix 的 nice 值已在 exec 前设置（Linux 上 setpriority
+    // 只作用于单个线程，启动后再设置不影响核心其余线程），这里只核对结果
+    fn apply_priority(child: &Child, priority: CorePriority) {
+        #[cfg(windows)]
+        let result = {
+            use std::os::windows::io::AsRawHandle;
+            use windows::Win32::Foundation::HANDLE;
+            use windows::Win32::System::Threading::{PROCESS_CREATION_FLAGS, SetPriorityClass};
+
+            unsafe {
+                SetPriorityClass(
+                    HANDLE(child.as_raw_handle()),
+                    PROCESS_CREATION_FLAGS(priority.windows_priority_class()),
+                )
+            }
+            .map_err(|e| e.to_string())
+        };
+
+        #[cfg(unix)]
+        let result = {
+            let nice =
+                unsafe { libc::getpriority(libc::PRIO_PROCESS as _, child.id() as libc::id_t) };
+            if nice == priority.nice_value() {
+                Ok(())
+            } else {
+                Err(format!("当前 nice 值为 {}", nice))
+            }
+        };
+
+        match result {
+            Ok(()) => log::info!("核心进程优先级已设置为 {:?}", priority),
+            Err(e) => log::warn!("设置核心进程优先级失败: {}", e),
+        }
+    }
+
+    // 强制停止 Clash（Windows 使用 taskkill，先尝试正常结束进程树，宽限期后 /F 强制终止）
+    #[cfg(windows)]
+    fn force_kill_windows(pid: u32, grace: Duration) -> Result<KillOutcome, String> {
+        Self::terminate_windows(pid, grace, || !Self::is_process_alive_windows(pid))
+    }
+
     #[cfg(windows)]
-    fn force_kill_windows(pid: u32) -> Result<(), String> {
-        log::warn!("使用强制终止方式清理进程 PID={}", pid);
+    fn terminate_windows(
+        pid: u32,
+        grace: Duration,
+        has_exited: impl FnMut() -> bool,
+    ) -> Result<KillOutcome, String> {
+        log::warn!(
+            "使用强制终止方式清理进程 PID={}（宽限期 {}ms）",
+            pid,
+            grace.as_millis()
+        );
+
+        // 步骤 1：不带 /F 的 taskkill 请求进程自行退出（无窗口的控制台进程可能直接失败）
+        let graceful = Command::new("taskkill")
+            .args(["/T", "/PID", &pid.to_string()])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+
+        if graceful && Self::wait_for_exit(grace, has_exited) {
+            log::info!("进程 PID={} 已安全退出", pid);
+            return Ok(KillOutcome::Exited);
+        }
 
-        // 使用 taskkill /F /T 强制终止进程树
+        // 步骤 2：使用 taskkill /F /T 强制终止进程树
         let output = Command::new("taskkill")
             .args(["/F", "/T", "/PID", &pid.to_string()])
             .output()
@@ -173,60 +348,70 @@ impl ClashManager {
 
         if output.status.success() {
             log::info!("进程 PID={} 已被强制终止", pid);
-            Ok(())
+            Ok(KillOutcome::Killed)
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
             Err(format!("taskkill 失败: {}", stderr))
         }
     }
 
+    // 检查进程是否存在（Windows 使用 tasklist 按 PID 过滤）
+    #[cfg(windows)]
+    fn is_process_alive_windows(pid: u32) -> bool {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+            .unwrap_or(false)
+    }
+
     // 强制停止 Clash（Unix 平台使用信号机制，优先安全退出）
     #[cfg(unix)]
-    fn force_kill_unix(pid: u32) -> Result<(), String> {
+    fn force_kill_unix(pid: u32, grace: Duration) -> Result<KillOutcome, String> {
+        use nix::sys::signal::kill;
+        use nix::unistd::Pid;
+
+        let nix_pid = Pid::from_raw(pid as i32);
+
+        // 非子进程由 init 回收，使用 signal 0 探测即可判断是否退出
+        Self::terminate_unix(pid, grace, || kill(nix_pid, None).is_err())
+    }
+
+    #[cfg(unix)]
+    fn terminate_unix(
+        pid: u32,
+        grace: Duration,
+        mut has_exited: impl FnMut() -> bool,
+    ) -> Result<KillOutcome, String> {
         use nix::sys::signal::{Signal, kill};
         use nix::unistd::Pid;
-        use std::thread;
-        use std::time::{Duration, Instant};
 
-        log::warn!("使用强制终止方式清理进程 PID={}", pid);
+        log::warn!(
+            "使用强制终止方式清理进程 PID={}（宽限期 {}ms）",
+            pid,
+            grace.as_millis()
+        );
 
         let nix_pid = Pid::from_raw(pid as i32);
 
         // 步骤 1：优先尝试安全退出（发送 SIGTERM 信号）
         log::debug!("发送 SIGTERM 信号到 PID={}", pid);
         if let Err(e) = kill(nix_pid, Signal::SIGTERM) {
+            if has_exited() {
+                return Ok(KillOutcome::Exited);
+            }
             return Err(format!("发送 SIGTERM 失败: {}", e));
         }
 
-        // 步骤 2：动态超时等待进程安全退出
-        // 轮询策略：初期高频探测（50ms 间隔），300ms 后降低轮询频率（100ms 间隔），最多等待 1 秒
+        // 步骤 2：在宽限期内等待进程安全退出
         let start = Instant::now();
-        let max_wait = Duration::from_secs(1);
-        let mut check_interval = Duration::from_millis(50);
-
-        while start.elapsed() < max_wait {
-            thread::sleep(check_interval);
-
-            // 探测进程存活状态（使用 signal 0 探测，不实际发送信号）
-            match kill(nix_pid, None) {
-                Err(_) => {
-                    // 进程已终止
-                    log::info!(
-                        "进程 PID={} 已安全退出（耗时 {}ms）",
-                        pid,
-                        start.elapsed().as_millis()
-                    );
-                    return Ok(());
-                }
-                Ok(_) => {
-                    // 进程仍在运行，继续轮询
-                    // 超过 300ms 后降低轮询频率以减少 CPU 开销
-                    if start.elapsed() > Duration::from_millis(300) {
-                        check_interval = Duration::from_millis(100);
-                    }
-                    continue;
-                }
-            }
+        if Self::wait_for_exit(grace, &mut has_exited) {
+            log::info!(
+                "进程 PID={} 已安全退出（耗时 {}ms）",
+                pid,
+                start.elapsed().as_millis()
+            );
+            return Ok(KillOutcome::Exited);
         }
 
         // 步骤 3：超时后使用 SIGKILL 强制终止
@@ -240,91 +425,106 @@ impl ClashManager {
         }
 
         // 等待进程回收确认（SIGKILL 通常立即生效）
-        thread::sleep(Duration::from_millis(100));
-        match kill(nix_pid, None) {
-            Err(_) => {
-                log::info!("进程 PID={} 已被强制终止", pid);
-                Ok(())
-            }
-            Ok(_) => Err(format!("进程 PID={} 在 SIGKILL 后仍然存在", pid)),
+        std::thread::sleep(Duration::from_millis(100));
+        if has_exited() {
+            log::info!("进程 PID={} 已被强制终止", pid);
+            Ok(KillOutcome::Killed)
+        } else {
+            Err(format!("进程 PID={} 在 SIGKILL 后仍然存在", pid))
         }
     }
 
-    // 检查并清理孤立的 Clash 核心进程
-    // 应用场景：处理心跳超时清理失败导致的僵尸进程（进程句柄丢失但进程仍在后台运行）
-    fn cleanup_orphan_processes() -> Result<(), String> {
-        #[cfg(windows)]
-        {
-            // 使用 tasklist 查找 clash-core.exe 进程
-            let output = Command::new("tasklist")
-                .args(["/FI", "IMAGENAME eq clash-core.exe", "/FO", "CSV", "/NH"])
-                .stdout(Stdio::piped())
-                .output()
-                .map_err(|e| format!("执行 tasklist 失败: {}", e))?;
-
-            if !output.status.success() {
-                return Err("tasklist 执行失败".to_string());
-            }
+    // 在宽限期内轮询等待进程退出
+    // 轮询策略：初期高频探测（50ms 间隔），300ms 后降低轮询频率（100ms 间隔），
+    // 宽限期较长时 1 秒后进一步降至 200ms，且不超过剩余时间
+    fn wait_for_exit(grace: Duration, mut has_exited: impl FnMut() -> bool) -> bool {
+        let start = Instant::now();
 
-            let stdout = String::from_utf8_lossy(&output.stdout);
-
-            // 解析 CSV 输出格式："clash-core.exe","1234","Console","1","12,345 K"
-            for line in stdout.lines() {
-                if line.contains("clash-core.exe") {
-                    // 提取 PID（第二列）
-                    let parts: Vec<&str> = line.split(',').collect();
-                    if parts.len() >= 2 {
-                        let pid_str = parts[1].trim_matches('"').trim();
-                        if let Ok(pid) = pid_str.parse::<u32>() {
-                            log::warn!("检测到孤立的 clash-core.exe 进程 PID={}，正在清理", pid);
-                            if let Err(e) = Self::force_kill_windows(pid) {
-                                log::error!("清理孤立进程 PID={} 失败: {}", pid, e);
-                            }
-                        }
-                    }
-                }
+        while start.elapsed() < grace {
+            let elapsed = start.elapsed();
+            let check_interval = if elapsed > Duration::from_secs(1) {
+                Duration::from_millis(200)
+            } else if elapsed > Duration::from_millis(300) {
+                Duration::from_millis(100)
+            } else {
+                Duration::from_millis(50)
+            };
+            std::thread::sleep(check_interval.min(grace - elapsed));
+
+            if has_exited() {
+                return true;
             }
+        }
 
-            Ok(())
+        false
+    }
+
+    // 强制终止指定进程（按平台选择终止方式）
+    fn force_kill(pid: u32, grace: Duration) -> Result<KillOutcome, String> {
+        #[cfg(windows)]
+        {
+            Self::force_kill_windows(pid, grace)
         }
 
         #[cfg(unix)]
         {
-            // 使用 pgrep 查找 clash-core 进程
-            let output = Command::new("pgrep")
-                .arg("clash-core")
-                .stdout(Stdio::piped())
-                .output()
-                .map_err(|e| format!("执行 pgrep 失败: {}", e))?;
-
-            // pgrep 返回 0 表示找到进程，1 表示未找到
-            if output.status.code() == Some(1) {
-                // 未找到进程，正常情况
-                return Ok(());
-            }
+            Self::force_kill_unix(pid, grace)
+        }
+    }
 
-            if !output.status.success() {
-                return Err(format!("pgrep 执行失败: 退出码 {:?}", output.status.code()));
+    // 检查并清理孤立的 Clash 核心进程
+    // 应用场景：处理心跳超时清理失败导致的僵尸进程（进程句柄丢失但进程仍在后台运行）
+    fn cleanup_orphan_processes() -> Result<(), String> {
+        for core in super::orphan::find_core_processes()? {
+            log::warn!("检测到孤立的 clash-core 进程 PID={}，正在清理", core.pid);
+            if let Err(e) = Self::force_kill(core.pid, DEFAULT_KILL_GRACE) {
+                log::error!("清理孤立进程 PID={} 失败: {}", core.pid, e);
             }
+        }
 
-            let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(())
+    }
 
-            // 每行是一个 PID
-            for line in stdout.lines() {
-                if let Ok(pid) = line.trim().parse::<u32>() {
-                    log::warn!("检测到孤立的 clash-core 进程 PID={}，正在清理", pid);
-                    if let Err(e) = Self::force_kill_unix(pid) {
-                        log::error!("清理孤立进程 PID={} 失败: {}", pid, e);
-                    }
-                }
+    // 列出孤立的核心进程（排除当前托管的核心）
+    pub fn list_orphan_cores(&self) -> Result<Vec<OrphanCore>, String> {
+        let managed_pid = self.get_status().pid;
+        let cores = super::orphan::find_core_processes()?;
+        Ok(cores
+            .into_iter()
+            .filter(|core| Some(core.pid) != managed_pid)
+            .collect())
+    }
+
+    // 强制终止选中的孤立核心进程，返回成功终止的 PID 与失败原因
+    // 仅允许终止仍被识别为孤立核心的 PID，避免通过 IPC 终止任意进程
+    pub fn kill_orphan_cores(&self, pids: &[u32]) -> Result<(Vec<u32>, Vec<String>), String> {
+        let orphans = self.list_orphan_cores()?;
+        let mut killed = Vec::new();
+        let mut failures = Vec::new();
+
+        for &pid in pids {
+            if !orphans.iter().any(|core| core.pid == pid) {
+                failures.push(format!("PID={} 不是孤立的核心进程", pid));
+                continue;
             }
 
-            Ok(())
+            log::warn!("按请求清理孤立的 clash-core 进程 PID={}", pid);
+            match Self::force_kill(pid, DEFAULT_KILL_GRACE) {
+                Ok(_) => killed.push(pid),
+                Err(e) => failures.push(format!("PID={}: {}", pid, e)),
+            }
         }
+
+        Ok((killed, failures))
     }
 
     // 停止 Clash 核心（改进版：带强制清理）
     pub fn stop(&mut self) -> Result<(), String> {
+        self.stop_with_grace(STOP_KILL_GRACE)
+    }
+
+    // 停止 Clash 核心，先请求安全退出，超过宽限期后强制终止
+    pub fn stop_with_grace(&mut self, grace: Duration) -> Result<(), String> {
         let mut child_guard = self.child.lock().unwrap_or_else(|e| {
             log::warn!("Child 锁中毒，正在恢复");
             e.into_inner()
@@ -332,66 +532,39 @@ impl ClashManager {
 
         if let Some(mut child) = child_guard.take() {
             let pid = child.id();
-            log::info!("停止 Clash 核心 (PID: {})", pid);
-
-            // 先尝试优雅停止
-            match child.kill() {
-                Ok(_) => {
-                    log::debug!("已发送 kill 信号到 PID={}", pid);
-
-                    // 在子线程中等待，避免阻塞
-                    let wait_handle = std::thread::spawn(move || child.wait());
-
-                    // 等待最多 3 秒
-                    let wait_result = wait_handle.join_timeout(std::time::Duration::from_secs(3));
-
-                    match wait_result {
-                        Ok(Ok(Ok(_))) => {
-                            log::info!("Clash 核心已正常停止 (PID: {})", pid);
-                        }
-                        Ok(Ok(Err(e))) => {
-                            log::warn!("等待进程退出失败: {}, 尝试强制清理", e);
-                            #[cfg(windows)]
-                            {
-                                let _ = Self::force_kill_windows(pid);
-                            }
-                        }
-                        Ok(Err(_)) => {
-                            log::error!("等待进程超时 (3 秒)，强制清理 PID={}", pid);
-                            #[cfg(windows)]
-                            {
-                                let _ = Self::force_kill_windows(pid);
-                            }
-                        }
-                        Err(_) => {
-                            log::error!("等待进程线程 panic，尝试强制清理 PID={}", pid);
-                            #[cfg(windows)]
-                            {
-                                let _ = Self::force_kill_windows(pid);
-                            }
-                        }
-                    }
+            log::info!(
+                "停止 Clash 核心 (PID: {}，宽限期 {}ms)",
+                pid,
+                grace.as_millis()
+            );
+
+            // 子进程需通过 try_wait 回收，否则僵尸进程仍会被判定为存活
+            let has_exited = || !matches!(child.try_wait(), Ok(None));
+
+            #[cfg(unix)]
+            let result = Self::terminate_unix(pid, grace, has_exited);
+            #[cfg(windows)]
+            let result = Self::terminate_windows(pid, grace, has_exited);
+
+            match result {
+                Ok(KillOutcome::Exited) => {
+                    log::info!("Clash 核心已正常停止 (PID: {})", pid);
+                }
+                Ok(KillOutcome::Killed) => {
+                    log::warn!("Clash 核心未在宽限期内退出，已强制终止 (PID: {})", pid);
                 }
                 Err(e) => {
-                    log::error!("发送 kill 信号失败: {}, 尝试强制清理", e);
-                    #[cfg(windows)]
-                    {
-                        let _ = Self::force_kill_windows(pid);
-                    }
-                    #[cfg(not(windows))]
-                    {
-                        let error_msg = format!(
-                            "停止 Clash 失败 (PID: {}): {}\n{}",
-                            pid,
-                            e,
-                            Self::format_io_error_hint(&e)
-                        );
-                        log::error!("{}", error_msg);
-                        return Err(error_msg);
-                    }
+                    let error_msg = format!("停止 Clash 失败 (PID: {}): {}", pid, e);
+                    log::error!("{}", error_msg);
+                    return Err(error_msg);
                 }
             }
 
+            // 回收子进程（进程已退出，不会阻塞）
+            if let Err(e) = child.try_wait() {
+                log::warn!("回收 Clash 进程失败 (PID: {}): {}", pid, e);
+            }
+
             // 清空状态
             *self.start_time.lock().unwrap_or_else(|e| {
                 log::warn!("StartTime 锁中毒，正在恢复");
@@ -424,6 +597,24 @@ impl ClashManager {
                     };
                     log::warn!("Clash 进程已退出 (PID: {}, {})", pid, exit_info);
 
+                    // StopClash 会先取走子进程句柄，这里检测到的退出均为意外退出
+                    publish_event(core_exited_event(status, self.output.snapshot()));
+                    kill_switch::on_core_stopped();
+
+                    let is_crash_loop = self
+                        .crashes
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .record_crash(Instant::now());
+                    if is_crash_loop {
+                        log::warn!(
+                            "核心在 {} 秒内意外退出 {} 次，将以安全模式重启",
+                            safe_mode::CRASH_WINDOW.as_secs(),
+                            safe_mode::CRASH_THRESHOLD
+                        );
+                        self.is_safe_mode_pending.store(true, Ordering::SeqCst);
+                    }
+
                     *child_guard = None;
                     *self.start_time.lock().unwrap_or_else(|e| {
                         log::warn!("StartTime 锁中毒，正在恢复");
@@ -453,6 +644,70 @@ impl ClashManager {
         }
     }
 
+    // 当前核心使用的配置文件
+    pub fn config_path(&self) -> Option<&str> {
+        self.config_path.as_deref()
+    }
+
+    // 沿用上次启动的核心路径、数据目录与启动参数，以新的配置文件重启核心
+    pub fn restart_with_config(&mut self, config_path: String) -> Result<(), StartError> {
+        let (Some(core_path), Some(data_dir)) = (self.core_path.clone(), self.data_dir.clone())
+        else {
+            return Err(StartError::Other(
+                "核心尚未启动过，无法切换配置".to_string(),
+            ));
+        };
+        self.start(
+            core_path,
+            config_path,
+            data_dir,
+            self.external_controller.clone(),
+            self.ports,
+            self.extra_args.clone(),
+        )
+    }
+
+    // 取出待执行的安全模式重启请求
+    pub fn take_safe_mode_request(&self) -> bool {
+        self.is_safe_mode_pending.swap(false, Ordering::SeqCst)
+    }
+
+    // 关闭 TUN 后以派生配置重启核心，返回安全模式使用的配置路径
+    pub fn restart_in_safe_mode(&mut self) -> Result<String, StartError> {
+        let config_path = self
+            .config_path
+            .clone()
+            .ok_or_else(|| StartError::Other("核心尚未启动过，无法进入安全模式".to_string()))?;
+        let safe_config_path =
+            safe_mode::write_safe_mode_config(&config_path).map_err(StartError::Other)?;
+        self.restart_with_config(safe_config_path.clone())?;
+        Ok(safe_config_path)
+    }
+
+    // 最近一次启动核心时的数据目录
+    pub fn data_dir(&self) -> Option<&str> {
+        self.data_dir.as_deref()
+    }
+
+    // 获取核心控制器 IPC 路径（从当前配置文件读取）
+    pub fn controller_path(&self) -> Option<String> {
+        self.config_path
+            .as_deref()
+            .and_then(super::controller::read_controller_path)
+    }
+
+    // 获取核心本地代理端口（启动时的覆盖优先，否则读取配置文件）
+    pub fn proxy_port(&self) -> Option<u16> {
+        self.ports
+            .mixed_port
+            .or_else(|| self.config_path.as_deref().and_then(read_proxy_port))
+    }
+
+    // 获取核心最近的 stdout/stderr 输出（核心退出后保留到下次启动）
+    pub fn recent_core_output(&self) -> Vec<String> {
+        self.output.snapshot()
+    }
+
     // 获取 Clash 状态（不需要可变引用，支持并发读）
     pub fn get_status(&self) -> ClashStatus {
         let running = self.is_running();
@@ -490,6 +745,93 @@ impl ClashManager {
         }
     }
 
+    // 启动前检查：核心与配置文件存在、核心路径通过策略校验、入站端口未被占用，
+    // 通过时返回用于启动的核心真实路径
+    fn check_launch(
+        core_path: &str,
+        config_path: &str,
+        ports: &PortOverrides,
+        check_policy: impl FnOnce(&Path) -> Result<PathBuf, String>,
+    ) -> Result<PathBuf, StartError> {
+        if !Path::new(core_path).exists() {
+            log::error!(
+                "Clash 核心文件不存在\n路径: {}\n提示: 请检查核心文件是否正确安装",
+                core_path
+            );
+            return Err(StartError::CoreNotFound(core_path.to_string()));
+        }
+
+        let resolved_core_path = check_policy(Path::new(core_path))
+            .inspect_err(|e| log::error!("{}", e))
+            .map_err(StartError::PermissionDenied)?;
+
+        if !Path::new(config_path).exists() {
+            log::error!(
+                "配置文件不存在\n路径: {}\n提示: 请检查配置文件是否正确生成",
+                config_path
+            );
+            return Err(StartError::ConfigNotFound(config_path.to_string()));
+        }
+
+        // 覆盖端口优先，否则使用配置中的代理端口
+        let inbound_ports = [
+            ports.mixed_port.or_else(|| read_proxy_port(config_path)),
+            ports.socks_port,
+        ];
+        if let Some(port) = inbound_ports
+            .into_iter()
+            .flatten()
+            .find(|port| !is_port_bindable(*port))
+        {
+            log::error!(
+                "端口 {} 已被占用\n可能原因：\n1. 其他代理程序正在使用该端口\n2. 残留的核心进程未退出\n提示: 请更换端口或关闭占用端口的程序",
+                port
+            );
+            return Err(StartError::PortInUse(port));
+        }
+
+        Ok(resolved_core_path)
+    }
+
+    // 创建核心进程，没有执行权限时单独归类
+    fn spawn_core(
+        core_path: &Path,
+        args: &[String],
+        priority: CorePriority,
+    ) -> Result<Child, StartError> {
+        let mut command = Command::new(core_path);
+        command
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        // 在子进程 exec 前设置 nice 值，核心此后创建的线程都会继承
+        #[cfg(unix)]
+        if priority != CorePriority::Normal {
+            use std::os::unix::process::CommandExt;
+            let nice = priority.nice_value();
+            unsafe {
+                command.pre_exec(move || {
+                    // 失败时核心以默认优先级运行，由 apply_priority 记录警告
+                    libc::setpriority(libc::PRIO_PROCESS as _, 0, nice);
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = priority;
+
+        command.spawn().map_err(|e| {
+            log::error!("{}", Self::format_io_error_hint(&e));
+            match e.kind() {
+                std::io::ErrorKind::PermissionDenied => {
+                    StartError::PermissionDenied(format!("{} ({})", core_path.display(), e))
+                }
+                _ => StartError::SpawnFailed(e),
+            }
+        })
+    }
+
     // 格式化 IO 错误提示
     fn format_io_error_hint(e: &std::io::Error) -> String {
         use std::io::ErrorKind;
@@ -531,31 +873,162 @@ impl Drop for ClashManager {
     }
 }
 
-// 扩展 JoinHandle 以支持超时
-trait JoinHandleExt<T> {
-    fn join_timeout(
-        self,
-        duration: std::time::Duration,
-    ) -> Result<Result<T, Box<dyn std::any::Any + Send>>, ()>;
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl<T> JoinHandleExt<T> for std::thread::JoinHandle<T> {
-    fn join_timeout(
-        self,
-        duration: std::time::Duration,
-    ) -> Result<Result<T, Box<dyn std::any::Any + Send>>, ()> {
-        let start = std::time::Instant::now();
+    fn allow_policy(path: &Path) -> Result<PathBuf, String> {
+        Ok(path.to_path_buf())
+    }
 
-        loop {
-            if self.is_finished() {
-                return Ok(self.join());
-            }
+    #[test]
+    fn test_check_launch_errors() {
+        let dir = std::env::temp_dir().join(format!("stelliberty-start-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let core = dir.join("clash-core");
+        std::fs::write(&core, b"#!/bin/sh\n").unwrap();
+        let core_path = core.to_string_lossy().into_owned();
+        let config = dir.join("config.yaml");
+        let config_path = config.to_string_lossy().into_owned();
+        let missing = dir.join("missing").to_string_lossy().into_owned();
+        let no_override = PortOverrides::default();
+
+        let result = ClashManager::check_launch(&missing, &config_path, &no_override, allow_policy);
+        assert!(matches!(result, Err(StartError::CoreNotFound(_))));
+
+        let result = ClashManager::check_launch(&core_path, &config_path, &no_override, |_| {
+            Err("核心路径不符合策略".to_string())
+        });
+        assert!(matches!(result, Err(StartError::PermissionDenied(_))));
+
+        let result = ClashManager::check_launch(&core_path, &missing, &no_override, allow_policy);
+        assert!(matches!(result, Err(StartError::ConfigNotFound(_))));
+
+        // 配置中的 mixed-port 已被占用
+        let occupied = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = occupied.local_addr().unwrap().port();
+        std::fs::write(&config, format!("mixed-port: {}\n", port)).unwrap();
+        let result =
+            ClashManager::check_launch(&core_path, &config_path, &no_override, allow_policy);
+        assert!(matches!(result, Err(StartError::PortInUse(p)) if p == port));
+        assert_eq!(StartError::PortInUse(port).code(), 1103);
+
+        // 覆盖端口可用时不再检查配置中的端口
+        let free_port = crate::clash::ports::suggest_free_ports(1).unwrap()[0];
+        let overrides = PortOverrides {
+            mixed_port: Some(free_port),
+            socks_port: None,
+        };
+        let result = ClashManager::check_launch(&core_path, &config_path, &overrides, allow_policy);
+        assert_eq!(result.unwrap(), core);
 
-            if start.elapsed() >= duration {
-                return Err(());
-            }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-            std::thread::sleep(std::time::Duration::from_millis(100));
-        }
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_core_errors() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("stelliberty-spawn-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let core = dir.join("clash-core");
+
+        // 没有执行权限
+        std::fs::write(&core, b"#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&core, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let result = ClashManager::spawn_core(&core, &[], CorePriority::Normal);
+        assert!(matches!(result, Err(StartError::PermissionDenied(_))));
+
+        // 可执行但不是有效的程序格式
+        std::fs::write(&core, [0u8, 1, 2, 3]).unwrap();
+        std::fs::set_permissions(&core, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let result = ClashManager::spawn_core(&core, &[], CorePriority::Normal);
+        assert!(matches!(result, Err(StartError::SpawnFailed(_))));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_core_sets_nice_before_exec() {
+        let mut child = ClashManager::spawn_core(
+            Path::new("/bin/sleep"),
+            &["5".to_string()],
+            CorePriority::Low,
+        )
+        .unwrap();
+        let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS as _, child.id() as libc::id_t) };
+        let _ = child.kill();
+        let _ = child.wait();
+        assert_eq!(nice, CorePriority::Low.nice_value());
+    }
+
+    #[test]
+    fn test_start_rejects_managed_extra_args() {
+        let mut manager = ClashManager::new();
+        let result = manager.start(
+            "/nonexistent/clash-core".to_string(),
+            "/nonexistent/config.yaml".to_string(),
+            "/nonexistent".to_string(),
+            String::new(),
+            PortOverrides::default(),
+            vec!["-d".to_string(), "/tmp".to_string()],
+        );
+        let error = result.unwrap_err();
+        assert!(matches!(error, StartError::InvalidArguments(_)));
+        assert_eq!(error.code(), 1106);
+
+        // 参数合法时按文件检查结果分类
+        let error = manager
+            .start(
+                "/nonexistent/clash-core".to_string(),
+                "/nonexistent/config.yaml".to_string(),
+                "/nonexistent".to_string(),
+                String::new(),
+                PortOverrides::default(),
+                Vec::new(),
+            )
+            .unwrap_err();
+        assert!(matches!(error, StartError::CoreNotFound(_)));
+    }
+
+    #[test]
+    fn test_wait_for_exit_times_out() {
+        let start = Instant::now();
+        assert!(!ClashManager::wait_for_exit(
+            Duration::from_millis(200),
+            || false
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_long_grace_avoids_sigkill() {
+        use std::os::unix::process::ExitStatusExt;
+
+        // 收到 SIGTERM 后 1.5 秒才退出的进程
+        let Ok(mut child) = Command::new("sh")
+            .args([
+                "-c",
+                "trap 'sleep 1.5; exit 0' TERM; while true; do sleep 0.1; done",
+            ])
+            .spawn()
+        else {
+            return;
+        };
+        // 等待 shell 注册 trap
+        std::thread::sleep(Duration::from_millis(200));
+
+        let pid = child.id();
+        let outcome = ClashManager::terminate_unix(pid, Duration::from_secs(3), || {
+            !matches!(child.try_wait(), Ok(None))
+        });
+        assert_eq!(outcome, Ok(KillOutcome::Exited));
+
+        // 未被 SIGKILL 终止
+        let status = child.wait().map(|s| s.signal());
+        assert!(matches!(status, Ok(None)));
     }
 }
@@ -0,0 +1,247 @@
+// 孤立核心进程探测：列出系统中残留的 clash-core 进程及其命令行
+
+use crate::ipc::protocol::OrphanCore;
+use std::process::{Command, Stdio};
+
+// 核心进程名（不含扩展名）
+const CORE_PROCESS_NAME: &str = "clash-core";
+
+// 查找系统中所有 clash-core 进程
+#[cfg(windows)]
+pub fn find_core_processes() -> Result<Vec<OrphanCore>, String> {
+    // 优先通过 CIM 获取完整命令行，失败时回退到 tasklist（仅有映像名）
+    let cim_query = format!(
+        "Get-CimInstance Win32_Process -Filter \"Name='{}.exe'\" | \
+         Select-Object ProcessId,CommandLine | ConvertTo-Csv -NoTypeInformation",
+        CORE_PROCESS_NAME
+    );
+    let cim_output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &cim_query])
+        .stdout(Stdio::piped())
+        .output();
+
+    if let Ok(output) = cim_output
+        && output.status.success()
+    {
+        return Ok(parse_cim_output(&String::from_utf8_lossy(&output.stdout)));
+    }
+
+    log::debug!("CIM 查询核心进程失败，回退到 tasklist");
+    let output = Command::new("tasklist")
+        .args([
+            "/FI",
+            &format!("IMAGENAME eq {}.exe", CORE_PROCESS_NAME),
+            "/FO",
+            "CSV",
+            "/NH",
+        ])
+        .stdout(Stdio::piped())
+        .output()
+        .map_err(|e| format!("执行 tasklist 失败: {}", e))?;
+
+    if !output.status.success() {
+        return Err("tasklist 执行失败".to_string());
+    }
+
+    Ok(parse_tasklist_output(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+// 查找系统中所有 clash-core 进程
+#[cfg(unix)]
+pub fn find_core_processes() -> Result<Vec<OrphanCore>, String> {
+    // comm 作为唯一的输出列可以完整保留含空格的路径，用于识别核心；
+    // args 的首个空白分隔段无法区分路径中的空格与参数，仅用于展示
+    let comm_output = run_ps("pid=,comm=")?;
+    let args_output = run_ps("pid=,args=")?;
+    Ok(parse_ps_output(&comm_output, &args_output))
+}
+
+#[cfg(unix)]
+fn run_ps(format: &str) -> Result<String, String> {
+    let output = Command::new("ps")
+        .args(["-A", "-o", format])
+        .stdout(Stdio::piped())
+        .output()
+        .map_err(|e| format!("执行 ps 失败: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("ps 执行失败: 退出码 {:?}", output.status.code()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// 判断可执行文件路径是否为核心程序
+pub(crate) fn is_core_executable(executable: &str) -> bool {
+    let file_name = executable
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(executable)
+        .to_ascii_lowercase();
+    file_name == CORE_PROCESS_NAME || file_name == format!("{}.exe", CORE_PROCESS_NAME)
+}
+
+// 拆分 ps 输出行：  1234 <剩余内容>
+#[cfg(any(unix, test))]
+fn split_ps_line(line: &str) -> Option<(u32, &str)> {
+    let (pid, rest) = line.trim().split_once(char::is_whitespace)?;
+    Some((pid.parse().ok()?, rest.trim()))
+}
+
+// 解析 ps 输出：按 comm（"pid=,comm="，可执行文件名或完整路径）识别核心进程，
+// 命令行取自 args（"pid=,args="）；两次调用之间退出的进程以 comm 代替命令行
+#[cfg(any(unix, test))]
+fn parse_ps_output(comm_stdout: &str, args_stdout: &str) -> Vec<OrphanCore> {
+    let command_lines = args_stdout
+        .lines()
+        .filter_map(split_ps_line)
+        .collect::<std::collections::HashMap<_, _>>();
+
+    comm_stdout
+        .lines()
+        .filter_map(split_ps_line)
+        .filter(|(_, executable)| is_core_executable(executable))
+        .map(|(pid, executable)| OrphanCore {
+            pid,
+            command_line: command_lines
+                .get(&pid)
+                .copied()
+                .unwrap_or(executable)
+                .to_string(),
+        })
+        .collect()
+}
+
+// 解析 tasklist CSV 输出："clash-core.exe","1234","Console","1","12,345 K"
+// tasklist 不提供命令行，使用映像名代替
+#[cfg(any(windows, test))]
+fn parse_tasklist_output(stdout: &str) -> Vec<OrphanCore> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let fields = split_csv_line(line);
+            let image_name = fields.first()?;
+            if !is_core_executable(image_name) {
+                return None;
+            }
+            let pid = fields.get(1)?.parse::<u32>().ok()?;
+            Some(OrphanCore {
+                pid,
+                command_line: image_name.clone(),
+            })
+        })
+        .collect()
+}
+
+// 解析 CIM 查询的 CSV 输出（首行为表头 "ProcessId","CommandLine"）
+#[cfg(any(windows, test))]
+fn parse_cim_output(stdout: &str) -> Vec<OrphanCore> {
+    stdout
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = split_csv_line(line);
+            let pid = fields.first()?.parse::<u32>().ok()?;
+            let command_line = fields.get(1).cloned().unwrap_or_default();
+            Some(OrphanCore { pid, command_line })
+        })
+        .collect()
+}
+
+// 拆分一行 CSV（支持引号包裹与 "" 转义）
+#[cfg(any(windows, test))]
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    fields.push(current);
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ps_output() {
+        let comm = "    1 init\n\
+                    4321 clash-core\n\
+                    4400 grep\n\
+                    4500 clash-core-helper\n";
+        let args = "    1 /sbin/init\n\
+                    4321 /opt/stelliberty/clash-core -d /data -f /data/config.yaml\n\
+                    4400 grep clash-core\n\
+                    4500 /usr/bin/clash-core-helper\n";
+        let cores = parse_ps_output(comm, args);
+        assert_eq!(
+            cores,
+            vec![OrphanCore {
+                pid: 4321,
+                command_line: "/opt/stelliberty/clash-core -d /data -f /data/config.yaml"
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_ps_output_path_with_spaces() {
+        // macOS 的 comm 为完整路径，安装目录可能包含空格
+        let comm = "  812 /Users/x/Library/Application Support/Stelliberty/clash-core\n\
+                    900 /Applications/Other App.app/Contents/MacOS/Other App\n";
+        let args = "  812 /Users/x/Library/Application Support/Stelliberty/clash-core -d /data\n\
+                    900 /Applications/Other App.app/Contents/MacOS/Other App\n";
+        let cores = parse_ps_output(comm, args);
+        assert_eq!(
+            cores,
+            vec![OrphanCore {
+                pid: 812,
+                command_line:
+                    "/Users/x/Library/Application Support/Stelliberty/clash-core -d /data"
+                        .to_string(),
+            }]
+        );
+
+        // 两次 ps 之间进程已退出：以 comm 代替命令行
+        let cores = parse_ps_output("  812 clash-core\n", "");
+        assert_eq!(cores[0].command_line, "clash-core");
+    }
+
+    #[test]
+    fn test_parse_tasklist_output() {
+        let stdout = "\"clash-core.exe\",\"1234\",\"Services\",\"0\",\"12,345 K\"\r\n\
+                      \"explorer.exe\",\"888\",\"Console\",\"1\",\"99,000 K\"\r\n";
+        let cores = parse_tasklist_output(stdout);
+        assert_eq!(cores.len(), 1);
+        assert_eq!(cores[0].pid, 1234);
+        assert_eq!(cores[0].command_line, "clash-core.exe");
+    }
+
+    #[test]
+    fn test_parse_cim_output() {
+        let stdout = "\"ProcessId\",\"CommandLine\"\r\n\
+                      \"5678\",\"\"\"C:\\Program Files\\Stelliberty\\clash-core.exe\"\" -d C:\\data\"\r\n";
+        let cores = parse_cim_output(stdout);
+        assert_eq!(cores.len(), 1);
+        assert_eq!(cores[0].pid, 5678);
+        assert_eq!(
+            cores[0].command_line,
+            "\"C:\\Program Files\\Stelliberty\\clash-core.exe\" -d C:\\data"
+        );
+    }
+}
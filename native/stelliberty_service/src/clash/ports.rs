@@ -0,0 +1,89 @@
+// 空闲端口建议：配置的端口被占用时，为 mixed/socks/控制器端口挑选当前可用的本地端口
+//
+// 逐个实际绑定再释放，比读取连接表更准确；探测范围避开系统临时端口段
+// （Linux 默认 32768 起，Windows 49152 起），以免建议的端口随即被出站连接占用。
+
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, UdpSocket};
+use std::ops::RangeInclusive;
+
+// 探测范围：从常用代理端口开始，止于 Linux 临时端口段之前
+pub const PORT_PROBE_RANGE: RangeInclusive<u16> = 7890..=32767;
+
+// 单次最多建议的端口数
+pub const MAX_SUGGESTED_PORTS: usize = 16;
+
+// 在默认范围内建议 count 个当前可绑定的端口
+pub fn suggest_free_ports(count: usize) -> Result<Vec<u16>, String> {
+    suggest_free_ports_in(PORT_PROBE_RANGE, count)
+}
+
+// 在指定范围内按顺序探测，返回 count 个互不相同的可绑定端口
+pub fn suggest_free_ports_in(range: RangeInclusive<u16>, count: usize) -> Result<Vec<u16>, String> {
+    if count == 0 || count > MAX_SUGGESTED_PORTS {
+        return Err(format!(
+            "端口数量无效: {}（应为 1-{}）",
+            count, MAX_SUGGESTED_PORTS
+        ));
+    }
+
+    let (start, end) = (*range.start(), *range.end());
+    let ports: Vec<u16> = range
+        .filter(|port| is_port_bindable(*port))
+        .take(count)
+        .collect();
+    if ports.len() < count {
+        return Err(format!(
+            "端口范围 {}-{} 内仅找到 {} 个可用端口（需要 {} 个）",
+            start,
+            end,
+            ports.len(),
+            count
+        ));
+    }
+    Ok(ports)
+}
+
+// 端口在 127.0.0.1 上能否同时绑定 TCP 与 UDP（mixed/socks 入站会使用 UDP 转发）
+pub fn is_port_bindable(port: u16) -> bool {
+    if port == 0 {
+        return false;
+    }
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    // 绑定成功后立即释放
+    TcpListener::bind(addr).is_ok() && UdpSocket::bind(addr).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggested_ports_are_bindable_and_distinct() {
+        let ports = suggest_free_ports(3).unwrap();
+        assert_eq!(ports.len(), 3);
+
+        let mut unique = ports.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), 3);
+
+        // 返回时仍可绑定，且不在临时端口段内
+        for port in ports {
+            assert!(PORT_PROBE_RANGE.contains(&port));
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).unwrap();
+            drop(listener);
+        }
+    }
+
+    #[test]
+    fn test_occupied_port_is_skipped() {
+        let occupied = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = occupied.local_addr().unwrap().port();
+        assert!(!is_port_bindable(port));
+
+        // 范围只有被占用的端口时报告不足
+        assert!(suggest_free_ports_in(port..=port, 1).is_err());
+        assert!(suggest_free_ports(0).is_err());
+        assert!(suggest_free_ports(MAX_SUGGESTED_PORTS + 1).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+// 启动前自检：在首次连接前检查核心程序、数据目录与运行权限，逐项给出结果
+//
+// 这些条件原本分散在 start/install 流程中，失败时只能看到核心启动报错；
+// 自检把每一项单独列出，主程序据此提示用户具体缺少什么。
+
+use super::core_policy::CorePolicy;
+use super::launch::{create_new_file, random_token};
+use crate::ipc::protocol::PreflightItem;
+use std::path::Path;
+
+// 自检项名称
+pub const ITEM_CORE_BINARY: &str = "core_binary";
+pub const ITEM_DATA_DIR: &str = "data_dir";
+pub const ITEM_PRIVILEGES: &str = "privileges";
+
+// TUN 与透明代理需要的 Linux capability（位序号见 linux/capability.h）
+#[cfg(any(target_os = "linux", test))]
+const REQUIRED_CAPABILITIES: &[(u32, &str)] = &[
+    (10, "CAP_NET_BIND_SERVICE"),
+    (12, "CAP_NET_ADMIN"),
+    (13, "CAP_NET_RAW"),
+];
+
+// 环境探测接口（测试中可替换为固定结果）
+pub trait PreflightProbe {
+    // 核心程序存在且可执行，返回说明
+    fn core_binary(&self, core_path: &Path) -> Result<String, String>;
+    // 数据目录存在且可写
+    fn data_dir(&self, data_dir: &Path) -> Result<String, String>;
+    // 当前进程具备运行核心所需的权限
+    fn privileges(&self) -> Result<String, String>;
+}
+
+// 执行全部自检项，顺序固定
+pub fn run_checks(
+    probe: &impl PreflightProbe,
+    core_path: &str,
+    data_dir: &str,
+) -> Vec<PreflightItem> {
+    let item = |name: &str, result: Result<String, String>| {
+        let (is_passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        PreflightItem {
+            name: name.to_string(),
+            is_passed,
+            detail,
+        }
+    };
+
+    vec![
+        item(ITEM_CORE_BINARY, probe.core_binary(Path::new(core_path))),
+        item(ITEM_DATA_DIR, probe.data_dir(Path::new(data_dir))),
+        item(ITEM_PRIVILEGES, probe.privileges()),
+    ]
+}
+
+// 基于真实系统环境的探测
+pub struct SystemProbe {
+    // 是否在服务进程中运行（服务会额外校验核心路径策略）
+    pub is_service: bool,
+}
+
+impl PreflightProbe for SystemProbe {
+    fn core_binary(&self, core_path: &Path) -> Result<String, String> {
+        let metadata = std::fs::metadata(core_path)
+            .map_err(|e| format!("核心程序不存在: {} ({})", core_path.display(), e))?;
+        if !metadata.is_file() {
+            return Err(format!("核心路径不是文件: {}", core_path.display()));
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if metadata.permissions().mode() & 0o111 == 0 {
+                return Err(format!("核心程序没有执行权限: {}", core_path.display()));
+            }
+        }
+
+        if self.is_service {
+            CorePolicy::load().and_then(|policy| policy.check(core_path))?;
+        }
+        Ok(core_path.display().to_string())
+    }
+
+    fn data_dir(&self, data_dir: &Path) -> Result<String, String> {
+        if !data_dir.is_dir() {
+            return Err(format!("数据目录不存在: {}", data_dir.display()));
+        }
+
+        // 实际创建一次，只读挂载或 ACL 限制无法通过元数据判断。数据目录由 IPC 客户端指定，
+        // 使用随机文件名独占创建（不跟随符号链接），不会覆盖目录中的已有文件
+        let probe_file = data_dir.join(format!(".preflight-{}", random_token()));
+        create_new_file(&probe_file)
+            .map_err(|e| format!("数据目录不可写: {} ({})", data_dir.display(), e))?;
+        let _ = std::fs::remove_file(&probe_file);
+        Ok(data_dir.display().to_string())
+    }
+
+    fn privileges(&self) -> Result<String, String> {
+        #[cfg(windows)]
+        {
+            use windows::Win32::UI::Shell::IsUserAnAdmin;
+            if unsafe { IsUserAnAdmin().as_bool() } {
+                Ok("以管理员权限运行".to_string())
+            } else {
+                Err("缺少管理员权限，TUN 模式与服务模式不可用".to_string())
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let status = std::fs::read_to_string("/proc/self/status")
+                .map_err(|e| format!("无法读取进程权限信息: {}", e))?;
+            let cap_eff = parse_cap_eff(&status).ok_or("无法解析 CapEff".to_string())?;
+            let missing = missing_capabilities(cap_eff);
+            if missing.is_empty() {
+                Ok("已具备网络管理权限".to_string())
+            } else {
+                Err(format!("缺少 capability: {}", missing.join(", ")))
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            if unsafe { libc::geteuid() } == 0 {
+                Ok("以 root 权限运行".to_string())
+            } else {
+                Err("缺少 root 权限，TUN 模式不可用".to_string())
+            }
+        }
+
+        #[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+        {
+            Err("当前平台不支持权限检查".to_string())
+        }
+    }
+}
+
+// 从 /proc/self/status 中读取有效 capability 集合
+#[cfg(any(target_os = "linux", test))]
+fn parse_cap_eff(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|value| u64::from_str_radix(value.trim(), 16).ok())
+}
+
+// 返回缺少的 capability 名称
+#[cfg(any(target_os = "linux", test))]
+fn missing_capabilities(cap_eff: u64) -> Vec<&'static str> {
+    REQUIRED_CAPABILITIES
+        .iter()
+        .filter(|(bit, _)| cap_eff & (1 << bit) == 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeProbe {
+        core_binary: Result<String, String>,
+        data_dir: Result<String, String>,
+        privileges: Result<String, String>,
+    }
+
+    impl PreflightProbe for FakeProbe {
+        fn core_binary(&self, _core_path: &Path) -> Result<String, String> {
+            self.core_binary.clone()
+        }
+
+        fn data_dir(&self, _data_dir: &Path) -> Result<String, String> {
+            self.data_dir.clone()
+        }
+
+        fn privileges(&self) -> Result<String, String> {
+            self.privileges.clone()
+        }
+    }
+
+    #[test]
+    fn test_run_checks_assembles_items() {
+        let probe = FakeProbe {
+            core_binary: Ok("/opt/clash-core".to_string()),
+            data_dir: Err("数据目录不可写".to_string()),
+            privileges: Err("缺少 capability: CAP_NET_ADMIN".to_string()),
+        };
+        let items = run_checks(&probe, "/opt/clash-core", "/var/lib/stelliberty");
+
+        let summary: Vec<(&str, bool)> = items
+            .iter()
+            .map(|item| (item.name.as_str(), item.is_passed))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ITEM_CORE_BINARY, true),
+                (ITEM_DATA_DIR, false),
+                (ITEM_PRIVILEGES, false),
+            ]
+        );
+        assert_eq!(items[1].detail, "数据目录不可写");
+    }
+
+    #[test]
+    fn test_system_probe_files() {
+        let dir =
+            std::env::temp_dir().join(format!("stelliberty-preflight-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let core = dir.join("clash-core");
+        std::fs::write(&core, b"#!/bin/sh\n").unwrap();
+
+        let probe = SystemProbe { is_service: false };
+        assert!(probe.data_dir(&dir).is_ok());
+        assert!(probe.data_dir(&dir.join("missing")).is_err());
+        assert!(probe.core_binary(&dir.join("missing")).is_err());
+        assert!(probe.core_binary(&dir).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&core, std::fs::Permissions::from_mode(0o644)).unwrap();
+            assert!(probe.core_binary(&core).is_err());
+            std::fs::set_permissions(&core, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        assert!(probe.core_binary(&core).is_ok());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_missing_capabilities() {
+        let status = "Name:\tstelliberty\nCapInh:\t0000000000000000\nCapEff:\t0000000000003000\n";
+        let cap_eff = parse_cap_eff(status).unwrap();
+        assert_eq!(missing_capabilities(cap_eff), vec!["CAP_NET_BIND_SERVICE"]);
+        assert!(missing_capabilities(u64::MAX).is_empty());
+        assert!(parse_cap_eff("Name:\tx\n").is_none());
+    }
+}
@@ -0,0 +1,130 @@
+// 安全模式：核心在短时间内反复意外退出（常见于错误的 TUN 配置）时，
+// 关闭 TUN 后以派生配置重新启动一次核心，并通知主程序关闭系统代理，
+// 让用户至少能回到可用状态修正配置，而不是不断重启进入同一个错误。
+//
+// 服务本身不会自动重启崩溃的核心：核心意外退出后由主程序重新发送 StartClash 拉起，
+// 因此只有主程序反复重启核心时崩溃次数才会累计。达到阈值后由服务自行以安全模式重启一次。
+
+use super::launch::write_derived_config;
+use serde_yaml_ng::Value as YamlValue;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// 触发安全模式的崩溃次数
+pub const CRASH_THRESHOLD: usize = 3;
+
+// 统计崩溃次数的时间窗口
+pub const CRASH_WINDOW: Duration = Duration::from_secs(120);
+
+// 安全模式派生配置的文件名前缀
+const SAFE_MODE_KIND: &str = "safe";
+
+// 记录最近的意外退出时间，判断是否需要进入安全模式
+#[derive(Debug, Default)]
+pub struct CrashTracker {
+    crashes: VecDeque<Instant>,
+}
+
+impl CrashTracker {
+    // 记录一次意外退出，窗口内次数达到阈值时返回 true 并清空记录，
+    // 安全模式下再次连续崩溃需要重新计满才会再次触发
+    pub fn record_crash(&mut self, now: Instant) -> bool {
+        while self
+            .crashes
+            .front()
+            .is_some_and(|crash| now.saturating_duration_since(*crash) > CRASH_WINDOW)
+        {
+            self.crashes.pop_front();
+        }
+        self.crashes.push_back(now);
+
+        if self.crashes.len() >= CRASH_THRESHOLD {
+            self.crashes.clear();
+            true
+        } else {
+            false
+        }
+    }
+
+    // 窗口内的崩溃次数
+    pub fn recent_crashes(&self) -> usize {
+        self.crashes.len()
+    }
+}
+
+// 关闭配置中的 TUN
+pub fn disable_tun(config: &mut YamlValue) {
+    if let Some(tun) = config.get_mut("tun").and_then(YamlValue::as_mapping_mut) {
+        tun.insert("enable".into(), false.into());
+    }
+}
+
+// 生成关闭 TUN 的派生配置文件（写入服务私有目录），返回其路径
+pub fn write_safe_mode_config(config_path: &str) -> Result<String, String> {
+    let content = std::fs::read_to_string(config_path)
+        .map_err(|e| format!("读取配置文件失败: {} ({})", config_path, e))?;
+    let mut config: YamlValue =
+        serde_yaml_ng::from_str(&content).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    disable_tun(&mut config);
+
+    let output =
+        serde_yaml_ng::to_string(&config).map_err(|e| format!("序列化配置文件失败: {}", e))?;
+    let output_path = write_derived_config(SAFE_MODE_KIND, &output)
+        .map_err(|e| format!("写入安全模式配置失败: {}", e))?;
+    Ok(output_path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_threshold_triggers_safe_mode() {
+        let mut tracker = CrashTracker::default();
+        let start = Instant::now();
+
+        assert!(!tracker.record_crash(start));
+        assert!(!tracker.record_crash(start + Duration::from_secs(10)));
+        assert!(tracker.record_crash(start + Duration::from_secs(20)));
+        // 触发后重新计数
+        assert_eq!(tracker.recent_crashes(), 0);
+        assert!(!tracker.record_crash(start + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_spaced_crashes_do_not_trigger() {
+        let mut tracker = CrashTracker::default();
+        let start = Instant::now();
+
+        // 间隔超过窗口的崩溃不累计
+        for i in 0..5 {
+            assert!(
+                !tracker.record_crash(start + CRASH_WINDOW * i + Duration::from_secs(i as u64))
+            );
+        }
+        assert_eq!(tracker.recent_crashes(), 1);
+
+        // 窗口边界内的崩溃仍然累计
+        let mut tracker = CrashTracker::default();
+        assert!(!tracker.record_crash(start));
+        assert!(!tracker.record_crash(start + CRASH_WINDOW / 2));
+        assert!(!tracker.record_crash(start + CRASH_WINDOW + Duration::from_secs(1)));
+        assert!(tracker.record_crash(start + CRASH_WINDOW + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_safe_mode_config() {
+        let mut config: YamlValue =
+            serde_yaml_ng::from_str("mixed-port: 7890\ntun:\n  enable: true\n  stack: mixed\n")
+                .unwrap();
+        disable_tun(&mut config);
+        assert_eq!(config["tun"]["enable"], YamlValue::Bool(false));
+        assert_eq!(config["tun"]["stack"].as_str(), Some("mixed"));
+        assert_eq!(config["mixed-port"].as_u64(), Some(7890));
+
+        // 未配置 TUN 时保持不变
+        let mut config: YamlValue = serde_yaml_ng::from_str("mixed-port: 7890\n").unwrap();
+        disable_tun(&mut config);
+        assert!(config.get("tun").is_none());
+    }
+}
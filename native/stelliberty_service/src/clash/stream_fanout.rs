@@ -0,0 +1,262 @@
+// 控制器流式接口扇出
+//
+// 主程序与 CLI 工具可能同时订阅 /traffic、/logs。同类订阅共享一个到核心的上游连接，
+// 由服务把每一帧广播给所有订阅者，避免核心侧连接数随客户端增长。
+// 上游连接只由最后一个订阅者离开时关闭；上游结束（核心停止等）时所有订阅随之结束。
+
+use crate::ipc::protocol::ControllerStream;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+// 每个订阅者可缓冲的帧数，超出后跳过最旧的帧
+const FRAME_BUFFER_SIZE: usize = 256;
+
+// 服务全局的扇出注册表
+pub static FANOUT: LazyLock<StreamFanout> = LazyLock::new(StreamFanout::new);
+
+// 一路上游连接及其广播通道
+struct FanoutChannel {
+    // 区分先后建立的上游，避免旧上游结束时误删新通道
+    id: u64,
+    sender: broadcast::Sender<String>,
+    upstream: JoinHandle<()>,
+}
+
+// 流式接口 → 共享的上游连接
+#[derive(Clone, Default)]
+pub struct StreamFanout {
+    channels: Arc<Mutex<HashMap<ControllerStream, FanoutChannel>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl StreamFanout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<ControllerStream, FanoutChannel>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // 确保该流已有上游连接，没有时调用 connect 建立，返回是否新建
+    // connect 收到广播发送端，应通过 forward_frame 持续发送帧直到上游结束
+    pub fn ensure_upstream<F, Fut>(&self, stream: ControllerStream, connect: F) -> bool
+    where
+        F: FnOnce(broadcast::Sender<String>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut channels = self.lock();
+        if channels
+            .get(&stream)
+            .is_some_and(|channel| !channel.upstream.is_finished())
+        {
+            return false;
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, _) = broadcast::channel(FRAME_BUFFER_SIZE);
+        let upstream = connect(sender.clone());
+        let fanout = self.clone();
+        let upstream = tokio::spawn(async move {
+            upstream.await;
+            log::debug!("控制器流 {:?} 的上游连接已结束", stream);
+            fanout.remove(stream, id);
+        });
+
+        log::info!("已建立控制器流 {:?} 的上游连接", stream);
+        channels.insert(
+            stream,
+            FanoutChannel {
+                id,
+                sender,
+                upstream,
+            },
+        );
+        true
+    }
+
+    // 订阅已建立的上游连接，上游不存在时返回 None
+    pub fn subscribe(&self, stream: ControllerStream) -> Option<FanoutSubscription> {
+        let channels = self.lock();
+        let channel = channels.get(&stream)?;
+        Some(FanoutSubscription {
+            receiver: Some(channel.sender.subscribe()),
+            stream,
+            id: channel.id,
+            fanout: self.clone(),
+        })
+    }
+
+    // 向该流的所有订阅者推送一帧，返回收到的订阅者数量
+    pub fn publish(&self, stream: ControllerStream, frame: String) -> usize {
+        self.lock()
+            .get(&stream)
+            .and_then(|channel| channel.sender.send(frame).ok())
+            .unwrap_or(0)
+    }
+
+    // 该流当前的订阅者数量
+    pub fn subscriber_count(&self, stream: ControllerStream) -> usize {
+        self.lock()
+            .get(&stream)
+            .map(|channel| channel.sender.receiver_count())
+            .unwrap_or(0)
+    }
+
+    // 移除通道（仍是同一上游时）并关闭上游，订阅者随后收到 Closed
+    fn remove(&self, stream: ControllerStream, id: u64) {
+        let mut channels = self.lock();
+        if channels
+            .get(&stream)
+            .is_some_and(|channel| channel.id == id)
+            && let Some(channel) = channels.remove(&stream)
+        {
+            channel.upstream.abort();
+        }
+    }
+}
+
+// 向订阅者转发一帧，始终返回 true（继续读取上游）。
+// 上游先于首个订阅者建立，此时没有接收端、发送必然失败，不能据此结束上游
+pub fn forward_frame(sender: &broadcast::Sender<String>, frame: String) -> bool {
+    let _ = sender.send(frame);
+    true
+}
+
+// 单个订阅者，释放时若已无其他订阅者则关闭上游连接
+pub struct FanoutSubscription {
+    receiver: Option<broadcast::Receiver<String>>,
+    stream: ControllerStream,
+    id: u64,
+    fanout: StreamFanout,
+}
+
+impl FanoutSubscription {
+    // 接收下一帧
+    pub async fn recv(&mut self) -> Result<String, broadcast::error::RecvError> {
+        match self.receiver.as_mut() {
+            Some(receiver) => receiver.recv().await,
+            None => Err(broadcast::error::RecvError::Closed),
+        }
+    }
+}
+
+impl Drop for FanoutSubscription {
+    fn drop(&mut self) {
+        let mut channels = self.fanout.lock();
+        // 持锁释放接收端再检查数量，避免两个订阅者同时离开时都认为对方仍在
+        self.receiver.take();
+        let is_last = channels
+            .get(&self.stream)
+            .is_some_and(|channel| channel.id == self.id && channel.sender.receiver_count() == 0);
+        if is_last && let Some(channel) = channels.remove(&self.stream) {
+            log::info!("控制器流 {:?} 已无订阅者，关闭上游连接", self.stream);
+            channel.upstream.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    // 模拟上游：保持连接直到被关闭
+    fn pending_upstream(
+        connects: &Arc<AtomicUsize>,
+    ) -> impl FnOnce(broadcast::Sender<String>) -> std::future::Pending<()> {
+        let connects = connects.clone();
+        move |_sender| {
+            connects.fetch_add(1, Ordering::SeqCst);
+            std::future::pending()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_two_subscribers_share_upstream() {
+        let fanout = StreamFanout::new();
+        let connects = Arc::new(AtomicUsize::new(0));
+
+        assert!(fanout.ensure_upstream(ControllerStream::Traffic, pending_upstream(&connects)));
+        let mut first = fanout.subscribe(ControllerStream::Traffic).unwrap();
+        assert!(!fanout.ensure_upstream(ControllerStream::Traffic, pending_upstream(&connects)));
+        let mut second = fanout.subscribe(ControllerStream::Traffic).unwrap();
+        assert_eq!(connects.load(Ordering::SeqCst), 1);
+        assert_eq!(fanout.subscriber_count(ControllerStream::Traffic), 2);
+
+        let frame = r#"{"up":128,"down":2048}"#.to_string();
+        assert_eq!(fanout.publish(ControllerStream::Traffic, frame.clone()), 2);
+        assert_eq!(first.recv().await.unwrap(), frame);
+        assert_eq!(second.recv().await.unwrap(), frame);
+
+        // 其他流不受影响
+        assert!(fanout.subscribe(ControllerStream::Logs).is_none());
+        assert_eq!(fanout.publish(ControllerStream::Logs, "{}".to_string()), 0);
+    }
+
+    #[tokio::test]
+    async fn test_upstream_closed_with_last_subscriber() {
+        let fanout = StreamFanout::new();
+        let connects = Arc::new(AtomicUsize::new(0));
+
+        fanout.ensure_upstream(ControllerStream::Logs, pending_upstream(&connects));
+        let first = fanout.subscribe(ControllerStream::Logs).unwrap();
+        let second = fanout.subscribe(ControllerStream::Logs).unwrap();
+
+        drop(first);
+        assert_eq!(fanout.subscriber_count(ControllerStream::Logs), 1);
+        drop(second);
+        assert!(fanout.subscribe(ControllerStream::Logs).is_none());
+
+        // 重新订阅时建立新的上游连接
+        assert!(fanout.ensure_upstream(ControllerStream::Logs, pending_upstream(&connects)));
+        assert_eq!(connects.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_frame_before_first_subscriber_keeps_upstream() {
+        let fanout = StreamFanout::new();
+        let (frame_tx, mut frame_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+
+        fanout.ensure_upstream(ControllerStream::Traffic, move |sender| async move {
+            while let Some(frame) = frame_rx.recv().await {
+                forward_frame(&sender, frame);
+            }
+        });
+
+        // 订阅者挂载前到达的帧被丢弃，但上游保持连接
+        frame_tx.send("early".to_string()).unwrap();
+        tokio::task::yield_now().await;
+        let mut subscription = fanout.subscribe(ControllerStream::Traffic).unwrap();
+
+        frame_tx.send("late".to_string()).unwrap();
+        assert_eq!(subscription.recv().await.unwrap(), "late");
+
+        // 最后一个订阅者离开时关闭上游
+        drop(subscription);
+        assert!(fanout.subscribe(ControllerStream::Traffic).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_closed_when_upstream_ends() {
+        let fanout = StreamFanout::new();
+        let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<()>();
+
+        fanout.ensure_upstream(ControllerStream::Traffic, move |sender| async move {
+            let _ = finish_rx.await;
+            drop(sender);
+        });
+        let mut subscription = fanout.subscribe(ControllerStream::Traffic).unwrap();
+
+        finish_tx.send(()).unwrap();
+        assert_eq!(
+            subscription.recv().await,
+            Err(broadcast::error::RecvError::Closed)
+        );
+        assert!(fanout.subscribe(ControllerStream::Traffic).is_none());
+    }
+}
@@ -9,5 +9,8 @@ pub mod server;
 
 pub use client::IpcClient;
 pub use error::{IpcError, Result};
-pub use protocol::{IpcCommand, IpcResponse};
+pub use protocol::{
+    CacheKind, ControllerStream, DnsServerReachability, GeoDataFile, IpcCommand, IpcResponse,
+    OrphanCore, PreflightItem, ServiceEvent, ServiceHealth, ServicePaths,
+};
 pub use server::IpcServer;
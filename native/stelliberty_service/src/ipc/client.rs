@@ -3,7 +3,7 @@
 // 预留给 Flutter 端使用
 
 use super::error::{IpcError, Result};
-use super::protocol::{IPC_PATH, IpcCommand, IpcResponse};
+use super::protocol::{ControllerStream, IPC_PATH, IpcCommand, IpcResponse, ServiceEvent};
 use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::time::timeout;
@@ -158,8 +158,66 @@ impl IpcClient {
     where
         F: FnMut(String) -> bool,
     {
-        // 序列化 StreamLogs 命令
-        let command = IpcCommand::StreamLogs;
+        self.stream_responses(IpcCommand::StreamLogs, |response| match response {
+            IpcResponse::LogStream { line } => Ok(callback(line)),
+            _ => Err(IpcError::Other("意外的日志流响应类型".to_string())),
+        })
+        .await
+    }
+
+    // 订阅核心输出流（持续接收核心 stdout/stderr，直到连接断开或返回错误）
+    // 参数 callback: 每收到一行输出时调用，返回 false 表示停止接收
+    pub async fn stream_core_output<F>(&self, mut callback: F) -> Result<()>
+    where
+        F: FnMut(String) -> bool,
+    {
+        self.stream_responses(IpcCommand::StreamCoreOutput, |response| match response {
+            IpcResponse::LogStream { line } => Ok(callback(line)),
+            _ => Err(IpcError::Other("意外的核心输出流响应类型".to_string())),
+        })
+        .await
+    }
+
+    // 订阅服务事件（核心意外退出等），直到连接断开或返回错误
+    // 参数 callback: 每收到一个事件时调用，返回 false 表示停止接收
+    pub async fn subscribe_events<F>(&self, mut callback: F) -> Result<()>
+    where
+        F: FnMut(ServiceEvent) -> bool,
+    {
+        self.stream_responses(IpcCommand::SubscribeEvents, |response| match response {
+            IpcResponse::Event { event } => Ok(callback(event)),
+            _ => Err(IpcError::Other("意外的事件响应类型".to_string())),
+        })
+        .await
+    }
+
+    // 订阅核心控制器的流式接口（与其他客户端共享服务到核心的上游连接）
+    // 参数 callback: 每收到一帧（单行 JSON）时调用，返回 false 表示停止接收
+    pub async fn stream_controller<F>(
+        &self,
+        stream: ControllerStream,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(String) -> bool,
+    {
+        self.stream_responses(
+            IpcCommand::SubscribeControllerStream { stream },
+            |response| match response {
+                IpcResponse::ControllerFrame { frame } => Ok(callback(frame)),
+                _ => Err(IpcError::Other("意外的控制器流响应类型".to_string())),
+            },
+        )
+        .await
+    }
+
+    // 发送流式命令并持续接收服务推送的响应
+    // 参数 on_response: 处理每条推送，返回 Ok(false) 表示停止接收
+    async fn stream_responses<F>(&self, command: IpcCommand, mut on_response: F) -> Result<()>
+    where
+        F: FnMut(IpcResponse) -> Result<bool>,
+    {
+        // 序列化命令
         let command_json = serde_json::to_string(&command)?;
         let command_bytes = command_json.as_bytes();
 
@@ -195,7 +253,7 @@ impl IpcClient {
         // 确认初始响应是成功
         match initial_response {
             IpcResponse::Success { .. } => {
-                // 继续接收日志流
+                // 继续接收推送
             }
             IpcResponse::Error { code, message } => {
                 return Err(IpcError::ServiceError(code, message));
@@ -205,9 +263,9 @@ impl IpcClient {
             }
         }
 
-        // 持续接收日志流
+        // 持续接收推送
         loop {
-            // 读取日志响应长度
+            // 读取响应长度
             let mut len_buf = [0u8; 4];
             match stream.read_exact(&mut len_buf).await {
                 Ok(_) => {}
@@ -220,33 +278,25 @@ impl IpcClient {
                 }
             }
 
-            let log_len = u32::from_le_bytes(len_buf) as usize;
+            let response_len = u32::from_le_bytes(len_buf) as usize;
 
             // 防止恶意响应
-            if log_len > 1024 * 1024 {
-                return Err(IpcError::Other("单条日志数据过大".to_string()));
+            if response_len > 1024 * 1024 {
+                return Err(IpcError::Other("单条推送数据过大".to_string()));
             }
 
-            // 读取日志数据
-            let mut log_buf = vec![0u8; log_len];
-            stream.read_exact(&mut log_buf).await?;
+            // 读取响应数据
+            let mut response_buf = vec![0u8; response_len];
+            stream.read_exact(&mut response_buf).await?;
 
-            // 反序列化日志响应
-            let log_response: IpcResponse = serde_json::from_slice(&log_buf)?;
+            // 反序列化响应
+            let response: IpcResponse = serde_json::from_slice(&response_buf)?;
 
-            match log_response {
-                IpcResponse::LogStream { line } => {
-                    // 调用回调函数，如果返回 false 则停止接收
-                    if !callback(line) {
-                        break;
-                    }
-                }
-                IpcResponse::Error { code, message } => {
-                    return Err(IpcError::ServiceError(code, message));
-                }
-                _ => {
-                    return Err(IpcError::Other("意外的日志流响应类型".to_string()));
-                }
+            if let IpcResponse::Error { code, message } = response {
+                return Err(IpcError::ServiceError(code, message));
+            }
+            if !on_response(response)? {
+                break;
             }
         }
 
@@ -25,6 +25,18 @@ pub enum IpcCommand {
         data_dir: String,
         // 外部控制器地址（HTTP API），空字符串表示禁用
         external_controller: String,
+        // 覆盖配置文件中的 mixed-port（未设置时沿用配置）
+        #[serde(default)]
+        mixed_port: Option<u16>,
+        // 覆盖配置文件中的 socks-port（未设置时沿用配置）
+        #[serde(default)]
+        socks_port: Option<u16>,
+        // 追加在内置参数之后的核心启动参数（不能覆盖 -d、-f、-ext-ctl）
+        #[serde(default)]
+        extra_args: Vec<String>,
+        // 核心进程优先级：normal（默认）、below_normal、low
+        #[serde(default)]
+        priority: Option<String>,
     },
 
     // 停止 Clash 核心
@@ -33,19 +45,255 @@ pub enum IpcCommand {
     // 获取服务状态
     GetStatus,
 
-    // 获取 Clash 日志（最近 N 行）
+    // 健康检查（供外部监控轮询）
+    Health,
+
+    // 获取 Clash 日志（最近 N 行，可选向前翻页）
     GetLogs {
         lines: usize,
+        // 从最新一条往前跳过的行数
+        #[serde(default)]
+        offset: usize,
+        // 仅返回早于该时间的日志（Unix 毫秒时间戳）
+        #[serde(default)]
+        before_timestamp: Option<i64>,
+        // 仅返回不早于该时间的日志（Unix 毫秒时间戳）
+        #[serde(default)]
+        since_timestamp: Option<i64>,
     },
 
+    // 获取服务日志文件路径
+    GetServiceLogsPath,
+
+    // 获取核心最近的 stdout/stderr 输出
+    GetCoreOutput,
+
+    // 流式获取核心输出（实时监听，逐行以 LogStream 推送）
+    StreamCoreOutput,
+
     // 流式获取日志（实时监听）
     StreamLogs,
 
+    // 订阅服务事件（核心意外退出等，由服务主动推送）
+    SubscribeEvents,
+
+    // 订阅核心控制器的流式接口（同类订阅共享一个上游连接，逐帧以 ControllerFrame 推送）
+    SubscribeControllerStream {
+        stream: ControllerStream,
+    },
+
     // 获取服务版本
     GetVersion,
 
+    // 获取服务实际使用的私有目录、程序路径与日志路径（主程序以此为准，不再自行推算）
+    GetServicePaths,
+
     // Heartbeat（心跳检测），由主程序定期发送
     Heartbeat,
+
+    // 暂停心跳超时自动关闭（耗时操作期间使用），最长暂停 5 分钟后自动恢复
+    SuspendAutoShutdown {
+        // 暂停时长（秒），未指定时按上限
+        #[serde(default)]
+        duration_secs: Option<u64>,
+    },
+
+    // 恢复心跳超时自动关闭
+    ResumeAutoShutdown,
+
+    // 清除核心缓存（转发到核心控制器）
+    FlushCache {
+        kind: CacheKind,
+    },
+
+    // 等待核心控制器就绪（轮询 GET /version 直到响应或超时）
+    WaitCoreReady {
+        timeout_ms: u64,
+    },
+
+    // 列出系统中残留的孤立核心进程
+    ListOrphanCores,
+
+    // 强制终止选中的孤立核心进程
+    KillOrphanCores {
+        pids: Vec<u32>,
+    },
+
+    // 检测 DoH/DoT 服务器在当前网络下是否可用（每个服务器单独超时）
+    CheckDnsReachability {
+        nameservers: Vec<String>,
+    },
+
+    // 经核心代理请求 IP 回显服务，检测当前出口 IP
+    CheckEgressIp {
+        // 回显服务地址（为空时使用默认服务）
+        #[serde(default)]
+        url: String,
+    },
+
+    // 建议 count 个当前可绑定的本地端口（用于替换被占用的入站/控制器端口）
+    SuggestFreePorts {
+        count: usize,
+    },
+
+    // 启用或关闭防泄漏开关（核心停止或崩溃后阻止除代理服务器外的出站连接）
+    SetKillSwitch {
+        enabled: bool,
+    },
+
+    // 启动前自检：核心程序、数据目录与运行权限
+    RunPreflightCheck {
+        core_path: String,
+        data_dir: String,
+    },
+
+    // 以新配置重启核心并等待就绪，失败时自动恢复原配置
+    SwitchConfig {
+        new_config_path: String,
+    },
+
+    // 查询数据目录中 GeoIP/GeoSite 等地理数据文件的状态
+    GetGeoDataInfo {
+        // 数据目录（为空时使用最近一次启动核心时的数据目录）
+        #[serde(default)]
+        data_dir: String,
+    },
+
+    // 导出核心当前运行的配置（GET /configs，包含经控制器修改的运行时状态）
+    ExportRunningConfig {
+        // 是否脱敏密码、UUID 等敏感字段
+        #[serde(default)]
+        redact: bool,
+    },
+}
+
+// 可清除的核心缓存类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    // Fake-IP 映射缓存
+    FakeIp,
+    // DNS 解析缓存
+    Dns,
+    // 全部缓存
+    All,
+}
+
+// 可订阅的核心控制器流式接口
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ControllerStream {
+    // 实时流量（每秒一帧）
+    Traffic,
+    // 核心日志
+    Logs,
+}
+
+impl ControllerStream {
+    // 控制器接口路径
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Traffic => "/traffic",
+            Self::Logs => "/logs",
+        }
+    }
+}
+
+// 服务主动推送的事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ServiceEvent {
+    // 核心意外退出（非 StopClash 触发）
+    CoreExited {
+        // 退出码（被信号终止时为 None）
+        code: Option<i32>,
+        // 终止信号（仅 Unix）
+        signal: Option<i32>,
+        // 核心退出前最近的输出
+        last_output_lines: Vec<String>,
+    },
+
+    // 核心短时间内反复崩溃，已关闭 TUN 以安全模式重新启动（主程序应关闭系统代理并提示用户）。
+    // 服务不会自动重启崩溃的核心，崩溃次数来自主程序的重复拉起；安全模式的这一次重启由服务执行
+    SafeModeActivated {
+        // 安全模式使用的派生配置（位于服务私有目录）
+        config_path: String,
+        // 触发安全模式的崩溃次数
+        crash_count: u32,
+    },
+}
+
+// 孤立的核心进程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanCore {
+    pub pid: u32,
+    // 进程命令行（无法获取时为映像名）
+    pub command_line: String,
+}
+
+// 服务实际使用的路径
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServicePaths {
+    // 服务私有目录（安装时服务程序复制到此处）
+    pub private_dir: String,
+    // 私有目录中的服务程序
+    pub binary_path: String,
+    // 日志文件（日志未写入文件时为 None）
+    pub log_path: Option<String>,
+}
+
+// 服务健康状态（health 命令与监控轮询使用）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceHealth {
+    // 服务是否在运行（IPC 可连接）
+    pub is_service_running: bool,
+    pub is_core_running: bool,
+    pub core_pid: Option<u32>,
+    // 服务已运行的秒数
+    pub service_uptime_secs: u64,
+    // 核心已运行的秒数（未运行时为 0）
+    pub core_uptime_secs: u64,
+    // 距离上次收到主程序心跳的秒数
+    pub heartbeat_age_secs: u64,
+}
+
+// 单项启动前自检结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreflightItem {
+    // 自检项名称（core_binary、data_dir、privileges 等）
+    pub name: String,
+    pub is_passed: bool,
+    // 通过时为说明，失败时为原因
+    pub detail: String,
+}
+
+// 单个地理数据文件的状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeoDataFile {
+    // 文件名（存在时为实际文件名）
+    pub name: String,
+    pub is_present: bool,
+    pub size: u64,
+    // 修改时间（Unix 秒）
+    pub modified_secs: Option<u64>,
+    // 数据库构建时间（Unix 秒，仅 MMDB 格式可解析）
+    pub build_epoch: Option<u64>,
+    // 数据库类型（如 GeoLite2-Country，仅 MMDB 格式可解析）
+    pub database_type: Option<String>,
+    // 距今天数（优先按构建时间，否则按修改时间）
+    pub age_days: Option<u64>,
+}
+
+// 单个 DNS 服务器的检测结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsServerReachability {
+    // 原始 nameserver 配置
+    pub nameserver: String,
+    pub is_reachable: bool,
+    // 完成一次查询的耗时（毫秒），不可达时为 None
+    pub latency_ms: Option<u64>,
+    // 不可达原因
+    pub error: Option<String>,
 }
 
 // 服务返回给客户端的响应
@@ -73,9 +321,27 @@ pub enum IpcResponse {
         service_uptime: u64,
     },
 
+    // 健康状态
+    Health {
+        health: ServiceHealth,
+    },
+
     // 日志内容
     Logs {
         lines: Vec<String>,
+        // 当前窗口之前是否还有更早的日志
+        #[serde(default)]
+        has_more: bool,
+    },
+
+    // 服务日志文件的绝对路径（日志未写入文件时为 None）
+    LogsPath {
+        path: Option<String>,
+    },
+
+    // 核心最近的输出（按时间顺序）
+    CoreOutput {
+        lines: Vec<String>,
     },
 
     // 日志流数据（单行）
@@ -88,6 +354,92 @@ pub enum IpcResponse {
         version: String,
     },
 
+    // 服务路径
+    ServicePaths {
+        paths: ServicePaths,
+    },
+
     // HeartbeatAck（心跳响应）
     HeartbeatAck,
+
+    // 自动关闭暂停状态
+    AutoShutdown {
+        is_suspended: bool,
+        // 剩余暂停时间（秒）
+        remaining_secs: u64,
+    },
+
+    // 核心就绪状态
+    CoreReadiness {
+        // 控制器是否已响应
+        is_ready: bool,
+        // 等待耗时（毫秒）
+        elapsed_ms: u64,
+    },
+
+    // 孤立核心进程列表
+    OrphanCores {
+        cores: Vec<OrphanCore>,
+    },
+
+    // DNS 服务器检测结果（顺序与请求一致）
+    DnsReachability {
+        results: Vec<DnsServerReachability>,
+    },
+
+    // 出口 IP 检测结果（回显服务未提供的字段为 None）
+    EgressIp {
+        ip: String,
+        country: Option<String>,
+        asn: Option<String>,
+    },
+
+    // 建议的空闲端口（升序、互不相同）
+    FreePorts {
+        ports: Vec<u16>,
+    },
+
+    // 防泄漏开关状态
+    KillSwitch {
+        is_enabled: bool,
+        // 拦截规则当前是否已生效
+        is_engaged: bool,
+    },
+
+    // 启动前自检结果（顺序固定）
+    Preflight {
+        items: Vec<PreflightItem>,
+    },
+
+    // 配置切换结果
+    ConfigSwitched {
+        // 最终生效的配置文件
+        active_config_path: String,
+        // 新配置启动失败，已恢复原配置
+        is_reverted: bool,
+        // 新配置失败的原因（仅恢复时）
+        reason: Option<String>,
+    },
+
+    // 地理数据文件状态（顺序固定）
+    GeoDataInfo {
+        files: Vec<GeoDataFile>,
+    },
+
+    // 核心运行中的配置（格式化后的 JSON 文本）
+    RunningConfig {
+        config: String,
+        // 被脱敏的字段数量
+        redacted_fields: u32,
+    },
+
+    // 服务事件（事件订阅连接上推送）
+    Event {
+        event: ServiceEvent,
+    },
+
+    // 控制器流式接口的一帧（原样转发核心输出的单行 JSON）
+    ControllerFrame {
+        frame: String,
+    },
 }
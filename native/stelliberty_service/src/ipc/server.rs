@@ -1,7 +1,7 @@
 // IPC 服务端实现
 
 use super::error::{IpcError, Result};
-use super::protocol::{IPC_PATH, IpcCommand, IpcResponse};
+use super::protocol::{ControllerStream, IPC_PATH, IpcCommand, IpcResponse};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -63,10 +63,10 @@ impl IpcServer {
 
     // 启动服务端（阻塞直到关闭）
     pub async fn run(&mut self) -> Result<()> {
-        // 删除旧的 IPC 文件
+        // 清理上次异常退出遗留的 Socket 文件
         #[cfg(not(windows))]
         {
-            let _ = std::fs::remove_file(IPC_PATH);
+            remove_stale_socket(IPC_PATH)?;
         }
 
         // 创建关闭通道
@@ -119,6 +119,9 @@ impl IpcServer {
                         IpcError::Other(format!("创建 Named Pipe 失败: {e}"))
                     })?;
 
+                // 确认管道实例可用（句柄有效且未被其他进程抢占）
+                verify_named_pipe(&pipe)?;
+
                 log::debug!("Named Pipe 实例创建成功（初始化）");
                 is_first_instance = false;
 
@@ -251,12 +254,34 @@ impl IpcServer {
             return Self::handle_log_stream(stream).await;
         }
 
+        // 处理 StreamCoreOutput 特殊命令（流式推送核心输出）
+        if matches!(command, IpcCommand::StreamCoreOutput) {
+            log::info!("启动核心输出流订阅");
+            return Self::handle_core_output_stream(stream).await;
+        }
+
+        // 处理 SubscribeEvents 特殊命令（服务主动推送事件）
+        if matches!(command, IpcCommand::SubscribeEvents) {
+            log::info!("启动事件订阅");
+            return Self::handle_event_stream(stream).await;
+        }
+
+        // 处理 SubscribeControllerStream 特殊命令（共享上游连接，扇出推送）
+        if let IpcCommand::SubscribeControllerStream { stream: kind } = command {
+            let response = handler(command).await;
+            if !matches!(response, IpcResponse::Success { .. }) {
+                return Self::write_response(&mut stream, &response).await;
+            }
+            log::info!("启动控制器流订阅: {kind:?}");
+            return Self::handle_controller_stream(stream, kind, response).await;
+        }
+
         // 处理普通命令（请求-响应）
         let response = handler(command).await;
 
         // 记录响应（避免日志递归：GetLogs 响应不打印完整内容）
         match &response {
-            IpcResponse::Logs { lines } => {
+            IpcResponse::Logs { lines, .. } => {
                 log::trace!("返回响应: Logs (共 {} 行)", lines.len());
             }
             _ => {
@@ -338,6 +363,172 @@ impl IpcServer {
         log::info!("日志流订阅结束");
         Ok(())
     }
+
+    // 处理核心输出流订阅（连接保持到客户端断开）
+    async fn handle_core_output_stream<S>(mut stream: S) -> Result<()>
+    where
+        S: AsyncReadExt + AsyncWriteExt + Unpin,
+    {
+        let mut output_receiver = crate::clash::exit_monitor::subscribe_core_output();
+
+        let initial_response = IpcResponse::Success {
+            message: Some("核心输出流已启用".to_string()),
+        };
+        Self::write_response(&mut stream, &initial_response).await?;
+
+        loop {
+            match output_receiver.recv().await {
+                Ok(line) => {
+                    let response = IpcResponse::LogStream { line };
+                    if let Err(e) = Self::write_response(&mut stream, &response).await {
+                        log::debug!("核心输出流客户端断开连接: {}", e);
+                        break;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("核心输出流客户端处理过慢，跳过了 {} 行输出", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    log::info!("核心输出广播通道已关闭，停止核心输出流");
+                    break;
+                }
+            }
+        }
+
+        log::info!("核心输出流订阅结束");
+        Ok(())
+    }
+
+    // 处理事件订阅（连接保持到客户端断开）
+    async fn handle_event_stream<S>(mut stream: S) -> Result<()>
+    where
+        S: AsyncReadExt + AsyncWriteExt + Unpin,
+    {
+        let mut event_receiver = crate::clash::exit_monitor::subscribe_events();
+
+        let initial_response = IpcResponse::Success {
+            message: Some("事件订阅已启用".to_string()),
+        };
+        Self::write_response(&mut stream, &initial_response).await?;
+
+        loop {
+            match event_receiver.recv().await {
+                Ok(event) => {
+                    log::debug!("推送服务事件: {event:?}");
+                    let response = IpcResponse::Event { event };
+                    if let Err(e) = Self::write_response(&mut stream, &response).await {
+                        log::debug!("事件订阅客户端断开连接: {}", e);
+                        break;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("事件订阅客户端处理过慢，跳过了 {} 个事件", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    log::info!("事件广播通道已关闭，停止事件订阅");
+                    break;
+                }
+            }
+        }
+
+        log::info!("事件订阅结束");
+        Ok(())
+    }
+
+    // 处理控制器流订阅（连接保持到客户端断开或上游结束）
+    async fn handle_controller_stream<S>(
+        mut stream: S,
+        kind: ControllerStream,
+        initial_response: IpcResponse,
+    ) -> Result<()>
+    where
+        S: AsyncReadExt + AsyncWriteExt + Unpin,
+    {
+        // 处理器建立上游后到此处订阅之间，上游可能已经结束
+        let Some(mut subscription) = crate::clash::stream_fanout::FANOUT.subscribe(kind) else {
+            let response = IpcResponse::Error {
+                code: 1012,
+                message: "订阅控制器流失败: 上游连接已断开".to_string(),
+            };
+            return Self::write_response(&mut stream, &response).await;
+        };
+        Self::write_response(&mut stream, &initial_response).await?;
+
+        loop {
+            match subscription.recv().await {
+                Ok(frame) => {
+                    let response = IpcResponse::ControllerFrame { frame };
+                    if let Err(e) = Self::write_response(&mut stream, &response).await {
+                        log::debug!("控制器流客户端断开连接: {}", e);
+                        break;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("控制器流客户端处理过慢，跳过了 {} 帧", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    log::info!("控制器流上游连接已结束，停止推送");
+                    break;
+                }
+            }
+        }
+
+        log::info!("控制器流订阅结束: {kind:?}");
+        Ok(())
+    }
+
+    // 发送一条响应（长度 + JSON 数据）
+    async fn write_response<S>(stream: &mut S, response: &IpcResponse) -> Result<()>
+    where
+        S: AsyncWriteExt + Unpin,
+    {
+        let response_json = serde_json::to_string(response)?;
+        let response_bytes = response_json.as_bytes();
+        let len = response_bytes.len() as u32;
+        stream.write_all(&len.to_le_bytes()).await?;
+        stream.write_all(response_bytes).await?;
+        stream.flush().await?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Unix Socket 辅助函数
+// ============================================================================
+
+#[cfg(not(windows))]
+// 移除遗留的 Unix Socket 文件
+//
+// 服务异常崩溃后 Socket 文件不会被自动删除，导致新实例 bind 失败。
+// 先尝试连接：能连上说明已有实例在监听，不能误删；
+// 连接被拒绝说明文件已失效，可以安全删除。
+fn remove_stale_socket(path: &str) -> Result<()> {
+    use std::io::ErrorKind;
+    use std::os::unix::net::UnixStream;
+
+    if std::fs::symlink_metadata(path).is_err() {
+        return Ok(());
+    }
+
+    match UnixStream::connect(path) {
+        Ok(_) => Err(IpcError::Other(format!(
+            "IPC Socket 已被其他服务实例占用: {path}"
+        ))),
+        Err(e) if matches!(e.kind(), ErrorKind::ConnectionRefused | ErrorKind::NotFound) => {
+            log::warn!("检测到遗留的 IPC Socket 文件，正在清理: {path}");
+            match std::fs::remove_file(path) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(IpcError::Other(format!("删除遗留 Socket 文件失败: {e}"))),
+            }
+        }
+        Err(e) => {
+            // 其他错误（如非 Socket 文件）仍尝试删除，由 bind 给出最终结果
+            log::warn!("探测 IPC Socket 失败（{e}），尝试直接删除: {path}");
+            let _ = std::fs::remove_file(path);
+            Ok(())
+        }
+    }
 }
 
 // ============================================================================
@@ -404,7 +595,7 @@ fn create_named_pipe_with_security(
     is_first_instance: bool,
     security_descriptor: &SecurityDescriptorWrapper,
 ) -> std::result::Result<tokio::net::windows::named_pipe::NamedPipeServer, String> {
-    use windows::Win32::Foundation::ERROR_PIPE_BUSY;
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_PIPE_BUSY};
     use windows::Win32::Security::SECURITY_ATTRIBUTES;
     use windows::Win32::Storage::FileSystem::{
         FILE_FLAG_FIRST_PIPE_INSTANCE, FILE_FLAG_OVERLAPPED, PIPE_ACCESS_DUPLEX,
@@ -446,6 +637,10 @@ fn create_named_pipe_with_security(
             if err.raw_os_error() == Some(ERROR_PIPE_BUSY.0 as i32) {
                 return Err("Named Pipe 繁忙".to_string());
             }
+            // 首个实例创建被拒绝：同名管道仍被旧实例持有（未完全退出）
+            if is_first_instance && err.raw_os_error() == Some(ERROR_ACCESS_DENIED.0 as i32) {
+                return Err(format!("Named Pipe 已被其他服务实例占用: {err}"));
+            }
             return Err(format!("CreateNamedPipeW 失败: {err}"));
         }
 
@@ -455,3 +650,61 @@ fn create_named_pipe_with_security(
             .map_err(|e| format!("包装 Named Pipe 失败: {e}"))
     }
 }
+
+#[cfg(windows)]
+// 校验新建的 Named Pipe 实例（查询管道信息失败说明句柄已失效）
+fn verify_named_pipe(pipe: &tokio::net::windows::named_pipe::NamedPipeServer) -> Result<()> {
+    pipe.info().map(|_| ()).map_err(|e| {
+        log::error!("Named Pipe 实例校验失败: {e}");
+        IpcError::Other(format!("Named Pipe 实例不可用: {e}"))
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    fn temp_socket_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "stelliberty-test-{}-{}.sock",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_remove_stale_socket_cleans_leftover_file() {
+        let path = temp_socket_path("stale");
+
+        // 绑定后直接丢弃监听器，模拟崩溃后遗留的 Socket 文件
+        drop(UnixListener::bind(&path).expect("bind"));
+        assert!(std::path::Path::new(&path).exists());
+
+        remove_stale_socket(&path).expect("cleanup");
+        assert!(!std::path::Path::new(&path).exists());
+
+        // 清理后可以重新绑定
+        drop(UnixListener::bind(&path).expect("rebind"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_remove_stale_socket_keeps_live_listener() {
+        let path = temp_socket_path("live");
+        let _listener = UnixListener::bind(&path).expect("bind");
+
+        assert!(remove_stale_socket(&path).is_err());
+        assert!(std::path::Path::new(&path).exists());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_remove_stale_socket_missing_path() {
+        let path = temp_socket_path("missing");
+        assert!(remove_stale_socket(&path).is_ok());
+    }
+}
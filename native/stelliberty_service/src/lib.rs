@@ -8,6 +8,7 @@ pub mod logger;
 pub mod service;
 
 use anyhow::Result;
+use serde::Serialize;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{RwLock, mpsc};
@@ -54,7 +55,10 @@ pub fn cli() -> Result<()> {
     }
 
     // 这些命令不需要管理员权限
-    let no_admin_required = matches!(args[1].as_str(), "logs" | "version" | "-v" | "--version");
+    let no_admin_required = matches!(
+        args[1].as_str(),
+        "logs" | "status" | "health" | "version" | "-v" | "--version"
+    );
 
     // 需要权限的命令检查权限
     if !no_admin_required && !check_privileges() {
@@ -96,17 +100,32 @@ pub fn print_usage() {
     println!("Stelliberty Service v{}", env!("CARGO_PKG_VERSION"));
     println!();
     println!("可用命令：");
-    println!("  install    - 安装并启动服务");
+    println!(
+        "  install    - 安装并启动服务（可选 --umask <八进制>，默认沿用已安装的值，首次安装为 0077）"
+    );
+    println!(
+        "               --capabilities <full|minimal>：systemd 权限集，minimal 不含 CAP_SYS_TIME/CAP_SYS_PTRACE，默认沿用已安装的值"
+    );
+    println!(
+        "               --private-dir <路径>：服务私有目录，由主程序在提权前解析（默认按当前用户推算）"
+    );
     println!("  uninstall  - 停止并卸载服务");
+    println!(
+        "  repair     - 重新复制服务程序并重启服务（保留服务注册，可选 --private-dir <路径>）"
+    );
     println!("  start      - 启动服务");
     println!("  stop       - 停止服务");
-    println!("  logs       - 实时监控服务日志");
-    println!("  version    - 显示版本号");
+    println!("  restart    - 重启服务（先停止再启动）");
+    println!("  logs       - 实时监控服务日志（可选 --since <时长>，如 30s/10m/2h）");
+    println!("               --core：改为监控核心自身的 stdout/stderr 输出");
+    println!("  status     - 查询服务运行状态（可选 --json）");
+    println!("  health     - 健康检查，核心未运行时以非零状态码退出（可选 --json）");
+    println!("  version    - 显示版本号（可选 --json）");
     println!();
     #[cfg(windows)]
-    println!("注意：install/uninstall/start/stop 需要管理员权限");
+    println!("注意：install/uninstall/repair/start/stop/restart 需要管理员权限");
     #[cfg(not(windows))]
-    println!("注意：install/uninstall/start/stop 需要 root 权限");
+    println!("注意：install/uninstall/repair/start/stop/restart 需要 root 权限");
 }
 
 // 控制台模式运行（用于调试）
@@ -128,6 +147,9 @@ pub async fn run_console_mode() -> Result<()> {
         let _ = shutdown_tx_clone.send(()).await;
     });
 
+    // 上次运行异常退出时可能残留防泄漏规则
+    clash::kill_switch::release_stale();
+
     // 创建共享状态
     let clash_manager = Arc::new(RwLock::new(clash::ClashManager::new()));
     let last_heartbeat = Arc::new(RwLock::new(Instant::now()));
@@ -136,28 +158,36 @@ pub async fn run_console_mode() -> Result<()> {
     let handler = service::handler::create_handler(clash_manager.clone(), last_heartbeat.clone());
     let mut ipc_server = ipc::IpcServer::new(handler);
 
+    // 启动核心退出监控
+    let exit_watcher_handle = clash::exit_monitor::spawn_exit_watcher(clash_manager.clone());
+
     // 启动心跳监控器（HeartbeatMonitor）任务
     let monitor_shutdown_tx = shutdown_tx.clone();
     tokio::spawn(async move {
-        const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(70);
-        const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+        use service::heartbeat::{self, CHECK_INTERVAL, HEARTBEAT_TIMEOUT, HeartbeatStatus};
 
         log::info!("启动心跳监控器，超时时间: {}s", HEARTBEAT_TIMEOUT.as_secs());
 
         loop {
             tokio::time::sleep(CHECK_INTERVAL).await;
-            let elapsed = last_heartbeat.read().await.elapsed();
-            if elapsed > HEARTBEAT_TIMEOUT {
-                log::warn!(
-                    "超过 {} 秒未收到主程序心跳，判定为孤立进程，服务将自动关闭...",
-                    HEARTBEAT_TIMEOUT.as_secs()
-                );
-                if monitor_shutdown_tx.send(()).await.is_err() {
-                    log::error!("发送关闭信号失败，服务可能无法正常退出");
+            let last = *last_heartbeat.read().await;
+            match heartbeat::evaluate(last, heartbeat::suspended_until(), Instant::now()) {
+                HeartbeatStatus::TimedOut(_) => {
+                    log::warn!(
+                        "超过 {} 秒未收到主程序心跳，判定为孤立进程，服务将自动关闭...",
+                        HEARTBEAT_TIMEOUT.as_secs()
+                    );
+                    if monitor_shutdown_tx.send(()).await.is_err() {
+                        log::error!("发送关闭信号失败，服务可能无法正常退出");
+                    }
+                    break;
+                }
+                HeartbeatStatus::Suspended(remaining) => {
+                    log::debug!("自动关闭已暂停，剩余: {}s", remaining.as_secs());
+                }
+                HeartbeatStatus::Alive(elapsed) => {
+                    log::debug!("心跳正常，距离上次心跳: {}s", elapsed.as_secs());
                 }
-                break;
-            } else {
-                log::debug!("心跳正常，距离上次心跳: {}s", elapsed.as_secs());
             }
         }
     });
@@ -192,6 +222,8 @@ pub async fn run_console_mode() -> Result<()> {
     }
 
     ipc_handle.abort();
+    exit_watcher_handle.abort();
+    clash::kill_switch::release_on_exit();
     log::info!("服务已停止");
     Ok(())
 }
@@ -206,13 +238,59 @@ pub fn handle_command(args: &[String]) -> Result<Option<()>> {
 
     match args[1].as_str() {
         "install" => {
-            service::install_service()?;
+            let mut options = service::installer::InstallOptions::default();
+            let mut rest = args.iter().skip(2);
+            while let Some(flag) = rest.next() {
+                match flag.as_str() {
+                    "--umask" => {
+                        let Some(value) = rest.next() else {
+                            eprintln!("缺少 --umask 参数值，示例: --umask 0027");
+                            return Ok(Some(()));
+                        };
+                        options.umask = Some(service::installer::parse_umask(value)?);
+                    }
+                    "--capabilities" => {
+                        let Some(value) = rest.next() else {
+                            eprintln!("缺少 --capabilities 参数值，可选: full、minimal");
+                            return Ok(Some(()));
+                        };
+                        options.capability_profile =
+                            Some(service::installer::parse_capability_profile(value)?);
+                    }
+                    "--private-dir" => {
+                        let Some(value) = rest.next() else {
+                            eprintln!("缺少 --private-dir 参数值");
+                            return Ok(Some(()));
+                        };
+                        service::installer::set_private_dir(value)?;
+                    }
+                    _ => {
+                        eprintln!("未知的 install 参数: {}", flag);
+                        return Ok(Some(()));
+                    }
+                }
+            }
+            service::install_service(&options)?;
             Ok(Some(()))
         }
         "uninstall" => {
             service::uninstall_service()?;
             Ok(Some(()))
         }
+        "repair" => {
+            match &args[2..] {
+                [] => {}
+                [flag, value] if flag == "--private-dir" => {
+                    service::installer::set_private_dir(value)?;
+                }
+                _ => {
+                    eprintln!("未知的 repair 参数: {}", args[2..].join(" "));
+                    return Ok(Some(()));
+                }
+            }
+            service::repair_service()?;
+            Ok(Some(()))
+        }
         "start" => {
             service::start_service()?;
             Ok(Some(()))
@@ -221,12 +299,51 @@ pub fn handle_command(args: &[String]) -> Result<Option<()>> {
             service::stop_service()?;
             Ok(Some(()))
         }
+        "restart" => {
+            service::restart_service()?;
+            Ok(Some(()))
+        }
         "logs" => {
-            tokio::runtime::Runtime::new()?.block_on(async { follow_logs().await })?;
+            let source = match parse_logs_args(&args[2..]) {
+                Ok(source) => source,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return Ok(Some(()));
+                }
+            };
+            let runtime = tokio::runtime::Runtime::new()?;
+            match source {
+                LogsSource::Service { since_timestamp } => {
+                    runtime.block_on(follow_logs(since_timestamp))?
+                }
+                LogsSource::Core => runtime.block_on(follow_core_output())?,
+            }
+            Ok(Some(()))
+        }
+        "status" => {
+            let report = tokio::runtime::Runtime::new()?.block_on(query_status());
+            if has_json_flag(args) {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                print_status(&report);
+            }
             Ok(Some(()))
         }
+        "health" => {
+            let health = tokio::runtime::Runtime::new()?.block_on(query_health());
+            if has_json_flag(args) {
+                println!("{}", serde_json::to_string(&health)?);
+            } else {
+                print_health(&health);
+            }
+            std::process::exit(health_exit_code(&health));
+        }
         "version" | "-v" | "--version" => {
-            println!("Stelliberty Service v{}", env!("CARGO_PKG_VERSION"));
+            if has_json_flag(args) {
+                println!("{}", version_json()?);
+            } else {
+                println!("Stelliberty Service v{}", env!("CARGO_PKG_VERSION"));
+            }
             Ok(Some(()))
         }
         _ => {
@@ -238,20 +355,180 @@ pub fn handle_command(args: &[String]) -> Result<Option<()>> {
     }
 }
 
-// 实时监控服务日志
-async fn follow_logs() -> Result<()> {
+// 检查命令参数中是否带有 --json
+fn has_json_flag(args: &[String]) -> bool {
+    args.iter().skip(2).any(|arg| arg == "--json")
+}
+
+// 版本信息（version --json 输出）
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+    version: &'static str,
+}
+
+// 生成 JSON 格式的版本信息，如 {"version":"1.5.0"}
+fn version_json() -> Result<String> {
+    let info = VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+    };
+    Ok(serde_json::to_string(&info)?)
+}
+
+// 服务状态报告（status 命令输出）
+#[derive(Debug, Serialize)]
+struct StatusReport {
+    version: &'static str,
+    // 服务是否在运行（IPC 可连接）
+    is_service_running: bool,
+    is_clash_running: bool,
+    clash_pid: Option<u32>,
+    // 服务已运行的秒数
+    service_uptime: Option<u64>,
+}
+
+impl StatusReport {
+    fn not_running() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            is_service_running: false,
+            is_clash_running: false,
+            clash_pid: None,
+            service_uptime: None,
+        }
+    }
+}
+
+// 通过 IPC 查询服务状态，连接失败视为服务未运行
+async fn query_status() -> StatusReport {
+    use ipc::IpcClient;
+    use ipc::protocol::{IpcCommand, IpcResponse};
+
+    let client = IpcClient::default().with_timeout(Duration::from_secs(3));
+    match client.send_command(IpcCommand::GetStatus).await {
+        Ok(IpcResponse::Status {
+            is_clash_running,
+            clash_pid,
+            service_uptime,
+        }) => StatusReport {
+            is_service_running: true,
+            is_clash_running,
+            clash_pid,
+            service_uptime: Some(service_uptime),
+            ..StatusReport::not_running()
+        },
+        _ => StatusReport::not_running(),
+    }
+}
+
+// 打印人类可读的状态信息
+fn print_status(report: &StatusReport) {
+    println!("Stelliberty Service v{}", report.version);
+    if !report.is_service_running {
+        println!("服务状态: 未运行");
+        return;
+    }
+    println!("服务状态: 运行中");
+    if let Some(uptime) = report.service_uptime {
+        println!("运行时长: {}s", uptime);
+    }
+    match (report.is_clash_running, report.clash_pid) {
+        (true, Some(pid)) => println!("Clash 核心: 运行中 (PID {})", pid),
+        (true, None) => println!("Clash 核心: 运行中"),
+        (false, _) => println!("Clash 核心: 未运行"),
+    }
+}
+
+// health 命令退出码：0 正常，1 核心未运行，2 服务未运行
+fn health_exit_code(health: &ipc::ServiceHealth) -> i32 {
+    match (health.is_service_running, health.is_core_running) {
+        (false, _) => 2,
+        (true, false) => 1,
+        (true, true) => 0,
+    }
+}
+
+// 通过 IPC 查询健康状态，连接失败视为服务未运行
+async fn query_health() -> ipc::ServiceHealth {
+    use ipc::IpcClient;
+    use ipc::protocol::{IpcCommand, IpcResponse};
+
+    let client = IpcClient::default().with_timeout(Duration::from_secs(3));
+    match client.send_command(IpcCommand::Health).await {
+        Ok(IpcResponse::Health { health }) => health,
+        _ => ipc::ServiceHealth::default(),
+    }
+}
+
+// 打印人类可读的健康状态
+fn print_health(health: &ipc::ServiceHealth) {
+    if !health.is_service_running {
+        println!("服务: 未运行");
+        return;
+    }
+    println!("服务: 运行中 ({}s)", health.service_uptime_secs);
+    match health.core_pid {
+        Some(pid) if health.is_core_running => {
+            println!("核心: 运行中 (PID {}, {}s)", pid, health.core_uptime_secs)
+        }
+        _ => println!("核心: 未运行"),
+    }
+    println!("上次心跳: {}s 前", health.heartbeat_age_secs);
+}
+
+// 解析 --since 参数（支持 s/m/h 后缀，无后缀按秒计），返回起始时间的 Unix 毫秒时间戳
+fn parse_since(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (number, unit_secs) = match value.chars().last()? {
+        's' => (&value[..value.len() - 1], 1),
+        'm' => (&value[..value.len() - 1], 60),
+        'h' => (&value[..value.len() - 1], 3600),
+        _ => (value, 1),
+    };
+    let secs = number.parse::<i64>().ok()?.checked_mul(unit_secs)?;
+    if secs < 0 {
+        return None;
+    }
+    Some(chrono::Local::now().timestamp_millis() - secs * 1000)
+}
+
+// logs 命令要监控的日志来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogsSource {
+    // 服务日志（可选起始时间，Unix 毫秒时间戳）
+    Service { since_timestamp: Option<i64> },
+    // 核心 stdout/stderr 输出
+    Core,
+}
+
+// 解析 logs 命令参数（args 不含命令名本身）
+fn parse_logs_args(args: &[String]) -> std::result::Result<LogsSource, String> {
+    let is_core = args.iter().any(|arg| arg == "--core");
+    let since = match args.iter().position(|arg| arg == "--since") {
+        Some(index) => Some(
+            args.get(index + 1)
+                .and_then(|value| parse_since(value))
+                .ok_or_else(|| "无效的 --since 参数，示例: --since 30m".to_string())?,
+        ),
+        None => None,
+    };
+
+    match (is_core, since) {
+        (true, Some(_)) => Err("--core 不支持 --since，核心输出仅保留最近的若干行".to_string()),
+        (true, None) => Ok(LogsSource::Core),
+        (false, since_timestamp) => Ok(LogsSource::Service { since_timestamp }),
+    }
+}
+
+// 实时监控核心输出：先打印缓冲的最近输出，再接收实时输出流
+async fn follow_core_output() -> Result<()> {
     use ipc::IpcClient;
     use ipc::protocol::{IpcCommand, IpcResponse};
 
     let client = IpcClient::default();
 
-    // 先获取历史日志（最近 500 条）
-    match client
-        .send_command(IpcCommand::GetLogs { lines: 500 })
-        .await
-    {
-        Ok(IpcResponse::Logs { lines: log_lines }) => {
-            for line in log_lines {
+    match client.send_command(IpcCommand::GetCoreOutput).await {
+        Ok(IpcResponse::CoreOutput { lines }) => {
+            for line in lines {
                 println!("{}", line);
             }
         }
@@ -262,10 +539,133 @@ async fn follow_logs() -> Result<()> {
         }
     }
 
+    let _ = client
+        .stream_core_output(|line| {
+            println!("{}", line);
+            true
+        })
+        .await;
+
+    println!("\n核心输出流已断开");
+    Ok(())
+}
+
+// 获取历史日志的超时时间：服务卡住时不再阻塞，直接尝试实时日志流
+const LOGS_HISTORY_TIMEOUT: Duration = Duration::from_secs(3);
+
+// 单行日志最多打印的字符数，避免超长行刷屏
+const MAX_LOG_LINE_CHARS: usize = 2000;
+
+// 获取历史日志的结果
+#[derive(Debug, PartialEq, Eq)]
+enum LogsHistory {
+    // 按时间顺序排列的历史日志
+    Lines(Vec<String>),
+    // 无法连接服务
+    NotRunning,
+    // 服务在超时时间内未响应
+    TimedOut,
+}
+
+// 分页获取历史日志：未指定 --since 时只取一页，否则向前翻页直到起始时间。
+// fetch_page 接收偏移量，返回该页日志（按时间顺序）与是否还有更早的日志，None 表示响应不是日志
+async fn fetch_logs_history<F, Fut, E>(
+    mut fetch_page: F,
+    since_timestamp: Option<i64>,
+    timeout: Duration,
+) -> LogsHistory
+where
+    F: FnMut(usize) -> Fut,
+    Fut: std::future::Future<Output = std::result::Result<Option<(Vec<String>, bool)>, E>>,
+{
+    let fetch_all = async {
+        let mut pages: Vec<Vec<String>> = Vec::new();
+        let mut offset = 0;
+        loop {
+            match fetch_page(offset).await {
+                Ok(Some((lines, has_more))) => {
+                    offset += lines.len();
+                    let is_empty = lines.is_empty();
+                    pages.push(lines);
+                    if since_timestamp.is_none() || !has_more || is_empty {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(_) => return LogsHistory::NotRunning,
+            }
+        }
+        LogsHistory::Lines(pages.into_iter().rev().flatten().collect())
+    };
+
+    tokio::time::timeout(timeout, fetch_all)
+        .await
+        .unwrap_or(LogsHistory::TimedOut)
+}
+
+// 截断超长日志行，标注被省略的字符数
+fn truncate_log_line(line: &str, max_chars: usize) -> std::borrow::Cow<'_, str> {
+    match line.char_indices().nth(max_chars) {
+        Some((end, _)) => {
+            let omitted = line[end..].chars().count();
+            format!("{}…（省略 {} 个字符）", &line[..end], omitted).into()
+        }
+        None => line.into(),
+    }
+}
+
+// 实时监控服务日志
+async fn follow_logs(since_timestamp: Option<i64>) -> Result<()> {
+    use ipc::IpcClient;
+    use ipc::protocol::{IpcCommand, IpcResponse};
+
+    const PAGE_SIZE: usize = 500;
+
+    let client = IpcClient::default();
+
+    // 先获取历史日志：未指定 --since 时取最近 500 条，否则向前翻页直到起始时间
+    let history = fetch_logs_history(
+        |offset| {
+            let command = IpcCommand::GetLogs {
+                lines: PAGE_SIZE,
+                offset,
+                before_timestamp: None,
+                since_timestamp,
+            };
+            let client = &client;
+            async move {
+                client
+                    .send_command(command)
+                    .await
+                    .map(|response| match response {
+                        IpcResponse::Logs { lines, has_more } => Some((lines, has_more)),
+                        _ => None,
+                    })
+            }
+        },
+        since_timestamp,
+        LOGS_HISTORY_TIMEOUT,
+    )
+    .await;
+
+    match history {
+        LogsHistory::Lines(lines) => {
+            for line in lines {
+                println!("{}", truncate_log_line(&line, MAX_LOG_LINE_CHARS));
+            }
+        }
+        LogsHistory::NotRunning => {
+            println!("服务未运行，请先启动服务");
+            return Ok(());
+        }
+        // 服务可能卡住，仍尝试接收实时日志
+        LogsHistory::TimedOut => println!("服务未运行，请先启动服务"),
+    }
+
     // 接收实时日志流
     let _ = client
         .stream_logs(|line| {
-            println!("{}", line);
+            println!("{}", truncate_log_line(&line, MAX_LOG_LINE_CHARS));
             true
         })
         .await;
@@ -300,3 +700,153 @@ pub async fn run() -> Result<()> {
         run_console_mode().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_logs_dispatch() {
+        assert_eq!(
+            parse_logs_args(&args(&[])),
+            Ok(LogsSource::Service {
+                since_timestamp: None
+            })
+        );
+        assert_eq!(parse_logs_args(&args(&["--core"])), Ok(LogsSource::Core));
+
+        let since = parse_logs_args(&args(&["--since", "10m"])).unwrap();
+        assert!(matches!(
+            since,
+            LogsSource::Service {
+                since_timestamp: Some(_)
+            }
+        ));
+
+        assert!(parse_logs_args(&args(&["--since", "abc"])).is_err());
+        assert!(parse_logs_args(&args(&["--core", "--since", "10m"])).is_err());
+    }
+
+    // 模拟 IPC 客户端：每页响应前等待 delay
+    async fn stub_page(
+        offset: usize,
+        delay: Duration,
+    ) -> std::result::Result<Option<(Vec<String>, bool)>, String> {
+        tokio::time::sleep(delay).await;
+        let page = if offset == 0 { ["c", "d"] } else { ["a", "b"] };
+        Ok(Some((args(&page), offset == 0)))
+    }
+
+    #[tokio::test]
+    async fn test_logs_history_timeout() {
+        // 服务卡住：超时后返回 TimedOut，不再阻塞
+        let started = Instant::now();
+        let history = fetch_logs_history(
+            |offset| stub_page(offset, Duration::from_secs(30)),
+            None,
+            Duration::from_millis(50),
+        )
+        .await;
+        assert_eq!(history, LogsHistory::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // 正常响应：翻页结果按时间顺序拼接
+        let history = fetch_logs_history(
+            |offset| stub_page(offset, Duration::ZERO),
+            Some(0),
+            Duration::from_secs(3),
+        )
+        .await;
+        assert_eq!(history, LogsHistory::Lines(args(&["a", "b", "c", "d"])));
+
+        // 连接失败：视为服务未运行
+        let history = fetch_logs_history(
+            |_| async { Err::<Option<(Vec<String>, bool)>, _>("connection refused") },
+            None,
+            Duration::from_secs(3),
+        )
+        .await;
+        assert_eq!(history, LogsHistory::NotRunning);
+    }
+
+    #[test]
+    fn test_truncate_log_line() {
+        assert_eq!(truncate_log_line("short", 10), "short");
+        assert_eq!(truncate_log_line("0123456789", 10), "0123456789");
+        assert_eq!(
+            truncate_log_line("日志内容很长很长", 4),
+            "日志内容…（省略 4 个字符）"
+        );
+    }
+
+    #[test]
+    fn test_version_json() {
+        let json = version_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_status_report_json() {
+        let json = serde_json::to_string(&StatusReport::not_running()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["is_service_running"], false);
+        assert_eq!(value["is_clash_running"], false);
+        assert!(value["clash_pid"].is_null());
+    }
+
+    #[test]
+    fn test_health_json() {
+        let health = ipc::ServiceHealth {
+            is_service_running: true,
+            is_core_running: true,
+            core_pid: Some(4321),
+            service_uptime_secs: 120,
+            core_uptime_secs: 90,
+            heartbeat_age_secs: 5,
+        };
+        assert_eq!(
+            serde_json::to_string(&health).unwrap(),
+            r#"{"is_service_running":true,"is_core_running":true,"core_pid":4321,"service_uptime_secs":120,"core_uptime_secs":90,"heartbeat_age_secs":5}"#
+        );
+
+        let response = ipc::IpcResponse::Health {
+            health: health.clone(),
+        };
+        let json = serde_json::to_string(&response).unwrap();
+        match serde_json::from_str(&json).unwrap() {
+            ipc::IpcResponse::Health { health: decoded } => assert_eq!(decoded, health),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_health_exit_code() {
+        let running = ipc::ServiceHealth {
+            is_service_running: true,
+            is_core_running: true,
+            core_pid: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(health_exit_code(&running), 0);
+
+        let core_down = ipc::ServiceHealth {
+            is_service_running: true,
+            ..Default::default()
+        };
+        assert_eq!(health_exit_code(&core_down), 1);
+        assert_eq!(health_exit_code(&ipc::ServiceHealth::default()), 2);
+    }
+
+    #[test]
+    fn test_has_json_flag() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(has_json_flag(&args(&["svc", "version", "--json"])));
+        assert!(!has_json_flag(&args(&["svc", "version"])));
+        assert!(!has_json_flag(&args(&["svc", "--json"])));
+    }
+}
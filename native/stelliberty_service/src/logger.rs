@@ -4,7 +4,8 @@
 
 use chrono::Local;
 use std::collections::VecDeque;
-use std::sync::{Arc, LazyLock, Mutex};
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
 use tokio::sync::broadcast;
 
 // 日志缓冲区容量
@@ -13,8 +14,30 @@ const LOG_BUFFER_CAPACITY: usize = 500;
 // 日志广播通道容量
 const LOG_BROADCAST_CAPACITY: usize = 100;
 
+// launchd 将服务的 stdout/stderr 重定向到以下文件（安装时写入 plist）
+#[cfg(target_os = "macos")]
+pub const LAUNCHD_STDOUT_LOG_PATH: &str = "/var/log/stelliberty-service.log";
+#[cfg(target_os = "macos")]
+pub const LAUNCHD_STDERR_LOG_PATH: &str = "/var/log/stelliberty-service-error.log";
+
+// 缓冲区中的单条日志（附带写入时间，用于按时间分页）
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    // 写入时间（Unix 毫秒时间戳）
+    pub timestamp: i64,
+    pub line: String,
+}
+
+// 日志窗口查询结果
+#[derive(Debug, Clone, Default)]
+pub struct LogWindow {
+    pub lines: Vec<String>,
+    // 窗口之前是否还有更早的日志
+    pub has_more: bool,
+}
+
 // 全局日志缓冲区
-static LOG_BUFFER: LazyLock<Arc<Mutex<VecDeque<String>>>> =
+static LOG_BUFFER: LazyLock<Arc<Mutex<VecDeque<LogEntry>>>> =
     LazyLock::new(|| Arc::new(Mutex::new(VecDeque::with_capacity(LOG_BUFFER_CAPACITY))));
 
 // 全局日志广播通道（用于实时日志流）
@@ -23,8 +46,22 @@ static LOG_BROADCASTER: LazyLock<broadcast::Sender<String>> = LazyLock::new(|| {
     tx
 });
 
+// 日志最终写入的文件（init_logger 时确定）
+static LOG_FILE_PATH: OnceLock<Option<PathBuf>> = OnceLock::new();
+
 // 获取最近的 N 行日志
 pub fn get_recent_logs(lines: usize) -> Vec<String> {
+    get_logs_window(lines, 0, None, None).lines
+}
+
+// 按窗口获取日志
+// offset 为从最新一条往前跳过的行数，before/since 为可选的时间范围（Unix 毫秒）
+pub fn get_logs_window(
+    lines: usize,
+    offset: usize,
+    before_timestamp: Option<i64>,
+    since_timestamp: Option<i64>,
+) -> LogWindow {
     let buffer = match LOG_BUFFER.lock() {
         Ok(guard) => guard,
         Err(poisoned) => {
@@ -33,12 +70,33 @@ pub fn get_recent_logs(lines: usize) -> Vec<String> {
             poisoned.into_inner()
         }
     };
-    let start = if buffer.len() > lines {
-        buffer.len() - lines
-    } else {
-        0
-    };
-    buffer.iter().skip(start).cloned().collect()
+    window_logs(&buffer, lines, offset, before_timestamp, since_timestamp)
+}
+
+// 在给定缓冲区上计算日志窗口（按时间正序返回）
+fn window_logs(
+    buffer: &VecDeque<LogEntry>,
+    lines: usize,
+    offset: usize,
+    before_timestamp: Option<i64>,
+    since_timestamp: Option<i64>,
+) -> LogWindow {
+    let matched: Vec<&LogEntry> = buffer
+        .iter()
+        .filter(|entry| before_timestamp.is_none_or(|before| entry.timestamp < before))
+        .filter(|entry| since_timestamp.is_none_or(|since| entry.timestamp >= since))
+        .collect();
+
+    let end = matched.len().saturating_sub(offset);
+    let start = end.saturating_sub(lines);
+
+    LogWindow {
+        lines: matched[start..end]
+            .iter()
+            .map(|entry| entry.line.clone())
+            .collect(),
+        has_more: start > 0,
+    }
 }
 
 // 订阅日志流
@@ -77,7 +135,10 @@ impl log::Log for MemoryLogger {
                     poisoned.into_inner()
                 }
             };
-            buffer.push_back(log_line.clone());
+            buffer.push_back(LogEntry {
+                timestamp: now.timestamp_millis(),
+                line: log_line.clone(),
+            });
 
             // 保持缓冲区大小不超过上限
             while buffer.len() > LOG_BUFFER_CAPACITY {
@@ -92,10 +153,31 @@ impl log::Log for MemoryLogger {
     fn flush(&self) {}
 }
 
+// 当前平台下 stderr 日志落盘的位置：macOS 由 launchd 重定向到固定文件（不做轮转）；
+// Linux 写入 journald，Windows 服务没有 stderr，均不产生日志文件
+fn configured_log_file() -> Option<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        Some(PathBuf::from(LAUNCHD_STDERR_LOG_PATH))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        None
+    }
+}
+
+// 获取日志文件路径，日志未写入文件或日志系统未初始化时为 None
+pub fn log_file_path() -> Option<PathBuf> {
+    LOG_FILE_PATH.get().cloned().flatten()
+}
+
 // 初始化日志系统
 pub fn init_logger() {
     static LOGGER: MemoryLogger = MemoryLogger;
 
+    LOG_FILE_PATH.get_or_init(configured_log_file);
+
     log::set_max_level(log::LevelFilter::Debug);
 
     if log::set_logger(&LOGGER).is_err() {
@@ -105,3 +187,75 @@ pub fn init_logger() {
 
     log::info!("日志系统初始化完成 (内存缓冲模式)");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 构造时间戳为 1..=count 的测试缓冲区
+    fn build_buffer(count: i64) -> VecDeque<LogEntry> {
+        (1..=count)
+            .map(|i| LogEntry {
+                timestamp: i,
+                line: format!("line-{}", i),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_log_file_path_matches_init() {
+        init_logger();
+        assert_eq!(log_file_path(), configured_log_file());
+
+        #[cfg(target_os = "macos")]
+        assert_eq!(
+            log_file_path(),
+            Some(PathBuf::from("/var/log/stelliberty-service-error.log"))
+        );
+        #[cfg(not(target_os = "macos"))]
+        assert_eq!(log_file_path(), None);
+    }
+
+    #[test]
+    fn test_window_tail() {
+        let buffer = build_buffer(10);
+        let window = window_logs(&buffer, 3, 0, None, None);
+        assert_eq!(window.lines, vec!["line-8", "line-9", "line-10"]);
+        assert!(window.has_more);
+
+        // 请求行数超过缓冲区时返回全部
+        let window = window_logs(&buffer, 50, 0, None, None);
+        assert_eq!(window.lines.len(), 10);
+        assert!(!window.has_more);
+    }
+
+    #[test]
+    fn test_window_offset() {
+        let buffer = build_buffer(10);
+        let window = window_logs(&buffer, 3, 3, None, None);
+        assert_eq!(window.lines, vec!["line-5", "line-6", "line-7"]);
+        assert!(window.has_more);
+
+        // 最后一页：不足一页且没有更早的日志
+        let window = window_logs(&buffer, 3, 8, None, None);
+        assert_eq!(window.lines, vec!["line-1", "line-2"]);
+        assert!(!window.has_more);
+
+        // 偏移超出范围时返回空
+        let window = window_logs(&buffer, 3, 20, None, None);
+        assert!(window.lines.is_empty());
+        assert!(!window.has_more);
+    }
+
+    #[test]
+    fn test_window_time_range() {
+        let buffer = build_buffer(10);
+        let window = window_logs(&buffer, 2, 0, Some(6), None);
+        assert_eq!(window.lines, vec!["line-4", "line-5"]);
+        assert!(window.has_more);
+
+        let window = window_logs(&buffer, 10, 0, Some(6), Some(4));
+        assert_eq!(window.lines, vec!["line-4", "line-5"]);
+        assert!(!window.has_more);
+    }
+}
@@ -1,6 +1,7 @@
 // 服务模块
 
 pub mod handler;
+pub mod heartbeat;
 pub mod installer;
 pub mod runner;
 
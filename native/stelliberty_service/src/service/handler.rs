@@ -1,11 +1,45 @@
 // IPC 命令处理器
 
-use crate::clash::ClashManager;
-use crate::ipc::{IpcCommand, IpcResponse};
+use crate::clash::config_switch::{self, SwitchOutcome};
+use crate::clash::launch::{CorePriority, PortOverrides};
+use crate::clash::{ClashManager, StartError};
+use crate::clash::{
+    config_export, controller, dns_check, egress, geodata, kill_switch, ports, preflight,
+    stream_fanout,
+};
+use crate::ipc::{IpcCommand, IpcResponse, ServiceHealth};
+use crate::service::heartbeat;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+// 切换配置后等待核心控制器就绪的最长时间
+const SWITCH_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 以指定配置重启核心，并确认控制器在超时内响应
+async fn restart_and_wait(
+    clash_manager: &RwLock<ClashManager>,
+    config_path: String,
+) -> Result<(), String> {
+    let controller_path = {
+        let mut manager = clash_manager.write().await;
+        manager
+            .restart_with_config(config_path)
+            .map_err(|e| e.to_string())?;
+        manager.controller_path()
+    };
+
+    let controller_path = controller_path.ok_or_else(|| "未找到核心控制器地址".to_string())?;
+    if crate::clash::controller::wait_until_ready(&controller_path, SWITCH_READY_TIMEOUT).await {
+        Ok(())
+    } else {
+        Err(format!(
+            "核心未在 {} 秒内就绪",
+            SWITCH_READY_TIMEOUT.as_secs()
+        ))
+    }
+}
+
 // 创建命令处理器（异步）
 pub fn create_handler(
     clash_manager: Arc<RwLock<ClashManager>>,
@@ -13,6 +47,9 @@ pub fn create_handler(
 ) -> impl Fn(IpcCommand) -> std::pin::Pin<Box<dyn std::future::Future<Output = IpcResponse> + Send>>
 + Send
 + Sync {
+    // 处理器随服务启动创建，以此作为服务启动时间
+    let service_started = Instant::now();
+
     move |command: IpcCommand| {
         let clash_manager = clash_manager.clone();
         let last_heartbeat = last_heartbeat.clone();
@@ -24,10 +61,37 @@ pub fn create_handler(
                     config_path,
                     data_dir,
                     external_controller,
+                    mixed_port,
+                    socks_port,
+                    extra_args,
+                    priority,
                 } => {
                     log::info!("收到启动 Clash 命令");
+                    let ports = PortOverrides {
+                        mixed_port,
+                        socks_port,
+                    };
+                    let priority = match CorePriority::parse(priority.as_deref()) {
+                        Ok(priority) => priority,
+                        Err(e) => {
+                            let e = StartError::InvalidArguments(e);
+                            log::error!("Clash 启动失败 (code={}): {}", e.code(), e);
+                            return IpcResponse::Error {
+                                code: e.code(),
+                                message: format!("Clash 启动失败: {}", e),
+                            };
+                        }
+                    };
                     let mut manager = clash_manager.write().await;
-                    match manager.start(core_path, config_path, data_dir, external_controller) {
+                    manager.set_priority(priority);
+                    match manager.start(
+                        core_path,
+                        config_path,
+                        data_dir,
+                        external_controller,
+                        ports,
+                        extra_args,
+                    ) {
                         Ok(()) => {
                             log::info!("Clash 启动成功");
                             IpcResponse::Success {
@@ -35,9 +99,9 @@ pub fn create_handler(
                             }
                         }
                         Err(e) => {
-                            log::error!("Clash 启动失败: {}", e);
+                            log::error!("Clash 启动失败 (code={}): {}", e.code(), e);
                             IpcResponse::Error {
-                                code: 1001,
+                                code: e.code(),
                                 message: format!("Clash 启动失败: {}", e),
                             }
                         }
@@ -50,6 +114,7 @@ pub fn create_handler(
                     match manager.stop() {
                         Ok(()) => {
                             log::info!("Clash 停止成功");
+                            kill_switch::on_core_stopped();
                             IpcResponse::Success {
                                 message: Some("Clash 停止成功".to_string()),
                             }
@@ -82,10 +147,52 @@ pub fn create_handler(
                     }
                 }
 
-                IpcCommand::GetLogs { lines } => {
-                    log::trace!("收到获取日志命令 (请求 {} 行)", lines);
-                    let log_lines = crate::logger::get_recent_logs(lines);
-                    IpcResponse::Logs { lines: log_lines }
+                IpcCommand::Health => {
+                    let status = clash_manager.read().await.get_status();
+                    let heartbeat_age = last_heartbeat.read().await.elapsed();
+                    IpcResponse::Health {
+                        health: ServiceHealth {
+                            is_service_running: true,
+                            is_core_running: status.is_running,
+                            core_pid: status.pid,
+                            service_uptime_secs: service_started.elapsed().as_secs(),
+                            core_uptime_secs: status.uptime,
+                            heartbeat_age_secs: heartbeat_age.as_secs(),
+                        },
+                    }
+                }
+
+                IpcCommand::GetLogs {
+                    lines,
+                    offset,
+                    before_timestamp,
+                    since_timestamp,
+                } => {
+                    log::trace!("收到获取日志命令 (请求 {} 行, 偏移 {})", lines, offset);
+                    let window = crate::logger::get_logs_window(
+                        lines,
+                        offset,
+                        before_timestamp,
+                        since_timestamp,
+                    );
+                    IpcResponse::Logs {
+                        lines: window.lines,
+                        has_more: window.has_more,
+                    }
+                }
+
+                IpcCommand::GetServiceLogsPath => {
+                    log::debug!("收到获取日志路径命令");
+                    IpcResponse::LogsPath {
+                        path: crate::logger::log_file_path()
+                            .map(|path| path.to_string_lossy().into_owned()),
+                    }
+                }
+
+                IpcCommand::GetCoreOutput => {
+                    log::debug!("收到获取核心输出命令");
+                    let lines = clash_manager.read().await.recent_core_output();
+                    IpcResponse::CoreOutput { lines }
                 }
 
                 IpcCommand::GetVersion => {
@@ -96,6 +203,17 @@ pub fn create_handler(
                     }
                 }
 
+                IpcCommand::GetServicePaths => {
+                    log::debug!("收到获取服务路径命令");
+                    match crate::service::installer::service_paths() {
+                        Ok(paths) => IpcResponse::ServicePaths { paths },
+                        Err(e) => IpcResponse::Error {
+                            code: 1013,
+                            message: format!("获取服务路径失败: {}", e),
+                        },
+                    }
+                }
+
                 IpcCommand::StreamLogs => {
                     log::debug!("收到日志流订阅命令");
                     // 返回成功，客户端将持续轮询获取新日志
@@ -104,11 +222,400 @@ pub fn create_handler(
                     }
                 }
 
+                IpcCommand::StreamCoreOutput => {
+                    // 核心输出流由服务端连接层直接处理，这里仅作兜底
+                    IpcResponse::Success {
+                        message: Some("核心输出流已启用".to_string()),
+                    }
+                }
+
+                IpcCommand::SubscribeEvents => {
+                    // 事件订阅由服务端连接层直接处理，这里仅作兜底
+                    IpcResponse::Success {
+                        message: Some("事件订阅已启用".to_string()),
+                    }
+                }
+
+                IpcCommand::SubscribeControllerStream { stream } => {
+                    // 服务端连接层在成功后挂载订阅，这里负责确认核心状态并建立上游连接
+                    log::info!("收到控制器流订阅命令: {:?}", stream);
+                    let controller_path = {
+                        let manager = clash_manager.read().await;
+                        if !manager.is_running() {
+                            return IpcResponse::Error {
+                                code: 1012,
+                                message: "订阅控制器流失败: Clash 未运行".to_string(),
+                            };
+                        }
+                        manager.controller_path()
+                    };
+
+                    let Some(controller_path) = controller_path else {
+                        return IpcResponse::Error {
+                            code: 1012,
+                            message: "订阅控制器流失败: 未找到核心控制器地址".to_string(),
+                        };
+                    };
+
+                    stream_fanout::FANOUT.ensure_upstream(stream, move |sender| async move {
+                        let result =
+                            controller::stream_lines(&controller_path, stream.path(), |frame| {
+                                stream_fanout::forward_frame(&sender, frame)
+                            })
+                            .await;
+                        if let Err(e) = result {
+                            log::warn!("控制器流 {:?} 上游连接中断: {}", stream, e);
+                        }
+                    });
+                    IpcResponse::Success {
+                        message: Some("控制器流已启用".to_string()),
+                    }
+                }
+
                 IpcCommand::Heartbeat => {
                     log::debug!("收到主程序心跳");
                     *last_heartbeat.write().await = Instant::now();
                     IpcResponse::HeartbeatAck
                 }
+
+                IpcCommand::SuspendAutoShutdown { duration_secs } => {
+                    let duration = heartbeat::suspend(duration_secs.map(Duration::from_secs));
+                    log::info!("暂停心跳超时自动关闭 {} 秒", duration.as_secs());
+                    IpcResponse::AutoShutdown {
+                        is_suspended: true,
+                        remaining_secs: duration.as_secs(),
+                    }
+                }
+
+                IpcCommand::ResumeAutoShutdown => {
+                    if heartbeat::resume() {
+                        log::info!("恢复心跳超时自动关闭");
+                    }
+                    // 从恢复时刻重新计时，避免暂停期间的空档立即触发超时
+                    *last_heartbeat.write().await = Instant::now();
+                    IpcResponse::AutoShutdown {
+                        is_suspended: false,
+                        remaining_secs: 0,
+                    }
+                }
+
+                IpcCommand::FlushCache { kind } => {
+                    log::info!("收到清除缓存命令: {:?}", kind);
+                    let controller_path = {
+                        let manager = clash_manager.read().await;
+                        if !manager.is_running() {
+                            return IpcResponse::Error {
+                                code: 1003,
+                                message: "清除缓存失败: Clash 未运行".to_string(),
+                            };
+                        }
+                        manager.controller_path()
+                    };
+
+                    let Some(controller_path) = controller_path else {
+                        return IpcResponse::Error {
+                            code: 1003,
+                            message: "清除缓存失败: 未找到核心控制器地址".to_string(),
+                        };
+                    };
+
+                    match crate::clash::controller::flush_cache(&controller_path, kind).await {
+                        Ok(message) => {
+                            log::info!("{}", message);
+                            IpcResponse::Success {
+                                message: Some(message),
+                            }
+                        }
+                        Err(e) => {
+                            log::error!("{}", e);
+                            IpcResponse::Error {
+                                code: 1003,
+                                message: e,
+                            }
+                        }
+                    }
+                }
+
+                IpcCommand::WaitCoreReady { timeout_ms } => {
+                    log::debug!("收到等待核心就绪命令 (超时 {}ms)", timeout_ms);
+                    // 读取控制器路径后立即释放锁，避免轮询期间阻塞其他命令
+                    let controller_path = {
+                        let manager = clash_manager.read().await;
+                        if !manager.is_running() {
+                            return IpcResponse::Error {
+                                code: 1004,
+                                message: "等待核心就绪失败: Clash 未运行".to_string(),
+                            };
+                        }
+                        manager.controller_path()
+                    };
+
+                    let Some(controller_path) = controller_path else {
+                        return IpcResponse::Error {
+                            code: 1004,
+                            message: "等待核心就绪失败: 未找到核心控制器地址".to_string(),
+                        };
+                    };
+
+                    let start = Instant::now();
+                    let is_ready = crate::clash::controller::wait_until_ready(
+                        &controller_path,
+                        Duration::from_millis(timeout_ms),
+                    )
+                    .await;
+                    IpcResponse::CoreReadiness {
+                        is_ready,
+                        elapsed_ms: start.elapsed().as_millis() as u64,
+                    }
+                }
+
+                IpcCommand::ListOrphanCores => {
+                    log::debug!("收到列出孤立核心进程命令");
+                    let manager = clash_manager.read().await;
+                    match manager.list_orphan_cores() {
+                        Ok(cores) => {
+                            log::info!("检测到 {} 个孤立核心进程", cores.len());
+                            IpcResponse::OrphanCores { cores }
+                        }
+                        Err(e) => {
+                            log::error!("列出孤立核心进程失败: {}", e);
+                            IpcResponse::Error {
+                                code: 1005,
+                                message: format!("列出孤立核心进程失败: {}", e),
+                            }
+                        }
+                    }
+                }
+
+                IpcCommand::KillOrphanCores { pids } => {
+                    log::info!("收到清理孤立核心进程命令: {:?}", pids);
+                    let manager = clash_manager.read().await;
+                    match manager.kill_orphan_cores(&pids) {
+                        Ok((killed, failures)) if failures.is_empty() => IpcResponse::Success {
+                            message: Some(format!("已清理 {} 个孤立核心进程", killed.len())),
+                        },
+                        Ok((killed, failures)) => {
+                            log::error!("部分孤立核心进程清理失败: {}", failures.join("; "));
+                            IpcResponse::Error {
+                                code: 1005,
+                                message: format!(
+                                    "已清理 {} 个孤立核心进程，{} 个失败: {}",
+                                    killed.len(),
+                                    failures.len(),
+                                    failures.join("; ")
+                                ),
+                            }
+                        }
+                        Err(e) => {
+                            log::error!("清理孤立核心进程失败: {}", e);
+                            IpcResponse::Error {
+                                code: 1005,
+                                message: format!("清理孤立核心进程失败: {}", e),
+                            }
+                        }
+                    }
+                }
+
+                IpcCommand::CheckDnsReachability { nameservers } => {
+                    log::debug!("收到 DNS 可达性检测命令: {:?}", nameservers);
+                    let results =
+                        dns_check::check_nameservers(nameservers, dns_check::DNS_CHECK_TIMEOUT)
+                            .await;
+                    let unreachable = results.iter().filter(|r| !r.is_reachable).count();
+                    if unreachable > 0 {
+                        log::warn!("{}/{} 个 DNS 服务器不可达", unreachable, results.len());
+                    }
+                    IpcResponse::DnsReachability { results }
+                }
+
+                IpcCommand::CheckEgressIp { url } => {
+                    log::debug!("收到出口 IP 检测命令: {}", url);
+                    let proxy_port = {
+                        let manager = clash_manager.read().await;
+                        if !manager.is_running() {
+                            return IpcResponse::Error {
+                                code: 1007,
+                                message: "检测出口 IP 失败: Clash 未运行".to_string(),
+                            };
+                        }
+                        manager.proxy_port()
+                    };
+
+                    let Some(proxy_port) = proxy_port else {
+                        return IpcResponse::Error {
+                            code: 1007,
+                            message: "检测出口 IP 失败: 配置中没有 mixed-port 或 port".to_string(),
+                        };
+                    };
+
+                    match egress::check_egress_ip(proxy_port, &url, egress::EGRESS_CHECK_TIMEOUT)
+                        .await
+                    {
+                        Ok(info) => {
+                            log::info!("出口 IP: {} ({:?}, {:?})", info.ip, info.country, info.asn);
+                            IpcResponse::EgressIp {
+                                ip: info.ip,
+                                country: info.country,
+                                asn: info.asn,
+                            }
+                        }
+                        Err(e) => {
+                            log::warn!("检测出口 IP 失败: {}", e);
+                            IpcResponse::Error {
+                                code: 1007,
+                                message: format!("检测出口 IP 失败: {}", e),
+                            }
+                        }
+                    }
+                }
+
+                IpcCommand::SuggestFreePorts { count } => {
+                    log::debug!("收到空闲端口建议命令: {}", count);
+                    // 逐个绑定探测，放到阻塞线程执行
+                    let result =
+                        tokio::task::spawn_blocking(move || ports::suggest_free_ports(count))
+                            .await
+                            .unwrap_or_else(|e| Err(format!("探测任务异常: {}", e)));
+                    match result {
+                        Ok(ports) => IpcResponse::FreePorts { ports },
+                        Err(e) => {
+                            log::warn!("建议空闲端口失败: {}", e);
+                            IpcResponse::Error {
+                                code: 1008,
+                                message: format!("建议空闲端口失败: {}", e),
+                            }
+                        }
+                    }
+                }
+
+                IpcCommand::SetKillSwitch { enabled } => {
+                    log::info!("收到设置防泄漏开关命令: {}", enabled);
+                    match kill_switch::set_enabled(enabled) {
+                        Ok(status) => IpcResponse::KillSwitch {
+                            is_enabled: status.is_enabled,
+                            is_engaged: status.is_engaged,
+                        },
+                        Err(e) => {
+                            log::error!("设置防泄漏开关失败: {}", e);
+                            IpcResponse::Error {
+                                code: 1009,
+                                message: format!("设置防泄漏开关失败: {}", e),
+                            }
+                        }
+                    }
+                }
+
+                IpcCommand::RunPreflightCheck {
+                    core_path,
+                    data_dir,
+                } => {
+                    log::debug!("收到启动前自检命令: {}", core_path);
+                    let probe = preflight::SystemProbe { is_service: true };
+                    let items = preflight::run_checks(&probe, &core_path, &data_dir);
+                    for item in items.iter().filter(|item| !item.is_passed) {
+                        log::warn!("自检未通过 [{}]: {}", item.name, item.detail);
+                    }
+                    IpcResponse::Preflight { items }
+                }
+
+                IpcCommand::SwitchConfig { new_config_path } => {
+                    log::info!("收到切换配置命令: {}", new_config_path);
+                    let previous_config = {
+                        let manager = clash_manager.read().await;
+                        match manager.config_path() {
+                            Some(path) if manager.is_running() => path.to_string(),
+                            _ => {
+                                return IpcResponse::Error {
+                                    code: 1011,
+                                    message: "切换配置失败: Clash 未运行".to_string(),
+                                };
+                            }
+                        }
+                    };
+
+                    let outcome =
+                        config_switch::switch_config(&previous_config, &new_config_path, |path| {
+                            restart_and_wait(&clash_manager, path)
+                        })
+                        .await;
+                    match outcome {
+                        Ok(SwitchOutcome::Switched) => IpcResponse::ConfigSwitched {
+                            active_config_path: new_config_path,
+                            is_reverted: false,
+                            reason: None,
+                        },
+                        Ok(SwitchOutcome::Reverted { reason }) => IpcResponse::ConfigSwitched {
+                            active_config_path: previous_config,
+                            is_reverted: true,
+                            reason: Some(reason),
+                        },
+                        Err(e) => {
+                            log::error!("切换配置失败: {}", e);
+                            IpcResponse::Error {
+                                code: 1011,
+                                message: format!("切换配置失败: {}", e),
+                            }
+                        }
+                    }
+                }
+
+                IpcCommand::GetGeoDataInfo { data_dir } => {
+                    let data_dir = if data_dir.is_empty() {
+                        match clash_manager.read().await.data_dir() {
+                            Some(data_dir) => data_dir.to_string(),
+                            None => {
+                                return IpcResponse::Error {
+                                    code: 1010,
+                                    message: "查询地理数据失败: 未指定数据目录且核心未启动过"
+                                        .to_string(),
+                                };
+                            }
+                        }
+                    } else {
+                        data_dir
+                    };
+                    log::debug!("收到查询地理数据命令: {}", data_dir);
+                    let files = geodata::collect_geo_info(
+                        std::path::Path::new(&data_dir),
+                        std::time::SystemTime::now(),
+                    );
+                    IpcResponse::GeoDataInfo { files }
+                }
+
+                IpcCommand::ExportRunningConfig { redact } => {
+                    log::info!("收到导出运行配置命令 (脱敏: {})", redact);
+                    let controller_path = {
+                        let manager = clash_manager.read().await;
+                        if !manager.is_running() {
+                            return IpcResponse::Error {
+                                code: 1006,
+                                message: "导出运行配置失败: Clash 未运行".to_string(),
+                            };
+                        }
+                        manager.controller_path()
+                    };
+
+                    let Some(controller_path) = controller_path else {
+                        return IpcResponse::Error {
+                            code: 1006,
+                            message: "导出运行配置失败: 未找到核心控制器地址".to_string(),
+                        };
+                    };
+
+                    match config_export::export_running_config(&controller_path, redact).await {
+                        Ok((config, redacted_fields)) => IpcResponse::RunningConfig {
+                            config,
+                            redacted_fields,
+                        },
+                        Err(e) => {
+                            log::error!("{}", e);
+                            IpcResponse::Error {
+                                code: 1006,
+                                message: e,
+                            }
+                        }
+                    }
+                }
             }
         })
     }
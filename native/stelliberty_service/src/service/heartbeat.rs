@@ -0,0 +1,142 @@
+// 心跳超时判定
+//
+// 主程序执行耗时操作（导入大型订阅、更新地理数据等）时可能无法按时发送心跳，
+// 可通过 IPC 暂停超时判定。暂停最长持续 MAX_SUSPEND_DURATION，到期自动恢复，
+// 防止主程序在暂停期间崩溃后服务一直不被回收。
+
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// 超过该时间未收到心跳即判定主程序已退出
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(70);
+
+// 监控器检查间隔
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+// 单次暂停的最长时间
+pub const MAX_SUSPEND_DURATION: Duration = Duration::from_secs(5 * 60);
+
+// 暂停截止时间，None 表示未暂停
+static SUSPENDED_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+
+fn lock_suspension() -> MutexGuard<'static, Option<Instant>> {
+    SUSPENDED_UNTIL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 单次检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeartbeatStatus {
+    // 心跳正常，附带距离上次心跳（或暂停结束）的时间
+    Alive(Duration),
+    // 自动关闭已暂停，附带剩余暂停时间
+    Suspended(Duration),
+    // 心跳超时
+    TimedOut(Duration),
+}
+
+// 暂停超时判定，未指定或超出上限时按上限计算，返回实际暂停时间
+pub fn suspend(duration: Option<Duration>) -> Duration {
+    let duration = duration.map_or(MAX_SUSPEND_DURATION, |d| d.min(MAX_SUSPEND_DURATION));
+    *lock_suspension() = Some(Instant::now() + duration);
+    duration
+}
+
+// 恢复超时判定，返回此前是否处于暂停中
+pub fn resume() -> bool {
+    lock_suspension()
+        .take()
+        .is_some_and(|until| until > Instant::now())
+}
+
+// 当前的暂停截止时间（可能已过期）
+pub fn suspended_until() -> Option<Instant> {
+    *lock_suspension()
+}
+
+// 判定心跳状态：暂停期间不超时；暂停到期后从到期时刻重新计时，
+// 避免暂停期间累计的时间让监控器在恢复瞬间立即触发
+pub fn evaluate(
+    last_heartbeat: Instant,
+    suspended_until: Option<Instant>,
+    now: Instant,
+) -> HeartbeatStatus {
+    if let Some(until) = suspended_until
+        && until > now
+    {
+        return HeartbeatStatus::Suspended(until - now);
+    }
+
+    let since = suspended_until.map_or(last_heartbeat, |until| until.max(last_heartbeat));
+    let elapsed = now.saturating_duration_since(since);
+    if elapsed > HEARTBEAT_TIMEOUT {
+        HeartbeatStatus::TimedOut(elapsed)
+    } else {
+        HeartbeatStatus::Alive(elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn secs(value: u64) -> Duration {
+        Duration::from_secs(value)
+    }
+
+    #[test]
+    fn test_no_timeout_while_suspended() {
+        let start = Instant::now();
+        let until = Some(start + MAX_SUSPEND_DURATION);
+
+        // 暂停期间即使长时间无心跳也不触发
+        for elapsed in [80, 150, 299] {
+            assert_eq!(
+                evaluate(start, until, start + secs(elapsed)),
+                HeartbeatStatus::Suspended(MAX_SUSPEND_DURATION - secs(elapsed))
+            );
+        }
+        // 未暂停时同样的间隔会超时
+        assert_eq!(
+            evaluate(start, None, start + secs(80)),
+            HeartbeatStatus::TimedOut(secs(80))
+        );
+    }
+
+    #[test]
+    fn test_enforcement_resumes_after_suspension() {
+        let start = Instant::now();
+        let until = start + MAX_SUSPEND_DURATION;
+
+        // 暂停到期后从到期时刻重新计时
+        assert_eq!(
+            evaluate(start, Some(until), until + secs(30)),
+            HeartbeatStatus::Alive(secs(30))
+        );
+        assert_eq!(
+            evaluate(start, Some(until), until + secs(71)),
+            HeartbeatStatus::TimedOut(secs(71))
+        );
+
+        // 到期后收到的心跳优先
+        let heartbeat = until + secs(60);
+        assert_eq!(
+            evaluate(heartbeat, Some(until), heartbeat + secs(10)),
+            HeartbeatStatus::Alive(secs(10))
+        );
+    }
+
+    #[test]
+    fn test_suspend_is_capped_and_resumable() {
+        assert_eq!(suspend(Some(secs(3600))), MAX_SUSPEND_DURATION);
+        let until = suspended_until().unwrap();
+        assert!(until <= Instant::now() + MAX_SUSPEND_DURATION);
+        assert!(matches!(
+            evaluate(Instant::now(), Some(until), Instant::now()),
+            HeartbeatStatus::Suspended(_)
+        ));
+
+        assert!(resume());
+        assert_eq!(suspended_until(), None);
+        assert!(!resume());
+    }
+}
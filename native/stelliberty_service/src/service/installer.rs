@@ -8,6 +8,127 @@ use anyhow::Context;
 #[cfg(any(windows, target_os = "linux"))]
 const SERVICE_NAME: &str = "StellibertyService";
 
+// 默认文件创建掩码：数据目录中的文件仅 root 可读写
+pub const DEFAULT_UMASK: u32 = 0o077;
+
+// systemd 服务的权限集（仅 Linux 生效）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CapabilityProfile {
+    // 完整权限：支持 TUN、进程匹配（find-process-mode）与时间同步
+    #[default]
+    Full,
+    // 精简权限：不授予 CAP_SYS_TIME 与 CAP_SYS_PTRACE
+    Minimal,
+}
+
+// 各权限及其用途（按 unit 文件中的输出顺序）
+const CAPABILITIES: &[(&str, &str)] = &[
+    ("CAP_NET_ADMIN", "网络管理（TUN 设备、路由表）"),
+    ("CAP_NET_RAW", "原始套接字（ICMP、透明代理）"),
+    ("CAP_NET_BIND_SERVICE", "绑定特权端口（< 1024）"),
+    ("CAP_SYS_TIME", "修改系统时间（NTP 同步）"),
+    ("CAP_SYS_PTRACE", "进程追踪（find-process-mode）"),
+    ("CAP_DAC_READ_SEARCH", "读取文件权限绕过（配置文件）"),
+    ("CAP_DAC_OVERRIDE", "写入文件权限绕过（日志文件）"),
+];
+
+impl CapabilityProfile {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Minimal => "minimal",
+        }
+    }
+
+    // 该权限集包含的权限及用途
+    pub fn capabilities(self) -> Vec<(&'static str, &'static str)> {
+        CAPABILITIES
+            .iter()
+            .copied()
+            .filter(|(name, _)| {
+                self == Self::Full || !matches!(*name, "CAP_SYS_TIME" | "CAP_SYS_PTRACE")
+            })
+            .collect()
+    }
+}
+
+// 解析权限集名称（full / minimal）
+pub fn parse_capability_profile(value: &str) -> Result<CapabilityProfile> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "full" => Ok(CapabilityProfile::Full),
+        "minimal" => Ok(CapabilityProfile::Minimal),
+        _ => bail!("无效的权限集：{}（可选 full 或 minimal）", value),
+    }
+}
+
+// 安装选项
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    // 服务进程的文件创建掩码（Windows 下忽略）。未指定时沿用已安装配置中的值，
+    // 首次安装使用 DEFAULT_UMASK
+    pub umask: Option<u32>,
+    // 服务进程的权限集（仅 Linux 生效）。未指定时沿用已安装 unit 中的权限集，
+    // 首次安装使用完整权限
+    pub capability_profile: Option<CapabilityProfile>,
+}
+
+// 解析八进制 umask 字符串（如 "0077"、"027"）
+pub fn parse_umask(value: &str) -> Result<u32> {
+    let digits = value.trim();
+    let digits = digits.strip_prefix("0o").unwrap_or(digits);
+
+    if digits.is_empty() || digits.len() > 4 || !digits.chars().all(|c| ('0'..='7').contains(&c)) {
+        bail!("无效的 umask：{}（应为八进制，如 0077）", value);
+    }
+
+    let umask = u32::from_str_radix(digits, 8)?;
+    if umask > 0o777 {
+        bail!("无效的 umask：{}（不支持特殊权限位）", value);
+    }
+
+    Ok(umask)
+}
+
+// 从已安装的 systemd unit 中读取 UMask
+#[cfg(any(target_os = "linux", test))]
+fn umask_from_unit(content: &str) -> Option<u32> {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("UMask="))
+        .and_then(|value| parse_umask(value).ok())
+}
+
+// 从已安装的 systemd unit 中读取权限集（按 CapabilityBoundingSet 匹配，无法识别时返回 None）
+#[cfg(any(target_os = "linux", test))]
+fn capability_profile_from_unit(content: &str) -> Option<CapabilityProfile> {
+    let mut installed: Vec<&str> = content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("CapabilityBoundingSet="))?
+        .split_whitespace()
+        .collect();
+    installed.sort_unstable();
+
+    [CapabilityProfile::Full, CapabilityProfile::Minimal]
+        .into_iter()
+        .find(|profile| {
+            let mut names: Vec<&str> = profile
+                .capabilities()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect();
+            names.sort_unstable();
+            names == installed
+        })
+}
+
+// 从已安装的 launchd plist 中读取 Umask（plist 中为十进制整数）
+#[cfg(any(target_os = "macos", test))]
+fn umask_from_plist(content: &str) -> Option<u32> {
+    let rest = &content[content.find("<key>Umask</key>")?..];
+    let value = rest.split_once("<integer>")?.1.split_once("</integer>")?.0;
+    value.trim().parse().ok().filter(|umask| *umask <= 0o777)
+}
+
 // ============ Windows Service 实现 ============
 
 #[cfg(windows)]
@@ -29,12 +150,15 @@ const SERVICE_DISPLAY_NAME: &str = "Stelliberty Service";
 const SERVICE_DESCRIPTION: &str = "Stelliberty 后台服务，用于管理 Clash 核心和提供系统级 TUN 支持";
 
 #[cfg(windows)]
-pub fn install_service() -> Result<()> {
+pub fn install_service(_options: &InstallOptions) -> Result<()> {
     println!("正在安装 Stelliberty Service...");
 
     let service_binary = std::env::current_exe().context("无法获取当前程序路径")?;
     println!("服务程序: {}", service_binary.display());
 
+    // 应用可能已移动到新目录而服务程序未变化，每次安装都按当前位置刷新核心路径策略
+    write_core_policy(&service_binary)?;
+
     let manager = ServiceManager::local_computer(
         None::<&str>,
         ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
@@ -381,7 +505,18 @@ use std::process::Command;
 const SERVICE_FILE: &str = "/etc/systemd/system/StellibertyService.service";
 
 #[cfg(target_os = "linux")]
-fn get_service_unit(binary_path: &str) -> String {
+fn get_service_unit(binary_path: &str, umask: u32, profile: CapabilityProfile) -> String {
+    let capabilities = profile.capabilities();
+    let capability_names = capabilities
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let capability_notes: String = capabilities
+        .iter()
+        .map(|(name, usage)| format!("# {name}: {usage}\n"))
+        .collect();
+
     format!(
         r#"[Unit]
 Description=Stelliberty Service
@@ -389,7 +524,7 @@ After=network.target
 
 [Service]
 Type=simple
-UMask=0077
+UMask={umask:04o}
 ExecStart={binary_path}
 Restart=on-failure
 RestartSec=5s
@@ -398,18 +533,11 @@ StandardError=journal
 SyslogIdentifier=stelliberty
 
 # 只授予 Clash 核心所需的最小权限集
-CapabilityBoundingSet=CAP_NET_ADMIN CAP_NET_RAW CAP_NET_BIND_SERVICE CAP_SYS_TIME CAP_SYS_PTRACE CAP_DAC_READ_SEARCH CAP_DAC_OVERRIDE
-AmbientCapabilities=CAP_NET_ADMIN CAP_NET_RAW CAP_NET_BIND_SERVICE CAP_SYS_TIME CAP_SYS_PTRACE CAP_DAC_READ_SEARCH CAP_DAC_OVERRIDE
+CapabilityBoundingSet={capability_names}
+AmbientCapabilities={capability_names}
 
 # 权限说明：
-# CAP_NET_ADMIN: 网络管理（TUN 设备、路由表）
-# CAP_NET_RAW: 原始套接字（ICMP、透明代理）
-# CAP_NET_BIND_SERVICE: 绑定特权端口（< 1024）
-# CAP_SYS_TIME: 修改系统时间（NTP 同步）
-# CAP_SYS_PTRACE: 进程追踪（find-process-mode）
-# CAP_DAC_READ_SEARCH: 读取文件权限绕过（配置文件）
-# CAP_DAC_OVERRIDE: 写入文件权限绕过（日志文件）
-
+{capability_notes}
 [Install]
 WantedBy=multi-user.target
 "#
@@ -417,20 +545,46 @@ WantedBy=multi-user.target
 }
 
 #[cfg(target_os = "linux")]
-pub fn install_service() -> Result<()> {
+pub fn install_service(options: &InstallOptions) -> Result<()> {
     println!("正在安装 Stelliberty Service (systemd)...");
 
     let service_binary = std::env::current_exe().context("无法获取当前程序路径")?;
     println!("服务程序: {}", service_binary.display());
 
+    // 应用可能已移动到新目录而服务程序未变化，每次安装都按当前位置刷新核心路径策略
+    write_core_policy(&service_binary)?;
+
+    let private_service_binary = get_service_private_binary()?;
+    let installed_unit = fs::read_to_string(SERVICE_FILE).ok();
+    // 未指定 --umask 时保留此前安装时配置的值
+    let umask = options
+        .umask
+        .or_else(|| installed_unit.as_deref().and_then(umask_from_unit))
+        .unwrap_or(DEFAULT_UMASK);
+    // 未指定 --capabilities 时同样沿用已安装的权限集，避免重装时静默恢复为完整权限
+    let capability_profile = options
+        .capability_profile
+        .or_else(|| {
+            installed_unit
+                .as_deref()
+                .and_then(capability_profile_from_unit)
+        })
+        .unwrap_or_default();
+    let unit_content = get_service_unit(
+        &private_service_binary.display().to_string(),
+        umask,
+        capability_profile,
+    );
+
     // 检查服务是否已安装
     if Path::new(SERVICE_FILE).exists() {
         println!("服务文件已存在，正在检查状态...");
 
-        // 检查是否需要更新
+        // 检查是否需要更新（程序文件或 unit 配置变化）
         let needs_update = check_service_needs_update(&service_binary)?;
+        let unit_outdated = installed_unit.as_deref() != Some(unit_content.as_str());
 
-        if needs_update {
+        if needs_update || unit_outdated {
             println!("检测到服务需要更新");
 
             // 获取当前服务状态
@@ -460,9 +614,21 @@ pub fn install_service() -> Result<()> {
             }
 
             // 更新服务二进制文件（原地覆盖）
-            println!("正在更新服务文件...");
-            update_service_binary(&service_binary)?;
-            println!("服务文件更新成功");
+            if needs_update {
+                println!("正在更新服务文件...");
+                update_service_binary(&service_binary)?;
+                println!("服务文件更新成功");
+            }
+
+            // 更新 unit 文件
+            if unit_outdated {
+                println!(
+                    "正在更新 systemd unit (UMask={:04o}, 权限集={})...",
+                    umask,
+                    capability_profile.name()
+                );
+                fs::write(SERVICE_FILE, &unit_content).context("更新 systemd unit 文件失败")?;
+            }
 
             // 重载 systemd 配置
             println!("正在重载 systemd...");
@@ -516,8 +682,6 @@ pub fn install_service() -> Result<()> {
     update_service_binary(&service_binary)?;
 
     // 注册服务（使用私有目录中的二进制文件）
-    let private_service_binary = get_service_private_binary()?;
-    let unit_content = get_service_unit(&private_service_binary.display().to_string());
     fs::write(SERVICE_FILE, unit_content)
         .context("创建 systemd unit 文件失败，请确保以 root 身份运行")?;
 
@@ -713,7 +877,7 @@ const SERVICE_LABEL: &str = "com.stelliberty.service";
 const SERVICE_PLIST_PATH: &str = "/Library/LaunchDaemons/com.stelliberty.service.plist";
 
 #[cfg(target_os = "macos")]
-fn get_launchd_plist(binary_path: &str) -> String {
+fn get_launchd_plist(binary_path: &str, umask: u32) -> String {
     format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
 <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
@@ -729,27 +893,41 @@ fn get_launchd_plist(binary_path: &str) -> String {
     <true/>
     <key>KeepAlive</key>
     <true/>
+    <key>Umask</key>
+    <integer>{}</integer>
     <key>StandardOutPath</key>
-    <string>/var/log/stelliberty-service.log</string>
+    <string>{}</string>
     <key>StandardErrorPath</key>
-    <string>/var/log/stelliberty-service-error.log</string>
+    <string>{}</string>
 </dict>
 </plist>"#,
-        SERVICE_LABEL, binary_path
+        SERVICE_LABEL,
+        binary_path,
+        umask,
+        crate::logger::LAUNCHD_STDOUT_LOG_PATH,
+        crate::logger::LAUNCHD_STDERR_LOG_PATH
     )
 }
 
+// 以管理员权限执行 shell 命令。主程序已通过 osascript 提权运行 install 等子命令，
+// 此时直接执行，避免再次弹出授权对话框
 #[cfg(target_os = "macos")]
 fn execute_with_privilege(script: &str) -> Result<()> {
-    let command = format!(
-        r#"do shell script "{}" with administrator privileges"#,
-        script.replace('"', "\\\"")
-    );
-
-    let status = Command::new("osascript")
-        .args(["-e", &command])
-        .status()
-        .context("执行 osascript 失败")?;
+    let status = if unsafe { libc::geteuid() } == 0 {
+        Command::new("/bin/sh")
+            .args(["-c", script])
+            .status()
+            .context("执行 shell 命令失败")?
+    } else {
+        let command = format!(
+            r#"do shell script "{}" with administrator privileges"#,
+            script.replace('"', "\\\"")
+        );
+        Command::new("osascript")
+            .args(["-e", &command])
+            .status()
+            .context("执行 osascript 失败")?
+    };
 
     if !status.success() {
         let exit_code = status
@@ -761,21 +939,52 @@ fn execute_with_privilege(script: &str) -> Result<()> {
     Ok(())
 }
 
+// 写入 launchd plist（先写临时文件，再提权复制到系统目录）
+#[cfg(target_os = "macos")]
+fn write_plist_with_privilege(plist_content: &str) -> Result<()> {
+    // 创建临时文件（使用唯一路径避免冲突）
+    let temp_plist = "/tmp/stelliberty-service-install.plist";
+    fs::write(temp_plist, plist_content).context("创建临时 plist 文件失败")?;
+
+    let copy_script = format!(
+        "cp {} {} && chmod 644 {}",
+        temp_plist, SERVICE_PLIST_PATH, SERVICE_PLIST_PATH
+    );
+    let result = execute_with_privilege(&copy_script);
+
+    // 清理临时文件
+    let _ = fs::remove_file(temp_plist);
+    result
+}
+
 #[cfg(target_os = "macos")]
-pub fn install_service() -> Result<()> {
+pub fn install_service(options: &InstallOptions) -> Result<()> {
     println!("正在安装 Stelliberty Service (launchd)...");
 
     let service_binary = std::env::current_exe().context("无法获取当前程序路径")?;
     println!("服务程序: {}", service_binary.display());
 
+    // 应用可能已移动到新目录而服务程序未变化，每次安装都按当前位置刷新核心路径策略
+    write_core_policy(&service_binary)?;
+
+    let private_service_binary = get_service_private_binary()?;
+    let installed_plist = fs::read_to_string(SERVICE_PLIST_PATH).ok();
+    // 未指定 --umask 时保留此前安装时配置的值
+    let umask = options
+        .umask
+        .or_else(|| installed_plist.as_deref().and_then(umask_from_plist))
+        .unwrap_or(DEFAULT_UMASK);
+    let plist_content = get_launchd_plist(&private_service_binary.display().to_string(), umask);
+
     // 检查服务是否已安装
     if Path::new(SERVICE_PLIST_PATH).exists() {
         println!("服务文件已存在，正在检查状态...");
 
-        // 检查是否需要更新
+        // 检查是否需要更新（程序文件或 plist 配置变化）
         let needs_update = check_service_needs_update(&service_binary)?;
+        let plist_outdated = installed_plist.as_deref() != Some(plist_content.as_str());
 
-        if needs_update {
+        if needs_update || plist_outdated {
             println!("检测到服务需要更新");
 
             // 检查服务是否在运行
@@ -794,9 +1003,17 @@ pub fn install_service() -> Result<()> {
             }
 
             // 更新服务二进制文件（原地覆盖）
-            println!("正在更新服务文件...");
-            update_service_binary(&service_binary)?;
-            println!("服务文件更新成功");
+            if needs_update {
+                println!("正在更新服务文件...");
+                update_service_binary(&service_binary)?;
+                println!("服务文件更新成功");
+            }
+
+            // 更新 plist 文件
+            if plist_outdated {
+                println!("正在更新 launchd plist (Umask={:04o})...", umask);
+                write_plist_with_privilege(&plist_content)?;
+            }
 
             // 如果服务之前在运行，重新加载
             if was_running {
@@ -836,23 +1053,11 @@ pub fn install_service() -> Result<()> {
     update_service_binary(&service_binary)?;
 
     // 注册服务（使用私有目录中的二进制文件）
-    let private_service_binary = get_service_private_binary()?;
-    let plist_content = get_launchd_plist(&private_service_binary.display().to_string());
-
-    // 创建临时文件（使用唯一路径避免冲突）
-    let temp_plist = "/tmp/stelliberty-service-install.plist";
-    fs::write(temp_plist, plist_content).context("创建临时 plist 文件失败")?;
-
-    // 使用 AppleScript 提权执行安装命令
-    let install_script = format!(
-        "cp {} {} && chmod 644 {} && launchctl load {}",
-        temp_plist, SERVICE_PLIST_PATH, SERVICE_PLIST_PATH, SERVICE_PLIST_PATH
-    );
-
-    execute_with_privilege(&install_script)?;
+    write_plist_with_privilege(&plist_content)?;
 
-    // 清理临时文件
-    let _ = fs::remove_file(temp_plist);
+    // 使用 AppleScript 提权执行加载命令
+    let load_script = format!("launchctl load {}", SERVICE_PLIST_PATH);
+    execute_with_privilege(&load_script)?;
 
     println!("服务安装成功");
     println!();
@@ -941,11 +1146,185 @@ pub fn stop_service() -> Result<()> {
     Ok(())
 }
 
+// ============ 服务重启 ============
+
+// 重启时等待服务退出的最长时间（Windows SCM 的停止请求是异步的）
+const RESTART_STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+const RESTART_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+// 按“停止 → 等待退出 → 启动”的顺序重启；停止失败或超时未退出时不再启动
+pub fn restart_with(
+    stop: impl FnOnce() -> Result<()>,
+    mut is_stopped: impl FnMut() -> bool,
+    start: impl FnOnce() -> Result<()>,
+    timeout: std::time::Duration,
+    poll_interval: std::time::Duration,
+) -> Result<()> {
+    stop()?;
+
+    let deadline = std::time::Instant::now() + timeout;
+    while !is_stopped() {
+        if std::time::Instant::now() >= deadline {
+            bail!("服务未在 {} 秒内停止，已取消启动", timeout.as_secs());
+        }
+        std::thread::sleep(poll_interval);
+    }
+
+    start()
+}
+
+// 重启服务：复用 stop/start 子命令的实现，不修改服务注册项与程序文件
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+pub fn restart_service() -> Result<()> {
+    println!("正在重启 Stelliberty Service...");
+
+    if !is_service_registered() {
+        bail!("服务未安装，请先运行 install 命令");
+    }
+
+    restart_with(
+        stop_service,
+        is_service_stopped,
+        start_service,
+        RESTART_STOP_TIMEOUT,
+        RESTART_POLL_INTERVAL,
+    )?;
+    println!("服务重启成功");
+    Ok(())
+}
+
+// ============ 服务修复 ============
+
+// 修复决策
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairAction {
+    // 服务已注册，但私有目录中的程序缺失或与内置程序不一致：重新复制并重启
+    Repair,
+    // 服务已注册且程序完好：无需复制，仅确保服务运行
+    Healthy,
+    // 服务未注册：无法修复，需要走完整安装流程
+    Install,
+}
+
+// 根据服务注册状态与私有目录程序状态决定修复方式
+pub fn plan_repair(is_registered: bool, is_binary_intact: bool) -> RepairAction {
+    match (is_registered, is_binary_intact) {
+        (false, _) => RepairAction::Install,
+        (true, false) => RepairAction::Repair,
+        (true, true) => RepairAction::Healthy,
+    }
+}
+
+// 修复服务：程序被杀毒软件隔离等情况下重新复制私有目录中的程序并重启服务
+// 不删除、不重建服务注册项
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+pub fn repair_service() -> Result<()> {
+    println!("正在修复 Stelliberty Service...");
+
+    let service_binary = std::env::current_exe().context("无法获取当前程序路径")?;
+    let is_binary_intact = !check_service_needs_update(&service_binary)?;
+    let action = plan_repair(is_service_registered(), is_binary_intact);
+    if action != RepairAction::Install {
+        write_core_policy(&service_binary)?;
+    }
+
+    match action {
+        RepairAction::Install => {
+            bail!("服务未注册，无法修复，请运行 install 命令安装服务");
+        }
+        RepairAction::Healthy => {
+            println!("私有目录中的服务程序完好，无需重新复制");
+            start_service()
+        }
+        RepairAction::Repair => {
+            println!("私有目录中的服务程序缺失或已损坏，正在重新复制...");
+
+            // 程序缺失时服务通常已停止，停止失败不影响后续复制
+            if let Err(e) = stop_service() {
+                println!("警告: 停止服务失败: {e}，继续修复");
+            }
+
+            update_service_binary(&service_binary)?;
+            start_service()?;
+            println!("服务修复成功");
+            Ok(())
+        }
+    }
+}
+
+// 检查服务是否已注册到系统服务管理器
+#[cfg(windows)]
+fn is_service_registered() -> bool {
+    ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .and_then(|manager| manager.open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS))
+        .is_ok()
+}
+
+#[cfg(target_os = "linux")]
+fn is_service_registered() -> bool {
+    Path::new(SERVICE_FILE).exists()
+}
+
+#[cfg(target_os = "macos")]
+fn is_service_registered() -> bool {
+    Path::new(SERVICE_PLIST_PATH).exists()
+}
+
+// 检查服务进程是否已退出
+#[cfg(windows)]
+fn is_service_stopped() -> bool {
+    ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .and_then(|manager| manager.open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS))
+        .and_then(|service| service.query_status())
+        .is_ok_and(|status| status.current_state == ServiceState::Stopped)
+}
+
+#[cfg(target_os = "linux")]
+fn is_service_stopped() -> bool {
+    Command::new("systemctl")
+        .args(["is-active", SERVICE_NAME])
+        .output()
+        .is_ok_and(|output| {
+            matches!(
+                String::from_utf8_lossy(&output.stdout).trim(),
+                "inactive" | "failed"
+            )
+        })
+}
+
+#[cfg(target_os = "macos")]
+fn is_service_stopped() -> bool {
+    // launchctl list 找不到标签说明服务已卸载
+    Command::new("launchctl")
+        .args(["list", SERVICE_LABEL])
+        .output()
+        .is_ok_and(|output| !output.status.success())
+}
+
 // ============ 辅助函数 ============
 
+// 主程序在提权前解析的私有目录（--private-dir）。提权后以 root 运行时
+// dirs::data_dir() 指向 root 的目录，不能再据此推算
+static PRIVATE_DIR_OVERRIDE: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
+// 设置私有目录（仅接受绝对路径）
+pub fn set_private_dir(value: &str) -> Result<()> {
+    let path = std::path::PathBuf::from(value);
+    if !path.is_absolute() {
+        bail!("私有目录必须是绝对路径：{}", value);
+    }
+    if PRIVATE_DIR_OVERRIDE.set(path).is_err() {
+        bail!("重复指定私有目录");
+    }
+    Ok(())
+}
+
 // 获取服务私有目录路径（AppData/Roaming/stelliberty/service）
 #[cfg(any(windows, target_os = "linux", target_os = "macos"))]
 fn get_service_private_dir() -> Result<std::path::PathBuf> {
+    if let Some(dir) = PRIVATE_DIR_OVERRIDE.get() {
+        return Ok(dir.clone());
+    }
     let app_data_dir = dirs::data_dir()
         .context("无法获取应用数据目录")?
         .join("stelliberty")
@@ -964,6 +1343,21 @@ fn get_service_private_binary() -> Result<std::path::PathBuf> {
     Ok(get_service_private_dir()?.join("stelliberty-service"))
 }
 
+// 服务实际使用的路径（响应 GetServicePaths，主程序据此定位服务程序）
+// 以正在运行的程序为准：服务以 LocalSystem / root 运行时 dirs::data_dir() 指向系统账户的目录，
+// 按其推算的私有目录与安装时使用的用户目录不一致
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+pub fn service_paths() -> Result<crate::ipc::ServicePaths> {
+    let to_string = |path: &std::path::Path| path.to_string_lossy().into_owned();
+    let binary = std::env::current_exe().context("无法获取当前程序路径")?;
+    let private_dir = binary.parent().context("无法获取服务程序所在目录")?;
+    Ok(crate::ipc::ServicePaths {
+        private_dir: to_string(private_dir),
+        binary_path: to_string(&binary),
+        log_path: crate::logger::log_file_path().map(|path| to_string(&path)),
+    })
+}
+
 // 检查服务是否需要更新（比较当前二进制文件和私有目录中的文件）
 #[cfg(any(windows, target_os = "linux", target_os = "macos"))]
 fn check_service_needs_update(current_exe: &std::path::Path) -> Result<bool> {
@@ -1003,19 +1397,34 @@ fn update_service_binary(current_exe: &std::path::Path) -> Result<()> {
             .with_context(|| format!("无法创建私有目录：{}", private_dir.display()))?;
     }
 
+    // 复制前校验源文件哈希，防止被替换的程序以管理员权限运行
+    verify_source_binary(current_exe)?;
+
     // 获取源文件大小用于验证
     let source_size = std::fs::metadata(current_exe)
         .with_context(|| format!("无法获取源文件元数据：{}", current_exe.display()))?
         .len();
 
-    // 复制文件（覆盖旧版本）
-    std::fs::copy(current_exe, &private_binary).with_context(|| {
+    // 复制文件（覆盖旧版本）；旧服务进程尚未释放文件时退避重试，
+    // 仍被占用则改为写入临时文件后替换
+    let copy_context = || {
         format!(
             "无法复制服务程序从 {} 到 {}",
             current_exe.display(),
             private_binary.display()
         )
-    })?;
+    };
+    match copy_with_retry(
+        || std::fs::copy(current_exe, &private_binary),
+        std::thread::sleep,
+    ) {
+        Ok(_) => {}
+        Err(e) if is_file_busy(&e) => {
+            println!("服务程序仍被占用，改为写入临时文件后替换");
+            replace_via_temp(current_exe, &private_binary).with_context(copy_context)?;
+        }
+        Err(e) => return Err(e).with_context(copy_context),
+    }
 
     // 验证文件完整性
     let copied_size = std::fs::metadata(&private_binary)
@@ -1033,3 +1442,559 @@ fn update_service_binary(current_exe: &std::path::Path) -> Result<()> {
     println!("服务程序已复制到私有目录（{} 字节）", copied_size);
     Ok(())
 }
+
+// 目标文件被占用时的系统错误码
+// Windows: ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION；Unix: 覆盖正在运行的程序返回 ETXTBSY
+#[cfg(windows)]
+const FILE_BUSY_CODES: &[i32] = &[32, 33];
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+const FILE_BUSY_CODES: &[i32] = &[libc::ETXTBSY];
+
+// 复制重试：首次等待 200ms，之后逐次翻倍，单次最多 1 秒（共约 4 秒）
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+const COPY_RETRY_ATTEMPTS: u32 = 6;
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+const COPY_RETRY_BASE_DELAY: std::time::Duration = std::time::Duration::from_millis(200);
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+const COPY_RETRY_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn is_file_busy(error: &std::io::Error) -> bool {
+    error
+        .raw_os_error()
+        .is_some_and(|code| FILE_BUSY_CODES.contains(&code))
+}
+
+// 第 attempt 次复制失败后的等待时间，不应重试时返回 None
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn copy_retry_delay(error: &std::io::Error, attempt: u32) -> Option<std::time::Duration> {
+    if attempt >= COPY_RETRY_ATTEMPTS || !is_file_busy(error) {
+        return None;
+    }
+    Some((COPY_RETRY_BASE_DELAY * 2u32.pow(attempt)).min(COPY_RETRY_MAX_DELAY))
+}
+
+// 执行复制，文件被占用时按退避间隔重试，其余错误直接返回
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn copy_with_retry(
+    mut copy: impl FnMut() -> std::io::Result<u64>,
+    sleep: impl Fn(std::time::Duration),
+) -> std::io::Result<u64> {
+    let mut attempt = 0;
+    loop {
+        match copy() {
+            Ok(size) => return Ok(size),
+            Err(e) => match copy_retry_delay(&e, attempt) {
+                Some(delay) => {
+                    println!(
+                        "服务程序被占用（第 {} 次尝试）: {}，{} ms 后重试",
+                        attempt + 1,
+                        e,
+                        delay.as_millis()
+                    );
+                    sleep(delay);
+                    attempt += 1;
+                }
+                None => return Err(e),
+            },
+        }
+    }
+}
+
+// 先复制到同目录的临时文件，再重命名覆盖目标文件；失败时清理临时文件
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn replace_via_temp(source: &std::path::Path, target: &std::path::Path) -> std::io::Result<()> {
+    let mut temp_name = target.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".new");
+    let temp = target.with_file_name(temp_name);
+
+    let result = std::fs::copy(source, &temp).and_then(|_| move_into_place(&temp, target));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+// 被替换下来的旧程序路径（<文件名>.old）
+#[cfg(any(windows, test))]
+fn replaced_binary_path(target: &std::path::Path) -> std::path::PathBuf {
+    let mut old_name = target.file_name().unwrap_or_default().to_os_string();
+    old_name.push(".old");
+    target.with_file_name(old_name)
+}
+
+// Windows 上正在运行的程序无法被覆盖，但可以重命名：先将目标移到 <文件名>.old
+// （服务下次启动时删除），再把临时文件移入；移入失败时还原
+#[cfg(windows)]
+fn move_into_place(temp: &std::path::Path, target: &std::path::Path) -> std::io::Result<()> {
+    if !target.exists() {
+        return std::fs::rename(temp, target);
+    }
+    let old = replaced_binary_path(target);
+    let _ = std::fs::remove_file(&old);
+    std::fs::rename(target, &old)?;
+    std::fs::rename(temp, target).inspect_err(|_| {
+        let _ = std::fs::rename(&old, target);
+    })
+}
+
+#[cfg(all(not(windows), any(target_os = "linux", target_os = "macos")))]
+fn move_into_place(temp: &std::path::Path, target: &std::path::Path) -> std::io::Result<()> {
+    std::fs::rename(temp, target)
+}
+
+// 删除上次更新时替换下来的旧程序（更新时旧程序仍在运行，无法立即删除）
+#[cfg(windows)]
+pub fn remove_replaced_binary() {
+    let Ok(current_exe) = std::env::current_exe() else {
+        return;
+    };
+    let old = replaced_binary_path(&current_exe);
+    if old.exists() {
+        match std::fs::remove_file(&old) {
+            Ok(()) => log::info!("已删除更新前的旧程序: {}", old.display()),
+            Err(e) => log::warn!("删除旧程序失败: {} ({})", old.display(), e),
+        }
+    }
+}
+
+// 记录核心路径策略：只允许服务启动应用核心目录下的 clash-core
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn write_core_policy(current_exe: &std::path::Path) -> Result<()> {
+    use crate::clash::core_policy::{CORE_POLICY_FILE_NAME, CorePolicy};
+
+    let private_dir = get_service_private_dir()?;
+    std::fs::create_dir_all(&private_dir)
+        .with_context(|| format!("无法创建私有目录：{}", private_dir.display()))?;
+
+    let policy = CorePolicy::for_bundled_service(current_exe);
+    policy
+        .save_to(&private_dir.join(CORE_POLICY_FILE_NAME))
+        .map_err(anyhow::Error::msg)?;
+
+    for root in &policy.allowed_roots {
+        println!("允许的核心目录: {}", root.display());
+    }
+    Ok(())
+}
+
+// 读取源文件旁的 SHA-256 校验文件（<文件名>.sha256），未提供时返回 None
+// 文件内容兼容 sha256sum 格式：第一个字段为十六进制哈希
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn read_expected_sha256(source: &std::path::Path) -> Result<Option<String>> {
+    let Some(file_name) = source.file_name() else {
+        return Ok(None);
+    };
+    let sidecar = source.with_file_name(format!("{}.sha256", file_name.to_string_lossy()));
+    if !sidecar.exists() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(&sidecar)
+        .with_context(|| format!("无法读取校验文件：{}", sidecar.display()))?;
+    let expected = content
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    if expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("校验文件格式无效：{}", sidecar.display());
+    }
+
+    Ok(Some(expected))
+}
+
+// 计算文件的 SHA-256（小写十六进制）
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn sha256_file(path: &std::path::Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("无法打开文件：{}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("读取文件失败：{}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+// 校验源服务程序的 SHA-256（未提供校验文件时跳过，保持原有行为）
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn verify_source_binary(source: &std::path::Path) -> Result<()> {
+    let Some(expected) = read_expected_sha256(source)? else {
+        return Ok(());
+    };
+
+    let actual = sha256_file(source)?;
+    if actual != expected {
+        bail!(
+            "服务程序 SHA-256 校验失败，拒绝安装：期望 {}，实际 {}",
+            expected,
+            actual
+        );
+    }
+
+    println!("服务程序 SHA-256 校验通过");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_service_paths_report_running_binary() {
+        let paths = service_paths().unwrap();
+        let current_exe = std::env::current_exe().unwrap();
+
+        assert_eq!(std::path::PathBuf::from(&paths.binary_path), current_exe);
+        assert!(std::path::Path::new(&paths.binary_path).is_file());
+        // 私有目录为正在运行的程序所在目录
+        assert_eq!(
+            current_exe.parent(),
+            Some(std::path::Path::new(&paths.private_dir))
+        );
+        assert_eq!(
+            paths.log_path,
+            crate::logger::log_file_path().map(|path| path.to_string_lossy().into_owned())
+        );
+    }
+
+    fn busy_error() -> std::io::Error {
+        std::io::Error::from_raw_os_error(FILE_BUSY_CODES[0])
+    }
+
+    #[test]
+    fn test_copy_retry_on_busy_file() {
+        use std::cell::RefCell;
+        use std::time::Duration;
+
+        // 仅文件被占用时重试，等待时间逐次翻倍且有上限
+        assert_eq!(
+            copy_retry_delay(&busy_error(), 0),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            copy_retry_delay(&busy_error(), 1),
+            Some(Duration::from_millis(400))
+        );
+        assert_eq!(
+            copy_retry_delay(&busy_error(), 3),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(copy_retry_delay(&busy_error(), COPY_RETRY_ATTEMPTS), None);
+        let not_found = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(copy_retry_delay(&not_found, 0), None);
+
+        // 前两次被占用，第三次成功
+        let delays = RefCell::new(Vec::new());
+        let mut attempts = 0;
+        let result = copy_with_retry(
+            || {
+                attempts += 1;
+                if attempts < 3 {
+                    Err(busy_error())
+                } else {
+                    Ok(42)
+                }
+            },
+            |delay| delays.borrow_mut().push(delay),
+        );
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(
+            delays.into_inner(),
+            vec![Duration::from_millis(200), Duration::from_millis(400)]
+        );
+
+        // 持续被占用：重试次数用尽后返回占用错误，由调用方改走临时文件
+        let mut attempts = 0;
+        let result = copy_with_retry(
+            || {
+                attempts += 1;
+                Err(busy_error())
+            },
+            |_| {},
+        );
+        assert!(is_file_busy(&result.unwrap_err()));
+        assert_eq!(attempts, COPY_RETRY_ATTEMPTS + 1);
+    }
+
+    #[test]
+    fn test_replace_via_temp() {
+        let dir = std::env::temp_dir().join(format!("stelliberty-replace-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("source");
+        let target = dir.join("stelliberty-service");
+        std::fs::write(&source, b"new binary").unwrap();
+        std::fs::write(&target, b"old").unwrap();
+
+        replace_via_temp(&source, &target).unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"new binary");
+        assert!(!dir.join("stelliberty-service.new").exists());
+
+        // 源文件不存在时不留下临时文件
+        assert!(replace_via_temp(&dir.join("missing"), &target).is_err());
+        assert!(!dir.join("stelliberty-service.new").exists());
+        assert_eq!(std::fs::read(&target).unwrap(), b"new binary");
+
+        // Windows 上旧程序先移到 <文件名>.old
+        assert_eq!(
+            replaced_binary_path(&target),
+            dir.join("stelliberty-service.old")
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_restart_sequence() {
+        use std::cell::RefCell;
+        use std::time::Duration;
+
+        let calls = RefCell::new(Vec::new());
+        let mut polls = 0;
+        let result = restart_with(
+            || {
+                calls.borrow_mut().push("stop");
+                Ok(())
+            },
+            || {
+                // 第三次检查时服务才退出
+                polls += 1;
+                calls.borrow_mut().push("poll");
+                polls >= 3
+            },
+            || {
+                calls.borrow_mut().push("start");
+                Ok(())
+            },
+            Duration::from_secs(5),
+            Duration::from_millis(1),
+        );
+        assert!(result.is_ok());
+        assert_eq!(
+            calls.into_inner(),
+            vec!["stop", "poll", "poll", "poll", "start"]
+        );
+
+        // 停止失败时不再启动
+        let is_started = RefCell::new(false);
+        let result = restart_with(
+            || bail!("停止服务失败"),
+            || true,
+            || {
+                *is_started.borrow_mut() = true;
+                Ok(())
+            },
+            Duration::from_secs(5),
+            Duration::from_millis(1),
+        );
+        assert!(result.is_err());
+        assert!(!is_started.into_inner());
+
+        // 超时未退出时不再启动
+        let is_started = RefCell::new(false);
+        let result = restart_with(
+            || Ok(()),
+            || false,
+            || {
+                *is_started.borrow_mut() = true;
+                Ok(())
+            },
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+        );
+        assert!(result.unwrap_err().to_string().contains("未在"));
+        assert!(!is_started.into_inner());
+    }
+
+    #[test]
+    fn test_parse_umask() {
+        assert_eq!(parse_umask("0077").ok(), Some(0o077));
+        assert_eq!(parse_umask("027").ok(), Some(0o027));
+        assert_eq!(parse_umask("0o022").ok(), Some(0o022));
+        assert!(parse_umask("").is_err());
+        assert!(parse_umask("0088").is_err());
+        assert!(parse_umask("7777").is_err());
+        assert!(parse_umask("-077").is_err());
+    }
+
+    #[test]
+    fn test_plan_repair() {
+        // 已注册但程序缺失：修复
+        assert_eq!(plan_repair(true, false), RepairAction::Repair);
+        // 已注册且程序完好：无需复制
+        assert_eq!(plan_repair(true, true), RepairAction::Healthy);
+        // 完全缺失（未注册）：提示安装
+        assert_eq!(plan_repair(false, false), RepairAction::Install);
+        assert_eq!(plan_repair(false, true), RepairAction::Install);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_service_unit_contains_umask() {
+        let unit = get_service_unit("/opt/stelliberty-service", 0o027, CapabilityProfile::Full);
+        assert!(unit.contains("\nUMask=0027\n"));
+
+        let unit = get_service_unit(
+            "/opt/stelliberty-service",
+            DEFAULT_UMASK,
+            CapabilityProfile::Full,
+        );
+        assert!(unit.contains("\nUMask=0077\n"));
+    }
+
+    #[test]
+    fn test_umask_read_from_installed_config() {
+        // 重新安装未指定 --umask 时从已安装的 unit / plist 中读回
+        let unit = "[Service]\nType=simple\nUMask=0027\nExecStart=/opt/stelliberty-service\n";
+        assert_eq!(umask_from_unit(unit), Some(0o027));
+        assert_eq!(umask_from_unit("[Service]\nType=simple\n"), None);
+        assert_eq!(umask_from_unit("[Service]\nUMask=9999\n"), None);
+
+        let plist = "<dict>\n    <key>KeepAlive</key>\n    <true/>\n    <key>Umask</key>\n    <integer>23</integer>\n</dict>";
+        assert_eq!(umask_from_plist(plist), Some(0o027));
+        assert_eq!(umask_from_plist("<dict></dict>"), None);
+        assert_eq!(
+            umask_from_plist("<key>Umask</key>\n<integer>4095</integer>"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_capability_profile_read_from_installed_unit() {
+        let unit =
+            |names: &str| format!("[Service]\nCapabilityBoundingSet={}\nUMask=0077\n", names);
+        assert_eq!(
+            capability_profile_from_unit(&unit(
+                "CAP_NET_ADMIN CAP_NET_RAW CAP_NET_BIND_SERVICE CAP_DAC_READ_SEARCH CAP_DAC_OVERRIDE"
+            )),
+            Some(CapabilityProfile::Minimal)
+        );
+        assert_eq!(
+            capability_profile_from_unit(&unit(
+                "CAP_NET_ADMIN CAP_NET_RAW CAP_NET_BIND_SERVICE CAP_SYS_TIME CAP_SYS_PTRACE CAP_DAC_READ_SEARCH CAP_DAC_OVERRIDE"
+            )),
+            Some(CapabilityProfile::Full)
+        );
+        // 手动修改过的权限集无法识别
+        assert_eq!(capability_profile_from_unit(&unit("CAP_NET_ADMIN")), None);
+        assert_eq!(capability_profile_from_unit("[Service]\n"), None);
+    }
+
+    #[test]
+    fn test_parse_capability_profile() {
+        assert_eq!(
+            parse_capability_profile("full").ok(),
+            Some(CapabilityProfile::Full)
+        );
+        assert_eq!(
+            parse_capability_profile(" Minimal ").ok(),
+            Some(CapabilityProfile::Minimal)
+        );
+        assert!(parse_capability_profile("").is_err());
+        assert!(parse_capability_profile("tun").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_service_unit_capability_profiles() {
+        let capability_lines = |profile| {
+            get_service_unit("/opt/stelliberty-service", DEFAULT_UMASK, profile)
+                .lines()
+                .filter(|line| line.contains("Capabilit") && !line.starts_with('#'))
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+
+        let full = "CAP_NET_ADMIN CAP_NET_RAW CAP_NET_BIND_SERVICE CAP_SYS_TIME CAP_SYS_PTRACE CAP_DAC_READ_SEARCH CAP_DAC_OVERRIDE";
+        assert_eq!(
+            capability_lines(CapabilityProfile::Full),
+            vec![
+                format!("CapabilityBoundingSet={}", full),
+                format!("AmbientCapabilities={}", full),
+            ]
+        );
+
+        let minimal =
+            "CAP_NET_ADMIN CAP_NET_RAW CAP_NET_BIND_SERVICE CAP_DAC_READ_SEARCH CAP_DAC_OVERRIDE";
+        assert_eq!(
+            capability_lines(CapabilityProfile::Minimal),
+            vec![
+                format!("CapabilityBoundingSet={}", minimal),
+                format!("AmbientCapabilities={}", minimal),
+            ]
+        );
+        let unit = get_service_unit(
+            "/opt/stelliberty-service",
+            DEFAULT_UMASK,
+            CapabilityProfile::Minimal,
+        );
+        assert!(!unit.contains("CAP_SYS_PTRACE"));
+        assert!(unit.contains("# CAP_NET_ADMIN: 网络管理（TUN 设备、路由表）\n"));
+    }
+
+    #[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+    fn write_temp_binary(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "stelliberty-installer-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join("stelliberty-service");
+        std::fs::write(&binary, b"service binary").unwrap();
+        binary
+    }
+
+    #[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_verify_source_binary_hash_match() {
+        let binary = write_temp_binary("match");
+
+        // 未提供校验文件时跳过
+        assert!(verify_source_binary(&binary).is_ok());
+
+        let hash = sha256_file(&binary).unwrap();
+        std::fs::write(
+            binary.with_file_name("stelliberty-service.sha256"),
+            format!("{}  stelliberty-service\n", hash.to_uppercase()),
+        )
+        .unwrap();
+        assert!(verify_source_binary(&binary).is_ok());
+
+        let _ = std::fs::remove_dir_all(binary.parent().unwrap());
+    }
+
+    #[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_verify_source_binary_hash_mismatch() {
+        let binary = write_temp_binary("mismatch");
+        std::fs::write(
+            binary.with_file_name("stelliberty-service.sha256"),
+            "0".repeat(64),
+        )
+        .unwrap();
+
+        let err = verify_source_binary(&binary).unwrap_err();
+        assert!(err.to_string().contains("校验失败"));
+
+        let _ = std::fs::remove_dir_all(binary.parent().unwrap());
+    }
+}
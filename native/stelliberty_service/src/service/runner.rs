@@ -3,9 +3,15 @@
 #[cfg(any(windows, target_os = "linux"))]
 use crate::clash::ClashManager;
 #[cfg(any(windows, target_os = "linux"))]
+use crate::clash::exit_monitor::spawn_exit_watcher;
+#[cfg(any(windows, target_os = "linux"))]
+use crate::clash::kill_switch;
+#[cfg(any(windows, target_os = "linux"))]
 use crate::ipc::IpcServer;
 #[cfg(any(windows, target_os = "linux"))]
 use crate::service::handler;
+#[cfg(any(windows, target_os = "linux"))]
+use crate::service::heartbeat::{self, CHECK_INTERVAL, HEARTBEAT_TIMEOUT, HeartbeatStatus};
 #[cfg(target_os = "linux")]
 use anyhow::Result;
 #[cfg(any(windows, target_os = "linux"))]
@@ -90,11 +96,18 @@ fn run_service_windows() -> Result<(), Box<dyn std::error::Error>> {
         .enable_all()
         .build()?;
 
+    // 上次运行异常退出时可能残留防泄漏规则
+    kill_switch::release_stale();
+    crate::service::installer::remove_replaced_binary();
+
     runtime.block_on(async move {
         let clash_manager = Arc::new(RwLock::new(ClashManager::new()));
         let last_heartbeat = Arc::new(RwLock::new(Instant::now()));
         let handler = handler::create_handler(clash_manager.clone(), last_heartbeat.clone());
 
+        // 启动核心退出监控
+        let exit_watcher_handle = spawn_exit_watcher(clash_manager.clone());
+
         // 创建就绪信号通道
         let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
         let mut ipc_server = IpcServer::new_with_ready_signal(handler, ready_tx);
@@ -132,9 +145,6 @@ fn run_service_windows() -> Result<(), Box<dyn std::error::Error>> {
         let heartbeat_clash_manager = clash_manager.clone();
         let heartbeat_last_heartbeat = last_heartbeat.clone();
         let heartbeat_handle = tokio::spawn(async move {
-            const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(70);
-            const CHECK_INTERVAL: Duration = Duration::from_secs(30);
-
             log::info!("启动心跳监控器，超时时间: {}s", HEARTBEAT_TIMEOUT.as_secs());
 
             // 记录上一次检查的时间，用于检测系统休眠
@@ -159,25 +169,32 @@ fn run_service_windows() -> Result<(), Box<dyn std::error::Error>> {
                     continue;
                 }
 
-                let elapsed = heartbeat_last_heartbeat.read().await.elapsed();
-                if elapsed > HEARTBEAT_TIMEOUT {
-                    log::warn!(
-                        "超过 {} 秒未收到主程序心跳，停止 Clash 核心（服务继续运行）",
-                        HEARTBEAT_TIMEOUT.as_secs()
-                    );
-
-                    // 只停止 Clash 核心，不关闭服务
-                    let mut manager = heartbeat_clash_manager.write().await;
-                    if let Err(e) = manager.stop() {
-                        log::error!("心跳超时停止 Clash 失败: {}", e);
-                    } else {
-                        log::info!("心跳超时，Clash 核心已停止，等待主程序重连");
+                let last = *heartbeat_last_heartbeat.read().await;
+                match heartbeat::evaluate(last, heartbeat::suspended_until(), now) {
+                    HeartbeatStatus::TimedOut(_) => {
+                        log::warn!(
+                            "超过 {} 秒未收到主程序心跳，停止 Clash 核心（服务继续运行）",
+                            HEARTBEAT_TIMEOUT.as_secs()
+                        );
+
+                        // 只停止 Clash 核心，不关闭服务
+                        let mut manager = heartbeat_clash_manager.write().await;
+                        if let Err(e) = manager.stop() {
+                            log::error!("心跳超时停止 Clash 失败: {}", e);
+                        } else {
+                            log::info!("心跳超时，Clash 核心已停止，等待主程序重连");
+                            kill_switch::on_core_stopped();
+                        }
+
+                        // 重置心跳时间，避免反复触发
+                        *heartbeat_last_heartbeat.write().await = Instant::now();
+                    }
+                    HeartbeatStatus::Suspended(remaining) => {
+                        log::debug!("自动关闭已暂停，剩余: {}s", remaining.as_secs());
+                    }
+                    HeartbeatStatus::Alive(elapsed) => {
+                        log::debug!("心跳正常，距离上次心跳: {}s", elapsed.as_secs());
                     }
-
-                    // 重置心跳时间，避免反复触发
-                    *heartbeat_last_heartbeat.write().await = Instant::now();
-                } else {
-                    log::debug!("心跳正常，距离上次心跳: {}s", elapsed.as_secs());
                 }
             }
         });
@@ -221,6 +238,8 @@ fn run_service_windows() -> Result<(), Box<dyn std::error::Error>> {
 
         heartbeat_handle.abort();
         ipc_handle.abort();
+        exit_watcher_handle.abort();
+        kill_switch::release_on_exit();
         log::info!("服务已停止");
     });
 
@@ -281,10 +300,16 @@ pub async fn run_service() -> Result<()> {
         let _ = shutdown_tx_clone.send(()).await;
     });
 
+    // 上次运行异常退出时可能残留防泄漏规则
+    kill_switch::release_stale();
+
     let clash_manager = Arc::new(RwLock::new(ClashManager::new()));
     let last_heartbeat = Arc::new(RwLock::new(Instant::now()));
     let handler = handler::create_handler(clash_manager.clone(), last_heartbeat.clone());
 
+    // 启动核心退出监控
+    let exit_watcher_handle = spawn_exit_watcher(clash_manager.clone());
+
     // 创建就绪信号通道
     let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
     let mut ipc_server = IpcServer::new_with_ready_signal(handler, ready_tx);
@@ -310,9 +335,6 @@ pub async fn run_service() -> Result<()> {
     let heartbeat_clash_manager = clash_manager.clone();
     let heartbeat_last_heartbeat = last_heartbeat.clone();
     let heartbeat_handle = tokio::spawn(async move {
-        const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(70);
-        const CHECK_INTERVAL: Duration = Duration::from_secs(30);
-
         log::info!("启动心跳监控器，超时时间: {}s", HEARTBEAT_TIMEOUT.as_secs());
 
         // 记录上一次检查的时间，用于检测系统休眠
@@ -337,25 +359,32 @@ pub async fn run_service() -> Result<()> {
                 continue;
             }
 
-            let elapsed = heartbeat_last_heartbeat.read().await.elapsed();
-            if elapsed > HEARTBEAT_TIMEOUT {
-                log::warn!(
-                    "超过 {} 秒未收到主程序心跳，停止 Clash 核心（服务继续运行）",
-                    HEARTBEAT_TIMEOUT.as_secs()
-                );
+            let last = *heartbeat_last_heartbeat.read().await;
+            match heartbeat::evaluate(last, heartbeat::suspended_until(), now) {
+                HeartbeatStatus::TimedOut(_) => {
+                    log::warn!(
+                        "超过 {} 秒未收到主程序心跳，停止 Clash 核心（服务继续运行）",
+                        HEARTBEAT_TIMEOUT.as_secs()
+                    );
 
-                // 只停止 Clash 核心，不关闭服务
-                let mut manager = heartbeat_clash_manager.write().await;
-                if let Err(e) = manager.stop() {
-                    log::error!("心跳超时停止 Clash 失败: {}", e);
-                } else {
-                    log::info!("心跳超时，Clash 核心已停止，等待主程序重连");
-                }
+                    // 只停止 Clash 核心，不关闭服务
+                    let mut manager = heartbeat_clash_manager.write().await;
+                    if let Err(e) = manager.stop() {
+                        log::error!("心跳超时停止 Clash 失败: {}", e);
+                    } else {
+                        log::info!("心跳超时，Clash 核心已停止，等待主程序重连");
+                        kill_switch::on_core_stopped();
+                    }
 
-                // 重置心跳时间，避免反复触发
-                *heartbeat_last_heartbeat.write().await = Instant::now();
-            } else {
-                log::debug!("心跳正常，距离上次心跳: {}s", elapsed.as_secs());
+                    // 重置心跳时间，避免反复触发
+                    *heartbeat_last_heartbeat.write().await = Instant::now();
+                }
+                HeartbeatStatus::Suspended(remaining) => {
+                    log::debug!("自动关闭已暂停，剩余: {}s", remaining.as_secs());
+                }
+                HeartbeatStatus::Alive(elapsed) => {
+                    log::debug!("心跳正常，距离上次心跳: {}s", elapsed.as_secs());
+                }
             }
         }
     });
@@ -387,6 +416,8 @@ pub async fn run_service() -> Result<()> {
 
     heartbeat_handle.abort();
     ipc_handle.abort();
+    exit_watcher_handle.abort();
+    kill_switch::release_on_exit();
     log::info!("服务已停止");
     Ok(())
 }
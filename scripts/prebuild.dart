@@ -1,6 +1,7 @@
 import 'dart:async';
 import 'dart:convert';
 import 'dart:io';
+import 'package:crypto/crypto.dart';
 import 'package:http/http.dart' as http;
 import 'package:path/path.dart' as p;
 import 'package:args/args.dart';
@@ -257,6 +258,12 @@ Future<void> buildStelliibertyService({required String projectRoot}) async {
     2,
   );
   log('✅ 复制到 assets/service: $exeName ($sizeInMB MB)');
+
+  // 生成 SHA-256 校验文件，服务安装时据此拒绝被替换的程序
+  final digest = sha256.convert(await targetExe.readAsBytes());
+  final checksumFile = File('${targetExe.path}.sha256');
+  await checksumFile.writeAsString('$digest  $exeName\n');
+  log('✅ 生成校验文件: $exeName.sha256');
 }
 
 // 下载并设置 Clash 核心（带重试机制）